@@ -1,7 +1,7 @@
 use std::path::Path;
 use terminus::devices::clint::Clint;
 use terminus::global::XLen;
-use terminus::processor::ProcessorCfg;
+use terminus::processor::{ProcessorCfg, SatpMode};
 use terminus::system::System;
 use terminus_spaceport::devices::term_exit;
 use terminus_spaceport::memory::region::GHEAP;
@@ -13,8 +13,17 @@ fn main() {
         ProcessorCfg {
             xlen: XLen::X64,
             enable_dirty: true,
-            extensions: vec!['m', 'f', 'd', 's', 'u', 'c', 'a'].into_boxed_slice(),
+            isa: "rv64imafdcsu_zicsr_zifencei_svinval_svnapot_svpbmt"
+                .parse()
+                .unwrap(),
             freq: 1000000000,
+            triggers: 4,
+            satp_modes: vec![SatpMode::Sv39, SatpMode::Sv48].into_boxed_slice(),
+            vlen: 128,
+            elen: 64,
+            cache_block_size: 64,
+            entropy_seed: 0,
+            hpm_counters: 8,
         };
         num_cores
     ];
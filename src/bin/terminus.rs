@@ -8,21 +8,24 @@ use std::rc::Rc;
 use std::str::FromStr;
 #[cfg(feature = "sdl")]
 use std::time::Duration;
+use terminus::devices::aplic::{AplicDomain, APLIC_M, APLIC_S, APLIC_SIZE};
 use terminus::devices::clint::Clint;
 #[cfg(feature = "sdl")]
 use terminus::devices::display::{Fb, SimpleFb};
+use terminus::devices::imsic::{ImsicFiles, IMSIC_M, IMSIC_S};
 use terminus::devices::plic::Plic;
 use terminus::devices::virtio_blk::{VirtIOBlk, VirtIOBlkConfig};
-use terminus::devices::virtio_console::{VirtIOConsole, VirtIOConsoleDevice};
 #[cfg(feature = "sdl")]
 use terminus::devices::virtio_input::{VirtIOKb, VirtIOKbDevice};
 #[cfg(feature = "sdl")]
 use terminus::devices::virtio_input::{VirtIOMouse, VirtIOMouseDevice};
-use terminus::devices::virtio_net::{VirtIONet, VirtIONetDevice};
+use terminus::devices::virtio_mmio::{TapNet, TermConsole, VirtIOMmioDevice};
 use terminus::global::XLen;
-use terminus::processor::ProcessorCfg;
+use terminus::processor::isa::{Isa, IsaExt};
+use terminus::processor::{ProcessorCfg, SatpMode};
 #[cfg(feature = "sdl")]
 use terminus::system::fdt::FdtProp;
+use terminus::system::gdb::GdbServer;
 use terminus::system::System;
 use terminus_spaceport::devices::term_exit;
 #[cfg(feature = "sdl")]
@@ -61,29 +64,82 @@ fn main() {
                 }
             })
             .default_value("0x80000000"))
-        .arg(Arg::with_name("xlen")
-            .short("l")
+        .arg(Arg::with_name("isa")
+            .short("e")
+            .long("isa")
+            .value_name("ISA")
+            .takes_value(true)
+            .validator(|raw| Isa::from_str(&raw).map(|_| ()))
+            .help("set isa string, xlen and extensions, e.g. rv64imafdcsu_zicsr_zifencei_zba_zbb, 's' and 'u' enable supervisor and user mode, 'h' implies 's' and 's' implies 'u'")
+            .default_value("rv64imafdcsu_zicsr_zifencei_svinval_svnapot_svpbmt")
+        )
+        .arg(Arg::with_name("satp_modes")
+            .long("satp_modes")
+            .value_name("SATP_MODES")
             .takes_value(true)
-            .help("set xlen, 64 or 32")
+            .require_delimiter(true)
             .validator(|raw| {
-                match raw.as_str() {
-                    "32" | "64" => Ok(()),
-                    _ => Err(String::from("-l 64 or 32"))
+                match raw.split_whitespace().collect::<String>().as_str() {
+                    "sv32" | "sv39" | "sv48" | "sv57" => Ok(()),
+                    _ => return Err(String::from("only support 'sv32', 'sv39', 'sv48', 'sv57'"))
                 }
             })
-            .default_value("64")
+            .help("set supported satp modes, default is sv32 for xlen 32 and sv39, sv48 for xlen 64")
         )
-        .arg(Arg::with_name("extensions")
-            .short("e")
+        .arg(Arg::with_name("vlen")
+            .long("vlen")
+            .value_name("VLEN")
             .takes_value(true)
-            .require_delimiter(true)
+            .help("set vector register length in bits for 'v' extension, must be decimal int")
+            .default_value("128")
+        )
+        .arg(Arg::with_name("elen")
+            .long("elen")
+            .value_name("ELEN")
+            .takes_value(true)
+            .help("set max vector element length in bits for 'v' extension, 32 or 64")
             .validator(|raw| {
-                match raw.split_whitespace().collect::<String>().as_str() {
-                    "a" | "c" | "d" | "f" | "i" | "m" | "s" | "u" => Ok(()),
-                    _ => return Err(String::from("only support 'a', 'c', 'd', 'f', 'i', 'm', 's', 'u'"))
+                match raw.as_str() {
+                    "32" | "64" => Ok(()),
+                    _ => Err(String::from("--elen 32 or 64"))
                 }
             })
-            .default_value("a, c, d, f, i, m, s, u")
+            .default_value("64")
+        )
+        .arg(Arg::with_name("cache_block_size")
+            .long("cache_block_size")
+            .value_name("CACHE_BLOCK_SIZE")
+            .takes_value(true)
+            .help("set cache block size in bytes for 'zicbom' and 'zicboz' extensions, must be decimal int")
+            .default_value("64")
+        )
+        .arg(Arg::with_name("entropy_seed")
+            .long("entropy_seed")
+            .value_name("ENTROPY_SEED")
+            .takes_value(true)
+            .help("set initial state of the entropy source of 'zkr' seed csr, must be decimal int")
+            .default_value("0")
+        )
+        .arg(Arg::with_name("hpm_counters")
+            .long("hpm_counters")
+            .value_name("HPM_COUNTERS")
+            .takes_value(true)
+            .help("set number of programmable mhpmcounters for 'zihpm' extension, must be decimal int in [0, 29]")
+            .default_value("8")
+        )
+        .arg(
+            Arg::with_name("aia")
+                .long("aia")
+                .value_name("AIA")
+                .takes_value(true)
+                .help("config external interrupt controllers:[none|aplic|aplic-imsic], 'none' is plic, 'aplic-imsic' needs 'ssaia' extension")
+                .validator(|mode| {
+                    match mode.as_str() {
+                        "none" | "aplic" | "aplic-imsic" => Ok(()),
+                        _ => Err("config external interrupt controllers:[none|aplic|aplic-imsic]".to_string())
+                    }
+                })
+                .default_value("none")
         )
         .arg(
             Arg::with_name("elf")
@@ -217,6 +273,55 @@ fn main() {
                 .long("trace_all")
                 .help("trace states of all processors every instruction, results is in terminus.trace")
         )
+        .arg(
+            Arg::with_name("gdb")
+                .long("gdb")
+                .value_name("GDB_ADDR")
+                .takes_value(true)
+                .help("wait for gdb connection before running, [host:port|unix:path], e.g. 127.0.0.1:1234")
+        )
+        .arg(
+            Arg::with_name("snapshot_file")
+                .long("snapshot_file")
+                .value_name("SNAPSHOT_FILE")
+                .takes_value(true)
+                .help("snapshot file path")
+                .default_value("terminus.snapshot")
+        )
+        .arg(
+            Arg::with_name("save_snapshot_at")
+                .long("save_snapshot_at")
+                .value_name("INSNS")
+                .takes_value(true)
+                .help("save snapshot to snapshot file when hart0 has executed INSNS instructions, must be decimal int")
+        )
+        .arg(
+            Arg::with_name("restore")
+                .long("restore")
+                .help("restore from snapshot file after reset, command line must match the one used to save it")
+        )
+        .arg(
+            Arg::with_name("record")
+                .long("record")
+                .value_name("RECORD_FILE")
+                .takes_value(true)
+                .conflicts_with("replay")
+                .help("record console and network input and timer ticks to RECORD_FILE")
+        )
+        .arg(
+            Arg::with_name("replay")
+                .long("replay")
+                .value_name("RECORD_FILE")
+                .takes_value(true)
+                .help("replay console and network input and timer ticks from RECORD_FILE, command line must match the one used to record it")
+        )
+        .arg(
+            Arg::with_name("checkpoint_interval")
+                .long("checkpoint_interval")
+                .value_name("INSNS")
+                .takes_value(true)
+                .help("take a checkpoint every INSNS instructions for gdb reverse execution, must be decimal int")
+        )
         .get_matches();
 
     let core_num = usize::from_str(matches.value_of("core_num").unwrap_or_default())
@@ -229,23 +334,40 @@ fn main() {
         16,
     )
     .expect("-m expect a hex int");
-    let xlen = match matches.value_of("xlen").unwrap_or_default() {
-        "32" => XLen::X32,
-        "64" => XLen::X64,
-        _ => unreachable!(),
-    };
-    let extensions = matches
-        .values_of("extensions")
-        .unwrap_or_default()
-        .map(|s| -> char {
-            s.split_whitespace()
-                .collect::<String>()
-                .chars()
-                .last()
-                .unwrap()
-        })
-        .collect::<Vec<char>>()
-        .into_boxed_slice();
+    let isa = Isa::from_str(matches.value_of("isa").unwrap_or_default()).unwrap();
+    let xlen = isa.xlen();
+    let satp_modes = if let Some(modes) = matches.values_of("satp_modes") {
+        modes
+            .map(|s| match s.split_whitespace().collect::<String>().as_str() {
+                "sv32" => SatpMode::Sv32,
+                "sv39" => SatpMode::Sv39,
+                "sv48" => SatpMode::Sv48,
+                "sv57" => SatpMode::Sv57,
+                _ => unreachable!(),
+            })
+            .collect::<Vec<SatpMode>>()
+    } else {
+        match xlen {
+            XLen::X32 => vec![SatpMode::Sv32],
+            XLen::X64 => vec![SatpMode::Sv39, SatpMode::Sv48],
+        }
+    }
+    .into_boxed_slice();
+    let vlen = usize::from_str(matches.value_of("vlen").unwrap_or_default())
+        .expect("--vlen expect a decimal int");
+    let elen = usize::from_str(matches.value_of("elen").unwrap_or_default())
+        .expect("--elen expect a decimal int");
+    let cache_block_size =
+        usize::from_str(matches.value_of("cache_block_size").unwrap_or_default())
+            .expect("--cache_block_size expect a decimal int");
+    let entropy_seed = u64::from_str(matches.value_of("entropy_seed").unwrap_or_default())
+        .expect("--entropy_seed expect a decimal int");
+    let hpm_counters = usize::from_str(matches.value_of("hpm_counters").unwrap_or_default())
+        .expect("--hpm_counters expect a decimal int");
+    let aia = matches.value_of("aia").unwrap_or_default();
+    if aia == "aplic-imsic" && !isa.has_ext(IsaExt::Ssaia) {
+        panic!("\"--aia aplic-imsic\" needs 'ssaia' extension!")
+    }
     let elf = Path::new(matches.value_of("elf").unwrap())
         .to_str()
         .unwrap();
@@ -278,6 +400,35 @@ fn main() {
         s => s,
     };
     let trace_all = matches.is_present("trace_all");
+    let gdb = matches.value_of("gdb");
+    let snapshot_file = matches.value_of("snapshot_file").unwrap_or_default();
+    let record = matches.value_of("record");
+    let replay = matches.value_of("replay");
+    let checkpoint_interval = matches.value_of("checkpoint_interval").map(|s| {
+        u64::from_str(s).expect("checkpoint_interval expect a decimal int")
+    });
+    //virtio block state is not saved, so it can not be restored by checkpoints
+    if image.is_some() && checkpoint_interval.is_some() {
+        clap::Error::with_description(
+            "--checkpoint_interval can not be used with --image",
+            clap::ErrorKind::ArgumentConflict,
+        )
+        .exit()
+    }
+    //virtio input devices read sdl events inside device models, which can not be recorded
+    #[cfg(feature = "sdl")]
+    {
+        if display_en && (record.is_some() || replay.is_some() || checkpoint_interval.is_some()) {
+            clap::Error::with_description(
+                "--record, --replay and --checkpoint_interval can not be used with --display",
+                clap::ErrorKind::ArgumentConflict,
+            )
+            .exit()
+        }
+    }
+    let mut save_snapshot_at = matches.value_of("save_snapshot_at").map(|s| {
+        u64::from_str(s).expect("save_snapshot_at expect a decimal int")
+    });
     let mut trace_file = if matches.is_present("trace") || trace_all {
         Some(
             OpenOptions::new()
@@ -294,13 +445,23 @@ fn main() {
         ProcessorCfg {
             xlen,
             enable_dirty: true,
-            extensions,
+            isa,
             freq: CORE_FREQ,
+            triggers: 4,
+            satp_modes,
+            vlen,
+            elen,
+            cache_block_size,
+            entropy_seed,
+            hpm_counters,
         };
         core_num
     ];
     let mut sys = System::new("sys", elf, TIMER_FREQ, 32);
     sys.register_htif(!virtio_input_en);
+    if aia != "none" {
+        sys.enable_aia(aia == "aplic-imsic");
+    }
     for cfg in configs {
         sys.new_processor(cfg)
     }
@@ -311,8 +472,25 @@ fn main() {
         .unwrap();
     sys.register_device("clint", 0x02000000, 0x000c0000, Clint::new(sys.timer()))
         .unwrap();
-    sys.register_device("plic", 0x0c000000, 0x4000000, Plic::new(sys.intc()))
-        .unwrap();
+    if let Some(aplic) = sys.aplic().cloned() {
+        let aplic_m = AplicDomain::new(&aplic, APLIC_M);
+        let aplic_s = AplicDomain::new(&aplic, APLIC_S);
+        sys.register_device("aplic_m", 0x0c000000, APLIC_SIZE, aplic_m)
+            .unwrap();
+        sys.register_device("aplic_s", 0x0d000000, APLIC_SIZE, aplic_s)
+            .unwrap();
+    } else {
+        sys.register_device("plic", 0x0c000000, 0x4000000, Plic::new(sys.intc()))
+            .unwrap();
+    }
+    if let Some(imsic) = sys.imsic().cloned() {
+        let imsic_m = ImsicFiles::new(&imsic, IMSIC_M);
+        let imsic_s = ImsicFiles::new(&imsic, IMSIC_S);
+        sys.register_device("imsic_m", 0x24000000, imsic_m.size(), imsic_m)
+            .unwrap();
+        sys.register_device("imsic_s", 0x28000000, imsic_s.size(), imsic_s)
+            .unwrap();
+    }
 
     //virtios
     let virtio_mem = Region::remap(0x80000000, &main_memory);
@@ -365,12 +543,12 @@ fn main() {
         }
     };
 
-    let irq_num = sys.intc().num_src();
-    let virtio_console_device = Rc::new(VirtIOConsoleDevice::new(
+    let virtio_console_device = Rc::new(VirtIOMmioDevice::new(
         &virtio_mem,
-        sys.intc().alloc_src(irq_num),
+        sys.intc(),
+        Box::new(TermConsole),
     ));
-    sys.register_virtio("virtio_console", VirtIOConsole::new(&virtio_console_device))
+    sys.register_virtio_mmio("virtio_console", &virtio_console_device)
         .unwrap();
 
     if let Some(image_file) = image {
@@ -385,15 +563,12 @@ fn main() {
         sys.register_virtio("virtio_blk", virtio_blk).unwrap();
     }
     let virtio_net_device = if let Some(tap) = net {
-        let irq_num = sys.intc().num_src();
-        let virtio_net_device = Rc::new(VirtIONetDevice::new(
+        let virtio_net_device = Rc::new(VirtIOMmioDevice::new(
             &virtio_mem,
-            sys.intc().alloc_src(irq_num),
-            tap,
-            0x0100_00000002,
-            1,
+            sys.intc(),
+            Box::new(TapNet::new(tap, 0x0100_00000002).expect("tap open fail!")),
         ));
-        sys.register_virtio("virtio_net", VirtIONet::new(&virtio_net_device))
+        sys.register_virtio_mmio("virtio_net", &virtio_net_device)
             .unwrap();
         Some(virtio_net_device)
     } else {
@@ -404,6 +579,20 @@ fn main() {
         .unwrap();
     sys.load_elf().unwrap();
     sys.reset(vec![-1i64 as u64; core_num]).unwrap();
+    if matches.is_present("restore") {
+        sys.restore_snapshot(snapshot_file)
+            .expect("restore snapshot fail!");
+    }
+    if let Some(file) = record {
+        sys.replay().record(file).expect("open record file fail!");
+    }
+    if let Some(file) = replay {
+        sys.replay().replay(file).expect("load record file fail!");
+    }
+    if let Some(interval) = checkpoint_interval {
+        sys.enable_checkpoints(interval)
+            .expect("enable checkpoints fail!");
+    }
     #[cfg(feature = "sdl")]
     let mut real_timer = if display_en {
         Some(std::time::Instant::now())
@@ -417,25 +606,14 @@ fn main() {
         None
     };
     let mut step_cnt: usize = 0;
-    loop {
-        if let Ok(msg) = EXIT_CTRL.poll() {
-            eprintln!("{}", msg);
-            break;
-        }
-        for p in sys.processors() {
-            if let Some(ref mut f) = trace_file {
-                p.step_with_debug(step, f, trace_all).unwrap()
-            } else {
-                p.step(step);
-            }
-        }
-        step_cnt += step;
+    let mut tick = |sys: &mut System, n: usize| {
+        step_cnt += n;
         if step_cnt >= CORE_STEP_TH {
             if virtio_input_en {
-                virtio_console_device.console_read();
+                virtio_console_device.poll();
             }
             if let Some(ref net_d) = virtio_net_device {
-                net_d.net_read();
+                net_d.poll();
             }
             #[cfg(feature = "sdl")]
             {
@@ -453,14 +631,50 @@ fn main() {
                     }
                 }
             }
-            sys.timer().tick(TIMER_STEP);
+            sys.tick_timer(TIMER_STEP);
             step_cnt -= CORE_STEP_TH
         }
+        sys.replay_sync();
+    };
+    let run = if let Some(addr) = gdb {
+        let mut server = GdbServer::accept(addr).expect("gdb server start fail!");
+        server
+            .serve(&mut sys, &mut tick)
+            .expect("gdb connection error!")
+    } else {
+        true
+    };
+    if run {
+        loop {
+            if let Ok(msg) = EXIT_CTRL.poll() {
+                eprintln!("{}", msg);
+                break;
+            }
+            for i in 0..sys.processors().len() {
+                if let Some(ref mut f) = trace_file {
+                    sys.step_hart(i, |p| p.step_with_debug(step, f, trace_all))
+                        .unwrap()
+                } else {
+                    sys.step_hart(i, |p| p.step(step));
+                }
+            }
+            tick(&mut sys, step);
+            if let Some(insns) = save_snapshot_at {
+                if *sys.processor(0).unwrap().state().insns_cnt().borrow() >= insns {
+                    match sys.save_snapshot(snapshot_file) {
+                        Ok(_) => eprintln!("snapshot saved to {}", snapshot_file),
+                        Err(e) => eprintln!("save snapshot fail! {}", e),
+                    }
+                    save_snapshot_at = None
+                }
+            }
+        }
     }
     if let Some(ref mut f) = trace_file {
         for p in sys.processors() {
             f.write_all(p.state().to_string().as_bytes()).unwrap()
         }
     }
+    sys.replay().flush().expect("flush record file fail!");
     term_exit();
 }
@@ -0,0 +1,837 @@
+use crate::devices::bus::{Bus, TerminusBus};
+use crate::devices::plic::Intc;
+use crate::snapshot::{SnapshotReader, SnapshotWriter};
+use std::cell::{Ref, RefCell, RefMut};
+use std::cmp::max;
+use std::io;
+use std::io::{Read, Write};
+use std::rc::{Rc, Weak};
+use terminus_spaceport::irq::{IrqVec, IrqVecSender};
+use terminus_spaceport::memory::prelude::*;
+
+//M level root domain and its only child, the S level domain
+pub const APLIC_M: usize = 0;
+pub const APLIC_S: usize = 1;
+pub const APLIC_SIZE: u64 = 0x8000;
+const APLIC_MAX_SRC: usize = 1024;
+
+const APLIC_DOMAINCFG: u64 = 0x0;
+const APLIC_SOURCECFG_BASE: u64 = 0x4;
+const APLIC_MMSIADDRCFG: u64 = 0x1bc0;
+const APLIC_SMSIADDRCFGH: u64 = 0x1bcc;
+const APLIC_SETIP_BASE: u64 = 0x1c00;
+const APLIC_SETIPNUM: u64 = 0x1cdc;
+const APLIC_CLRIP_BASE: u64 = 0x1d00;
+const APLIC_CLRIPNUM: u64 = 0x1ddc;
+const APLIC_SETIE_BASE: u64 = 0x1e00;
+const APLIC_SETIENUM: u64 = 0x1edc;
+const APLIC_CLRIE_BASE: u64 = 0x1f00;
+const APLIC_CLRIENUM: u64 = 0x1fdc;
+const APLIC_SETIPNUM_LE: u64 = 0x2000;
+const APLIC_SETIPNUM_BE: u64 = 0x2004;
+const APLIC_GENMSI: u64 = 0x3000;
+const APLIC_TARGET_BASE: u64 = 0x3004;
+const APLIC_IDC_BASE: u64 = 0x4000;
+const APLIC_IDC_SIZE: u64 = 0x20;
+
+const IDC_IDELIVERY: u64 = 0x0;
+const IDC_IFORCE: u64 = 0x4;
+const IDC_ITHRESHOLD: u64 = 0x8;
+const IDC_TOPI: u64 = 0x18;
+const IDC_CLAIMI: u64 = 0x1c;
+
+const DOMAINCFG_IE: u32 = 1 << 8;
+const DOMAINCFG_DM: u32 = 1 << 2;
+//reads as 0x80 in the top byte to tell it from a plic
+const DOMAINCFG_RO: u32 = 0x80 << 24;
+
+const SOURCECFG_D: u32 = 1 << 10;
+const SOURCECFG_SM: u32 = 0x7;
+const SM_INACTIVE: u32 = 0;
+const SM_DETACHED: u32 = 1;
+
+//hart index of target and genmsi
+const TARGET_HART: u32 = 0x3fff << 18;
+const TARGET_IPRIO: u32 = 0xff;
+const TARGET_EIID: u32 = 0x7ff;
+
+const MSIADDRCFGH_L: u32 = 1 << 31;
+
+#[derive(Default)]
+struct Idc {
+    idelivery: u32,
+    iforce: u32,
+    ithreshold: u32,
+}
+
+struct AplicInner {
+    domaincfg: [u32; 2],
+    //delegation of root domain or source mode
+    sourcecfg: [Vec<u32>; 2],
+    target: Vec<u32>,
+    ie: Vec<u32>,
+    //mmsiaddrcfg, mmsiaddrcfgh, smsiaddrcfg and smsiaddrcfgh
+    msiaddrcfg: [u32; 4],
+    genmsi: [u32; 2],
+    idcs: [Vec<Idc>; 2],
+    //meip and seip of harts in direct delivery mode
+    irq_vecs: Vec<(IrqVecSender, IrqVecSender)>,
+}
+
+impl AplicInner {
+    fn new() -> AplicInner {
+        AplicInner {
+            domaincfg: [0; 2],
+            sourcecfg: [vec![0; APLIC_MAX_SRC], vec![0; APLIC_MAX_SRC]],
+            target: vec![0; APLIC_MAX_SRC],
+            ie: vec![0; APLIC_MAX_SRC >> 5],
+            msiaddrcfg: [0; 4],
+            genmsi: [0; 2],
+            idcs: [vec![], vec![]],
+            irq_vecs: vec![],
+        }
+    }
+
+    //domain a source is active in
+    fn domain(&self, id: usize) -> Option<usize> {
+        let (domain, cfg) = if self.sourcecfg[APLIC_M][id] & SOURCECFG_D != 0 {
+            (APLIC_S, self.sourcecfg[APLIC_S][id])
+        } else {
+            (APLIC_M, self.sourcecfg[APLIC_M][id])
+        };
+        if cfg & SOURCECFG_SM == SM_INACTIVE {
+            None
+        } else {
+            Some(domain)
+        }
+    }
+
+    fn active(&self, domain: usize, id: usize) -> bool {
+        self.domain(id) == Some(domain)
+    }
+
+    fn ie(&self, id: usize) -> bool {
+        (self.ie[id >> 5] >> (id & 0x1f)) & 1 == 1
+    }
+
+    fn set_ie(&mut self, id: usize, enable: bool) {
+        if enable {
+            self.ie[id >> 5] |= 1 << (id & 0x1f)
+        } else {
+            self.ie[id >> 5] &= !(1 << (id & 0x1f))
+        }
+    }
+
+    fn legalize_target(&self, domain: usize, value: u32) -> u32 {
+        if self.domaincfg[domain] & DOMAINCFG_DM == 0 {
+            value & TARGET_HART | max(value & TARGET_IPRIO, 1)
+        } else {
+            value & (TARGET_HART | TARGET_EIID)
+        }
+    }
+
+    //highest priority source pending and enabled under threshold, direct delivery mode
+    fn topi(&self, intc: &Intc, domain: usize, hart: usize) -> u32 {
+        let threshold = self.idcs[domain][hart].ithreshold;
+        let mut topi = 0;
+        for id in 1..intc.num_src() {
+            let target = self.target[id];
+            let iprio = target & TARGET_IPRIO;
+            if self.active(domain, id)
+                && self.ie(id)
+                && intc.pending(id)
+                && (target >> 18) as usize == hart
+                && (threshold == 0 || iprio < threshold)
+                && (topi == 0 || iprio < topi & TARGET_IPRIO)
+            {
+                topi = (id as u32) << 16 | iprio
+            }
+        }
+        topi
+    }
+
+    //msi address of a hart in the domain
+    fn msi_addr(&self, domain: usize, hart: u32) -> u64 {
+        let cfgh = self.msiaddrcfg[1];
+        let lhxw = (cfgh >> 12) & 0xf;
+        let hhxw = (cfgh >> 16) & 0x7;
+        let hhxs = (cfgh >> 24) & 0x1f;
+        let (ppn, lhxs) = if domain == APLIC_M {
+            (
+                (cfgh as u64 & 0xfff) << 32 | self.msiaddrcfg[0] as u64,
+                (cfgh >> 20) & 0x7,
+            )
+        } else {
+            let scfgh = self.msiaddrcfg[3];
+            (
+                (scfgh as u64 & 0xfff) << 32 | self.msiaddrcfg[2] as u64,
+                (scfgh >> 20) & 0x7,
+            )
+        };
+        let group = (hart as u64 >> lhxw) & ((1 << hhxw) - 1);
+        let index = hart as u64 & ((1 << lhxw) - 1);
+        (ppn | group << (hhxs + 12) | index << lhxs) << 12
+    }
+}
+
+//interrupt domains routing wired sources of intc to harts, by idcs in direct mode or by msis to imsic
+pub struct Aplic {
+    intc: Rc<Intc>,
+    bus: Weak<TerminusBus>,
+    inner: RefCell<AplicInner>,
+}
+
+impl Aplic {
+    pub fn new(intc: &Rc<Intc>, bus: &Rc<TerminusBus>) -> Rc<Aplic> {
+        let aplic = Rc::new(Aplic {
+            intc: intc.clone(),
+            bus: Rc::downgrade(bus),
+            inner: RefCell::new(AplicInner::new()),
+        });
+        intc.add_route({
+            let aplic = Rc::downgrade(&aplic);
+            move |_| {
+                if let Some(aplic) = aplic.upgrade() {
+                    aplic.update()
+                }
+            }
+        });
+        aplic
+    }
+
+    //line 0 is meip driven by M domain and line 1 is seip driven by S domain
+    pub fn alloc_irq(&self) -> IrqVec {
+        let irq_vec = IrqVec::new(2);
+        irq_vec.set_enable_uncheck(0, true);
+        irq_vec.set_enable_uncheck(1, true);
+        let mut inner = self.inner_mut();
+        inner
+            .irq_vecs
+            .push((irq_vec.sender(0).unwrap(), irq_vec.sender(1).unwrap()));
+        inner.idcs[APLIC_M].push(Idc::default());
+        inner.idcs[APLIC_S].push(Idc::default());
+        irq_vec
+    }
+
+    pub fn num_src(&self) -> usize {
+        self.intc.num_src()
+    }
+
+    //drive idc lines in direct mode, or forward pending sources as msis and clear them
+    fn update(&self) {
+        let mut msis = vec![];
+        {
+            let inner = self.inner();
+            for domain in [APLIC_M, APLIC_S].iter() {
+                let cfg = inner.domaincfg[*domain];
+                if cfg & DOMAINCFG_DM == 0 {
+                    for (hart, idc) in inner.idcs[*domain].iter().enumerate() {
+                        let line = if *domain == APLIC_M {
+                            &inner.irq_vecs[hart].0
+                        } else {
+                            &inner.irq_vecs[hart].1
+                        };
+                        if cfg & DOMAINCFG_IE != 0
+                            && idc.idelivery == 1
+                            && (idc.iforce == 1 || inner.topi(&self.intc, *domain, hart) != 0)
+                        {
+                            line.send().unwrap()
+                        } else {
+                            line.clear().unwrap()
+                        }
+                    }
+                } else {
+                    for (m, s) in inner.irq_vecs.iter() {
+                        if *domain == APLIC_M {
+                            m.clear().unwrap()
+                        } else {
+                            s.clear().unwrap()
+                        }
+                    }
+                    if cfg & DOMAINCFG_IE != 0 {
+                        for id in 1..self.num_src() {
+                            if inner.active(*domain, id) && inner.ie(id) && self.intc.pending(id) {
+                                let target = inner.target[id];
+                                msis.push((
+                                    id,
+                                    inner.msi_addr(*domain, target >> 18),
+                                    target & TARGET_EIID,
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        for (id, addr, eiid) in msis {
+            self.intc.set_pending(id, false);
+            self.send_msi(addr, eiid)
+        }
+    }
+
+    fn send_msi(&self, addr: u64, eiid: u32) {
+        if let Some(bus) = self.bus.upgrade() {
+            bus.write_u32(&addr, &eiid).ok();
+        }
+    }
+
+    fn set_pending(&self, domain: usize, id: usize, pending: bool) {
+        if id < APLIC_MAX_SRC && self.inner().active(domain, id) {
+            self.intc.set_pending(id, pending)
+        }
+    }
+
+    fn set_enable(&self, domain: usize, id: usize, enable: bool) {
+        let mut inner = self.inner_mut();
+        if id < APLIC_MAX_SRC && inner.active(domain, id) {
+            inner.set_ie(id, enable)
+        }
+    }
+
+    fn claim(&self, domain: usize, hart: usize) -> u32 {
+        let topi = {
+            let mut inner = self.inner_mut();
+            let topi = inner.topi(&self.intc, domain, hart);
+            if topi == 0 {
+                inner.idcs[domain][hart].iforce = 0;
+            }
+            topi
+        };
+        self.intc.set_pending((topi >> 16) as usize, false);
+        self.update();
+        topi
+    }
+
+    fn write_sourcecfg(&self, domain: usize, id: usize, value: u32) {
+        let mut inner = self.inner_mut();
+        if domain == APLIC_S && inner.sourcecfg[APLIC_M][id] & SOURCECFG_D == 0 {
+            return;
+        }
+        //child index must be 0, and the S domain has no child
+        let cfg = if domain == APLIC_M && value & SOURCECFG_D != 0 {
+            if value & 0x3ff == 0 {
+                SOURCECFG_D
+            } else {
+                0
+            }
+        } else {
+            match value & SOURCECFG_SM {
+                SM_INACTIVE | SM_DETACHED | 4..=7 => value & SOURCECFG_SM,
+                _ => SM_INACTIVE,
+            }
+        };
+        inner.sourcecfg[domain][id] = cfg;
+        if domain == APLIC_M && cfg & SOURCECFG_D == 0 {
+            inner.sourcecfg[APLIC_S][id] = 0;
+        }
+        let inactive = inner.domain(id).is_none();
+        if inactive {
+            inner.set_ie(id, false);
+            inner.target[id] = 0;
+        }
+        drop(inner);
+        if inactive {
+            self.intc.set_pending(id, false)
+        }
+    }
+
+    pub fn save_snapshot<W: Write>(&self, w: &mut SnapshotWriter<W>) -> io::Result<()> {
+        let inner = self.inner();
+        for cfg in inner.domaincfg.iter().chain(inner.msiaddrcfg.iter()) {
+            w.write_u32(*cfg)?;
+        }
+        for cfg in inner.genmsi.iter() {
+            w.write_u32(*cfg)?;
+        }
+        for i in 0..APLIC_MAX_SRC {
+            w.write_u32(inner.sourcecfg[APLIC_M][i])?;
+            w.write_u32(inner.sourcecfg[APLIC_S][i])?;
+            w.write_u32(inner.target[i])?;
+        }
+        for ie in inner.ie.iter() {
+            w.write_u32(*ie)?;
+        }
+        w.write_u64(inner.irq_vecs.len() as u64)?;
+        for idc in inner.idcs.iter().flat_map(|idcs| idcs.iter()) {
+            w.write_u32(idc.idelivery)?;
+            w.write_u32(idc.iforce)?;
+            w.write_u32(idc.ithreshold)?;
+        }
+        Ok(())
+    }
+
+    pub fn restore_snapshot<R: Read>(&self, r: &mut SnapshotReader<R>) -> io::Result<()> {
+        {
+            let mut inner = self.inner_mut();
+            let AplicInner {
+                ref mut domaincfg,
+                ref mut msiaddrcfg,
+                ref mut genmsi,
+                ref mut sourcecfg,
+                ref mut target,
+                ref mut ie,
+                ref mut idcs,
+                ref irq_vecs,
+            } = *inner;
+            for cfg in domaincfg
+                .iter_mut()
+                .chain(msiaddrcfg.iter_mut())
+                .chain(genmsi.iter_mut())
+            {
+                *cfg = r.read_u32()?;
+            }
+            for i in 0..APLIC_MAX_SRC {
+                sourcecfg[APLIC_M][i] = r.read_u32()?;
+                sourcecfg[APLIC_S][i] = r.read_u32()?;
+                target[i] = r.read_u32()?;
+            }
+            for e in ie.iter_mut() {
+                *e = r.read_u32()?;
+            }
+            r.expect_u64(irq_vecs.len() as u64, "aplic harts")?;
+            for idc in idcs.iter_mut().flat_map(|idcs| idcs.iter_mut()) {
+                idc.idelivery = r.read_u32()?;
+                idc.iforce = r.read_u32()?;
+                idc.ithreshold = r.read_u32()?;
+            }
+        }
+        self.update();
+        Ok(())
+    }
+
+    fn inner(&self) -> Ref<'_, AplicInner> {
+        self.inner.borrow()
+    }
+
+    fn inner_mut(&self) -> RefMut<'_, AplicInner> {
+        self.inner.borrow_mut()
+    }
+}
+
+//memory mapped registers of a domain
+#[derive_io(Bytes, U32, U64)]
+pub struct AplicDomain(Rc<Aplic>, usize);
+
+impl AplicDomain {
+    pub fn new(aplic: &Rc<Aplic>, domain: usize) -> AplicDomain {
+        AplicDomain(aplic.clone(), domain)
+    }
+
+    //bits of setip, in_clrip or setie
+    fn bits(&self, offset: u64, f: impl Fn(usize) -> bool) -> u32 {
+        let inner = self.0.inner();
+        let mut res = 0;
+        for i in 0..32 {
+            let id = ((offset >> 2) << 5) as usize + i;
+            if id != 0 && id < self.0.num_src() && inner.active(self.1, id) && f(id) {
+                res |= 1 << i
+            }
+        }
+        res
+    }
+
+    fn idc_read(&self, offset: u64) -> u32 {
+        let hart = (offset / APLIC_IDC_SIZE) as usize;
+        if hart >= self.0.inner().idcs[self.1].len() {
+            return 0;
+        }
+        match offset % APLIC_IDC_SIZE {
+            IDC_IDELIVERY => self.0.inner().idcs[self.1][hart].idelivery,
+            IDC_IFORCE => self.0.inner().idcs[self.1][hart].iforce,
+            IDC_ITHRESHOLD => self.0.inner().idcs[self.1][hart].ithreshold,
+            IDC_TOPI => self.0.inner().topi(&self.0.intc, self.1, hart),
+            IDC_CLAIMI => self.0.claim(self.1, hart),
+            _ => 0,
+        }
+    }
+
+    fn idc_write(&self, offset: u64, data: u32) {
+        let hart = (offset / APLIC_IDC_SIZE) as usize;
+        let mut inner = self.0.inner_mut();
+        if let Some(idc) = inner.idcs[self.1].get_mut(hart) {
+            match offset % APLIC_IDC_SIZE {
+                IDC_IDELIVERY => idc.idelivery = data & 1,
+                IDC_IFORCE => idc.iforce = data & 1,
+                IDC_ITHRESHOLD => idc.ithreshold = data & TARGET_IPRIO,
+                _ => {}
+            }
+        }
+    }
+}
+
+impl BytesAccess for AplicDomain {
+    fn write(&self, addr: &u64, data: &[u8]) -> std::result::Result<usize, String> {
+        if data.len() == 4 {
+            let mut bytes = [0; 4];
+            bytes.copy_from_slice(data);
+            U32Access::write(self, addr, u32::from_le_bytes(bytes))
+        } else if data.len() == 8 {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(data);
+            U64Access::write(self, addr, u64::from_le_bytes(bytes))
+        }
+        Ok(0)
+    }
+
+    fn read(&self, addr: &u64, data: &mut [u8]) -> std::result::Result<usize, String> {
+        if data.len() == 4 {
+            data.copy_from_slice(&U32Access::read(self, addr).to_le_bytes())
+        } else if data.len() == 8 {
+            data.copy_from_slice(&U64Access::read(self, addr).to_le_bytes())
+        }
+        Ok(0)
+    }
+}
+
+impl U32Access for AplicDomain {
+    fn write(&self, addr: &u64, data: u32) {
+        assert!(
+            (*addr).trailing_zeros() > 1,
+            "U32Access:unaligned addr:{:#x}", addr
+        );
+        let (aplic, domain) = (&self.0, self.1);
+        let id = data as usize;
+        match *addr {
+            APLIC_DOMAINCFG => {
+                aplic.inner_mut().domaincfg[domain] = data & (DOMAINCFG_IE | DOMAINCFG_DM)
+            }
+            APLIC_SOURCECFG_BASE..=0xffc => {
+                let id = ((*addr - APLIC_SOURCECFG_BASE) >> 2) as usize + 1;
+                if id < aplic.num_src() {
+                    aplic.write_sourcecfg(domain, id, data)
+                }
+            }
+            APLIC_MMSIADDRCFG..=APLIC_SMSIADDRCFGH => {
+                let mut inner = aplic.inner_mut();
+                if domain == APLIC_M && inner.msiaddrcfg[1] & MSIADDRCFGH_L == 0 {
+                    inner.msiaddrcfg[((*addr - APLIC_MMSIADDRCFG) >> 2) as usize] = data
+                }
+            }
+            APLIC_SETIP_BASE..=0x1c7c => {
+                let base = ((*addr - APLIC_SETIP_BASE) << 3) as usize;
+                for i in (0..32).filter(|i| (data >> i) & 1 == 1) {
+                    aplic.set_pending(domain, base + i, true)
+                }
+            }
+            APLIC_SETIPNUM | APLIC_SETIPNUM_LE => aplic.set_pending(domain, id, true),
+            APLIC_SETIPNUM_BE => aplic.set_pending(domain, data.swap_bytes() as usize, true),
+            APLIC_CLRIP_BASE..=0x1d7c => {
+                let base = ((*addr - APLIC_CLRIP_BASE) << 3) as usize;
+                for i in (0..32).filter(|i| (data >> i) & 1 == 1) {
+                    aplic.set_pending(domain, base + i, false)
+                }
+            }
+            APLIC_CLRIPNUM => aplic.set_pending(domain, id, false),
+            APLIC_SETIE_BASE..=0x1e7c => {
+                let base = ((*addr - APLIC_SETIE_BASE) << 3) as usize;
+                for i in (0..32).filter(|i| (data >> i) & 1 == 1) {
+                    aplic.set_enable(domain, base + i, true)
+                }
+            }
+            APLIC_SETIENUM => aplic.set_enable(domain, id, true),
+            APLIC_CLRIE_BASE..=0x1f7c => {
+                let base = ((*addr - APLIC_CLRIE_BASE) << 3) as usize;
+                for i in (0..32).filter(|i| (data >> i) & 1 == 1) {
+                    aplic.set_enable(domain, base + i, false)
+                }
+            }
+            APLIC_CLRIENUM => aplic.set_enable(domain, id, false),
+            //msi is sent at once, busy is never seen
+            APLIC_GENMSI => {
+                let msi = {
+                    let mut inner = aplic.inner_mut();
+                    let value = data & (TARGET_HART | TARGET_EIID);
+                    inner.genmsi[domain] = value;
+                    if inner.domaincfg[domain] & DOMAINCFG_DM != 0 {
+                        Some((inner.msi_addr(domain, value >> 18), value & TARGET_EIID))
+                    } else {
+                        None
+                    }
+                };
+                if let Some((addr, eiid)) = msi {
+                    aplic.send_msi(addr, eiid)
+                }
+            }
+            APLIC_TARGET_BASE..=0x3ffc => {
+                let id = ((*addr - APLIC_TARGET_BASE) >> 2) as usize + 1;
+                let mut inner = aplic.inner_mut();
+                if id < aplic.num_src() && inner.active(domain, id) {
+                    let target = inner.legalize_target(domain, data);
+                    inner.target[id] = target
+                }
+            }
+            APLIC_IDC_BASE..=0x7ffc => self.idc_write(*addr - APLIC_IDC_BASE, data),
+            _ => {}
+        }
+        aplic.update()
+    }
+
+    fn read(&self, addr: &u64) -> u32 {
+        assert!(
+            (*addr).trailing_zeros() > 1,
+            "U32Access:unaligned addr:{:#x}", addr
+        );
+        let (aplic, domain) = (&self.0, self.1);
+        match *addr {
+            APLIC_DOMAINCFG => DOMAINCFG_RO | aplic.inner().domaincfg[domain],
+            APLIC_SOURCECFG_BASE..=0xffc => {
+                let id = ((*addr - APLIC_SOURCECFG_BASE) >> 2) as usize + 1;
+                let inner = aplic.inner();
+                if id >= aplic.num_src()
+                    || domain == APLIC_S && inner.sourcecfg[APLIC_M][id] & SOURCECFG_D == 0
+                {
+                    0
+                } else {
+                    inner.sourcecfg[domain][id]
+                }
+            }
+            APLIC_MMSIADDRCFG..=APLIC_SMSIADDRCFGH => {
+                if domain == APLIC_M {
+                    aplic.inner().msiaddrcfg[((*addr - APLIC_MMSIADDRCFG) >> 2) as usize]
+                } else {
+                    0
+                }
+            }
+            APLIC_SETIP_BASE..=0x1c7c => {
+                self.bits(*addr - APLIC_SETIP_BASE, |id| aplic.intc.pending(id))
+            }
+            //input values are not modeled apart from pending states
+            APLIC_CLRIP_BASE..=0x1d7c => {
+                self.bits(*addr - APLIC_CLRIP_BASE, |id| aplic.intc.pending(id))
+            }
+            APLIC_SETIE_BASE..=0x1e7c => {
+                let inner = aplic.inner();
+                self.bits(*addr - APLIC_SETIE_BASE, |id| inner.ie(id))
+            }
+            APLIC_GENMSI => aplic.inner().genmsi[domain],
+            APLIC_TARGET_BASE..=0x3ffc => {
+                let id = ((*addr - APLIC_TARGET_BASE) >> 2) as usize + 1;
+                let inner = aplic.inner();
+                if id < aplic.num_src() && inner.active(domain, id) {
+                    inner.target[id]
+                } else {
+                    0
+                }
+            }
+            APLIC_IDC_BASE..=0x7ffc => self.idc_read(*addr - APLIC_IDC_BASE),
+            _ => 0,
+        }
+    }
+}
+
+impl U64Access for AplicDomain {
+    fn write(&self, addr: &u64, data: u64) {
+        assert!(
+            (*addr).trailing_zeros() > 2,
+            "U64Access:unaligned addr:{:#x}", addr
+        );
+        U32Access::write(self, addr, data as u32);
+        U32Access::write(self, &(*addr + 4), (data >> 32) as u32);
+    }
+
+    fn read(&self, addr: &u64) -> u64 {
+        assert!(
+            (*addr).trailing_zeros() > 2,
+            "U64Access:unaligned addr:{:#x}", addr
+        );
+        U32Access::read(self, addr) as u64 | ((U32Access::read(self, &(*addr + 4)) as u64) << 32)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::devices::imsic::{HartImsic, Imsic, ImsicFiles, IMSIC_M, IMSIC_S};
+    use crate::prelude::{RegT, XLen};
+    use terminus_spaceport::memory::region::Region;
+
+    const SM_EDGE1: u32 = 4;
+    const IMSIC_EIDELIVERY: RegT = 0x70;
+    const IMSIC_EIP0: RegT = 0x80;
+    const IMSIC_EIE0: RegT = 0xc0;
+
+    fn sourcecfg(id: usize) -> u64 {
+        APLIC_SOURCECFG_BASE + (id as u64 - 1) * 4
+    }
+
+    fn target(id: usize) -> u64 {
+        APLIC_TARGET_BASE + (id as u64 - 1) * 4
+    }
+
+    fn idc(hart: usize, reg: u64) -> u64 {
+        APLIC_IDC_BASE + hart as u64 * APLIC_IDC_SIZE + reg
+    }
+
+    #[test]
+    fn aplic_delegation_test() {
+        let intc = Rc::new(Intc::new(32));
+        let bus = Rc::new(TerminusBus::new());
+        let aplic = Aplic::new(&intc, &bus);
+        let irqs = vec![aplic.alloc_irq(), aplic.alloc_irq()];
+        let src1 = intc.alloc_src(1);
+        let src2 = intc.alloc_src(2);
+        let src3 = intc.alloc_src(3);
+        let m = AplicDomain::new(&aplic, APLIC_M);
+        let s = AplicDomain::new(&aplic, APLIC_S);
+        let eip =
+            |hart: usize, domain: usize| irqs[hart].listener(domain).unwrap().pending_uncheck();
+
+        //the S domain is the only child
+        U32Access::write(&m, &sourcecfg(1), SOURCECFG_D | 1);
+        assert_eq!(U32Access::read(&m, &sourcecfg(1)), 0);
+        //sources 1 and 2 are delegated to the S domain, source 3 stays in the M domain
+        U32Access::write(&m, &sourcecfg(1), SOURCECFG_D);
+        U32Access::write(&m, &sourcecfg(2), SOURCECFG_D);
+        U32Access::write(&m, &sourcecfg(3), SM_EDGE1);
+        assert_eq!(U32Access::read(&m, &sourcecfg(1)), SOURCECFG_D);
+        assert_eq!(U32Access::read(&s, &sourcecfg(3)), 0);
+        U32Access::write(&s, &sourcecfg(3), SM_EDGE1 + 1);
+        assert_eq!(U32Access::read(&m, &sourcecfg(3)), SM_EDGE1);
+        U32Access::write(&s, &sourcecfg(1), SM_EDGE1);
+        U32Access::write(&s, &sourcecfg(2), SM_EDGE1);
+        assert_eq!(U32Access::read(&s, &sourcecfg(2)), SM_EDGE1);
+
+        //a domain only configures its active sources
+        U32Access::write(&m, &target(3), 1);
+        U32Access::write(&s, &target(3), 1 << 18 | 2);
+        U32Access::write(&s, &target(1), 1 << 18 | 2);
+        U32Access::write(&s, &target(2), 1 << 18 | 1);
+        assert_eq!(U32Access::read(&m, &target(3)), 1);
+        assert_eq!(U32Access::read(&m, &target(1)), 0);
+        assert_eq!(U32Access::read(&s, &target(1)), 1 << 18 | 2);
+        U32Access::write(&m, &APLIC_SETIENUM, 1);
+        U32Access::write(&m, &APLIC_SETIENUM, 3);
+        U32Access::write(&s, &APLIC_SETIENUM, 3);
+        U32Access::write(&s, &APLIC_SETIE_BASE, 0x6);
+        assert_eq!(U32Access::read(&m, &APLIC_SETIE_BASE), 0x8);
+        assert_eq!(U32Access::read(&s, &APLIC_SETIE_BASE), 0x6);
+
+        U32Access::write(&m, &APLIC_DOMAINCFG, DOMAINCFG_IE);
+        U32Access::write(&s, &APLIC_DOMAINCFG, DOMAINCFG_IE);
+        assert_eq!(U32Access::read(&m, &APLIC_DOMAINCFG), 0x8000_0100);
+        U32Access::write(&m, &idc(0, IDC_IDELIVERY), 1);
+        U32Access::write(&s, &idc(1, IDC_IDELIVERY), 1);
+
+        src1.send().unwrap();
+        assert!(eip(1, APLIC_S) && !eip(1, APLIC_M) && !eip(0, APLIC_M) && !eip(0, APLIC_S));
+        assert_eq!(U32Access::read(&s, &APLIC_SETIP_BASE), 0x2);
+        assert_eq!(U32Access::read(&m, &APLIC_SETIP_BASE), 0);
+        src2.send().unwrap();
+        src3.send().unwrap();
+        assert!(eip(0, APLIC_M));
+        //lower priority number first
+        assert_eq!(U32Access::read(&s, &idc(1, IDC_TOPI)), 2 << 16 | 1);
+        assert_eq!(U32Access::read(&m, &idc(0, IDC_TOPI)), 3 << 16 | 1);
+        assert_eq!(U32Access::read(&m, &idc(1, IDC_TOPI)), 0);
+
+        //claims of a domain do not affect the other
+        assert_eq!(U32Access::read(&s, &idc(1, IDC_CLAIMI)), 2 << 16 | 1);
+        assert_eq!(U32Access::read(&s, &idc(1, IDC_CLAIMI)), 1 << 16 | 2);
+        assert!(!eip(1, APLIC_S) && eip(0, APLIC_M));
+        assert_eq!(U32Access::read(&s, &idc(1, IDC_CLAIMI)), 0);
+        assert_eq!(U32Access::read(&s, &APLIC_SETIP_BASE), 0);
+        assert_eq!(U32Access::read(&m, &APLIC_SETIP_BASE), 0x8);
+
+        //threshold
+        U32Access::write(&m, &idc(0, IDC_ITHRESHOLD), 1);
+        assert!(!eip(0, APLIC_M));
+        U32Access::write(&m, &idc(0, IDC_ITHRESHOLD), 0);
+        assert!(eip(0, APLIC_M));
+        assert_eq!(U32Access::read(&m, &idc(0, IDC_CLAIMI)), 3 << 16 | 1);
+        assert!(!eip(0, APLIC_M));
+
+        //iforce is cleared by a claim without pending sources
+        U32Access::write(&s, &idc(1, IDC_IFORCE), 1);
+        assert!(eip(1, APLIC_S));
+        assert_eq!(U32Access::read(&s, &idc(1, IDC_CLAIMI)), 0);
+        assert!(!eip(1, APLIC_S));
+
+        //undelegated sources become inactive in the S domain
+        U32Access::write(&m, &sourcecfg(1), 0);
+        assert_eq!(U32Access::read(&s, &sourcecfg(1)), 0);
+        assert_eq!(U32Access::read(&s, &APLIC_SETIE_BASE), 0x4);
+        src1.send().unwrap();
+        assert!(!eip(1, APLIC_S) && !eip(0, APLIC_M));
+        assert_eq!(U32Access::read(&m, &APLIC_SETIP_BASE), 0);
+    }
+
+    #[test]
+    fn aplic_msi_test() {
+        let intc = Rc::new(Intc::new(32));
+        let bus = Rc::new(TerminusBus::new());
+        let imsic = Rc::new(Imsic::new(63));
+        let files = vec![HartImsic::alloc(&imsic), HartImsic::alloc(&imsic)];
+        for (name, base, level) in [
+            ("imsic_m", 0x2400_0000, IMSIC_M),
+            ("imsic_s", 0x2800_0000, IMSIC_S),
+        ]
+        .iter()
+        {
+            let imsic_files = ImsicFiles::new(&imsic, *level);
+            let region = Region::io(0, imsic_files.size(), Box::new(imsic_files));
+            bus.space_mut()
+                .add_region(name, &Region::remap(*base, &region))
+                .unwrap();
+        }
+        let aplic = Aplic::new(&intc, &bus);
+        let irqs = vec![aplic.alloc_irq(), aplic.alloc_irq()];
+        let src1 = intc.alloc_src(1);
+        let src2 = intc.alloc_src(2);
+        let m = AplicDomain::new(&aplic, APLIC_M);
+        let s = AplicDomain::new(&aplic, APLIC_S);
+        let eip =
+            |hart: usize, level: usize| files[hart].ireg(level, IMSIC_EIP0, XLen::X64).unwrap();
+
+        //one hart index bit, interrupt files of harts are in consecutive pages
+        U32Access::write(&m, &APLIC_MMSIADDRCFG, 0x2400_0000 >> 12);
+        U32Access::write(&m, &(APLIC_MMSIADDRCFG + 4), 1 << 12);
+        U32Access::write(&m, &(APLIC_MMSIADDRCFG + 8), 0x2800_0000 >> 12);
+        //msi addresses are only configured by the M domain, and locked by L
+        U32Access::write(&s, &(APLIC_MMSIADDRCFG + 8), 0);
+        assert_eq!(U32Access::read(&s, &(APLIC_MMSIADDRCFG + 8)), 0);
+        assert_eq!(U32Access::read(&m, &(APLIC_MMSIADDRCFG + 8)), 0x28000);
+        U32Access::write(&m, &(APLIC_MMSIADDRCFG + 4), MSIADDRCFGH_L | 1 << 12);
+        U32Access::write(&m, &(APLIC_MMSIADDRCFG + 8), 0);
+        assert_eq!(U32Access::read(&m, &(APLIC_MMSIADDRCFG + 8)), 0x28000);
+
+        U32Access::write(&m, &APLIC_DOMAINCFG, DOMAINCFG_IE | DOMAINCFG_DM);
+        U32Access::write(&s, &APLIC_DOMAINCFG, DOMAINCFG_IE | DOMAINCFG_DM);
+        U32Access::write(&m, &sourcecfg(1), SM_EDGE1);
+        U32Access::write(&m, &sourcecfg(2), SOURCECFG_D);
+        U32Access::write(&s, &sourcecfg(2), SM_EDGE1);
+        //hart index and eiid, iprio does not exist in msi mode
+        U32Access::write(&m, &target(1), 1 << 18 | 0x7f << 11 | 5);
+        U32Access::write(&s, &target(2), 7);
+        assert_eq!(U32Access::read(&m, &target(1)), 1 << 18 | 5);
+        U32Access::write(&m, &APLIC_SETIENUM, 1);
+        U32Access::write(&s, &APLIC_SETIENUM, 2);
+
+        //pending sources are forwarded as msis and cleared
+        src1.send().unwrap();
+        assert_eq!(eip(1, IMSIC_M), 1 << 5);
+        assert_eq!(eip(0, IMSIC_M) | eip(0, IMSIC_S) | eip(1, IMSIC_S), 0);
+        assert_eq!(U32Access::read(&m, &APLIC_SETIP_BASE), 0);
+        src2.send().unwrap();
+        assert_eq!(eip(0, IMSIC_S), 1 << 7);
+        assert_eq!(eip(1, IMSIC_S), 0);
+        //direct delivery lines are never driven
+        assert!(irqs
+            .iter()
+            .all(|irq| !irq.listener(0).unwrap().pending_uncheck()
+                && !irq.listener(1).unwrap().pending_uncheck()));
+
+        //msi of genmsi goes to the file of the domain
+        U32Access::write(&s, &APLIC_GENMSI, 1 << 18 | 9);
+        assert_eq!(U32Access::read(&s, &APLIC_GENMSI), 1 << 18 | 9);
+        assert_eq!(eip(1, IMSIC_S), 1 << 9);
+
+        //delivered identities are claimed by topei of the file
+        assert_eq!(files[1].topei(IMSIC_M), 0);
+        files[1].set_ireg(IMSIC_M, IMSIC_EIDELIVERY, 1, XLen::X64);
+        files[1].set_ireg(IMSIC_M, IMSIC_EIE0, 1 << 5, XLen::X64);
+        assert_eq!(files[1].topei(IMSIC_M), 5 << 16 | 5);
+        files[1].claim(IMSIC_M);
+        assert_eq!(files[1].topei(IMSIC_M), 0);
+        assert_eq!(eip(1, IMSIC_M), 0);
+    }
+}
@@ -64,6 +64,26 @@ impl TerminusBus {
     pub fn space_mut(&self) -> RefMut<'_, Space> {
         self.space.borrow_mut()
     }
+
+    //(addr, len, holder) of current LR reservations, for snapshot
+    pub fn locks(&self) -> Vec<(u64, usize, usize)> {
+        self.lock_table
+            .borrow()
+            .iter()
+            .map(|e| (e.addr, e.len, e.holder))
+            .collect()
+    }
+
+    pub fn set_locks(&self, locks: &[(u64, usize, usize)]) {
+        *self.lock_table.borrow_mut() = locks
+            .iter()
+            .map(|(addr, len, holder)| LockEntry {
+                addr: *addr,
+                len: *len,
+                holder: *holder,
+            })
+            .collect()
+    }
 }
 
 impl Bus for TerminusBus {
@@ -1,4 +1,7 @@
+use crate::snapshot::{SnapshotReader, SnapshotWriter};
 use std::cell::{Ref, RefCell, RefMut};
+use std::io;
+use std::io::{Read, Write};
 use std::num::Wrapping;
 use std::rc::Rc;
 use terminus_spaceport::irq::{IrqVec, IrqVecListener, IrqVecSender};
@@ -12,6 +15,10 @@ struct TimerInner {
     sints: Vec<IrqVecSender>,
     sint_status: Vec<IrqVecListener>,
     mtimecmps: Vec<u64>,
+    //sstc
+    stints: Vec<IrqVecSender>,
+    stint_status: Vec<IrqVecListener>,
+    stimecmps: Vec<u64>,
 }
 
 impl TimerInner {
@@ -23,6 +30,9 @@ impl TimerInner {
             sints: vec![],
             sint_status: vec![],
             mtimecmps: vec![],
+            stints: vec![],
+            stint_status: vec![],
+            stimecmps: vec![],
         }
     }
 
@@ -36,13 +46,18 @@ impl TimerInner {
     }
 
     fn alloc_irq(&mut self) -> IrqVec {
-        let irq_vec = IrqVec::new(2);
+        let irq_vec = IrqVec::new(3);
         irq_vec.set_enable_uncheck(0, true);
         irq_vec.set_enable_uncheck(1, true);
+        irq_vec.set_enable_uncheck(2, true);
         self.sints.push(irq_vec.sender(0).unwrap());
         self.sint_status.push(irq_vec.listener(0).unwrap());
         self.tints.push(irq_vec.sender(1).unwrap());
         self.mtimecmps.push(0);
+        self.stints.push(irq_vec.sender(2).unwrap());
+        self.stint_status.push(irq_vec.listener(2).unwrap());
+        //stip is not pending until stimecmp is written
+        self.stimecmps.push(u64::MAX);
         irq_vec
     }
 
@@ -54,6 +69,12 @@ impl TimerInner {
                 tint.send().unwrap();
             }
         }
+        for (stint, stimecmp) in self.stints.iter().zip(self.stimecmps.iter()) {
+            stint.clear().unwrap();
+            if self.cnt >= *stimecmp {
+                stint.send().unwrap();
+            }
+        }
     }
 }
 
@@ -76,10 +97,47 @@ impl Timer {
         self.0.borrow().freq
     }
 
+    pub fn time(&self) -> u64 {
+        self.0.borrow().cnt
+    }
+
     pub fn reset(&self) {
         self.0.borrow_mut().reset()
     }
 
+    pub fn save_snapshot<W: Write>(&self, w: &mut SnapshotWriter<W>) -> io::Result<()> {
+        let timer = self.inner();
+        w.write_u64(timer.cnt)?;
+        w.write_u64(timer.mtimecmps.len() as u64)?;
+        for (mtimecmp, sint) in timer.mtimecmps.iter().zip(timer.sint_status.iter()) {
+            w.write_u64(*mtimecmp)?;
+            w.write_bool(sint.pending_uncheck())?;
+        }
+        for stimecmp in timer.stimecmps.iter() {
+            w.write_u64(*stimecmp)?;
+        }
+        Ok(())
+    }
+
+    pub fn restore_snapshot<R: Read>(&self, r: &mut SnapshotReader<R>) -> io::Result<()> {
+        let mut timer = self.inner_mut();
+        timer.cnt = r.read_u64()?;
+        r.expect_u64(timer.mtimecmps.len() as u64, "timer harts")?;
+        for i in 0..timer.mtimecmps.len() {
+            timer.mtimecmps[i] = r.read_u64()?;
+            if r.read_bool()? {
+                timer.sints[i].send().unwrap();
+            } else {
+                timer.sints[i].clear().unwrap();
+            }
+        }
+        for i in 0..timer.stimecmps.len() {
+            timer.stimecmps[i] = r.read_u64()?;
+        }
+        timer.tick(0);
+        Ok(())
+    }
+
     fn inner(&self) -> Ref<'_, TimerInner> {
         self.0.borrow()
     }
@@ -89,6 +147,46 @@ impl Timer {
     }
 }
 
+//time and sstc stimecmp of a hart, stip is driven by the timer ticks
+#[derive(Clone)]
+pub struct HartTimer {
+    timer: Rc<Timer>,
+    id: usize,
+}
+
+impl HartTimer {
+    pub fn alloc(timer: &Rc<Timer>) -> (HartTimer, IrqVec) {
+        let mut inner = timer.inner_mut();
+        let irq_vec = inner.alloc_irq();
+        let id = inner.stimecmps.len() - 1;
+        (
+            HartTimer {
+                timer: timer.clone(),
+                id,
+            },
+            irq_vec,
+        )
+    }
+
+    pub fn time(&self) -> u64 {
+        self.timer.time()
+    }
+
+    pub fn stimecmp(&self) -> u64 {
+        self.timer.inner().stimecmps[self.id]
+    }
+
+    pub fn set_stimecmp(&self, value: u64) {
+        let mut inner = self.timer.inner_mut();
+        inner.stimecmps[self.id] = value;
+        inner.tick(0);
+    }
+
+    pub fn stip(&self) -> bool {
+        self.timer.inner().stint_status[self.id].pending_uncheck()
+    }
+}
+
 const MSIP_BASE: u64 = 0x0;
 const MSIP_SIZE: u64 = 4;
 const MTIMECMP_BASE: u64 = 0x4000;
@@ -1,6 +1,8 @@
+use crate::replay::{InputSrc, Replay};
 use std::borrow::BorrowMut;
 use std::cell::RefCell;
 use std::io::{ErrorKind, Read, Write};
+use std::rc::Rc;
 use terminus_spaceport::devices::TERM;
 use terminus_spaceport::memory::prelude::*;
 use terminus_spaceport::EXIT_CTRL;
@@ -21,39 +23,71 @@ impl HTIFDesp {
     }
 }
 
+//handle of tohost/fromhost registers, used by snapshot
+#[derive(Clone)]
+pub struct HTIFRegs(Rc<RefCell<HTIFDesp>>);
+
+impl HTIFRegs {
+    pub fn tohost(&self) -> u64 {
+        self.0.borrow().tohost
+    }
+    pub fn fromhost(&self) -> u64 {
+        self.0.borrow().fromhost
+    }
+    pub fn set_tohost(&self, v: u64) {
+        self.0.borrow_mut().tohost = v
+    }
+    pub fn set_fromhost(&self, v: u64) {
+        self.0.borrow_mut().fromhost = v
+    }
+}
+
 #[derive_io(Bytes, U32, U64)]
 pub struct HTIF {
-    desc: RefCell<HTIFDesp>,
+    desc: Rc<RefCell<HTIFDesp>>,
     tohost_off: u64,
     fromhost_off: Option<u64>,
     input_en: bool,
+    replay: Option<Rc<Replay>>,
 }
 
 impl HTIF {
     pub fn new(tohost_off: u64, fromhost_off: Option<u64>, input_en: bool) -> HTIF {
         HTIF {
-            desc: RefCell::new(HTIFDesp {
+            desc: Rc::new(RefCell::new(HTIFDesp {
                 tohost: 0,
                 fromhost: 0,
-            }),
+            })),
             tohost_off,
             fromhost_off,
             input_en,
+            replay: None,
         }
     }
 
+    //route console input and output through record/replay
+    pub fn set_replay(&mut self, replay: &Rc<Replay>) {
+        self.replay = Some(replay.clone())
+    }
+
+    pub fn regs(&self) -> HTIFRegs {
+        HTIFRegs(self.desc.clone())
+    }
+
     fn handle_cmd(&self, desp: &mut HTIFDesp) {
         if desp.tohost_device() == 0 && desp.tohost_cmd() == 0 {
             if desp.tohost & 0x1 == 1 {
                 EXIT_CTRL.exit("htif shutdown!").unwrap();
             }
         } else if desp.tohost_device() == 1 && desp.tohost_cmd() == 1 {
-            let mut data = [0u8; 1];
-            data[0] = desp.tohost as u8;
-            let stdout = TERM.stdout();
-            let mut handle = stdout.lock();
-            handle.write(&data).unwrap();
-            handle.flush().unwrap();
+            if !self.replay.as_ref().map_or(false, |r| r.muted()) {
+                let mut data = [0u8; 1];
+                data[0] = desp.tohost as u8;
+                let stdout = TERM.stdout();
+                let mut handle = stdout.lock();
+                handle.write(&data).unwrap();
+                handle.flush().unwrap();
+            }
             desp.tohost = 0;
         } else if desp.tohost_device() == 1 && desp.tohost_cmd() == 0 {
             desp.tohost = 0;
@@ -64,15 +98,23 @@ impl HTIF {
 
     fn fromhost_poll(&self, desp: &mut HTIFDesp) {
         if desp.fromhost == 0 && self.input_en {
-            let mut data = [0u8; 1];
-            match TERM.stdin().lock().read_exact(&mut data) {
-                Ok(_) => {
-                    desp.fromhost.set_bit_range(8, 8, 1);
-                    desp.fromhost.set_bit_range(7, 0, data[0]);
-                    desp.fromhost.set_bit_range(63, 48, 0x0100);
+            let read_stdin = || {
+                let mut data = [0u8; 1];
+                match TERM.stdin().lock().read_exact(&mut data) {
+                    Ok(_) => Some(data[0]),
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => None,
+                    Err(e) => panic!("{:?}", e),
                 }
-                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
-                Err(e) => panic!("{:?}", e),
+            };
+            let data = if let Some(ref replay) = self.replay {
+                replay.input(InputSrc::Htif, read_stdin)
+            } else {
+                read_stdin()
+            };
+            if let Some(data) = data {
+                desp.fromhost.set_bit_range(8, 8, 1);
+                desp.fromhost.set_bit_range(7, 0, data);
+                desp.fromhost.set_bit_range(63, 48, 0x0100);
             }
         }
     }
@@ -0,0 +1,386 @@
+use crate::prelude::{RegT, XLen};
+use crate::snapshot::{SnapshotReader, SnapshotWriter};
+use std::cell::{Ref, RefCell, RefMut};
+use std::io;
+use std::io::{Read, Write};
+use std::rc::Rc;
+use terminus_spaceport::memory::prelude::*;
+
+//interrupt file of M level and S level, guest interrupt files are not supported
+pub const IMSIC_M: usize = 0;
+pub const IMSIC_S: usize = 1;
+pub const IMSIC_FILE_SIZE: u64 = 0x1000;
+pub const IMSIC_NUM_IDS: usize = 255;
+
+const SETEIPNUM_LE: u64 = 0x0;
+const SETEIPNUM_BE: u64 = 0x4;
+
+//indirect registers selected by miselect and siselect
+const EIDELIVERY: RegT = 0x70;
+const EITHRESHOLD: RegT = 0x72;
+const EIP0: RegT = 0x80;
+const EIP63: RegT = 0xbf;
+const EIE0: RegT = 0xc0;
+const EIE63: RegT = 0xff;
+
+struct InterruptFile {
+    eidelivery: u32,
+    eithreshold: u32,
+    eip: Vec<u32>,
+    eie: Vec<u32>,
+}
+
+impl InterruptFile {
+    fn new(num_ids: usize) -> InterruptFile {
+        InterruptFile {
+            eidelivery: 0,
+            eithreshold: 0,
+            eip: vec![0; (num_ids >> 5) + 1],
+            eie: vec![0; (num_ids >> 5) + 1],
+        }
+    }
+
+    //lowest identity pending and enabled under threshold
+    fn topei(&self) -> u32 {
+        if self.eidelivery != 1 {
+            return 0;
+        }
+        for (i, (eip, eie)) in self.eip.iter().zip(self.eie.iter()).enumerate() {
+            let pending = eip & eie;
+            if pending != 0 {
+                let id = ((i as u32) << 5) + pending.trailing_zeros();
+                return if self.eithreshold == 0 || id < self.eithreshold {
+                    id
+                } else {
+                    0
+                };
+            }
+        }
+        0
+    }
+
+    fn set_eip(&mut self, id: u32, pending: bool) {
+        if let Some(eip) = self.eip.get_mut((id >> 5) as usize) {
+            if pending {
+                *eip |= 1 << (id & 0x1f)
+            } else {
+                *eip &= !(1 << (id & 0x1f))
+            }
+        }
+        //identity 0 is never valid
+        self.eip[0] &= !1;
+    }
+}
+
+struct ImsicInner {
+    num_ids: usize,
+    files: Vec<[InterruptFile; 2]>,
+}
+
+//interrupt files of all harts, written by msi and accessed by mireg, mtopei and S level counterparts
+pub struct Imsic(RefCell<ImsicInner>);
+
+impl Imsic {
+    pub fn new(num_ids: usize) -> Imsic {
+        assert!(
+            num_ids & 0x3f == 0x3f && num_ids < 2048,
+            "imsic: number of identities must be 64 * n - 1!"
+        );
+        Imsic(RefCell::new(ImsicInner {
+            num_ids,
+            files: vec![],
+        }))
+    }
+
+    pub fn num_ids(&self) -> usize {
+        self.inner().num_ids
+    }
+
+    pub fn num_harts(&self) -> usize {
+        self.inner().files.len()
+    }
+
+    //msi of a file, the identity is the data written
+    fn send(&self, hart: usize, level: usize, id: u32) {
+        let mut inner = self.inner_mut();
+        if id as usize <= inner.num_ids {
+            if let Some(files) = inner.files.get_mut(hart) {
+                files[level].set_eip(id, true)
+            }
+        }
+    }
+
+    pub fn save_snapshot<W: Write>(&self, w: &mut SnapshotWriter<W>) -> io::Result<()> {
+        let inner = self.inner();
+        w.write_u64(inner.files.len() as u64)?;
+        for file in inner.files.iter().flat_map(|f| f.iter()) {
+            w.write_u32(file.eidelivery)?;
+            w.write_u32(file.eithreshold)?;
+            for (eip, eie) in file.eip.iter().zip(file.eie.iter()) {
+                w.write_u32(*eip)?;
+                w.write_u32(*eie)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn restore_snapshot<R: Read>(&self, r: &mut SnapshotReader<R>) -> io::Result<()> {
+        let mut inner = self.inner_mut();
+        r.expect_u64(inner.files.len() as u64, "imsic harts")?;
+        for file in inner.files.iter_mut().flat_map(|f| f.iter_mut()) {
+            file.eidelivery = r.read_u32()?;
+            file.eithreshold = r.read_u32()?;
+            for i in 0..file.eip.len() {
+                file.eip[i] = r.read_u32()?;
+                file.eie[i] = r.read_u32()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn inner(&self) -> Ref<'_, ImsicInner> {
+        self.0.borrow()
+    }
+
+    fn inner_mut(&self) -> RefMut<'_, ImsicInner> {
+        self.0.borrow_mut()
+    }
+}
+
+//interrupt files of a hart
+#[derive(Clone)]
+pub struct HartImsic {
+    imsic: Rc<Imsic>,
+    id: usize,
+}
+
+impl HartImsic {
+    pub fn alloc(imsic: &Rc<Imsic>) -> HartImsic {
+        let mut inner = imsic.inner_mut();
+        let num_ids = inner.num_ids;
+        inner
+            .files
+            .push([InterruptFile::new(num_ids), InterruptFile::new(num_ids)]);
+        HartImsic {
+            imsic: imsic.clone(),
+            id: inner.files.len() - 1,
+        }
+    }
+
+    pub fn pending(&self, level: usize) -> bool {
+        self.topei(level) != 0
+    }
+
+    //value of mtopei and stopei
+    pub fn topei(&self, level: usize) -> RegT {
+        let id = self.imsic.inner().files[self.id][level].topei() as RegT;
+        id << 16 | id
+    }
+
+    pub fn claim(&self, level: usize) {
+        let mut inner = self.imsic.inner_mut();
+        let file = &mut inner.files[self.id][level];
+        let id = file.topei();
+        file.set_eip(id, false)
+    }
+
+    //eipX and eieX are 64 bits in rv64, odd X does not exist
+    pub fn ireg(&self, level: usize, select: RegT, xlen: XLen) -> Option<RegT> {
+        let inner = self.imsic.inner();
+        let file = &inner.files[self.id][level];
+        let word = |regs: &Vec<u32>, idx: usize| *regs.get(idx).unwrap_or(&0) as RegT;
+        let array = |regs: &Vec<u32>, idx: usize| {
+            if xlen == XLen::X64 {
+                if idx & 1 != 0 {
+                    None
+                } else {
+                    Some(word(regs, idx) | word(regs, idx + 1) << 32)
+                }
+            } else {
+                Some(word(regs, idx))
+            }
+        };
+        match select {
+            EIDELIVERY => Some(file.eidelivery as RegT),
+            EITHRESHOLD => Some(file.eithreshold as RegT),
+            EIP0..=EIP63 => array(&file.eip, (select - EIP0) as usize),
+            EIE0..=EIE63 => array(&file.eie, (select - EIE0) as usize),
+            _ => None,
+        }
+    }
+
+    pub fn set_ireg(&self, level: usize, select: RegT, value: RegT, xlen: XLen) -> Option<()> {
+        let mut inner = self.imsic.inner_mut();
+        let num_ids = inner.num_ids as RegT;
+        let file = &mut inner.files[self.id][level];
+        let array = |regs: &mut Vec<u32>, idx: usize| {
+            if xlen == XLen::X64 {
+                if idx & 1 != 0 {
+                    return None;
+                }
+                if let Some(w) = regs.get_mut(idx + 1) {
+                    *w = (value >> 32) as u32
+                }
+            }
+            if let Some(w) = regs.get_mut(idx) {
+                *w = value as u32
+            }
+            regs[0] &= !1;
+            Some(())
+        };
+        match select {
+            //delivery from aplic is not supported
+            EIDELIVERY => {
+                file.eidelivery = (value & 1) as u32;
+                Some(())
+            }
+            EITHRESHOLD => {
+                if value <= num_ids {
+                    file.eithreshold = value as u32
+                }
+                Some(())
+            }
+            EIP0..=EIP63 => array(&mut file.eip, (select - EIP0) as usize),
+            EIE0..=EIE63 => array(&mut file.eie, (select - EIE0) as usize),
+            _ => None,
+        }
+    }
+}
+
+//memory mapped interrupt files of one level, a page per hart
+#[derive_io(Bytes, U32, U64)]
+pub struct ImsicFiles(Rc<Imsic>, usize);
+
+impl ImsicFiles {
+    pub fn new(imsic: &Rc<Imsic>, level: usize) -> ImsicFiles {
+        ImsicFiles(imsic.clone(), level)
+    }
+
+    pub fn size(&self) -> u64 {
+        self.0.num_harts() as u64 * IMSIC_FILE_SIZE
+    }
+}
+
+impl BytesAccess for ImsicFiles {
+    fn write(&self, addr: &u64, data: &[u8]) -> std::result::Result<usize, String> {
+        if data.len() == 4 {
+            let mut bytes = [0; 4];
+            bytes.copy_from_slice(data);
+            U32Access::write(self, addr, u32::from_le_bytes(bytes))
+        } else if data.len() == 8 {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(data);
+            U64Access::write(self, addr, u64::from_le_bytes(bytes))
+        }
+        Ok(0)
+    }
+
+    fn read(&self, addr: &u64, data: &mut [u8]) -> std::result::Result<usize, String> {
+        if data.len() == 4 {
+            data.copy_from_slice(&U32Access::read(self, addr).to_le_bytes())
+        } else if data.len() == 8 {
+            data.copy_from_slice(&U64Access::read(self, addr).to_le_bytes())
+        }
+        Ok(0)
+    }
+}
+
+impl U32Access for ImsicFiles {
+    fn write(&self, addr: &u64, data: u32) {
+        assert!(
+            (*addr).trailing_zeros() > 1,
+            "U32Access:unaligned addr:{:#x}", addr
+        );
+        let hart = (*addr / IMSIC_FILE_SIZE) as usize;
+        match *addr % IMSIC_FILE_SIZE {
+            SETEIPNUM_LE => self.0.send(hart, self.1, data),
+            SETEIPNUM_BE => self.0.send(hart, self.1, data.swap_bytes()),
+            _ => {}
+        }
+    }
+
+    //seteipnum registers read as zero
+    fn read(&self, addr: &u64) -> u32 {
+        assert!(
+            (*addr).trailing_zeros() > 1,
+            "U32Access:unaligned addr:{:#x}", addr
+        );
+        0
+    }
+}
+
+impl U64Access for ImsicFiles {
+    fn write(&self, addr: &u64, data: u64) {
+        assert!(
+            (*addr).trailing_zeros() > 2,
+            "U64Access:unaligned addr:{:#x}", addr
+        );
+        U32Access::write(self, addr, data as u32);
+        U32Access::write(self, &(*addr + 4), (data >> 32) as u32);
+    }
+
+    fn read(&self, addr: &u64) -> u64 {
+        assert!(
+            (*addr).trailing_zeros() > 2,
+            "U64Access:unaligned addr:{:#x}", addr
+        );
+        U32Access::read(self, addr) as u64 | ((U32Access::read(self, &(*addr + 4)) as u64) << 32)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn imsic_topei_claim_test() {
+        let imsic = Rc::new(Imsic::new(63));
+        let harts = vec![HartImsic::alloc(&imsic), HartImsic::alloc(&imsic)];
+        let files = ImsicFiles::new(&imsic, IMSIC_S);
+        assert_eq!(files.size(), 2 * IMSIC_FILE_SIZE);
+        let eip = |hart: usize, level: usize| harts[hart].ireg(level, EIP0, XLen::X64).unwrap();
+
+        //identity 0 and identities beyond num_ids are ignored
+        U32Access::write(&files, &(IMSIC_FILE_SIZE + SETEIPNUM_LE), 40);
+        U32Access::write(&files, &(IMSIC_FILE_SIZE + SETEIPNUM_BE), 3u32.swap_bytes());
+        U32Access::write(&files, &(IMSIC_FILE_SIZE + SETEIPNUM_LE), 0);
+        U32Access::write(&files, &(IMSIC_FILE_SIZE + SETEIPNUM_LE), 64);
+        assert_eq!(U32Access::read(&files, &IMSIC_FILE_SIZE), 0);
+        assert_eq!(eip(1, IMSIC_S), 1 << 40 | 1 << 3);
+        assert_eq!(eip(1, IMSIC_M) | eip(0, IMSIC_S) | eip(0, IMSIC_M), 0);
+        //odd eipX does not exist in rv64
+        assert_eq!(harts[1].ireg(IMSIC_S, EIP0 + 1, XLen::X64), None);
+        assert_eq!(harts[1].ireg(IMSIC_S, EIP0 + 1, XLen::X32), Some(1 << 8));
+
+        //delivered only when enabled
+        assert_eq!(harts[1].topei(IMSIC_S), 0);
+        harts[1].set_ireg(IMSIC_S, EIDELIVERY, 1, XLen::X64);
+        assert_eq!(harts[1].topei(IMSIC_S), 0);
+        harts[1].set_ireg(IMSIC_S, EIE0, !0, XLen::X64);
+        assert_eq!(harts[1].ireg(IMSIC_S, EIE0, XLen::X64), Some(!1));
+        assert_eq!(harts[1].topei(IMSIC_S), 3 << 16 | 3);
+        assert!(harts[1].pending(IMSIC_S) && !harts[1].pending(IMSIC_M));
+
+        //only identities below a nonzero threshold are delivered
+        harts[1].set_ireg(IMSIC_S, EITHRESHOLD, 3, XLen::X64);
+        assert_eq!(harts[1].topei(IMSIC_S), 0);
+        harts[1].set_ireg(IMSIC_S, EITHRESHOLD, 41, XLen::X64);
+        harts[1].set_ireg(IMSIC_S, EITHRESHOLD, 64, XLen::X64);
+        assert_eq!(harts[1].ireg(IMSIC_S, EITHRESHOLD, XLen::X64), Some(41));
+        assert_eq!(harts[1].topei(IMSIC_S), 3 << 16 | 3);
+
+        //claims clear the lowest identity
+        harts[1].claim(IMSIC_S);
+        assert_eq!(harts[1].topei(IMSIC_S), 40 << 16 | 40);
+        harts[1].claim(IMSIC_S);
+        assert_eq!(harts[1].topei(IMSIC_S), 0);
+        assert_eq!(eip(1, IMSIC_S), 0);
+        harts[1].claim(IMSIC_S);
+        assert_eq!(eip(1, IMSIC_S), 0);
+
+        harts[1].set_ireg(IMSIC_S, EIP0, 1 << 7, XLen::X64);
+        assert_eq!(harts[1].topei(IMSIC_S), 7 << 16 | 7);
+        harts[1].set_ireg(IMSIC_S, EIDELIVERY, 0, XLen::X64);
+        assert_eq!(harts[1].topei(IMSIC_S), 0);
+    }
+}
@@ -1,5 +1,8 @@
+pub mod aplic;
 pub mod bus;
 pub mod clint;
 pub mod htif;
+pub mod imsic;
 pub mod plic;
+pub mod virtio_mmio;
 pub use terminus_spaceport::devices::armory::*;
@@ -1,52 +1,85 @@
+use crate::snapshot::{SnapshotReader, SnapshotWriter};
 use std::cell::{Ref, RefCell};
+use std::io;
+use std::io::{Read, Write};
 use std::rc::Rc;
 use terminus_spaceport::irq::{IrqVec, IrqVecSender};
 use terminus_spaceport::memory::prelude::*;
 
+//M level context and S level context of a hart
+struct Context {
+    threshold: u32,
+    enables: Vec<u32>,
+}
+
+impl Context {
+    fn enabled(&self, id: usize) -> bool {
+        (self.enables[id >> 5] >> (id as u32 & 0x1f)) & 0x1 == 0x1
+    }
+}
+
 struct IntHarts {
-    irq_vecs: Vec<IrqVecSender>,
-    priority_threshold: Vec<u32>,
+    //meip and seip
+    irq_vecs: Vec<(IrqVecSender, IrqVecSender)>,
+    //context 2 * hart is M level and context 2 * hart + 1 is S level
+    contexts: Vec<Context>,
     priority: Vec<u32>,
-    enables: Vec<Vec<u32>>,
+    //gateway does not forward a claimed source until completion
+    claimed: Vec<bool>,
 }
 
 impl IntHarts {
     fn new(max_len: usize) -> IntHarts {
         IntHarts {
             irq_vecs: vec![],
-            priority_threshold: vec![],
+            contexts: vec![],
             priority: vec![0; max_len],
-            enables: vec![],
+            claimed: vec![false; max_len],
         }
     }
+    //line 0 is meip driven by M context and line 1 is seip driven by S context
     fn alloc_irq(&mut self) -> IrqVec {
-        let irq_vec = IrqVec::new(1);
+        let irq_vec = IrqVec::new(2);
         let len = self.priority.len();
         irq_vec.set_enable_uncheck(0, true);
-        self.irq_vecs.push(irq_vec.sender(0).unwrap());
-        self.priority_threshold.push(0);
-        self.enables.push(vec![0; (len + 31) >> 5]);
+        irq_vec.set_enable_uncheck(1, true);
+        self.irq_vecs
+            .push((irq_vec.sender(0).unwrap(), irq_vec.sender(1).unwrap()));
+        for _ in 0..2 {
+            self.contexts.push(Context {
+                threshold: 0,
+                enables: vec![0; (len + 31) >> 5],
+            });
+        }
         irq_vec
     }
 
-    fn update_meip(&self, id: usize) {
-        for (vec, (ths, enables)) in self
-            .irq_vecs
-            .iter()
-            .zip(self.priority_threshold.iter().zip(self.enables.iter()))
-        {
-            if unsafe {
-                (*enables.get_unchecked(id >> 5) >> (id as u32 & 0x1f)) & 0x1 == 0x1
-                    && *self.priority.get_unchecked(id) > *ths
-            } {
-                vec.send().unwrap();
+    fn line(&self, ctx: usize) -> &IrqVecSender {
+        let vec = &self.irq_vecs[ctx >> 1];
+        if ctx & 1 == 0 {
+            &vec.0
+        } else {
+            &vec.1
+        }
+    }
+
+    fn forwarded(&self, ctx: usize, id: usize) -> bool {
+        let context = &self.contexts[ctx];
+        !self.claimed[id] && context.enabled(id) && self.priority[id] > context.threshold
+    }
+
+    fn update_eip(&self, id: usize) {
+        for ctx in 0..self.contexts.len() {
+            if self.forwarded(ctx, id) {
+                self.line(ctx).send().unwrap();
             }
         }
     }
 
-    fn clear_all_meip(&self) {
+    fn clear_all_eip(&self) {
         for vec in self.irq_vecs.iter() {
-            vec.clear().unwrap();
+            vec.0.clear().unwrap();
+            vec.1.clear().unwrap();
         }
     }
 }
@@ -55,6 +88,8 @@ struct IntcInner {
     harts: Rc<RefCell<IntHarts>>,
     irq_src: IrqVec,
     num_src: usize,
+    //other controllers the sources are routed to, e.g. aplic
+    routes: Rc<RefCell<Vec<Box<dyn Fn(usize)>>>>,
 }
 
 impl IntcInner {
@@ -63,6 +98,7 @@ impl IntcInner {
             harts: Rc::new(RefCell::new(IntHarts::new(max_len))),
             irq_src: IrqVec::new(max_len),
             num_src: 1,
+            routes: Rc::new(RefCell::new(vec![])),
         }
     }
 
@@ -78,18 +114,24 @@ impl IntcInner {
             .binder()
             .bind(id, {
                 let harts = self.harts.clone();
-                move || (*harts).borrow().update_meip(id)
+                let routes = self.routes.clone();
+                move || {
+                    (*harts).borrow().update_eip(id);
+                    for route in (*routes).borrow().iter() {
+                        route(id)
+                    }
+                }
             })
             .unwrap();
         self.irq_src.sender(id).unwrap()
     }
 
-    fn update_all_meip(&self) {
+    fn update_all_eip(&self) {
         let harts = (*self.harts).borrow();
-        harts.clear_all_meip();
+        harts.clear_all_eip();
         for i in 1..self.num_src {
             if self.irq_src.pending_uncheck(i) {
-                harts.update_meip(i)
+                harts.update_eip(i)
             }
         }
     }
@@ -98,32 +140,58 @@ impl IntcInner {
         (self.num_src + 31) >> 5
     }
 
+    fn num_contexts(&self) -> usize {
+        (*self.harts).borrow().contexts.len()
+    }
+
     fn pending(&self, offset: u64) -> u32 {
         let mut res: u32 = 0;
         let start = offset << 3;
         for i in start..start + 32 {
-            res |= self.irq_src.pending_uncheck(i as usize) as u32
+            res |= (self.irq_src.pending_uncheck(i as usize) as u32) << (i - start)
         }
         res
     }
 
-    fn pick_claim(&self) -> u32 {
+    //highest priority source forwarded to the context, the lowest id wins a tie
+    fn pick_claim(&self, ctx: usize) -> u32 {
         let mut max_pri: u32 = 0;
         let mut idx: u32 = 0;
         let harts = (*self.harts).borrow();
         for i in 1..self.num_src {
-            if self.irq_src.pending_uncheck(i) {
-                let pri = unsafe { harts.priority.get_unchecked(i) };
-                if *pri == 0x7 {
+            if self.irq_src.pending_uncheck(i) && harts.forwarded(ctx, i) {
+                let pri = harts.priority[i];
+                if pri == 0x7 {
                     return i as u32;
-                } else if *pri > max_pri {
-                    max_pri = *pri;
+                } else if pri > max_pri {
+                    max_pri = pri;
                     idx = i as u32
                 }
             }
         }
         idx
     }
+
+    fn claim(&self, ctx: usize) -> u32 {
+        let claim = self.pick_claim(ctx);
+        if claim != 0 {
+            self.irq_src.set_pending_uncheck(claim as usize, false);
+            (*self.harts).borrow_mut().claimed[claim as usize] = true;
+        }
+        self.update_all_eip();
+        claim
+    }
+
+    //completion of a source not enabled in the context is ignored
+    fn complete(&self, ctx: usize, id: usize) {
+        {
+            let mut harts = (*self.harts).borrow_mut();
+            if id < self.num_src && harts.contexts[ctx].enabled(id) {
+                harts.claimed[id] = false;
+            }
+        }
+        self.update_all_eip();
+    }
 }
 
 pub struct Intc(RefCell<IntcInner>);
@@ -145,6 +213,74 @@ impl Intc {
         self.0.borrow().num_src
     }
 
+    pub fn add_route<F: Fn(usize) + 'static>(&self, route: F) {
+        (*self.inner().routes).borrow_mut().push(Box::new(route))
+    }
+
+    //pending states of sources are shared by all controllers
+    pub fn pending(&self, id: usize) -> bool {
+        id != 0 && id < self.num_src() && self.inner().irq_src.pending_uncheck(id)
+    }
+
+    pub fn set_pending(&self, id: usize, pending: bool) {
+        if id != 0 && id < self.num_src() {
+            self.inner().irq_src.set_pending_uncheck(id, pending)
+        }
+    }
+
+    pub fn save_snapshot<W: Write>(&self, w: &mut SnapshotWriter<W>) -> io::Result<()> {
+        let inner = self.inner();
+        let harts = (*inner.harts).borrow();
+        w.write_u64(harts.priority.len() as u64)?;
+        for (i, (p, claimed)) in harts.priority.iter().zip(harts.claimed.iter()).enumerate() {
+            w.write_u32(*p)?;
+            w.write_bool(*claimed)?;
+            w.write_bool(inner.irq_src.pending_uncheck(i))?;
+        }
+        w.write_u64(harts.contexts.len() as u64)?;
+        for ctx in harts.contexts.iter() {
+            w.write_u32(ctx.threshold)?;
+            for e in ctx.enables.iter() {
+                w.write_u32(*e)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn restore_snapshot<R: Read>(&self, r: &mut SnapshotReader<R>) -> io::Result<()> {
+        let inner = self.inner();
+        let mut pendings = vec![];
+        {
+            let mut harts = (*inner.harts).borrow_mut();
+            r.expect_u64(harts.priority.len() as u64, "intc sources")?;
+            let IntHarts {
+                ref mut priority,
+                ref mut claimed,
+                ref mut contexts,
+                ..
+            } = *harts;
+            for (p, c) in priority.iter_mut().zip(claimed.iter_mut()) {
+                *p = r.read_u32()?;
+                *c = r.read_bool()?;
+                pendings.push(r.read_bool()?);
+            }
+            r.expect_u64(contexts.len() as u64, "intc contexts")?;
+            for ctx in contexts.iter_mut() {
+                ctx.threshold = r.read_u32()?;
+                for e in ctx.enables.iter_mut() {
+                    *e = r.read_u32()?;
+                }
+            }
+        }
+        for (i, pending) in pendings.into_iter().enumerate().skip(1) {
+            if i < inner.num_src {
+                inner.irq_src.set_pending_uncheck(i, pending);
+            }
+        }
+        inner.update_all_eip();
+        Ok(())
+    }
+
     fn inner(&self) -> Ref<'_, IntcInner> {
         self.0.borrow()
     }
@@ -153,9 +289,9 @@ impl Intc {
 const PLIC_PRI_BASE: u64 = 0x0;
 const PLIC_PENDING_BASE: u64 = 0x1000;
 const PLIC_ENABLE_BASE: u64 = 0x2000;
-// const PLIC_ENABLE_SIZE:u64 = 0x80;
-const PLIC_HART_BASE: u64 = 0x200000;
-// const PLIC_HART_SIZE:u64 = 0x1000;
+const PLIC_ENABLE_SIZE: u64 = 0x80;
+const PLIC_CONTEXT_BASE: u64 = 0x200000;
+const PLIC_CONTEXT_SIZE: u64 = 0x1000;
 
 #[derive_io(Bytes, U32, U64)]
 pub struct Plic(Rc<Intc>);
@@ -190,6 +326,36 @@ impl BytesAccess for Plic {
     }
 }
 
+impl Plic {
+    //context and word offset of enables
+    fn enable_offset(&self, addr: u64) -> Option<(usize, usize)> {
+        let inner = self.0.inner();
+        if addr < PLIC_ENABLE_BASE || addr >= PLIC_CONTEXT_BASE {
+            return None;
+        }
+        let ctx = ((addr - PLIC_ENABLE_BASE) / PLIC_ENABLE_SIZE) as usize;
+        let offset = (((addr - PLIC_ENABLE_BASE) % PLIC_ENABLE_SIZE) >> 2) as usize;
+        if ctx < inner.num_contexts() && offset < inner.enable_per_hart() {
+            Some((ctx, offset))
+        } else {
+            None
+        }
+    }
+
+    //context and register offset of threshold and claim/complete
+    fn context_offset(&self, addr: u64) -> Option<(usize, u64)> {
+        if addr < PLIC_CONTEXT_BASE {
+            return None;
+        }
+        let ctx = ((addr - PLIC_CONTEXT_BASE) / PLIC_CONTEXT_SIZE) as usize;
+        if ctx < self.0.inner().num_contexts() {
+            Some((ctx, (addr - PLIC_CONTEXT_BASE) % PLIC_CONTEXT_SIZE))
+        } else {
+            None
+        }
+    }
+}
+
 impl U32Access for Plic {
     fn write(&self, addr: &u64, data: u32) {
         assert!(
@@ -200,32 +366,20 @@ impl U32Access for Plic {
         if *addr >= PLIC_PRI_BASE && *addr + 4 <= PLIC_PRI_BASE + ((inner.num_src as u64) << 2) {
             let offset = ((*addr - PLIC_PRI_BASE) >> 2) as usize;
             inner.harts.borrow_mut().priority[offset] = data & 0x7;
-            return;
-        } else if *addr >= PLIC_ENABLE_BASE
-            && *addr + 4
-                <= PLIC_ENABLE_BASE
-                    + (((inner.num_src + 31) as u64) >> 3)
-                        * (inner.harts.borrow().irq_vecs.len() as u64)
-        {
-            let offset = ((*addr - PLIC_ENABLE_BASE) >> 2) as usize;
-            let enable_per_hart = inner.enable_per_hart();
-            let hart_offset = offset / enable_per_hart;
-            let en_offset = offset % enable_per_hart;
-            inner.harts.borrow_mut().enables[hart_offset][en_offset] = data;
-            return;
-        } else if *addr >= PLIC_HART_BASE
-            && *addr + 4 <= PLIC_HART_BASE + ((inner.harts.borrow().irq_vecs.len() as u64) << 3)
-        {
-            if (*addr).trailing_zeros() == 2 {
-                inner.update_all_meip()
-            } else {
-                let offset = ((*addr - PLIC_HART_BASE) >> 3) as usize;
-                inner.harts.borrow_mut().priority_threshold[offset] = data & 0x7;
+            inner.update_all_eip();
+        } else if let Some((ctx, offset)) = self.enable_offset(*addr) {
+            inner.harts.borrow_mut().contexts[ctx].enables[offset] = data;
+            inner.update_all_eip();
+        } else if let Some((ctx, offset)) = self.context_offset(*addr) {
+            match offset {
+                0 => {
+                    inner.harts.borrow_mut().contexts[ctx].threshold = data & 0x7;
+                    inner.update_all_eip();
+                }
+                4 => inner.complete(ctx, data as usize),
+                _ => {}
             }
-            return;
         }
-
-        // panic!(format!("plic:U32Access Invalid addr {:#x}!", *addr));
     }
 
     fn read(&self, addr: &u64) -> u32 {
@@ -236,38 +390,22 @@ impl U32Access for Plic {
         let inner = self.0.inner();
         if *addr >= PLIC_PRI_BASE && *addr + 4 <= PLIC_PRI_BASE + ((inner.num_src as u64) << 2) {
             let offset = ((*addr - PLIC_PRI_BASE) >> 2) as usize;
-            return inner.harts.borrow().priority[offset];
+            inner.harts.borrow().priority[offset]
         } else if *addr >= PLIC_PENDING_BASE
             && *addr + 4 <= PLIC_PENDING_BASE + (((inner.num_src + 31) as u64) >> 3)
         {
-            let offset = *addr - PLIC_PENDING_BASE;
-            return inner.pending(offset);
-        } else if *addr >= PLIC_ENABLE_BASE
-            && *addr + 4
-                <= PLIC_ENABLE_BASE
-                    + (((inner.num_src + 31) as u64) >> 3)
-                        * (inner.harts.borrow().irq_vecs.len() as u64)
-        {
-            let offset = ((*addr - PLIC_ENABLE_BASE) >> 2) as usize;
-            let enable_per_hart = inner.enable_per_hart();
-            let hart_offset = offset / enable_per_hart;
-            let en_offset = offset % enable_per_hart;
-            return inner.harts.borrow().enables[hart_offset][en_offset];
-        } else if *addr >= PLIC_HART_BASE
-            && *addr + 4 <= PLIC_HART_BASE + ((inner.harts.borrow().irq_vecs.len() as u64) << 3)
-        {
-            if (*addr).trailing_zeros() == 2 {
-                let claim = inner.pick_claim();
-                inner.irq_src.set_pending_uncheck(claim as usize, false);
-                inner.update_all_meip();
-                return claim;
-            } else {
-                let offset = ((*addr - PLIC_HART_BASE) >> 3) as usize;
-                return inner.harts.borrow().priority_threshold[offset];
+            inner.pending(*addr - PLIC_PENDING_BASE)
+        } else if let Some((ctx, offset)) = self.enable_offset(*addr) {
+            inner.harts.borrow().contexts[ctx].enables[offset]
+        } else if let Some((ctx, offset)) = self.context_offset(*addr) {
+            match offset {
+                0 => inner.harts.borrow().contexts[ctx].threshold,
+                4 => inner.claim(ctx),
+                _ => 0,
             }
+        } else {
+            0
         }
-        0
-        // panic!(format!("plic:U32Access Invalid addr {:#x}!", *addr));
     }
 }
 
@@ -289,3 +427,105 @@ impl U64Access for Plic {
         U32Access::read(self, addr) as u64 | ((U32Access::read(self, &(*addr + 4)) as u64) << 32)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const M: usize = 0;
+    const S: usize = 1;
+
+    fn enable(hart: usize, level: usize) -> u64 {
+        PLIC_ENABLE_BASE + ((hart * 2 + level) as u64) * PLIC_ENABLE_SIZE
+    }
+
+    fn threshold(hart: usize, level: usize) -> u64 {
+        PLIC_CONTEXT_BASE + ((hart * 2 + level) as u64) * PLIC_CONTEXT_SIZE
+    }
+
+    fn claim(hart: usize, level: usize) -> u64 {
+        threshold(hart, level) + 4
+    }
+
+    #[test]
+    fn plic_claim_complete_test() {
+        let intc = Rc::new(Intc::new(32));
+        let irqs = vec![intc.alloc_irq(), intc.alloc_irq()];
+        let src1 = intc.alloc_src(1);
+        let src2 = intc.alloc_src(2);
+        let plic = Plic::new(&intc);
+        let eip = |hart: usize, level: usize| irqs[hart].listener(level).unwrap().pending_uncheck();
+
+        U32Access::write(&plic, &(PLIC_PRI_BASE + 4), 1);
+        U32Access::write(&plic, &(PLIC_PRI_BASE + 8), 2);
+        //both sources are enabled in S contexts of both harts, source 2 is also in M context of hart 0
+        U32Access::write(&plic, &enable(0, S), 0x6);
+        U32Access::write(&plic, &enable(1, S), 0x6);
+        U32Access::write(&plic, &enable(0, M), 0x4);
+        assert_eq!(U32Access::read(&plic, &enable(0, M)), 0x4);
+
+        src1.send().unwrap();
+        assert!(eip(0, S) && eip(1, S) && !eip(0, M) && !eip(1, M));
+        src2.send().unwrap();
+        assert!(eip(0, M));
+        assert_eq!(U32Access::read(&plic, &PLIC_PENDING_BASE), 0x6);
+
+        //only the first claim of a source gets it
+        assert_eq!(U32Access::read(&plic, &claim(1, S)), 2);
+        assert_eq!(U32Access::read(&plic, &claim(0, M)), 0);
+        assert!(!eip(0, M) && eip(0, S) && eip(1, S));
+        assert_eq!(U32Access::read(&plic, &claim(0, S)), 1);
+        assert_eq!(U32Access::read(&plic, &claim(1, S)), 0);
+        assert!(!eip(0, S) && !eip(1, S));
+
+        //a claimed source is not forwarded again until completion
+        src2.send().unwrap();
+        assert!(!eip(0, M) && !eip(0, S) && !eip(1, S));
+        //completion from a context the source is not enabled in is ignored
+        U32Access::write(&plic, &claim(1, M), 2);
+        assert!(!eip(0, M));
+        U32Access::write(&plic, &claim(1, S), 2);
+        assert!(eip(0, M) && eip(0, S) && eip(1, S));
+
+        //threshold of a context does not affect others
+        U32Access::write(&plic, &threshold(1, S), 2);
+        assert!(eip(0, M) && eip(0, S) && !eip(1, S));
+        assert_eq!(U32Access::read(&plic, &claim(1, S)), 0);
+        assert_eq!(U32Access::read(&plic, &claim(0, M)), 2);
+        assert!(!eip(0, M) && !eip(0, S) && !eip(1, S));
+        U32Access::write(&plic, &claim(0, M), 2);
+        U32Access::write(&plic, &claim(0, S), 1);
+        assert_eq!(U32Access::read(&plic, &PLIC_PENDING_BASE), 0);
+        assert!(!eip(0, M) && !eip(0, S) && !eip(1, S));
+    }
+
+    #[test]
+    fn plic_snapshot_test() {
+        let intc = Rc::new(Intc::new(32));
+        let irq = intc.alloc_irq();
+        let src1 = intc.alloc_src(1);
+        let _src2 = intc.alloc_src(2);
+        let plic = Plic::new(&intc);
+        U32Access::write(&plic, &(PLIC_PRI_BASE + 4), 1);
+        U32Access::write(&plic, &(PLIC_PRI_BASE + 8), 1);
+        U32Access::write(&plic, &enable(0, S), 0x6);
+        src1.send().unwrap();
+        let mut snapshot = vec![];
+        intc.save_snapshot(&mut SnapshotWriter::new(&mut snapshot).unwrap())
+            .unwrap();
+
+        //pending source not claimed yet is restored with enables and priorities
+        let restored = Rc::new(Intc::new(32));
+        let restored_irq = restored.alloc_irq();
+        let _ = restored.alloc_src(1);
+        let _ = restored.alloc_src(2);
+        restored
+            .restore_snapshot(&mut SnapshotReader::new(&snapshot[..]).unwrap())
+            .unwrap();
+        let restored_plic = Plic::new(&restored);
+        assert!(restored.pending(1) && !restored.pending(2));
+        assert!(restored_irq.listener(S).unwrap().pending_uncheck());
+        assert_eq!(U32Access::read(&restored_plic, &claim(0, S)), 1);
+        assert!(!irq.listener(M).unwrap().pending_uncheck());
+    }
+}
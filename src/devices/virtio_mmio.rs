@@ -0,0 +1,904 @@
+use crate::devices::plic::Intc;
+use crate::replay::{InputSrc, Replay};
+use crate::snapshot::{SnapshotReader, SnapshotWriter};
+use std::cell::RefCell;
+use std::cmp::min;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::rc::Rc;
+use terminus_spaceport::devices::TERM;
+use terminus_spaceport::irq::IrqVecSender;
+use terminus_spaceport::memory::prelude::*;
+use terminus_spaceport::memory::region::Region;
+use terminus_vault::*;
+
+// Virtio-mmio version 2 transport with split virtqueues, for devices with a receive queue 0 and a
+// transmit queue 1. Host reads go through record/replay, so the console and the network can be
+// recorded, replayed and re-executed.
+
+const VIRTIO_MAGIC: u32 = 0x7472_6976;
+const VIRTIO_VERSION: u32 = 2;
+const VIRTIO_F_VERSION_1: u64 = 1 << 32;
+const VIRTIO_STATUS_NEEDS_RESET: u32 = 0x40;
+const VIRTIO_INT_USED_RING: u32 = 0x1;
+const VIRTIO_INT_CONFIG: u32 = 0x2;
+const VIRTQ_DESC_F_NEXT: u16 = 0x1;
+const VIRTQ_DESC_F_WRITE: u16 = 0x2;
+const VIRTQ_AVAIL_F_NO_INTERRUPT: u16 = 0x1;
+const QUEUE_NUM_MAX: u16 = 256;
+const RX_QUEUE: usize = 0;
+const TX_QUEUE: usize = 1;
+
+//host side of a device
+pub trait VirtIOBackend {
+    fn device_id(&self) -> u32;
+    fn features(&self) -> u64;
+    fn config(&self) -> Vec<u8>;
+    //header before every buffer in both directions, given to the driver on receive
+    fn header(&self) -> Vec<u8>;
+    fn input_src(&self) -> InputSrc;
+    //read at most len bytes from host, None if nothing arrived
+    fn recv(&mut self, len: usize) -> Option<Vec<u8>>;
+    fn send(&mut self, data: &[u8]);
+}
+
+#[derive(Default, Copy, Clone)]
+struct VirtQueue {
+    num: u16,
+    ready: bool,
+    desc: u64,
+    avail: u64,
+    used: u64,
+    last_avail: u16,
+    used_idx: u16,
+}
+
+struct VirtDesc {
+    addr: u64,
+    len: u32,
+    flags: u16,
+}
+
+struct VirtIOState {
+    status: u32,
+    int_status: u32,
+    device_features_sel: u32,
+    driver_features_sel: u32,
+    driver_features: u64,
+    queue_sel: u32,
+    queues: [VirtQueue; 2],
+}
+
+impl VirtIOState {
+    fn new() -> VirtIOState {
+        VirtIOState {
+            status: 0,
+            int_status: 0,
+            device_features_sel: 0,
+            driver_features_sel: 0,
+            driver_features: 0,
+            queue_sel: 0,
+            queues: [VirtQueue::default(); 2],
+        }
+    }
+
+    fn queue(&mut self) -> Option<&mut VirtQueue> {
+        self.queues.get_mut(self.queue_sel as usize)
+    }
+}
+
+pub struct VirtIOMmioDevice {
+    mem: Rc<Region>,
+    irq: IrqVecSender,
+    irq_id: usize,
+    state: RefCell<VirtIOState>,
+    backend: RefCell<Box<dyn VirtIOBackend>>,
+    replay: RefCell<Option<Rc<Replay>>>,
+}
+
+impl VirtIOMmioDevice {
+    pub fn new(mem: &Rc<Region>, intc: &Intc, backend: Box<dyn VirtIOBackend>) -> VirtIOMmioDevice {
+        let irq_id = intc.num_src();
+        VirtIOMmioDevice {
+            mem: mem.clone(),
+            irq: intc.alloc_src(irq_id),
+            irq_id,
+            state: RefCell::new(VirtIOState::new()),
+            backend: RefCell::new(backend),
+            replay: RefCell::new(None),
+        }
+    }
+
+    pub fn irq_id(&self) -> usize {
+        self.irq_id
+    }
+
+    //route host input and output through record/replay
+    pub fn set_replay(&self, replay: &Rc<Replay>) {
+        *self.replay.borrow_mut() = Some(replay.clone())
+    }
+
+    //the backend keeps no state, host input is only read when a buffer is available
+    pub fn save_snapshot<W: Write>(&self, w: &mut SnapshotWriter<W>) -> io::Result<()> {
+        let state = self.state.borrow();
+        w.write_u32(state.status)?;
+        w.write_u32(state.int_status)?;
+        w.write_u32(state.device_features_sel)?;
+        w.write_u32(state.driver_features_sel)?;
+        w.write_u64(state.driver_features)?;
+        w.write_u32(state.queue_sel)?;
+        for q in state.queues.iter() {
+            w.write_u32(q.num as u32)?;
+            w.write_bool(q.ready)?;
+            w.write_u64(q.desc)?;
+            w.write_u64(q.avail)?;
+            w.write_u64(q.used)?;
+            w.write_u32(q.last_avail as u32)?;
+            w.write_u32(q.used_idx as u32)?;
+        }
+        Ok(())
+    }
+
+    pub fn restore_snapshot<R: Read>(&self, r: &mut SnapshotReader<R>) -> io::Result<()> {
+        let mut state = self.state.borrow_mut();
+        state.status = r.read_u32()?;
+        state.int_status = r.read_u32()?;
+        state.device_features_sel = r.read_u32()?;
+        state.driver_features_sel = r.read_u32()?;
+        state.driver_features = r.read_u64()?;
+        state.queue_sel = r.read_u32()?;
+        for q in state.queues.iter_mut() {
+            q.num = r.read_u32()? as u16;
+            q.ready = r.read_bool()?;
+            q.desc = r.read_u64()?;
+            q.avail = r.read_u64()?;
+            q.used = r.read_u64()?;
+            q.last_avail = r.read_u32()? as u16;
+            q.used_idx = r.read_u32()? as u16;
+        }
+        Ok(())
+    }
+
+    fn muted(&self) -> bool {
+        self.replay.borrow().as_ref().map_or(false, |r| r.muted())
+    }
+
+    fn check_range(&self, addr: u64, len: usize) -> Result<(), String> {
+        let info = &self.mem.info;
+        if addr < info.base || addr.saturating_add(len as u64) > info.base + info.size {
+            Err(format!("virtio dma out of memory:{:#x}+{:#x}", addr, len))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn read_mem(&self, addr: u64, data: &mut [u8]) -> Result<(), String> {
+        self.check_range(addr, data.len())?;
+        BytesAccess::read(&*self.mem, &addr, data).map(|_| ())
+    }
+
+    fn write_mem(&self, addr: u64, data: &[u8]) -> Result<(), String> {
+        self.check_range(addr, data.len())?;
+        BytesAccess::write(&*self.mem, &addr, data).map(|_| ())
+    }
+
+    fn read_u16(&self, addr: u64) -> Result<u16, String> {
+        let mut data = [0u8; 2];
+        self.read_mem(addr, &mut data)?;
+        Ok(u16::from_le_bytes(data))
+    }
+
+    //head of the next available chain
+    fn pop(&self, q: &mut VirtQueue) -> Result<Option<u16>, String> {
+        if !q.ready || q.num == 0 {
+            return Ok(None);
+        }
+        let idx = self.read_u16(q.avail + 2)?;
+        if idx == q.last_avail {
+            return Ok(None);
+        }
+        let head = self.read_u16(q.avail + 4 + 2 * (q.last_avail % q.num) as u64)?;
+        q.last_avail = q.last_avail.wrapping_add(1);
+        Ok(Some(head))
+    }
+
+    fn chain(&self, q: &VirtQueue, head: u16) -> Result<Vec<VirtDesc>, String> {
+        let mut descs = vec![];
+        let mut i = head;
+        loop {
+            if i >= q.num || descs.len() >= q.num as usize {
+                return Err(format!("invalid virtio descriptor chain from {}", head));
+            }
+            let mut data = [0u8; 16];
+            self.read_mem(q.desc + 16 * i as u64, &mut data)?;
+            let desc = VirtDesc {
+                addr: u64::from_le_bytes([
+                    data[0], data[1], data[2], data[3], data[4], data[5], data[6], data[7],
+                ]),
+                len: u32::from_le_bytes([data[8], data[9], data[10], data[11]]),
+                flags: u16::from_le_bytes([data[12], data[13]]),
+            };
+            let next = u16::from_le_bytes([data[14], data[15]]);
+            let has_next = desc.flags & VIRTQ_DESC_F_NEXT != 0;
+            descs.push(desc);
+            if !has_next {
+                return Ok(descs);
+            }
+            i = next
+        }
+    }
+
+    fn push(&self, q: &mut VirtQueue, head: u16, len: u32) -> Result<bool, String> {
+        let mut elem = [0u8; 8];
+        elem[..4].copy_from_slice(&(head as u32).to_le_bytes());
+        elem[4..].copy_from_slice(&len.to_le_bytes());
+        self.write_mem(q.used + 4 + 8 * (q.used_idx % q.num) as u64, &elem)?;
+        q.used_idx = q.used_idx.wrapping_add(1);
+        self.write_mem(q.used + 2, &q.used_idx.to_le_bytes())?;
+        Ok(self.read_u16(q.avail)? & VIRTQ_AVAIL_F_NO_INTERRUPT == 0)
+    }
+
+    fn interrupt(&self, state: &mut VirtIOState, int: u32) {
+        state.int_status |= int;
+        self.irq.send().unwrap();
+    }
+
+    //the driver has to reset the device after a dma error
+    fn fail(&self, state: &mut VirtIOState, msg: String) {
+        eprintln!("{}", msg);
+        state.status |= VIRTIO_STATUS_NEEDS_RESET;
+        self.interrupt(state, VIRTIO_INT_CONFIG)
+    }
+
+    fn transmit(&self, state: &mut VirtIOState) -> Result<(), String> {
+        let mut q = state.queues[TX_QUEUE];
+        let result = self.transmit_queue(&mut q);
+        state.queues[TX_QUEUE] = q;
+        if result? {
+            self.interrupt(state, VIRTIO_INT_USED_RING)
+        }
+        Ok(())
+    }
+
+    fn transmit_queue(&self, q: &mut VirtQueue) -> Result<bool, String> {
+        let mut backend = self.backend.borrow_mut();
+        let header = backend.header().len();
+        let mut int = false;
+        while let Some(head) = self.pop(q)? {
+            let mut data = vec![];
+            for desc in self.chain(q, head)?.iter() {
+                if desc.flags & VIRTQ_DESC_F_WRITE == 0 {
+                    let start = data.len();
+                    data.resize(start + desc.len as usize, 0);
+                    self.read_mem(desc.addr, &mut data[start..])?;
+                }
+            }
+            if data.len() > header && !self.muted() {
+                backend.send(&data[header..])
+            }
+            int |= self.push(q, head, 0)?;
+        }
+        Ok(int)
+    }
+
+    fn receive(&self, state: &mut VirtIOState) -> Result<(), String> {
+        let mut q = state.queues[RX_QUEUE];
+        let result = self.receive_queue(&mut q);
+        state.queues[RX_QUEUE] = q;
+        if result? {
+            self.interrupt(state, VIRTIO_INT_USED_RING)
+        }
+        Ok(())
+    }
+
+    fn receive_queue(&self, q: &mut VirtQueue) -> Result<bool, String> {
+        let mut backend = self.backend.borrow_mut();
+        let header = backend.header();
+        let mut int = false;
+        while let Some(head) = self.pop(q)? {
+            let descs = self
+                .chain(q, head)?
+                .into_iter()
+                .filter(|desc| desc.flags & VIRTQ_DESC_F_WRITE != 0)
+                .collect::<Vec<_>>();
+            let len = descs.iter().map(|desc| desc.len as usize).sum::<usize>();
+            if len <= header.len() {
+                int |= self.push(q, head, 0)?;
+                continue;
+            }
+            let src = backend.input_src();
+            let mut recv = || backend.recv(len - header.len());
+            let input = if let Some(ref replay) = *self.replay.borrow() {
+                replay.input_bytes(src, recv)
+            } else {
+                recv()
+            };
+            let input = match input {
+                Some(input) => input,
+                None => {
+                    //keep the buffer for the next poll
+                    q.last_avail = q.last_avail.wrapping_sub(1);
+                    break;
+                }
+            };
+            let data = [&header[..], &input[..]].concat();
+            let mut offset = 0;
+            for desc in descs.iter() {
+                if offset == data.len() {
+                    break;
+                }
+                let n = min(desc.len as usize, data.len() - offset);
+                self.write_mem(desc.addr, &data[offset..offset + n])?;
+                offset += n;
+            }
+            int |= self.push(q, head, offset as u32)?;
+        }
+        Ok(int)
+    }
+
+    //move host input to the receive queue, should be called periodically
+    pub fn poll(&self) {
+        let mut state = self.state.borrow_mut();
+        if state.status & VIRTIO_STATUS_NEEDS_RESET != 0 {
+            return;
+        }
+        if let Err(msg) = self.receive(&mut state) {
+            self.fail(&mut state, msg)
+        }
+    }
+
+    fn notify(&self, state: &mut VirtIOState, queue: u32) {
+        if state.status & VIRTIO_STATUS_NEEDS_RESET != 0 {
+            return;
+        }
+        let result = match queue as usize {
+            RX_QUEUE => self.receive(state),
+            TX_QUEUE => self.transmit(state),
+            _ => Ok(()),
+        };
+        if let Err(msg) = result {
+            self.fail(state, msg)
+        }
+    }
+
+    fn read_reg(&self, offset: u64) -> u32 {
+        let mut state = self.state.borrow_mut();
+        let backend = self.backend.borrow();
+        let features = backend.features() | VIRTIO_F_VERSION_1;
+        match offset {
+            0x000 => VIRTIO_MAGIC,
+            0x004 => VIRTIO_VERSION,
+            0x008 => backend.device_id(),
+            0x010 => match state.device_features_sel {
+                0 => features as u32,
+                1 => (features >> 32) as u32,
+                _ => 0,
+            },
+            0x034 => state.queue().map_or(0, |_| QUEUE_NUM_MAX as u32),
+            0x044 => state.queue().map_or(0, |q| q.ready as u32),
+            0x060 => state.int_status,
+            0x070 => state.status,
+            //vendor id and config generation are 0
+            _ => 0,
+        }
+    }
+
+    fn write_reg(&self, offset: u64, data: u32) {
+        let mut state = self.state.borrow_mut();
+        let set_low = |v: &mut u64| *v = (*v & !0xffff_ffff) | data as u64;
+        let set_high = |v: &mut u64| *v = (*v & 0xffff_ffff) | ((data as u64) << 32);
+        match offset {
+            0x014 => state.device_features_sel = data,
+            0x020 => match state.driver_features_sel {
+                0 => set_low(&mut state.driver_features),
+                1 => set_high(&mut state.driver_features),
+                _ => {}
+            },
+            0x024 => state.driver_features_sel = data,
+            0x030 => state.queue_sel = data,
+            0x038 => {
+                if let Some(q) = state.queue() {
+                    if data <= QUEUE_NUM_MAX as u32 {
+                        q.num = data as u16
+                    }
+                }
+            }
+            0x044 => {
+                if let Some(q) = state.queue() {
+                    q.ready = data & 0x1 != 0
+                }
+            }
+            0x050 => self.notify(&mut state, data),
+            0x064 => state.int_status &= !data,
+            0x070 => {
+                if data == 0 {
+                    *state = VirtIOState::new()
+                } else {
+                    state.status = data
+                }
+            }
+            0x080..=0x0a4 => {
+                if let Some(q) = state.queue() {
+                    match offset {
+                        0x080 => set_low(&mut q.desc),
+                        0x084 => set_high(&mut q.desc),
+                        0x090 => set_low(&mut q.avail),
+                        0x094 => set_high(&mut q.avail),
+                        0x0a0 => set_low(&mut q.used),
+                        0x0a4 => set_high(&mut q.used),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive_io(Bytes, U32, U64)]
+pub struct VirtIOMmio(Rc<VirtIOMmioDevice>);
+
+impl VirtIOMmio {
+    pub fn new(device: &Rc<VirtIOMmioDevice>) -> VirtIOMmio {
+        VirtIOMmio(device.clone())
+    }
+}
+
+impl BytesAccess for VirtIOMmio {
+    fn write(&self, addr: &u64, data: &[u8]) -> std::result::Result<usize, String> {
+        if *addr >= 0x100 {
+            //config space is read only
+        } else if data.len() == 4 {
+            let mut bytes = [0; 4];
+            bytes.copy_from_slice(data);
+            U32Access::write(self, addr, u32::from_le_bytes(bytes))
+        } else if data.len() == 8 {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(data);
+            U64Access::write(self, addr, u64::from_le_bytes(bytes))
+        }
+        Ok(0)
+    }
+
+    fn read(&self, addr: &u64, data: &mut [u8]) -> std::result::Result<usize, String> {
+        if *addr >= 0x100 {
+            let config = self.0.backend.borrow().config();
+            for (i, b) in data.iter_mut().enumerate() {
+                *b = *config.get((*addr - 0x100) as usize + i).unwrap_or(&0)
+            }
+        } else if data.len() == 4 {
+            data.copy_from_slice(&U32Access::read(self, addr).to_le_bytes())
+        } else if data.len() == 8 {
+            data.copy_from_slice(&U64Access::read(self, addr).to_le_bytes())
+        }
+        Ok(0)
+    }
+}
+
+impl U32Access for VirtIOMmio {
+    fn write(&self, addr: &u64, data: u32) {
+        if *addr >= 0x100 {
+            BytesAccess::write(self, addr, &data.to_le_bytes()).unwrap();
+        } else {
+            self.0.write_reg(*addr, data)
+        }
+    }
+
+    fn read(&self, addr: &u64) -> u32 {
+        if *addr >= 0x100 {
+            let mut data = [0u8; 4];
+            BytesAccess::read(self, addr, &mut data).unwrap();
+            u32::from_le_bytes(data)
+        } else {
+            self.0.read_reg(*addr)
+        }
+    }
+}
+
+impl U64Access for VirtIOMmio {
+    fn write(&self, addr: &u64, data: u64) {
+        U32Access::write(self, addr, data as u32);
+        U32Access::write(self, &(*addr + 4), (data >> 32) as u32);
+    }
+
+    fn read(&self, addr: &u64) -> u64 {
+        U32Access::read(self, addr) as u64 | ((U32Access::read(self, &(*addr + 4)) as u64) << 32)
+    }
+}
+
+//console on the host terminal
+pub struct TermConsole;
+
+impl VirtIOBackend for TermConsole {
+    fn device_id(&self) -> u32 {
+        3
+    }
+
+    fn features(&self) -> u64 {
+        0
+    }
+
+    fn config(&self) -> Vec<u8> {
+        vec![]
+    }
+
+    fn header(&self) -> Vec<u8> {
+        vec![]
+    }
+
+    fn input_src(&self) -> InputSrc {
+        InputSrc::VirtIOConsole
+    }
+
+    fn recv(&mut self, len: usize) -> Option<Vec<u8>> {
+        let mut data = vec![0u8; len];
+        match TERM.stdin().lock().read(&mut data) {
+            Ok(0) => None,
+            Ok(n) => {
+                data.truncate(n);
+                Some(data)
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => None,
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    fn send(&mut self, data: &[u8]) {
+        let stdout = TERM.stdout();
+        let mut handle = stdout.lock();
+        handle.write_all(data).unwrap();
+        handle.flush().unwrap();
+    }
+}
+
+const VIRTIO_NET_F_MAC: u64 = 1 << 5;
+const TUNSETIFF: u64 = 0x4004_54ca;
+const IFF_TAP: i16 = 0x0002;
+const IFF_NO_PI: i16 = 0x1000;
+//ethernet frame with vlan tag
+const ETH_FRAME_MAX: usize = 1518;
+
+//network on a host tap interface
+pub struct TapNet {
+    tap: File,
+    mac: [u8; 6],
+}
+
+impl TapNet {
+    //mac is in little endian, 0x0100_00000002 is 02:00:00:00:00:01
+    pub fn new(name: &str, mac: u64) -> io::Result<TapNet> {
+        if name.len() >= 16 {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("tap name {} is too long!", name),
+            ));
+        }
+        let tap = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open("/dev/net/tun")?;
+        //struct ifreq: 16 bytes name followed by flags
+        let mut ifr = [0u8; 40];
+        ifr[..name.len()].copy_from_slice(name.as_bytes());
+        ifr[16..18].copy_from_slice(&(IFF_TAP | IFF_NO_PI).to_ne_bytes());
+        if unsafe { libc::ioctl(tap.as_raw_fd(), TUNSETIFF as _, ifr.as_mut_ptr()) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut mac_addr = [0u8; 6];
+        mac_addr.copy_from_slice(&mac.to_le_bytes()[..6]);
+        Ok(TapNet { tap, mac: mac_addr })
+    }
+}
+
+impl VirtIOBackend for TapNet {
+    fn device_id(&self) -> u32 {
+        1
+    }
+
+    fn features(&self) -> u64 {
+        VIRTIO_NET_F_MAC
+    }
+
+    fn config(&self) -> Vec<u8> {
+        self.mac.to_vec()
+    }
+
+    //struct virtio_net_hdr with num_buffers 1
+    fn header(&self) -> Vec<u8> {
+        vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0]
+    }
+
+    fn input_src(&self) -> InputSrc {
+        InputSrc::VirtIONet
+    }
+
+    fn recv(&mut self, len: usize) -> Option<Vec<u8>> {
+        let mut data = vec![0u8; ETH_FRAME_MAX];
+        match self.tap.read(&mut data) {
+            Ok(0) => None,
+            Ok(n) => {
+                //frames larger than the buffer are truncated
+                data.truncate(min(n, len));
+                Some(data)
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => None,
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    fn send(&mut self, data: &[u8]) {
+        match self.tap.write(data) {
+            Ok(_) => {}
+            //dropped like a full link
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use terminus_spaceport::memory::region::GHEAP;
+
+    const BASE: u64 = 0x8000_0000;
+
+    struct Loopback {
+        rx: Rc<RefCell<Vec<Vec<u8>>>>,
+        tx: Rc<RefCell<Vec<Vec<u8>>>>,
+    }
+
+    impl VirtIOBackend for Loopback {
+        fn device_id(&self) -> u32 {
+            1
+        }
+        fn features(&self) -> u64 {
+            VIRTIO_NET_F_MAC
+        }
+        fn config(&self) -> Vec<u8> {
+            vec![1, 2, 3, 4, 5, 6]
+        }
+        fn header(&self) -> Vec<u8> {
+            vec![0xaa, 0xbb]
+        }
+        fn input_src(&self) -> InputSrc {
+            InputSrc::VirtIONet
+        }
+        fn recv(&mut self, len: usize) -> Option<Vec<u8>> {
+            let mut rx = self.rx.borrow_mut();
+            if rx.is_empty() {
+                None
+            } else {
+                let mut data = rx.remove(0);
+                data.truncate(len);
+                Some(data)
+            }
+        }
+        fn send(&mut self, data: &[u8]) {
+            self.tx.borrow_mut().push(data.to_vec())
+        }
+    }
+
+    fn write_desc(mem: &Region, table: u64, i: u64, addr: u64, len: u32, flags: u16, next: u16) {
+        let mut data = vec![];
+        data.extend_from_slice(&addr.to_le_bytes());
+        data.extend_from_slice(&len.to_le_bytes());
+        data.extend_from_slice(&flags.to_le_bytes());
+        data.extend_from_slice(&next.to_le_bytes());
+        BytesAccess::write(mem, &(table + 16 * i), &data).unwrap();
+    }
+
+    fn make_avail(mem: &Region, avail: u64, idx: u16, head: u16) {
+        BytesAccess::write(
+            mem,
+            &(avail + 4 + 2 * ((idx - 1) % 4) as u64),
+            &head.to_le_bytes(),
+        )
+        .unwrap();
+        BytesAccess::write(mem, &(avail + 2), &idx.to_le_bytes()).unwrap();
+    }
+
+    fn read_bytes(mem: &Region, addr: u64, len: usize) -> Vec<u8> {
+        let mut data = vec![0u8; len];
+        BytesAccess::read(mem, &addr, &mut data).unwrap();
+        data
+    }
+
+    fn setup_queue(mmio: &VirtIOMmio, queue: u32, base: u64) {
+        U32Access::write(mmio, &0x030, queue);
+        assert_eq!(U32Access::read(mmio, &0x034), QUEUE_NUM_MAX as u32);
+        U32Access::write(mmio, &0x038, 4);
+        U64Access::write(mmio, &0x080, base);
+        U64Access::write(mmio, &0x090, base + 0x100);
+        U64Access::write(mmio, &0x0a0, base + 0x200);
+        U32Access::write(mmio, &0x044, 1);
+    }
+
+    #[test]
+    fn virtio_mmio_test() {
+        let heap = GHEAP.alloc(0x10000, 1).unwrap();
+        let mem = Region::remap(BASE, &heap);
+        let intc = Intc::new(32);
+        let rx = Rc::new(RefCell::new(vec![]));
+        let tx = Rc::new(RefCell::new(vec![]));
+        let backend = Loopback {
+            rx: rx.clone(),
+            tx: tx.clone(),
+        };
+        let device = Rc::new(VirtIOMmioDevice::new(&mem, &intc, Box::new(backend)));
+        let mmio = VirtIOMmio::new(&device);
+        assert_eq!(device.irq_id(), 1);
+        assert_eq!(U32Access::read(&mmio, &0x000), VIRTIO_MAGIC);
+        assert_eq!(U32Access::read(&mmio, &0x004), 2);
+        assert_eq!(U32Access::read(&mmio, &0x008), 1);
+        assert_eq!(U32Access::read(&mmio, &0x010), VIRTIO_NET_F_MAC as u32);
+        U32Access::write(&mmio, &0x014, 1);
+        assert_eq!(U32Access::read(&mmio, &0x010), 1);
+        let mut mac = [0u8; 6];
+        BytesAccess::read(&mmio, &0x100, &mut mac).unwrap();
+        assert_eq!(mac, [1, 2, 3, 4, 5, 6]);
+        U32Access::write(&mmio, &0x070, 0xf);
+        setup_queue(&mmio, 0, BASE + 0x1000);
+        setup_queue(&mmio, 1, BASE + 0x2000);
+        //queue 2 does not exist
+        U32Access::write(&mmio, &0x030, 2);
+        assert_eq!(U32Access::read(&mmio, &0x034), 0);
+
+        //transmit, header is dropped and the chain is gathered
+        write_desc(
+            &mem,
+            BASE + 0x2000,
+            0,
+            BASE + 0x3000,
+            2,
+            VIRTQ_DESC_F_NEXT,
+            1,
+        );
+        write_desc(&mem, BASE + 0x2000, 1, BASE + 0x3010, 3, 0, 0);
+        BytesAccess::write(&*mem, &(BASE + 0x3000), &[0xaa, 0xbb]).unwrap();
+        BytesAccess::write(&*mem, &(BASE + 0x3010), &[1, 2, 3]).unwrap();
+        make_avail(&mem, BASE + 0x2100, 1, 0);
+        U32Access::write(&mmio, &0x050, 1);
+        assert_eq!(*tx.borrow(), vec![vec![1, 2, 3]]);
+        assert_eq!(read_bytes(&mem, BASE + 0x2202, 2), vec![1, 0]);
+        assert_eq!(read_bytes(&mem, BASE + 0x2204, 8), vec![0; 8]);
+        assert_eq!(U32Access::read(&mmio, &0x060), VIRTIO_INT_USED_RING);
+        assert!(intc.pending(1));
+        U32Access::write(&mmio, &0x064, VIRTIO_INT_USED_RING);
+        assert_eq!(U32Access::read(&mmio, &0x060), 0);
+
+        //receive, the buffer is kept until host input arrives and input is truncated to the buffer
+        write_desc(
+            &mem,
+            BASE + 0x1000,
+            2,
+            BASE + 0x4000,
+            4,
+            VIRTQ_DESC_F_WRITE | VIRTQ_DESC_F_NEXT,
+            3,
+        );
+        write_desc(
+            &mem,
+            BASE + 0x1000,
+            3,
+            BASE + 0x4010,
+            4,
+            VIRTQ_DESC_F_WRITE,
+            0,
+        );
+        make_avail(&mem, BASE + 0x1100, 1, 2);
+        device.poll();
+        assert_eq!(read_bytes(&mem, BASE + 0x1202, 2), vec![0, 0]);
+        rx.borrow_mut().push(vec![9, 8, 7, 6, 5, 4, 3]);
+        device.poll();
+        assert_eq!(read_bytes(&mem, BASE + 0x1202, 2), vec![1, 0]);
+        assert_eq!(
+            read_bytes(&mem, BASE + 0x1204, 8),
+            vec![2, 0, 0, 0, 8, 0, 0, 0]
+        );
+        assert_eq!(read_bytes(&mem, BASE + 0x4000, 4), vec![0xaa, 0xbb, 9, 8]);
+        assert_eq!(read_bytes(&mem, BASE + 0x4010, 4), vec![7, 6, 5, 4]);
+        assert_eq!(U32Access::read(&mmio, &0x060), VIRTIO_INT_USED_RING);
+
+        //dma out of memory needs reset
+        write_desc(
+            &mem,
+            BASE + 0x1000,
+            0,
+            BASE + 0x10000,
+            4,
+            VIRTQ_DESC_F_WRITE,
+            0,
+        );
+        make_avail(&mem, BASE + 0x1100, 2, 0);
+        rx.borrow_mut().push(vec![1]);
+        device.poll();
+        assert_eq!(
+            U32Access::read(&mmio, &0x070),
+            0xf | VIRTIO_STATUS_NEEDS_RESET
+        );
+        U32Access::write(&mmio, &0x070, 0);
+        assert_eq!(U32Access::read(&mmio, &0x070), 0);
+        assert_eq!(U32Access::read(&mmio, &0x044), 0);
+        assert_eq!(U32Access::read(&mmio, &0x060), 0);
+    }
+
+    #[test]
+    fn virtio_mmio_snapshot_test() {
+        let heap = GHEAP.alloc(0x10000, 1).unwrap();
+        let mem = Region::remap(BASE, &heap);
+        let intc = Intc::new(32);
+        let backend = Loopback {
+            rx: Rc::new(RefCell::new(vec![])),
+            tx: Rc::new(RefCell::new(vec![])),
+        };
+        let device = Rc::new(VirtIOMmioDevice::new(&mem, &intc, Box::new(backend)));
+        let mmio = VirtIOMmio::new(&device);
+        U32Access::write(&mmio, &0x070, 0xf);
+        setup_queue(&mmio, 1, BASE + 0x2000);
+        write_desc(&mem, BASE + 0x2000, 0, BASE + 0x3000, 4, 0, 0);
+        make_avail(&mem, BASE + 0x2100, 1, 0);
+        U32Access::write(&mmio, &0x050, 1);
+        let mut data = vec![];
+        device
+            .save_snapshot(&mut SnapshotWriter::new(&mut data).unwrap())
+            .unwrap();
+
+        U32Access::write(&mmio, &0x070, 0);
+        assert_eq!(U32Access::read(&mmio, &0x044), 0);
+        device
+            .restore_snapshot(&mut SnapshotReader::new(&data[..]).unwrap())
+            .unwrap();
+        assert_eq!(U32Access::read(&mmio, &0x070), 0xf);
+        assert_eq!(U32Access::read(&mmio, &0x060), VIRTIO_INT_USED_RING);
+        assert_eq!(U32Access::read(&mmio, &0x044), 1);
+        //used index continues from the saved one
+        make_avail(&mem, BASE + 0x2100, 2, 0);
+        U32Access::write(&mmio, &0x050, 1);
+        assert_eq!(read_bytes(&mem, BASE + 0x2202, 2), vec![2, 0]);
+    }
+
+    #[test]
+    fn ext_virtio_snapshot_test() {
+        use crate::devices::bus::Bus;
+        use crate::devices::virtio_console::{VirtIOConsole, VirtIOConsoleDevice};
+        use crate::system::System;
+
+        let mut sys = System::new("test", "top_tests/elf/rv64ui-p-add", 100, 32);
+        let heap = GHEAP.alloc(0x10000, 1).unwrap();
+        sys.register_memory("main_memory", BASE, &heap).unwrap();
+        let mem = Region::remap(BASE, &heap);
+        let backend = Loopback {
+            rx: Rc::new(RefCell::new(vec![])),
+            tx: Rc::new(RefCell::new(vec![])),
+        };
+        let device = Rc::new(VirtIOMmioDevice::new(&mem, sys.intc(), Box::new(backend)));
+        sys.register_virtio_mmio("virtio_mmio", &device).unwrap();
+        let irq_num = sys.intc().num_src();
+        let console = Rc::new(VirtIOConsoleDevice::new(
+            &mem,
+            sys.intc().alloc_src(irq_num),
+        ));
+        sys.register_virtio("virtio_console", VirtIOConsole::new(&console))
+            .unwrap();
+
+        sys.bus().write_u32(&(0x4000_0000 + 0x070), &0xf).unwrap();
+        let mut data = vec![];
+        sys.save_snapshot_to(&mut data).unwrap();
+        sys.bus().write_u32(&(0x4000_0000 + 0x070), &0).unwrap();
+        sys.restore_snapshot_from(&data[..]).unwrap();
+        let mut status = 0;
+        sys.bus()
+            .read_u32(&(0x4000_0000 + 0x070), &mut status)
+            .unwrap();
+        assert_eq!(status, 0xf);
+
+        //the driver starts to use the console whose state can not be saved
+        sys.bus().write_u32(&(0x4000_1000 + 0x070), &0x1).unwrap();
+        assert!(sys.save_snapshot_to(&mut vec![]).is_err());
+    }
+}
@@ -16,3 +16,7 @@ pub mod devices;
 pub mod system;
 
 pub mod global;
+
+pub mod snapshot;
+
+pub mod replay;
@@ -0,0 +1,317 @@
+use crate::devices::imsic::{IMSIC_M, IMSIC_S};
+use crate::prelude::*;
+use crate::processor::trap::Exception;
+use crate::processor::{HasCsr, ProcessorState};
+use crate::snapshot::{SnapshotReader, SnapshotWriter};
+use std::cell::Cell;
+use std::io;
+use std::io::{Read, Write};
+
+const SIEH: InsnT = 0x114;
+const SISELECT: InsnT = 0x150;
+const SIREG: InsnT = 0x151;
+const SIPH: InsnT = 0x154;
+const STOPEI: InsnT = 0x15C;
+const MIDELEGH: InsnT = 0x313;
+const MIEH: InsnT = 0x314;
+const MVIENH: InsnT = 0x318;
+const MVIPH: InsnT = 0x319;
+const MISELECT: InsnT = 0x350;
+const MIREG: InsnT = 0x351;
+const MIPH: InsnT = 0x354;
+const MTOPEI: InsnT = 0x35C;
+const STOPI: InsnT = 0xDB0;
+const MTOPI: InsnT = 0xFB0;
+
+//major interrupt priority array, iprio0-15 are selected by 0x30-0x3f
+const IPRIO0: RegT = 0x30;
+const IPRIO15: RegT = 0x3f;
+const IMSIC_FIRST: RegT = 0x70;
+const IMSIC_LAST: RegT = 0xff;
+
+//default priority order of major interrupts, MEI > MSI > MTI > SEI > SSI > STI > SGEI > VSEI > VSSI > VSTI > LCOFI
+const DEFAULT_PRIORITY: [RegT; 11] = [11, 3, 7, 9, 1, 5, 12, 10, 2, 6, 13];
+
+//smaia and ssaia csrs, VS level csrs and guest interrupt files are not supported
+pub struct Aia {
+    xlen: XLen,
+    smaia: bool,
+    ssaia: bool,
+    miselect: Cell<RegT>,
+    siselect: Cell<RegT>,
+}
+
+impl Aia {
+    pub fn new(xlen: XLen, smaia: bool, ssaia: bool) -> Aia {
+        Aia {
+            xlen,
+            smaia,
+            ssaia,
+            miselect: Cell::new(0),
+            siselect: Cell::new(0),
+        }
+    }
+
+    //iprio array is read-only zero, so IPRIO of topi is always 1
+    fn topi(pendings: RegT) -> RegT {
+        DEFAULT_PRIORITY
+            .iter()
+            .find(|i| (pendings >> **i) & 1 == 1)
+            .map_or(0, |i| *i << 16 | 1)
+    }
+
+    fn ireg(&self, state: &ProcessorState, level: usize, select: RegT) -> Option<RegT> {
+        match select {
+            IPRIO0..=IPRIO15 => {
+                if self.xlen == XLen::X64 && select & 1 != 0 {
+                    None
+                } else {
+                    Some(0)
+                }
+            }
+            IMSIC_FIRST..=IMSIC_LAST => state
+                .imsic()
+                .and_then(|imsic| imsic.ireg(level, select, self.xlen)),
+            _ => None,
+        }
+    }
+
+    fn set_ireg(
+        &self,
+        state: &ProcessorState,
+        level: usize,
+        select: RegT,
+        value: RegT,
+    ) -> Option<()> {
+        match select {
+            IPRIO0..=IPRIO15 => self.ireg(state, level, select).map(|_| ()),
+            IMSIC_FIRST..=IMSIC_LAST => state
+                .imsic()
+                .and_then(|imsic| imsic.set_ireg(level, select, value, self.xlen)),
+            _ => None,
+        }
+    }
+
+    fn s_accessible(&self, state: &ProcessorState) -> bool {
+        self.ssaia && !state.virt()
+    }
+
+    //imsic registers selected by siselect are gated by mstateen0.imsic, and hstateen0.imsic in
+    //VS mode, where vsiselect is not supported and siselect is used
+    pub fn check_stateen(&self, state: &ProcessorState, addr: InsnT) -> Result<(), Exception> {
+        match (addr, self.siselect.get()) {
+            (SIREG, IMSIC_FIRST..=IMSIC_LAST) if self.ssaia => state.check_stateen(0, 58),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn save_snapshot<W: Write>(&self, w: &mut SnapshotWriter<W>) -> io::Result<()> {
+        w.write_u64(self.miselect.get() as u64)?;
+        w.write_u64(self.siselect.get() as u64)
+    }
+
+    pub fn restore_snapshot<R: Read>(&self, r: &mut SnapshotReader<R>) -> io::Result<()> {
+        self.miselect.set(r.read_u64()? as RegT);
+        self.siselect.set(r.read_u64()? as RegT);
+        Ok(())
+    }
+}
+
+impl HasCsr for Aia {
+    fn csr_write(&self, state: &ProcessorState, addr: InsnT, value: RegT) -> Option<()> {
+        match addr {
+            MISELECT if self.smaia => self.miselect.set(value & 0xfff),
+            MIREG if self.smaia => {
+                return self.set_ireg(state, IMSIC_M, self.miselect.get(), value)
+            }
+            MTOPEI if self.smaia => state.imsic()?.claim(IMSIC_M),
+            MIDELEGH | MIEH | MVIENH | MVIPH | MIPH if self.smaia && self.xlen == XLen::X32 => {}
+            SISELECT if self.s_accessible(state) => self.siselect.set(value & 0xfff),
+            SIREG if self.s_accessible(state) => {
+                return self.set_ireg(state, IMSIC_S, self.siselect.get(), value)
+            }
+            STOPEI if self.s_accessible(state) => state.imsic()?.claim(IMSIC_S),
+            SIEH | SIPH if self.s_accessible(state) && self.xlen == XLen::X32 => {}
+            _ => return None,
+        }
+        Some(())
+    }
+
+    fn csr_read(&self, state: &ProcessorState, addr: InsnT) -> Option<RegT> {
+        match addr {
+            MISELECT if self.smaia => Some(self.miselect.get()),
+            MIREG if self.smaia => self.ireg(state, IMSIC_M, self.miselect.get()),
+            MTOPEI if self.smaia => Some(state.imsic()?.topei(IMSIC_M)),
+            MTOPI if self.smaia => {
+                let m = state.priv_m();
+                Some(Aia::topi(
+                    m.mip().get() & m.mie().get() & !m.mideleg().get(),
+                ))
+            }
+            MIDELEGH | MIEH | MVIENH | MVIPH | MIPH if self.smaia && self.xlen == XLen::X32 => {
+                Some(0)
+            }
+            SISELECT if self.s_accessible(state) => Some(self.siselect.get()),
+            SIREG if self.s_accessible(state) => self.ireg(state, IMSIC_S, self.siselect.get()),
+            STOPEI if self.s_accessible(state) => Some(state.imsic()?.topei(IMSIC_S)),
+            STOPI if self.s_accessible(state) => {
+                let s = state.priv_s().ok()?;
+                Some(Aia::topi(s.sip().get() & s.sie().get()))
+            }
+            SIEH | SIPH if self.s_accessible(state) && self.xlen == XLen::X32 => Some(0),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::devices::bus::Bus;
+    use crate::devices::imsic::ImsicFiles;
+    use crate::processor::privilege::Privilege;
+    use crate::processor::{Processor, ProcessorCfg, SatpMode};
+    use crate::system::System;
+    use terminus_spaceport::memory::region::GHEAP;
+
+    const EIDELIVERY: RegT = 0x70;
+    const EIE0: RegT = 0xc0;
+
+    #[test]
+    fn topei_claim_test() {
+        let mut sys = System::new("test", "top_tests/elf/rv64ui-p-add", 100, 1);
+        sys.enable_aia(true);
+        sys.new_processor(ProcessorCfg {
+            xlen: XLen::X64,
+            enable_dirty: true,
+            isa: "rv64imacs_ssaia".parse().unwrap(),
+            freq: 1000000000,
+            triggers: 0,
+            satp_modes: vec![SatpMode::Sv39].into_boxed_slice(),
+            vlen: 128,
+            elen: 64,
+            cache_block_size: 64,
+            entropy_seed: 0,
+            hpm_counters: 0,
+        });
+        sys.register_memory("main_memory", 0x8000_0000, &GHEAP.alloc(0x2000, 1).unwrap())
+            .unwrap();
+        let imsic = sys.imsic().cloned().unwrap();
+        let imsic_m = ImsicFiles::new(&imsic, IMSIC_M);
+        let imsic_s = ImsicFiles::new(&imsic, IMSIC_S);
+        sys.register_device("imsic_m", 0x2400_0000, imsic_m.size(), imsic_m)
+            .unwrap();
+        sys.register_device("imsic_s", 0x2800_0000, imsic_s.size(), imsic_s)
+            .unwrap();
+        let insns: [u32; 3] = [
+            0x35c02573, //csrrs a0, mtopei, zero
+            0x35c01573, //csrrw a0, mtopei, zero
+            0x15c01573, //csrrw a0, stopei, zero
+        ];
+        for (i, insn) in insns.iter().enumerate() {
+            sys.bus()
+                .write_u32(&(0x8000_0000 + i as u64 * 4), insn)
+                .unwrap();
+        }
+        sys.reset(vec![0x8000_0000]).unwrap();
+        for level in [IMSIC_M, IMSIC_S].iter() {
+            let p = sys.processor(0).unwrap();
+            let file = p.state().imsic().unwrap();
+            file.set_ireg(*level, EIDELIVERY, 1, XLen::X64);
+            file.set_ireg(*level, EIE0, !0, XLen::X64);
+        }
+        //msis to both files of the hart
+        sys.bus().write_u32(&0x2400_0000, &5).unwrap();
+        sys.bus().write_u32(&0x2400_0000, &3).unwrap();
+        sys.bus().write_u32(&0x2800_0000, &9).unwrap();
+
+        let p = sys.processor(0).unwrap();
+        //reading mtopei does not claim
+        p.step(1);
+        assert_eq!(*p.state().xreg(10), 3 << 16 | 3);
+        assert_eq!(p.state().imsic().unwrap().topei(IMSIC_M), 3 << 16 | 3);
+        //writing mtopei claims the identity read
+        p.step(1);
+        assert_eq!(*p.state().xreg(10), 3 << 16 | 3);
+        assert_eq!(p.state().imsic().unwrap().topei(IMSIC_M), 5 << 16 | 5);
+        //stopei claims from the S level file only
+        p.step(1);
+        assert_eq!(*p.state().xreg(10), 9 << 16 | 9);
+        assert_eq!(p.state().imsic().unwrap().topei(IMSIC_S), 0);
+        assert_eq!(p.state().imsic().unwrap().topei(IMSIC_M), 5 << 16 | 5);
+    }
+
+    //mret to S mode, read sireg and ecall back to M mode, return sireg or mcause of the trap
+    fn sireg(p: &mut Processor, virt: bool) -> Result<RegT, RegT> {
+        p.state().priv_m().mstatus_mut().set_mpp(1);
+        p.state().priv_m().mstatus_mut().set_mpv(virt as RegT);
+        p.state().priv_m().mepc_mut().set(0x8000_0004);
+        p.state_mut().set_pc(0x8000_0000);
+        p.step(2);
+        if *p.state().privilege() == Privilege::M {
+            return Err(p.state().priv_m().mcause().get());
+        }
+        let value = *p.state().xreg(10);
+        p.step(1);
+        assert_eq!(*p.state().privilege(), Privilege::M);
+        Ok(value)
+    }
+
+    #[test]
+    fn sireg_stateen_test() {
+        let mut sys = System::new("test", "top_tests/elf/rv64ui-p-add", 100, 1);
+        sys.enable_aia(true);
+        sys.new_processor(ProcessorCfg {
+            xlen: XLen::X64,
+            enable_dirty: true,
+            isa: "rv64imach_smstateen_ssaia".parse().unwrap(),
+            freq: 1000000000,
+            triggers: 0,
+            satp_modes: vec![SatpMode::Sv39].into_boxed_slice(),
+            vlen: 128,
+            elen: 64,
+            cache_block_size: 64,
+            entropy_seed: 0,
+            hpm_counters: 0,
+        });
+        sys.register_memory("main_memory", 0x8000_0000, &GHEAP.alloc(0x2000, 1).unwrap())
+            .unwrap();
+        let insns: [u32; 3] = [
+            0x30200073, //mret
+            0x15102573, //csrr a0, sireg
+            0x00000073, //ecall
+        ];
+        for (i, insn) in insns.iter().enumerate() {
+            sys.bus()
+                .write_u32(&(0x8000_0000 + i as u64 * 4), insn)
+                .unwrap();
+        }
+        sys.reset(vec![0x8000_0000]).unwrap();
+        let p = sys.processor(0).unwrap();
+        //TOR RWX below 4GiB
+        p.state().priv_m().pmpcfg0_mut().set_bit_range(4, 0, 0xf);
+        p.state().priv_m().pmpaddr0_mut().set(0x1_0000_0000 >> 2);
+        p.state().priv_m().mstateen0_mut().set_csrind(1);
+        p.state().priv_h().unwrap().hstateen0_mut().set_csrind(1);
+        p.state()
+            .imsic()
+            .unwrap()
+            .set_ireg(IMSIC_S, EIDELIVERY, 1, XLen::X64);
+        p.state().aia.siselect.set(EIDELIVERY);
+
+        //disabled by mstateen0.imsic
+        assert_eq!(sireg(p, false), Err(2));
+        assert_eq!(sireg(p, true), Err(2));
+        //enabled by mstateen0.imsic but disabled by hstateen0.imsic
+        p.state().priv_m().mstateen0_mut().set_imsic(1);
+        assert_eq!(sireg(p, false), Ok(1));
+        assert_eq!(sireg(p, true), Err(22));
+        //VS level interrupt files are not supported
+        p.state().priv_h().unwrap().hstateen0_mut().set_imsic(1);
+        assert_eq!(sireg(p, true), Err(2));
+        //other registers are not gated by imsic
+        p.state().priv_m().mstateen0_mut().set_imsic(0);
+        p.state().aia.siselect.set(IPRIO0);
+        assert_eq!(sireg(p, false), Ok(0));
+    }
+}
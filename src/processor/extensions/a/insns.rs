@@ -109,7 +109,7 @@ impl Execution for SCW {
             let addr = p.state().xreg(self.rs1(p.state().ir()));
             let data = p.state().xreg(self.rs2(p.state().ir()));
             let mut lc_res = a.lc_res.borrow_mut();
-            let success = if lc_res.valid {
+            let success = if lc_res.active(p) {
                 if *addr != lc_res.addr || lc_res.len != 4 {
                     false
                 } else {
@@ -155,7 +155,7 @@ impl Execution for SCD {
             let addr = p.state().xreg(self.rs1(p.state().ir()));
             let data = p.state().xreg(self.rs2(p.state().ir()));
             let mut lc_res = a.lc_res.borrow_mut();
-            let success = if lc_res.valid {
+            let success = if lc_res.active(p) {
                 if *addr != lc_res.addr || lc_res.len != 8 {
                     false
                 } else {
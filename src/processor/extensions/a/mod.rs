@@ -1,15 +1,42 @@
 use crate::prelude::RegT;
 use crate::processor::extensions::{HasStepCb, NoCsr};
+use crate::processor::isa::IsaExt;
+use crate::processor::trap::Exception;
 use crate::processor::{Processor, ProcessorState};
+use crate::snapshot::{SnapshotReader, SnapshotWriter};
 use std::cell::RefCell;
+use std::io;
+use std::io::{Read, Write};
 
 mod insns;
+mod zabha;
+mod zacas;
+mod zawrs;
 
 struct LCReservation {
     valid: bool,
     addr: RegT,
     len: u64,
     timestamp: u64,
+    //remaining steps of a stalling wrs before it times out
+    wrs: Option<u64>,
+}
+
+//instructions after lr for which the reservation lives, counted per instruction so that
+//lr/sc outcomes do not depend on how many instructions a single step() executes
+const LC_RES_TIMEOUT: u64 = 16;
+
+impl LCReservation {
+    fn active(&self, p: &Processor) -> bool {
+        self.valid && *p.state().insns_cnt().borrow() <= self.timestamp + LC_RES_TIMEOUT
+    }
+
+    //lost when any other hart writes the reserved bytes
+    fn held(&self, p: &Processor) -> bool {
+        self.active(p)
+            && p.load_store()
+                .holding_lock(p.state(), &self.addr, self.len as usize, p.mmu())
+    }
 }
 
 pub struct ExtensionA {
@@ -24,21 +51,63 @@ impl ExtensionA {
                 addr: 0,
                 len: 0,
                 timestamp: 0,
+                wrs: None,
             }),
         }
     }
+
+    pub fn save_snapshot<W: Write>(&self, w: &mut SnapshotWriter<W>) -> io::Result<()> {
+        let lc_res = self.lc_res.borrow();
+        w.write_bool(lc_res.valid)?;
+        w.write_u64(lc_res.addr)?;
+        w.write_u64(lc_res.len)?;
+        w.write_u64(lc_res.timestamp)?;
+        w.write_bool(lc_res.wrs.is_some())?;
+        w.write_u64(lc_res.wrs.unwrap_or_default())
+    }
+
+    pub fn restore_snapshot<R: Read>(&self, r: &mut SnapshotReader<R>) -> io::Result<()> {
+        let mut lc_res = self.lc_res.borrow_mut();
+        lc_res.valid = r.read_bool()?;
+        lc_res.addr = r.read_u64()?;
+        lc_res.len = r.read_u64()?;
+        lc_res.timestamp = r.read_u64()?;
+        let wrs = r.read_bool()?;
+        let steps = r.read_u64()?;
+        lc_res.wrs = if wrs { Some(steps) } else { None };
+        Ok(())
+    }
+
+    //a stalling wrs wakes up when the reservation is lost or it times out
+    pub fn wrs_wakeup(&self, p: &Processor) -> bool {
+        let mut lc_res = self.lc_res.borrow_mut();
+        if let Some(steps) = lc_res.wrs {
+            if !lc_res.held(p) {
+                return true;
+            }
+            lc_res.wrs = Some(steps.saturating_sub(1));
+            steps <= 1
+        } else {
+            false
+        }
+    }
+}
+
+//zabha, zacas and zawrs are also disabled by clearing misa.a
+fn check_a_ext(p: &Processor, ext: IsaExt) -> Result<(), Exception> {
+    p.state().check_extension('a')?;
+    p.state().check_isa_ext(ext)
 }
 
 impl NoCsr for ExtensionA {}
 
+//sc already fails on an expired reservation, only the bus lock is released lazily here
 impl HasStepCb for ExtensionA {
     fn step_cb(&self, p: &Processor) {
         let mut lc_res = self.lc_res.borrow_mut();
-        if lc_res.valid {
-            if *p.state().insns_cnt().borrow() > lc_res.timestamp + 16 {
-                lc_res.valid = false;
-                p.load_store().release(p.state())
-            }
+        if lc_res.valid && !lc_res.active(p) {
+            lc_res.valid = false;
+            p.load_store().release(p.state())
         }
     }
 }
@@ -0,0 +1,428 @@
+use crate::prelude::*;
+use crate::processor::extensions::a::check_a_ext;
+use crate::processor::isa::IsaExt;
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+use std::cmp::{max, min};
+use std::num::Wrapping;
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b00001????????????000?????0101111")]
+#[derive(Debug)]
+struct AMOSWAPB();
+
+impl Execution for AMOSWAPB {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zabha)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let src = *p.state().xreg(self.rs2(p.state().ir())) as u8;
+        let data = p.load_store().amo_byte(p.state(), addr, |_| src, p.mmu())?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 8) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b00000????????????000?????0101111")]
+#[derive(Debug)]
+struct AMOADDB();
+
+impl Execution for AMOADDB {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zabha)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let src: Wrapping<u8> = Wrapping(*p.state().xreg(self.rs2(p.state().ir())) as u8);
+        let data =
+            p.load_store()
+                .amo_byte(p.state(), addr, |read| (src + Wrapping(read)).0, p.mmu())?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 8) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b00100????????????000?????0101111")]
+#[derive(Debug)]
+struct AMOXORB();
+
+impl Execution for AMOXORB {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zabha)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let src = *p.state().xreg(self.rs2(p.state().ir())) as u8;
+        let data = p
+            .load_store()
+            .amo_byte(p.state(), addr, |read| src ^ read, p.mmu())?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 8) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b01100????????????000?????0101111")]
+#[derive(Debug)]
+struct AMOANDB();
+
+impl Execution for AMOANDB {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zabha)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let src = *p.state().xreg(self.rs2(p.state().ir())) as u8;
+        let data = p
+            .load_store()
+            .amo_byte(p.state(), addr, |read| src & read, p.mmu())?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 8) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b01000????????????000?????0101111")]
+#[derive(Debug)]
+struct AMOORB();
+
+impl Execution for AMOORB {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zabha)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let src = *p.state().xreg(self.rs2(p.state().ir())) as u8;
+        let data = p
+            .load_store()
+            .amo_byte(p.state(), addr, |read| src | read, p.mmu())?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 8) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b10000????????????000?????0101111")]
+#[derive(Debug)]
+struct AMOMINB();
+
+impl Execution for AMOMINB {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zabha)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let src = *p.state().xreg(self.rs2(p.state().ir())) as u8 as i8;
+        let data =
+            p.load_store()
+                .amo_byte(p.state(), addr, |read| min(src, read as i8) as u8, p.mmu())?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 8) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b10100????????????000?????0101111")]
+#[derive(Debug)]
+struct AMOMAXB();
+
+impl Execution for AMOMAXB {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zabha)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let src = *p.state().xreg(self.rs2(p.state().ir())) as u8 as i8;
+        let data =
+            p.load_store()
+                .amo_byte(p.state(), addr, |read| max(src, read as i8) as u8, p.mmu())?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 8) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b11000????????????000?????0101111")]
+#[derive(Debug)]
+struct AMOMINUB();
+
+impl Execution for AMOMINUB {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zabha)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let src = *p.state().xreg(self.rs2(p.state().ir())) as u8;
+        let data = p
+            .load_store()
+            .amo_byte(p.state(), addr, |read| min(src, read), p.mmu())?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 8) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b11100????????????000?????0101111")]
+#[derive(Debug)]
+struct AMOMAXUB();
+
+impl Execution for AMOMAXUB {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zabha)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let src = *p.state().xreg(self.rs2(p.state().ir())) as u8;
+        let data = p
+            .load_store()
+            .amo_byte(p.state(), addr, |read| max(src, read), p.mmu())?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 8) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b00001????????????001?????0101111")]
+#[derive(Debug)]
+struct AMOSWAPH();
+
+impl Execution for AMOSWAPH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zabha)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let src = *p.state().xreg(self.rs2(p.state().ir())) as u16;
+        let data = p
+            .load_store()
+            .amo_half_word(p.state(), addr, |_| src, p.mmu())?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 16) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b00000????????????001?????0101111")]
+#[derive(Debug)]
+struct AMOADDH();
+
+impl Execution for AMOADDH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zabha)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let src: Wrapping<u16> = Wrapping(*p.state().xreg(self.rs2(p.state().ir())) as u16);
+        let data = p.load_store().amo_half_word(
+            p.state(),
+            addr,
+            |read| (src + Wrapping(read)).0,
+            p.mmu(),
+        )?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 16) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b00100????????????001?????0101111")]
+#[derive(Debug)]
+struct AMOXORH();
+
+impl Execution for AMOXORH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zabha)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let src = *p.state().xreg(self.rs2(p.state().ir())) as u16;
+        let data = p
+            .load_store()
+            .amo_half_word(p.state(), addr, |read| src ^ read, p.mmu())?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 16) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b01100????????????001?????0101111")]
+#[derive(Debug)]
+struct AMOANDH();
+
+impl Execution for AMOANDH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zabha)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let src = *p.state().xreg(self.rs2(p.state().ir())) as u16;
+        let data = p
+            .load_store()
+            .amo_half_word(p.state(), addr, |read| src & read, p.mmu())?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 16) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b01000????????????001?????0101111")]
+#[derive(Debug)]
+struct AMOORH();
+
+impl Execution for AMOORH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zabha)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let src = *p.state().xreg(self.rs2(p.state().ir())) as u16;
+        let data = p
+            .load_store()
+            .amo_half_word(p.state(), addr, |read| src | read, p.mmu())?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 16) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b10000????????????001?????0101111")]
+#[derive(Debug)]
+struct AMOMINH();
+
+impl Execution for AMOMINH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zabha)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let src = *p.state().xreg(self.rs2(p.state().ir())) as u16 as i16;
+        let data = p.load_store().amo_half_word(
+            p.state(),
+            addr,
+            |read| min(src, read as i16) as u16,
+            p.mmu(),
+        )?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 16) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b10100????????????001?????0101111")]
+#[derive(Debug)]
+struct AMOMAXH();
+
+impl Execution for AMOMAXH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zabha)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let src = *p.state().xreg(self.rs2(p.state().ir())) as u16 as i16;
+        let data = p.load_store().amo_half_word(
+            p.state(),
+            addr,
+            |read| max(src, read as i16) as u16,
+            p.mmu(),
+        )?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 16) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b11000????????????001?????0101111")]
+#[derive(Debug)]
+struct AMOMINUH();
+
+impl Execution for AMOMINUH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zabha)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let src = *p.state().xreg(self.rs2(p.state().ir())) as u16;
+        let data = p
+            .load_store()
+            .amo_half_word(p.state(), addr, |read| min(src, read), p.mmu())?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 16) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b11100????????????001?????0101111")]
+#[derive(Debug)]
+struct AMOMAXUH();
+
+impl Execution for AMOMAXUH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zabha)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let src = *p.state().xreg(self.rs2(p.state().ir())) as u16;
+        let data = p
+            .load_store()
+            .amo_half_word(p.state(), addr, |read| max(src, read), p.mmu())?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 16) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
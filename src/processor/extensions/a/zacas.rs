@@ -0,0 +1,186 @@
+use crate::prelude::*;
+use crate::processor::extensions::a::check_a_ext;
+use crate::processor::isa::IsaExt;
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+
+//amocas.d in rv32 and amocas.q use register pairs, rd and rs2 must be even
+fn check_pairs(p: &Processor) -> Result<(), Exception> {
+    let ir = *p.state().ir();
+    if (ir >> 7) & 1 != 0 || (ir >> 20) & 1 != 0 {
+        Err(Exception::IllegalInsn(ir))
+    } else {
+        Ok(())
+    }
+}
+
+//pair of x0 reads as 0
+fn read_pair(p: &Processor, id: InsnT) -> u128 {
+    if id == 0 {
+        0
+    } else {
+        let xlen = p.state().config().xlen.len();
+        (*p.state().xreg(id + 1) as u128) << xlen | *p.state().xreg(id) as u128
+    }
+}
+
+//pair of x0 is not written
+fn write_pair(p: &mut Processor, id: InsnT, value: u128) {
+    if id != 0 {
+        let xlen = p.state().config().xlen.len();
+        let mask = p.state().config().xlen.mask();
+        p.state_mut().set_xreg(id, value as RegT & mask);
+        p.state_mut()
+            .set_xreg(id + 1, (value >> xlen) as RegT & mask);
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b00101????????????000?????0101111")]
+#[derive(Debug)]
+struct AMOCASB();
+
+impl Execution for AMOCASB {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zabha)?;
+        check_a_ext(p, IsaExt::Zacas)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let cmp = *p.state().xreg(self.rd(p.state().ir())) as u8;
+        let src = *p.state().xreg(self.rs2(p.state().ir())) as u8;
+        let data = p.load_store().amo_byte(
+            p.state(),
+            addr,
+            |read| if read == cmp { src } else { read },
+            p.mmu(),
+        )?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 8) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b00101????????????001?????0101111")]
+#[derive(Debug)]
+struct AMOCASH();
+
+impl Execution for AMOCASH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zabha)?;
+        check_a_ext(p, IsaExt::Zacas)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let cmp = *p.state().xreg(self.rd(p.state().ir())) as u16;
+        let src = *p.state().xreg(self.rs2(p.state().ir())) as u16;
+        let data = p.load_store().amo_half_word(
+            p.state(),
+            addr,
+            |read| if read == cmp { src } else { read },
+            p.mmu(),
+        )?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 16) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b00101????????????010?????0101111")]
+#[derive(Debug)]
+struct AMOCASW();
+
+impl Execution for AMOCASW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zacas)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let cmp = *p.state().xreg(self.rd(p.state().ir())) as u32;
+        let src = *p.state().xreg(self.rs2(p.state().ir())) as u32;
+        let data = p.load_store().amo_word(
+            p.state(),
+            addr,
+            |read| if read == cmp { src } else { read },
+            p.mmu(),
+        )?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 32) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b00101????????????011?????0101111")]
+#[derive(Debug)]
+struct AMOCASD();
+
+impl Execution for AMOCASD {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_a_ext(p, IsaExt::Zacas)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        if p.state().config().xlen == XLen::X64 {
+            let cmp = *p.state().xreg(rd) as u64;
+            let src = *p.state().xreg(self.rs2(p.state().ir())) as u64;
+            let data = p.load_store().amo_double_word(
+                p.state(),
+                addr,
+                |read| if read == cmp { src } else { read },
+                p.mmu(),
+            )?;
+            p.state_mut().set_xreg(rd, data);
+        } else {
+            check_pairs(p)?;
+            let cmp = read_pair(p, rd) as u64;
+            let src = read_pair(p, self.rs2(p.state().ir())) as u64;
+            let data = p.load_store().amo_double_word(
+                p.state(),
+                addr,
+                |read| if read == cmp { src } else { read },
+                p.mmu(),
+            )?;
+            write_pair(p, rd, data as u128);
+        }
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b00101????????????100?????0101111")]
+#[derive(Debug)]
+struct AMOCASQ();
+
+impl Execution for AMOCASQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_xlen(XLen::X64)?;
+        check_a_ext(p, IsaExt::Zacas)?;
+        check_pairs(p)?;
+        let addr = p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let cmp = read_pair(p, rd);
+        let src = read_pair(p, self.rs2(p.state().ir()));
+        let data = p.load_store().amo_quad_word(
+            p.state(),
+            addr,
+            |read| if read == cmp { src } else { read },
+            p.mmu(),
+        )?;
+        write_pair(p, rd, data);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+use crate::prelude::*;
+use crate::processor::extensions::a::check_a_ext;
+use crate::processor::extensions::Extension;
+use crate::processor::isa::IsaExt;
+use crate::processor::privilege::Privilege;
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+
+//steps of wrs.sto before it times out, wrs.nto never does in practice
+const WRS_STO_STEPS: u64 = 1024;
+const WRS_NTO_STEPS: u64 = u64::MAX;
+
+//stall like wfi while the lr reservation is held and no interrupt is pending,
+//wrs is executed again when waking up and retires once it needn't stall
+fn wait_on_reservation(p: &mut Processor, sto: bool) -> Result<(), Exception> {
+    check_a_ext(p, IsaExt::Zawrs)?;
+    let steps = if sto { WRS_STO_STEPS } else { WRS_NTO_STEPS };
+    let stall = if let Extension::A(ref a) = p.state().get_extension('a') {
+        let mut lc_res = a.lc_res.borrow_mut();
+        let m = p.state().priv_m();
+        let stall = lc_res.wrs != Some(0) && lc_res.held(p) && m.mip().get() & m.mie().get() == 0;
+        //wrs.nto with tw or vtw traps instead of stalling
+        if stall && !sto {
+            if m.mstatus().tw() != 0 && *p.state().privilege() != Privilege::M {
+                lc_res.wrs = None;
+                return Err(Exception::IllegalInsn(*p.state().ir()));
+            }
+            if p.state().virt() && p.state().priv_h()?.hstatus().vtw() != 0 {
+                lc_res.wrs = None;
+                return Err(Exception::VirtualInsn(*p.state().ir()));
+            }
+        }
+        lc_res.wrs = if stall {
+            Some(lc_res.wrs.unwrap_or(steps))
+        } else {
+            None
+        };
+        stall
+    } else {
+        return Err(Exception::IllegalInsn(*p.state().ir()));
+    };
+    if stall {
+        p.state_mut().set_wfi(true);
+    } else {
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_pc(pc);
+        p.state_mut().set_wfi(false);
+    }
+    Ok(())
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b00000000110100000000000001110011")]
+#[derive(Debug)]
+struct WRSNTO();
+
+impl Execution for WRSNTO {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        wait_on_reservation(p, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b00000001110100000000000001110011")]
+#[derive(Debug)]
+struct WRSSTO();
+
+impl Execution for WRSSTO {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        wait_on_reservation(p, true)
+    }
+}
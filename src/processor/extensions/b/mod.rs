@@ -0,0 +1,46 @@
+use crate::prelude::*;
+use crate::processor::extensions::{NoCsr, NoStepCb};
+use crate::processor::isa::IsaExt;
+use crate::processor::trap::Exception;
+use crate::processor::{Processor, ProcessorState};
+
+mod zba;
+mod zbb;
+mod zbc;
+mod zbs;
+
+//zba, zbb, zbc and zbs are enabled individually by isa
+pub struct ExtensionB {}
+
+impl ExtensionB {
+    pub fn new(_: &ProcessorState) -> ExtensionB {
+        ExtensionB {}
+    }
+}
+
+impl NoCsr for ExtensionB {}
+
+impl NoStepCb for ExtensionB {}
+
+//shamt of immediate shift/rotate/single-bit insns, shamt[5] is reserved in rv32
+fn shamt(p: &Processor) -> Result<RegT, Exception> {
+    let shamt = ((*p.state().ir() >> 20) & 0x3f) as RegT;
+    if shamt >> p.state().config().xlen.len().trailing_zeros() != 0 {
+        return Err(Exception::IllegalInsn(*p.state().ir()));
+    }
+    Ok(shamt)
+}
+
+//rotates, andn, orn, xnor, rev8 and pack are shared by zbb and zbkb
+fn check_zbb_zbkb(p: &Processor) -> Result<(), Exception> {
+    p.state()
+        .check_isa_ext(IsaExt::Zbb)
+        .or_else(|_| p.state().check_isa_ext(IsaExt::Zbkb))
+}
+
+//clmul and clmulh are shared by zbc and zbkc
+fn check_zbc_zbkc(p: &Processor) -> Result<(), Exception> {
+    p.state()
+        .check_isa_ext(IsaExt::Zbc)
+        .or_else(|_| p.state().check_isa_ext(IsaExt::Zbkc))
+}
@@ -0,0 +1,170 @@
+use crate::prelude::*;
+use crate::processor::extensions::b::shamt;
+use crate::processor::isa::IsaExt;
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010000??????????010?????0110011")]
+#[derive(Debug)]
+struct SH1ADD();
+
+impl Execution for SH1ADD {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zba)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = rs2.wrapping_add(rs1 << 1) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010000??????????100?????0110011")]
+#[derive(Debug)]
+struct SH2ADD();
+
+impl Execution for SH2ADD {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zba)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = rs2.wrapping_add(rs1 << 2) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010000??????????110?????0110011")]
+#[derive(Debug)]
+struct SH3ADD();
+
+impl Execution for SH3ADD {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zba)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = rs2.wrapping_add(rs1 << 3) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0000100??????????000?????0111011")]
+#[derive(Debug)]
+struct ADDUW();
+
+impl Execution for ADDUW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zba)?;
+        p.state().check_xlen(XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = rs2.wrapping_add(rs1 & 0xffff_ffff);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010000??????????010?????0111011")]
+#[derive(Debug)]
+struct SH1ADDUW();
+
+impl Execution for SH1ADDUW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zba)?;
+        p.state().check_xlen(XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = rs2.wrapping_add((rs1 & 0xffff_ffff) << 1);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010000??????????100?????0111011")]
+#[derive(Debug)]
+struct SH2ADDUW();
+
+impl Execution for SH2ADDUW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zba)?;
+        p.state().check_xlen(XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = rs2.wrapping_add((rs1 & 0xffff_ffff) << 2);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010000??????????110?????0111011")]
+#[derive(Debug)]
+struct SH3ADDUW();
+
+impl Execution for SH3ADDUW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zba)?;
+        p.state().check_xlen(XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = rs2.wrapping_add((rs1 & 0xffff_ffff) << 3);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b000010???????????001?????0011011")]
+#[derive(Debug)]
+struct SLLIUW();
+
+impl Execution for SLLIUW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zba)?;
+        p.state().check_xlen(XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let shamt = shamt(p)?;
+        let rd = self.rd(p.state().ir());
+        let value = (rs1 & 0xffff_ffff) << shamt;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
@@ -0,0 +1,570 @@
+use crate::prelude::*;
+use crate::processor::extensions::b::{check_zbb_zbkb, shamt};
+use crate::processor::isa::IsaExt;
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+
+fn rotate_left(value: RegT, shamt: u32, xlen: usize) -> RegT {
+    if xlen == 32 {
+        (value as u32).rotate_left(shamt & 0x1f) as RegT
+    } else {
+        value.rotate_left(shamt & 0x3f)
+    }
+}
+
+fn rotate_right(value: RegT, shamt: u32, xlen: usize) -> RegT {
+    if xlen == 32 {
+        (value as u32).rotate_right(shamt & 0x1f) as RegT
+    } else {
+        value.rotate_right(shamt & 0x3f)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0100000??????????111?????0110011")]
+#[derive(Debug)]
+struct ANDN();
+
+impl Execution for ANDN {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zbb_zbkb(p)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = (rs1 & !rs2) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0100000??????????110?????0110011")]
+#[derive(Debug)]
+struct ORN();
+
+impl Execution for ORN {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zbb_zbkb(p)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = (rs1 | !rs2) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0100000??????????100?????0110011")]
+#[derive(Debug)]
+struct XNOR();
+
+impl Execution for XNOR {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zbb_zbkb(p)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = !(rs1 ^ rs2) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b011000000000?????001?????0010011")]
+#[derive(Debug)]
+struct CLZ();
+
+impl Execution for CLZ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbb)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let xlen = p.state().config().xlen.len();
+        let rd = self.rd(p.state().ir());
+        let value = (rs1.leading_zeros() - (64 - xlen) as u32) as RegT;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b011000000001?????001?????0010011")]
+#[derive(Debug)]
+struct CTZ();
+
+impl Execution for CTZ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbb)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let xlen = p.state().config().xlen.len();
+        let rd = self.rd(p.state().ir());
+        let value = rs1.trailing_zeros().min(xlen as u32) as RegT;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b011000000010?????001?????0010011")]
+#[derive(Debug)]
+struct CPOP();
+
+impl Execution for CPOP {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbb)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = rs1.count_ones() as RegT;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b011000000000?????001?????0011011")]
+#[derive(Debug)]
+struct CLZW();
+
+impl Execution for CLZW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbb)?;
+        p.state().check_xlen(XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = (rs1 as u32).leading_zeros() as RegT;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b011000000001?????001?????0011011")]
+#[derive(Debug)]
+struct CTZW();
+
+impl Execution for CTZW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbb)?;
+        p.state().check_xlen(XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = (rs1 as u32).trailing_zeros() as RegT;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b011000000010?????001?????0011011")]
+#[derive(Debug)]
+struct CPOPW();
+
+impl Execution for CPOPW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbb)?;
+        p.state().check_xlen(XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = (rs1 as u32).count_ones() as RegT;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0000101??????????110?????0110011")]
+#[derive(Debug)]
+struct MAX();
+
+impl Execution for MAX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbb)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let xlen = p.state().config().xlen.len();
+        let rd = self.rd(p.state().ir());
+        let value = if (sext(rs1, xlen) as SRegT) < (sext(rs2, xlen) as SRegT) {
+            rs2
+        } else {
+            rs1
+        };
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0000101??????????111?????0110011")]
+#[derive(Debug)]
+struct MAXU();
+
+impl Execution for MAXU {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbb)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = rs1.max(rs2);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0000101??????????100?????0110011")]
+#[derive(Debug)]
+struct MIN();
+
+impl Execution for MIN {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbb)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let xlen = p.state().config().xlen.len();
+        let rd = self.rd(p.state().ir());
+        let value = if (sext(rs1, xlen) as SRegT) < (sext(rs2, xlen) as SRegT) {
+            rs1
+        } else {
+            rs2
+        };
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0000101??????????101?????0110011")]
+#[derive(Debug)]
+struct MINU();
+
+impl Execution for MINU {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbb)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = rs1.min(rs2);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b011000000100?????001?????0010011")]
+#[derive(Debug)]
+struct SEXTB();
+
+impl Execution for SEXTB {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbb)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = sext(rs1 & 0xff, 8) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b011000000101?????001?????0010011")]
+#[derive(Debug)]
+struct SEXTH();
+
+impl Execution for SEXTH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbb)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = sext(rs1 & 0xffff, 16) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+//zext.h of zbb is pack rd, rs1, x0 in rv32 and packw rd, rs1, x0 in rv64
+fn check_pack(p: &Processor, xlen: XLen) -> Result<(), Exception> {
+    if (*p.state().ir() >> 20) & 0x1f == 0 && p.state().config().xlen == xlen {
+        check_zbb_zbkb(p)
+    } else {
+        p.state().check_isa_ext(IsaExt::Zbkb)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0000100??????????100?????0110011")]
+#[derive(Debug)]
+struct PACK();
+
+impl Execution for PACK {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_pack(p, XLen::X32)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let half = p.state().config().xlen.len() >> 1;
+        let rd = self.rd(p.state().ir());
+        let value = (rs2 << half | rs1 & ((1 << half) - 1)) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0000100??????????100?????0111011")]
+#[derive(Debug)]
+struct PACKW();
+
+impl Execution for PACKW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_xlen(XLen::X64)?;
+        check_pack(p, XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = sext((rs2 & 0xffff) << 16 | rs1 & 0xffff, 32) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0110000??????????001?????0110011")]
+#[derive(Debug)]
+struct ROL();
+
+impl Execution for ROL {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zbb_zbkb(p)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let xlen = p.state().config().xlen.len();
+        let rd = self.rd(p.state().ir());
+        let value = rotate_left(rs1, rs2 as u32, xlen);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0110000??????????101?????0110011")]
+#[derive(Debug)]
+struct ROR();
+
+impl Execution for ROR {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zbb_zbkb(p)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let xlen = p.state().config().xlen.len();
+        let rd = self.rd(p.state().ir());
+        let value = rotate_right(rs1, rs2 as u32, xlen);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b011000???????????101?????0010011")]
+#[derive(Debug)]
+struct RORI();
+
+impl Execution for RORI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zbb_zbkb(p)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let shamt = shamt(p)?;
+        let xlen = p.state().config().xlen.len();
+        let rd = self.rd(p.state().ir());
+        let value = rotate_right(rs1, shamt as u32, xlen);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0110000??????????001?????0111011")]
+#[derive(Debug)]
+struct ROLW();
+
+impl Execution for ROLW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zbb_zbkb(p)?;
+        p.state().check_xlen(XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = sext((rs1 as u32).rotate_left(rs2 as u32) as RegT, 32);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0110000??????????101?????0111011")]
+#[derive(Debug)]
+struct RORW();
+
+impl Execution for RORW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zbb_zbkb(p)?;
+        p.state().check_xlen(XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = sext((rs1 as u32).rotate_right(rs2 as u32) as RegT, 32);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b0110000??????????101?????0011011")]
+#[derive(Debug)]
+struct RORIW();
+
+impl Execution for RORIW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zbb_zbkb(p)?;
+        p.state().check_xlen(XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let shamt = shamt(p)?;
+        let rd = self.rd(p.state().ir());
+        let value = sext((rs1 as u32).rotate_right(shamt as u32) as RegT, 32);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b001010000111?????101?????0010011")]
+#[derive(Debug)]
+struct ORCB();
+
+impl Execution for ORCB {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbb)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = (0..8).fold(0, |acc, i| {
+            if (rs1 >> (i << 3)) & 0xff != 0 {
+                acc | (0xff << (i << 3))
+            } else {
+                acc
+            }
+        }) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b011010011000?????101?????0010011")]
+#[derive(Debug)]
+struct REV832();
+
+impl Execution for REV832 {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zbb_zbkb(p)?;
+        p.state().check_xlen(XLen::X32)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = (rs1 as u32).swap_bytes() as RegT;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b011010111000?????101?????0010011")]
+#[derive(Debug)]
+struct REV864();
+
+impl Execution for REV864 {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zbb_zbkb(p)?;
+        p.state().check_xlen(XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = rs1.swap_bytes();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use crate::prelude::*;
+use crate::processor::extensions::b::check_zbc_zbkc;
+use crate::processor::isa::IsaExt;
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+
+//carry-less product of xlen bits operands
+fn clmul(a: RegT, b: RegT, xlen: usize) -> u128 {
+    (0..xlen)
+        .filter(|i| (b >> i) & 1 == 1)
+        .fold(0, |acc, i| acc ^ ((a as u128) << i))
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0000101??????????001?????0110011")]
+#[derive(Debug)]
+struct CLMUL();
+
+impl Execution for CLMUL {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zbc_zbkc(p)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let xlen = p.state().config().xlen.len();
+        let rd = self.rd(p.state().ir());
+        let value = clmul(rs1, rs2, xlen) as RegT & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0000101??????????011?????0110011")]
+#[derive(Debug)]
+struct CLMULH();
+
+impl Execution for CLMULH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zbc_zbkc(p)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let xlen = p.state().config().xlen.len();
+        let rd = self.rd(p.state().ir());
+        let value = (clmul(rs1, rs2, xlen) >> xlen) as RegT & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0000101??????????010?????0110011")]
+#[derive(Debug)]
+struct CLMULR();
+
+impl Execution for CLMULR {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbc)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let xlen = p.state().config().xlen.len();
+        let rd = self.rd(p.state().ir());
+        let value = (clmul(rs1, rs2, xlen) >> (xlen - 1)) as RegT & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
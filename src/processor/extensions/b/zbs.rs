@@ -0,0 +1,173 @@
+use crate::prelude::*;
+use crate::processor::extensions::b::shamt;
+use crate::processor::isa::IsaExt;
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0100100??????????001?????0110011")]
+#[derive(Debug)]
+struct BCLR();
+
+impl Execution for BCLR {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbs)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let xlen = p.state().config().xlen.len();
+        let idx = rs2 & (xlen as RegT - 1);
+        let rd = self.rd(p.state().ir());
+        let value = (rs1 & !(1 << idx)) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b010010???????????001?????0010011")]
+#[derive(Debug)]
+struct BCLRI();
+
+impl Execution for BCLRI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbs)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let shamt = shamt(p)?;
+        let rd = self.rd(p.state().ir());
+        let value = (rs1 & !(1 << shamt)) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0100100??????????101?????0110011")]
+#[derive(Debug)]
+struct BEXT();
+
+impl Execution for BEXT {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbs)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let xlen = p.state().config().xlen.len();
+        let idx = rs2 & (xlen as RegT - 1);
+        let rd = self.rd(p.state().ir());
+        let value = ((rs1 >> idx) & 1) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b010010???????????101?????0010011")]
+#[derive(Debug)]
+struct BEXTI();
+
+impl Execution for BEXTI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbs)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let shamt = shamt(p)?;
+        let rd = self.rd(p.state().ir());
+        let value = ((rs1 >> shamt) & 1) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0110100??????????001?????0110011")]
+#[derive(Debug)]
+struct BINV();
+
+impl Execution for BINV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbs)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let xlen = p.state().config().xlen.len();
+        let idx = rs2 & (xlen as RegT - 1);
+        let rd = self.rd(p.state().ir());
+        let value = (rs1 ^ (1 << idx)) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b011010???????????001?????0010011")]
+#[derive(Debug)]
+struct BINVI();
+
+impl Execution for BINVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbs)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let shamt = shamt(p)?;
+        let rd = self.rd(p.state().ir());
+        let value = (rs1 ^ (1 << shamt)) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010100??????????001?????0110011")]
+#[derive(Debug)]
+struct BSET();
+
+impl Execution for BSET {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbs)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let xlen = p.state().config().xlen.len();
+        let idx = rs2 & (xlen as RegT - 1);
+        let rd = self.rd(p.state().ir());
+        let value = (rs1 | (1 << idx)) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b001010???????????001?????0010011")]
+#[derive(Debug)]
+struct BSETI();
+
+impl Execution for BSETI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbs)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let shamt = shamt(p)?;
+        let rd = self.rd(p.state().ir());
+        let value = (rs1 | (1 << shamt)) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
@@ -0,0 +1,20 @@
+use crate::prelude::*;
+csr_map! {
+pub CCsrs(0x0, 0xfff) {
+    jvt(RW):Jvt, 0x017;
+}
+}
+
+define_csr! {
+Jvt {
+    fields{
+        mode(RW):5, 0;
+    },
+    fields32{
+        base(RW):31, 6;
+    },
+    fields64{
+        base(RW):63, 6;
+    }
+}
+}
@@ -1,6 +1,8 @@
 use crate::prelude::*;
+use crate::processor::extensions::c::zcm;
 use crate::processor::extensions::f::float::FloatInsn;
 use crate::processor::extensions::f::{FLen, FRegT};
+use crate::processor::isa::IsaExt;
 use crate::processor::trap::Exception;
 use crate::processor::Processor;
 use std::num::Wrapping;
@@ -58,6 +60,7 @@ impl CLDSPCFLWSP {
         Ok(())
     }
     fn execute_c_lwsp(&self, p: &mut Processor, base: Wrapping<RegT>) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zcf)?;
         let f = self.get_f_ext(p)?;
         let offset_7_6: RegT = (self.imm(p.state().ir()) & 0x3) as RegT;
         let offset_5: RegT = ((self.imm(p.state().ir()) >> 5) & 0x1) as RegT;
@@ -102,6 +105,7 @@ impl Execution for CFLDSP {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('c')?;
         p.state().check_extension('d')?;
+        p.state().check_isa_ext(IsaExt::Zcd)?;
         let f = self.get_f_ext(p)?;
         let base: Wrapping<RegT> = Wrapping(*p.state().xreg(2));
         let offset_8_6: RegT = (self.imm(p.state().ir()) & 0x7) as RegT;
@@ -162,6 +166,7 @@ impl CSDSPFSWSP {
             .store_double_word(p.state(), &(base + offset).0, src, p.mmu())
     }
     fn execute_c_fswsp(&self, p: &mut Processor, base: Wrapping<RegT>) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zcf)?;
         let f = self.get_f_ext(p)?;
         let offset_7_6: RegT = (self.imm(p.state().ir()) & 0x3) as RegT;
         let offset_5_2: RegT = ((self.imm(p.state().ir()) >> 2) & 0xf) as RegT;
@@ -197,14 +202,12 @@ impl Execution for CSDSPFSWSP {
 #[format(CSS)]
 #[code("32b????????????????101???????????10")]
 #[derive(Debug)]
-struct CFSDSP();
+struct CFSDSPCM();
 
-impl FloatInsn for CFSDSP {}
-
-impl Execution for CFSDSP {
-    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
-        p.state().check_extension('c')?;
+impl CFSDSPCM {
+    fn execute_c_fsdsp(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
+        p.state().check_isa_ext(IsaExt::Zcd)?;
         let f = self.get_f_ext(p)?;
         let base: Wrapping<RegT> = Wrapping(*p.state().xreg(2));
         let offset_8_6: RegT = (self.imm(p.state().ir()) & 0x7) as RegT;
@@ -223,6 +226,21 @@ impl Execution for CFSDSP {
     }
 }
 
+impl FloatInsn for CFSDSPCM {}
+
+impl Execution for CFSDSPCM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('c')?;
+        //zcmp and zcmt are exclusive with zcd by isa
+        let isa = &p.state().config().isa;
+        if isa.has_ext(IsaExt::Zcmp) || isa.has_ext(IsaExt::Zcmt) {
+            zcm::execute(p)
+        } else {
+            self.execute_c_fsdsp(p)
+        }
+    }
+}
+
 #[derive(Instruction)]
 #[format(CL)]
 #[code("32b????????????????010???????????00")]
@@ -269,6 +287,7 @@ impl CLDFLW {
         Ok(())
     }
     fn execute_c_flw(&self, p: &mut Processor, base: Wrapping<RegT>) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zcf)?;
         let f = self.get_f_ext(p)?;
         let offset_6: RegT = (self.imm(p.state().ir()) & 0x1) as RegT;
         let offset_5_3: RegT = ((self.imm(p.state().ir()) >> 2) & 0x7) as RegT;
@@ -313,6 +332,7 @@ impl Execution for CFLD {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('c')?;
         p.state().check_extension('d')?;
+        p.state().check_isa_ext(IsaExt::Zcd)?;
         let f = self.get_f_ext(p)?;
         let base: Wrapping<RegT> = Wrapping(*p.state().xreg(self.rs1(p.state().ir())));
         let offset_7_6: RegT = (self.imm(p.state().ir()) & 0x3) as RegT;
@@ -373,6 +393,7 @@ impl CSDFSW {
             .store_double_word(p.state(), &(base + offset).0, src, p.mmu())
     }
     fn execute_c_fsw(&self, p: &mut Processor, base: Wrapping<RegT>) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zcf)?;
         let f = self.get_f_ext(p)?;
         let offset_6: RegT = (self.imm(p.state().ir()) & 0x1) as RegT;
         let offset_5_3: RegT = ((self.imm(p.state().ir()) >> 2) & 0x7) as RegT;
@@ -417,6 +438,7 @@ impl Execution for CFSD {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('c')?;
         p.state().check_extension('d')?;
+        p.state().check_isa_ext(IsaExt::Zcd)?;
         let f = self.get_f_ext(p)?;
         let base: Wrapping<RegT> = Wrapping(*p.state().xreg(self.rs1(p.state().ir())));
         let offset_7_6: RegT = (self.imm(p.state().ir()) & 0x3) as RegT;
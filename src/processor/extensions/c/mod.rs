@@ -1,16 +1,60 @@
-use crate::processor::extensions::{NoCsr, NoStepCb};
+use crate::prelude::*;
+use crate::processor::extensions::{HasCsr, NoStepCb};
+use crate::processor::isa::IsaExt;
 use crate::processor::ProcessorState;
+use crate::snapshot::{SnapshotReader, SnapshotWriter};
+use std::io;
+use std::io::{Read, Write};
 
+pub mod csrs;
 mod insns;
+mod zcb;
+mod zcm;
 
-pub struct ExtensionC {}
+use csrs::CCsrs;
+
+//zca, zcb, zcd, zcf, zcmp and zcmt are enabled individually by isa
+pub struct ExtensionC {
+    csrs: CCsrs,
+}
 
 impl ExtensionC {
-    pub fn new(_: &ProcessorState) -> ExtensionC {
-        ExtensionC {}
+    pub fn new(state: &ProcessorState) -> ExtensionC {
+        let e = ExtensionC {
+            csrs: CCsrs::new(state.config().xlen.len()),
+        };
+        //only jump table mode is supported
+        e.csrs.jvt_mut().set_mode_transform(|_| 0);
+        e
+    }
+
+    pub fn jvt_base(&self) -> RegT {
+        self.csrs.jvt().base() << 6
+    }
+
+    pub fn save_snapshot<W: Write>(&self, w: &mut SnapshotWriter<W>) -> io::Result<()> {
+        w.write_u64(self.csrs.jvt().get())
+    }
+
+    pub fn restore_snapshot<R: Read>(&self, r: &mut SnapshotReader<R>) -> io::Result<()> {
+        self.csrs.jvt_mut().set(r.read_u64()?);
+        Ok(())
     }
 }
 
-impl NoCsr for ExtensionC {}
+impl HasCsr for ExtensionC {
+    fn csr_write(&self, state: &ProcessorState, addr: InsnT, value: RegT) -> Option<()> {
+        if !state.config().isa.has_ext(IsaExt::Zcmt) {
+            return None;
+        }
+        self.csrs.write(addr as u64, value)
+    }
+    fn csr_read(&self, state: &ProcessorState, addr: InsnT) -> Option<RegT> {
+        if !state.config().isa.has_ext(IsaExt::Zcmt) {
+            return None;
+        }
+        self.csrs.read(addr as u64)
+    }
+}
 
 impl NoStepCb for ExtensionC {}
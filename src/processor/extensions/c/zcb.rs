@@ -0,0 +1,266 @@
+use crate::prelude::*;
+use crate::processor::isa::IsaExt;
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+use std::num::Wrapping;
+
+fn check_zcb(p: &Processor) -> Result<(), Exception> {
+    p.state().check_extension('c')?;
+    p.state().check_isa_ext(IsaExt::Zcb)
+}
+
+#[derive(Instruction)]
+#[format(CL)]
+#[code("32b????????????????100000????????00")]
+#[derive(Debug)]
+struct CLBU();
+
+impl Execution for CLBU {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zcb(p)?;
+        let base: Wrapping<RegT> = Wrapping(*p.state().xreg(self.rs1(p.state().ir())));
+        let offset_0: RegT = ((self.imm(p.state().ir()) >> 1) & 0x1) as RegT;
+        let offset_1: RegT = (self.imm(p.state().ir()) & 0x1) as RegT;
+        let offset: Wrapping<RegT> = Wrapping(offset_1 << 1 | offset_0);
+        let mut data: u8 = 0;
+        p.load_store()
+            .load_byte(p.state(), &(base + offset).0, &mut data, p.mmu())?;
+        let rd = self.rd(p.state().ir());
+        let value = data as RegT;
+        let pc = *p.state().pc() + 2;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(CL)]
+#[code("32b????????????????100001???0????00")]
+#[derive(Debug)]
+struct CLHU();
+
+impl Execution for CLHU {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zcb(p)?;
+        let base: Wrapping<RegT> = Wrapping(*p.state().xreg(self.rs1(p.state().ir())));
+        let offset_1: RegT = (self.imm(p.state().ir()) & 0x1) as RegT;
+        let offset: Wrapping<RegT> = Wrapping(offset_1 << 1);
+        let mut data: u16 = 0;
+        p.load_store()
+            .load_half_word(p.state(), &(base + offset).0, &mut data, p.mmu())?;
+        let rd = self.rd(p.state().ir());
+        let value = data as RegT;
+        let pc = *p.state().pc() + 2;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(CL)]
+#[code("32b????????????????100001???1????00")]
+#[derive(Debug)]
+struct CLH();
+
+impl Execution for CLH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zcb(p)?;
+        let base: Wrapping<RegT> = Wrapping(*p.state().xreg(self.rs1(p.state().ir())));
+        let offset_1: RegT = (self.imm(p.state().ir()) & 0x1) as RegT;
+        let offset: Wrapping<RegT> = Wrapping(offset_1 << 1);
+        let mut data: u16 = 0;
+        p.load_store()
+            .load_half_word(p.state(), &(base + offset).0, &mut data, p.mmu())?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data as RegT, 16) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 2;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(CS)]
+#[code("32b????????????????100010????????00")]
+#[derive(Debug)]
+struct CSB();
+
+impl Execution for CSB {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zcb(p)?;
+        let base: Wrapping<RegT> = Wrapping(*p.state().xreg(self.rs1(p.state().ir())));
+        let offset_0: RegT = ((self.imm(p.state().ir()) >> 1) & 0x1) as RegT;
+        let offset_1: RegT = (self.imm(p.state().ir()) & 0x1) as RegT;
+        let offset: Wrapping<RegT> = Wrapping(offset_1 << 1 | offset_0);
+        let src = *p.state().xreg(self.rs2(p.state().ir())) as u8;
+        p.load_store()
+            .store_byte(p.state(), &(base + offset).0, &src, p.mmu())?;
+        let pc = *p.state().pc() + 2;
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(CS)]
+#[code("32b????????????????100011???0????00")]
+#[derive(Debug)]
+struct CSH();
+
+impl Execution for CSH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zcb(p)?;
+        let base: Wrapping<RegT> = Wrapping(*p.state().xreg(self.rs1(p.state().ir())));
+        let offset_1: RegT = (self.imm(p.state().ir()) & 0x1) as RegT;
+        let offset: Wrapping<RegT> = Wrapping(offset_1 << 1);
+        let src = *p.state().xreg(self.rs2(p.state().ir())) as u16;
+        p.load_store()
+            .store_half_word(p.state(), &(base + offset).0, &src, p.mmu())?;
+        let pc = *p.state().pc() + 2;
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(CA)]
+#[code("32b????????????????100111???1100001")]
+#[derive(Debug)]
+struct CZEXTB();
+
+impl Execution for CZEXTB {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zcb(p)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = rs1 & 0xff;
+        let pc = *p.state().pc() + 2;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(CA)]
+#[code("32b????????????????100111???1100101")]
+#[derive(Debug)]
+struct CSEXTB();
+
+impl Execution for CSEXTB {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zcb(p)?;
+        p.state().check_isa_ext(IsaExt::Zbb)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = sext(rs1, 8) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 2;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(CA)]
+#[code("32b????????????????100111???1101001")]
+#[derive(Debug)]
+struct CZEXTH();
+
+impl Execution for CZEXTH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zcb(p)?;
+        p.state().check_isa_ext(IsaExt::Zbb)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = rs1 & 0xffff;
+        let pc = *p.state().pc() + 2;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(CA)]
+#[code("32b????????????????100111???1101101")]
+#[derive(Debug)]
+struct CSEXTH();
+
+impl Execution for CSEXTH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zcb(p)?;
+        p.state().check_isa_ext(IsaExt::Zbb)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = sext(rs1, 16) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 2;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(CA)]
+#[code("32b????????????????100111???1110001")]
+#[derive(Debug)]
+struct CZEXTW();
+
+impl Execution for CZEXTW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zcb(p)?;
+        p.state().check_xlen(XLen::X64)?;
+        p.state().check_isa_ext(IsaExt::Zba)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = rs1 & 0xffff_ffff;
+        let pc = *p.state().pc() + 2;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(CA)]
+#[code("32b????????????????100111???1110101")]
+#[derive(Debug)]
+struct CNOT();
+
+impl Execution for CNOT {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zcb(p)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = !rs1 & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 2;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(CA)]
+#[code("32b????????????????100111???10???01")]
+#[derive(Debug)]
+struct CMUL();
+
+impl Execution for CMUL {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_zcb(p)?;
+        p.state().check_extension('m')?;
+        let rs1: Wrapping<RegT> = Wrapping(*p.state().xreg(self.rs1(p.state().ir())));
+        let rs2: Wrapping<RegT> = Wrapping(*p.state().xreg(self.rs2(p.state().ir())));
+        let rd = self.rd(p.state().ir());
+        let value = (rs1 * rs2).0 & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 2;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
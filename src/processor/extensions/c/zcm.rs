@@ -0,0 +1,362 @@
+use crate::prelude::*;
+use crate::processor::extensions::Extension;
+use crate::processor::isa::IsaExt;
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+use std::num::Wrapping;
+
+//registers of cm.push/cm.pop from the top of the stack frame, rlist selects the last ones
+const RLIST: [InsnT; 13] = [27, 26, 25, 24, 23, 22, 21, 20, 19, 18, 9, 8, 1];
+
+//zcmp and zcmt reuse encodings of c.fsdsp
+pub fn execute(p: &mut Processor) -> Result<(), Exception> {
+    match (*p.state().ir() >> 10) & 0x7 {
+        0 => execute_cm_jt(p),
+        3 => execute_cm_mv(p),
+        6 | 7 => execute_cm_push_pop(p),
+        _ => Err(Exception::IllegalInsn(*p.state().ir())),
+    }
+}
+
+fn load_reg(p: &mut Processor, addr: RegT, id: InsnT) -> Result<(), Exception> {
+    let value = if p.state().config().xlen == XLen::X64 {
+        let mut data: u64 = 0;
+        p.load_store()
+            .load_double_word(p.state(), &addr, &mut data, p.mmu())?;
+        data as RegT
+    } else {
+        let mut data: u32 = 0;
+        p.load_store()
+            .load_word(p.state(), &addr, &mut data, p.mmu())?;
+        data as RegT
+    };
+    p.state_mut().set_xreg(id, value);
+    Ok(())
+}
+
+fn store_reg(p: &Processor, addr: RegT, id: InsnT) -> Result<(), Exception> {
+    let src = *p.state().xreg(id);
+    if p.state().config().xlen == XLen::X64 {
+        p.load_store()
+            .store_double_word(p.state(), &addr, &src, p.mmu())
+    } else {
+        p.load_store()
+            .store_word(p.state(), &addr, &(src as u32), p.mmu())
+    }
+}
+
+//cm.push, cm.pop, cm.popretz and cm.popret
+fn execute_cm_push_pop(p: &mut Processor) -> Result<(), Exception> {
+    p.state().check_isa_ext(IsaExt::Zcmp)?;
+    let ir = *p.state().ir();
+    let rlist = (ir >> 4) & 0xf;
+    if rlist < 4 {
+        return Err(Exception::IllegalInsn(ir));
+    }
+    //rlist 15 is {ra, s0-s11}, there is no {ra, s0-s10}
+    let regs = &RLIST[RLIST.len() - if rlist == 15 { 13 } else { rlist as usize - 3 }..];
+    let bytes = (p.state().config().xlen.len() >> 3) as RegT;
+    let spimm = ((ir >> 2) & 0x3) as RegT;
+    let stack_adj = Wrapping(((regs.len() as RegT * bytes + 15) & !0xf) + spimm * 16);
+    let sp: Wrapping<RegT> = Wrapping(*p.state().xreg(2));
+    let mask = p.state().config().xlen.mask();
+    let op = (ir >> 8) & 0x1f;
+    match op {
+        0x18 => {
+            for (i, id) in regs.iter().enumerate() {
+                let addr = sp - Wrapping((i as RegT + 1) * bytes);
+                store_reg(p, addr.0 & mask, *id)?;
+            }
+            let pc = *p.state().pc() + 2;
+            p.state_mut().set_xreg(2, (sp - stack_adj).0 & mask);
+            p.state_mut().set_pc(pc);
+        }
+        0x1a | 0x1c | 0x1e => {
+            let top = sp + stack_adj;
+            for (i, id) in regs.iter().enumerate() {
+                let addr = top - Wrapping((i as RegT + 1) * bytes);
+                load_reg(p, addr.0 & mask, *id)?;
+            }
+            p.state_mut().set_xreg(2, top.0 & mask);
+            if op == 0x1c {
+                p.state_mut().set_xreg(10, 0);
+            }
+            let pc = if op == 0x1a {
+                *p.state().pc() + 2
+            } else {
+                *p.state().xreg(1) & !0x1
+            };
+            p.state_mut().set_pc(pc);
+        }
+        _ => return Err(Exception::IllegalInsn(ir)),
+    }
+    Ok(())
+}
+
+//cm.mvsa01 and cm.mva01s, s0-s1 are x8-x9 and s2-s7 are x18-x23
+fn execute_cm_mv(p: &mut Processor) -> Result<(), Exception> {
+    p.state().check_isa_ext(IsaExt::Zcmp)?;
+    let ir = *p.state().ir();
+    let sreg = |r: InsnT| if r < 2 { 8 + r } else { 16 + r };
+    let r1s = sreg((ir >> 7) & 0x7);
+    let r2s = sreg((ir >> 2) & 0x7);
+    if r1s == r2s {
+        return Err(Exception::IllegalInsn(ir));
+    }
+    match (ir >> 5) & 0x3 {
+        0x1 => {
+            let a0 = *p.state().xreg(10);
+            let a1 = *p.state().xreg(11);
+            p.state_mut().set_xreg(r1s, a0);
+            p.state_mut().set_xreg(r2s, a1);
+        }
+        0x3 => {
+            let s1 = *p.state().xreg(r1s);
+            let s2 = *p.state().xreg(r2s);
+            p.state_mut().set_xreg(10, s1);
+            p.state_mut().set_xreg(11, s2);
+        }
+        _ => return Err(Exception::IllegalInsn(ir)),
+    }
+    let pc = *p.state().pc() + 2;
+    p.state_mut().set_pc(pc);
+    Ok(())
+}
+
+//cm.jt with index < 32, cm.jalt otherwise
+fn execute_cm_jt(p: &mut Processor) -> Result<(), Exception> {
+    p.state().check_isa_ext(IsaExt::Zcmt)?;
+    //jvt is gated by stateen0.jvt
+    p.state().check_stateen(0, 2)?;
+    let index = ((*p.state().ir() >> 2) & 0xff) as RegT;
+    let base = if let Extension::C(ref c) = p.state().get_extension('c') {
+        c.jvt_base()
+    } else {
+        return Err(Exception::IllegalInsn(*p.state().ir()));
+    };
+    let bytes = (p.state().config().xlen.len() >> 3) as RegT;
+    let addr = (Wrapping(base) + Wrapping(index * bytes)).0 & p.state().config().xlen.mask();
+    let entry = p.fetcher().fetch_table_entry(p.state(), &addr, p.mmu())?;
+    if index >= 32 {
+        let ra = *p.state().pc() + 2;
+        p.state_mut().set_xreg(1, ra);
+    }
+    p.state_mut().set_pc(entry as RegT & !0x1);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::devices::bus::Bus;
+    use crate::prelude::*;
+    use crate::processor::privilege::Privilege;
+    use crate::processor::{ProcessorCfg, SatpMode};
+    use crate::system::System;
+    use terminus_spaceport::memory::region::GHEAP;
+
+    const SP: RegT = 0x8000_2000;
+
+    fn system(xlen: XLen, isa: &str, insns: &[u16]) -> System {
+        let mut sys = System::new("test", "top_tests/elf/rv64ui-p-add", 100, 1);
+        sys.new_processor(ProcessorCfg {
+            xlen,
+            enable_dirty: true,
+            isa: isa.parse().unwrap(),
+            freq: 1000000000,
+            triggers: 0,
+            satp_modes: vec![if xlen == XLen::X32 {
+                SatpMode::Sv32
+            } else {
+                SatpMode::Sv39
+            }]
+            .into_boxed_slice(),
+            vlen: 128,
+            elen: 64,
+            cache_block_size: 64,
+            entropy_seed: 0,
+            hpm_counters: 0,
+        });
+        sys.register_memory("main_memory", 0x8000_0000, &GHEAP.alloc(0x2000, 1).unwrap())
+            .unwrap();
+        for (i, insn) in insns.iter().enumerate() {
+            sys.bus()
+                .write_u16(&(0x8000_0000 + i as u64 * 2), insn)
+                .unwrap();
+        }
+        sys.reset(vec![0x8000_0000]).unwrap();
+        sys
+    }
+
+    fn read_xlen(sys: &mut System, xlen: XLen, addr: RegT) -> RegT {
+        if xlen == XLen::X64 {
+            let mut data = 0;
+            sys.bus().read_u64(&addr, &mut data).unwrap();
+            data
+        } else {
+            let mut data = 0;
+            sys.bus().read_u32(&addr, &mut data).unwrap();
+            data as RegT
+        }
+    }
+
+    fn run(sys: &mut System, pc: RegT, n: usize) {
+        let p = sys.processor(0).unwrap();
+        p.state_mut().set_pc(pc);
+        p.step(n);
+    }
+
+    fn push_pop(xlen: XLen, isa: &str) {
+        let mut sys = system(
+            xlen,
+            isa,
+            &[
+                0xb8fe, //cm.push {ra, s0-s11}, -(stack_adj + 48)
+                0xbafe, //cm.pop {ra, s0-s11}, stack_adj + 48
+                0xb862, //cm.push {ra, s0-s1}, -stack_adj
+                0xbc62, //cm.popretz {ra, s0-s1}, stack_adj
+                0xb862, //cm.push {ra, s0-s1}, -stack_adj
+                0xbe62, //cm.popret {ra, s0-s1}, stack_adj
+            ],
+        );
+        let bytes = (xlen.len() >> 3) as RegT;
+        let regs: [InsnT; 13] = [1, 8, 9, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27];
+        let value = |id: InsnT| 0x1000 + id as RegT;
+        let p = sys.processor(0).unwrap();
+        for id in regs.iter() {
+            p.state_mut().set_xreg(*id, value(*id));
+        }
+        p.state_mut().set_xreg(2, SP);
+
+        //s11 is stored at the top, ra at the bottom
+        run(&mut sys, 0x8000_0000, 1);
+        for (i, id) in regs.iter().rev().enumerate() {
+            let addr = SP - (i as RegT + 1) * bytes;
+            assert_eq!(read_xlen(&mut sys, xlen, addr), value(*id));
+        }
+        //13 registers aligned to 16 bytes, plus spimm 3 * 16
+        let stack_adj = if xlen == XLen::X64 { 112 + 48 } else { 64 + 48 };
+        let p = sys.processor(0).unwrap();
+        assert_eq!(*p.state().xreg(2), SP - stack_adj);
+        for id in regs.iter() {
+            p.state_mut().set_xreg(*id, 0);
+        }
+        run(&mut sys, 0x8000_0002, 1);
+        let p = sys.processor(0).unwrap();
+        for id in regs.iter() {
+            assert_eq!(*p.state().xreg(*id), value(*id));
+        }
+        assert_eq!(*p.state().xreg(2), SP);
+        assert_eq!(*p.state().pc(), 0x8000_0004);
+
+        //popretz clears a0 and returns to ra, 3 registers aligned to 16 bytes
+        let stack_adj = if xlen == XLen::X64 { 32 } else { 16 };
+        p.state_mut().set_xreg(1, 0x8000_0100);
+        p.state_mut().set_xreg(10, 0x77);
+        run(&mut sys, 0x8000_0004, 1);
+        let p = sys.processor(0).unwrap();
+        assert_eq!(*p.state().xreg(2), SP - stack_adj);
+        p.state_mut().set_xreg(1, 0);
+        p.state_mut().set_xreg(8, 0);
+        p.step(1);
+        assert_eq!(*p.state().xreg(10), 0);
+        assert_eq!(*p.state().xreg(1), 0x8000_0100);
+        assert_eq!(*p.state().xreg(8), value(8));
+        assert_eq!(*p.state().xreg(2), SP);
+        assert_eq!(*p.state().pc(), 0x8000_0100);
+
+        //popret keeps a0
+        p.state_mut().set_xreg(10, 0x77);
+        run(&mut sys, 0x8000_0008, 2);
+        let p = sys.processor(0).unwrap();
+        assert_eq!(*p.state().xreg(10), 0x77);
+        assert_eq!(*p.state().xreg(2), SP);
+        assert_eq!(*p.state().pc(), 0x8000_0100);
+    }
+
+    fn mv(xlen: XLen, isa: &str) {
+        let mut sys = system(
+            xlen,
+            isa,
+            &[
+                0xac2a, //cm.mvsa01 s0, s2
+                0xacee, //cm.mva01s s1, s3
+                0xac22, //cm.mvsa01 s0, s0
+            ],
+        );
+        let p = sys.processor(0).unwrap();
+        p.state_mut().set_xreg(10, 0xa0);
+        p.state_mut().set_xreg(11, 0xa1);
+        p.state_mut().set_xreg(9, 0xb1);
+        p.state_mut().set_xreg(19, 0xb3);
+        p.step(1);
+        assert_eq!((*p.state().xreg(8), *p.state().xreg(18)), (0xa0, 0xa1));
+        p.step(1);
+        assert_eq!((*p.state().xreg(10), *p.state().xreg(11)), (0xb1, 0xb3));
+        //r1s == r2s is reserved
+        p.step(1);
+        assert_eq!(p.state().priv_m().mcause().get(), 2);
+    }
+
+    #[test]
+    fn zcmp_rv32_test() {
+        push_pop(XLen::X32, "rv32imac_zcmp");
+        mv(XLen::X32, "rv32imac_zcmp");
+    }
+
+    #[test]
+    fn zcmp_rv64_test() {
+        push_pop(XLen::X64, "rv64imac_zcmp");
+        mv(XLen::X64, "rv64imac_zcmp");
+    }
+
+    #[test]
+    fn zcmt_test() {
+        let mut sys = system(
+            XLen::X64,
+            "rv64imacu_zcmt_smstateen",
+            &[
+                0xa006, //cm.jt 1
+                0xa082, //cm.jalt 32
+                0x0073, 0x3020, //mret
+                0xa006, //cm.jt 1
+            ],
+        );
+        sys.bus().write_u64(&0x8000_1008, &0x8000_0100).unwrap();
+        sys.bus().write_u64(&0x8000_1100, &0x8000_0201).unwrap();
+        let p = sys.processor(0).unwrap();
+        p.state().debug_set_csr(0x017, 0x8000_1000).unwrap();
+        //TOR RWX below 4GiB
+        p.state().priv_m().pmpcfg0_mut().set_bit_range(4, 0, 0xf);
+        p.state().priv_m().pmpaddr0_mut().set(0x1_0000_0000 >> 2);
+
+        run(&mut sys, 0x8000_0000, 1);
+        let p = sys.processor(0).unwrap();
+        assert_eq!(*p.state().pc(), 0x8000_0100);
+        assert_eq!(*p.state().xreg(1), 0);
+        //cm.jalt links and clears bit 0 of the entry
+        run(&mut sys, 0x8000_0002, 1);
+        let p = sys.processor(0).unwrap();
+        assert_eq!(*p.state().pc(), 0x8000_0200);
+        assert_eq!(*p.state().xreg(1), 0x8000_0004);
+
+        //jvt is blocked by mstateen0.jvt out of M mode
+        let user_jt = |sys: &mut System| {
+            let p = sys.processor(0).unwrap();
+            p.state().priv_m().mstatus_mut().set_mpp(0);
+            p.state().priv_m().mepc_mut().set(0x8000_0008);
+            run(sys, 0x8000_0004, 2);
+        };
+        let p = sys.processor(0).unwrap();
+        p.state().priv_m().mstateen0_mut().set_jvt(0);
+        user_jt(&mut sys);
+        let p = sys.processor(0).unwrap();
+        assert_eq!(*p.state().privilege(), Privilege::M);
+        assert_eq!(p.state().priv_m().mcause().get(), 2);
+        assert_eq!(p.state().priv_m().mepc().get(), 0x8000_0008);
+        p.state().priv_m().mstateen0_mut().set_jvt(1);
+        user_jt(&mut sys);
+        let p = sys.processor(0).unwrap();
+        assert_eq!(*p.state().privilege(), Privilege::U);
+        assert_eq!(*p.state().pc(), 0x8000_0100);
+    }
+}
@@ -1,8 +1,10 @@
 use crate::prelude::*;
 use crate::processor::extensions::f::float::*;
 use crate::processor::extensions::f::{FLen, FRegT};
+use crate::processor::isa::IsaExt;
 use crate::processor::trap::Exception;
 use crate::processor::Processor;
+use simple_soft_float::StatusFlags;
 use std::cmp::Ordering;
 use std::num::Wrapping;
 
@@ -79,8 +81,8 @@ impl Execution for FADDD {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
-        let rs2: u64 = (*f.freg(self.rs2(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let rs2: u64 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F64) as u64;
         let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
         let rd = self.rd(p.state().ir());
         let value = f.flen.padding(res as FRegT, FLen::F64);
@@ -109,8 +111,8 @@ impl Execution for FSUBD {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
-        let rs2: u64 = (*f.freg(self.rs2(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let rs2: u64 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F64) as u64;
         let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
         let rd = self.rd(p.state().ir());
         let value = f.flen.padding(res as FRegT, FLen::F64);
@@ -139,8 +141,8 @@ impl Execution for FMULD {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
-        let rs2: u64 = (*f.freg(self.rs2(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let rs2: u64 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F64) as u64;
         let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
         let rd = self.rd(p.state().ir());
         let value = f.flen.padding(res as FRegT, FLen::F64);
@@ -169,8 +171,8 @@ impl Execution for FDIVD {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
-        let rs2: u64 = (*f.freg(self.rs2(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let rs2: u64 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F64) as u64;
         let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
         let rd = self.rd(p.state().ir());
         let value = f.flen.padding(res as FRegT, FLen::F64);
@@ -199,7 +201,7 @@ impl Execution for FSQRTD {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
         let res = self.compute(p.state().ir(), &*f, rs1, 0, 0)?;
         let rd = self.rd(p.state().ir());
         let value = f.flen.padding(res as FRegT, FLen::F64);
@@ -238,8 +240,8 @@ impl Execution for FMIND {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
-        let rs2: u64 = (*f.freg(self.rs2(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let rs2: u64 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F64) as u64;
         let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
         let rd = self.rd(p.state().ir());
         let value = f.flen.padding(res as FRegT, FLen::F64);
@@ -278,8 +280,8 @@ impl Execution for FMAXD {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
-        let rs2: u64 = (*f.freg(self.rs2(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let rs2: u64 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F64) as u64;
         let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
         let rd = self.rd(p.state().ir());
         let value = f.flen.padding(res as FRegT, FLen::F64);
@@ -308,9 +310,9 @@ impl Execution for FMADDD {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
-        let rs2: u64 = (*f.freg(self.rs2(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
-        let rs3: u64 = (*f.freg(self.rs3(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let rs2: u64 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F64) as u64;
+        let rs3: u64 = f.flen.boxed(*f.freg(self.rs3(p.state().ir())), FLen::F64) as u64;
         let res = self.compute(p.state().ir(), &*f, rs1, rs2, rs3)?;
         let rd = self.rd(p.state().ir());
         let value = f.flen.padding(res as FRegT, FLen::F64);
@@ -344,9 +346,9 @@ impl Execution for FMSUBD {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
-        let rs2: u64 = (*f.freg(self.rs2(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
-        let rs3: u64 = (*f.freg(self.rs3(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let rs2: u64 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F64) as u64;
+        let rs3: u64 = f.flen.boxed(*f.freg(self.rs3(p.state().ir())), FLen::F64) as u64;
         let res = self.compute(p.state().ir(), &*f, rs1, rs2, rs3)?;
         let rd = self.rd(p.state().ir());
         let value = f.flen.padding(res as FRegT, FLen::F64);
@@ -381,9 +383,9 @@ impl Execution for FMNSUBD {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
-        let rs2: u64 = (*f.freg(self.rs2(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
-        let rs3: u64 = (*f.freg(self.rs3(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let rs2: u64 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F64) as u64;
+        let rs3: u64 = f.flen.boxed(*f.freg(self.rs3(p.state().ir())), FLen::F64) as u64;
         let res = self.compute(p.state().ir(), &*f, rs1, rs2, rs3)?;
         let rd = self.rd(p.state().ir());
         let value = f.flen.padding(res as FRegT, FLen::F64);
@@ -413,9 +415,9 @@ impl Execution for FMNADDD {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
-        let rs2: u64 = (*f.freg(self.rs2(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
-        let rs3: u64 = (*f.freg(self.rs3(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let rs2: u64 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F64) as u64;
+        let rs3: u64 = f.flen.boxed(*f.freg(self.rs3(p.state().ir())), FLen::F64) as u64;
         let res = self.compute(p.state().ir(), &*f, rs1, rs2, rs3)?;
         let rd = self.rd(p.state().ir());
         let value = f.flen.padding(res as FRegT, FLen::F64);
@@ -453,7 +455,7 @@ impl Execution for FCVTWD {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
         let res = self.convert(p.state().ir(), &*f, rs1)? as u32;
         let rd = self.rd(p.state().ir());
         let value = sext(res as RegT, 32) & p.state().config().xlen.mask();
@@ -491,7 +493,7 @@ impl Execution for FCVTWUD {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
         let res = self.convert(p.state().ir(), &*f, rs1)?;
         let rd = self.rd(p.state().ir());
         let value = sext(res as RegT, 32) & p.state().config().xlen.mask();
@@ -530,7 +532,7 @@ impl Execution for FCVTLD {
         p.state().check_xlen(XLen::X64)?;
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
         let res = self.convert(p.state().ir(), &*f, rs1)? as u64;
         let rd = self.rd(p.state().ir());
         let value = res as RegT & p.state().config().xlen.mask();
@@ -569,7 +571,7 @@ impl Execution for FCVTLUD {
         p.state().check_xlen(XLen::X64)?;
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
         let res = self.convert(p.state().ir(), &*f, rs1)?;
         let rd = self.rd(p.state().ir());
         let value = res as RegT & p.state().config().xlen.mask();
@@ -723,7 +725,7 @@ impl Execution for FCVTSD {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
         let fres = self.convert(p.state().ir(), &*f, rs1)?;
         let rd = self.rd(p.state().ir());
         let value = f.flen.padding(fres as FRegT, FLen::F32);
@@ -754,7 +756,7 @@ impl Execution for FCVTDS {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u32 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff) as u32;
+        let rs1: u32 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F32) as u32;
         let fres = self.convert(p.state().ir(), &*f, rs1)?;
         let rd = self.rd(p.state().ir());
         let value = f.flen.padding(fres as FRegT, FLen::F64);
@@ -777,8 +779,8 @@ impl Execution for FSGNJD {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
-        let rs2: u64 = (*f.freg(self.rs2(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let rs2: u64 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F64) as u64;
         let res = rs1 & ((1 << 63) - 1) | rs2 & (1 << 63);
         let rd = self.rd(p.state().ir());
         let value = f.flen.padding(res as FRegT, FLen::F64);
@@ -801,8 +803,8 @@ impl Execution for FSGNJND {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
-        let rs2: u64 = (*f.freg(self.rs2(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let rs2: u64 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F64) as u64;
         let res = rs1 & ((1 << 63) - 1) | !rs2 & (1 << 63);
         let rd = self.rd(p.state().ir());
         let value = f.flen.padding(res as FRegT, FLen::F64);
@@ -825,8 +827,8 @@ impl Execution for FSGNJXD {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
-        let rs2: u64 = (*f.freg(self.rs2(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let rs2: u64 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F64) as u64;
         let res = rs1 & ((1 << 63) - 1) | (rs1 ^ rs2) & (1 << 63);
         let rd = self.rd(p.state().ir());
         let value = f.flen.padding(res as FRegT, FLen::F64);
@@ -851,8 +853,8 @@ impl Execution for FEQD {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
-        let rs2: u64 = (*f.freg(self.rs2(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let rs2: u64 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F64) as u64;
         if let Some(Ordering::Equal) = self.compare(p.state().ir(), &*f, rs1, rs2, false)? {
             let rd = self.rd(p.state().ir());
             let value = 1;
@@ -882,8 +884,8 @@ impl Execution for FLTD {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
-        let rs2: u64 = (*f.freg(self.rs2(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let rs2: u64 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F64) as u64;
         if let Some(Ordering::Less) = self.compare(p.state().ir(), &*f, rs1, rs2, true)? {
             let rd = self.rd(p.state().ir());
             let value = 1;
@@ -913,8 +915,8 @@ impl Execution for FLED {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
-        let rs2: u64 = (*f.freg(self.rs2(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let rs2: u64 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F64) as u64;
         let res = self.compare(p.state().ir(), &*f, rs1, rs2, true)?;
         if let Some(Ordering::Equal) = res {
             let rd = self.rd(p.state().ir());
@@ -949,7 +951,7 @@ impl Execution for FCLASSD {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('d')?;
         let f = self.get_f_ext(p)?;
-        let rs1: u64 = (*f.freg(self.rs1(p.state().ir())) & 0xffff_ffff_ffff_ffff) as u64;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
         let rd = self.rd(p.state().ir());
         let value = self.class(rs1);
         let pc = *p.state().pc() + 4;
@@ -1004,3 +1006,542 @@ impl Execution for FMVDX {
         Ok(())
     }
 }
+
+//Zfa
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111100100001?????000?????1010011")]
+#[derive(Debug)]
+struct FLID();
+
+impl FloatInsn for FLID {}
+
+impl FLoadImm<u64, F64Traits> for FLID {
+    const MIN_NORMAL: f64 = f64::MIN_POSITIVE;
+}
+
+impl Execution for FLID {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('d')?;
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let res = self.load_imm(p.state().ir());
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F64);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010101??????????010?????1010011")]
+#[derive(Debug)]
+struct FMINMD();
+
+impl FloatInsn for FMINMD {}
+
+impl FCompute<u64, F64Traits> for FMINMD {
+    fn opt(&self, _: &InsnT, frs1: F64, frs2: F64, _: F64, fp_state: &mut FPState) -> F64 {
+        let res = frs1.compare_quiet(&frs2, Some(fp_state));
+        if frs1.is_nan() || frs2.is_nan() {
+            return F64::quiet_nan();
+        }
+        if frs1.is_negative_zero() && frs2.is_zero() {
+            return frs1;
+        }
+        if let Some(Ordering::Less) = res {
+            frs1
+        } else {
+            frs2
+        }
+    }
+}
+
+impl Execution for FMINMD {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('d')?;
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let rs2: u64 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F64) as u64;
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F64);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010101??????????011?????1010011")]
+#[derive(Debug)]
+struct FMAXMD();
+
+impl FloatInsn for FMAXMD {}
+
+impl FCompute<u64, F64Traits> for FMAXMD {
+    fn opt(&self, _: &InsnT, frs1: F64, frs2: F64, _: F64, fp_state: &mut FPState) -> F64 {
+        let res = frs1.compare_quiet(&frs2, Some(fp_state));
+        if frs1.is_nan() || frs2.is_nan() {
+            return F64::quiet_nan();
+        }
+        if frs1.is_positive_zero() && frs2.is_zero() {
+            return frs1;
+        }
+        if let Some(Ordering::Greater) = res {
+            frs1
+        } else {
+            frs2
+        }
+    }
+}
+
+impl Execution for FMAXMD {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('d')?;
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let rs2: u64 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F64) as u64;
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F64);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010000100100?????????????1010011")]
+#[derive(Debug)]
+struct FROUNDD();
+
+impl FloatInsn for FROUNDD {}
+
+impl FCompute<u64, F64Traits> for FROUNDD {
+    fn opt(&self, ir: &InsnT, frs1: F64, _: F64, _: F64, fp_state: &mut FPState) -> F64 {
+        let res = frs1.round_to_integral(false, Self::rm_from_bits(self.rm(ir)), Some(fp_state));
+        if res.is_nan() {
+            F64::quiet_nan()
+        } else {
+            res
+        }
+    }
+}
+
+impl Execution for FROUNDD {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('d')?;
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let res = self.compute(p.state().ir(), &*f, rs1, 0, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F64);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010000100101?????????????1010011")]
+#[derive(Debug)]
+struct FROUNDNXD();
+
+impl FloatInsn for FROUNDNXD {}
+
+impl FCompute<u64, F64Traits> for FROUNDNXD {
+    fn opt(&self, ir: &InsnT, frs1: F64, _: F64, _: F64, fp_state: &mut FPState) -> F64 {
+        let res = frs1.round_to_integral(true, Self::rm_from_bits(self.rm(ir)), Some(fp_state));
+        if res.is_nan() {
+            F64::quiet_nan()
+        } else {
+            res
+        }
+    }
+}
+
+impl Execution for FROUNDNXD {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('d')?;
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let res = self.compute(p.state().ir(), &*f, rs1, 0, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F64);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b1010001??????????100?????1010011")]
+#[derive(Debug)]
+struct FLEQD();
+
+impl FloatInsn for FLEQD {}
+
+impl FCompare<u64, F64Traits> for FLEQD {}
+
+impl Execution for FLEQD {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('d')?;
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let rs2: u64 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F64) as u64;
+        let res = self.compare(p.state().ir(), &*f, rs1, rs2, false)?;
+        let rd = self.rd(p.state().ir());
+        let value = if let Some(Ordering::Less) | Some(Ordering::Equal) = res {
+            1
+        } else {
+            0
+        };
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b1010001??????????101?????1010011")]
+#[derive(Debug)]
+struct FLTQD();
+
+impl FloatInsn for FLTQD {}
+
+impl FCompare<u64, F64Traits> for FLTQD {}
+
+impl Execution for FLTQD {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('d')?;
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let rs2: u64 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F64) as u64;
+        let res = self.compare(p.state().ir(), &*f, rs1, rs2, false)?;
+        let rd = self.rd(p.state().ir());
+        let value = if let Some(Ordering::Less) = res { 1 } else { 0 };
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110000101000?????001?????1010011")]
+#[derive(Debug)]
+struct FCVTMODWD();
+
+impl FloatInsn for FCVTMODWD {}
+
+impl FToX<u64, F64Traits> for FCVTMODWD {
+    type T = i32;
+    //truncated and wrapped to 32 bits, nan and infinity are converted to 0
+    fn opt(&self, _: &InsnT, frs1: F64, state: &mut FPState) -> Self::T {
+        let bits = *frs1.bits();
+        let exp = ((bits >> 52) & 0x7ff) as i32;
+        let fraction = bits & ((1 << 52) - 1);
+        if exp == 0x7ff {
+            state.status_flags |= StatusFlags::INVALID_OPERATION;
+            return 0;
+        }
+        if exp < 1023 {
+            if exp != 0 || fraction != 0 {
+                state.status_flags |= StatusFlags::INEXACT;
+            }
+            return 0;
+        }
+        let mantissa = fraction | 1 << 52;
+        let shift = exp - 1075;
+        let (int, inexact) = if shift >= 0 {
+            (if shift < 64 { mantissa << shift } else { 0 }, false)
+        } else {
+            (mantissa >> -shift, mantissa & ((1 << -shift) - 1) != 0)
+        };
+        let negative = frs1.sign() == Sign::Negative;
+        //out of range of i32 except -2^31
+        if exp > 1054 || exp == 1054 && !(negative && fraction == 0) {
+            state.status_flags |= StatusFlags::INVALID_OPERATION;
+        } else if inexact {
+            state.status_flags |= StatusFlags::INEXACT;
+        }
+        if negative {
+            int.wrapping_neg() as i32
+        } else {
+            int as i32
+        }
+    }
+}
+
+impl Execution for FCVTMODWD {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('d')?;
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let res = self.convert(p.state().ir(), &*f, rs1)? as u32;
+        let rd = self.rd(p.state().ir());
+        let value = sext(res as RegT, 32) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111000100001?????000?????1010011")]
+#[derive(Debug)]
+struct FMVHXD();
+
+impl FloatInsn for FMVHXD {}
+
+impl Execution for FMVHXD {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_xlen(XLen::X32)?;
+        p.state().check_extension('d')?;
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let data: RegT = ((*f.freg(self.rs1(p.state().ir())) >> 32) & 0xffff_ffff) as RegT;
+        let rd = self.rd(p.state().ir());
+        let value = data & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b1011001??????????000?????1010011")]
+#[derive(Debug)]
+struct FMVPDX();
+
+impl FloatInsn for FMVPDX {}
+
+impl Execution for FMVPDX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_xlen(XLen::X32)?;
+        p.state().check_extension('d')?;
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let high: RegT = *p.state().xreg(self.rs2(p.state().ir())) & 0xffff_ffff;
+        let low: RegT = *p.state().xreg(self.rs1(p.state().ir())) & 0xffff_ffff;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding((high << 32 | low) as FRegT, FLen::F64);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::devices::bus::Bus;
+    use crate::processor::extensions::f::ExtensionF;
+    use crate::processor::extensions::Extension;
+    use crate::processor::{ProcessorCfg, SatpMode};
+    use crate::system::System;
+    use terminus_spaceport::memory::region::GHEAP;
+
+    //rd = 10, rs1 = 11, rs2 = 12
+    const FLI_D: u32 = 0xf2100553;
+    const FMINM_D: u32 = 0x2ac5a553;
+    const FMAXM_D: u32 = 0x2ac5b553;
+    const FROUND_D_RNE: u32 = 0x42458553;
+    const FROUND_D_RTZ: u32 = 0x42459553;
+    const FROUNDNX_D_RNE: u32 = 0x42558553;
+    const FLEQ_D: u32 = 0xa2c5c553;
+    const FLTQ_D: u32 = 0xa2c5d553;
+    const FCVTMOD_W_D: u32 = 0xc2859553;
+
+    const NV: RegT = 0x10;
+    const NX: RegT = 0x1;
+
+    const QNAN: u64 = 0x7ff8_0000_0000_0000;
+    const SNAN: u64 = 0x7ff0_0000_0000_0001;
+    const PZERO: u64 = 0;
+    const NZERO: u64 = 0x8000_0000_0000_0000;
+    const INF: u64 = 0x7ff0_0000_0000_0000;
+    const NINF: u64 = 0xfff0_0000_0000_0000;
+
+    fn d(v: f64) -> u64 {
+        v.to_bits()
+    }
+
+    fn system() -> System {
+        let mut sys = System::new("test", "top_tests/elf/rv64ui-p-add", 100, 1);
+        sys.new_processor(ProcessorCfg {
+            xlen: XLen::X64,
+            enable_dirty: true,
+            isa: "rv64imafdc_zfa".parse().unwrap(),
+            freq: 1000000000,
+            triggers: 0,
+            satp_modes: vec![SatpMode::Sv39].into_boxed_slice(),
+            vlen: 128,
+            elen: 64,
+            cache_block_size: 64,
+            entropy_seed: 0,
+            hpm_counters: 0,
+        });
+        sys.register_memory("main_memory", 0x8000_0000, &GHEAP.alloc(0x2000, 1).unwrap())
+            .unwrap();
+        sys.reset(vec![0x8000_0000]).unwrap();
+        sys
+    }
+
+    fn f_ext(p: &mut Processor) -> &mut ExtensionF {
+        if let Extension::F(f) = p.state_mut().get_extension_mut('f') {
+            f
+        } else {
+            unreachable!()
+        }
+    }
+
+    //clear fflags, execute insn with f11 = rs1 and f12 = rs2, return (f10, x10, fflags)
+    fn exec(sys: &mut System, insn: u32, rs1: u64, rs2: u64) -> (u64, RegT, RegT) {
+        sys.bus().write_u32(&0x8000_0000, &0x00101073).unwrap(); //csrw fflags, zero
+        sys.bus().write_u32(&0x8000_0004, &insn).unwrap();
+        let p = sys.processor(0).unwrap();
+        p.fetcher().flush_icache();
+        p.state_mut().set_pc(0x8000_0000);
+        p.state_mut().set_xreg(10, 0xdead_beef);
+        f_ext(p).set_freg(10, 0);
+        f_ext(p).set_freg(11, rs1 as FRegT);
+        f_ext(p).set_freg(12, rs2 as FRegT);
+        p.step(2);
+        assert_eq!(*p.state().next_pc(), 0x8000_0008, "{:#x} trapped!", insn);
+        (
+            *f_ext(p).freg(10) as u64,
+            *p.state().xreg(10),
+            p.state().debug_csr(0x001).unwrap(),
+        )
+    }
+
+    fn exec_f(sys: &mut System, insn: u32, rs1: u64, rs2: u64) -> (u64, RegT) {
+        let (f, _, flags) = exec(sys, insn, rs1, rs2);
+        (f, flags)
+    }
+
+    fn exec_x(sys: &mut System, insn: u32, rs1: u64, rs2: u64) -> (RegT, RegT) {
+        let (_, x, flags) = exec(sys, insn, rs1, rs2);
+        (x, flags)
+    }
+
+    #[test]
+    fn fli_test() {
+        let mut sys = system();
+        let mut fli = |imm: u32| exec_f(&mut sys, FLI_D | imm << 15, 0, 0);
+        assert_eq!(fli(0), (d(-1.0), 0));
+        assert_eq!(fli(1), (d(f64::MIN_POSITIVE), 0));
+        assert_eq!(fli(2), (0x3ef0_0000_0000_0000, 0));
+        assert_eq!(fli(16), (d(1.0), 0));
+        assert_eq!(fli(29), (d(65536.0), 0));
+        assert_eq!(fli(30), (INF, 0));
+        assert_eq!(fli(31), (QNAN, 0));
+    }
+
+    #[test]
+    fn fminm_fmaxm_test() {
+        let mut sys = system();
+        //any nan input gives the canonical nan, only snan is invalid
+        assert_eq!(exec_f(&mut sys, FMINM_D, d(1.0), QNAN | 1), (QNAN, 0));
+        assert_eq!(exec_f(&mut sys, FMAXM_D, QNAN | 1, d(1.0)), (QNAN, 0));
+        assert_eq!(exec_f(&mut sys, FMINM_D, SNAN, d(1.0)), (QNAN, NV));
+        assert_eq!(exec_f(&mut sys, FMAXM_D, d(1.0), SNAN), (QNAN, NV));
+        //-0 < +0
+        assert_eq!(exec_f(&mut sys, FMINM_D, NZERO, PZERO), (NZERO, 0));
+        assert_eq!(exec_f(&mut sys, FMINM_D, PZERO, NZERO), (NZERO, 0));
+        assert_eq!(exec_f(&mut sys, FMAXM_D, NZERO, PZERO), (PZERO, 0));
+        assert_eq!(exec_f(&mut sys, FMAXM_D, PZERO, NZERO), (PZERO, 0));
+        assert_eq!(exec_f(&mut sys, FMINM_D, NINF, d(1.0)), (NINF, 0));
+        assert_eq!(exec_f(&mut sys, FMAXM_D, d(1.0), d(2.0)), (d(2.0), 0));
+    }
+
+    #[test]
+    fn fround_test() {
+        let mut sys = system();
+        //fround never raises inexact, froundnx does
+        assert_eq!(exec_f(&mut sys, FROUND_D_RNE, d(2.5), 0), (d(2.0), 0));
+        assert_eq!(exec_f(&mut sys, FROUNDNX_D_RNE, d(2.5), 0), (d(2.0), NX));
+        assert_eq!(exec_f(&mut sys, FROUNDNX_D_RNE, d(3.0), 0), (d(3.0), 0));
+        assert_eq!(exec_f(&mut sys, FROUND_D_RTZ, d(-1.5), 0), (d(-1.0), 0));
+        //sign of zero is kept
+        assert_eq!(exec_f(&mut sys, FROUND_D_RNE, d(-0.5), 0), (NZERO, 0));
+        assert_eq!(exec_f(&mut sys, FROUNDNX_D_RNE, d(-0.5), 0), (NZERO, NX));
+        assert_eq!(exec_f(&mut sys, FROUND_D_RNE, NZERO, 0), (NZERO, 0));
+        assert_eq!(exec_f(&mut sys, FROUND_D_RNE, d(1e300), 0), (d(1e300), 0));
+        assert_eq!(exec_f(&mut sys, FROUNDNX_D_RNE, NINF, 0), (NINF, 0));
+        //nan results are canonical
+        assert_eq!(exec_f(&mut sys, FROUNDNX_D_RNE, QNAN | 1, 0), (QNAN, 0));
+        assert_eq!(exec_f(&mut sys, FROUND_D_RNE, SNAN, 0), (QNAN, NV));
+    }
+
+    #[test]
+    fn fleq_fltq_test() {
+        let mut sys = system();
+        assert_eq!(exec_x(&mut sys, FLTQ_D, d(1.0), d(2.0)), (1, 0));
+        assert_eq!(exec_x(&mut sys, FLEQ_D, d(2.0), d(1.0)), (0, 0));
+        assert_eq!(exec_x(&mut sys, FLEQ_D, d(1.0), d(1.0)), (1, 0));
+        assert_eq!(exec_x(&mut sys, FLTQ_D, d(1.0), d(1.0)), (0, 0));
+        //-0 == +0
+        assert_eq!(exec_x(&mut sys, FLEQ_D, NZERO, PZERO), (1, 0));
+        assert_eq!(exec_x(&mut sys, FLTQ_D, NZERO, PZERO), (0, 0));
+        //quiet: qnan is unordered without invalid, snan is invalid
+        assert_eq!(exec_x(&mut sys, FLEQ_D, QNAN, d(1.0)), (0, 0));
+        assert_eq!(exec_x(&mut sys, FLTQ_D, d(1.0), QNAN), (0, 0));
+        assert_eq!(exec_x(&mut sys, FLEQ_D, SNAN, d(1.0)), (0, NV));
+        assert_eq!(exec_x(&mut sys, FLTQ_D, d(1.0), SNAN), (0, NV));
+    }
+
+    #[test]
+    fn fcvtmod_test() {
+        let mut sys = system();
+        let mut cvt = |v: u64| exec_x(&mut sys, FCVTMOD_W_D, v, 0);
+        assert_eq!(cvt(d(3.0)), (3, 0));
+        assert_eq!(cvt(NZERO), (0, 0));
+        //truncated toward zero
+        assert_eq!(cvt(d(1.5)), (1, NX));
+        assert_eq!(cvt(d(-1.5)), (0xffff_ffff_ffff_ffff, NX));
+        assert_eq!(cvt(d(0.5)), (0, NX));
+        //-2^31 is the only exact value with exponent 31
+        assert_eq!(cvt(d(-2147483648.0)), (0xffff_ffff_8000_0000, 0));
+        //out of range: wrapped modulo 2^32 and invalid, never inexact
+        assert_eq!(cvt(d(2147483648.0)), (0xffff_ffff_8000_0000, NV));
+        assert_eq!(cvt(d(4294967301.0)), (5, NV));
+        assert_eq!(cvt(d(-4294967301.0)), (0xffff_ffff_ffff_fffb, NV));
+        assert_eq!(cvt(d(4294967296.5)), (0, NV));
+        assert_eq!(cvt(d(1e20)), (0x6310_0000, NV));
+        assert_eq!(cvt(d(1e300)), (0, NV));
+        //nan and infinity give 0
+        assert_eq!(cvt(QNAN), (0, NV));
+        assert_eq!(cvt(SNAN), (0, NV));
+        assert_eq!(cvt(INF), (0, NV));
+        assert_eq!(cvt(NINF), (0, NV));
+    }
+}
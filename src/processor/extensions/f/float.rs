@@ -5,11 +5,26 @@ use crate::processor::extensions::f::ExtensionF;
 use crate::processor::extensions::Extension;
 use crate::processor::trap::Exception;
 use crate::processor::Processor;
-use simple_soft_float::{Float, FloatBitsType, FloatClass, FloatTraits, RoundingMode, StatusFlags};
+use simple_soft_float::{
+    Float, FloatBitsType, FloatClass, FloatProperties, FloatTraits, RoundingMode, StatusFlags,
+};
 use std::cmp::Ordering;
 use std::num::Wrapping;
 
-pub use simple_soft_float::{F32Traits, F64Traits, FPState, Sign, F32, F64};
+pub use simple_soft_float::{F16Traits, F32Traits, F64Traits, FPState, Sign, F16, F32, F64};
+
+//bfloat16, 8 bits exponent and 7 bits mantissa
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BF16Traits;
+
+impl FloatTraits for BF16Traits {
+    type Bits = u16;
+    fn properties(&self) -> FloatProperties {
+        FloatProperties::new(8, 7)
+    }
+}
+
+pub type BF16 = Float<BF16Traits>;
 
 pub trait FloatInsn: InstructionImp {
     fn get_f_ext<'p>(&self, p: &'p Processor) -> Result<&'p ExtensionF, Exception> {
@@ -56,6 +71,59 @@ pub trait FloatInsn: InstructionImp {
     }
 }
 
+//immediates of zfa fli, index 1 is the minimum positive normal and 31 is the canonical nan
+const FLI_IMM: [f64; 32] = [
+    -1.0,
+    0.0,
+    1.52587890625e-5,
+    3.0517578125e-5,
+    0.00390625,
+    0.0078125,
+    0.0625,
+    0.125,
+    0.25,
+    0.3125,
+    0.375,
+    0.4375,
+    0.5,
+    0.625,
+    0.75,
+    0.875,
+    1.0,
+    1.25,
+    1.5,
+    1.75,
+    2.0,
+    2.5,
+    3.0,
+    4.0,
+    8.0,
+    16.0,
+    128.0,
+    256.0,
+    32768.0,
+    65536.0,
+    f64::INFINITY,
+    0.0,
+];
+
+pub trait FLoadImm<Bits: FloatBitsType + Copy, FpTrait: FloatTraits<Bits = Bits> + Default>:
+    FloatInsn
+{
+    const MIN_NORMAL: f64;
+    //exact in all formats, except that 2^16 overflows to inf in half precision
+    fn load_imm(&self, ir: &InsnT) -> Bits {
+        let imm = match self.rs1(ir) {
+            1 => Self::MIN_NORMAL,
+            31 => return *Float::<FpTrait>::quiet_nan().bits(),
+            i => FLI_IMM[i as usize],
+        };
+        *F64::from_bits(imm.to_bits())
+            .convert_to_float::<FpTrait>(None, None)
+            .bits()
+    }
+}
+
 pub trait FStore: FloatInsn {
     fn offset(&self, code: &InsnT) -> Wrapping<RegT> {
         let high: RegT = ((self.imm(code) >> 5) & 0x7f) as RegT;
@@ -100,9 +168,7 @@ pub trait FCompute<Bits: FloatBitsType + Copy, FpTrait: FloatTraits<Bits = Bits>
         let frs2 = Float::<FpTrait>::from_bits(rs2);
         let frs3 = Float::<FpTrait>::from_bits(rs3);
         let fres = self.opt(ir, frs1, frs2, frs3, &mut fp_state);
-        f.csrs
-            .fflags_mut()
-            .set(Self::status_flags_to_bits(&fp_state.status_flags));
+        f.accrue_fflags(Self::status_flags_to_bits(&fp_state.status_flags));
         Ok(*fres.bits())
     }
 }
@@ -125,9 +191,7 @@ pub trait FToX<Bits: FloatBitsType + Copy, FpTrait: FloatTraits<Bits = Bits> + D
         };
         let fres = Float::<FpTrait>::from_bits(rs1);
         let res: Self::T = self.opt(ir, fres, &mut fp_state);
-        f.csrs
-            .fflags_mut()
-            .set(Self::status_flags_to_bits(&fp_state.status_flags));
+        f.accrue_fflags(Self::status_flags_to_bits(&fp_state.status_flags));
         Ok(res)
     }
 }
@@ -149,9 +213,7 @@ pub trait XToF<Bits: FloatBitsType + Copy, FpTrait: FloatTraits<Bits = Bits> + D
             }
         };
         let res = self.opt(ir, rs1, &mut fp_state);
-        f.csrs
-            .fflags_mut()
-            .set(Self::status_flags_to_bits(&fp_state.status_flags));
+        f.accrue_fflags(Self::status_flags_to_bits(&fp_state.status_flags));
         Ok(*res.bits())
     }
 }
@@ -185,9 +247,7 @@ pub trait FCompare<Bits: FloatBitsType + Copy, FpTrait: FloatTraits<Bits = Bits>
                 fp_state.status_flags = StatusFlags::INVALID_OPERATION;
             }
         }
-        f.csrs
-            .fflags_mut()
-            .set(Self::status_flags_to_bits(&fp_state.status_flags));
+        f.accrue_fflags(Self::status_flags_to_bits(&fp_state.status_flags));
         Ok(res)
     }
 }
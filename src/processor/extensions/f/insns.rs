@@ -1,8 +1,10 @@
 use crate::prelude::*;
 use crate::processor::extensions::f::float::{
-    F32Traits, FClass, FCompare, FCompute, FPState, FStore, FToX, FloatInsn, Sign, XToF, F32,
+    F32Traits, FClass, FCompare, FCompute, FLoadImm, FPState, FStore, FToX, FloatInsn, Sign, XToF,
+    F32,
 };
 use crate::processor::extensions::f::{FLen, FRegT};
+use crate::processor::isa::IsaExt;
 use crate::processor::trap::Exception;
 use crate::processor::Processor;
 use std::cmp::Ordering;
@@ -911,3 +913,236 @@ impl Execution for FMVWX {
         Ok(())
     }
 }
+
+//Zfa
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111100000001?????000?????1010011")]
+#[derive(Debug)]
+struct FLIS();
+
+impl FloatInsn for FLIS {}
+
+impl FLoadImm<u32, F32Traits> for FLIS {
+    const MIN_NORMAL: f64 = f32::MIN_POSITIVE as f64;
+}
+
+impl Execution for FLIS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let res = self.load_imm(p.state().ir());
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F32);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010100??????????010?????1010011")]
+#[derive(Debug)]
+struct FMINMS();
+
+impl FloatInsn for FMINMS {}
+
+impl FCompute<u32, F32Traits> for FMINMS {
+    fn opt(&self, _: &InsnT, frs1: F32, frs2: F32, _: F32, fp_state: &mut FPState) -> F32 {
+        let res = frs1.compare_quiet(&frs2, Some(fp_state));
+        if frs1.is_nan() || frs2.is_nan() {
+            return F32::quiet_nan();
+        }
+        if frs1.is_negative_zero() && frs2.is_zero() {
+            return frs1;
+        }
+        if let Some(Ordering::Less) = res {
+            frs1
+        } else {
+            frs2
+        }
+    }
+}
+
+impl Execution for FMINMS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u32 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F32) as u32;
+        let rs2: u32 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F32) as u32;
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F32);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010100??????????011?????1010011")]
+#[derive(Debug)]
+struct FMAXMS();
+
+impl FloatInsn for FMAXMS {}
+
+impl FCompute<u32, F32Traits> for FMAXMS {
+    fn opt(&self, _: &InsnT, frs1: F32, frs2: F32, _: F32, fp_state: &mut FPState) -> F32 {
+        let res = frs1.compare_quiet(&frs2, Some(fp_state));
+        if frs1.is_nan() || frs2.is_nan() {
+            return F32::quiet_nan();
+        }
+        if frs1.is_positive_zero() && frs2.is_zero() {
+            return frs1;
+        }
+        if let Some(Ordering::Greater) = res {
+            frs1
+        } else {
+            frs2
+        }
+    }
+}
+
+impl Execution for FMAXMS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u32 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F32) as u32;
+        let rs2: u32 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F32) as u32;
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F32);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010000000100?????????????1010011")]
+#[derive(Debug)]
+struct FROUNDS();
+
+impl FloatInsn for FROUNDS {}
+
+impl FCompute<u32, F32Traits> for FROUNDS {
+    fn opt(&self, ir: &InsnT, frs1: F32, _: F32, _: F32, fp_state: &mut FPState) -> F32 {
+        let res = frs1.round_to_integral(false, Self::rm_from_bits(self.rm(ir)), Some(fp_state));
+        if res.is_nan() {
+            F32::quiet_nan()
+        } else {
+            res
+        }
+    }
+}
+
+impl Execution for FROUNDS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u32 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F32) as u32;
+        let res = self.compute(p.state().ir(), &*f, rs1, 0, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F32);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010000000101?????????????1010011")]
+#[derive(Debug)]
+struct FROUNDNXS();
+
+impl FloatInsn for FROUNDNXS {}
+
+impl FCompute<u32, F32Traits> for FROUNDNXS {
+    fn opt(&self, ir: &InsnT, frs1: F32, _: F32, _: F32, fp_state: &mut FPState) -> F32 {
+        let res = frs1.round_to_integral(true, Self::rm_from_bits(self.rm(ir)), Some(fp_state));
+        if res.is_nan() {
+            F32::quiet_nan()
+        } else {
+            res
+        }
+    }
+}
+
+impl Execution for FROUNDNXS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u32 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F32) as u32;
+        let res = self.compute(p.state().ir(), &*f, rs1, 0, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F32);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b1010000??????????100?????1010011")]
+#[derive(Debug)]
+struct FLEQS();
+
+impl FloatInsn for FLEQS {}
+
+impl FCompare<u32, F32Traits> for FLEQS {}
+
+impl Execution for FLEQS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u32 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F32) as u32;
+        let rs2: u32 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F32) as u32;
+        let res = self.compare(p.state().ir(), &*f, rs1, rs2, false)?;
+        let rd = self.rd(p.state().ir());
+        let value = if let Some(Ordering::Less) | Some(Ordering::Equal) = res {
+            1
+        } else {
+            0
+        };
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b1010000??????????101?????1010011")]
+#[derive(Debug)]
+struct FLTQS();
+
+impl FloatInsn for FLTQS {}
+
+impl FCompare<u32, F32Traits> for FLTQS {}
+
+impl Execution for FLTQS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u32 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F32) as u32;
+        let rs2: u32 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F32) as u32;
+        let res = self.compare(p.state().ir(), &*f, rs1, rs2, false)?;
+        let rd = self.rd(p.state().ir());
+        let value = if let Some(Ordering::Less) = res { 1 } else { 0 };
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
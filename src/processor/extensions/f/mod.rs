@@ -1,12 +1,16 @@
 use crate::prelude::{InsnT, RegT};
 use crate::processor::extensions::{HasCsr, NoStepCb};
 use crate::processor::ProcessorState;
+use crate::snapshot::{SnapshotReader, SnapshotWriter};
 use std::cell::RefCell;
+use std::io;
+use std::io::{Read, Write};
 use std::rc::Rc;
 
 pub mod csrs;
 pub mod float;
 mod insns;
+mod zfh;
 
 use csrs::FCsrs;
 
@@ -14,6 +18,8 @@ pub type FRegT = u128;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum FLen {
+    F16,
+    BF16,
     F32,
     F64,
     F128,
@@ -22,6 +28,7 @@ pub enum FLen {
 impl FLen {
     pub fn len(&self) -> usize {
         match self {
+            FLen::F16 | FLen::BF16 => 16,
             FLen::F32 => 32,
             FLen::F64 => 64,
             FLen::F128 => 128,
@@ -34,8 +41,9 @@ impl FLen {
 
     pub fn mask(&self) -> FRegT {
         match self {
-            FLen::F32 => ((1 as FRegT) << (self.len() as FRegT)) - 1,
-            FLen::F64 => ((1 as FRegT) << (self.len() as FRegT)) - 1,
+            FLen::F16 | FLen::BF16 | FLen::F32 | FLen::F64 => {
+                ((1 as FRegT) << (self.len() as FRegT)) - 1
+            }
             FLen::F128 => -1i128 as FRegT,
         }
     }
@@ -56,6 +64,8 @@ impl FLen {
                     v
                 } else {
                     match flen {
+                        FLen::F16 => *float::F16::quiet_nan().bits() as FRegT,
+                        FLen::BF16 => *float::BF16::quiet_nan().bits() as FRegT,
                         FLen::F32 => *float::F32::quiet_nan().bits() as FRegT,
                         FLen::F64 => *float::F64::quiet_nan().bits() as FRegT,
                         _ => unreachable!(),
@@ -83,9 +93,9 @@ impl ExtensionF {
             dirty: Rc::new(RefCell::new(0)),
         };
 
-        if state.config().extensions.contains(&'q') {
+        if state.config().isa.has('q') {
             e.flen = FLen::F128
-        } else if state.config().extensions.contains(&'d') {
+        } else if state.config().isa.has('d') {
             e.flen = FLen::F64
         }
 
@@ -157,6 +167,36 @@ impl ExtensionF {
     pub fn fregs(&self) -> &[FRegT; 32] {
         &self.freg
     }
+
+    pub fn frm(&self) -> RegT {
+        self.csrs.frm().get()
+    }
+
+    //vector instructions accumulate exception flags
+    pub fn accrue_fflags(&self, flags: RegT) {
+        if flags != 0 {
+            let fflags = self.csrs.fflags().get();
+            self.csrs.fflags_mut().set(fflags | flags);
+            *self.dirty.borrow_mut() = 0x3;
+        }
+    }
+
+    pub fn save_snapshot<W: Write>(&self, w: &mut SnapshotWriter<W>) -> io::Result<()> {
+        for f in self.freg.iter() {
+            w.write_u128(*f)?;
+        }
+        w.write_u64(self.csrs.fcsr().get())?;
+        w.write_u64(self.dirty())
+    }
+
+    pub fn restore_snapshot<R: Read>(&mut self, r: &mut SnapshotReader<R>) -> io::Result<()> {
+        for f in self.freg.iter_mut() {
+            *f = r.read_u128()?;
+        }
+        self.csrs.fcsr_mut().set(r.read_u64()?);
+        *self.dirty.borrow_mut() = r.read_u64()?;
+        Ok(())
+    }
 }
 
 impl HasCsr for ExtensionF {
@@ -0,0 +1,1378 @@
+use crate::prelude::*;
+use crate::processor::extensions::f::float::*;
+use crate::processor::extensions::f::{FLen, FRegT};
+use crate::processor::isa::IsaExt;
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+use std::cmp::Ordering;
+use std::num::Wrapping;
+
+//flh, fsh, fmv.x.h and fmv.h.x are shared by zfhmin and zfbfmin
+fn check_half_move(p: &Processor) -> Result<(), Exception> {
+    p.state()
+        .check_isa_ext(IsaExt::Zfhmin)
+        .or_else(|_| p.state().check_isa_ext(IsaExt::Zfbfmin))
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b?????????????????001?????0000111")]
+#[derive(Debug)]
+struct FLH();
+
+impl FloatInsn for FLH {}
+
+impl Execution for FLH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_half_move(p)?;
+        let f = self.get_f_ext(p)?;
+        let base: Wrapping<RegT> = Wrapping(*p.state().xreg(self.rs1(p.state().ir())));
+        let offset: Wrapping<RegT> =
+            Wrapping(sext(self.imm(p.state().ir()) as RegT, self.imm_len()));
+        let mut data: u16 = 0;
+        p.load_store()
+            .load_half_word(p.state(), &(base + offset).0, &mut data, p.mmu())?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(data as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b?????????????????001?????0100111")]
+#[derive(Debug)]
+struct FSH();
+
+impl FloatInsn for FSH {}
+
+impl FStore for FSH {}
+
+impl Execution for FSH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_half_move(p)?;
+        let f = self.get_f_ext(p)?;
+        let base: Wrapping<RegT> = Wrapping(*p.state().xreg(self.rs1(p.state().ir())));
+        let data = f.freg(self.src(p.state().ir()));
+        p.load_store().store_half_word(
+            p.state(),
+            &(base + self.offset(p.state().ir())).0,
+            unsafe { &*(data as *const FRegT as *const u16) },
+            p.mmu(),
+        )?;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0000010??????????????????1010011")]
+#[derive(Debug)]
+struct FADDH();
+
+impl FloatInsn for FADDH {}
+
+impl FCompute<u16, F16Traits> for FADDH {
+    fn opt(&self, ir: &InsnT, frs1: F16, frs2: F16, _: F16, fp_state: &mut FPState) -> F16 {
+        frs1.add(&frs2, Self::rm_from_bits(self.rm(ir)), Some(fp_state))
+    }
+}
+
+impl Execution for FADDH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let rs2: u16 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F16) as u16;
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0000110??????????????????1010011")]
+#[derive(Debug)]
+struct FSUBH();
+
+impl FloatInsn for FSUBH {}
+
+impl FCompute<u16, F16Traits> for FSUBH {
+    fn opt(&self, ir: &InsnT, frs1: F16, frs2: F16, _: F16, fp_state: &mut FPState) -> F16 {
+        frs1.sub(&frs2, Self::rm_from_bits(self.rm(ir)), Some(fp_state))
+    }
+}
+
+impl Execution for FSUBH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let rs2: u16 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F16) as u16;
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0001010??????????????????1010011")]
+#[derive(Debug)]
+struct FMULH();
+
+impl FloatInsn for FMULH {}
+
+impl FCompute<u16, F16Traits> for FMULH {
+    fn opt(&self, ir: &InsnT, frs1: F16, frs2: F16, _: F16, fp_state: &mut FPState) -> F16 {
+        frs1.mul(&frs2, Self::rm_from_bits(self.rm(ir)), Some(fp_state))
+    }
+}
+
+impl Execution for FMULH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let rs2: u16 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F16) as u16;
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0001110??????????????????1010011")]
+#[derive(Debug)]
+struct FDIVH();
+
+impl FloatInsn for FDIVH {}
+
+impl FCompute<u16, F16Traits> for FDIVH {
+    fn opt(&self, ir: &InsnT, frs1: F16, frs2: F16, _: F16, fp_state: &mut FPState) -> F16 {
+        frs1.div(&frs2, Self::rm_from_bits(self.rm(ir)), Some(fp_state))
+    }
+}
+
+impl Execution for FDIVH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let rs2: u16 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F16) as u16;
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010111000000?????????????1010011")]
+#[derive(Debug)]
+struct FSQRTH();
+
+impl FloatInsn for FSQRTH {}
+
+impl FCompute<u16, F16Traits> for FSQRTH {
+    fn opt(&self, ir: &InsnT, frs1: F16, _: F16, _: F16, fp_state: &mut FPState) -> F16 {
+        frs1.sqrt(Self::rm_from_bits(self.rm(ir)), Some(fp_state))
+    }
+}
+
+impl Execution for FSQRTH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let res = self.compute(p.state().ir(), &*f, rs1, 0, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010110??????????000?????1010011")]
+#[derive(Debug)]
+struct FMINH();
+
+impl FloatInsn for FMINH {}
+
+impl FCompute<u16, F16Traits> for FMINH {
+    fn opt(&self, _: &InsnT, frs1: F16, frs2: F16, _: F16, fp_state: &mut FPState) -> F16 {
+        if frs1.is_nan() && frs2.is_nan() {
+            return F16::quiet_nan();
+        }
+        if frs1.is_negative_zero() && frs2.is_zero() {
+            return frs1;
+        }
+        if let Some(Ordering::Less) = frs1.compare_quiet(&frs2, Some(fp_state)) {
+            frs1
+        } else {
+            frs2
+        }
+    }
+}
+
+impl Execution for FMINH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let rs2: u16 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F16) as u16;
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010110??????????001?????1010011")]
+#[derive(Debug)]
+struct FMAXH();
+
+impl FloatInsn for FMAXH {}
+
+impl FCompute<u16, F16Traits> for FMAXH {
+    fn opt(&self, _: &InsnT, frs1: F16, frs2: F16, _: F16, fp_state: &mut FPState) -> F16 {
+        if frs1.is_nan() && frs2.is_nan() {
+            return F16::quiet_nan();
+        }
+        if frs1.is_positive_zero() && frs2.is_zero() {
+            return frs1;
+        }
+        if let Some(Ordering::Greater) = frs1.compare_quiet(&frs2, Some(fp_state)) {
+            frs1
+        } else {
+            frs2
+        }
+    }
+}
+
+impl Execution for FMAXH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let rs2: u16 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F16) as u16;
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b?????10??????????????????1000011")]
+#[derive(Debug)]
+struct FMADDH();
+
+impl FloatInsn for FMADDH {}
+
+impl FCompute<u16, F16Traits> for FMADDH {
+    fn opt(&self, ir: &InsnT, frs1: F16, frs2: F16, frs3: F16, state: &mut FPState) -> F16 {
+        frs1.fused_mul_add(&frs2, &frs3, Self::rm_from_bits(self.rm(ir)), Some(state))
+    }
+}
+
+impl Execution for FMADDH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let rs2: u16 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F16) as u16;
+        let rs3: u16 = f.flen.boxed(*f.freg(self.rs3(p.state().ir())), FLen::F16) as u16;
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, rs3)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b?????10??????????????????1000111")]
+#[derive(Debug)]
+struct FMSUBH();
+
+impl FloatInsn for FMSUBH {}
+
+impl FCompute<u16, F16Traits> for FMSUBH {
+    fn opt(&self, ir: &InsnT, frs1: F16, frs2: F16, frs3: F16, state: &mut FPState) -> F16 {
+        frs1.fused_mul_add(
+            &frs2,
+            &frs3.neg(),
+            Self::rm_from_bits(self.rm(ir)),
+            Some(state),
+        )
+    }
+}
+
+impl Execution for FMSUBH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let rs2: u16 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F16) as u16;
+        let rs3: u16 = f.flen.boxed(*f.freg(self.rs3(p.state().ir())), FLen::F16) as u16;
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, rs3)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b?????10??????????????????1001011")]
+#[derive(Debug)]
+struct FNMSUBH();
+
+impl FloatInsn for FNMSUBH {}
+
+impl FCompute<u16, F16Traits> for FNMSUBH {
+    fn opt(&self, ir: &InsnT, frs1: F16, frs2: F16, frs3: F16, state: &mut FPState) -> F16 {
+        frs1.fused_mul_add(
+            &frs2,
+            &frs3.neg(),
+            Self::rm_from_bits(self.rm(ir)),
+            Some(state),
+        )
+        .neg()
+    }
+}
+
+impl Execution for FNMSUBH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let rs2: u16 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F16) as u16;
+        let rs3: u16 = f.flen.boxed(*f.freg(self.rs3(p.state().ir())), FLen::F16) as u16;
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, rs3)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b?????10??????????????????1001111")]
+#[derive(Debug)]
+struct FNMADDH();
+
+impl FloatInsn for FNMADDH {}
+
+impl FCompute<u16, F16Traits> for FNMADDH {
+    fn opt(&self, ir: &InsnT, frs1: F16, frs2: F16, frs3: F16, state: &mut FPState) -> F16 {
+        frs1.fused_mul_add(&frs2, &frs3, Self::rm_from_bits(self.rm(ir)), Some(state))
+            .neg()
+    }
+}
+
+impl Execution for FNMADDH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let rs2: u16 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F16) as u16;
+        let rs3: u16 = f.flen.boxed(*f.freg(self.rs3(p.state().ir())), FLen::F16) as u16;
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, rs3)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010010??????????000?????1010011")]
+#[derive(Debug)]
+struct FSGNJH();
+
+impl FloatInsn for FSGNJH {}
+
+impl Execution for FSGNJH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let rs2: u16 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F16) as u16;
+        let res = rs1 & ((1 << 15) - 1) | rs2 & (1 << 15);
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010010??????????001?????1010011")]
+#[derive(Debug)]
+struct FSGNJNH();
+
+impl FloatInsn for FSGNJNH {}
+
+impl Execution for FSGNJNH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let rs2: u16 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F16) as u16;
+        let res = rs1 & ((1 << 15) - 1) | !rs2 & (1 << 15);
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010010??????????010?????1010011")]
+#[derive(Debug)]
+struct FSGNJXH();
+
+impl FloatInsn for FSGNJXH {}
+
+impl Execution for FSGNJXH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let rs2: u16 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F16) as u16;
+        let res = rs1 & ((1 << 15) - 1) | (rs1 ^ rs2) & (1 << 15);
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b1010010??????????010?????1010011")]
+#[derive(Debug)]
+struct FEQH();
+
+impl FloatInsn for FEQH {}
+
+impl FCompare<u16, F16Traits> for FEQH {}
+
+impl Execution for FEQH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let rs2: u16 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F16) as u16;
+        if let Some(Ordering::Equal) = self.compare(p.state().ir(), &*f, rs1, rs2, false)? {
+            let rd = self.rd(p.state().ir());
+            let value = 1;
+            p.state_mut().set_xreg(rd, value);
+        } else {
+            let rd = self.rd(p.state().ir());
+            let value = 0;
+            p.state_mut().set_xreg(rd, value);
+        }
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b1010010??????????001?????1010011")]
+#[derive(Debug)]
+struct FLTH();
+
+impl FloatInsn for FLTH {}
+
+impl FCompare<u16, F16Traits> for FLTH {}
+
+impl Execution for FLTH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let rs2: u16 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F16) as u16;
+        if let Some(Ordering::Less) = self.compare(p.state().ir(), &*f, rs1, rs2, true)? {
+            let rd = self.rd(p.state().ir());
+            let value = 1;
+            p.state_mut().set_xreg(rd, value);
+        } else {
+            let rd = self.rd(p.state().ir());
+            let value = 0;
+            p.state_mut().set_xreg(rd, value);
+        }
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b1010010??????????000?????1010011")]
+#[derive(Debug)]
+struct FLEH();
+
+impl FloatInsn for FLEH {}
+
+impl FCompare<u16, F16Traits> for FLEH {}
+
+impl Execution for FLEH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let rs2: u16 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F16) as u16;
+        if let Some(Ordering::Less) | Some(Ordering::Equal) =
+            self.compare(p.state().ir(), &*f, rs1, rs2, true)?
+        {
+            let rd = self.rd(p.state().ir());
+            let value = 1;
+            p.state_mut().set_xreg(rd, value);
+        } else {
+            let rd = self.rd(p.state().ir());
+            let value = 0;
+            p.state_mut().set_xreg(rd, value);
+        }
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111001000000?????001?????1010011")]
+#[derive(Debug)]
+struct FCLASSH();
+
+impl FloatInsn for FCLASSH {}
+
+impl FClass<u16, F16Traits> for FCLASSH {}
+
+impl Execution for FCLASSH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let rd = self.rd(p.state().ir());
+        let value = self.class(rs1);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111001000000?????000?????1010011")]
+#[derive(Debug)]
+struct FMVXH();
+
+impl FloatInsn for FMVXH {}
+
+impl Execution for FMVXH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_half_move(p)?;
+        let f = self.get_f_ext(p)?;
+        let data: RegT = (*f.freg(self.rs1(p.state().ir())) & 0xffff) as RegT;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data, 16) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111101000000?????000?????1010011")]
+#[derive(Debug)]
+struct FMVHX();
+
+impl FloatInsn for FMVHX {}
+
+impl Execution for FMVHX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_half_move(p)?;
+        let f = self.get_f_ext(p)?;
+        let data: RegT = *p.state().xreg(self.rs1(p.state().ir())) & 0xffff;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(data as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110001000000?????????????1010011")]
+#[derive(Debug)]
+struct FCVTWH();
+
+impl FloatInsn for FCVTWH {}
+
+impl FToX<u16, F16Traits> for FCVTWH {
+    type T = i32;
+    fn opt(&self, ir: &InsnT, frs1: F16, state: &mut FPState) -> Self::T {
+        if let Some(v) = frs1.to_i32(true, Self::rm_from_bits(self.rm(ir)), Some(state)) {
+            v
+        } else {
+            if frs1.is_nan() || frs1.sign() == Sign::Positive {
+                ((1u32 << 31) - 1) as Self::T
+            } else {
+                (1u32 << 31) as Self::T
+            }
+        }
+    }
+}
+
+impl Execution for FCVTWH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let res = self.convert(p.state().ir(), &*f, rs1)? as u32;
+        let rd = self.rd(p.state().ir());
+        let value = sext(res as RegT, 32) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110001000001?????????????1010011")]
+#[derive(Debug)]
+struct FCVTWUH();
+
+impl FloatInsn for FCVTWUH {}
+
+impl FToX<u16, F16Traits> for FCVTWUH {
+    type T = u32;
+    fn opt(&self, ir: &InsnT, frs1: F16, state: &mut FPState) -> Self::T {
+        if let Some(v) = frs1.to_u32(true, Self::rm_from_bits(self.rm(ir)), Some(state)) {
+            v
+        } else {
+            if frs1.is_nan() || frs1.sign() == Sign::Positive {
+                -1i32 as Self::T
+            } else {
+                0
+            }
+        }
+    }
+}
+
+impl Execution for FCVTWUH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let res = self.convert(p.state().ir(), &*f, rs1)?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(res as RegT, 32) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110001000010?????????????1010011")]
+#[derive(Debug)]
+struct FCVTLH();
+
+impl FloatInsn for FCVTLH {}
+
+impl FToX<u16, F16Traits> for FCVTLH {
+    type T = i64;
+    fn opt(&self, ir: &InsnT, frs1: F16, state: &mut FPState) -> Self::T {
+        if let Some(v) = frs1.to_i64(true, Self::rm_from_bits(self.rm(ir)), Some(state)) {
+            v
+        } else {
+            if frs1.is_nan() || frs1.sign() == Sign::Positive {
+                ((1u64 << 63) - 1) as Self::T
+            } else {
+                (1u64 << 63) as Self::T
+            }
+        }
+    }
+}
+
+impl Execution for FCVTLH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_xlen(XLen::X64)?;
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let res = self.convert(p.state().ir(), &*f, rs1)? as u64;
+        let rd = self.rd(p.state().ir());
+        let value = res as RegT & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110001000011?????????????1010011")]
+#[derive(Debug)]
+struct FCVTLUH();
+
+impl FloatInsn for FCVTLUH {}
+
+impl FToX<u16, F16Traits> for FCVTLUH {
+    type T = u64;
+    fn opt(&self, ir: &InsnT, frs1: F16, state: &mut FPState) -> Self::T {
+        if let Some(v) = frs1.to_u64(true, Self::rm_from_bits(self.rm(ir)), Some(state)) {
+            v
+        } else {
+            if frs1.is_nan() || frs1.sign() == Sign::Positive {
+                -1i64 as Self::T
+            } else {
+                0
+            }
+        }
+    }
+}
+
+impl Execution for FCVTLUH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_xlen(XLen::X64)?;
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let res = self.convert(p.state().ir(), &*f, rs1)?;
+        let rd = self.rd(p.state().ir());
+        let value = res as RegT & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110101000000?????????????1010011")]
+#[derive(Debug)]
+struct FCVTHW();
+
+impl FloatInsn for FCVTHW {}
+
+impl XToF<u16, F16Traits> for FCVTHW {
+    type T = i32;
+    fn opt(&self, ir: &InsnT, rs1: Self::T, state: &mut FPState) -> F16 {
+        F16::from_i32(rs1, Self::rm_from_bits(self.rm(ir)), Some(state))
+    }
+}
+
+impl Execution for FCVTHW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: RegT = sext(*p.state().xreg(self.rs1(p.state().ir())), 32);
+        let fres = self.convert(p.state().ir(), &*f, rs1 as i32)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(fres as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110101000001?????????????1010011")]
+#[derive(Debug)]
+struct FCVTHWU();
+
+impl FloatInsn for FCVTHWU {}
+
+impl XToF<u16, F16Traits> for FCVTHWU {
+    type T = u32;
+    fn opt(&self, ir: &InsnT, rs1: Self::T, state: &mut FPState) -> F16 {
+        F16::from_u32(rs1, Self::rm_from_bits(self.rm(ir)), Some(state))
+    }
+}
+
+impl Execution for FCVTHWU {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: RegT = *p.state().xreg(self.rs1(p.state().ir())) & 0xffff_ffff;
+        let fres = self.convert(p.state().ir(), &*f, rs1 as u32)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(fres as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110101000010?????????????1010011")]
+#[derive(Debug)]
+struct FCVTHL();
+
+impl FloatInsn for FCVTHL {}
+
+impl XToF<u16, F16Traits> for FCVTHL {
+    type T = i64;
+    fn opt(&self, ir: &InsnT, rs1: Self::T, state: &mut FPState) -> F16 {
+        F16::from_i64(rs1, Self::rm_from_bits(self.rm(ir)), Some(state))
+    }
+}
+
+impl Execution for FCVTHL {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_xlen(XLen::X64)?;
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: RegT = *p.state().xreg(self.rs1(p.state().ir()));
+        let fres = self.convert(p.state().ir(), &*f, rs1 as i64)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(fres as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110101000011?????????????1010011")]
+#[derive(Debug)]
+struct FCVTHLU();
+
+impl FloatInsn for FCVTHLU {}
+
+impl XToF<u16, F16Traits> for FCVTHLU {
+    type T = u64;
+    fn opt(&self, ir: &InsnT, rs1: Self::T, state: &mut FPState) -> F16 {
+        F16::from_u64(rs1, Self::rm_from_bits(self.rm(ir)), Some(state))
+    }
+}
+
+impl Execution for FCVTHLU {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_xlen(XLen::X64)?;
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: RegT = *p.state().xreg(self.rs1(p.state().ir()));
+        let fres = self.convert(p.state().ir(), &*f, rs1 as u64)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(fres as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010001000000?????????????1010011")]
+#[derive(Debug)]
+struct FCVTHS();
+
+impl FloatInsn for FCVTHS {}
+
+impl FToX<u32, F32Traits> for FCVTHS {
+    type T = u16;
+    fn opt(&self, ir: &InsnT, frs1: F32, state: &mut FPState) -> Self::T {
+        *frs1
+            .convert_to_float::<F16Traits>(Self::rm_from_bits(self.rm(ir)), Some(state))
+            .bits()
+    }
+}
+
+impl Execution for FCVTHS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfhmin)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u32 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F32) as u32;
+        let fres = self.convert(p.state().ir(), &*f, rs1)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(fres as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010000000010?????????????1010011")]
+#[derive(Debug)]
+struct FCVTSH();
+
+impl FloatInsn for FCVTSH {}
+
+impl XToF<u32, F32Traits> for FCVTSH {
+    type T = u16;
+    fn opt(&self, ir: &InsnT, rs1: Self::T, state: &mut FPState) -> F32 {
+        let frs1 = F16::from_bits(rs1);
+        F32::convert_from_float::<F16Traits>(&frs1, Self::rm_from_bits(self.rm(ir)), Some(state))
+    }
+}
+
+impl Execution for FCVTSH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfhmin)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let fres = self.convert(p.state().ir(), &*f, rs1)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(fres as FRegT, FLen::F32);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010001000001?????????????1010011")]
+#[derive(Debug)]
+struct FCVTHD();
+
+impl FloatInsn for FCVTHD {}
+
+impl FToX<u64, F64Traits> for FCVTHD {
+    type T = u16;
+    fn opt(&self, ir: &InsnT, frs1: F64, state: &mut FPState) -> Self::T {
+        *frs1
+            .convert_to_float::<F16Traits>(Self::rm_from_bits(self.rm(ir)), Some(state))
+            .bits()
+    }
+}
+
+impl Execution for FCVTHD {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('d')?;
+        p.state().check_isa_ext(IsaExt::Zfhmin)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let fres = self.convert(p.state().ir(), &*f, rs1)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(fres as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010000100010?????????????1010011")]
+#[derive(Debug)]
+struct FCVTDH();
+
+impl FloatInsn for FCVTDH {}
+
+impl XToF<u64, F64Traits> for FCVTDH {
+    type T = u16;
+    fn opt(&self, ir: &InsnT, rs1: Self::T, state: &mut FPState) -> F64 {
+        let frs1 = F16::from_bits(rs1);
+        F64::convert_from_float::<F16Traits>(&frs1, Self::rm_from_bits(self.rm(ir)), Some(state))
+    }
+}
+
+impl Execution for FCVTDH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('d')?;
+        p.state().check_isa_ext(IsaExt::Zfhmin)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let fres = self.convert(p.state().ir(), &*f, rs1)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(fres as FRegT, FLen::F64);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+//Zfbfmin
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010001001000?????????????1010011")]
+#[derive(Debug)]
+struct FCVTBF16S();
+
+impl FloatInsn for FCVTBF16S {}
+
+impl FToX<u32, F32Traits> for FCVTBF16S {
+    type T = u16;
+    fn opt(&self, ir: &InsnT, frs1: F32, state: &mut FPState) -> Self::T {
+        *frs1
+            .convert_to_float::<BF16Traits>(Self::rm_from_bits(self.rm(ir)), Some(state))
+            .bits()
+    }
+}
+
+impl Execution for FCVTBF16S {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfbfmin)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u32 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F32) as u32;
+        let fres = self.convert(p.state().ir(), &*f, rs1)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(fres as FRegT, FLen::BF16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010000000110?????????????1010011")]
+#[derive(Debug)]
+struct FCVTSBF16();
+
+impl FloatInsn for FCVTSBF16 {}
+
+impl XToF<u32, F32Traits> for FCVTSBF16 {
+    type T = u16;
+    fn opt(&self, ir: &InsnT, rs1: Self::T, state: &mut FPState) -> F32 {
+        let frs1 = BF16::from_bits(rs1);
+        F32::convert_from_float::<BF16Traits>(&frs1, Self::rm_from_bits(self.rm(ir)), Some(state))
+    }
+}
+
+impl Execution for FCVTSBF16 {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfbfmin)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::BF16) as u16;
+        let fres = self.convert(p.state().ir(), &*f, rs1)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(fres as FRegT, FLen::F32);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+//Zfa
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111101000001?????000?????1010011")]
+#[derive(Debug)]
+struct FLIH();
+
+impl FloatInsn for FLIH {}
+
+impl FLoadImm<u16, F16Traits> for FLIH {
+    const MIN_NORMAL: f64 = 6.103515625e-5;
+}
+
+impl Execution for FLIH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let res = self.load_imm(p.state().ir());
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010110??????????010?????1010011")]
+#[derive(Debug)]
+struct FMINMH();
+
+impl FloatInsn for FMINMH {}
+
+impl FCompute<u16, F16Traits> for FMINMH {
+    fn opt(&self, _: &InsnT, frs1: F16, frs2: F16, _: F16, fp_state: &mut FPState) -> F16 {
+        let res = frs1.compare_quiet(&frs2, Some(fp_state));
+        if frs1.is_nan() || frs2.is_nan() {
+            return F16::quiet_nan();
+        }
+        if frs1.is_negative_zero() && frs2.is_zero() {
+            return frs1;
+        }
+        if let Some(Ordering::Less) = res {
+            frs1
+        } else {
+            frs2
+        }
+    }
+}
+
+impl Execution for FMINMH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let rs2: u16 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F16) as u16;
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010110??????????011?????1010011")]
+#[derive(Debug)]
+struct FMAXMH();
+
+impl FloatInsn for FMAXMH {}
+
+impl FCompute<u16, F16Traits> for FMAXMH {
+    fn opt(&self, _: &InsnT, frs1: F16, frs2: F16, _: F16, fp_state: &mut FPState) -> F16 {
+        let res = frs1.compare_quiet(&frs2, Some(fp_state));
+        if frs1.is_nan() || frs2.is_nan() {
+            return F16::quiet_nan();
+        }
+        if frs1.is_positive_zero() && frs2.is_zero() {
+            return frs1;
+        }
+        if let Some(Ordering::Greater) = res {
+            frs1
+        } else {
+            frs2
+        }
+    }
+}
+
+impl Execution for FMAXMH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let rs2: u16 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F16) as u16;
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010001000100?????????????1010011")]
+#[derive(Debug)]
+struct FROUNDH();
+
+impl FloatInsn for FROUNDH {}
+
+impl FCompute<u16, F16Traits> for FROUNDH {
+    fn opt(&self, ir: &InsnT, frs1: F16, _: F16, _: F16, fp_state: &mut FPState) -> F16 {
+        let res = frs1.round_to_integral(false, Self::rm_from_bits(self.rm(ir)), Some(fp_state));
+        if res.is_nan() {
+            F16::quiet_nan()
+        } else {
+            res
+        }
+    }
+}
+
+impl Execution for FROUNDH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let res = self.compute(p.state().ir(), &*f, rs1, 0, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010001000101?????????????1010011")]
+#[derive(Debug)]
+struct FROUNDNXH();
+
+impl FloatInsn for FROUNDNXH {}
+
+impl FCompute<u16, F16Traits> for FROUNDNXH {
+    fn opt(&self, ir: &InsnT, frs1: F16, _: F16, _: F16, fp_state: &mut FPState) -> F16 {
+        let res = frs1.round_to_integral(true, Self::rm_from_bits(self.rm(ir)), Some(fp_state));
+        if res.is_nan() {
+            F16::quiet_nan()
+        } else {
+            res
+        }
+    }
+}
+
+impl Execution for FROUNDNXH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let res = self.compute(p.state().ir(), &*f, rs1, 0, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F16);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b1010010??????????100?????1010011")]
+#[derive(Debug)]
+struct FLEQH();
+
+impl FloatInsn for FLEQH {}
+
+impl FCompare<u16, F16Traits> for FLEQH {}
+
+impl Execution for FLEQH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let rs2: u16 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F16) as u16;
+        let res = self.compare(p.state().ir(), &*f, rs1, rs2, false)?;
+        let rd = self.rd(p.state().ir());
+        let value = if let Some(Ordering::Less) | Some(Ordering::Equal) = res {
+            1
+        } else {
+            0
+        };
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b1010010??????????101?????1010011")]
+#[derive(Debug)]
+struct FLTQH();
+
+impl FloatInsn for FLTQH {}
+
+impl FCompare<u16, F16Traits> for FLTQH {}
+
+impl Execution for FLTQH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zfh)?;
+        p.state().check_isa_ext(IsaExt::Zfa)?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u16 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F16) as u16;
+        let rs2: u16 = f.flen.boxed(*f.freg(self.rs2(p.state().ir())), FLen::F16) as u16;
+        let res = self.compare(p.state().ir(), &*f, rs1, rs2, false)?;
+        let rd = self.rd(p.state().ir());
+        let value = if let Some(Ordering::Less) = res { 1 } else { 0 };
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
@@ -0,0 +1,424 @@
+use crate::prelude::*;
+use crate::processor::isa::IsaExt;
+use crate::processor::trap::Exception;
+use crate::processor::{Privilege, Processor};
+
+//hfence and hinval are allowed in HS and M
+fn check_hfence(p: &Processor, gvma: bool) -> Result<(), Exception> {
+    p.state().check_extension('h')?;
+    if p.state().virt() {
+        return Err(Exception::VirtualInsn(*p.state().ir()));
+    }
+    if *p.state().privilege() == Privilege::U
+        || gvma
+            && *p.state().privilege() == Privilege::S
+            && p.state().priv_m().mstatus().tvm() == 1
+    {
+        return Err(Exception::IllegalInsn(*p.state().ir()));
+    }
+    Ok(())
+}
+
+fn hfence_vvma(p: &mut Processor, rs1: InsnT, rs2: InsnT) {
+    let pc = *p.state().pc() + 4;
+    let va = if rs1 != 0 {
+        Some(*p.state().xreg(rs1))
+    } else {
+        None
+    };
+    let asid = if rs2 != 0 {
+        Some(*p.state().xreg(rs2))
+    } else {
+        None
+    };
+    p.mmu().flush(p.state(), true, va, asid);
+    p.fetcher().flush_icache();
+    p.state_mut().set_pc(pc);
+}
+
+fn hfence_gvma(p: &mut Processor, rs2: InsnT) {
+    let pc = *p.state().pc() + 4;
+    //guest physical address in rs1 is ignored, tlb entries only keep the final translation
+    let vmid = if rs2 != 0 {
+        Some(*p.state().xreg(rs2))
+    } else {
+        None
+    };
+    p.mmu().flush_gstage(vmid);
+    p.fetcher().flush_icache();
+    p.state_mut().set_pc(pc);
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010001??????????000000001110011")]
+#[derive(Debug)]
+struct HFENCEVVMA();
+
+impl Execution for HFENCEVVMA {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_hfence(p, false)?;
+        hfence_vvma(p, self.rs1(p.state().ir()), self.rs2(p.state().ir()));
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0110001??????????000000001110011")]
+#[derive(Debug)]
+struct HFENCEGVMA();
+
+impl Execution for HFENCEGVMA {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_hfence(p, true)?;
+        hfence_gvma(p, self.rs2(p.state().ir()));
+        Ok(())
+    }
+}
+
+//Svinval
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010011??????????000000001110011")]
+#[derive(Debug)]
+struct HINVALVVMA();
+
+impl Execution for HINVALVVMA {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Svinval)?;
+        check_hfence(p, false)?;
+        hfence_vvma(p, self.rs1(p.state().ir()), self.rs2(p.state().ir()));
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0110011??????????000000001110011")]
+#[derive(Debug)]
+struct HINVALGVMA();
+
+impl Execution for HINVALGVMA {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Svinval)?;
+        check_hfence(p, true)?;
+        hfence_gvma(p, self.rs2(p.state().ir()));
+        Ok(())
+    }
+}
+
+//hypervisor virtual-machine load and store are allowed in HS, M and U if hstatus.hu is set
+fn check_hyper_access(p: &Processor) -> Result<(), Exception> {
+    p.state().check_extension('h')?;
+    if p.state().virt() {
+        return Err(Exception::VirtualInsn(*p.state().ir()));
+    }
+    if *p.state().privilege() == Privilege::U && p.state().priv_h()?.hstatus().hu() == 0 {
+        return Err(Exception::IllegalInsn(*p.state().ir()));
+    }
+    Ok(())
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011000000000?????100?????1110011")]
+#[derive(Debug)]
+struct HLVB();
+
+impl Execution for HLVB {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_hyper_access(p)?;
+        let addr = *p.state().xreg(self.rs1(p.state().ir()));
+        let mut data: u8 = 0;
+        p.mmu().hyper_access(false, || {
+            p.load_store()
+                .load_byte(p.state(), &addr, &mut data, p.mmu())
+        })?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data as RegT, 8) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011000000001?????100?????1110011")]
+#[derive(Debug)]
+struct HLVBU();
+
+impl Execution for HLVBU {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_hyper_access(p)?;
+        let addr = *p.state().xreg(self.rs1(p.state().ir()));
+        let mut data: u8 = 0;
+        p.mmu().hyper_access(false, || {
+            p.load_store()
+                .load_byte(p.state(), &addr, &mut data, p.mmu())
+        })?;
+        let rd = self.rd(p.state().ir());
+        let value = data as RegT;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011001000000?????100?????1110011")]
+#[derive(Debug)]
+struct HLVH();
+
+impl Execution for HLVH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_hyper_access(p)?;
+        let addr = *p.state().xreg(self.rs1(p.state().ir()));
+        let mut data: u16 = 0;
+        p.mmu().hyper_access(false, || {
+            p.load_store()
+                .load_half_word(p.state(), &addr, &mut data, p.mmu())
+        })?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data as RegT, 16) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011001000001?????100?????1110011")]
+#[derive(Debug)]
+struct HLVHU();
+
+impl Execution for HLVHU {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_hyper_access(p)?;
+        let addr = *p.state().xreg(self.rs1(p.state().ir()));
+        let mut data: u16 = 0;
+        p.mmu().hyper_access(false, || {
+            p.load_store()
+                .load_half_word(p.state(), &addr, &mut data, p.mmu())
+        })?;
+        let rd = self.rd(p.state().ir());
+        let value = data as RegT;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011001000011?????100?????1110011")]
+#[derive(Debug)]
+struct HLVXHU();
+
+impl Execution for HLVXHU {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_hyper_access(p)?;
+        let addr = *p.state().xreg(self.rs1(p.state().ir()));
+        let mut data: u16 = 0;
+        p.mmu().hyper_access(true, || {
+            p.load_store()
+                .load_half_word(p.state(), &addr, &mut data, p.mmu())
+        })?;
+        let rd = self.rd(p.state().ir());
+        let value = data as RegT;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011010000000?????100?????1110011")]
+#[derive(Debug)]
+struct HLVW();
+
+impl Execution for HLVW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_hyper_access(p)?;
+        let addr = *p.state().xreg(self.rs1(p.state().ir()));
+        let mut data: u32 = 0;
+        p.mmu().hyper_access(false, || {
+            p.load_store()
+                .load_word(p.state(), &addr, &mut data, p.mmu())
+        })?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(data as RegT, 32) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011010000001?????100?????1110011")]
+#[derive(Debug)]
+struct HLVWU();
+
+impl Execution for HLVWU {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_xlen(XLen::X64)?;
+        check_hyper_access(p)?;
+        let addr = *p.state().xreg(self.rs1(p.state().ir()));
+        let mut data: u32 = 0;
+        p.mmu().hyper_access(false, || {
+            p.load_store()
+                .load_word(p.state(), &addr, &mut data, p.mmu())
+        })?;
+        let rd = self.rd(p.state().ir());
+        let value = data as RegT;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011010000011?????100?????1110011")]
+#[derive(Debug)]
+struct HLVXWU();
+
+impl Execution for HLVXWU {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_hyper_access(p)?;
+        let addr = *p.state().xreg(self.rs1(p.state().ir()));
+        let mut data: u32 = 0;
+        p.mmu().hyper_access(true, || {
+            p.load_store()
+                .load_word(p.state(), &addr, &mut data, p.mmu())
+        })?;
+        let rd = self.rd(p.state().ir());
+        let value = data as RegT;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011011000000?????100?????1110011")]
+#[derive(Debug)]
+struct HLVD();
+
+impl Execution for HLVD {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_xlen(XLen::X64)?;
+        check_hyper_access(p)?;
+        let addr = *p.state().xreg(self.rs1(p.state().ir()));
+        let mut data: u64 = 0;
+        p.mmu().hyper_access(false, || {
+            p.load_store()
+                .load_double_word(p.state(), &addr, &mut data, p.mmu())
+        })?;
+        let rd = self.rd(p.state().ir());
+        let value = data as RegT;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0110001??????????100000001110011")]
+#[derive(Debug)]
+struct HSVB();
+
+impl Execution for HSVB {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_hyper_access(p)?;
+        let addr = *p.state().xreg(self.rs1(p.state().ir()));
+        let data = p.state().xreg(self.rs2(p.state().ir()));
+        p.mmu().hyper_access(false, || {
+            p.load_store()
+                .store_byte(p.state(), &addr, &(*data as u8), p.mmu())
+        })?;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0110011??????????100000001110011")]
+#[derive(Debug)]
+struct HSVH();
+
+impl Execution for HSVH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_hyper_access(p)?;
+        let addr = *p.state().xreg(self.rs1(p.state().ir()));
+        let data = p.state().xreg(self.rs2(p.state().ir()));
+        p.mmu().hyper_access(false, || {
+            p.load_store()
+                .store_half_word(p.state(), &addr, &(*data as u16), p.mmu())
+        })?;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0110101??????????100000001110011")]
+#[derive(Debug)]
+struct HSVW();
+
+impl Execution for HSVW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_hyper_access(p)?;
+        let addr = *p.state().xreg(self.rs1(p.state().ir()));
+        let data = p.state().xreg(self.rs2(p.state().ir()));
+        p.mmu().hyper_access(false, || {
+            p.load_store()
+                .store_word(p.state(), &addr, &(*data as u32), p.mmu())
+        })?;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0110111??????????100000001110011")]
+#[derive(Debug)]
+struct HSVD();
+
+impl Execution for HSVD {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_xlen(XLen::X64)?;
+        check_hyper_access(p)?;
+        let addr = *p.state().xreg(self.rs1(p.state().ir()));
+        let data = p.state().xreg(self.rs2(p.state().ir()));
+        p.mmu().hyper_access(false, || {
+            p.load_store()
+                .store_double_word(p.state(), &addr, data, p.mmu())
+        })?;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
@@ -0,0 +1,15 @@
+use crate::processor::extensions::NoStepCb;
+use crate::processor::{NoCsr, ProcessorState};
+
+mod insns;
+
+pub struct ExtensionH {}
+
+impl ExtensionH {
+    pub fn new(_: &ProcessorState) -> ExtensionH {
+        ExtensionH {}
+    }
+}
+impl NoCsr for ExtensionH {}
+
+impl NoStepCb for ExtensionH {}
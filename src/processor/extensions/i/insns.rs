@@ -473,6 +473,7 @@ impl Execution for XORI {
 #[derive(Debug)]
 struct ORI();
 
+//prefetch.i/r/w of zicbop are ori with rd == x0, executed as no-ops
 impl Execution for ORI {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
@@ -1273,6 +1274,7 @@ impl Execution for ECALL {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         match p.state().privilege() {
             Privilege::M => Err(Exception::MCall),
+            Privilege::S if p.state().virt() => Err(Exception::VSCall),
             Privilege::S => Err(Exception::SCall),
             Privilege::U => Err(Exception::UCall),
         }
@@ -1300,6 +1302,22 @@ struct MRET();
 impl Execution for MRET {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state_mut().trap_return(&Privilege::M);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b01111011001000000000000001110011")]
+#[derive(Debug)]
+struct DRET();
+
+impl Execution for DRET {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        if !p.state().debug_mode() {
+            return Err(Exception::IllegalInsn(*p.state().ir()));
+        }
+        p.state_mut().debug_return();
         p.mmu().flush_tlb();
         p.fetcher().flush_icache();
         Ok(())
@@ -1320,6 +1338,9 @@ impl Execution for WFI {
         {
             return Err(Exception::IllegalInsn(*p.state().ir()));
         }
+        if p.state().virt() && p.state().priv_h()?.hstatus().vtw() != 0 {
+            return Err(Exception::VirtualInsn(*p.state().ir()));
+        }
         if m.mip().get() & m.mie().get() != 0 {
             let pc = *p.state().pc() + 4;
             p.state_mut().set_pc(pc);
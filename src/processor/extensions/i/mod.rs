@@ -2,6 +2,7 @@ use crate::processor::extensions::NoStepCb;
 use crate::processor::{NoCsr, ProcessorState};
 
 mod insns;
+mod zicbo;
 
 pub struct ExtensionI {}
 
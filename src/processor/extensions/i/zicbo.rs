@@ -0,0 +1,110 @@
+use crate::prelude::*;
+use crate::processor::isa::IsaExt;
+use crate::processor::privilege::Envcfg;
+use crate::processor::trap::Exception;
+use crate::processor::{Privilege, Processor};
+
+//cbie, cbcfe and cbze of menvcfg, henvcfg and senvcfg enable cbo insns in less privileged modes
+fn check_cbo_enabled<F: Fn(&Envcfg) -> RegT>(p: &Processor, enabled: F) -> Result<(), Exception> {
+    let ir = *p.state().ir();
+    if *p.state().privilege() == Privilege::M {
+        return Ok(());
+    }
+    if enabled(&*p.state().priv_m().menvcfg()) == 0 {
+        return Err(Exception::IllegalInsn(ir));
+    }
+    if p.state().virt() && enabled(&*p.state().priv_h()?.henvcfg()) == 0 {
+        return Err(Exception::VirtualInsn(ir));
+    }
+    if *p.state().privilege() == Privilege::U {
+        if let Ok(s) = p.state().priv_s() {
+            if enabled(&*s.senvcfg()) == 0 {
+                return Err(if p.state().virt() {
+                    Exception::VirtualInsn(ir)
+                } else {
+                    Exception::IllegalInsn(ir)
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+fn cache_block_op(p: &mut Processor, rs1: InsnT) -> Result<(), Exception> {
+    let addr = *p.state().xreg(rs1);
+    p.load_store().check_cache_block(
+        p.state(),
+        &addr,
+        p.state().config().cache_block_size,
+        p.mmu(),
+    )?;
+    let pc = *p.state().pc() + 4;
+    p.state_mut().set_pc(pc);
+    Ok(())
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b000000000000?????010000000001111")]
+#[derive(Debug)]
+struct CBOINVAL();
+
+//no cache is modeled, inval is the same as flush whatever cbie is
+impl Execution for CBOINVAL {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zicbom)?;
+        check_cbo_enabled(p, |envcfg| envcfg.cbie())?;
+        cache_block_op(p, self.rs1(p.state().ir()))
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b000000000001?????010000000001111")]
+#[derive(Debug)]
+struct CBOCLEAN();
+
+impl Execution for CBOCLEAN {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zicbom)?;
+        check_cbo_enabled(p, |envcfg| envcfg.cbcfe())?;
+        cache_block_op(p, self.rs1(p.state().ir()))
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b000000000010?????010000000001111")]
+#[derive(Debug)]
+struct CBOFLUSH();
+
+impl Execution for CBOFLUSH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zicbom)?;
+        check_cbo_enabled(p, |envcfg| envcfg.cbcfe())?;
+        cache_block_op(p, self.rs1(p.state().ir()))
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b000000000100?????010000000001111")]
+#[derive(Debug)]
+struct CBOZERO();
+
+impl Execution for CBOZERO {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zicboz)?;
+        check_cbo_enabled(p, |envcfg| envcfg.cbze())?;
+        let addr = *p.state().xreg(self.rs1(p.state().ir()));
+        p.load_store().zero_cache_block(
+            p.state(),
+            &addr,
+            p.state().config().cache_block_size,
+            p.mmu(),
+        )?;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+use crate::prelude::*;
+use crate::processor::isa::IsaExt;
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+
+//look up each sew bits element of rs2 in rs1, out of range indices give 0
+fn xperm(rs1: RegT, rs2: RegT, sew: usize, xlen: usize) -> RegT {
+    let mask = (1 << sew) - 1;
+    (0..xlen).step_by(sew).fold(0, |acc, i| {
+        let pos = ((rs2 >> i) & mask) as usize * sew;
+        if pos < xlen {
+            acc | ((rs1 >> pos) & mask) << i
+        } else {
+            acc
+        }
+    })
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0000100??????????111?????0110011")]
+#[derive(Debug)]
+struct PACKH();
+
+impl Execution for PACKH {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbkb)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = (rs2 & 0xff) << 8 | rs1 & 0xff;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b011010000111?????101?????0010011")]
+#[derive(Debug)]
+struct BREV8();
+
+impl Execution for BREV8 {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbkb)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = (0..8).fold(0, |acc, i| {
+            acc | (((rs1 >> (i << 3)) as u8).reverse_bits() as RegT) << (i << 3)
+        }) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b000010001111?????001?????0010011")]
+#[derive(Debug)]
+struct ZIP();
+
+impl Execution for ZIP {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbkb)?;
+        p.state().check_xlen(XLen::X32)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = (0..16).fold(0, |acc, i| {
+            acc | ((rs1 >> i) & 1) << (i << 1) | ((rs1 >> (i + 16)) & 1) << ((i << 1) + 1)
+        });
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b000010001111?????101?????0010011")]
+#[derive(Debug)]
+struct UNZIP();
+
+impl Execution for UNZIP {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbkb)?;
+        p.state().check_xlen(XLen::X32)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = (0..16).fold(0, |acc, i| {
+            acc | ((rs1 >> (i << 1)) & 1) << i | ((rs1 >> ((i << 1) + 1)) & 1) << (i + 16)
+        });
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010100??????????100?????0110011")]
+#[derive(Debug)]
+struct XPERM8();
+
+impl Execution for XPERM8 {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbkx)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = xperm(rs1, rs2, 8, p.state().config().xlen.len());
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010100??????????010?????0110011")]
+#[derive(Debug)]
+struct XPERM4();
+
+impl Execution for XPERM4 {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zbkx)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = xperm(rs1, rs2, 4, p.state().config().xlen.len());
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
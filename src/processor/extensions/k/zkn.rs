@@ -0,0 +1,692 @@
+use crate::prelude::*;
+use crate::processor::isa::IsaExt;
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+
+const AES_SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+const AES_INV_SBOX: [u8; 256] = [
+    0x52, 0x09, 0x6a, 0xd5, 0x30, 0x36, 0xa5, 0x38, 0xbf, 0x40, 0xa3, 0x9e, 0x81, 0xf3, 0xd7, 0xfb,
+    0x7c, 0xe3, 0x39, 0x82, 0x9b, 0x2f, 0xff, 0x87, 0x34, 0x8e, 0x43, 0x44, 0xc4, 0xde, 0xe9, 0xcb,
+    0x54, 0x7b, 0x94, 0x32, 0xa6, 0xc2, 0x23, 0x3d, 0xee, 0x4c, 0x95, 0x0b, 0x42, 0xfa, 0xc3, 0x4e,
+    0x08, 0x2e, 0xa1, 0x66, 0x28, 0xd9, 0x24, 0xb2, 0x76, 0x5b, 0xa2, 0x49, 0x6d, 0x8b, 0xd1, 0x25,
+    0x72, 0xf8, 0xf6, 0x64, 0x86, 0x68, 0x98, 0x16, 0xd4, 0xa4, 0x5c, 0xcc, 0x5d, 0x65, 0xb6, 0x92,
+    0x6c, 0x70, 0x48, 0x50, 0xfd, 0xed, 0xb9, 0xda, 0x5e, 0x15, 0x46, 0x57, 0xa7, 0x8d, 0x9d, 0x84,
+    0x90, 0xd8, 0xab, 0x00, 0x8c, 0xbc, 0xd3, 0x0a, 0xf7, 0xe4, 0x58, 0x05, 0xb8, 0xb3, 0x45, 0x06,
+    0xd0, 0x2c, 0x1e, 0x8f, 0xca, 0x3f, 0x0f, 0x02, 0xc1, 0xaf, 0xbd, 0x03, 0x01, 0x13, 0x8a, 0x6b,
+    0x3a, 0x91, 0x11, 0x41, 0x4f, 0x67, 0xdc, 0xea, 0x97, 0xf2, 0xcf, 0xce, 0xf0, 0xb4, 0xe6, 0x73,
+    0x96, 0xac, 0x74, 0x22, 0xe7, 0xad, 0x35, 0x85, 0xe2, 0xf9, 0x37, 0xe8, 0x1c, 0x75, 0xdf, 0x6e,
+    0x47, 0xf1, 0x1a, 0x71, 0x1d, 0x29, 0xc5, 0x89, 0x6f, 0xb7, 0x62, 0x0e, 0xaa, 0x18, 0xbe, 0x1b,
+    0xfc, 0x56, 0x3e, 0x4b, 0xc6, 0xd2, 0x79, 0x20, 0x9a, 0xdb, 0xc0, 0xfe, 0x78, 0xcd, 0x5a, 0xf4,
+    0x1f, 0xdd, 0xa8, 0x33, 0x88, 0x07, 0xc7, 0x31, 0xb1, 0x12, 0x10, 0x59, 0x27, 0x80, 0xec, 0x5f,
+    0x60, 0x51, 0x7f, 0xa9, 0x19, 0xb5, 0x4a, 0x0d, 0x2d, 0xe5, 0x7a, 0x9f, 0x93, 0xc9, 0x9c, 0xef,
+    0xa0, 0xe0, 0x3b, 0x4d, 0xae, 0x2a, 0xf5, 0xb0, 0xc8, 0xeb, 0xbb, 0x3c, 0x83, 0x53, 0x99, 0x61,
+    0x17, 0x2b, 0x04, 0x7e, 0xba, 0x77, 0xd6, 0x26, 0xe1, 0x69, 0x14, 0x63, 0x55, 0x21, 0x0c, 0x7d,
+];
+
+const AES_RCON: [u8; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+//mixcolumns and inverse mixcolumns matrix rows
+const AES_MIX: [u8; 4] = [2, 3, 1, 1];
+const AES_INV_MIX: [u8; 4] = [14, 11, 13, 9];
+
+//multiply in GF(2^8) modulo x^8 + x^4 + x^3 + x + 1
+fn aes_gmul(a: u8, b: u8) -> u8 {
+    (0..8)
+        .fold((0, a), |(acc, a), i| {
+            let acc = if (b >> i) & 1 == 1 { acc ^ a } else { acc };
+            let a = if a & 0x80 != 0 {
+                (a << 1) ^ 0x1b
+            } else {
+                a << 1
+            };
+            (acc, a)
+        })
+        .0
+}
+
+//one column, byte 0 is row 0
+fn aes_mixcolumn(col: u32, mix: &[u8; 4]) -> u32 {
+    (0..4).fold(0, |acc, i| {
+        let b = (0..4).fold(0, |b, j| {
+            b ^ aes_gmul((col >> (j << 3)) as u8, mix[(j + 4 - i) & 3])
+        });
+        acc | (b as u32) << (i << 3)
+    })
+}
+
+fn aes_subword(word: u32, sbox: &[u8; 256]) -> u32 {
+    (0..4).fold(0, |acc, i| {
+        acc | (sbox[((word >> (i << 3)) & 0xff) as usize] as u32) << (i << 3)
+    })
+}
+
+//column 0 and 1 of (inverse) shiftrows and subbytes, state is rs2:rs1 in column-major order
+fn aes64_shift_sub(rs1: RegT, rs2: RegT, inverse: bool) -> u64 {
+    let state = (rs2 as u128) << 64 | rs1 as u128;
+    (0..8).fold(0, |acc, k| {
+        let (r, c) = (k & 3, k >> 2);
+        let src = if inverse {
+            (c + 4 - r) & 3
+        } else {
+            (c + r) & 3
+        };
+        let b = ((state >> (((src << 2) + r) << 3)) & 0xff) as usize;
+        let s = if inverse {
+            AES_INV_SBOX[b]
+        } else {
+            AES_SBOX[b]
+        };
+        acc | (s as u64) << (k << 3)
+    })
+}
+
+fn aes64_mixcolumns(value: u64, mix: &[u8; 4]) -> u64 {
+    (aes_mixcolumn((value >> 32) as u32, mix) as u64) << 32
+        | aes_mixcolumn(value as u32, mix) as u64
+}
+
+//aes32 insns xor one transformed byte of rs2 selected by bs into rs1
+fn aes32(p: &Processor, rs1: RegT, rs2: RegT, sbox: &[u8; 256], mix: Option<&[u8; 4]>) -> RegT {
+    let shamt = (*p.state().ir() >> 30) << 3;
+    let so = sbox[((rs2 >> shamt) & 0xff) as usize] as u32;
+    let mixed = if let Some(mix) = mix {
+        aes_mixcolumn(so, mix)
+    } else {
+        so
+    };
+    sext((rs1 as u32 ^ mixed.rotate_left(shamt)) as RegT, 32) & p.state().config().xlen.mask()
+}
+
+//key schedule of round rnum, rnum == 0xa skips rotation and rcon for aes-256
+fn aes64_ks1(rs1: RegT, rnum: usize) -> u64 {
+    let temp = (rs1 >> 32) as u32;
+    let temp = if rnum == 0xa {
+        aes_subword(temp, &AES_SBOX)
+    } else {
+        aes_subword(temp.rotate_right(8), &AES_SBOX) ^ AES_RCON[rnum] as u32
+    };
+    (temp as u64) << 32 | temp as u64
+}
+
+//aes64ks1i and aes64ks2 are shared by zkne and zknd
+fn check_aes64_ks(p: &Processor) -> Result<(), Exception> {
+    p.state().check_xlen(XLen::X64)?;
+    p.state()
+        .check_isa_ext(IsaExt::Zkne)
+        .or_else(|_| p.state().check_isa_ext(IsaExt::Zknd))
+}
+
+fn sha256_sig0(x: u32) -> u32 {
+    x.rotate_right(7) ^ x.rotate_right(18) ^ (x >> 3)
+}
+
+fn sha256_sig1(x: u32) -> u32 {
+    x.rotate_right(17) ^ x.rotate_right(19) ^ (x >> 10)
+}
+
+fn sha256_sum0(x: u32) -> u32 {
+    x.rotate_right(2) ^ x.rotate_right(13) ^ x.rotate_right(22)
+}
+
+fn sha256_sum1(x: u32) -> u32 {
+    x.rotate_right(6) ^ x.rotate_right(11) ^ x.rotate_right(25)
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b??10001??????????000?????0110011")]
+#[derive(Debug)]
+struct AES32ESI();
+
+impl Execution for AES32ESI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zkne)?;
+        p.state().check_xlen(XLen::X32)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = aes32(p, rs1, rs2, &AES_SBOX, None);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b??10011??????????000?????0110011")]
+#[derive(Debug)]
+struct AES32ESMI();
+
+impl Execution for AES32ESMI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zkne)?;
+        p.state().check_xlen(XLen::X32)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = aes32(p, rs1, rs2, &AES_SBOX, Some(&AES_MIX));
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b??10101??????????000?????0110011")]
+#[derive(Debug)]
+struct AES32DSI();
+
+impl Execution for AES32DSI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zknd)?;
+        p.state().check_xlen(XLen::X32)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = aes32(p, rs1, rs2, &AES_INV_SBOX, None);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b??10111??????????000?????0110011")]
+#[derive(Debug)]
+struct AES32DSMI();
+
+impl Execution for AES32DSMI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zknd)?;
+        p.state().check_xlen(XLen::X32)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = aes32(p, rs1, rs2, &AES_INV_SBOX, Some(&AES_INV_MIX));
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0011001??????????000?????0110011")]
+#[derive(Debug)]
+struct AES64ES();
+
+impl Execution for AES64ES {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zkne)?;
+        p.state().check_xlen(XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = aes64_shift_sub(rs1, rs2, false);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0011011??????????000?????0110011")]
+#[derive(Debug)]
+struct AES64ESM();
+
+impl Execution for AES64ESM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zkne)?;
+        p.state().check_xlen(XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = aes64_mixcolumns(aes64_shift_sub(rs1, rs2, false), &AES_MIX);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0011101??????????000?????0110011")]
+#[derive(Debug)]
+struct AES64DS();
+
+impl Execution for AES64DS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zknd)?;
+        p.state().check_xlen(XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = aes64_shift_sub(rs1, rs2, true);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0011111??????????000?????0110011")]
+#[derive(Debug)]
+struct AES64DSM();
+
+impl Execution for AES64DSM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zknd)?;
+        p.state().check_xlen(XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = aes64_mixcolumns(aes64_shift_sub(rs1, rs2, true), &AES_INV_MIX);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b001100000000?????001?????0010011")]
+#[derive(Debug)]
+struct AES64IM();
+
+impl Execution for AES64IM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zknd)?;
+        p.state().check_xlen(XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = aes64_mixcolumns(rs1, &AES_INV_MIX);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b00110001?????????001?????0010011")]
+#[derive(Debug)]
+struct AES64KS1I();
+
+impl Execution for AES64KS1I {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_aes64_ks(p)?;
+        let rnum = ((*p.state().ir() >> 20) & 0xf) as usize;
+        if rnum > 0xa {
+            return Err(Exception::IllegalInsn(*p.state().ir()));
+        }
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = aes64_ks1(rs1, rnum);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0111111??????????000?????0110011")]
+#[derive(Debug)]
+struct AES64KS2();
+
+impl Execution for AES64KS2 {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        check_aes64_ks(p)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = {
+            let w0 = (rs1 >> 32) as u32 ^ rs2 as u32;
+            let w1 = w0 ^ (rs2 >> 32) as u32;
+            (w1 as u64) << 32 | w0 as u64
+        };
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b000100000000?????001?????0010011")]
+#[derive(Debug)]
+struct SHA256SUM0();
+
+impl Execution for SHA256SUM0 {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zknh)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = sext(sha256_sum0(rs1 as u32) as RegT, 32) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b000100000001?????001?????0010011")]
+#[derive(Debug)]
+struct SHA256SUM1();
+
+impl Execution for SHA256SUM1 {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zknh)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = sext(sha256_sum1(rs1 as u32) as RegT, 32) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b000100000010?????001?????0010011")]
+#[derive(Debug)]
+struct SHA256SIG0();
+
+impl Execution for SHA256SIG0 {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zknh)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = sext(sha256_sig0(rs1 as u32) as RegT, 32) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b000100000011?????001?????0010011")]
+#[derive(Debug)]
+struct SHA256SIG1();
+
+impl Execution for SHA256SIG1 {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zknh)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = sext(sha256_sig1(rs1 as u32) as RegT, 32) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b000100000100?????001?????0010011")]
+#[derive(Debug)]
+struct SHA512SUM0();
+
+impl Execution for SHA512SUM0 {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zknh)?;
+        p.state().check_xlen(XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = rs1.rotate_right(28) ^ rs1.rotate_right(34) ^ rs1.rotate_right(39);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b000100000101?????001?????0010011")]
+#[derive(Debug)]
+struct SHA512SUM1();
+
+impl Execution for SHA512SUM1 {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zknh)?;
+        p.state().check_xlen(XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = rs1.rotate_right(14) ^ rs1.rotate_right(18) ^ rs1.rotate_right(41);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b000100000110?????001?????0010011")]
+#[derive(Debug)]
+struct SHA512SIG0();
+
+impl Execution for SHA512SIG0 {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zknh)?;
+        p.state().check_xlen(XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = rs1.rotate_right(1) ^ rs1.rotate_right(8) ^ (rs1 >> 7);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b000100000111?????001?????0010011")]
+#[derive(Debug)]
+struct SHA512SIG1();
+
+impl Execution for SHA512SIG1 {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zknh)?;
+        p.state().check_xlen(XLen::X64)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = rs1.rotate_right(19) ^ rs1.rotate_right(61) ^ (rs1 >> 6);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0101000??????????000?????0110011")]
+#[derive(Debug)]
+struct SHA512SUM0R();
+
+impl Execution for SHA512SUM0R {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zknh)?;
+        p.state().check_xlen(XLen::X32)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let (a, b) = (rs1 as u32, rs2 as u32);
+        let rd = self.rd(p.state().ir());
+        let value = sext(
+            ((a << 25) ^ (a << 30) ^ (a >> 28) ^ (b >> 7) ^ (b >> 2) ^ (b << 4)) as RegT,
+            32,
+        ) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0101001??????????000?????0110011")]
+#[derive(Debug)]
+struct SHA512SUM1R();
+
+impl Execution for SHA512SUM1R {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zknh)?;
+        p.state().check_xlen(XLen::X32)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let (a, b) = (rs1 as u32, rs2 as u32);
+        let rd = self.rd(p.state().ir());
+        let value = sext(
+            ((a << 23) ^ (a >> 14) ^ (a >> 18) ^ (b >> 9) ^ (b << 18) ^ (b << 14)) as RegT,
+            32,
+        ) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0101010??????????000?????0110011")]
+#[derive(Debug)]
+struct SHA512SIG0L();
+
+impl Execution for SHA512SIG0L {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zknh)?;
+        p.state().check_xlen(XLen::X32)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let (a, b) = (rs1 as u32, rs2 as u32);
+        let rd = self.rd(p.state().ir());
+        let value = sext(
+            ((a >> 1) ^ (a >> 7) ^ (a >> 8) ^ (b << 31) ^ (b << 25) ^ (b << 24)) as RegT,
+            32,
+        ) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0101110??????????000?????0110011")]
+#[derive(Debug)]
+struct SHA512SIG0H();
+
+impl Execution for SHA512SIG0H {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zknh)?;
+        p.state().check_xlen(XLen::X32)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let (a, b) = (rs1 as u32, rs2 as u32);
+        let rd = self.rd(p.state().ir());
+        let value = sext(
+            ((a >> 1) ^ (a >> 7) ^ (a >> 8) ^ (b << 31) ^ (b << 24)) as RegT,
+            32,
+        ) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0101011??????????000?????0110011")]
+#[derive(Debug)]
+struct SHA512SIG1L();
+
+impl Execution for SHA512SIG1L {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zknh)?;
+        p.state().check_xlen(XLen::X32)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let (a, b) = (rs1 as u32, rs2 as u32);
+        let rd = self.rd(p.state().ir());
+        let value = sext(
+            ((a << 3) ^ (a >> 6) ^ (a >> 19) ^ (b >> 29) ^ (b << 26) ^ (b << 13)) as RegT,
+            32,
+        ) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0101111??????????000?????0110011")]
+#[derive(Debug)]
+struct SHA512SIG1H();
+
+impl Execution for SHA512SIG1H {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_isa_ext(IsaExt::Zknh)?;
+        p.state().check_xlen(XLen::X32)?;
+        let rs1 = *p.state().xreg(self.rs1(p.state().ir()));
+        let rs2 = *p.state().xreg(self.rs2(p.state().ir()));
+        let (a, b) = (rs1 as u32, rs2 as u32);
+        let rd = self.rd(p.state().ir());
+        let value = sext(
+            ((a << 3) ^ (a >> 6) ^ (a >> 19) ^ (b >> 29) ^ (b << 13)) as RegT,
+            32,
+        ) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
@@ -61,4 +61,4 @@ macro_rules! declare_extension {
     }
 }
 
-declare_extension!(a, c, d, f, i, m, s, u);
+declare_extension!(a, b, c, d, f, h, i, k, m, q, s, u, v);
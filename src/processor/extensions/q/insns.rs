@@ -0,0 +1,1023 @@
+use crate::prelude::*;
+use crate::processor::extensions::f::float::*;
+use crate::processor::extensions::f::{FLen, FRegT};
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+use std::cmp::Ordering;
+use std::num::Wrapping;
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b?????????????????100?????0000111")]
+#[derive(Debug)]
+struct FLQ();
+
+impl FloatInsn for FLQ {}
+
+impl Execution for FLQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let base: Wrapping<RegT> = Wrapping(*p.state().xreg(self.rs1(p.state().ir())));
+        let offset: Wrapping<RegT> =
+            Wrapping(sext(self.imm(p.state().ir()) as RegT, self.imm_len()));
+        let mut data: u128 = 0;
+        p.load_store()
+            .load_quad_word(p.state(), &(base + offset).0, &mut data, p.mmu())?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(data as FRegT, FLen::F128);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b?????????????????100?????0100111")]
+#[derive(Debug)]
+struct FSQ();
+
+impl FloatInsn for FSQ {}
+
+impl FStore for FSQ {}
+
+impl Execution for FSQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let base: Wrapping<RegT> = Wrapping(*p.state().xreg(self.rs1(p.state().ir())));
+        let data = f.freg(self.src(p.state().ir()));
+        p.load_store().store_quad_word(
+            p.state(),
+            &(base + self.offset(p.state().ir())).0,
+            unsafe { &*(data as *const FRegT as *const u128) },
+            p.mmu(),
+        )?;
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0000011??????????????????1010011")]
+#[derive(Debug)]
+struct FADDQ();
+
+impl FloatInsn for FADDQ {}
+
+impl FCompute<u128, F128Traits> for FADDQ {
+    fn opt(&self, ir: &InsnT, frs1: F128, frs2: F128, _: F128, fp_state: &mut FPState) -> F128 {
+        frs1.add(&frs2, Self::rm_from_bits(self.rm(ir)), Some(fp_state))
+    }
+}
+
+impl Execution for FADDQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let rs2: u128 = *f.freg(self.rs2(p.state().ir()));
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F128);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0000111??????????????????1010011")]
+#[derive(Debug)]
+struct FSUBQ();
+
+impl FloatInsn for FSUBQ {}
+
+impl FCompute<u128, F128Traits> for FSUBQ {
+    fn opt(&self, ir: &InsnT, frs1: F128, frs2: F128, _: F128, fp_state: &mut FPState) -> F128 {
+        frs1.sub(&frs2, Self::rm_from_bits(self.rm(ir)), Some(fp_state))
+    }
+}
+
+impl Execution for FSUBQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let rs2: u128 = *f.freg(self.rs2(p.state().ir()));
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F128);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0001011??????????????????1010011")]
+#[derive(Debug)]
+struct FMULQ();
+
+impl FloatInsn for FMULQ {}
+
+impl FCompute<u128, F128Traits> for FMULQ {
+    fn opt(&self, ir: &InsnT, frs1: F128, frs2: F128, _: F128, fp_state: &mut FPState) -> F128 {
+        frs1.mul(&frs2, Self::rm_from_bits(self.rm(ir)), Some(fp_state))
+    }
+}
+
+impl Execution for FMULQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let rs2: u128 = *f.freg(self.rs2(p.state().ir()));
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F128);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0001111??????????????????1010011")]
+#[derive(Debug)]
+struct FDIVQ();
+
+impl FloatInsn for FDIVQ {}
+
+impl FCompute<u128, F128Traits> for FDIVQ {
+    fn opt(&self, ir: &InsnT, frs1: F128, frs2: F128, _: F128, fp_state: &mut FPState) -> F128 {
+        frs1.div(&frs2, Self::rm_from_bits(self.rm(ir)), Some(fp_state))
+    }
+}
+
+impl Execution for FDIVQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let rs2: u128 = *f.freg(self.rs2(p.state().ir()));
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F128);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010111100000?????????????1010011")]
+#[derive(Debug)]
+struct FSQRTQ();
+
+impl FloatInsn for FSQRTQ {}
+
+impl FCompute<u128, F128Traits> for FSQRTQ {
+    fn opt(&self, ir: &InsnT, frs1: F128, _: F128, _: F128, fp_state: &mut FPState) -> F128 {
+        frs1.sqrt(Self::rm_from_bits(self.rm(ir)), Some(fp_state))
+    }
+}
+
+impl Execution for FSQRTQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let res = self.compute(p.state().ir(), &*f, rs1, 0, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F128);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010111??????????000?????1010011")]
+#[derive(Debug)]
+struct FMINQ();
+
+impl FloatInsn for FMINQ {}
+
+impl FCompute<u128, F128Traits> for FMINQ {
+    fn opt(&self, _: &InsnT, frs1: F128, frs2: F128, _: F128, fp_state: &mut FPState) -> F128 {
+        if frs1.is_nan() && frs2.is_nan() {
+            return F128::quiet_nan();
+        }
+        if frs1.is_negative_zero() && frs2.is_zero() {
+            return frs1;
+        }
+        if let Some(Ordering::Less) = frs1.compare_quiet(&frs2, Some(fp_state)) {
+            frs1
+        } else {
+            frs2
+        }
+    }
+}
+
+impl Execution for FMINQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let rs2: u128 = *f.freg(self.rs2(p.state().ir()));
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F128);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010111??????????001?????1010011")]
+#[derive(Debug)]
+struct FMAXQ();
+
+impl FloatInsn for FMAXQ {}
+
+impl FCompute<u128, F128Traits> for FMAXQ {
+    fn opt(&self, _: &InsnT, frs1: F128, frs2: F128, _: F128, fp_state: &mut FPState) -> F128 {
+        if frs1.is_nan() && frs2.is_nan() {
+            return F128::quiet_nan();
+        }
+        if frs1.is_positive_zero() && frs2.is_zero() {
+            return frs1;
+        }
+        if let Some(Ordering::Greater) = frs1.compare_quiet(&frs2, Some(fp_state)) {
+            frs1
+        } else {
+            frs2
+        }
+    }
+}
+
+impl Execution for FMAXQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let rs2: u128 = *f.freg(self.rs2(p.state().ir()));
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, 0)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F128);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b?????11??????????????????1000011")]
+#[derive(Debug)]
+struct FMADDQ();
+
+impl FloatInsn for FMADDQ {}
+
+impl FCompute<u128, F128Traits> for FMADDQ {
+    fn opt(&self, ir: &InsnT, frs1: F128, frs2: F128, frs3: F128, state: &mut FPState) -> F128 {
+        frs1.fused_mul_add(&frs2, &frs3, Self::rm_from_bits(self.rm(ir)), Some(state))
+    }
+}
+
+impl Execution for FMADDQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let rs2: u128 = *f.freg(self.rs2(p.state().ir()));
+        let rs3: u128 = *f.freg(self.rs3(p.state().ir()));
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, rs3)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F128);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b?????11??????????????????1000111")]
+#[derive(Debug)]
+struct FMSUBQ();
+
+impl FloatInsn for FMSUBQ {}
+
+impl FCompute<u128, F128Traits> for FMSUBQ {
+    fn opt(&self, ir: &InsnT, frs1: F128, frs2: F128, frs3: F128, state: &mut FPState) -> F128 {
+        frs1.fused_mul_add(
+            &frs2,
+            &frs3.neg(),
+            Self::rm_from_bits(self.rm(ir)),
+            Some(state),
+        )
+    }
+}
+
+impl Execution for FMSUBQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let rs2: u128 = *f.freg(self.rs2(p.state().ir()));
+        let rs3: u128 = *f.freg(self.rs3(p.state().ir()));
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, rs3)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F128);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b?????11??????????????????1001011")]
+#[derive(Debug)]
+struct FMNSUBQ();
+
+impl FloatInsn for FMNSUBQ {}
+
+impl FCompute<u128, F128Traits> for FMNSUBQ {
+    fn opt(&self, ir: &InsnT, frs1: F128, frs2: F128, frs3: F128, state: &mut FPState) -> F128 {
+        frs1.fused_mul_add(
+            &frs2,
+            &frs3.neg(),
+            Self::rm_from_bits(self.rm(ir)),
+            Some(state),
+        )
+        .neg()
+    }
+}
+
+impl Execution for FMNSUBQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let rs2: u128 = *f.freg(self.rs2(p.state().ir()));
+        let rs3: u128 = *f.freg(self.rs3(p.state().ir()));
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, rs3)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F128);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b?????11??????????????????1001111")]
+#[derive(Debug)]
+struct FMNADDQ();
+
+impl FloatInsn for FMNADDQ {}
+
+impl FCompute<u128, F128Traits> for FMNADDQ {
+    fn opt(&self, ir: &InsnT, frs1: F128, frs2: F128, frs3: F128, state: &mut FPState) -> F128 {
+        frs1.fused_mul_add(&frs2, &frs3, Self::rm_from_bits(self.rm(ir)), Some(state))
+            .neg()
+    }
+}
+
+impl Execution for FMNADDQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let rs2: u128 = *f.freg(self.rs2(p.state().ir()));
+        let rs3: u128 = *f.freg(self.rs3(p.state().ir()));
+        let res = self.compute(p.state().ir(), &*f, rs1, rs2, rs3)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F128);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110001100000?????????????1010011")]
+#[derive(Debug)]
+struct FCVTWQ();
+
+impl FloatInsn for FCVTWQ {}
+
+impl FToX<u128, F128Traits> for FCVTWQ {
+    type T = i32;
+    fn opt(&self, ir: &InsnT, frs1: F128, state: &mut FPState) -> Self::T {
+        if let Some(v) = frs1.to_i32(true, Self::rm_from_bits(self.rm(ir)), Some(state)) {
+            v
+        } else {
+            if frs1.is_nan() || frs1.sign() == Sign::Positive {
+                ((1u32 << 31) - 1) as Self::T
+            } else {
+                (1u32 << 31) as Self::T
+            }
+        }
+    }
+}
+
+impl Execution for FCVTWQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let res = self.convert(p.state().ir(), &*f, rs1)? as u32;
+        let rd = self.rd(p.state().ir());
+        let value = sext(res as RegT, 32) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110001100001?????????????1010011")]
+#[derive(Debug)]
+struct FCVTWUQ();
+
+impl FloatInsn for FCVTWUQ {}
+
+impl FToX<u128, F128Traits> for FCVTWUQ {
+    type T = u32;
+    fn opt(&self, ir: &InsnT, frs1: F128, state: &mut FPState) -> Self::T {
+        if let Some(v) = frs1.to_u32(true, Self::rm_from_bits(self.rm(ir)), Some(state)) {
+            v
+        } else {
+            if frs1.is_nan() || frs1.sign() == Sign::Positive {
+                -1i32 as Self::T
+            } else {
+                0
+            }
+        }
+    }
+}
+
+impl Execution for FCVTWUQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let res = self.convert(p.state().ir(), &*f, rs1)?;
+        let rd = self.rd(p.state().ir());
+        let value = sext(res as RegT, 32) & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110001100010?????????????1010011")]
+#[derive(Debug)]
+struct FCVTLQ();
+
+impl FloatInsn for FCVTLQ {}
+
+impl FToX<u128, F128Traits> for FCVTLQ {
+    type T = i64;
+    fn opt(&self, ir: &InsnT, frs1: F128, state: &mut FPState) -> Self::T {
+        if let Some(v) = frs1.to_i64(true, Self::rm_from_bits(self.rm(ir)), Some(state)) {
+            v
+        } else {
+            if frs1.is_nan() || frs1.sign() == Sign::Positive {
+                ((1u64 << 63) - 1) as Self::T
+            } else {
+                (1u64 << 63) as Self::T
+            }
+        }
+    }
+}
+
+impl Execution for FCVTLQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_xlen(XLen::X64)?;
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let res = self.convert(p.state().ir(), &*f, rs1)? as u64;
+        let rd = self.rd(p.state().ir());
+        let value = res as RegT & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110001100011?????????????1010011")]
+#[derive(Debug)]
+struct FCVTLUQ();
+
+impl FloatInsn for FCVTLUQ {}
+
+impl FToX<u128, F128Traits> for FCVTLUQ {
+    type T = u64;
+    fn opt(&self, ir: &InsnT, frs1: F128, state: &mut FPState) -> Self::T {
+        if let Some(v) = frs1.to_u64(true, Self::rm_from_bits(self.rm(ir)), Some(state)) {
+            v
+        } else {
+            if frs1.is_nan() || frs1.sign() == Sign::Positive {
+                -1i64 as Self::T
+            } else {
+                0
+            }
+        }
+    }
+}
+
+impl Execution for FCVTLUQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_xlen(XLen::X64)?;
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let res = self.convert(p.state().ir(), &*f, rs1)?;
+        let rd = self.rd(p.state().ir());
+        let value = res as RegT & p.state().config().xlen.mask();
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110101100000?????????????1010011")]
+#[derive(Debug)]
+struct FCVTQW();
+
+impl FloatInsn for FCVTQW {}
+
+impl XToF<u128, F128Traits> for FCVTQW {
+    type T = i32;
+    fn opt(&self, ir: &InsnT, rs1: Self::T, state: &mut FPState) -> F128 {
+        F128::from_i32(rs1, Self::rm_from_bits(self.rm(ir)), Some(state))
+    }
+}
+
+impl Execution for FCVTQW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: RegT = sext(*p.state().xreg(self.rs1(p.state().ir())), 32);
+        let fres = self.convert(p.state().ir(), &*f, rs1 as i32)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(fres as FRegT, FLen::F128);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110101100001?????????????1010011")]
+#[derive(Debug)]
+struct FCVTQWU();
+
+impl FloatInsn for FCVTQWU {}
+
+impl XToF<u128, F128Traits> for FCVTQWU {
+    type T = u32;
+    fn opt(&self, ir: &InsnT, rs1: Self::T, state: &mut FPState) -> F128 {
+        F128::from_u32(rs1, Self::rm_from_bits(self.rm(ir)), Some(state))
+    }
+}
+
+impl Execution for FCVTQWU {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: RegT = *p.state().xreg(self.rs1(p.state().ir())) & 0xffff_ffff;
+        let fres = self.convert(p.state().ir(), &*f, rs1 as u32)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(fres as FRegT, FLen::F128);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110101100010?????????????1010011")]
+#[derive(Debug)]
+struct FCVTQL();
+
+impl FloatInsn for FCVTQL {}
+
+impl XToF<u128, F128Traits> for FCVTQL {
+    type T = i64;
+    fn opt(&self, ir: &InsnT, rs1: Self::T, state: &mut FPState) -> F128 {
+        F128::from_i64(rs1, Self::rm_from_bits(self.rm(ir)), Some(state))
+    }
+}
+
+impl Execution for FCVTQL {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_xlen(XLen::X64)?;
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: RegT = *p.state().xreg(self.rs1(p.state().ir()));
+        let fres = self.convert(p.state().ir(), &*f, rs1 as i64)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(fres as FRegT, FLen::F128);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110101100011?????????????1010011")]
+#[derive(Debug)]
+struct FCVTQLU();
+
+impl FloatInsn for FCVTQLU {}
+
+impl XToF<u128, F128Traits> for FCVTQLU {
+    type T = u64;
+    fn opt(&self, ir: &InsnT, rs1: Self::T, state: &mut FPState) -> F128 {
+        F128::from_u64(rs1, Self::rm_from_bits(self.rm(ir)), Some(state))
+    }
+}
+
+impl Execution for FCVTQLU {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_xlen(XLen::X64)?;
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: RegT = *p.state().xreg(self.rs1(p.state().ir()));
+        let fres = self.convert(p.state().ir(), &*f, rs1 as u64)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(fres as FRegT, FLen::F128);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010011??????????000?????1010011")]
+#[derive(Debug)]
+struct FSGNJQ();
+
+impl FloatInsn for FSGNJQ {}
+
+impl Execution for FSGNJQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let rs2: u128 = *f.freg(self.rs2(p.state().ir()));
+        let res = rs1 & ((1 << 127) - 1) | rs2 & (1 << 127);
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F128);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010011??????????001?????1010011")]
+#[derive(Debug)]
+struct FSGNJNQ();
+
+impl FloatInsn for FSGNJNQ {}
+
+impl Execution for FSGNJNQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let rs2: u128 = *f.freg(self.rs2(p.state().ir()));
+        let res = rs1 & ((1 << 127) - 1) | !rs2 & (1 << 127);
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F128);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0010011??????????010?????1010011")]
+#[derive(Debug)]
+struct FSGNJXQ();
+
+impl FloatInsn for FSGNJXQ {}
+
+impl Execution for FSGNJXQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let rs2: u128 = *f.freg(self.rs2(p.state().ir()));
+        let res = rs1 & ((1 << 127) - 1) | (rs1 ^ rs2) & (1 << 127);
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(res as FRegT, FLen::F128);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b1010011??????????010?????1010011")]
+#[derive(Debug)]
+struct FEQQ();
+
+impl FloatInsn for FEQQ {}
+
+impl FCompare<u128, F128Traits> for FEQQ {}
+
+impl Execution for FEQQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let rs2: u128 = *f.freg(self.rs2(p.state().ir()));
+        if let Some(Ordering::Equal) = self.compare(p.state().ir(), &*f, rs1, rs2, false)? {
+            let rd = self.rd(p.state().ir());
+            let value = 1;
+            p.state_mut().set_xreg(rd, value);
+        } else {
+            let rd = self.rd(p.state().ir());
+            let value = 0;
+            p.state_mut().set_xreg(rd, value);
+        }
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b1010011??????????001?????1010011")]
+#[derive(Debug)]
+struct FLTQ();
+
+impl FloatInsn for FLTQ {}
+
+impl FCompare<u128, F128Traits> for FLTQ {}
+
+impl Execution for FLTQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let rs2: u128 = *f.freg(self.rs2(p.state().ir()));
+        if let Some(Ordering::Less) = self.compare(p.state().ir(), &*f, rs1, rs2, true)? {
+            let rd = self.rd(p.state().ir());
+            let value = 1;
+            p.state_mut().set_xreg(rd, value);
+        } else {
+            let rd = self.rd(p.state().ir());
+            let value = 0;
+            p.state_mut().set_xreg(rd, value);
+        }
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b1010011??????????000?????1010011")]
+#[derive(Debug)]
+struct FLEQ();
+
+impl FloatInsn for FLEQ {}
+
+impl FCompare<u128, F128Traits> for FLEQ {}
+
+impl Execution for FLEQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let rs2: u128 = *f.freg(self.rs2(p.state().ir()));
+        let res = self.compare(p.state().ir(), &*f, rs1, rs2, true)?;
+        if let Some(Ordering::Equal) = res {
+            let rd = self.rd(p.state().ir());
+            let value = 1;
+            p.state_mut().set_xreg(rd, value);
+        } else if let Some(Ordering::Less) = res {
+            let rd = self.rd(p.state().ir());
+            let value = 1;
+            p.state_mut().set_xreg(rd, value);
+        } else {
+            let rd = self.rd(p.state().ir());
+            let value = 0;
+            p.state_mut().set_xreg(rd, value);
+        }
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111001100000?????001?????1010011")]
+#[derive(Debug)]
+struct FCLASSQ();
+
+impl FloatInsn for FCLASSQ {}
+
+impl FClass<u128, F128Traits> for FCLASSQ {}
+
+impl Execution for FCLASSQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let rd = self.rd(p.state().ir());
+        let value = self.class(rs1);
+        let pc = *p.state().pc() + 4;
+        p.state_mut().set_xreg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010000000011?????????????1010011")]
+#[derive(Debug)]
+struct FCVTSQ();
+
+impl FloatInsn for FCVTSQ {}
+
+impl FToX<u128, F128Traits> for FCVTSQ {
+    type T = u32;
+    fn opt(&self, ir: &InsnT, frs1: F128, state: &mut FPState) -> Self::T {
+        *frs1
+            .convert_to_float::<F32Traits>(Self::rm_from_bits(self.rm(ir)), Some(state))
+            .bits()
+    }
+}
+
+impl Execution for FCVTSQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let fres = self.convert(p.state().ir(), &*f, rs1)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(fres as FRegT, FLen::F32);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010001100000?????????????1010011")]
+#[derive(Debug)]
+struct FCVTQS();
+
+impl FloatInsn for FCVTQS {}
+
+impl XToF<u128, F128Traits> for FCVTQS {
+    type T = u32;
+    fn opt(&self, ir: &InsnT, rs1: Self::T, state: &mut FPState) -> F128 {
+        let frs1 = F32::from_bits(rs1);
+        F128::convert_from_float::<F32Traits>(&frs1, Self::rm_from_bits(self.rm(ir)), Some(state))
+    }
+}
+
+impl Execution for FCVTQS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u32 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F32) as u32;
+        let fres = self.convert(p.state().ir(), &*f, rs1)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(fres as FRegT, FLen::F128);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010000100011?????????????1010011")]
+#[derive(Debug)]
+struct FCVTDQ();
+
+impl FloatInsn for FCVTDQ {}
+
+impl FToX<u128, F128Traits> for FCVTDQ {
+    type T = u64;
+    fn opt(&self, ir: &InsnT, frs1: F128, state: &mut FPState) -> Self::T {
+        *frs1
+            .convert_to_float::<F64Traits>(Self::rm_from_bits(self.rm(ir)), Some(state))
+            .bits()
+    }
+}
+
+impl Execution for FCVTDQ {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u128 = *f.freg(self.rs1(p.state().ir()));
+        let fres = self.convert(p.state().ir(), &*f, rs1)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(fres as FRegT, FLen::F64);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010001100001?????????????1010011")]
+#[derive(Debug)]
+struct FCVTQD();
+
+impl FloatInsn for FCVTQD {}
+
+impl XToF<u128, F128Traits> for FCVTQD {
+    type T = u64;
+    fn opt(&self, ir: &InsnT, rs1: Self::T, state: &mut FPState) -> F128 {
+        let frs1 = F64::from_bits(rs1);
+        F128::convert_from_float::<F64Traits>(&frs1, Self::rm_from_bits(self.rm(ir)), Some(state))
+    }
+}
+
+impl Execution for FCVTQD {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('q')?;
+        let f = self.get_f_ext(p)?;
+        let rs1: u64 = f.flen.boxed(*f.freg(self.rs1(p.state().ir())), FLen::F64) as u64;
+        let fres = self.convert(p.state().ir(), &*f, rs1)?;
+        let rd = self.rd(p.state().ir());
+        let value = f.flen.padding(fres as FRegT, FLen::F128);
+        let pc = *p.state().pc() + 4;
+        self.get_f_ext_mut(p).set_freg(rd, value);
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
@@ -0,0 +1,13 @@
+use crate::processor::extensions::{NoCsr, NoStepCb};
+use crate::processor::ProcessorState;
+
+mod insns;
+pub struct ExtensionQ {}
+
+impl ExtensionQ {
+    pub fn new(_: &ProcessorState) -> ExtensionQ {
+        ExtensionQ {}
+    }
+}
+impl NoCsr for ExtensionQ {}
+impl NoStepCb for ExtensionQ {}
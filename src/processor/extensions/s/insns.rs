@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use crate::processor::isa::IsaExt;
 use crate::processor::trap::Exception;
 use crate::processor::{Privilege, Processor};
 
@@ -11,19 +12,26 @@ struct SRET();
 impl Execution for SRET {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('s')?;
-        let tsr = p.state().priv_m().mstatus().tsr();
-        if tsr == 1 && *p.state().privilege() == Privilege::S {
-            return Err(Exception::IllegalInsn(*p.state().ir()));
+        if p.state().virt() {
+            if *p.state().privilege() == Privilege::U || p.state().priv_h()?.hstatus().vtsr() == 1
+            {
+                return Err(Exception::VirtualInsn(*p.state().ir()));
+            }
+        } else {
+            let tsr = p.state().priv_m().mstatus().tsr();
+            if *p.state().privilege() == Privilege::U
+                || tsr == 1 && *p.state().privilege() == Privilege::S
+            {
+                return Err(Exception::IllegalInsn(*p.state().ir()));
+            }
         }
         p.state_mut().trap_return(&Privilege::S);
-        p.mmu().flush_tlb();
-        p.fetcher().flush_icache();
         Ok(())
     }
 }
 
 #[derive(Instruction)]
-#[format(I)]
+#[format(R)]
 #[code("32b0001001??????????000000001110011")]
 #[derive(Debug)]
 struct SFENCEVMA();
@@ -31,18 +39,103 @@ struct SFENCEVMA();
 impl Execution for SFENCEVMA {
     fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
         p.state().check_extension('s')?;
-        if *p.state().privilege() == Privilege::S && p.state().priv_m().mstatus().tvm() == 1 {
+        check_sfence_vma(p)?;
+        sfence_vma(p, self.rs1(p.state().ir()), self.rs2(p.state().ir()));
+        Ok(())
+    }
+}
+
+fn check_sfence_vma(p: &Processor) -> Result<(), Exception> {
+    if p.state().virt() {
+        if *p.state().privilege() == Privilege::U || p.state().priv_h()?.hstatus().vtvm() == 1 {
+            return Err(Exception::VirtualInsn(*p.state().ir()));
+        }
+    } else if *p.state().privilege() == Privilege::U
+        || *p.state().privilege() == Privilege::S && p.state().priv_m().mstatus().tvm() == 1
+    {
+        return Err(Exception::IllegalInsn(*p.state().ir()));
+    }
+    Ok(())
+}
+
+//in virtual mode, flush VS-stage translations of current vmid
+fn sfence_vma(p: &mut Processor, rs1: InsnT, rs2: InsnT) {
+    let pc = *p.state().pc() + 4;
+    let va = if rs1 != 0 {
+        Some(*p.state().xreg(rs1))
+    } else {
+        None
+    };
+    let asid = if rs2 != 0 {
+        Some(*p.state().xreg(rs2))
+    } else {
+        None
+    };
+    p.mmu().flush(p.state(), p.state().virt(), va, asid);
+    if let Some(va) = va {
+        //icache entries are 4KiB, flush all entries may belong to the same 64KiB NAPOT page
+        let base = (va >> 12) & !0xf;
+        for vpn in base..=base | 0xf {
+            p.fetcher().flush_icache_by_vpn(vpn);
+        }
+    } else {
+        p.fetcher().flush_icache();
+    }
+    p.state_mut().set_pc(pc);
+}
+
+//Svinval
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0001011??????????000000001110011")]
+#[derive(Debug)]
+struct SINVALVMA();
+
+impl Execution for SINVALVMA {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('s')?;
+        p.state().check_isa_ext(IsaExt::Svinval)?;
+        check_sfence_vma(p)?;
+        sfence_vma(p, self.rs1(p.state().ir()), self.rs2(p.state().ir()));
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b00011000000000000000000001110011")]
+#[derive(Debug)]
+struct SFENCEWINVAL();
+
+impl Execution for SFENCEWINVAL {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('s')?;
+        p.state().check_isa_ext(IsaExt::Svinval)?;
+        if *p.state().privilege() == Privilege::U {
             return Err(Exception::IllegalInsn(*p.state().ir()));
         }
+        //stores are never reordered with sinval.vma
         let pc = *p.state().pc() + 4;
-        if self.rs1(p.state().ir()) != 0 {
-            let va = *p.state().xreg(self.rs1(p.state().ir()));
-            p.mmu().flush_by_vpn(va >> 12);
-            p.fetcher().flush_icache_by_vpn(va >> 12);
-        } else {
-            p.mmu().flush_tlb();
-            p.fetcher().flush_icache();
+        p.state_mut().set_pc(pc);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b00011000000100000000000001110011")]
+#[derive(Debug)]
+struct SFENCEINVALIR();
+
+impl Execution for SFENCEINVALIR {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        p.state().check_extension('s')?;
+        p.state().check_isa_ext(IsaExt::Svinval)?;
+        if *p.state().privilege() == Privilege::U {
+            return Err(Exception::IllegalInsn(*p.state().ir()));
         }
+        //sinval.vma takes effect immediately
+        let pc = *p.state().pc() + 4;
         p.state_mut().set_pc(pc);
         Ok(())
     }
@@ -0,0 +1,66 @@
+use crate::prelude::*;
+csr_map! {
+pub VCsrs(0x0, 0xfff) {
+    vstart(RW):Vstart, 0x008;
+    vxsat(RW):Vxsat, 0x009;
+    vxrm(RW):Vxrm, 0x00A;
+    vcsr(RW):Vcsr, 0x00F;
+    vl(RO):Vl, 0xC20;
+    vtype(RO):Vtype, 0xC21;
+    vlenb(RO):Vlenb, 0xC22;
+}
+}
+
+define_csr! {
+Vstart {}
+}
+
+define_csr! {
+Vxsat {
+    fields{
+        vxsat(RW):0, 0;
+    }
+}
+}
+
+define_csr! {
+Vxrm {
+    fields{
+        vxrm(RW):1, 0;
+    }
+}
+}
+
+define_csr! {
+Vcsr {
+    fields{
+        vxsat(RW):0, 0;
+        vxrm(RW):2, 1;
+    }
+}
+}
+
+define_csr! {
+Vl {}
+}
+
+define_csr! {
+Vtype {
+    fields{
+        vlmul(RW):2, 0;
+        vsew(RW):5, 3;
+        vta(RW):6, 6;
+        vma(RW):7, 7;
+    },
+    fields32{
+        vill(RW):31, 31;
+    },
+    fields64{
+        vill(RW):63, 63;
+    },
+}
+}
+
+define_csr! {
+Vlenb {}
+}
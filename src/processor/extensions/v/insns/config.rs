@@ -0,0 +1,85 @@
+use crate::prelude::*;
+use crate::processor::extensions::v::vector::{next_pc, v_ext};
+use crate::processor::extensions::v::VType;
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+
+fn vsetvl(p: &mut Processor, avl: Option<RegT>, vtype: RegT) -> Result<(), Exception> {
+    let rd = (*p.state().ir() >> 7) & 0x1f;
+    let vl = {
+        let v = v_ext(p)?;
+        let xlen = p.state().config().xlen.len();
+        let t = VType::new(vtype, xlen, v.elen);
+        if t.vill {
+            v.set_vl_vtype(0, 1 << (xlen - 1));
+            0
+        } else {
+            let vlmax = t.vlmax(v.vlen);
+            //rs1 = x0 and rd = x0 keeps vl
+            let avl = avl.unwrap_or(if rd == 0 { v.vl() as RegT } else { RegT::MAX });
+            let vl = if avl > vlmax as RegT {
+                vlmax
+            } else {
+                avl as usize
+            };
+            v.set_vl_vtype(vl, vtype);
+            vl
+        }
+    };
+    v_ext(p)?.set_vstart(0);
+    p.state_mut().set_xreg(rd, vl as RegT);
+    next_pc(p);
+    Ok(())
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b0????????????????111?????1010111")]
+#[derive(Debug)]
+struct VSETVLI();
+
+impl Execution for VSETVLI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        let rs1 = self.rs1(p.state().ir());
+        let avl = if rs1 != 0 {
+            Some(*p.state().xreg(rs1))
+        } else {
+            None
+        };
+        let vtype = (self.imm(p.state().ir()) & 0x7ff) as RegT;
+        vsetvl(p, avl, vtype)
+    }
+}
+
+#[derive(Instruction)]
+#[format(I)]
+#[code("32b11???????????????111?????1010111")]
+#[derive(Debug)]
+struct VSETIVLI();
+
+impl Execution for VSETIVLI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        let avl = self.rs1(p.state().ir()) as RegT;
+        let vtype = (self.imm(p.state().ir()) & 0x3ff) as RegT;
+        vsetvl(p, Some(avl), vtype)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b1000000??????????111?????1010111")]
+#[derive(Debug)]
+struct VSETVL();
+
+impl Execution for VSETVL {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        let rs1 = self.rs1(p.state().ir());
+        let avl = if rs1 != 0 {
+            Some(*p.state().xreg(rs1))
+        } else {
+            None
+        };
+        let vtype = *p.state().xreg(self.rs2(p.state().ir()));
+        vsetvl(p, avl, vtype)
+    }
+}
@@ -0,0 +1,505 @@
+use crate::prelude::*;
+use crate::processor::extensions::v::vector::{
+    Shape, Src, arith_engine, mask, next_pc, roundoff_signed, roundoff_unsigned, signed, v_ext,
+};
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+
+//op(vs2[i], vs1[i]/scalar, sew, vxrm, saturated)
+fn fixed<F: Fn(u64, u64, usize, u8, &mut bool) -> u64>(
+    p: &mut Processor,
+    src: Src,
+    shape: Shape,
+    op: F,
+) -> Result<(), Exception> {
+    let vxrm = v_ext(p)?.vxrm();
+    let mut sat = false;
+    arith_engine(p, src, shape, |_, a, b, sew| op(a, b, sew, vxrm, &mut sat))?;
+    if sat {
+        v_ext(p)?.set_vxsat();
+    }
+    next_pc(p);
+    Ok(())
+}
+
+fn clip_unsigned(value: i128, bits: usize, sat: &mut bool) -> u64 {
+    let max = mask(bits) as i128;
+    if value < 0 {
+        *sat = true;
+        0
+    } else if value > max {
+        *sat = true;
+        max as u64
+    } else {
+        value as u64
+    }
+}
+
+fn clip_signed(value: i128, bits: usize, sat: &mut bool) -> u64 {
+    let max = (1i128 << (bits - 1)) - 1;
+    let min = -(1i128 << (bits - 1));
+    if value < min {
+        *sat = true;
+        min as u64
+    } else if value > max {
+        *sat = true;
+        max as u64
+    } else {
+        value as u64
+    }
+}
+
+fn smul(a: u64, b: u64, sew: usize, vxrm: u8, sat: &mut bool) -> u64 {
+    let prod = signed(a, sew) as i128 * signed(b, sew) as i128;
+    clip_signed(roundoff_signed(prod, sew - 1, vxrm), sew, sat)
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100000???????????000?????1010111")]
+#[derive(Debug)]
+struct VSADDUVV();
+
+impl Execution for VSADDUVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::V, Shape::Single, |a, b, sew, _, sat| {
+            clip_unsigned(a as i128 + b as i128, sew, sat)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100000???????????100?????1010111")]
+#[derive(Debug)]
+struct VSADDUVX();
+
+impl Execution for VSADDUVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::X, Shape::Single, |a, b, sew, _, sat| {
+            clip_unsigned(a as i128 + b as i128, sew, sat)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100000???????????011?????1010111")]
+#[derive(Debug)]
+struct VSADDUVI();
+
+impl Execution for VSADDUVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::I, Shape::Single, |a, b, sew, _, sat| {
+            clip_unsigned(a as i128 + b as i128, sew, sat)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100001???????????000?????1010111")]
+#[derive(Debug)]
+struct VSADDVV();
+
+impl Execution for VSADDVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::V, Shape::Single, |a, b, sew, _, sat| {
+            clip_signed(signed(a, sew) as i128 + signed(b, sew) as i128, sew, sat)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100001???????????100?????1010111")]
+#[derive(Debug)]
+struct VSADDVX();
+
+impl Execution for VSADDVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::X, Shape::Single, |a, b, sew, _, sat| {
+            clip_signed(signed(a, sew) as i128 + signed(b, sew) as i128, sew, sat)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100001???????????011?????1010111")]
+#[derive(Debug)]
+struct VSADDVI();
+
+impl Execution for VSADDVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::I, Shape::Single, |a, b, sew, _, sat| {
+            clip_signed(signed(a, sew) as i128 + signed(b, sew) as i128, sew, sat)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100010???????????000?????1010111")]
+#[derive(Debug)]
+struct VSSUBUVV();
+
+impl Execution for VSSUBUVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::V, Shape::Single, |a, b, sew, _, sat| {
+            clip_unsigned(a as i128 - b as i128, sew, sat)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100010???????????100?????1010111")]
+#[derive(Debug)]
+struct VSSUBUVX();
+
+impl Execution for VSSUBUVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::X, Shape::Single, |a, b, sew, _, sat| {
+            clip_unsigned(a as i128 - b as i128, sew, sat)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100011???????????000?????1010111")]
+#[derive(Debug)]
+struct VSSUBVV();
+
+impl Execution for VSSUBVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::V, Shape::Single, |a, b, sew, _, sat| {
+            clip_signed(signed(a, sew) as i128 - signed(b, sew) as i128, sew, sat)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100011???????????100?????1010111")]
+#[derive(Debug)]
+struct VSSUBVX();
+
+impl Execution for VSSUBVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::X, Shape::Single, |a, b, sew, _, sat| {
+            clip_signed(signed(a, sew) as i128 - signed(b, sew) as i128, sew, sat)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001000???????????010?????1010111")]
+#[derive(Debug)]
+struct VAADDUVV();
+
+impl Execution for VAADDUVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::V, Shape::Single, |a, b, _, vxrm, _| {
+            roundoff_unsigned(a as u128 + b as u128, 1, vxrm) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001000???????????110?????1010111")]
+#[derive(Debug)]
+struct VAADDUVX();
+
+impl Execution for VAADDUVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::X, Shape::Single, |a, b, _, vxrm, _| {
+            roundoff_unsigned(a as u128 + b as u128, 1, vxrm) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001001???????????010?????1010111")]
+#[derive(Debug)]
+struct VAADDVV();
+
+impl Execution for VAADDVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::V, Shape::Single, |a, b, sew, vxrm, _| {
+            roundoff_signed(signed(a, sew) as i128 + signed(b, sew) as i128, 1, vxrm) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001001???????????110?????1010111")]
+#[derive(Debug)]
+struct VAADDVX();
+
+impl Execution for VAADDVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::X, Shape::Single, |a, b, sew, vxrm, _| {
+            roundoff_signed(signed(a, sew) as i128 + signed(b, sew) as i128, 1, vxrm) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001010???????????010?????1010111")]
+#[derive(Debug)]
+struct VASUBUVV();
+
+impl Execution for VASUBUVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::V, Shape::Single, |a, b, _, vxrm, _| {
+            roundoff_signed(a as i128 - b as i128, 1, vxrm) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001010???????????110?????1010111")]
+#[derive(Debug)]
+struct VASUBUVX();
+
+impl Execution for VASUBUVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::X, Shape::Single, |a, b, _, vxrm, _| {
+            roundoff_signed(a as i128 - b as i128, 1, vxrm) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001011???????????010?????1010111")]
+#[derive(Debug)]
+struct VASUBVV();
+
+impl Execution for VASUBVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::V, Shape::Single, |a, b, sew, vxrm, _| {
+            roundoff_signed(signed(a, sew) as i128 - signed(b, sew) as i128, 1, vxrm) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001011???????????110?????1010111")]
+#[derive(Debug)]
+struct VASUBVX();
+
+impl Execution for VASUBVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::X, Shape::Single, |a, b, sew, vxrm, _| {
+            roundoff_signed(signed(a, sew) as i128 - signed(b, sew) as i128, 1, vxrm) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100111???????????000?????1010111")]
+#[derive(Debug)]
+struct VSMULVV();
+
+impl Execution for VSMULVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::V, Shape::Single, smul)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100111???????????100?????1010111")]
+#[derive(Debug)]
+struct VSMULVX();
+
+impl Execution for VSMULVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::X, Shape::Single, smul)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101010???????????000?????1010111")]
+#[derive(Debug)]
+struct VSSRLVV();
+
+impl Execution for VSSRLVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::V, Shape::Single, |a, b, sew, vxrm, _| {
+            roundoff_unsigned(a as u128, b as usize & (sew - 1), vxrm) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101010???????????100?????1010111")]
+#[derive(Debug)]
+struct VSSRLVX();
+
+impl Execution for VSSRLVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::X, Shape::Single, |a, b, sew, vxrm, _| {
+            roundoff_unsigned(a as u128, b as usize & (sew - 1), vxrm) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101010???????????011?????1010111")]
+#[derive(Debug)]
+struct VSSRLVI();
+
+impl Execution for VSSRLVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::U, Shape::Single, |a, b, sew, vxrm, _| {
+            roundoff_unsigned(a as u128, b as usize & (sew - 1), vxrm) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101011???????????000?????1010111")]
+#[derive(Debug)]
+struct VSSRAVV();
+
+impl Execution for VSSRAVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::V, Shape::Single, |a, b, sew, vxrm, _| {
+            roundoff_signed(signed(a, sew) as i128, b as usize & (sew - 1), vxrm) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101011???????????100?????1010111")]
+#[derive(Debug)]
+struct VSSRAVX();
+
+impl Execution for VSSRAVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::X, Shape::Single, |a, b, sew, vxrm, _| {
+            roundoff_signed(signed(a, sew) as i128, b as usize & (sew - 1), vxrm) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101011???????????011?????1010111")]
+#[derive(Debug)]
+struct VSSRAVI();
+
+impl Execution for VSSRAVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::U, Shape::Single, |a, b, sew, vxrm, _| {
+            roundoff_signed(signed(a, sew) as i128, b as usize & (sew - 1), vxrm) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101110???????????000?????1010111")]
+#[derive(Debug)]
+struct VNCLIPUWV();
+
+impl Execution for VNCLIPUWV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::V, Shape::Narrow, |a, b, sew, vxrm, sat| {
+            let value = roundoff_unsigned(a as u128, b as usize & (sew * 2 - 1), vxrm);
+            clip_unsigned(value as i128, sew, sat)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101110???????????100?????1010111")]
+#[derive(Debug)]
+struct VNCLIPUWX();
+
+impl Execution for VNCLIPUWX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::X, Shape::Narrow, |a, b, sew, vxrm, sat| {
+            let value = roundoff_unsigned(a as u128, b as usize & (sew * 2 - 1), vxrm);
+            clip_unsigned(value as i128, sew, sat)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101110???????????011?????1010111")]
+#[derive(Debug)]
+struct VNCLIPUWI();
+
+impl Execution for VNCLIPUWI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::U, Shape::Narrow, |a, b, sew, vxrm, sat| {
+            let value = roundoff_unsigned(a as u128, b as usize & (sew * 2 - 1), vxrm);
+            clip_unsigned(value as i128, sew, sat)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101111???????????000?????1010111")]
+#[derive(Debug)]
+struct VNCLIPWV();
+
+impl Execution for VNCLIPWV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::V, Shape::Narrow, |a, b, sew, vxrm, sat| {
+            let shamt = b as usize & (sew * 2 - 1);
+            clip_signed(roundoff_signed(signed(a, sew * 2) as i128, shamt, vxrm), sew, sat)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101111???????????100?????1010111")]
+#[derive(Debug)]
+struct VNCLIPWX();
+
+impl Execution for VNCLIPWX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::X, Shape::Narrow, |a, b, sew, vxrm, sat| {
+            let shamt = b as usize & (sew * 2 - 1);
+            clip_signed(roundoff_signed(signed(a, sew * 2) as i128, shamt, vxrm), sew, sat)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101111???????????011?????1010111")]
+#[derive(Debug)]
+struct VNCLIPWI();
+
+impl Execution for VNCLIPWI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fixed(p, Src::U, Shape::Narrow, |a, b, sew, vxrm, sat| {
+            let shamt = b as usize & (sew * 2 - 1);
+            clip_signed(roundoff_signed(signed(a, sew * 2) as i128, shamt, vxrm), sew, sat)
+        })
+    }
+}
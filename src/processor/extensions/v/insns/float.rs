@@ -0,0 +1,1576 @@
+use crate::prelude::*;
+use crate::processor::extensions::f::float::{F32Traits, FPState, F32, F64};
+use crate::processor::extensions::v::vector::{
+    Shape, Src, VCtx, accrue_fflags, arith_engine, check_fsew, compare_engine, fp_state, mask,
+    merge_engine, next_pc, signed,
+};
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+use simple_soft_float::{FloatClass, RoundingMode, StatusFlags};
+use std::cmp::Ordering;
+
+//expand body with $F as F32 or F64 and $B as its bits type
+macro_rules! float_op {
+    ($sew:expr, $F:ident, $B:ident => $body:expr) => {
+        if $sew == 32 {
+            type $F = F32;
+            type $B = u32;
+            $body
+        } else {
+            type $F = F64;
+            type $B = u64;
+            $body
+        }
+    };
+}
+
+//op(vd[i], vs2[i], vs1[i]/f[rs1], sew, fp state)
+fn float<F: FnMut(u64, u64, u64, usize, &mut FPState) -> u64>(
+    p: &mut Processor,
+    src: Src,
+    shape: Shape,
+    mut op: F,
+) -> Result<(), Exception> {
+    let sew = VCtx::new(p)?.sew;
+    check_fsew(p, sew)?;
+    if shape != Shape::Single {
+        check_fsew(p, sew * 2)?;
+    }
+    let mut state = fp_state(p)?;
+    arith_engine(p, src, shape, |d, a, b, sew| op(d, a, b, sew, &mut state))?;
+    accrue_fflags(p, &state)?;
+    next_pc(p);
+    Ok(())
+}
+
+fn fcompare<F: FnMut(u64, u64, usize, &mut FPState) -> bool>(
+    p: &mut Processor,
+    src: Src,
+    mut op: F,
+) -> Result<(), Exception> {
+    let sew = VCtx::new(p)?.sew;
+    check_fsew(p, sew)?;
+    let mut state = fp_state(p)?;
+    compare_engine(p, src, |a, b, sew| op(a, b, sew, &mut state))?;
+    accrue_fflags(p, &state)?;
+    next_pc(p);
+    Ok(())
+}
+
+//unary ops, vs1 encodes the operation; the side of int operands needs no float support
+fn unary<F: FnMut(u64, usize, &mut FPState) -> u64>(
+    p: &mut Processor,
+    shape: Shape,
+    float_src: bool,
+    float_dst: bool,
+    mut op: F,
+) -> Result<(), Exception> {
+    let sew = VCtx::new(p)?.sew;
+    let (src_eew, dst_eew) = match shape {
+        Shape::Widen => (sew, sew * 2),
+        Shape::Narrow => (sew * 2, sew),
+        _ => (sew, sew),
+    };
+    if float_src {
+        check_fsew(p, src_eew)?;
+    }
+    if float_dst {
+        check_fsew(p, dst_eew)?;
+    }
+    let mut state = fp_state(p)?;
+    arith_engine(p, Src::U, shape, |_, a, _, sew| op(a, sew, &mut state))?;
+    accrue_fflags(p, &state)?;
+    next_pc(p);
+    Ok(())
+}
+
+fn merge(p: &mut Processor) -> Result<(), Exception> {
+    merge_engine(p, Src::F)?;
+    next_pc(p);
+    Ok(())
+}
+
+fn sign_bit(sew: usize) -> u64 {
+    1 << (sew - 1)
+}
+
+fn quiet_nan(sew: usize) -> u64 {
+    float_op!(sew, F, B => *F::quiet_nan().bits() as u64)
+}
+
+fn is_nan(a: u64, sew: usize) -> bool {
+    float_op!(sew, F, B => F::from_bits(a as B).is_nan())
+}
+
+fn invalid(s: &mut FPState) {
+    s.status_flags |= StatusFlags::INVALID_OPERATION
+}
+
+pub fn fadd(a: u64, b: u64, sew: usize, s: &mut FPState) -> u64 {
+    float_op!(sew, F, B => {
+        let res = F::from_bits(a as B).add(&F::from_bits(b as B), None, Some(s));
+        *res.bits() as u64
+    })
+}
+
+fn fsub(a: u64, b: u64, sew: usize, s: &mut FPState) -> u64 {
+    float_op!(sew, F, B => {
+        let res = F::from_bits(a as B).sub(&F::from_bits(b as B), None, Some(s));
+        *res.bits() as u64
+    })
+}
+
+fn fmul(a: u64, b: u64, sew: usize, s: &mut FPState) -> u64 {
+    float_op!(sew, F, B => {
+        let res = F::from_bits(a as B).mul(&F::from_bits(b as B), None, Some(s));
+        *res.bits() as u64
+    })
+}
+
+fn fdiv(a: u64, b: u64, sew: usize, s: &mut FPState) -> u64 {
+    float_op!(sew, F, B => {
+        let res = F::from_bits(a as B).div(&F::from_bits(b as B), None, Some(s));
+        *res.bits() as u64
+    })
+}
+
+//(-1)^neg_prod * a * b + (-1)^neg_add * c
+fn fma(a: u64, b: u64, c: u64, neg_prod: bool, neg_add: bool, sew: usize, s: &mut FPState) -> u64 {
+    let a = if neg_prod { a ^ sign_bit(sew) } else { a };
+    let c = if neg_add { c ^ sign_bit(sew) } else { c };
+    float_op!(sew, F, B => {
+        let (a, b, c) = (F::from_bits(a as B), F::from_bits(b as B), F::from_bits(c as B));
+        *a.fused_mul_add(&b, &c, None, Some(s)).bits() as u64
+    })
+}
+
+fn fcmp(a: u64, b: u64, sew: usize, s: &mut FPState, signaling: bool) -> Option<Ordering> {
+    if signaling && (is_nan(a, sew) || is_nan(b, sew)) {
+        invalid(s)
+    }
+    float_op!(sew, F, B => F::from_bits(a as B).compare_quiet(&F::from_bits(b as B), Some(s)))
+}
+
+//a nan operand is ignored
+pub fn fmin(a: u64, b: u64, sew: usize, s: &mut FPState) -> u64 {
+    match fcmp(a, b, sew, s, false) {
+        None => match (is_nan(a, sew), is_nan(b, sew)) {
+            (true, true) => quiet_nan(sew),
+            (true, false) => b,
+            _ => a,
+        },
+        Some(Ordering::Less) => a,
+        Some(Ordering::Greater) => b,
+        //-0.0 < +0.0
+        Some(Ordering::Equal) => a | (b & sign_bit(sew)),
+    }
+}
+
+pub fn fmax(a: u64, b: u64, sew: usize, s: &mut FPState) -> u64 {
+    match fcmp(a, b, sew, s, false) {
+        None => match (is_nan(a, sew), is_nan(b, sew)) {
+            (true, true) => quiet_nan(sew),
+            (true, false) => b,
+            _ => a,
+        },
+        Some(Ordering::Greater) => a,
+        Some(Ordering::Less) => b,
+        Some(Ordering::Equal) => a & (b | !sign_bit(sew)),
+    }
+}
+
+//f32 to f64
+pub fn fwiden(a: u64, sew: usize, s: &mut FPState) -> u64 {
+    assert_eq!(sew, 32);
+    *F64::convert_from_float::<F32Traits>(&F32::from_bits(a as u32), None, Some(s)).bits()
+}
+
+//f64 to f32
+fn fnarrow(a: u64, rm: Option<RoundingMode>, s: &mut FPState) -> u64 {
+    *F64::from_bits(a).convert_to_float::<F32Traits>(rm, Some(s)).bits() as u64
+}
+
+//f64 to f32, rounding towards odd
+fn fnarrow_odd(a: u64, s: &mut FPState) -> u64 {
+    let mut state = FPState::default();
+    let res = fnarrow(a, Some(RoundingMode::TowardZero), &mut state);
+    s.status_flags |= state.status_flags;
+    if state.status_flags.contains(StatusFlags::INEXACT) {
+        res | 1
+    } else {
+        res
+    }
+}
+
+//float of sew to int of bits, saturating
+fn f_to_int(
+    a: u64,
+    sew: usize,
+    bits: usize,
+    sign: bool,
+    rm: Option<RoundingMode>,
+    s: &mut FPState,
+) -> u64 {
+    let mut state = FPState::default();
+    state.rounding_mode = s.rounding_mode;
+    let (min, max) = if sign {
+        (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1)
+    } else {
+        (0, mask(bits) as i128)
+    };
+    let value = float_op!(sew, F, B => {
+        let f = F::from_bits(a as B);
+        if sign {
+            f.to_i64(true, rm, Some(&mut state)).map(|v| v as i128)
+        } else {
+            f.to_u64(true, rm, Some(&mut state)).map(|v| v as i128)
+        }
+    });
+    match value {
+        Some(v) if v >= min && v <= max => {
+            s.status_flags |= state.status_flags;
+            v as u64
+        }
+        _ => {
+            invalid(s);
+            if is_nan(a, sew) || a & sign_bit(sew) == 0 {
+                max as u64
+            } else {
+                min as u64
+            }
+        }
+    }
+}
+
+//int of bits to float of sew
+fn int_to_f(a: u64, bits: usize, sew: usize, sign: bool, s: &mut FPState) -> u64 {
+    float_op!(sew, F, B => {
+        let res = if sign {
+            F::from_i64(signed(a, bits), None, Some(s))
+        } else {
+            F::from_u64(a & mask(bits), None, Some(s))
+        };
+        *res.bits() as u64
+    })
+}
+
+fn fsqrt(a: u64, sew: usize, s: &mut FPState) -> u64 {
+    float_op!(sew, F, B => *F::from_bits(a as B).sqrt(None, Some(s)).bits() as u64)
+}
+
+fn fclass(a: u64, sew: usize) -> u64 {
+    let class = float_op!(sew, F, B => F::from_bits(a as B).class());
+    1 << match class {
+        FloatClass::NegativeInfinity => 0,
+        FloatClass::NegativeNormal => 1,
+        FloatClass::NegativeSubnormal => 2,
+        FloatClass::NegativeZero => 3,
+        FloatClass::PositiveZero => 4,
+        FloatClass::PositiveSubnormal => 5,
+        FloatClass::PositiveNormal => 6,
+        FloatClass::PositiveInfinity => 7,
+        FloatClass::SignalingNaN => 8,
+        FloatClass::QuietNaN => 9,
+    }
+}
+
+const REC7: [u64; 128] = [
+    127, 125, 123, 121, 119, 117, 116, 114, 112, 110, 109, 107, 105, 104, 102, 100,
+    99, 97, 96, 94, 93, 91, 90, 88, 87, 85, 84, 83, 81, 80, 79, 77,
+    76, 75, 74, 72, 71, 70, 69, 68, 66, 65, 64, 63, 62, 61, 60, 59,
+    58, 57, 56, 55, 54, 53, 52, 51, 50, 49, 48, 47, 46, 45, 44, 43,
+    42, 41, 40, 40, 39, 38, 37, 36, 35, 35, 34, 33, 32, 31, 31, 30,
+    29, 28, 28, 27, 26, 25, 25, 24, 23, 23, 22, 21, 21, 20, 19, 19,
+    18, 17, 17, 16, 15, 15, 14, 14, 13, 12, 12, 11, 11, 10, 9, 9,
+    8, 8, 7, 7, 6, 5, 5, 4, 4, 3, 3, 2, 2, 1, 1, 0,
+];
+
+const RSQRT7: [u64; 128] = [
+    52, 51, 50, 48, 47, 46, 44, 43, 42, 41, 40, 39, 38, 36, 35, 34,
+    33, 32, 31, 30, 30, 29, 28, 27, 26, 25, 24, 23, 23, 22, 21, 20,
+    19, 19, 18, 17, 16, 16, 15, 14, 14, 13, 12, 12, 11, 10, 10, 9,
+    9, 8, 7, 7, 6, 6, 5, 4, 4, 3, 3, 2, 2, 1, 1, 0,
+    127, 125, 123, 121, 119, 118, 116, 114, 113, 111, 109, 108, 106, 105, 103, 102,
+    100, 99, 97, 96, 95, 93, 92, 91, 90, 88, 87, 86, 85, 84, 83, 82,
+    80, 79, 78, 77, 76, 75, 74, 73, 72, 71, 70, 70, 69, 68, 67, 66,
+    65, 64, 63, 63, 62, 61, 60, 59, 59, 58, 57, 56, 56, 55, 54, 53,
+];
+
+//exponent bits and mantissa bits
+fn float_fmt(sew: usize) -> (usize, usize) {
+    if sew == 32 {
+        (8, 23)
+    } else {
+        (11, 52)
+    }
+}
+
+//normalized exponent and mantissa without the hidden bit
+fn normalize(a: u64, sew: usize) -> (i64, u64) {
+    let (e, m) = float_fmt(sew);
+    let mut exp = ((a >> m) & mask(e)) as i64;
+    let mut sig = a & mask(m);
+    if exp == 0 {
+        while sig & (1 << (m - 1)) == 0 {
+            exp -= 1;
+            sig <<= 1;
+        }
+        sig = (sig << 1) & mask(m);
+    }
+    (exp, sig)
+}
+
+fn fspecial(a: u64, sew: usize, s: &mut FPState) -> Option<FloatClass> {
+    let class = float_op!(sew, F, B => F::from_bits(a as B).class());
+    match class {
+        FloatClass::NegativeNormal
+        | FloatClass::NegativeSubnormal
+        | FloatClass::PositiveNormal
+        | FloatClass::PositiveSubnormal => None,
+        FloatClass::SignalingNaN => {
+            invalid(s);
+            Some(class)
+        }
+        _ => Some(class),
+    }
+}
+
+//reciprocal square root estimate with 7 bits precision
+fn frsqrt7(a: u64, sew: usize, s: &mut FPState) -> u64 {
+    let (e, m) = float_fmt(sew);
+    let sign = a & sign_bit(sew);
+    match fspecial(a, sew, s) {
+        Some(FloatClass::PositiveInfinity) => return 0,
+        Some(FloatClass::PositiveZero) | Some(FloatClass::NegativeZero) => {
+            s.status_flags |= StatusFlags::DIVISION_BY_ZERO;
+            return sign | (mask(e) << m);
+        }
+        Some(FloatClass::NegativeInfinity) => {
+            invalid(s);
+            return quiet_nan(sew);
+        }
+        Some(_) => return quiet_nan(sew),
+        None => {}
+    }
+    if sign != 0 {
+        invalid(s);
+        return quiet_nan(sew);
+    }
+    let bias = (1i64 << (e - 1)) - 1;
+    let (exp, sig) = normalize(a, sew);
+    let idx = (((exp & 1) << 6) as u64) | (sig >> (m - 6));
+    let out_exp = (3 * bias - 1 - exp) / 2;
+    ((out_exp as u64) << m) | (RSQRT7[idx as usize] << (m - 7))
+}
+
+//reciprocal estimate with 7 bits precision
+fn frec7(a: u64, sew: usize, s: &mut FPState) -> u64 {
+    let (e, m) = float_fmt(sew);
+    let sign = a & sign_bit(sew);
+    match fspecial(a, sew, s) {
+        Some(FloatClass::PositiveInfinity) | Some(FloatClass::NegativeInfinity) => return sign,
+        Some(FloatClass::PositiveZero) | Some(FloatClass::NegativeZero) => {
+            s.status_flags |= StatusFlags::DIVISION_BY_ZERO;
+            return sign | (mask(e) << m);
+        }
+        Some(_) => return quiet_nan(sew),
+        None => {}
+    }
+    let bias = (1i64 << (e - 1)) - 1;
+    let (exp, sig) = normalize(a, sew);
+    //result overflows
+    if exp < -1 {
+        s.status_flags |= StatusFlags::OVERFLOW | StatusFlags::INEXACT;
+        let max_finite = match s.rounding_mode {
+            RoundingMode::TowardZero => true,
+            RoundingMode::TowardNegative => sign == 0,
+            RoundingMode::TowardPositive => sign != 0,
+            _ => false,
+        };
+        return if max_finite {
+            sign | ((mask(e) << m) - 1)
+        } else {
+            sign | (mask(e) << m)
+        };
+    }
+    let mut out_exp = 2 * bias - 1 - exp;
+    let mut out_sig = REC7[(sig >> (m - 7)) as usize] << (m - 7);
+    //subnormal result
+    if out_exp <= 0 {
+        out_sig = (out_sig >> 1) | (1 << (m - 1));
+        if out_exp < 0 {
+            out_sig >>= 1;
+        }
+        out_exp = 0;
+    }
+    sign | ((out_exp as u64) << m) | out_sig
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000000???????????001?????1010111")]
+#[derive(Debug)]
+struct VFADDVV();
+
+impl Execution for VFADDVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Single, |_, a, b, sew, s| fadd(a, b, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000000???????????101?????1010111")]
+#[derive(Debug)]
+struct VFADDVF();
+
+impl Execution for VFADDVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Single, |_, a, b, sew, s| fadd(a, b, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000010???????????001?????1010111")]
+#[derive(Debug)]
+struct VFSUBVV();
+
+impl Execution for VFSUBVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Single, |_, a, b, sew, s| fsub(a, b, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000010???????????101?????1010111")]
+#[derive(Debug)]
+struct VFSUBVF();
+
+impl Execution for VFSUBVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Single, |_, a, b, sew, s| fsub(a, b, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000100???????????001?????1010111")]
+#[derive(Debug)]
+struct VFMINVV();
+
+impl Execution for VFMINVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Single, |_, a, b, sew, s| fmin(a, b, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000100???????????101?????1010111")]
+#[derive(Debug)]
+struct VFMINVF();
+
+impl Execution for VFMINVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Single, |_, a, b, sew, s| fmin(a, b, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000110???????????001?????1010111")]
+#[derive(Debug)]
+struct VFMAXVV();
+
+impl Execution for VFMAXVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Single, |_, a, b, sew, s| fmax(a, b, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000110???????????101?????1010111")]
+#[derive(Debug)]
+struct VFMAXVF();
+
+impl Execution for VFMAXVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Single, |_, a, b, sew, s| fmax(a, b, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001000???????????001?????1010111")]
+#[derive(Debug)]
+struct VFSGNJVV();
+
+impl Execution for VFSGNJVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Single, |_, a, b, sew, _| {
+            (a & !sign_bit(sew)) | (b & sign_bit(sew))
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001000???????????101?????1010111")]
+#[derive(Debug)]
+struct VFSGNJVF();
+
+impl Execution for VFSGNJVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Single, |_, a, b, sew, _| {
+            (a & !sign_bit(sew)) | (b & sign_bit(sew))
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001001???????????001?????1010111")]
+#[derive(Debug)]
+struct VFSGNJNVV();
+
+impl Execution for VFSGNJNVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Single, |_, a, b, sew, _| {
+            (a & !sign_bit(sew)) | (!b & sign_bit(sew))
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001001???????????101?????1010111")]
+#[derive(Debug)]
+struct VFSGNJNVF();
+
+impl Execution for VFSGNJNVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Single, |_, a, b, sew, _| {
+            (a & !sign_bit(sew)) | (!b & sign_bit(sew))
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001010???????????001?????1010111")]
+#[derive(Debug)]
+struct VFSGNJXVV();
+
+impl Execution for VFSGNJXVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Single, |_, a, b, sew, _| a ^ (b & sign_bit(sew)))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001010???????????101?????1010111")]
+#[derive(Debug)]
+struct VFSGNJXVF();
+
+impl Execution for VFSGNJXVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Single, |_, a, b, sew, _| a ^ (b & sign_bit(sew)))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100000???????????001?????1010111")]
+#[derive(Debug)]
+struct VFDIVVV();
+
+impl Execution for VFDIVVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Single, |_, a, b, sew, s| fdiv(a, b, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100000???????????101?????1010111")]
+#[derive(Debug)]
+struct VFDIVVF();
+
+impl Execution for VFDIVVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Single, |_, a, b, sew, s| fdiv(a, b, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100001???????????101?????1010111")]
+#[derive(Debug)]
+struct VFRDIVVF();
+
+impl Execution for VFRDIVVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Single, |_, a, b, sew, s| fdiv(b, a, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100100???????????001?????1010111")]
+#[derive(Debug)]
+struct VFMULVV();
+
+impl Execution for VFMULVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Single, |_, a, b, sew, s| fmul(a, b, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100100???????????101?????1010111")]
+#[derive(Debug)]
+struct VFMULVF();
+
+impl Execution for VFMULVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Single, |_, a, b, sew, s| fmul(a, b, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100111???????????101?????1010111")]
+#[derive(Debug)]
+struct VFRSUBVF();
+
+impl Execution for VFRSUBVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Single, |_, a, b, sew, s| fsub(b, a, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101000???????????001?????1010111")]
+#[derive(Debug)]
+struct VFMADDVV();
+
+impl Execution for VFMADDVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Single, |d, a, b, sew, s| fma(b, d, a, false, false, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101000???????????101?????1010111")]
+#[derive(Debug)]
+struct VFMADDVF();
+
+impl Execution for VFMADDVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Single, |d, a, b, sew, s| fma(b, d, a, false, false, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101001???????????001?????1010111")]
+#[derive(Debug)]
+struct VFNMADDVV();
+
+impl Execution for VFNMADDVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Single, |d, a, b, sew, s| fma(b, d, a, true, true, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101001???????????101?????1010111")]
+#[derive(Debug)]
+struct VFNMADDVF();
+
+impl Execution for VFNMADDVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Single, |d, a, b, sew, s| fma(b, d, a, true, true, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101010???????????001?????1010111")]
+#[derive(Debug)]
+struct VFMSUBVV();
+
+impl Execution for VFMSUBVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Single, |d, a, b, sew, s| fma(b, d, a, false, true, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101010???????????101?????1010111")]
+#[derive(Debug)]
+struct VFMSUBVF();
+
+impl Execution for VFMSUBVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Single, |d, a, b, sew, s| fma(b, d, a, false, true, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101011???????????001?????1010111")]
+#[derive(Debug)]
+struct VFNMSUBVV();
+
+impl Execution for VFNMSUBVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Single, |d, a, b, sew, s| fma(b, d, a, true, false, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101011???????????101?????1010111")]
+#[derive(Debug)]
+struct VFNMSUBVF();
+
+impl Execution for VFNMSUBVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Single, |d, a, b, sew, s| fma(b, d, a, true, false, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101100???????????001?????1010111")]
+#[derive(Debug)]
+struct VFMACCVV();
+
+impl Execution for VFMACCVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Single, |d, a, b, sew, s| fma(b, a, d, false, false, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101100???????????101?????1010111")]
+#[derive(Debug)]
+struct VFMACCVF();
+
+impl Execution for VFMACCVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Single, |d, a, b, sew, s| fma(b, a, d, false, false, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101101???????????001?????1010111")]
+#[derive(Debug)]
+struct VFNMACCVV();
+
+impl Execution for VFNMACCVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Single, |d, a, b, sew, s| fma(b, a, d, true, true, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101101???????????101?????1010111")]
+#[derive(Debug)]
+struct VFNMACCVF();
+
+impl Execution for VFNMACCVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Single, |d, a, b, sew, s| fma(b, a, d, true, true, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101110???????????001?????1010111")]
+#[derive(Debug)]
+struct VFMSACVV();
+
+impl Execution for VFMSACVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Single, |d, a, b, sew, s| fma(b, a, d, false, true, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101110???????????101?????1010111")]
+#[derive(Debug)]
+struct VFMSACVF();
+
+impl Execution for VFMSACVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Single, |d, a, b, sew, s| fma(b, a, d, false, true, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101111???????????001?????1010111")]
+#[derive(Debug)]
+struct VFNMSACVV();
+
+impl Execution for VFNMSACVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Single, |d, a, b, sew, s| fma(b, a, d, true, false, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101111???????????101?????1010111")]
+#[derive(Debug)]
+struct VFNMSACVF();
+
+impl Execution for VFNMSACVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Single, |d, a, b, sew, s| fma(b, a, d, true, false, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110000???????????001?????1010111")]
+#[derive(Debug)]
+struct VFWADDVV();
+
+impl Execution for VFWADDVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Widen, |_, a, b, sew, s| {
+            let (a, b) = (fwiden(a, sew, s), fwiden(b, sew, s));
+            fadd(a, b, sew * 2, s)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110000???????????101?????1010111")]
+#[derive(Debug)]
+struct VFWADDVF();
+
+impl Execution for VFWADDVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Widen, |_, a, b, sew, s| {
+            let (a, b) = (fwiden(a, sew, s), fwiden(b, sew, s));
+            fadd(a, b, sew * 2, s)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110010???????????001?????1010111")]
+#[derive(Debug)]
+struct VFWSUBVV();
+
+impl Execution for VFWSUBVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Widen, |_, a, b, sew, s| {
+            let (a, b) = (fwiden(a, sew, s), fwiden(b, sew, s));
+            fsub(a, b, sew * 2, s)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110010???????????101?????1010111")]
+#[derive(Debug)]
+struct VFWSUBVF();
+
+impl Execution for VFWSUBVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Widen, |_, a, b, sew, s| {
+            let (a, b) = (fwiden(a, sew, s), fwiden(b, sew, s));
+            fsub(a, b, sew * 2, s)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111000???????????001?????1010111")]
+#[derive(Debug)]
+struct VFWMULVV();
+
+impl Execution for VFWMULVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Widen, |_, a, b, sew, s| {
+            let (a, b) = (fwiden(a, sew, s), fwiden(b, sew, s));
+            fmul(a, b, sew * 2, s)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111000???????????101?????1010111")]
+#[derive(Debug)]
+struct VFWMULVF();
+
+impl Execution for VFWMULVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Widen, |_, a, b, sew, s| {
+            let (a, b) = (fwiden(a, sew, s), fwiden(b, sew, s));
+            fmul(a, b, sew * 2, s)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110100???????????001?????1010111")]
+#[derive(Debug)]
+struct VFWADDWWV();
+
+impl Execution for VFWADDWWV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::WidenW, |_, a, b, sew, s| {
+            let b = fwiden(b, sew, s);
+            fadd(a, b, sew * 2, s)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110100???????????101?????1010111")]
+#[derive(Debug)]
+struct VFWADDWWF();
+
+impl Execution for VFWADDWWF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::WidenW, |_, a, b, sew, s| {
+            let b = fwiden(b, sew, s);
+            fadd(a, b, sew * 2, s)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110110???????????001?????1010111")]
+#[derive(Debug)]
+struct VFWSUBWWV();
+
+impl Execution for VFWSUBWWV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::WidenW, |_, a, b, sew, s| {
+            let b = fwiden(b, sew, s);
+            fsub(a, b, sew * 2, s)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110110???????????101?????1010111")]
+#[derive(Debug)]
+struct VFWSUBWWF();
+
+impl Execution for VFWSUBWWF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::WidenW, |_, a, b, sew, s| {
+            let b = fwiden(b, sew, s);
+            fsub(a, b, sew * 2, s)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111100???????????001?????1010111")]
+#[derive(Debug)]
+struct VFWMACCVV();
+
+impl Execution for VFWMACCVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Widen, |d, a, b, sew, s| {
+            let (a, b) = (fwiden(a, sew, s), fwiden(b, sew, s));
+            fma(b, a, d, false, false, sew * 2, s)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111100???????????101?????1010111")]
+#[derive(Debug)]
+struct VFWMACCVF();
+
+impl Execution for VFWMACCVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Widen, |d, a, b, sew, s| {
+            let (a, b) = (fwiden(a, sew, s), fwiden(b, sew, s));
+            fma(b, a, d, false, false, sew * 2, s)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111101???????????001?????1010111")]
+#[derive(Debug)]
+struct VFWNMACCVV();
+
+impl Execution for VFWNMACCVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Widen, |d, a, b, sew, s| {
+            let (a, b) = (fwiden(a, sew, s), fwiden(b, sew, s));
+            fma(b, a, d, true, true, sew * 2, s)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111101???????????101?????1010111")]
+#[derive(Debug)]
+struct VFWNMACCVF();
+
+impl Execution for VFWNMACCVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Widen, |d, a, b, sew, s| {
+            let (a, b) = (fwiden(a, sew, s), fwiden(b, sew, s));
+            fma(b, a, d, true, true, sew * 2, s)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111110???????????001?????1010111")]
+#[derive(Debug)]
+struct VFWMSACVV();
+
+impl Execution for VFWMSACVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Widen, |d, a, b, sew, s| {
+            let (a, b) = (fwiden(a, sew, s), fwiden(b, sew, s));
+            fma(b, a, d, false, true, sew * 2, s)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111110???????????101?????1010111")]
+#[derive(Debug)]
+struct VFWMSACVF();
+
+impl Execution for VFWMSACVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Widen, |d, a, b, sew, s| {
+            let (a, b) = (fwiden(a, sew, s), fwiden(b, sew, s));
+            fma(b, a, d, false, true, sew * 2, s)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111111???????????001?????1010111")]
+#[derive(Debug)]
+struct VFWNMSACVV();
+
+impl Execution for VFWNMSACVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::V, Shape::Widen, |d, a, b, sew, s| {
+            let (a, b) = (fwiden(a, sew, s), fwiden(b, sew, s));
+            fma(b, a, d, true, false, sew * 2, s)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111111???????????101?????1010111")]
+#[derive(Debug)]
+struct VFWNMSACVF();
+
+impl Execution for VFWNMSACVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        float(p, Src::F, Shape::Widen, |d, a, b, sew, s| {
+            let (a, b) = (fwiden(a, sew, s), fwiden(b, sew, s));
+            fma(b, a, d, true, false, sew * 2, s)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011000???????????001?????1010111")]
+#[derive(Debug)]
+struct VMFEQVV();
+
+impl Execution for VMFEQVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fcompare(p, Src::V, |a, b, sew, s| fcmp(a, b, sew, s, false) == Some(Ordering::Equal))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011000???????????101?????1010111")]
+#[derive(Debug)]
+struct VMFEQVF();
+
+impl Execution for VMFEQVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fcompare(p, Src::F, |a, b, sew, s| fcmp(a, b, sew, s, false) == Some(Ordering::Equal))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011001???????????001?????1010111")]
+#[derive(Debug)]
+struct VMFLEVV();
+
+impl Execution for VMFLEVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fcompare(p, Src::V, |a, b, sew, s| {
+            let res = fcmp(a, b, sew, s, true);
+            res == Some(Ordering::Less) || res == Some(Ordering::Equal)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011001???????????101?????1010111")]
+#[derive(Debug)]
+struct VMFLEVF();
+
+impl Execution for VMFLEVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fcompare(p, Src::F, |a, b, sew, s| {
+            let res = fcmp(a, b, sew, s, true);
+            res == Some(Ordering::Less) || res == Some(Ordering::Equal)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011011???????????001?????1010111")]
+#[derive(Debug)]
+struct VMFLTVV();
+
+impl Execution for VMFLTVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fcompare(p, Src::V, |a, b, sew, s| fcmp(a, b, sew, s, true) == Some(Ordering::Less))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011011???????????101?????1010111")]
+#[derive(Debug)]
+struct VMFLTVF();
+
+impl Execution for VMFLTVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fcompare(p, Src::F, |a, b, sew, s| fcmp(a, b, sew, s, true) == Some(Ordering::Less))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011100???????????001?????1010111")]
+#[derive(Debug)]
+struct VMFNEVV();
+
+impl Execution for VMFNEVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fcompare(p, Src::V, |a, b, sew, s| fcmp(a, b, sew, s, false) != Some(Ordering::Equal))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011100???????????101?????1010111")]
+#[derive(Debug)]
+struct VMFNEVF();
+
+impl Execution for VMFNEVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fcompare(p, Src::F, |a, b, sew, s| fcmp(a, b, sew, s, false) != Some(Ordering::Equal))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011101???????????101?????1010111")]
+#[derive(Debug)]
+struct VMFGTVF();
+
+impl Execution for VMFGTVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fcompare(p, Src::F, |a, b, sew, s| fcmp(a, b, sew, s, true) == Some(Ordering::Greater))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011111???????????101?????1010111")]
+#[derive(Debug)]
+struct VMFGEVF();
+
+impl Execution for VMFGEVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        fcompare(p, Src::F, |a, b, sew, s| {
+            let res = fcmp(a, b, sew, s, true);
+            res == Some(Ordering::Greater) || res == Some(Ordering::Equal)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0101110??????????101?????1010111")]
+#[derive(Debug)]
+struct VFMERGEVFM();
+
+impl Execution for VFMERGEVFM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        merge(p)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010111100000?????101?????1010111")]
+#[derive(Debug)]
+struct VFMVVF();
+
+impl Execution for VFMVVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        merge(p)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????00000001?????1010111")]
+#[derive(Debug)]
+struct VFCVTXUFV();
+
+impl Execution for VFCVTXUFV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        unary(p, Shape::Single, true, false, |a, sew, s| f_to_int(a, sew, sew, false, None, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????00001001?????1010111")]
+#[derive(Debug)]
+struct VFCVTXFV();
+
+impl Execution for VFCVTXFV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        unary(p, Shape::Single, true, false, |a, sew, s| f_to_int(a, sew, sew, true, None, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????00010001?????1010111")]
+#[derive(Debug)]
+struct VFCVTFXUV();
+
+impl Execution for VFCVTFXUV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        unary(p, Shape::Single, false, true, |a, sew, s| int_to_f(a, sew, sew, false, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????00011001?????1010111")]
+#[derive(Debug)]
+struct VFCVTFXV();
+
+impl Execution for VFCVTFXV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        unary(p, Shape::Single, false, true, |a, sew, s| int_to_f(a, sew, sew, true, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????00110001?????1010111")]
+#[derive(Debug)]
+struct VFCVTRTZXUFV();
+
+impl Execution for VFCVTRTZXUFV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        let rtz = Some(RoundingMode::TowardZero);
+        unary(p, Shape::Single, true, false, |a, sew, s| f_to_int(a, sew, sew, false, rtz, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????00111001?????1010111")]
+#[derive(Debug)]
+struct VFCVTRTZXFV();
+
+impl Execution for VFCVTRTZXFV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        let rtz = Some(RoundingMode::TowardZero);
+        unary(p, Shape::Single, true, false, |a, sew, s| f_to_int(a, sew, sew, true, rtz, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????01000001?????1010111")]
+#[derive(Debug)]
+struct VFWCVTXUFV();
+
+impl Execution for VFWCVTXUFV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        unary(p, Shape::Widen, true, false, |a, sew, s| f_to_int(a, sew, sew * 2, false, None, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????01001001?????1010111")]
+#[derive(Debug)]
+struct VFWCVTXFV();
+
+impl Execution for VFWCVTXFV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        unary(p, Shape::Widen, true, false, |a, sew, s| f_to_int(a, sew, sew * 2, true, None, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????01010001?????1010111")]
+#[derive(Debug)]
+struct VFWCVTFXUV();
+
+impl Execution for VFWCVTFXUV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        unary(p, Shape::Widen, false, true, |a, sew, s| int_to_f(a, sew, sew * 2, false, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????01011001?????1010111")]
+#[derive(Debug)]
+struct VFWCVTFXV();
+
+impl Execution for VFWCVTFXV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        unary(p, Shape::Widen, false, true, |a, sew, s| int_to_f(a, sew, sew * 2, true, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????01100001?????1010111")]
+#[derive(Debug)]
+struct VFWCVTFFV();
+
+impl Execution for VFWCVTFFV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        unary(p, Shape::Widen, true, true, |a, sew, s| fwiden(a, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????01110001?????1010111")]
+#[derive(Debug)]
+struct VFWCVTRTZXUFV();
+
+impl Execution for VFWCVTRTZXUFV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        let rtz = Some(RoundingMode::TowardZero);
+        unary(p, Shape::Widen, true, false, |a, sew, s| f_to_int(a, sew, sew * 2, false, rtz, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????01111001?????1010111")]
+#[derive(Debug)]
+struct VFWCVTRTZXFV();
+
+impl Execution for VFWCVTRTZXFV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        let rtz = Some(RoundingMode::TowardZero);
+        unary(p, Shape::Widen, true, false, |a, sew, s| f_to_int(a, sew, sew * 2, true, rtz, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????10000001?????1010111")]
+#[derive(Debug)]
+struct VFNCVTXUFW();
+
+impl Execution for VFNCVTXUFW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        unary(p, Shape::Narrow, true, false, |a, sew, s| f_to_int(a, sew * 2, sew, false, None, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????10001001?????1010111")]
+#[derive(Debug)]
+struct VFNCVTXFW();
+
+impl Execution for VFNCVTXFW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        unary(p, Shape::Narrow, true, false, |a, sew, s| f_to_int(a, sew * 2, sew, true, None, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????10010001?????1010111")]
+#[derive(Debug)]
+struct VFNCVTFXUW();
+
+impl Execution for VFNCVTFXUW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        unary(p, Shape::Narrow, false, true, |a, sew, s| int_to_f(a, sew * 2, sew, false, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????10011001?????1010111")]
+#[derive(Debug)]
+struct VFNCVTFXW();
+
+impl Execution for VFNCVTFXW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        unary(p, Shape::Narrow, false, true, |a, sew, s| int_to_f(a, sew * 2, sew, true, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????10100001?????1010111")]
+#[derive(Debug)]
+struct VFNCVTFFW();
+
+impl Execution for VFNCVTFFW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        unary(p, Shape::Narrow, true, true, |a, _, s| fnarrow(a, None, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????10101001?????1010111")]
+#[derive(Debug)]
+struct VFNCVTRODFFW();
+
+impl Execution for VFNCVTRODFFW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        unary(p, Shape::Narrow, true, true, |a, _, s| fnarrow_odd(a, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????10110001?????1010111")]
+#[derive(Debug)]
+struct VFNCVTRTZXUFW();
+
+impl Execution for VFNCVTRTZXUFW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        let rtz = Some(RoundingMode::TowardZero);
+        unary(p, Shape::Narrow, true, false, |a, sew, s| f_to_int(a, sew * 2, sew, false, rtz, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????10111001?????1010111")]
+#[derive(Debug)]
+struct VFNCVTRTZXFW();
+
+impl Execution for VFNCVTRTZXFW {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        let rtz = Some(RoundingMode::TowardZero);
+        unary(p, Shape::Narrow, true, false, |a, sew, s| f_to_int(a, sew * 2, sew, true, rtz, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010011??????00000001?????1010111")]
+#[derive(Debug)]
+struct VFSQRTV();
+
+impl Execution for VFSQRTV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        unary(p, Shape::Single, true, true, |a, sew, s| fsqrt(a, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010011??????00100001?????1010111")]
+#[derive(Debug)]
+struct VFRSQRT7V();
+
+impl Execution for VFRSQRT7V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        unary(p, Shape::Single, true, true, |a, sew, s| frsqrt7(a, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010011??????00101001?????1010111")]
+#[derive(Debug)]
+struct VFREC7V();
+
+impl Execution for VFREC7V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        unary(p, Shape::Single, true, true, |a, sew, s| frec7(a, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010011??????10000001?????1010111")]
+#[derive(Debug)]
+struct VFCLASSV();
+
+impl Execution for VFCLASSV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        unary(p, Shape::Single, true, true, |a, sew, _| fclass(a, sew))
+    }
+}
@@ -0,0 +1,1759 @@
+use crate::prelude::*;
+use crate::processor::extensions::v::vector::{
+    Shape, Src, VCtx, arith_engine, carry_engine, compare_engine, mask, merge_engine, next_pc,
+    signed, vd, vs2,
+};
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+
+fn int<F: Fn(u64, u64, u64, usize) -> u64>(
+    p: &mut Processor,
+    src: Src,
+    shape: Shape,
+    op: F,
+) -> Result<(), Exception> {
+    arith_engine(p, src, shape, op)?;
+    next_pc(p);
+    Ok(())
+}
+
+fn compare<F: Fn(u64, u64, usize) -> bool>(
+    p: &mut Processor,
+    src: Src,
+    op: F,
+) -> Result<(), Exception> {
+    compare_engine(p, src, op)?;
+    next_pc(p);
+    Ok(())
+}
+
+fn carry<F: Fn(u64, u64, bool, usize) -> u64>(
+    p: &mut Processor,
+    src: Src,
+    mask_out: bool,
+    op: F,
+) -> Result<(), Exception> {
+    carry_engine(p, src, mask_out, op)?;
+    next_pc(p);
+    Ok(())
+}
+
+fn merge(p: &mut Processor, src: Src) -> Result<(), Exception> {
+    merge_engine(p, src)?;
+    next_pc(p);
+    Ok(())
+}
+
+//vzext and vsext, vs2 eew is SEW / frac
+fn extend(p: &mut Processor, frac: usize, sign: bool) -> Result<(), Exception> {
+    {
+        let c = VCtx::new(p)?;
+        let (vd, vs2) = (vd(c.ir), vs2(c.ir));
+        let frac_log = frac.trailing_zeros() as isize;
+        let (eew, emul) = (c.sew / frac, c.vtype.lmul - frac_log);
+        if eew < 8 {
+            return Err(c.illegal());
+        }
+        c.check_group(vd, c.vtype.lmul)?;
+        c.check_group(vs2, emul)?;
+        c.check_vd_v0(vd)?;
+        c.check_overlap(vd, c.vtype.lmul, c.sew, vs2, emul, eew)?;
+        for i in c.vstart..c.vl {
+            if c.active(i) {
+                let value = c.v.elem(vs2, eew, i);
+                let value = if sign {
+                    signed(value, eew) as u64 & mask(c.sew)
+                } else {
+                    value
+                };
+                c.v.set_elem(vd, c.sew, i, value);
+            }
+        }
+        c.v.set_vstart(0);
+    }
+    next_pc(p);
+    Ok(())
+}
+
+fn min_signed(a: u64, b: u64, sew: usize) -> u64 {
+    if signed(a, sew) < signed(b, sew) {
+        a
+    } else {
+        b
+    }
+}
+
+fn max_signed(a: u64, b: u64, sew: usize) -> u64 {
+    if signed(a, sew) > signed(b, sew) {
+        a
+    } else {
+        b
+    }
+}
+
+fn div_signed(a: u64, b: u64, sew: usize) -> u64 {
+    if b == 0 {
+        !0
+    } else {
+        signed(a, sew).wrapping_div(signed(b, sew)) as u64
+    }
+}
+
+fn rem_signed(a: u64, b: u64, sew: usize) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        signed(a, sew).wrapping_rem(signed(b, sew)) as u64
+    }
+}
+
+fn mulh(a: u64, b: u64, sew: usize) -> u64 {
+    ((signed(a, sew) as i128 * signed(b, sew) as i128) >> sew) as u64
+}
+
+fn mulhu(a: u64, b: u64, sew: usize) -> u64 {
+    ((a as u128 * b as u128) >> sew) as u64
+}
+
+//vs2 is signed, vs1 is unsigned
+fn mulhsu(a: u64, b: u64, sew: usize) -> u64 {
+    ((signed(a, sew) as i128 * b as i128) >> sew) as u64
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000000???????????000?????1010111")]
+#[derive(Debug)]
+struct VADDVV();
+
+impl Execution for VADDVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |_, a, b, _| a.wrapping_add(b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000000???????????100?????1010111")]
+#[derive(Debug)]
+struct VADDVX();
+
+impl Execution for VADDVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |_, a, b, _| a.wrapping_add(b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000000???????????011?????1010111")]
+#[derive(Debug)]
+struct VADDVI();
+
+impl Execution for VADDVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::I, Shape::Single, |_, a, b, _| a.wrapping_add(b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000010???????????000?????1010111")]
+#[derive(Debug)]
+struct VSUBVV();
+
+impl Execution for VSUBVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |_, a, b, _| a.wrapping_sub(b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000010???????????100?????1010111")]
+#[derive(Debug)]
+struct VSUBVX();
+
+impl Execution for VSUBVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |_, a, b, _| a.wrapping_sub(b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000011???????????100?????1010111")]
+#[derive(Debug)]
+struct VRSUBVX();
+
+impl Execution for VRSUBVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |_, a, b, _| b.wrapping_sub(a))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000011???????????011?????1010111")]
+#[derive(Debug)]
+struct VRSUBVI();
+
+impl Execution for VRSUBVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::I, Shape::Single, |_, a, b, _| b.wrapping_sub(a))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000100???????????000?????1010111")]
+#[derive(Debug)]
+struct VMINUVV();
+
+impl Execution for VMINUVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |_, a, b, _| a.min(b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000100???????????100?????1010111")]
+#[derive(Debug)]
+struct VMINUVX();
+
+impl Execution for VMINUVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |_, a, b, _| a.min(b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000101???????????000?????1010111")]
+#[derive(Debug)]
+struct VMINVV();
+
+impl Execution for VMINVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |_, a, b, sew| min_signed(a, b, sew))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000101???????????100?????1010111")]
+#[derive(Debug)]
+struct VMINVX();
+
+impl Execution for VMINVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |_, a, b, sew| min_signed(a, b, sew))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000110???????????000?????1010111")]
+#[derive(Debug)]
+struct VMAXUVV();
+
+impl Execution for VMAXUVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |_, a, b, _| a.max(b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000110???????????100?????1010111")]
+#[derive(Debug)]
+struct VMAXUVX();
+
+impl Execution for VMAXUVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |_, a, b, _| a.max(b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000111???????????000?????1010111")]
+#[derive(Debug)]
+struct VMAXVV();
+
+impl Execution for VMAXVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |_, a, b, sew| max_signed(a, b, sew))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000111???????????100?????1010111")]
+#[derive(Debug)]
+struct VMAXVX();
+
+impl Execution for VMAXVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |_, a, b, sew| max_signed(a, b, sew))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001001???????????000?????1010111")]
+#[derive(Debug)]
+struct VANDVV();
+
+impl Execution for VANDVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |_, a, b, _| a & b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001001???????????100?????1010111")]
+#[derive(Debug)]
+struct VANDVX();
+
+impl Execution for VANDVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |_, a, b, _| a & b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001001???????????011?????1010111")]
+#[derive(Debug)]
+struct VANDVI();
+
+impl Execution for VANDVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::I, Shape::Single, |_, a, b, _| a & b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001010???????????000?????1010111")]
+#[derive(Debug)]
+struct VORVV();
+
+impl Execution for VORVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |_, a, b, _| a | b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001010???????????100?????1010111")]
+#[derive(Debug)]
+struct VORVX();
+
+impl Execution for VORVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |_, a, b, _| a | b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001010???????????011?????1010111")]
+#[derive(Debug)]
+struct VORVI();
+
+impl Execution for VORVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::I, Shape::Single, |_, a, b, _| a | b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001011???????????000?????1010111")]
+#[derive(Debug)]
+struct VXORVV();
+
+impl Execution for VXORVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |_, a, b, _| a ^ b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001011???????????100?????1010111")]
+#[derive(Debug)]
+struct VXORVX();
+
+impl Execution for VXORVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |_, a, b, _| a ^ b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001011???????????011?????1010111")]
+#[derive(Debug)]
+struct VXORVI();
+
+impl Execution for VXORVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::I, Shape::Single, |_, a, b, _| a ^ b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100101???????????000?????1010111")]
+#[derive(Debug)]
+struct VSLLVV();
+
+impl Execution for VSLLVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |_, a, b, sew| a << (b as usize & (sew - 1)))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100101???????????100?????1010111")]
+#[derive(Debug)]
+struct VSLLVX();
+
+impl Execution for VSLLVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |_, a, b, sew| a << (b as usize & (sew - 1)))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100101???????????011?????1010111")]
+#[derive(Debug)]
+struct VSLLVI();
+
+impl Execution for VSLLVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::U, Shape::Single, |_, a, b, sew| a << (b as usize & (sew - 1)))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101000???????????000?????1010111")]
+#[derive(Debug)]
+struct VSRLVV();
+
+impl Execution for VSRLVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |_, a, b, sew| a >> (b as usize & (sew - 1)))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101000???????????100?????1010111")]
+#[derive(Debug)]
+struct VSRLVX();
+
+impl Execution for VSRLVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |_, a, b, sew| a >> (b as usize & (sew - 1)))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101000???????????011?????1010111")]
+#[derive(Debug)]
+struct VSRLVI();
+
+impl Execution for VSRLVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::U, Shape::Single, |_, a, b, sew| a >> (b as usize & (sew - 1)))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101001???????????000?????1010111")]
+#[derive(Debug)]
+struct VSRAVV();
+
+impl Execution for VSRAVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |_, a, b, sew| {
+            (signed(a, sew) >> (b as usize & (sew - 1))) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101001???????????100?????1010111")]
+#[derive(Debug)]
+struct VSRAVX();
+
+impl Execution for VSRAVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |_, a, b, sew| {
+            (signed(a, sew) >> (b as usize & (sew - 1))) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101001???????????011?????1010111")]
+#[derive(Debug)]
+struct VSRAVI();
+
+impl Execution for VSRAVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::U, Shape::Single, |_, a, b, sew| {
+            (signed(a, sew) >> (b as usize & (sew - 1))) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100000???????????010?????1010111")]
+#[derive(Debug)]
+struct VDIVUVV();
+
+impl Execution for VDIVUVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |_, a, b, _| if b == 0 { !0 } else { a / b })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100000???????????110?????1010111")]
+#[derive(Debug)]
+struct VDIVUVX();
+
+impl Execution for VDIVUVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |_, a, b, _| if b == 0 { !0 } else { a / b })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100001???????????010?????1010111")]
+#[derive(Debug)]
+struct VDIVVV();
+
+impl Execution for VDIVVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |_, a, b, sew| div_signed(a, b, sew))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100001???????????110?????1010111")]
+#[derive(Debug)]
+struct VDIVVX();
+
+impl Execution for VDIVVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |_, a, b, sew| div_signed(a, b, sew))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100010???????????010?????1010111")]
+#[derive(Debug)]
+struct VREMUVV();
+
+impl Execution for VREMUVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |_, a, b, _| if b == 0 { a } else { a % b })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100010???????????110?????1010111")]
+#[derive(Debug)]
+struct VREMUVX();
+
+impl Execution for VREMUVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |_, a, b, _| if b == 0 { a } else { a % b })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100011???????????010?????1010111")]
+#[derive(Debug)]
+struct VREMVV();
+
+impl Execution for VREMVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |_, a, b, sew| rem_signed(a, b, sew))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100011???????????110?????1010111")]
+#[derive(Debug)]
+struct VREMVX();
+
+impl Execution for VREMVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |_, a, b, sew| rem_signed(a, b, sew))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100100???????????010?????1010111")]
+#[derive(Debug)]
+struct VMULHUVV();
+
+impl Execution for VMULHUVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |_, a, b, sew| mulhu(a, b, sew))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100100???????????110?????1010111")]
+#[derive(Debug)]
+struct VMULHUVX();
+
+impl Execution for VMULHUVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |_, a, b, sew| mulhu(a, b, sew))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100101???????????010?????1010111")]
+#[derive(Debug)]
+struct VMULVV();
+
+impl Execution for VMULVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |_, a, b, _| a.wrapping_mul(b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100101???????????110?????1010111")]
+#[derive(Debug)]
+struct VMULVX();
+
+impl Execution for VMULVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |_, a, b, _| a.wrapping_mul(b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100110???????????010?????1010111")]
+#[derive(Debug)]
+struct VMULHSUVV();
+
+impl Execution for VMULHSUVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |_, a, b, sew| mulhsu(a, b, sew))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100110???????????110?????1010111")]
+#[derive(Debug)]
+struct VMULHSUVX();
+
+impl Execution for VMULHSUVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |_, a, b, sew| mulhsu(a, b, sew))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100111???????????010?????1010111")]
+#[derive(Debug)]
+struct VMULHVV();
+
+impl Execution for VMULHVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |_, a, b, sew| mulh(a, b, sew))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b100111???????????110?????1010111")]
+#[derive(Debug)]
+struct VMULHVX();
+
+impl Execution for VMULHVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |_, a, b, sew| mulh(a, b, sew))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101001???????????010?????1010111")]
+#[derive(Debug)]
+struct VMADDVV();
+
+impl Execution for VMADDVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |d, a, b, _| b.wrapping_mul(d).wrapping_add(a))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101001???????????110?????1010111")]
+#[derive(Debug)]
+struct VMADDVX();
+
+impl Execution for VMADDVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |d, a, b, _| b.wrapping_mul(d).wrapping_add(a))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101011???????????010?????1010111")]
+#[derive(Debug)]
+struct VNMSUBVV();
+
+impl Execution for VNMSUBVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |d, a, b, _| a.wrapping_sub(b.wrapping_mul(d)))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101011???????????110?????1010111")]
+#[derive(Debug)]
+struct VNMSUBVX();
+
+impl Execution for VNMSUBVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |d, a, b, _| a.wrapping_sub(b.wrapping_mul(d)))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101101???????????010?????1010111")]
+#[derive(Debug)]
+struct VMACCVV();
+
+impl Execution for VMACCVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |d, a, b, _| b.wrapping_mul(a).wrapping_add(d))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101101???????????110?????1010111")]
+#[derive(Debug)]
+struct VMACCVX();
+
+impl Execution for VMACCVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |d, a, b, _| b.wrapping_mul(a).wrapping_add(d))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101111???????????010?????1010111")]
+#[derive(Debug)]
+struct VNMSACVV();
+
+impl Execution for VNMSACVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Single, |d, a, b, _| d.wrapping_sub(b.wrapping_mul(a)))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101111???????????110?????1010111")]
+#[derive(Debug)]
+struct VNMSACVX();
+
+impl Execution for VNMSACVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Single, |d, a, b, _| d.wrapping_sub(b.wrapping_mul(a)))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110000???????????010?????1010111")]
+#[derive(Debug)]
+struct VWADDUVV();
+
+impl Execution for VWADDUVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Widen, |_, a, b, _| a + b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110000???????????110?????1010111")]
+#[derive(Debug)]
+struct VWADDUVX();
+
+impl Execution for VWADDUVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Widen, |_, a, b, _| a + b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110001???????????010?????1010111")]
+#[derive(Debug)]
+struct VWADDVV();
+
+impl Execution for VWADDVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Widen, |_, a, b, sew| (signed(a, sew) + signed(b, sew)) as u64)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110001???????????110?????1010111")]
+#[derive(Debug)]
+struct VWADDVX();
+
+impl Execution for VWADDVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Widen, |_, a, b, sew| (signed(a, sew) + signed(b, sew)) as u64)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110010???????????010?????1010111")]
+#[derive(Debug)]
+struct VWSUBUVV();
+
+impl Execution for VWSUBUVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Widen, |_, a, b, _| a.wrapping_sub(b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110010???????????110?????1010111")]
+#[derive(Debug)]
+struct VWSUBUVX();
+
+impl Execution for VWSUBUVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Widen, |_, a, b, _| a.wrapping_sub(b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110011???????????010?????1010111")]
+#[derive(Debug)]
+struct VWSUBVV();
+
+impl Execution for VWSUBVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Widen, |_, a, b, sew| (signed(a, sew) - signed(b, sew)) as u64)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110011???????????110?????1010111")]
+#[derive(Debug)]
+struct VWSUBVX();
+
+impl Execution for VWSUBVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Widen, |_, a, b, sew| (signed(a, sew) - signed(b, sew)) as u64)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110100???????????010?????1010111")]
+#[derive(Debug)]
+struct VWADDUWV();
+
+impl Execution for VWADDUWV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::WidenW, |_, a, b, _| a.wrapping_add(b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110100???????????110?????1010111")]
+#[derive(Debug)]
+struct VWADDUWX();
+
+impl Execution for VWADDUWX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::WidenW, |_, a, b, _| a.wrapping_add(b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110101???????????010?????1010111")]
+#[derive(Debug)]
+struct VWADDWV();
+
+impl Execution for VWADDWV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::WidenW, |_, a, b, sew| {
+            (signed(a, sew * 2).wrapping_add(signed(b, sew))) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110101???????????110?????1010111")]
+#[derive(Debug)]
+struct VWADDWX();
+
+impl Execution for VWADDWX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::WidenW, |_, a, b, sew| {
+            (signed(a, sew * 2).wrapping_add(signed(b, sew))) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110110???????????010?????1010111")]
+#[derive(Debug)]
+struct VWSUBUWV();
+
+impl Execution for VWSUBUWV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::WidenW, |_, a, b, _| a.wrapping_sub(b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110110???????????110?????1010111")]
+#[derive(Debug)]
+struct VWSUBUWX();
+
+impl Execution for VWSUBUWX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::WidenW, |_, a, b, _| a.wrapping_sub(b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110111???????????010?????1010111")]
+#[derive(Debug)]
+struct VWSUBWV();
+
+impl Execution for VWSUBWV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::WidenW, |_, a, b, sew| {
+            (signed(a, sew * 2).wrapping_sub(signed(b, sew))) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110111???????????110?????1010111")]
+#[derive(Debug)]
+struct VWSUBWX();
+
+impl Execution for VWSUBWX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::WidenW, |_, a, b, sew| {
+            (signed(a, sew * 2).wrapping_sub(signed(b, sew))) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111000???????????010?????1010111")]
+#[derive(Debug)]
+struct VWMULUVV();
+
+impl Execution for VWMULUVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Widen, |_, a, b, _| a * b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111000???????????110?????1010111")]
+#[derive(Debug)]
+struct VWMULUVX();
+
+impl Execution for VWMULUVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Widen, |_, a, b, _| a * b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111010???????????010?????1010111")]
+#[derive(Debug)]
+struct VWMULSUVV();
+
+impl Execution for VWMULSUVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Widen, |_, a, b, sew| signed(a, sew).wrapping_mul(b as i64) as u64)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111010???????????110?????1010111")]
+#[derive(Debug)]
+struct VWMULSUVX();
+
+impl Execution for VWMULSUVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Widen, |_, a, b, sew| signed(a, sew).wrapping_mul(b as i64) as u64)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111011???????????010?????1010111")]
+#[derive(Debug)]
+struct VWMULVV();
+
+impl Execution for VWMULVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Widen, |_, a, b, sew| (signed(a, sew) * signed(b, sew)) as u64)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111011???????????110?????1010111")]
+#[derive(Debug)]
+struct VWMULVX();
+
+impl Execution for VWMULVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Widen, |_, a, b, sew| (signed(a, sew) * signed(b, sew)) as u64)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111100???????????010?????1010111")]
+#[derive(Debug)]
+struct VWMACCUVV();
+
+impl Execution for VWMACCUVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Widen, |d, a, b, _| d.wrapping_add(a * b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111100???????????110?????1010111")]
+#[derive(Debug)]
+struct VWMACCUVX();
+
+impl Execution for VWMACCUVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Widen, |d, a, b, _| d.wrapping_add(a * b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111101???????????010?????1010111")]
+#[derive(Debug)]
+struct VWMACCVV();
+
+impl Execution for VWMACCVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Widen, |d, a, b, sew| {
+            d.wrapping_add((signed(a, sew) * signed(b, sew)) as u64)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111101???????????110?????1010111")]
+#[derive(Debug)]
+struct VWMACCVX();
+
+impl Execution for VWMACCVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Widen, |d, a, b, sew| {
+            d.wrapping_add((signed(a, sew) * signed(b, sew)) as u64)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111111???????????010?????1010111")]
+#[derive(Debug)]
+struct VWMACCSUVV();
+
+impl Execution for VWMACCSUVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Widen, |d, a, b, sew| {
+            d.wrapping_add(signed(b, sew).wrapping_mul(a as i64) as u64)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111111???????????110?????1010111")]
+#[derive(Debug)]
+struct VWMACCSUVX();
+
+impl Execution for VWMACCSUVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Widen, |d, a, b, sew| {
+            d.wrapping_add(signed(b, sew).wrapping_mul(a as i64) as u64)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b111110???????????110?????1010111")]
+#[derive(Debug)]
+struct VWMACCUSVX();
+
+impl Execution for VWMACCUSVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Widen, |d, a, b, sew| {
+            d.wrapping_add(signed(a, sew).wrapping_mul(b as i64) as u64)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101100???????????000?????1010111")]
+#[derive(Debug)]
+struct VNSRLWV();
+
+impl Execution for VNSRLWV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Narrow, |_, a, b, sew| a >> (b as usize & (sew * 2 - 1)))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101100???????????100?????1010111")]
+#[derive(Debug)]
+struct VNSRLWX();
+
+impl Execution for VNSRLWX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Narrow, |_, a, b, sew| a >> (b as usize & (sew * 2 - 1)))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101100???????????011?????1010111")]
+#[derive(Debug)]
+struct VNSRLWI();
+
+impl Execution for VNSRLWI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::U, Shape::Narrow, |_, a, b, sew| a >> (b as usize & (sew * 2 - 1)))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101101???????????000?????1010111")]
+#[derive(Debug)]
+struct VNSRAWV();
+
+impl Execution for VNSRAWV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::V, Shape::Narrow, |_, a, b, sew| {
+            (signed(a, sew * 2) >> (b as usize & (sew * 2 - 1))) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101101???????????100?????1010111")]
+#[derive(Debug)]
+struct VNSRAWX();
+
+impl Execution for VNSRAWX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::X, Shape::Narrow, |_, a, b, sew| {
+            (signed(a, sew * 2) >> (b as usize & (sew * 2 - 1))) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b101101???????????011?????1010111")]
+#[derive(Debug)]
+struct VNSRAWI();
+
+impl Execution for VNSRAWI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        int(p, Src::U, Shape::Narrow, |_, a, b, sew| {
+            (signed(a, sew * 2) >> (b as usize & (sew * 2 - 1))) as u64
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011000???????????000?????1010111")]
+#[derive(Debug)]
+struct VMSEQVV();
+
+impl Execution for VMSEQVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        compare(p, Src::V, |a, b, _| a == b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011000???????????100?????1010111")]
+#[derive(Debug)]
+struct VMSEQVX();
+
+impl Execution for VMSEQVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        compare(p, Src::X, |a, b, _| a == b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011000???????????011?????1010111")]
+#[derive(Debug)]
+struct VMSEQVI();
+
+impl Execution for VMSEQVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        compare(p, Src::I, |a, b, _| a == b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011001???????????000?????1010111")]
+#[derive(Debug)]
+struct VMSNEVV();
+
+impl Execution for VMSNEVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        compare(p, Src::V, |a, b, _| a != b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011001???????????100?????1010111")]
+#[derive(Debug)]
+struct VMSNEVX();
+
+impl Execution for VMSNEVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        compare(p, Src::X, |a, b, _| a != b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011001???????????011?????1010111")]
+#[derive(Debug)]
+struct VMSNEVI();
+
+impl Execution for VMSNEVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        compare(p, Src::I, |a, b, _| a != b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011010???????????000?????1010111")]
+#[derive(Debug)]
+struct VMSLTUVV();
+
+impl Execution for VMSLTUVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        compare(p, Src::V, |a, b, _| a < b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011010???????????100?????1010111")]
+#[derive(Debug)]
+struct VMSLTUVX();
+
+impl Execution for VMSLTUVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        compare(p, Src::X, |a, b, _| a < b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011011???????????000?????1010111")]
+#[derive(Debug)]
+struct VMSLTVV();
+
+impl Execution for VMSLTVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        compare(p, Src::V, |a, b, sew| signed(a, sew) < signed(b, sew))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011011???????????100?????1010111")]
+#[derive(Debug)]
+struct VMSLTVX();
+
+impl Execution for VMSLTVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        compare(p, Src::X, |a, b, sew| signed(a, sew) < signed(b, sew))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011100???????????000?????1010111")]
+#[derive(Debug)]
+struct VMSLEUVV();
+
+impl Execution for VMSLEUVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        compare(p, Src::V, |a, b, _| a <= b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011100???????????100?????1010111")]
+#[derive(Debug)]
+struct VMSLEUVX();
+
+impl Execution for VMSLEUVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        compare(p, Src::X, |a, b, _| a <= b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011100???????????011?????1010111")]
+#[derive(Debug)]
+struct VMSLEUVI();
+
+impl Execution for VMSLEUVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        compare(p, Src::I, |a, b, _| a <= b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011101???????????000?????1010111")]
+#[derive(Debug)]
+struct VMSLEVV();
+
+impl Execution for VMSLEVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        compare(p, Src::V, |a, b, sew| signed(a, sew) <= signed(b, sew))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011101???????????100?????1010111")]
+#[derive(Debug)]
+struct VMSLEVX();
+
+impl Execution for VMSLEVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        compare(p, Src::X, |a, b, sew| signed(a, sew) <= signed(b, sew))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011101???????????011?????1010111")]
+#[derive(Debug)]
+struct VMSLEVI();
+
+impl Execution for VMSLEVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        compare(p, Src::I, |a, b, sew| signed(a, sew) <= signed(b, sew))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011110???????????100?????1010111")]
+#[derive(Debug)]
+struct VMSGTUVX();
+
+impl Execution for VMSGTUVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        compare(p, Src::X, |a, b, _| a > b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011110???????????011?????1010111")]
+#[derive(Debug)]
+struct VMSGTUVI();
+
+impl Execution for VMSGTUVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        compare(p, Src::I, |a, b, _| a > b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011111???????????100?????1010111")]
+#[derive(Debug)]
+struct VMSGTVX();
+
+impl Execution for VMSGTVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        compare(p, Src::X, |a, b, sew| signed(a, sew) > signed(b, sew))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b011111???????????011?????1010111")]
+#[derive(Debug)]
+struct VMSGTVI();
+
+impl Execution for VMSGTVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        compare(p, Src::I, |a, b, sew| signed(a, sew) > signed(b, sew))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0100000??????????000?????1010111")]
+#[derive(Debug)]
+struct VADCVVM();
+
+impl Execution for VADCVVM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        carry(p, Src::V, false, |a, b, c, _| a.wrapping_add(b).wrapping_add(c as u64))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0100000??????????100?????1010111")]
+#[derive(Debug)]
+struct VADCVXM();
+
+impl Execution for VADCVXM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        carry(p, Src::X, false, |a, b, c, _| a.wrapping_add(b).wrapping_add(c as u64))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0100000??????????011?????1010111")]
+#[derive(Debug)]
+struct VADCVIM();
+
+impl Execution for VADCVIM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        carry(p, Src::I, false, |a, b, c, _| a.wrapping_add(b).wrapping_add(c as u64))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010001???????????000?????1010111")]
+#[derive(Debug)]
+struct VMADCVV();
+
+impl Execution for VMADCVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        carry(p, Src::V, true, |a, b, c, sew| ((a as u128 + b as u128 + c as u128) >> sew) as u64)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010001???????????100?????1010111")]
+#[derive(Debug)]
+struct VMADCVX();
+
+impl Execution for VMADCVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        carry(p, Src::X, true, |a, b, c, sew| ((a as u128 + b as u128 + c as u128) >> sew) as u64)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010001???????????011?????1010111")]
+#[derive(Debug)]
+struct VMADCVI();
+
+impl Execution for VMADCVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        carry(p, Src::I, true, |a, b, c, sew| ((a as u128 + b as u128 + c as u128) >> sew) as u64)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0100100??????????000?????1010111")]
+#[derive(Debug)]
+struct VSBCVVM();
+
+impl Execution for VSBCVVM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        carry(p, Src::V, false, |a, b, c, _| a.wrapping_sub(b).wrapping_sub(c as u64))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0100100??????????100?????1010111")]
+#[derive(Debug)]
+struct VSBCVXM();
+
+impl Execution for VSBCVXM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        carry(p, Src::X, false, |a, b, c, _| a.wrapping_sub(b).wrapping_sub(c as u64))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010011???????????000?????1010111")]
+#[derive(Debug)]
+struct VMSBCVV();
+
+impl Execution for VMSBCVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        carry(p, Src::V, true, |a, b, c, _| ((a as u128) < b as u128 + c as u128) as u64)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010011???????????100?????1010111")]
+#[derive(Debug)]
+struct VMSBCVX();
+
+impl Execution for VMSBCVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        carry(p, Src::X, true, |a, b, c, _| ((a as u128) < b as u128 + c as u128) as u64)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0101110??????????000?????1010111")]
+#[derive(Debug)]
+struct VMERGEVVM();
+
+impl Execution for VMERGEVVM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        merge(p, Src::V)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0101110??????????100?????1010111")]
+#[derive(Debug)]
+struct VMERGEVXM();
+
+impl Execution for VMERGEVXM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        merge(p, Src::X)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0101110??????????011?????1010111")]
+#[derive(Debug)]
+struct VMERGEVIM();
+
+impl Execution for VMERGEVIM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        merge(p, Src::I)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010111100000?????000?????1010111")]
+#[derive(Debug)]
+struct VMVVV();
+
+impl Execution for VMVVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        merge(p, Src::V)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010111100000?????100?????1010111")]
+#[derive(Debug)]
+struct VMVVX();
+
+impl Execution for VMVVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        merge(p, Src::X)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010111100000?????011?????1010111")]
+#[derive(Debug)]
+struct VMVVI();
+
+impl Execution for VMVVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        merge(p, Src::I)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????00010010?????1010111")]
+#[derive(Debug)]
+struct VZEXTVF8();
+
+impl Execution for VZEXTVF8 {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        extend(p, 8, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????00011010?????1010111")]
+#[derive(Debug)]
+struct VSEXTVF8();
+
+impl Execution for VSEXTVF8 {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        extend(p, 8, true)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????00100010?????1010111")]
+#[derive(Debug)]
+struct VZEXTVF4();
+
+impl Execution for VZEXTVF4 {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        extend(p, 4, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????00101010?????1010111")]
+#[derive(Debug)]
+struct VSEXTVF4();
+
+impl Execution for VSEXTVF4 {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        extend(p, 4, true)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????00110010?????1010111")]
+#[derive(Debug)]
+struct VZEXTVF2();
+
+impl Execution for VZEXTVF2 {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        extend(p, 2, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010010??????00111010?????1010111")]
+#[derive(Debug)]
+struct VSEXTVF2();
+
+impl Execution for VSEXTVF2 {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        extend(p, 2, true)
+    }
+}
@@ -0,0 +1,275 @@
+use crate::prelude::*;
+use crate::processor::extensions::v::vector::{VCtx, mask, next_pc, vd, vs1, vs2};
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+
+//vd.mask[i] = op(vs2.mask[i], vs1.mask[i])
+fn logical<F: Fn(bool, bool) -> bool>(p: &mut Processor, op: F) -> Result<(), Exception> {
+    {
+        let c = VCtx::new(p)?;
+        let (vd, vs2, vs1) = (vd(c.ir), vs2(c.ir), vs1(c.ir));
+        for i in c.vstart..c.vl {
+            c.v.set_mask(vd, i, op(c.v.mask(vs2, i), c.v.mask(vs1, i)));
+        }
+        c.v.set_vstart(0);
+    }
+    next_pc(p);
+    Ok(())
+}
+
+//active set bits of vs2.mask
+fn set_bits(p: &Processor) -> Result<Vec<usize>, Exception> {
+    let c = VCtx::new(p)?;
+    c.check_vstart_zero()?;
+    let vs2 = vs2(c.ir);
+    Ok((0..c.vl)
+        .filter(|i| c.active(*i) && c.v.mask(vs2, *i))
+        .collect())
+}
+
+fn set_xreg(p: &mut Processor, value: RegT) {
+    let rd = vd(*p.state().ir());
+    let value = value & p.state().config().xlen.mask();
+    p.state_mut().set_xreg(rd, value);
+    next_pc(p);
+}
+
+//vmsbf, vmsif and vmsof, op(idx, first set idx) -> bit
+fn set_first<F: Fn(usize, Option<usize>) -> bool>(
+    p: &mut Processor,
+    op: F,
+) -> Result<(), Exception> {
+    {
+        let c = VCtx::new(p)?;
+        let (vd, vs2) = (vd(c.ir), vs2(c.ir));
+        c.check_vstart_zero()?;
+        if vd == vs2 || c.masked && vd == 0 {
+            return Err(c.illegal());
+        }
+        let first = (0..c.vl).find(|i| c.active(*i) && c.v.mask(vs2, *i));
+        for i in 0..c.vl {
+            if c.active(i) {
+                c.v.set_mask(vd, i, op(i, first));
+            }
+        }
+    }
+    next_pc(p);
+    Ok(())
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0110001??????????010?????1010111")]
+#[derive(Debug)]
+struct VMANDNMM();
+
+impl Execution for VMANDNMM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        logical(p, |a, b| a && !b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0110011??????????010?????1010111")]
+#[derive(Debug)]
+struct VMANDMM();
+
+impl Execution for VMANDMM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        logical(p, |a, b| a && b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0110101??????????010?????1010111")]
+#[derive(Debug)]
+struct VMORMM();
+
+impl Execution for VMORMM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        logical(p, |a, b| a || b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0110111??????????010?????1010111")]
+#[derive(Debug)]
+struct VMXORMM();
+
+impl Execution for VMXORMM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        logical(p, |a, b| a ^ b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0111001??????????010?????1010111")]
+#[derive(Debug)]
+struct VMORNMM();
+
+impl Execution for VMORNMM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        logical(p, |a, b| a || !b)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0111011??????????010?????1010111")]
+#[derive(Debug)]
+struct VMNANDMM();
+
+impl Execution for VMNANDMM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        logical(p, |a, b| !(a && b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0111101??????????010?????1010111")]
+#[derive(Debug)]
+struct VMNORMM();
+
+impl Execution for VMNORMM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        logical(p, |a, b| !(a || b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0111111??????????010?????1010111")]
+#[derive(Debug)]
+struct VMXNORMM();
+
+impl Execution for VMXNORMM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        logical(p, |a, b| !(a ^ b))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010000??????10000010?????1010111")]
+#[derive(Debug)]
+struct VCPOPM();
+
+impl Execution for VCPOPM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        let count = set_bits(p)?.len();
+        set_xreg(p, count as RegT);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010000??????10001010?????1010111")]
+#[derive(Debug)]
+struct VFIRSTM();
+
+impl Execution for VFIRSTM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        let first = set_bits(p)?.first().map_or(!0, |i| *i as RegT);
+        set_xreg(p, first);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010100??????00001010?????1010111")]
+#[derive(Debug)]
+struct VMSBFM();
+
+impl Execution for VMSBFM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        set_first(p, |i, first| first.map_or(true, |f| i < f))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010100??????00010010?????1010111")]
+#[derive(Debug)]
+struct VMSOFM();
+
+impl Execution for VMSOFM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        set_first(p, |i, first| first == Some(i))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010100??????00011010?????1010111")]
+#[derive(Debug)]
+struct VMSIFM();
+
+impl Execution for VMSIFM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        set_first(p, |i, first| first.map_or(true, |f| i <= f))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010100??????10000010?????1010111")]
+#[derive(Debug)]
+struct VIOTAM();
+
+impl Execution for VIOTAM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        {
+            let c = VCtx::new(p)?;
+            let (vd, vs2) = (vd(c.ir), vs2(c.ir));
+            c.check_vstart_zero()?;
+            c.check_group(vd, c.vtype.lmul)?;
+            c.check_vd_v0(vd)?;
+            if vd <= vs2 && vs2 < vd + (1 << c.vtype.lmul.max(0)) {
+                return Err(c.illegal());
+            }
+            let mut sum = 0;
+            for i in 0..c.vl {
+                if c.active(i) {
+                    c.v.set_elem(vd, c.sew, i, sum & mask(c.sew));
+                    if c.v.mask(vs2, i) {
+                        sum += 1;
+                    }
+                }
+            }
+        }
+        next_pc(p);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010100?0000010001010?????1010111")]
+#[derive(Debug)]
+struct VIDV();
+
+impl Execution for VIDV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        {
+            let c = VCtx::new(p)?;
+            let vd = vd(c.ir);
+            c.check_group(vd, c.vtype.lmul)?;
+            c.check_vd_v0(vd)?;
+            for i in c.vstart..c.vl {
+                if c.active(i) {
+                    c.v.set_elem(vd, c.sew, i, i as u64 & mask(c.sew));
+                }
+            }
+            c.v.set_vstart(0);
+        }
+        next_pc(p);
+        Ok(())
+    }
+}
@@ -0,0 +1,709 @@
+use crate::prelude::*;
+use crate::processor::extensions::v::vector::{VCtx, next_pc, v_ext, vd, vs1, vs2};
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Mode {
+    Unit,
+    Strided,
+    //ordered and unordered indexed accesses are both performed in element order
+    Indexed,
+}
+
+//eew encoded in width
+fn width_eew(ir: InsnT) -> usize {
+    match (ir >> 12) & 0x7 {
+        0 => 8,
+        5 => 16,
+        6 => 32,
+        _ => 64,
+    }
+}
+
+fn nf(ir: InsnT) -> usize {
+    ((ir >> 29) & 0x7) as usize + 1
+}
+
+fn log2(value: usize) -> isize {
+    value.trailing_zeros() as isize
+}
+
+fn load_elem(p: &Processor, addr: RegT, eew: usize) -> Result<u64, Exception> {
+    let (state, mmu) = (p.state(), p.mmu());
+    match eew {
+        8 => {
+            let mut data: u8 = 0;
+            p.load_store().load_byte(state, &addr, &mut data, mmu)?;
+            Ok(data as u64)
+        }
+        16 => {
+            let mut data: u16 = 0;
+            p.load_store().load_half_word(state, &addr, &mut data, mmu)?;
+            Ok(data as u64)
+        }
+        32 => {
+            let mut data: u32 = 0;
+            p.load_store().load_word(state, &addr, &mut data, mmu)?;
+            Ok(data as u64)
+        }
+        _ => {
+            let mut data: u64 = 0;
+            p.load_store().load_double_word(state, &addr, &mut data, mmu)?;
+            Ok(data)
+        }
+    }
+}
+
+fn store_elem(p: &Processor, addr: RegT, eew: usize, value: u64) -> Result<(), Exception> {
+    let (state, mmu) = (p.state(), p.mmu());
+    match eew {
+        8 => p.load_store().store_byte(state, &addr, &(value as u8), mmu),
+        16 => p.load_store().store_half_word(state, &addr, &(value as u16), mmu),
+        32 => p.load_store().store_word(state, &addr, &(value as u32), mmu),
+        _ => p.load_store().store_double_word(state, &addr, &value, mmu),
+    }
+}
+
+//unit-stride, strided and indexed accesses with nf fields per segment
+fn access(p: &mut Processor, mode: Mode, store: bool, ff: bool) -> Result<(), Exception> {
+    {
+        let c = VCtx::new(p)?;
+        let (vd, rs1, rs2) = (vd(c.ir), vs1(c.ir), vs2(c.ir));
+        let (width, nf) = (width_eew(c.ir), nf(c.ir));
+        let xlen_mask = p.state().config().xlen.mask();
+        let base = *p.state().xreg(rs1);
+        let stride = *p.state().xreg(rs2);
+        let width_emul = c.vtype.lmul + log2(width) - log2(c.sew);
+        let (eew, emul) = if mode == Mode::Indexed {
+            (c.sew, c.vtype.lmul)
+        } else {
+            (width, width_emul)
+        };
+        c.check_eew(width)?;
+        c.check_group(vd, emul)?;
+        let regs = 1 << emul.max(0) as usize;
+        if nf * regs > 8 || vd as usize + nf * regs > 32 {
+            return Err(c.illegal());
+        }
+        if !store {
+            c.check_vd_v0(vd)?;
+        }
+        if mode == Mode::Indexed {
+            c.check_group(rs2, width_emul)?;
+            if !store {
+                c.check_overlap(vd, emul, eew, rs2, width_emul, width)?;
+                if nf > 1 && vd <= rs2 && rs2 < vd + (nf * regs) as InsnT {
+                    return Err(c.illegal());
+                }
+            }
+        }
+        for i in c.vstart..c.vl {
+            if !c.active(i) {
+                continue;
+            }
+            for f in 0..nf {
+                let offset = match mode {
+                    Mode::Unit => ((i * nf + f) * eew / 8) as RegT,
+                    Mode::Strided => (i as RegT)
+                        .wrapping_mul(stride)
+                        .wrapping_add((f * eew / 8) as RegT),
+                    Mode::Indexed => c.v.elem(rs2, width, i).wrapping_add((f * eew / 8) as RegT),
+                };
+                let addr = base.wrapping_add(offset) & xlen_mask;
+                let reg = vd + (f * regs) as InsnT;
+                let res = if store {
+                    store_elem(p, addr, eew, c.v.elem(reg, eew, i))
+                } else {
+                    load_elem(p, addr, eew).map(|value| c.v.set_elem(reg, eew, i, value))
+                };
+                if let Err(e) = res {
+                    //fault-only-first only traps on element 0
+                    if ff && i != 0 {
+                        c.v.set_vl(i);
+                        break;
+                    }
+                    c.v.set_vstart(i);
+                    return Err(e);
+                }
+            }
+            if c.v.vl() != c.vl {
+                break;
+            }
+        }
+        c.v.set_vstart(0);
+    }
+    next_pc(p);
+    Ok(())
+}
+
+//vlm.v and vsm.v, ceil(vl/8) bytes
+fn access_mask(p: &mut Processor, store: bool) -> Result<(), Exception> {
+    {
+        let c = VCtx::new(p)?;
+        let (vd, rs1) = (vd(c.ir), vs1(c.ir));
+        let xlen_mask = p.state().config().xlen.mask();
+        let base = *p.state().xreg(rs1);
+        for i in c.vstart..(c.vl + 7) / 8 {
+            let addr = base.wrapping_add(i as RegT) & xlen_mask;
+            let res = if store {
+                store_elem(p, addr, 8, c.v.elem(vd, 8, i))
+            } else {
+                load_elem(p, addr, 8).map(|value| c.v.set_elem(vd, 8, i, value))
+            };
+            if let Err(e) = res {
+                c.v.set_vstart(i);
+                return Err(e);
+            }
+        }
+        c.v.set_vstart(0);
+    }
+    next_pc(p);
+    Ok(())
+}
+
+//whole register accesses, nf encodes the number of registers
+fn access_whole(p: &mut Processor, store: bool) -> Result<(), Exception> {
+    {
+        let v = v_ext(p)?;
+        let ir = *p.state().ir();
+        let (vd, rs1, nr) = (vd(ir), vs1(ir), nf(ir));
+        let eew = if store { 8 } else { width_eew(ir) };
+        if !nr.is_power_of_two() || vd as usize % nr != 0 || eew > v.elen {
+            return Err(Exception::IllegalInsn(ir));
+        }
+        let xlen_mask = p.state().config().xlen.mask();
+        let base = *p.state().xreg(rs1);
+        for i in v.vstart()..nr * v.vlen / eew {
+            let addr = base.wrapping_add((i * eew / 8) as RegT) & xlen_mask;
+            let res = if store {
+                store_elem(p, addr, eew, v.elem(vd, eew, i))
+            } else {
+                load_elem(p, addr, eew).map(|value| v.set_elem(vd, eew, i, value))
+            };
+            if let Err(e) = res {
+                v.set_vstart(i);
+                return Err(e);
+            }
+        }
+        v.set_vstart(0);
+    }
+    next_pc(p);
+    Ok(())
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???000?00000?????000?????0000111")]
+#[derive(Debug)]
+struct VLE8V();
+
+impl Execution for VLE8V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Unit, false, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???000?10000?????000?????0000111")]
+#[derive(Debug)]
+struct VLE8FFV();
+
+impl Execution for VLE8FFV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Unit, false, true)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???010???????????000?????0000111")]
+#[derive(Debug)]
+struct VLSE8V();
+
+impl Execution for VLSE8V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Strided, false, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???001???????????000?????0000111")]
+#[derive(Debug)]
+struct VLUXEI8V();
+
+impl Execution for VLUXEI8V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Indexed, false, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???011???????????000?????0000111")]
+#[derive(Debug)]
+struct VLOXEI8V();
+
+impl Execution for VLOXEI8V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Indexed, false, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???000101000?????000?????0000111")]
+#[derive(Debug)]
+struct VLRE8V();
+
+impl Execution for VLRE8V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access_whole(p, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???000?00000?????000?????0100111")]
+#[derive(Debug)]
+struct VSE8V();
+
+impl Execution for VSE8V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Unit, true, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???010???????????000?????0100111")]
+#[derive(Debug)]
+struct VSSE8V();
+
+impl Execution for VSSE8V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Strided, true, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???001???????????000?????0100111")]
+#[derive(Debug)]
+struct VSUXEI8V();
+
+impl Execution for VSUXEI8V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Indexed, true, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???011???????????000?????0100111")]
+#[derive(Debug)]
+struct VSOXEI8V();
+
+impl Execution for VSOXEI8V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Indexed, true, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???000?00000?????101?????0000111")]
+#[derive(Debug)]
+struct VLE16V();
+
+impl Execution for VLE16V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Unit, false, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???000?10000?????101?????0000111")]
+#[derive(Debug)]
+struct VLE16FFV();
+
+impl Execution for VLE16FFV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Unit, false, true)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???010???????????101?????0000111")]
+#[derive(Debug)]
+struct VLSE16V();
+
+impl Execution for VLSE16V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Strided, false, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???001???????????101?????0000111")]
+#[derive(Debug)]
+struct VLUXEI16V();
+
+impl Execution for VLUXEI16V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Indexed, false, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???011???????????101?????0000111")]
+#[derive(Debug)]
+struct VLOXEI16V();
+
+impl Execution for VLOXEI16V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Indexed, false, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???000101000?????101?????0000111")]
+#[derive(Debug)]
+struct VLRE16V();
+
+impl Execution for VLRE16V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access_whole(p, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???000?00000?????101?????0100111")]
+#[derive(Debug)]
+struct VSE16V();
+
+impl Execution for VSE16V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Unit, true, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???010???????????101?????0100111")]
+#[derive(Debug)]
+struct VSSE16V();
+
+impl Execution for VSSE16V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Strided, true, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???001???????????101?????0100111")]
+#[derive(Debug)]
+struct VSUXEI16V();
+
+impl Execution for VSUXEI16V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Indexed, true, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???011???????????101?????0100111")]
+#[derive(Debug)]
+struct VSOXEI16V();
+
+impl Execution for VSOXEI16V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Indexed, true, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???000?00000?????110?????0000111")]
+#[derive(Debug)]
+struct VLE32V();
+
+impl Execution for VLE32V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Unit, false, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???000?10000?????110?????0000111")]
+#[derive(Debug)]
+struct VLE32FFV();
+
+impl Execution for VLE32FFV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Unit, false, true)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???010???????????110?????0000111")]
+#[derive(Debug)]
+struct VLSE32V();
+
+impl Execution for VLSE32V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Strided, false, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???001???????????110?????0000111")]
+#[derive(Debug)]
+struct VLUXEI32V();
+
+impl Execution for VLUXEI32V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Indexed, false, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???011???????????110?????0000111")]
+#[derive(Debug)]
+struct VLOXEI32V();
+
+impl Execution for VLOXEI32V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Indexed, false, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???000101000?????110?????0000111")]
+#[derive(Debug)]
+struct VLRE32V();
+
+impl Execution for VLRE32V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access_whole(p, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???000?00000?????110?????0100111")]
+#[derive(Debug)]
+struct VSE32V();
+
+impl Execution for VSE32V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Unit, true, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???010???????????110?????0100111")]
+#[derive(Debug)]
+struct VSSE32V();
+
+impl Execution for VSSE32V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Strided, true, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???001???????????110?????0100111")]
+#[derive(Debug)]
+struct VSUXEI32V();
+
+impl Execution for VSUXEI32V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Indexed, true, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???011???????????110?????0100111")]
+#[derive(Debug)]
+struct VSOXEI32V();
+
+impl Execution for VSOXEI32V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Indexed, true, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???000?00000?????111?????0000111")]
+#[derive(Debug)]
+struct VLE64V();
+
+impl Execution for VLE64V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Unit, false, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???000?10000?????111?????0000111")]
+#[derive(Debug)]
+struct VLE64FFV();
+
+impl Execution for VLE64FFV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Unit, false, true)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???010???????????111?????0000111")]
+#[derive(Debug)]
+struct VLSE64V();
+
+impl Execution for VLSE64V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Strided, false, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???001???????????111?????0000111")]
+#[derive(Debug)]
+struct VLUXEI64V();
+
+impl Execution for VLUXEI64V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Indexed, false, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???011???????????111?????0000111")]
+#[derive(Debug)]
+struct VLOXEI64V();
+
+impl Execution for VLOXEI64V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Indexed, false, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???000101000?????111?????0000111")]
+#[derive(Debug)]
+struct VLRE64V();
+
+impl Execution for VLRE64V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access_whole(p, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???000?00000?????111?????0100111")]
+#[derive(Debug)]
+struct VSE64V();
+
+impl Execution for VSE64V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Unit, true, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???010???????????111?????0100111")]
+#[derive(Debug)]
+struct VSSE64V();
+
+impl Execution for VSSE64V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Strided, true, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???001???????????111?????0100111")]
+#[derive(Debug)]
+struct VSUXEI64V();
+
+impl Execution for VSUXEI64V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Indexed, true, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???011???????????111?????0100111")]
+#[derive(Debug)]
+struct VSOXEI64V();
+
+impl Execution for VSOXEI64V {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access(p, Mode::Indexed, true, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000000101011?????000?????0000111")]
+#[derive(Debug)]
+struct VLMV();
+
+impl Execution for VLMV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access_mask(p, false)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000000101011?????000?????0100111")]
+#[derive(Debug)]
+struct VSMV();
+
+impl Execution for VSMV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access_mask(p, true)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b???000101000?????000?????0100111")]
+#[derive(Debug)]
+struct VSRV();
+
+impl Execution for VSRV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        access_whole(p, true)
+    }
+}
@@ -0,0 +1,8 @@
+mod config;
+mod fixed;
+mod float;
+mod int;
+mod mask;
+mod mem;
+mod permute;
+mod reduction;
@@ -0,0 +1,428 @@
+use crate::prelude::*;
+use crate::processor::extensions::v::vector::{
+    Src, VCtx, check_fsew, f_scalar, mask, next_pc, set_f_scalar, v_ext, vd, vs1, vs2,
+};
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+
+fn overlap(a: InsnT, a_emul: isize, b: InsnT, b_emul: isize) -> bool {
+    a < b + (1 << b_emul.max(0)) && b < a + (1 << a_emul.max(0))
+}
+
+//x[rs1] as an offset or index, or uimm
+fn offset(p: &Processor, src: Src) -> u64 {
+    let rs1 = vs1(*p.state().ir());
+    match src {
+        Src::X => *p.state().xreg(rs1) as u64,
+        _ => rs1 as u64,
+    }
+}
+
+//vd[i] = op(i) over active elements from start, vd can not overlap vs2
+fn gather<F: Fn(&VCtx, usize) -> u64>(
+    p: &mut Processor,
+    start: u64,
+    op: F,
+) -> Result<(), Exception> {
+    {
+        let c = VCtx::new(p)?;
+        let (vd, vs2) = (vd(c.ir), vs2(c.ir));
+        let lmul = c.vtype.lmul;
+        c.check_group(vd, lmul)?;
+        c.check_group(vs2, lmul)?;
+        c.check_vd_v0(vd)?;
+        if overlap(vd, lmul, vs2, lmul) {
+            return Err(c.illegal());
+        }
+        let start = (start.min(c.vl as u64) as usize).max(c.vstart);
+        for i in start..c.vl {
+            if c.active(i) {
+                c.v.set_elem(vd, c.sew, i, op(&c, i));
+            }
+        }
+        c.v.set_vstart(0);
+    }
+    next_pc(p);
+    Ok(())
+}
+
+//index operand vs1 of emul
+fn check_index(p: &Processor, emul: isize) -> Result<(), Exception> {
+    let c = VCtx::new(p)?;
+    let (vd, vs1) = (vd(c.ir), vs1(c.ir));
+    c.check_group(vs1, emul)?;
+    if overlap(vd, c.vtype.lmul, vs1, emul) {
+        Err(c.illegal())
+    } else {
+        Ok(())
+    }
+}
+
+fn slide_up(p: &mut Processor, src: Src) -> Result<(), Exception> {
+    let off = offset(p, src);
+    gather(p, off, |c, i| c.v.elem(vs2(c.ir), c.sew, i - off as usize))
+}
+
+fn slide_down(p: &mut Processor, src: Src) -> Result<(), Exception> {
+    let off = offset(p, src);
+    gather(p, 0, |c, i| {
+        if (i as u64).saturating_add(off) < c.vlmax as u64 {
+            c.v.elem(vs2(c.ir), c.sew, i + off as usize)
+        } else {
+            0
+        }
+    })
+}
+
+fn gather_scalar(p: &mut Processor, src: Src) -> Result<(), Exception> {
+    let idx = offset(p, src);
+    gather(p, 0, |c, _| {
+        if idx < c.vlmax as u64 {
+            c.v.elem(vs2(c.ir), c.sew, idx as usize)
+        } else {
+            0
+        }
+    })
+}
+
+//scalar of sew inserted at the end
+fn slide1_up(p: &mut Processor, value: u64) -> Result<(), Exception> {
+    gather(p, 0, |c, i| {
+        if i == 0 {
+            value & mask(c.sew)
+        } else {
+            c.v.elem(vs2(c.ir), c.sew, i - 1)
+        }
+    })
+}
+
+fn slide1_down(p: &mut Processor, value: u64) -> Result<(), Exception> {
+    gather(p, 0, |c, i| {
+        if i + 1 == c.vl {
+            value & mask(c.sew)
+        } else {
+            c.v.elem(vs2(c.ir), c.sew, i + 1)
+        }
+    })
+}
+
+fn x_scalar(p: &Processor) -> u64 {
+    let rs1 = vs1(*p.state().ir());
+    sext(*p.state().xreg(rs1), p.state().config().xlen.len())
+}
+
+fn f_scalar1(p: &Processor) -> Result<u64, Exception> {
+    let sew = VCtx::new(p)?.sew;
+    f_scalar(p, vs1(*p.state().ir()), sew)
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001100???????????000?????1010111")]
+#[derive(Debug)]
+struct VRGATHERVV();
+
+impl Execution for VRGATHERVV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        let lmul = VCtx::new(p)?.vtype.lmul;
+        check_index(p, lmul)?;
+        gather(p, 0, |c, i| {
+            let idx = c.v.elem(vs1(c.ir), c.sew, i);
+            if idx < c.vlmax as u64 {
+                c.v.elem(vs2(c.ir), c.sew, idx as usize)
+            } else {
+                0
+            }
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001110???????????000?????1010111")]
+#[derive(Debug)]
+struct VRGATHEREI16VV();
+
+impl Execution for VRGATHEREI16VV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        let (sew, lmul) = {
+            let c = VCtx::new(p)?;
+            (c.sew, c.vtype.lmul)
+        };
+        check_index(p, lmul + 4 - sew.trailing_zeros() as isize)?;
+        gather(p, 0, |c, i| {
+            let idx = c.v.elem(vs1(c.ir), 16, i);
+            if idx < c.vlmax as u64 {
+                c.v.elem(vs2(c.ir), c.sew, idx as usize)
+            } else {
+                0
+            }
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001100???????????100?????1010111")]
+#[derive(Debug)]
+struct VRGATHERVX();
+
+impl Execution for VRGATHERVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        gather_scalar(p, Src::X)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001100???????????011?????1010111")]
+#[derive(Debug)]
+struct VRGATHERVI();
+
+impl Execution for VRGATHERVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        gather_scalar(p, Src::U)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001110???????????100?????1010111")]
+#[derive(Debug)]
+struct VSLIDEUPVX();
+
+impl Execution for VSLIDEUPVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        slide_up(p, Src::X)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001110???????????011?????1010111")]
+#[derive(Debug)]
+struct VSLIDEUPVI();
+
+impl Execution for VSLIDEUPVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        slide_up(p, Src::U)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001111???????????100?????1010111")]
+#[derive(Debug)]
+struct VSLIDEDOWNVX();
+
+impl Execution for VSLIDEDOWNVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        slide_down(p, Src::X)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001111???????????011?????1010111")]
+#[derive(Debug)]
+struct VSLIDEDOWNVI();
+
+impl Execution for VSLIDEDOWNVI {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        slide_down(p, Src::U)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001110???????????110?????1010111")]
+#[derive(Debug)]
+struct VSLIDE1UPVX();
+
+impl Execution for VSLIDE1UPVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        let value = x_scalar(p);
+        slide1_up(p, value)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001111???????????110?????1010111")]
+#[derive(Debug)]
+struct VSLIDE1DOWNVX();
+
+impl Execution for VSLIDE1DOWNVX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        let value = x_scalar(p);
+        slide1_down(p, value)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001110???????????101?????1010111")]
+#[derive(Debug)]
+struct VFSLIDE1UPVF();
+
+impl Execution for VFSLIDE1UPVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        let value = f_scalar1(p)?;
+        slide1_up(p, value)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b001111???????????101?????1010111")]
+#[derive(Debug)]
+struct VFSLIDE1DOWNVF();
+
+impl Execution for VFSLIDE1DOWNVF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        let value = f_scalar1(p)?;
+        slide1_down(p, value)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0101111??????????010?????1010111")]
+#[derive(Debug)]
+struct VCOMPRESSVM();
+
+impl Execution for VCOMPRESSVM {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        {
+            let c = VCtx::new(p)?;
+            let (vd, vs2, vs1) = (vd(c.ir), vs2(c.ir), vs1(c.ir));
+            let lmul = c.vtype.lmul;
+            c.check_vstart_zero()?;
+            c.check_group(vd, lmul)?;
+            c.check_group(vs2, lmul)?;
+            if overlap(vd, lmul, vs2, lmul) || overlap(vd, lmul, vs1, 0) {
+                return Err(c.illegal());
+            }
+            let mut j = 0;
+            for i in 0..c.vl {
+                if c.v.mask(vs1, i) {
+                    c.v.set_elem(vd, c.sew, j, c.v.elem(vs2, c.sew, i));
+                    j += 1;
+                }
+            }
+        }
+        next_pc(p);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0100001?????00000010?????1010111")]
+#[derive(Debug)]
+struct VMVXS();
+
+impl Execution for VMVXS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        let value = {
+            let c = VCtx::new(p)?;
+            sext(c.v.elem(vs2(c.ir), c.sew, 0), c.sew)
+        };
+        let rd = vd(*p.state().ir());
+        let value = value & p.state().config().xlen.mask();
+        p.state_mut().set_xreg(rd, value);
+        next_pc(p);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010000100000?????110?????1010111")]
+#[derive(Debug)]
+struct VMVSX();
+
+impl Execution for VMVSX {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        {
+            let value = x_scalar(p);
+            let c = VCtx::new(p)?;
+            if c.vstart < c.vl {
+                c.v.set_elem(vd(c.ir), c.sew, 0, value & mask(c.sew));
+            }
+            c.v.set_vstart(0);
+        }
+        next_pc(p);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b0100001?????00000001?????1010111")]
+#[derive(Debug)]
+struct VFMVFS();
+
+impl Execution for VFMVFS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        let (sew, value) = {
+            let c = VCtx::new(p)?;
+            (c.sew, c.v.elem(vs2(c.ir), c.sew, 0))
+        };
+        check_fsew(p, sew)?;
+        let rd = vd(*p.state().ir());
+        set_f_scalar(p, rd, sew, value);
+        next_pc(p);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b010000100000?????101?????1010111")]
+#[derive(Debug)]
+struct VFMVSF();
+
+impl Execution for VFMVSF {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        {
+            let value = f_scalar1(p)?;
+            let c = VCtx::new(p)?;
+            if c.vstart < c.vl {
+                c.v.set_elem(vd(c.ir), c.sew, 0, value);
+            }
+            c.v.set_vstart(0);
+        }
+        next_pc(p);
+        Ok(())
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b1001111??????????011?????1010111")]
+#[derive(Debug)]
+struct VMVNRRV();
+
+impl Execution for VMVNRRV {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        {
+            let v = v_ext(p)?;
+            let ir = *p.state().ir();
+            let (vd, vs2, nr) = (vd(ir), vs2(ir), vs1(ir) + 1);
+            if !nr.is_power_of_two() || nr > 8 || vd % nr != 0 || vs2 % nr != 0 {
+                return Err(Exception::IllegalInsn(ir));
+            }
+            //works without a legal vtype
+            let vtype = v.vtype(p.state().config().xlen.len());
+            let eew = if vtype.vill { 8 } else { vtype.sew };
+            let evl = nr as usize * v.vlen / eew;
+            for i in v.vstart()..evl {
+                v.set_elem(vd, eew, i, v.elem(vs2, eew, i));
+            }
+            v.set_vstart(0);
+        }
+        next_pc(p);
+        Ok(())
+    }
+}
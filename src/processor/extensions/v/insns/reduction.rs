@@ -0,0 +1,234 @@
+use crate::prelude::*;
+use crate::processor::extensions::f::float::FPState;
+use crate::processor::extensions::v::insns::float::{fadd, fmax, fmin, fwiden};
+use crate::processor::extensions::v::vector::{
+    VCtx, accrue_fflags, check_fsew, fp_state, next_pc, reduce_engine, signed,
+};
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+
+fn reduce<F: Fn(u64, u64, usize) -> u64>(
+    p: &mut Processor,
+    widen: bool,
+    op: F,
+) -> Result<(), Exception> {
+    reduce_engine(p, widen, op)?;
+    next_pc(p);
+    Ok(())
+}
+
+//ordered and unordered sums are both computed in element order
+fn freduce<F: Fn(u64, u64, usize, &mut FPState) -> u64>(
+    p: &mut Processor,
+    widen: bool,
+    op: F,
+) -> Result<(), Exception> {
+    let sew = VCtx::new(p)?.sew;
+    check_fsew(p, sew)?;
+    if widen {
+        check_fsew(p, sew * 2)?;
+    }
+    let mut state = fp_state(p)?;
+    reduce_engine(p, widen, |acc, elem, sew| op(acc, elem, sew, &mut state))?;
+    accrue_fflags(p, &state)?;
+    next_pc(p);
+    Ok(())
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000000???????????010?????1010111")]
+#[derive(Debug)]
+struct VREDSUMVS();
+
+impl Execution for VREDSUMVS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        reduce(p, false, |acc, e, _| acc.wrapping_add(e))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000001???????????010?????1010111")]
+#[derive(Debug)]
+struct VREDANDVS();
+
+impl Execution for VREDANDVS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        reduce(p, false, |acc, e, _| acc & e)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000010???????????010?????1010111")]
+#[derive(Debug)]
+struct VREDORVS();
+
+impl Execution for VREDORVS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        reduce(p, false, |acc, e, _| acc | e)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000011???????????010?????1010111")]
+#[derive(Debug)]
+struct VREDXORVS();
+
+impl Execution for VREDXORVS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        reduce(p, false, |acc, e, _| acc ^ e)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000100???????????010?????1010111")]
+#[derive(Debug)]
+struct VREDMINUVS();
+
+impl Execution for VREDMINUVS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        reduce(p, false, |acc, e, _| acc.min(e))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000101???????????010?????1010111")]
+#[derive(Debug)]
+struct VREDMINVS();
+
+impl Execution for VREDMINVS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        reduce(p, false, |acc, e, sew| signed(acc, sew).min(signed(e, sew)) as u64)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000110???????????010?????1010111")]
+#[derive(Debug)]
+struct VREDMAXUVS();
+
+impl Execution for VREDMAXUVS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        reduce(p, false, |acc, e, _| acc.max(e))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000111???????????010?????1010111")]
+#[derive(Debug)]
+struct VREDMAXVS();
+
+impl Execution for VREDMAXVS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        reduce(p, false, |acc, e, sew| signed(acc, sew).max(signed(e, sew)) as u64)
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110000???????????000?????1010111")]
+#[derive(Debug)]
+struct VWREDSUMUVS();
+
+impl Execution for VWREDSUMUVS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        reduce(p, true, |acc, e, _| acc.wrapping_add(e))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110001???????????000?????1010111")]
+#[derive(Debug)]
+struct VWREDSUMVS();
+
+impl Execution for VWREDSUMVS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        reduce(p, true, |acc, e, sew| acc.wrapping_add(signed(e, sew) as u64))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000001???????????001?????1010111")]
+#[derive(Debug)]
+struct VFREDUSUMVS();
+
+impl Execution for VFREDUSUMVS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        freduce(p, false, |acc, e, sew, s| fadd(acc, e, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000011???????????001?????1010111")]
+#[derive(Debug)]
+struct VFREDOSUMVS();
+
+impl Execution for VFREDOSUMVS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        freduce(p, false, |acc, e, sew, s| fadd(acc, e, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000101???????????001?????1010111")]
+#[derive(Debug)]
+struct VFREDMINVS();
+
+impl Execution for VFREDMINVS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        freduce(p, false, |acc, e, sew, s| fmin(acc, e, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b000111???????????001?????1010111")]
+#[derive(Debug)]
+struct VFREDMAXVS();
+
+impl Execution for VFREDMAXVS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        freduce(p, false, |acc, e, sew, s| fmax(acc, e, sew, s))
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110001???????????001?????1010111")]
+#[derive(Debug)]
+struct VFWREDUSUMVS();
+
+impl Execution for VFWREDUSUMVS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        freduce(p, true, |acc, e, sew, s| {
+            let e = fwiden(e, sew, s);
+            fadd(acc, e, sew * 2, s)
+        })
+    }
+}
+
+#[derive(Instruction)]
+#[format(R)]
+#[code("32b110011???????????001?????1010111")]
+#[derive(Debug)]
+struct VFWREDOSUMVS();
+
+impl Execution for VFWREDOSUMVS {
+    fn execute(&self, p: &mut Processor) -> Result<(), Exception> {
+        freduce(p, true, |acc, e, sew, s| {
+            let e = fwiden(e, sew, s);
+            fadd(acc, e, sew * 2, s)
+        })
+    }
+}
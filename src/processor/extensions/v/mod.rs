@@ -0,0 +1,476 @@
+use crate::prelude::{InsnT, RegT};
+use crate::processor::extensions::{HasCsr, NoStepCb};
+use crate::processor::ProcessorState;
+use crate::snapshot::{SnapshotReader, SnapshotWriter};
+use std::cell::RefCell;
+use std::io;
+use std::io::{Read, Write};
+use std::rc::Rc;
+
+pub mod csrs;
+mod insns;
+mod vector;
+
+use csrs::VCsrs;
+
+//decoded vtype
+#[derive(Copy, Clone, Debug)]
+pub struct VType {
+    pub vill: bool,
+    pub vma: bool,
+    pub vta: bool,
+    //element width in bits
+    pub sew: usize,
+    //log2 of LMUL, -3..=3
+    pub lmul: isize,
+}
+
+impl VType {
+    pub fn new(value: RegT, xlen: usize, elen: usize) -> VType {
+        let invalid = VType {
+            vill: true,
+            vma: false,
+            vta: false,
+            sew: 8,
+            lmul: 0,
+        };
+        //reserved bits
+        if (value >> 8) & ((1 << (xlen - 8)) - 1) != 0 {
+            return invalid;
+        }
+        let vsew = (value >> 3) & 0x7;
+        if vsew > 3 || (8 << vsew) > elen {
+            return invalid;
+        }
+        let sew = 8 << vsew as usize;
+        let lmul = match value & 0x7 {
+            0 => 0,
+            1 => 1,
+            2 => 2,
+            3 => 3,
+            5 => -3,
+            6 => -2,
+            7 => -1,
+            _ => return invalid,
+        };
+        //SEW <= LMUL * ELEN
+        if lmul < 0 && sew > elen >> -lmul as usize {
+            return invalid;
+        }
+        VType {
+            vill: false,
+            vma: (value >> 7) & 1 == 1,
+            vta: (value >> 6) & 1 == 1,
+            sew,
+            lmul,
+        }
+    }
+
+    pub fn vlmax(&self, vlen: usize) -> usize {
+        if self.lmul < 0 {
+            (vlen / self.sew) >> -self.lmul as usize
+        } else {
+            (vlen / self.sew) << self.lmul as usize
+        }
+    }
+}
+
+pub struct ExtensionV {
+    pub vlen: usize,
+    pub elen: usize,
+    vreg: RefCell<Box<[u8]>>,
+    csrs: Rc<VCsrs>,
+    dirty: Rc<RefCell<RegT>>,
+}
+
+impl ExtensionV {
+    pub fn new(state: &ProcessorState) -> ExtensionV {
+        let vlen = state.config().vlen;
+        let elen = state.config().elen;
+        let e = ExtensionV {
+            vlen,
+            elen,
+            vreg: RefCell::new(vec![0u8; vlen / 8 * 32].into_boxed_slice()),
+            csrs: Rc::new(VCsrs::new(state.config().xlen.len())),
+            dirty: Rc::new(RefCell::new(0)),
+        };
+        e.csrs.vlenb_mut().set((vlen / 8) as RegT);
+        e.csrs.vtype_mut().set_vill(1);
+
+        //map dirty to mstatus.vs
+        state.priv_m().mstatus_mut().set_vs_transform({
+            let dirty = e.dirty.clone();
+            move |value| {
+                *dirty.borrow_mut() = value & 0x3;
+                0
+            }
+        });
+        state.priv_m().mstatus_mut().vs_transform({
+            let dirty = e.dirty.clone();
+            move |_| *(*dirty).borrow()
+        });
+        //deleg vxrm and vxsat to vcsr
+        e.csrs.vxsat_mut().vxsat_transform({
+            let csrs = e.csrs.clone();
+            move |_| csrs.vcsr().vxsat()
+        });
+        e.csrs.vxsat_mut().set_vxsat_transform({
+            let csrs = e.csrs.clone();
+            move |field| {
+                csrs.vcsr_mut().set_vxsat(field);
+                0
+            }
+        });
+        e.csrs.vxrm_mut().vxrm_transform({
+            let csrs = e.csrs.clone();
+            move |_| csrs.vcsr().vxrm()
+        });
+        e.csrs.vxrm_mut().set_vxrm_transform({
+            let csrs = e.csrs.clone();
+            move |field| {
+                csrs.vcsr_mut().set_vxrm(field);
+                0
+            }
+        });
+        e
+    }
+
+    pub fn vlenb(&self) -> usize {
+        self.vlen >> 3
+    }
+
+    pub fn vtype(&self, xlen: usize) -> VType {
+        VType::new(self.csrs.vtype().get(), xlen, self.elen)
+    }
+
+    pub fn vl(&self) -> usize {
+        self.csrs.vl().get() as usize
+    }
+
+    pub fn vstart(&self) -> usize {
+        self.csrs.vstart().get() as usize
+    }
+
+    pub fn set_vstart(&self, value: usize) {
+        *self.dirty.borrow_mut() = 0x3;
+        self.csrs.vstart_mut().set(value as RegT)
+    }
+
+    //fault-only-first loads truncate vl
+    pub fn set_vl(&self, vl: usize) {
+        *self.dirty.borrow_mut() = 0x3;
+        self.csrs.vl_mut().set(vl as RegT)
+    }
+
+    pub fn set_vl_vtype(&self, vl: usize, vtype: RegT) {
+        *self.dirty.borrow_mut() = 0x3;
+        self.csrs.vl_mut().set(vl as RegT);
+        self.csrs.vtype_mut().set(vtype);
+    }
+
+    pub fn vxrm(&self) -> u8 {
+        self.csrs.vcsr().vxrm() as u8
+    }
+
+    pub fn set_vxsat(&self) {
+        *self.dirty.borrow_mut() = 0x3;
+        self.csrs.vcsr_mut().set_vxsat(1)
+    }
+
+    //element idx of register group starting at reg, eew in bits
+    pub fn elem(&self, reg: InsnT, eew: usize, idx: usize) -> u64 {
+        let bytes = eew >> 3;
+        let offset = reg as usize * self.vlenb() + idx * bytes;
+        let mut value = [0u8; 8];
+        value[..bytes].copy_from_slice(&self.vreg.borrow()[offset..offset + bytes]);
+        u64::from_le_bytes(value)
+    }
+
+    pub fn set_elem(&self, reg: InsnT, eew: usize, idx: usize, value: u64) {
+        let bytes = eew >> 3;
+        let offset = reg as usize * self.vlenb() + idx * bytes;
+        *self.dirty.borrow_mut() = 0x3;
+        self.vreg.borrow_mut()[offset..offset + bytes].copy_from_slice(&value.to_le_bytes()[..bytes])
+    }
+
+    pub fn mask(&self, reg: InsnT, idx: usize) -> bool {
+        let offset = reg as usize * self.vlenb() + (idx >> 3);
+        (self.vreg.borrow()[offset] >> (idx & 0x7)) & 1 == 1
+    }
+
+    pub fn set_mask(&self, reg: InsnT, idx: usize, value: bool) {
+        let offset = reg as usize * self.vlenb() + (idx >> 3);
+        *self.dirty.borrow_mut() = 0x3;
+        let mut vreg = self.vreg.borrow_mut();
+        if value {
+            vreg[offset] |= 1 << (idx & 0x7)
+        } else {
+            vreg[offset] &= !(1 << (idx & 0x7))
+        }
+    }
+
+    pub fn vreg(&self, id: InsnT) -> Vec<u8> {
+        let offset = (id & 0x1f) as usize * self.vlenb();
+        self.vreg.borrow()[offset..offset + self.vlenb()].to_vec()
+    }
+
+    pub fn dirty(&self) -> RegT {
+        *(*self.dirty).borrow()
+    }
+
+    pub fn save_snapshot<W: Write>(&self, w: &mut SnapshotWriter<W>) -> io::Result<()> {
+        w.write_bytes(&self.vreg.borrow())?;
+        w.write_u64(self.csrs.vstart().get())?;
+        w.write_u64(self.csrs.vcsr().get())?;
+        w.write_u64(self.csrs.vl().get())?;
+        w.write_u64(self.csrs.vtype().get())?;
+        w.write_u64(self.dirty())
+    }
+
+    pub fn restore_snapshot<R: Read>(&self, r: &mut SnapshotReader<R>) -> io::Result<()> {
+        r.read_bytes(&mut self.vreg.borrow_mut())?;
+        self.csrs.vstart_mut().set(r.read_u64()?);
+        self.csrs.vcsr_mut().set(r.read_u64()?);
+        self.csrs.vl_mut().set(r.read_u64()?);
+        self.csrs.vtype_mut().set(r.read_u64()?);
+        *self.dirty.borrow_mut() = r.read_u64()?;
+        Ok(())
+    }
+}
+
+impl HasCsr for ExtensionV {
+    fn csr_write(&self, _: &ProcessorState, addr: InsnT, value: RegT) -> Option<()> {
+        if self.dirty() == 0 {
+            return None;
+        }
+        //vstart only holds element indices
+        let value = if addr == 0x008 {
+            value & (self.vlen - 1) as RegT
+        } else {
+            value
+        };
+        let res = self.csrs.write(addr as u64, value);
+        if res.is_some() {
+            *self.dirty.borrow_mut() = 0x3;
+        }
+        res
+    }
+    fn csr_read(&self, _: &ProcessorState, addr: InsnT) -> Option<RegT> {
+        if self.dirty() == 0 {
+            None
+        } else {
+            self.csrs.read(addr as u64)
+        }
+    }
+}
+
+impl NoStepCb for ExtensionV {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::devices::bus::Bus;
+    use crate::prelude::XLen;
+    use crate::processor::extensions::Extension;
+    use crate::processor::{Processor, ProcessorCfg, SatpMode};
+    use crate::system::System;
+    use terminus_spaceport::memory::region::GHEAP;
+
+    //vlen = 128, elen = 64, insns are placed at 0x8000_0000
+    fn system(insns: &[u32]) -> System {
+        let mut sys = System::new("test", "top_tests/elf/rv64ui-p-add", 100, 1);
+        sys.new_processor(ProcessorCfg {
+            xlen: XLen::X64,
+            enable_dirty: true,
+            isa: "rv64imafdcvsu".parse().unwrap(),
+            freq: 1000000000,
+            triggers: 0,
+            satp_modes: vec![SatpMode::Sv39].into_boxed_slice(),
+            vlen: 128,
+            elen: 64,
+            cache_block_size: 64,
+            entropy_seed: 0,
+            hpm_counters: 0,
+        });
+        sys.register_memory("main_memory", 0x8000_0000, &GHEAP.alloc(0x2000, 1).unwrap())
+            .unwrap();
+        for (i, insn) in insns.iter().enumerate() {
+            sys.bus()
+                .write_u32(&(0x8000_0000 + i as u64 * 4), insn)
+                .unwrap();
+        }
+        sys.reset(vec![0x8000_0000]).unwrap();
+        //writing vector state turns mstatus.vs on
+        v_ext(sys.processor(0).unwrap()).set_vstart(0);
+        sys
+    }
+
+    fn v_ext(p: &Processor) -> &ExtensionV {
+        if let Extension::V(v) = p.state().get_extension('v') {
+            v
+        } else {
+            unreachable!()
+        }
+    }
+
+    #[test]
+    fn vtype_test() {
+        //e32, m1
+        let t = VType::new(0x10, 64, 64);
+        assert!(!t.vill && !t.vta && !t.vma);
+        assert_eq!((t.sew, t.lmul, t.vlmax(128)), (32, 0, 4));
+        //e8, mf8, ta, ma
+        let t = VType::new(0xc5, 64, 64);
+        assert!(!t.vill && t.vta && t.vma);
+        assert_eq!((t.sew, t.lmul, t.vlmax(128)), (8, -3, 2));
+        //e64, m8
+        assert_eq!(VType::new(0x1b, 64, 64).vlmax(128), 16);
+        //e16, mf8 breaks SEW <= LMUL * ELEN
+        assert!(VType::new(0x0d, 64, 64).vill);
+        //reserved lmul, sew and bits
+        assert!(VType::new(0x14, 64, 64).vill);
+        assert!(VType::new(0x20, 64, 64).vill);
+        assert!(VType::new(0x110, 64, 64).vill);
+        assert!(VType::new(1 << 31, 32, 64).vill);
+        //sew over elen
+        assert!(VType::new(0x18, 64, 32).vill);
+    }
+
+    #[test]
+    fn vsetvl_test() {
+        let mut sys = system(&[
+            0x0d05f557, //vsetvli a0, a1, e32, m1, ta, ma
+            0x01807657, //vsetvli a2, zero, e64, m1, tu, mu
+            0x01007057, //vsetvli zero, zero, e32, m1, tu, mu
+            0x80e5f6d7, //vsetvl a3, a1, a4
+        ]);
+        let p = sys.processor(0).unwrap();
+        p.state_mut().set_xreg(11, 10);
+        p.state_mut().set_xreg(14, 0x20);
+        p.state_mut().set_xreg(13, 1);
+
+        //avl over vlmax
+        p.step(1);
+        assert_eq!(*p.state().xreg(10), 4);
+        assert_eq!(v_ext(p).vl(), 4);
+        assert!(v_ext(p).vtype(64).vta);
+        //rs1 = x0 sets vlmax
+        p.step(1);
+        assert_eq!(*p.state().xreg(12), 2);
+        assert_eq!(v_ext(p).vtype(64).sew, 64);
+        //rs1 = x0 and rd = x0 keeps vl
+        p.step(1);
+        assert_eq!(v_ext(p).vl(), 2);
+        assert_eq!(v_ext(p).vtype(64).sew, 32);
+        //illegal vtype sets vill and clears vl
+        p.step(1);
+        assert_eq!(*p.state().xreg(13), 0);
+        assert_eq!(v_ext(p).vl(), 0);
+        assert!(v_ext(p).vtype(64).vill);
+        assert_eq!(v_ext(p).csrs.vtype().get(), 1 << 63);
+    }
+
+    #[test]
+    fn vadd_mask_tail_test() {
+        let mut sys = system(&[
+            0x0d05f557, //vsetvli a0, a1, e32, m1, ta, ma
+            0x002081d7, //vadd.vv v3, v1, v2, v0.t
+            0x02208257, //vadd.vv v4, v1, v2
+            0x00208057, //vadd.vv v0, v1, v2, v0.t
+        ]);
+        let p = sys.processor(0).unwrap();
+        p.state_mut().set_xreg(11, 3);
+        let v = v_ext(p);
+        for i in 0..4 {
+            v.set_elem(1, 32, i, i as u64 + 1);
+            v.set_elem(2, 32, i, (i as u64 + 1) * 10);
+            v.set_elem(3, 32, i, 0xdead);
+            v.set_elem(4, 32, i, 0xbeef);
+        }
+        v.set_elem(0, 8, 0, 0b101);
+
+        p.step(3);
+        let v = v_ext(p);
+        //agnostic elements are left undisturbed
+        assert_eq!(
+            (0..4).map(|i| v.elem(3, 32, i)).collect::<Vec<u64>>(),
+            vec![11, 0xdead, 33, 0xdead]
+        );
+        assert_eq!(
+            (0..4).map(|i| v.elem(4, 32, i)).collect::<Vec<u64>>(),
+            vec![11, 22, 33, 0xbeef]
+        );
+        //masked instructions can not write v0
+        p.step(1);
+        assert_eq!(p.state().priv_m().mcause().get(), 2);
+        assert_eq!(v_ext(p).elem(0, 8, 0), 0b101);
+    }
+
+    #[test]
+    fn vseg_test() {
+        let mut sys = system(&[
+            0x0d05f557, //vsetvli a0, a1, e32, m1, ta, ma
+            0x22056207, //vlseg2e32.v v4, (a0)
+            0x22066227, //vsseg2e32.v v4, (a2)
+        ]);
+        let bus = sys.bus().clone();
+        for i in 0..8u64 {
+            bus.write_u32(&(0x8000_1000 + i * 4), &(i as u32 + 1))
+                .unwrap();
+            bus.write_u32(&(0x8000_1100 + i * 4), &0xdead).unwrap();
+        }
+        let p = sys.processor(0).unwrap();
+        p.state_mut().set_xreg(11, 3);
+        p.step(1);
+        p.state_mut().set_xreg(10, 0x8000_1000);
+        p.state_mut().set_xreg(12, 0x8000_1100);
+        p.step(1);
+        let v = v_ext(p);
+        //fields of a segment go to consecutive registers
+        assert_eq!(
+            (0..3).map(|i| v.elem(4, 32, i)).collect::<Vec<u64>>(),
+            vec![1, 3, 5]
+        );
+        assert_eq!(
+            (0..3).map(|i| v.elem(5, 32, i)).collect::<Vec<u64>>(),
+            vec![2, 4, 6]
+        );
+        p.step(1);
+        let mut data = 0;
+        for i in 0..8u64 {
+            bus.read_u32(&(0x8000_1100 + i * 4), &mut data).unwrap();
+            assert_eq!(data, if i < 6 { i as u32 + 1 } else { 0xdead });
+        }
+    }
+
+    #[test]
+    fn vwiden_narrow_test() {
+        let mut sys = system(&[
+            0x0c85f557, //vsetvli a0, a1, e16, m1, ta, ma
+            0xc2112257, //vwaddu.vv v4, v1, v2
+            0xb240b357, //vnsrl.wi v6, v4, 1
+            0xc21120d7, //vwaddu.vv v1, v1, v2
+        ]);
+        let p = sys.processor(0).unwrap();
+        p.state_mut().set_xreg(11, 8);
+        let v = v_ext(p);
+        for i in 0..8 {
+            v.set_elem(1, 16, i, if i == 0 { 0xffff } else { i as u64 });
+            v.set_elem(2, 16, i, 1);
+        }
+        p.step(3);
+        let v = v_ext(p);
+        //2*SEW results fill the register group v4, v5
+        assert_eq!(
+            (0..8).map(|i| v.elem(4, 32, i)).collect::<Vec<u64>>(),
+            vec![0x10000, 2, 3, 4, 5, 6, 7, 8]
+        );
+        assert_eq!(
+            (0..8).map(|i| v.elem(6, 16, i)).collect::<Vec<u64>>(),
+            vec![0x8000, 1, 1, 2, 2, 3, 3, 4]
+        );
+        //the widened destination group must be aligned
+        p.step(1);
+        assert_eq!(p.state().priv_m().mcause().get(), 2);
+    }
+}
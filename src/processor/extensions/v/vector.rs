@@ -0,0 +1,439 @@
+use crate::prelude::*;
+use crate::processor::extensions::f::float::FPState;
+use crate::processor::extensions::f::{ExtensionF, FLen, FRegT};
+use crate::processor::extensions::v::{ExtensionV, VType};
+use crate::processor::extensions::Extension;
+use crate::processor::trap::Exception;
+use crate::processor::Processor;
+use simple_soft_float::{RoundingMode, StatusFlags};
+
+pub fn vd(ir: InsnT) -> InsnT {
+    (ir >> 7) & 0x1f
+}
+
+pub fn vs1(ir: InsnT) -> InsnT {
+    (ir >> 15) & 0x1f
+}
+
+pub fn vs2(ir: InsnT) -> InsnT {
+    (ir >> 20) & 0x1f
+}
+
+//vm == 0, masked by v0
+pub fn masked(ir: InsnT) -> bool {
+    (ir >> 25) & 0x1 == 0
+}
+
+pub fn mask(bits: usize) -> u64 {
+    if bits >= 64 {
+        !0
+    } else {
+        (1 << bits) - 1
+    }
+}
+
+pub fn signed(value: u64, bits: usize) -> i64 {
+    sext(value & mask(bits), bits) as i64
+}
+
+pub fn next_pc(p: &mut Processor) {
+    let pc = *p.state().pc() + 4;
+    p.state_mut().set_pc(pc);
+}
+
+pub fn v_ext(p: &Processor) -> Result<&ExtensionV, Exception> {
+    p.state().check_extension('v')?;
+    if let Extension::V(v) = p.state().get_extension('v') {
+        if v.dirty() == 0 {
+            Err(Exception::IllegalInsn(*p.state().ir()))
+        } else {
+            Ok(v)
+        }
+    } else {
+        Err(Exception::IllegalInsn(*p.state().ir()))
+    }
+}
+
+pub fn f_ext(p: &Processor) -> Result<&ExtensionF, Exception> {
+    p.state().check_extension('f')?;
+    if let Extension::F(f) = p.state().get_extension('f') {
+        if f.dirty() == 0 {
+            Err(Exception::IllegalInsn(*p.state().ir()))
+        } else {
+            Ok(f)
+        }
+    } else {
+        Err(Exception::IllegalInsn(*p.state().ir()))
+    }
+}
+
+//floating-point element width needs the scalar extension of the same width
+pub fn check_fsew(p: &Processor, sew: usize) -> Result<(), Exception> {
+    f_ext(p)?;
+    match sew {
+        32 => Ok(()),
+        64 => p.state().check_extension('d'),
+        _ => Err(Exception::IllegalInsn(*p.state().ir())),
+    }
+}
+
+pub fn fp_state(p: &Processor) -> Result<FPState, Exception> {
+    let mut state = FPState::default();
+    state.rounding_mode = match f_ext(p)?.frm() {
+        0 => RoundingMode::TiesToEven,
+        1 => RoundingMode::TowardZero,
+        2 => RoundingMode::TowardNegative,
+        3 => RoundingMode::TowardPositive,
+        4 => RoundingMode::TiesToAway,
+        _ => return Err(Exception::IllegalInsn(*p.state().ir())),
+    };
+    Ok(state)
+}
+
+pub fn status_flags_to_bits(s: &StatusFlags) -> RegT {
+    (s.bits() << 27).reverse_bits() as RegT
+}
+
+pub fn accrue_fflags(p: &Processor, state: &FPState) -> Result<(), Exception> {
+    f_ext(p)?.accrue_fflags(status_flags_to_bits(&state.status_flags));
+    Ok(())
+}
+
+//scalar f register as an element of sew
+pub fn f_scalar(p: &Processor, id: InsnT, sew: usize) -> Result<u64, Exception> {
+    check_fsew(p, sew)?;
+    let f = f_ext(p)?;
+    match sew {
+        32 => Ok(f.flen.boxed(*f.freg(id), FLen::F32) as u64),
+        _ => Ok(f.flen.boxed(*f.freg(id), FLen::F64) as u64),
+    }
+}
+
+pub fn set_f_scalar(p: &mut Processor, id: InsnT, sew: usize, value: u64) {
+    if let Extension::F(f) = p.state_mut().get_extension_mut('f') {
+        let flen = if sew == 32 { FLen::F32 } else { FLen::F64 };
+        let value = f.flen.padding(value as FRegT, flen);
+        f.set_freg(id, value)
+    }
+}
+
+//the second operand
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum Src {
+    //vs1
+    V,
+    //x[rs1]
+    X,
+    //sign-extended imm
+    I,
+    //zero-extended imm
+    U,
+    //f[rs1]
+    F,
+}
+
+//element widths of vd and vs2, in SEW
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum Shape {
+    //SEW = SEW op SEW
+    Single,
+    //2*SEW = SEW op SEW
+    Widen,
+    //2*SEW = 2*SEW op SEW
+    WidenW,
+    //SEW = 2*SEW op SEW
+    Narrow,
+}
+
+pub struct VCtx<'p> {
+    pub v: &'p ExtensionV,
+    pub ir: InsnT,
+    pub vtype: VType,
+    pub sew: usize,
+    pub vl: usize,
+    pub vstart: usize,
+    pub vlmax: usize,
+    pub masked: bool,
+}
+
+impl<'p> VCtx<'p> {
+    pub fn new(p: &'p Processor) -> Result<VCtx<'p>, Exception> {
+        let v = v_ext(p)?;
+        let ir = *p.state().ir();
+        let vtype = v.vtype(p.state().config().xlen.len());
+        if vtype.vill {
+            return Err(Exception::IllegalInsn(ir));
+        }
+        Ok(VCtx {
+            v,
+            ir,
+            vtype,
+            sew: vtype.sew,
+            vl: v.vl(),
+            vstart: v.vstart(),
+            vlmax: vtype.vlmax(v.vlen),
+            masked: masked(ir),
+        })
+    }
+
+    pub fn illegal(&self) -> Exception {
+        Exception::IllegalInsn(self.ir)
+    }
+
+    pub fn active(&self, idx: usize) -> bool {
+        !self.masked || self.v.mask(0, idx)
+    }
+
+    //emul is log2 of EMUL
+    pub fn check_group(&self, reg: InsnT, emul: isize) -> Result<(), Exception> {
+        if emul < -3 || emul > 3 || emul > 0 && reg & ((1 << emul) - 1) != 0 {
+            Err(self.illegal())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn check_eew(&self, eew: usize) -> Result<(), Exception> {
+        if eew > self.v.elen {
+            Err(self.illegal())
+        } else {
+            Ok(())
+        }
+    }
+
+    //destination of a masked instruction can not be v0 unless it is a mask
+    pub fn check_vd_v0(&self, vd: InsnT) -> Result<(), Exception> {
+        if self.masked && vd == 0 {
+            Err(self.illegal())
+        } else {
+            Ok(())
+        }
+    }
+
+    //overlap rules of register groups with different eew, eew 1 for masks
+    pub fn check_overlap(
+        &self,
+        dst: InsnT,
+        dst_emul: isize,
+        dst_eew: usize,
+        src: InsnT,
+        src_emul: isize,
+        src_eew: usize,
+    ) -> Result<(), Exception> {
+        let len = |emul: isize| if emul > 0 { 1 << emul } else { 1 };
+        let (dst_end, src_end) = (dst + len(dst_emul), src + len(src_emul));
+        let allowed = dst >= src_end
+            || src >= dst_end
+            || dst_eew == src_eew
+            || dst_eew < src_eew && dst == src
+            || dst_eew > src_eew && src_emul >= 0 && src_end == dst_end;
+        if allowed {
+            Ok(())
+        } else {
+            Err(self.illegal())
+        }
+    }
+
+    pub fn check_vstart_zero(&self) -> Result<(), Exception> {
+        if self.vstart != 0 {
+            Err(self.illegal())
+        } else {
+            Ok(())
+        }
+    }
+
+    //the second operand as a scalar
+    pub fn scalar(&self, p: &Processor, src: Src, sew: usize) -> Result<Option<u64>, Exception> {
+        let rs1 = vs1(self.ir);
+        let xlen = p.state().config().xlen.len();
+        Ok(match src {
+            Src::V => None,
+            Src::X => Some(sext(*p.state().xreg(rs1), xlen) & mask(sew)),
+            Src::I => Some(sext(rs1 as RegT, 5) & mask(sew)),
+            Src::U => Some(rs1 as u64),
+            Src::F => Some(f_scalar(p, rs1, sew)?),
+        })
+    }
+}
+
+//vd[i] = op(vd[i], vs2[i], vs1[i]/scalar, sew), vd[i] is only meaningful for multiply-add
+pub fn arith_engine<F: FnMut(u64, u64, u64, usize) -> u64>(
+    p: &Processor,
+    src: Src,
+    shape: Shape,
+    mut op: F,
+) -> Result<(), Exception> {
+    let c = VCtx::new(p)?;
+    let (vd, vs2, vs1) = (vd(c.ir), vs2(c.ir), vs1(c.ir));
+    let lmul = c.vtype.lmul;
+    let sew = c.sew;
+    let (d_eew, d_emul, s2_eew, s2_emul) = match shape {
+        Shape::Single => (sew, lmul, sew, lmul),
+        Shape::Widen => (sew * 2, lmul + 1, sew, lmul),
+        Shape::WidenW => (sew * 2, lmul + 1, sew * 2, lmul + 1),
+        Shape::Narrow => (sew, lmul, sew * 2, lmul + 1),
+    };
+    c.check_eew(d_eew)?;
+    c.check_eew(s2_eew)?;
+    c.check_group(vd, d_emul)?;
+    c.check_group(vs2, s2_emul)?;
+    c.check_vd_v0(vd)?;
+    c.check_overlap(vd, d_emul, d_eew, vs2, s2_emul, s2_eew)?;
+    if src == Src::V {
+        c.check_group(vs1, lmul)?;
+        c.check_overlap(vd, d_emul, d_eew, vs1, lmul, sew)?;
+    }
+    let scalar = c.scalar(p, src, sew)?;
+    for i in c.vstart..c.vl {
+        if c.active(i) {
+            let b = scalar.unwrap_or_else(|| c.v.elem(vs1, sew, i));
+            let res = op(c.v.elem(vd, d_eew, i), c.v.elem(vs2, s2_eew, i), b, sew);
+            c.v.set_elem(vd, d_eew, i, res & mask(d_eew));
+        }
+    }
+    c.v.set_vstart(0);
+    Ok(())
+}
+
+//vd.mask[i] = op(vs2[i], vs1[i]/scalar, sew)
+pub fn compare_engine<F: FnMut(u64, u64, usize) -> bool>(
+    p: &Processor,
+    src: Src,
+    mut op: F,
+) -> Result<(), Exception> {
+    let c = VCtx::new(p)?;
+    let (vd, vs2, vs1) = (vd(c.ir), vs2(c.ir), vs1(c.ir));
+    let lmul = c.vtype.lmul;
+    c.check_group(vs2, lmul)?;
+    c.check_overlap(vd, 0, 1, vs2, lmul, c.sew)?;
+    if src == Src::V {
+        c.check_group(vs1, lmul)?;
+        c.check_overlap(vd, 0, 1, vs1, lmul, c.sew)?;
+    }
+    let scalar = c.scalar(p, src, c.sew)?;
+    for i in c.vstart..c.vl {
+        if c.active(i) {
+            let b = scalar.unwrap_or_else(|| c.v.elem(vs1, c.sew, i));
+            c.v.set_mask(vd, i, op(c.v.elem(vs2, c.sew, i), b, c.sew));
+        }
+    }
+    c.v.set_vstart(0);
+    Ok(())
+}
+
+//vadc/vsbc when mask is false, vmadc/vmsbc when mask is true,
+//op(vs2[i], vs1[i]/scalar, v0.mask[i], sew) returns the sum or the carry
+pub fn carry_engine<F: Fn(u64, u64, bool, usize) -> u64>(
+    p: &Processor,
+    src: Src,
+    mask_out: bool,
+    op: F,
+) -> Result<(), Exception> {
+    let c = VCtx::new(p)?;
+    let (vd, vs2, vs1) = (vd(c.ir), vs2(c.ir), vs1(c.ir));
+    let lmul = c.vtype.lmul;
+    c.check_group(vs2, lmul)?;
+    if mask_out {
+        c.check_overlap(vd, 0, 1, vs2, lmul, c.sew)?;
+    } else {
+        c.check_group(vd, lmul)?;
+        c.check_vd_v0(vd)?;
+    }
+    if src == Src::V {
+        c.check_group(vs1, lmul)?;
+        if mask_out {
+            c.check_overlap(vd, 0, 1, vs1, lmul, c.sew)?;
+        }
+    }
+    let scalar = c.scalar(p, src, c.sew)?;
+    for i in c.vstart..c.vl {
+        let b = scalar.unwrap_or_else(|| c.v.elem(vs1, c.sew, i));
+        let carry = c.masked && c.v.mask(0, i);
+        let res = op(c.v.elem(vs2, c.sew, i), b, carry, c.sew);
+        if mask_out {
+            c.v.set_mask(vd, i, res != 0);
+        } else {
+            c.v.set_elem(vd, c.sew, i, res & mask(c.sew));
+        }
+    }
+    c.v.set_vstart(0);
+    Ok(())
+}
+
+//vmerge and vmv.v
+pub fn merge_engine(p: &Processor, src: Src) -> Result<(), Exception> {
+    let c = VCtx::new(p)?;
+    let (vd, vs2, vs1) = (vd(c.ir), vs2(c.ir), vs1(c.ir));
+    let lmul = c.vtype.lmul;
+    c.check_group(vd, lmul)?;
+    c.check_vd_v0(vd)?;
+    if c.masked {
+        c.check_group(vs2, lmul)?;
+    }
+    if src == Src::V {
+        c.check_group(vs1, lmul)?;
+    }
+    let scalar = c.scalar(p, src, c.sew)?;
+    for i in c.vstart..c.vl {
+        let value = if c.active(i) {
+            scalar.unwrap_or_else(|| c.v.elem(vs1, c.sew, i))
+        } else {
+            c.v.elem(vs2, c.sew, i)
+        };
+        c.v.set_elem(vd, c.sew, i, value);
+    }
+    c.v.set_vstart(0);
+    Ok(())
+}
+
+//vd[0] = op(...op(vs1[0], vs2[0])..., vs2[vl-1]) over active elements
+pub fn reduce_engine<F: FnMut(u64, u64, usize) -> u64>(
+    p: &Processor,
+    widen: bool,
+    mut op: F,
+) -> Result<(), Exception> {
+    let c = VCtx::new(p)?;
+    let (vd, vs2, vs1) = (vd(c.ir), vs2(c.ir), vs1(c.ir));
+    let d_eew = if widen { c.sew * 2 } else { c.sew };
+    c.check_eew(d_eew)?;
+    c.check_group(vs2, c.vtype.lmul)?;
+    c.check_vstart_zero()?;
+    if c.vl == 0 {
+        return Ok(());
+    }
+    let mut acc = c.v.elem(vs1, d_eew, 0);
+    for i in 0..c.vl {
+        if c.active(i) {
+            acc = op(acc, c.v.elem(vs2, c.sew, i), c.sew) & mask(d_eew);
+        }
+    }
+    c.v.set_elem(vd, d_eew, 0, acc);
+    Ok(())
+}
+
+//rounding increment of shifting value right by d bits
+pub fn round_increment(value: u128, d: usize, vxrm: u8) -> u128 {
+    if d == 0 {
+        return 0;
+    }
+    let bit = |n: usize| (value >> n) & 1;
+    let lower = |n: usize| n != 0 && value & ((1u128 << n) - 1) != 0;
+    match vxrm {
+        //rnu
+        0 => bit(d - 1),
+        //rne
+        1 => bit(d - 1) & (lower(d - 1) as u128 | bit(d)),
+        //rdn
+        2 => 0,
+        //rod
+        _ => (bit(d) == 0 && lower(d)) as u128,
+    }
+}
+
+pub fn roundoff_unsigned(value: u128, d: usize, vxrm: u8) -> u128 {
+    (value >> d) + round_increment(value, d, vxrm)
+}
+
+pub fn roundoff_signed(value: i128, d: usize, vxrm: u8) -> i128 {
+    (value >> d) + round_increment(value as u128, d, vxrm) as i128
+}
@@ -1,7 +1,9 @@
 use crate::devices::bus::Bus;
 use crate::prelude::*;
+use crate::processor::hpm::HpmEvent;
 use crate::processor::mmu::Mmu;
 use crate::processor::trap::Exception;
+use crate::processor::trigger::TriggerOp;
 use crate::processor::ProcessorState;
 use std::cell::RefCell;
 use std::mem::MaybeUninit;
@@ -9,6 +11,7 @@ use std::rc::Rc;
 struct ICacheEntry {
     accessed: bool,
     tag: u64,
+    ctx: u64,
     insn: Option<(InsnT, &'static Instruction)>,
 }
 
@@ -29,6 +32,7 @@ impl ICacheBasket {
                         .write(ICacheEntry {
                             accessed: false,
                             tag: 0,
+                            ctx: 0,
                             insn: None,
                         });
                 }
@@ -37,12 +41,12 @@ impl ICacheBasket {
         }
     }
 
-    fn get_insn(&mut self, tag: u64) -> Option<&(InsnT, &'static Instruction)> {
+    fn get_insn(&mut self, tag: u64, ctx: u64) -> Option<&(InsnT, &'static Instruction)> {
         let mut ptr = self.ptr;
         let tail = self.tail();
         while ptr != tail {
             let e = unsafe { self.entries.get_unchecked_mut(ptr as usize) };
-            if e.tag == tag {
+            if e.tag == tag && e.ctx == ctx {
                 if e.insn.is_some() {
                     e.accessed = true;
                     self.ptr = ptr;
@@ -84,7 +88,7 @@ impl ICacheBasket {
         }
     }
 
-    fn set_entry(&mut self, tag: u64, ir: InsnT, insn: &'static Instruction) {
+    fn set_entry(&mut self, tag: u64, ctx: u64, ir: InsnT, insn: &'static Instruction) {
         let mut ptr = self.tail();
         let tail = self.ptr;
         while ptr != tail {
@@ -97,6 +101,7 @@ impl ICacheBasket {
         let e = unsafe { self.entries.get_unchecked_mut(ptr as usize) };
         e.accessed = true;
         e.tag = tag;
+        e.ctx = ctx;
         e.insn = Some((ir, insn));
         self.ptr = ptr;
     }
@@ -131,20 +136,20 @@ impl ICache {
         cache
     }
     #[cfg_attr(feature = "no-inline", inline(never))]
-    fn get_insn(&mut self, addr: u64) -> Option<&(InsnT, &'static Instruction)> {
+    fn get_insn(&mut self, addr: u64, ctx: u64) -> Option<&(InsnT, &'static Instruction)> {
         unsafe {
             self.baskets
                 .get_unchecked_mut(((addr >> 1) as usize) & (self.size - 1))
         }
-        .get_insn(addr >> 1)
+        .get_insn(addr >> 1, ctx)
     }
     #[cfg_attr(feature = "no-inline", inline(never))]
-    fn set_entry(&mut self, addr: u64, ir: InsnT, insn: &'static Instruction) {
+    fn set_entry(&mut self, addr: u64, ctx: u64, ir: InsnT, insn: &'static Instruction) {
         unsafe {
             self.baskets
                 .get_unchecked_mut(((addr >> 1) as usize) & (self.size - 1))
         }
-        .set_entry(addr >> 1, ir, insn)
+        .set_entry(addr >> 1, ctx, ir, insn)
     }
 
     fn invalid_all(&mut self) {
@@ -191,16 +196,54 @@ impl Fetcher {
         self.icache.borrow_mut().invalid_by_vpn(vpn)
     }
 
+    //entries of zcmt jump table are fetched with execute permission
+    pub fn fetch_table_entry(
+        &self,
+        state: &ProcessorState,
+        addr: &u64,
+        mmu: &Mmu,
+    ) -> Result<u64, Exception> {
+        if state.config().xlen == XLen::X64 {
+            let pa = mmu.fetch_translate(state, addr, 8)?;
+            let mut data = 0;
+            match self.bus.read_u64(&pa, &mut data) {
+                Ok(_) => Ok(data),
+                Err(_) => Err(Exception::FetchAccess(*addr)),
+            }
+        } else {
+            let pa = mmu.fetch_translate(state, addr, 4)?;
+            let mut data = 0;
+            match self.bus.read_u32(&pa, &mut data) {
+                Ok(_) => Ok(data as u64),
+                Err(_) => Err(Exception::FetchAccess(*addr)),
+            }
+        }
+    }
+
     pub fn fetch(
         &self,
         state: &ProcessorState,
         mmu: &Mmu,
+    ) -> Result<(InsnT, &'static Instruction), Exception> {
+        let (ir, insn) = self.fetch_insn(state, mmu)?;
+        let len = if ir & 0x3 == 0x3 { 4 } else { 2 };
+        state.check_triggers(TriggerOp::Execute, *state.pc(), len, ir as RegT)?;
+        Ok((ir, insn))
+    }
+
+    fn fetch_insn(
+        &self,
+        state: &ProcessorState,
+        mmu: &Mmu,
     ) -> Result<(InsnT, &'static Instruction), Exception> {
         let mut icache = self.icache.borrow_mut();
         let pc = state.pc();
-        if let Some(res) = icache.get_insn(*pc) {
+        //entries are kept across traps, tagged with privilege and address space
+        let ctx = mmu.fetch_context(state);
+        if let Some(res) = icache.get_insn(*pc, ctx) {
             return Ok(*res);
         }
+        state.hpm_event(HpmEvent::ICacheMiss);
         if pc.trailing_zeros() == 1 {
             let pa = mmu.fetch_translate(state, pc, 2)?;
             let mut data_low = 0;
@@ -208,7 +251,7 @@ impl Fetcher {
             if data_low & 0x3 != 0x3 {
                 let data = data_low as u16 as InsnT;
                 let insn = GDECODER.decode(&data)?;
-                icache.set_entry(*pc, data, insn);
+                icache.set_entry(*pc, ctx, data, insn);
                 Ok((data, insn))
             } else {
                 let pa_high = if (*pc & 0xfff) == 0xffe {
@@ -220,7 +263,7 @@ impl Fetcher {
                 self.fetch_u16_slow(&pa_high, pc, &mut data_high)?;
                 let data = data_low as u16 as InsnT | ((data_high as u16 as InsnT) << 16);
                 let insn = GDECODER.decode(&data)?;
-                icache.set_entry(*pc, data, insn);
+                icache.set_entry(*pc, ctx, data, insn);
                 Ok((data, insn))
             }
         } else {
@@ -230,11 +273,11 @@ impl Fetcher {
             if data & 0x3 != 0x3 {
                 let data_low = data as u16 as InsnT;
                 let insn = GDECODER.decode(&data_low)?;
-                icache.set_entry(*pc, data_low, insn);
+                icache.set_entry(*pc, ctx, data_low, insn);
                 Ok((data_low, insn))
             } else {
                 let insn = GDECODER.decode(&data)?;
-                icache.set_entry(*pc, data, insn);
+                icache.set_entry(*pc, ctx, data, insn);
                 Ok((data, insn))
             }
         }
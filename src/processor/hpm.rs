@@ -0,0 +1,544 @@
+use crate::prelude::*;
+use crate::processor::privilege::Privilege;
+use crate::snapshot::{SnapshotReader, SnapshotWriter};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use std::cell::{Cell, RefCell};
+use std::convert::TryFrom;
+use std::io;
+use std::io::{Read, Write};
+
+const MCOUNTINHIBIT: InsnT = 0x320;
+const MHPMEVENT3: InsnT = 0x323;
+const MHPMEVENT31: InsnT = 0x33F;
+const MHPMEVENT3H: InsnT = 0x723;
+const MHPMEVENT31H: InsnT = 0x73F;
+const MCYCLE: InsnT = 0xB00;
+const MHPMCOUNTER31: InsnT = 0xB1F;
+const MCYCLEH: InsnT = 0xB80;
+const MHPMCOUNTER31H: InsnT = 0xB9F;
+const CYCLE: InsnT = 0xC00;
+const HPMCOUNTER31: InsnT = 0xC1F;
+const CYCLEH: InsnT = 0xC80;
+const HPMCOUNTER31H: InsnT = 0xC9F;
+const SCOUNTOVF: InsnT = 0xDA0;
+
+const CY: usize = 0;
+const TM: usize = 1;
+const IR: usize = 2;
+//mhpmcounter3 is the first programmable counter
+const HPM_BASE: usize = 3;
+pub const MAX_HPM_COUNTERS: usize = 29;
+
+//mhpmevent of sscofpmf, mhpmeventh holds the upper half in rv32
+const EVENT_OF: u64 = 1 << 63;
+const EVENT_MINH: u64 = 1 << 62;
+const EVENT_SINH: u64 = 1 << 61;
+const EVENT_UINH: u64 = 1 << 60;
+const EVENT_VSINH: u64 = 1 << 59;
+const EVENT_VUINH: u64 = 1 << 58;
+const EVENT_FILTERS: u64 =
+    EVENT_OF | EVENT_MINH | EVENT_SINH | EVENT_UINH | EVENT_VSINH | EVENT_VUINH;
+const EVENT_SEL: u64 = 0xff;
+
+//event selectors of mhpmevent
+#[derive(IntoPrimitive, TryFromPrimitive, Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(u8)]
+pub enum HpmEvent {
+    LoadRetired = 1,
+    StoreRetired = 2,
+    AmoRetired = 3,
+    BranchRetired = 4,
+    BranchTaken = 5,
+    JumpRetired = 6,
+    FloatRetired = 7,
+    VectorRetired = 8,
+    CsrRetired = 9,
+    Exception = 10,
+    Interrupt = 11,
+    ITlbMiss = 12,
+    DTlbLoadMiss = 13,
+    DTlbStoreMiss = 14,
+    ICacheMiss = 15,
+}
+
+//sbi pmu generic events counted by hpm events, reported to sbi in the device tree
+pub const HPM_SBI_EVENTS: [(u32, HpmEvent); 5] = [
+    //HW_BRANCH_INSTRUCTIONS
+    (0x5, HpmEvent::BranchRetired),
+    //L1I_READ_MISS
+    (0x10009, HpmEvent::ICacheMiss),
+    //DTLB_READ_MISS
+    (0x10019, HpmEvent::DTlbLoadMiss),
+    //DTLB_WRITE_MISS
+    (0x1001b, HpmEvent::DTlbStoreMiss),
+    //ITLB_READ_MISS
+    (0x10021, HpmEvent::ITlbMiss),
+];
+
+impl HpmEvent {
+    //class of a retired instruction by its major opcode
+    fn of_insn(ir: InsnT, xlen: XLen) -> Option<HpmEvent> {
+        let funct3 = (ir >> 13) & 0x7;
+        match ir & 0x3 {
+            0 => match funct3 {
+                1..=3 => Some(HpmEvent::LoadRetired),
+                //zcb c.lbu, c.lhu and c.lh, c.sb and c.sh
+                4 => match (ir >> 10) & 0x7 {
+                    0 | 1 => Some(HpmEvent::LoadRetired),
+                    2 | 3 => Some(HpmEvent::StoreRetired),
+                    _ => None,
+                },
+                5..=7 => Some(HpmEvent::StoreRetired),
+                _ => None,
+            },
+            1 => match funct3 {
+                1 if xlen == XLen::X32 => Some(HpmEvent::JumpRetired),
+                5 => Some(HpmEvent::JumpRetired),
+                6 | 7 => Some(HpmEvent::BranchRetired),
+                _ => None,
+            },
+            2 => match funct3 {
+                1..=3 => Some(HpmEvent::LoadRetired),
+                //c.jr and c.jalr
+                4 if (ir >> 2) & 0x1f == 0 && (ir >> 7) & 0x1f != 0 => Some(HpmEvent::JumpRetired),
+                5..=7 => Some(HpmEvent::StoreRetired),
+                _ => None,
+            },
+            _ => match (ir >> 2) & 0x1f {
+                0x00 | 0x01 => Some(HpmEvent::LoadRetired),
+                0x08 | 0x09 => Some(HpmEvent::StoreRetired),
+                0x0b => Some(HpmEvent::AmoRetired),
+                0x10..=0x14 => Some(HpmEvent::FloatRetired),
+                0x15 => Some(HpmEvent::VectorRetired),
+                0x18 => Some(HpmEvent::BranchRetired),
+                0x19 | 0x1b => Some(HpmEvent::JumpRetired),
+                0x1c if (ir >> 12) & 0x3 != 0 => Some(HpmEvent::CsrRetired),
+                _ => None,
+            },
+        }
+    }
+}
+
+//mcycle, minstret, mhpmcounters and sscofpmf overflow
+pub struct Hpm {
+    xlen: XLen,
+    num: usize,
+    zihpm: bool,
+    sscofpmf: bool,
+    counters: RefCell<[u64; 32]>,
+    events: RefCell<[u64; 32]>,
+    inhibit: Cell<u32>,
+    //events selected by counters not inhibited
+    events_en: Cell<u32>,
+}
+
+impl Hpm {
+    pub fn new(xlen: XLen, num: usize, zihpm: bool, sscofpmf: bool) -> Hpm {
+        Hpm {
+            xlen,
+            num: if zihpm { num } else { 0 },
+            zihpm,
+            sscofpmf,
+            counters: RefCell::new([0; 32]),
+            events: RefCell::new([0; 32]),
+            inhibit: Cell::new(0),
+            events_en: Cell::new(0),
+        }
+    }
+
+    pub fn num(&self) -> usize {
+        self.num
+    }
+
+    fn implemented(&self) -> u32 {
+        ((1u64 << (HPM_BASE + self.num)) as u32).wrapping_sub(1 << HPM_BASE)
+    }
+
+    fn update_events_en(&self, events: &[u64; 32]) {
+        let active = self.implemented() & !self.inhibit.get();
+        let en = (HPM_BASE..HPM_BASE + self.num)
+            .filter(|i| active & (1 << *i) != 0)
+            .fold(0, |acc, i| acc | (1u32 << (events[i] & EVENT_SEL)) & !1);
+        self.events_en.set(en)
+    }
+
+    //WARL, unsupported events select nothing and filters are only implemented by sscofpmf
+    fn legalize(&self, value: u64) -> u64 {
+        let sel = if HpmEvent::try_from((value & EVENT_SEL) as u8).is_ok() {
+            value & EVENT_SEL
+        } else {
+            0
+        };
+        if self.sscofpmf {
+            value & EVENT_FILTERS | sel
+        } else {
+            sel
+        }
+    }
+
+    fn inhibited(&self, event: u64, privilege: &Privilege, virt: bool) -> bool {
+        let filter = match (privilege, virt) {
+            (Privilege::M, _) => EVENT_MINH,
+            (Privilege::S, false) => EVENT_SINH,
+            (Privilege::U, false) => EVENT_UINH,
+            (Privilege::S, true) => EVENT_VSINH,
+            (Privilege::U, true) => EVENT_VUINH,
+        };
+        event & filter != 0
+    }
+
+    fn read_counter(&self, idx: usize, high: bool) -> RegT {
+        let value = self.counters.borrow()[idx];
+        if high {
+            value >> 32
+        } else {
+            value & self.xlen.mask()
+        }
+    }
+
+    //returns true if an overflow interrupt is raised
+    pub fn count(&self, event: HpmEvent, privilege: &Privilege, virt: bool) -> bool {
+        let sel: u8 = event.into();
+        if self.events_en.get() & (1 << sel) == 0 {
+            return false;
+        }
+        let mut counters = self.counters.borrow_mut();
+        let mut events = self.events.borrow_mut();
+        let active = self.implemented() & !self.inhibit.get();
+        let mut ovf = false;
+        for i in HPM_BASE..HPM_BASE + self.num {
+            if active & (1 << i) == 0
+                || events[i] & EVENT_SEL != sel as u64
+                || self.inhibited(events[i], privilege, virt)
+            {
+                continue;
+            }
+            counters[i] = counters[i].wrapping_add(1);
+            if counters[i] == 0 && self.sscofpmf && events[i] & EVENT_OF == 0 {
+                events[i] |= EVENT_OF;
+                ovf = true;
+            }
+        }
+        ovf
+    }
+
+    pub fn tick(&self) {
+        if self.inhibit.get() & (1 << CY) == 0 {
+            let mut counters = self.counters.borrow_mut();
+            counters[CY] = counters[CY].wrapping_add(1);
+        }
+    }
+
+    pub fn retire(&self, ir: InsnT, taken: bool, privilege: &Privilege, virt: bool) -> bool {
+        if self.inhibit.get() & (1 << IR) == 0 {
+            let mut counters = self.counters.borrow_mut();
+            counters[IR] = counters[IR].wrapping_add(1);
+        }
+        if self.events_en.get() == 0 {
+            return false;
+        }
+        match HpmEvent::of_insn(ir, self.xlen) {
+            Some(HpmEvent::BranchRetired) => {
+                let ovf = self.count(HpmEvent::BranchRetired, privilege, virt);
+                if taken {
+                    self.count(HpmEvent::BranchTaken, privilege, virt) || ovf
+                } else {
+                    ovf
+                }
+            }
+            Some(event) => self.count(event, privilege, virt),
+            None => false,
+        }
+    }
+
+    //of bits of mhpmevents, masked by counteren below M mode
+    pub fn overflows(&self) -> RegT {
+        let events = self.events.borrow();
+        (HPM_BASE..HPM_BASE + self.num)
+            .filter(|i| events[*i] & EVENT_OF != 0)
+            .fold(0, |acc, i| acc | 1 << i)
+    }
+
+    //counters below M mode are accessible if enabled by counteren
+    pub fn csr_read(&self, addr: InsnT, counter_en: impl Fn(usize) -> bool) -> Option<RegT> {
+        let idx = (addr & 0x1f) as usize;
+        let high = addr & 0x80 != 0;
+        match addr {
+            MCOUNTINHIBIT => Some(self.inhibit.get() as RegT),
+            MHPMEVENT3..=MHPMEVENT31 => Some(self.events.borrow()[idx] & self.xlen.mask()),
+            MHPMEVENT3H..=MHPMEVENT31H => {
+                if self.xlen == XLen::X32 && self.sscofpmf {
+                    Some(self.events.borrow()[idx] >> 32)
+                } else {
+                    None
+                }
+            }
+            MCYCLE..=MHPMCOUNTER31 | MCYCLEH..=MHPMCOUNTER31H => {
+                if idx == TM || high && self.xlen != XLen::X32 {
+                    None
+                } else {
+                    Some(self.read_counter(idx, high))
+                }
+            }
+            CYCLE..=HPMCOUNTER31 | CYCLEH..=HPMCOUNTER31H => {
+                if idx == TM
+                    || high && self.xlen != XLen::X32
+                    || idx >= HPM_BASE && !self.zihpm
+                    || !counter_en(idx)
+                {
+                    None
+                } else {
+                    Some(self.read_counter(idx, high))
+                }
+            }
+            SCOUNTOVF => {
+                if self.sscofpmf {
+                    Some(
+                        (0..32)
+                            .filter(|i| counter_en(*i))
+                            .fold(0, |acc, i| acc | 1 << i)
+                            & self.overflows(),
+                    )
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    pub fn csr_write(&self, addr: InsnT, value: RegT) -> Option<()> {
+        let idx = (addr & 0x1f) as usize;
+        let implemented = idx < HPM_BASE || self.implemented() & (1 << idx) != 0;
+        match addr {
+            MCOUNTINHIBIT => {
+                self.inhibit
+                    .set(value as u32 & (self.implemented() | 1 << CY | 1 << IR));
+                self.update_events_en(&self.events.borrow());
+            }
+            MHPMEVENT3..=MHPMEVENT31 => {
+                let mut events = self.events.borrow_mut();
+                if implemented {
+                    events[idx] = if self.xlen == XLen::X64 {
+                        self.legalize(value)
+                    } else {
+                        self.legalize(events[idx] & !0xffff_ffff | value)
+                    };
+                    self.update_events_en(&events);
+                }
+            }
+            MHPMEVENT3H..=MHPMEVENT31H => {
+                if self.xlen != XLen::X32 || !self.sscofpmf {
+                    return None;
+                }
+                let mut events = self.events.borrow_mut();
+                if implemented {
+                    events[idx] = self.legalize(events[idx] & 0xffff_ffff | value << 32);
+                    self.update_events_en(&events);
+                }
+            }
+            MCYCLE..=MHPMCOUNTER31 | MCYCLEH..=MHPMCOUNTER31H => {
+                let high = addr & 0x80 != 0;
+                if idx == TM || high && self.xlen != XLen::X32 {
+                    return None;
+                }
+                let mut counters = self.counters.borrow_mut();
+                if implemented {
+                    counters[idx] = if high {
+                        counters[idx] & 0xffff_ffff | value << 32
+                    } else if self.xlen == XLen::X32 {
+                        counters[idx] & !0xffff_ffff | value
+                    } else {
+                        value
+                    };
+                }
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+
+    pub fn save_snapshot<W: Write>(&self, w: &mut SnapshotWriter<W>) -> io::Result<()> {
+        w.write_u64(self.inhibit.get() as u64)?;
+        for (counter, event) in self
+            .counters
+            .borrow()
+            .iter()
+            .zip(self.events.borrow().iter())
+        {
+            w.write_u64(*counter)?;
+            w.write_u64(*event)?;
+        }
+        Ok(())
+    }
+
+    pub fn restore_snapshot<R: Read>(&self, r: &mut SnapshotReader<R>) -> io::Result<()> {
+        self.inhibit.set(r.read_u64()? as u32);
+        let mut counters = self.counters.borrow_mut();
+        let mut events = self.events.borrow_mut();
+        for i in 0..32 {
+            counters[i] = r.read_u64()?;
+            events[i] = r.read_u64()?;
+        }
+        self.update_events_en(&events);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::devices::bus::Bus;
+    use crate::processor::{ProcessorCfg, SatpMode};
+    use crate::system::System;
+    use terminus_spaceport::memory::region::GHEAP;
+
+    const LW: InsnT = 0x0005a503; //lw a0, 0(a1)
+    const LOAD: RegT = HpmEvent::LoadRetired as RegT;
+
+    fn read(hpm: &Hpm, addr: InsnT) -> RegT {
+        hpm.csr_read(addr, |_| true).unwrap()
+    }
+
+    fn write(hpm: &Hpm, addr: InsnT, value: RegT) {
+        hpm.csr_write(addr, value).unwrap()
+    }
+
+    #[test]
+    fn hpm_inhibit_test() {
+        let hpm = Hpm::new(XLen::X64, 2, true, true);
+        write(&hpm, MHPMEVENT3, LOAD);
+        write(&hpm, MHPMEVENT3 + 1, LOAD);
+        hpm.tick();
+        hpm.retire(LW, false, &Privilege::M, false);
+        assert_eq!(read(&hpm, MCYCLE), 1);
+        assert_eq!(read(&hpm, MCYCLE + IR as InsnT), 1);
+        assert_eq!(read(&hpm, MCYCLE + 3), 1);
+        assert_eq!(read(&hpm, MCYCLE + 4), 1);
+
+        //tm and unimplemented counters can not be inhibited
+        write(&hpm, MCOUNTINHIBIT, 1 << CY | 1 << TM | 1 << 4 | 1 << 5);
+        assert_eq!(read(&hpm, MCOUNTINHIBIT), 1 << CY | 1 << 4);
+        hpm.tick();
+        hpm.retire(LW, false, &Privilege::M, false);
+        assert_eq!(read(&hpm, MCYCLE), 1);
+        assert_eq!(read(&hpm, MCYCLE + IR as InsnT), 2);
+        assert_eq!(read(&hpm, MCYCLE + 3), 2);
+        assert_eq!(read(&hpm, MCYCLE + 4), 1);
+
+        //all events disabled
+        write(&hpm, MCOUNTINHIBIT, 1 << IR | 1 << 3 | 1 << 4);
+        assert_eq!(hpm.events_en.get(), 0);
+        hpm.tick();
+        hpm.retire(LW, false, &Privilege::M, false);
+        assert_eq!(read(&hpm, MCYCLE), 2);
+        assert_eq!(read(&hpm, MCYCLE + IR as InsnT), 2);
+        assert_eq!(read(&hpm, MCYCLE + 3), 2);
+        assert_eq!(read(&hpm, MCYCLE + 4), 1);
+
+        write(&hpm, MCOUNTINHIBIT, 0);
+        hpm.retire(LW, false, &Privilege::M, false);
+        assert_eq!(read(&hpm, MCYCLE + 3), 3);
+        assert_eq!(read(&hpm, MCYCLE + 4), 2);
+    }
+
+    #[test]
+    fn hpm_filter_test() {
+        let hpm = Hpm::new(XLen::X64, 2, true, true);
+        //counter 3 only counts in U, VS and VU mode, counter 4 only counts in M and S mode
+        write(&hpm, MHPMEVENT3, EVENT_MINH | EVENT_SINH | LOAD);
+        let filters = EVENT_UINH | EVENT_VSINH | EVENT_VUINH;
+        write(&hpm, MHPMEVENT3 + 1, filters | LOAD);
+        let count = |privilege: Privilege, virt: bool| {
+            hpm.retire(LW, false, &privilege, virt);
+            (read(&hpm, MCYCLE + 3), read(&hpm, MCYCLE + 4))
+        };
+        assert_eq!(count(Privilege::M, false), (0, 1));
+        assert_eq!(count(Privilege::S, false), (0, 2));
+        assert_eq!(count(Privilege::U, false), (1, 2));
+        assert_eq!(count(Privilege::S, true), (2, 2));
+        assert_eq!(count(Privilege::U, true), (3, 2));
+
+        //filters and unsupported events are WARL zero without sscofpmf
+        let hpm = Hpm::new(XLen::X64, 1, true, false);
+        write(&hpm, MHPMEVENT3, EVENT_FILTERS | LOAD);
+        assert_eq!(read(&hpm, MHPMEVENT3), LOAD);
+        hpm.retire(LW, false, &Privilege::M, false);
+        assert_eq!(read(&hpm, MCYCLE + 3), 1);
+        write(&hpm, MHPMEVENT3, 0xfe);
+        assert_eq!(read(&hpm, MHPMEVENT3), 0);
+    }
+
+    #[test]
+    fn hpm_overflow_test() {
+        let hpm = Hpm::new(XLen::X64, 1, true, true);
+        write(&hpm, MHPMEVENT3, LOAD);
+        write(&hpm, MCYCLE + 3, u64::MAX - 1);
+        assert!(!hpm.retire(LW, false, &Privilege::M, false));
+        assert_eq!(read(&hpm, MCYCLE + 3), u64::MAX);
+        //of is set and an interrupt is raised on wrap
+        assert!(hpm.retire(LW, false, &Privilege::M, false));
+        assert_eq!(read(&hpm, MCYCLE + 3), 0);
+        assert_eq!(read(&hpm, MHPMEVENT3), EVENT_OF | LOAD);
+        assert_eq!(read(&hpm, SCOUNTOVF), 1 << 3);
+        assert_eq!(hpm.csr_read(SCOUNTOVF, |i| i != 3), Some(0));
+        //no more interrupts until of is cleared
+        write(&hpm, MCYCLE + 3, u64::MAX);
+        assert!(!hpm.retire(LW, false, &Privilege::M, false));
+        write(&hpm, MHPMEVENT3, LOAD);
+        assert_eq!(read(&hpm, SCOUNTOVF), 0);
+        write(&hpm, MCYCLE + 3, u64::MAX);
+        assert!(hpm.retire(LW, false, &Privilege::M, false));
+
+        //of is in mhpmeventh in rv32
+        let hpm = Hpm::new(XLen::X32, 1, true, true);
+        write(&hpm, MHPMEVENT3, LOAD);
+        write(&hpm, MCYCLE + 3, 0xffff_ffff);
+        write(&hpm, MCYCLEH + 3, 0xffff_ffff);
+        assert!(hpm.retire(LW, false, &Privilege::M, false));
+        assert_eq!(read(&hpm, MCYCLE + 3), 0);
+        assert_eq!(read(&hpm, MCYCLEH + 3), 0);
+        assert_eq!(read(&hpm, MHPMEVENT3), LOAD);
+        assert_eq!(read(&hpm, MHPMEVENT3H), 0x8000_0000);
+
+        //no of without sscofpmf
+        let hpm = Hpm::new(XLen::X64, 1, true, false);
+        write(&hpm, MHPMEVENT3, LOAD);
+        write(&hpm, MCYCLE + 3, u64::MAX);
+        assert!(!hpm.retire(LW, false, &Privilege::M, false));
+        assert_eq!(read(&hpm, MCYCLE + 3), 0);
+        assert_eq!(hpm.csr_read(SCOUNTOVF, |_| true), None);
+    }
+
+    #[test]
+    fn hpm_lcofi_test() {
+        let mut sys = System::new("test", "top_tests/elf/rv64ui-p-add", 100, 1);
+        sys.new_processor(ProcessorCfg {
+            xlen: XLen::X64,
+            enable_dirty: true,
+            isa: "rv64imacs_sscofpmf".parse().unwrap(),
+            freq: 1000000000,
+            triggers: 0,
+            satp_modes: vec![SatpMode::Sv39].into_boxed_slice(),
+            vlen: 128,
+            elen: 64,
+            cache_block_size: 64,
+            entropy_seed: 0,
+            hpm_counters: 1,
+        });
+        sys.register_memory("main_memory", 0x8000_0000, &GHEAP.alloc(0x2000, 1).unwrap())
+            .unwrap();
+        sys.bus().write_u32(&0x8000_0000, &LW).unwrap();
+        sys.bus().write_u32(&0x8000_0004, &LW).unwrap();
+        sys.reset(vec![0x8000_0000]).unwrap();
+        let p = sys.processor(0).unwrap();
+        p.state_mut().set_xreg(11, 0x8000_0000);
+        write(&p.state().hpm, MHPMEVENT3, LOAD);
+        write(&p.state().hpm, MCYCLE + 3, u64::MAX - 1);
+        p.step(1);
+        assert_eq!(p.state().priv_m().mip().lcofip(), 0);
+        p.step(1);
+        assert_eq!(p.state().priv_m().mip().lcofip(), 1);
+        assert_eq!(read(&p.state().hpm, MHPMEVENT3), EVENT_OF | LOAD);
+    }
+}
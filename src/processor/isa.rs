@@ -0,0 +1,496 @@
+use crate::prelude::*;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+//canonical order of single-letter extensions, 's' and 'u' select supported privilege modes
+const LETTERS: &str = "imafdqlcbkjtpvhsu";
+const SUPPORTED_LETTERS: &str = "imafdqcbvhsu";
+
+macro_rules! isa_extensions {
+    ($($ext:ident: $name:literal => [$($dep:literal),*]),+ $(,)?) => {
+        //supported multi-letter extensions
+        #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+        pub enum IsaExt {
+            $($ext,)+
+        }
+
+        impl IsaExt {
+            pub const fn name(&self) -> &'static str {
+                match self {
+                    $(IsaExt::$ext => $name,)+
+                }
+            }
+
+            const fn deps(&self) -> &'static [&'static str] {
+                match self {
+                    $(IsaExt::$ext => &[$($dep),*],)+
+                }
+            }
+
+            fn from_name(name: &str) -> Option<IsaExt> {
+                match name {
+                    $($name => Some(IsaExt::$ext),)+
+                    _ => None,
+                }
+            }
+
+            const ALL: &'static [IsaExt] = &[$(IsaExt::$ext,)+];
+        }
+    };
+}
+
+isa_extensions!(
+    Zicbom: "zicbom" => [],
+    Zicbop: "zicbop" => [],
+    Zicboz: "zicboz" => [],
+    Zicsr: "zicsr" => [],
+    Zifencei: "zifencei" => [],
+    Zihpm: "zihpm" => ["zicsr"],
+    Zabha: "zabha" => ["a"],
+    Zacas: "zacas" => ["a"],
+    Zawrs: "zawrs" => ["a"],
+    Zfa: "zfa" => ["f"],
+    Zfbfmin: "zfbfmin" => ["f"],
+    Zfh: "zfh" => ["f"],
+    Zfhmin: "zfhmin" => ["f"],
+    Zca: "zca" => [],
+    Zcb: "zcb" => ["zca"],
+    Zcd: "zcd" => ["zca", "d"],
+    Zcf: "zcf" => ["zca", "f"],
+    Zcmp: "zcmp" => ["zca"],
+    Zcmt: "zcmt" => ["zca", "zicsr"],
+    Zba: "zba" => [],
+    Zbb: "zbb" => [],
+    Zbc: "zbc" => [],
+    Zbkb: "zbkb" => [],
+    Zbkc: "zbkc" => [],
+    Zbkx: "zbkx" => [],
+    Zbs: "zbs" => [],
+    Zk: "zk" => [],
+    Zkn: "zkn" => [],
+    Zknd: "zknd" => [],
+    Zkne: "zkne" => [],
+    Zknh: "zknh" => [],
+    Zkr: "zkr" => [],
+    Zks: "zks" => [],
+    Zksed: "zksed" => [],
+    Zksh: "zksh" => [],
+    Zkt: "zkt" => [],
+    Smaia: "smaia" => [],
+    Smstateen: "smstateen" => [],
+    Ssaia: "ssaia" => ["s", "smaia"],
+    Sscofpmf: "sscofpmf" => ["s", "zihpm"],
+    Sstc: "sstc" => ["s"],
+    Svinval: "svinval" => ["s"],
+    Svnapot: "svnapot" => ["s"],
+    Svpbmt: "svpbmt" => ["s"],
+);
+
+//extensions only supported when xlen == X64
+const RV64_ONLY: &[&str] = &["h", "svnapot", "svpbmt"];
+
+fn deps(name: &str) -> &'static [&'static str] {
+    match name {
+        "d" => &["f"],
+        "q" => &["d"],
+        "h" => &["s"],
+        _ => match IsaExt::from_name(name) {
+            Some(ext) => ext.deps(),
+            None => &[],
+        },
+    }
+}
+
+//sort key of canonical order: letters, then 'z' by category, then 's', then 'x'
+fn order(name: &str) -> Result<(usize, usize, &str), String> {
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => LETTERS
+            .find(c)
+            .map(|pos| (0, pos, name))
+            .ok_or(format!("unknown extension '{}'!", name)),
+        (Some('z'), Some(c)) => LETTERS
+            .find(c)
+            .map(|pos| (1, pos, name))
+            .ok_or(format!("unknown extension category of '{}'!", name)),
+        (Some('s'), Some(_)) => Ok((2, 0, name)),
+        (Some('x'), Some(_)) => Ok((3, 0, name)),
+        _ => Err(format!("invalid extension name '{}'!", name)),
+    }
+}
+
+//zvl<N>b, minimum vector length
+fn zvl(name: &str) -> Option<usize> {
+    name.strip_prefix("zvl")
+        .and_then(|n| n.strip_suffix('b'))
+        .and_then(|n| usize::from_str(n).ok())
+}
+
+//parsed isa string, e.g. "rv64imafdc_zicsr_zifencei_zba_zbb"
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Isa {
+    isa: String,
+    xlen: XLen,
+    //bit n is letter 'a' + n
+    letters: u32,
+    //bit n is IsaExt n
+    exts: u64,
+    zvl: Option<usize>,
+}
+
+impl Isa {
+    pub const fn xlen(&self) -> XLen {
+        self.xlen
+    }
+
+    pub const fn has(&self, ext: char) -> bool {
+        (self.letters >> (ext as u8 - b'a')) & 1 == 1
+    }
+
+    pub const fn has_ext(&self, ext: IsaExt) -> bool {
+        (self.exts >> ext as u8) & 1 == 1
+    }
+
+    pub const fn zvl(&self) -> Option<usize> {
+        self.zvl
+    }
+
+    fn has_name(&self, name: &str) -> bool {
+        if name.len() == 1 {
+            self.has(name.chars().next().unwrap())
+        } else if let Some(ext) = IsaExt::from_name(name) {
+            self.has_ext(ext)
+        } else {
+            zvl(name).is_some() && self.zvl == zvl(name)
+        }
+    }
+
+    fn set_letter(&mut self, ext: char) {
+        self.letters |= 1 << (ext as u8 - b'a')
+    }
+
+    fn set_ext(&mut self, ext: IsaExt) {
+        self.exts |= 1 << ext as u8
+    }
+
+    //"riscv,isa-base" of device tree
+    pub fn base(&self) -> String {
+        format!("rv{}i", self.xlen.len())
+    }
+
+    //"riscv,isa-extensions" of device tree, implied extensions included
+    pub fn extensions(&self) -> Vec<String> {
+        let mut exts = LETTERS
+            .chars()
+            .filter(|c| *c != 's' && *c != 'u' && self.has(*c))
+            .map(|c| c.to_string())
+            .collect::<Vec<String>>();
+        let mut multi = IsaExt::ALL
+            .iter()
+            .filter(|e| self.has_ext(**e))
+            .map(|e| e.name().to_string())
+            .collect::<Vec<String>>();
+        if let Some(vlen) = self.zvl {
+            multi.push(format!("zvl{}b", vlen));
+        }
+        multi.sort_by(|a, b| order(a).unwrap().cmp(&order(b).unwrap()));
+        exts.append(&mut multi);
+        exts
+    }
+
+    fn check(&self) -> Result<(), String> {
+        let mut names = LETTERS
+            .chars()
+            .filter(|c| self.has(*c))
+            .map(|c| c.to_string())
+            .collect::<Vec<String>>();
+        names.extend(
+            IsaExt::ALL
+                .iter()
+                .filter(|e| self.has_ext(**e))
+                .map(|e| e.name().to_string()),
+        );
+        for name in names.iter() {
+            if let Some(dep) = deps(name).iter().find(|d| !self.has_name(d)) {
+                return Err(format!("'{}' requires '{}'!", name, dep));
+            }
+            if self.xlen != XLen::X64 && RV64_ONLY.iter().any(|n| *n == name.as_str()) {
+                return Err(format!("'{}' requires xlen == X64!", name));
+            }
+        }
+        if self.xlen != XLen::X32 && self.has_ext(IsaExt::Zcf) {
+            return Err("'zcf' requires xlen == X32!".to_string());
+        }
+        //zcmp and zcmt reuse encodings of c.fsdsp
+        if let Some(ext) = [IsaExt::Zcmp, IsaExt::Zcmt]
+            .iter()
+            .find(|e| self.has_ext(**e) && self.has_ext(IsaExt::Zcd))
+        {
+            return Err(format!("'{}' is incompatible with 'zcd'!", ext.name()));
+        }
+        if let Some(vlen) = self.zvl {
+            if !self.has('v') {
+                return Err(format!("'zvl{}b' requires 'v'!", vlen));
+            }
+            if !vlen.is_power_of_two() || vlen < 32 {
+                return Err(format!("invalid 'zvl{}b'!", vlen));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Isa {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Isa, String> {
+        let isa = s.trim().to_lowercase();
+        let (xlen, rest) = if let Some(rest) = isa.strip_prefix("rv32") {
+            (XLen::X32, rest)
+        } else if let Some(rest) = isa.strip_prefix("rv64") {
+            (XLen::X64, rest)
+        } else {
+            return Err(format!(
+                "isa \"{}\" should start with \"rv32\" or \"rv64\"!",
+                s
+            ));
+        };
+        let mut result = Isa {
+            isa: isa.clone(),
+            xlen,
+            letters: 0,
+            exts: 0,
+            zvl: None,
+        };
+        let mut parts = rest.split('_');
+        let mut letters = parts.next().unwrap().chars();
+        let mut last = match letters.next() {
+            Some('i') => {
+                result.set_letter('i');
+                0
+            }
+            Some('g') => {
+                "imafd".chars().for_each(|c| result.set_letter(c));
+                LETTERS.find('d').unwrap()
+            }
+            Some('e') => return Err("'e' base is not supported!".to_string()),
+            _ => return Err(format!("isa \"{}\" should have base 'i' or 'g'!", s)),
+        };
+        for c in letters {
+            if c.is_ascii_digit() {
+                return Err(format!(
+                    "extension versions in \"{}\" are not supported!",
+                    s
+                ));
+            }
+            let pos = order(&c.to_string())?.1;
+            if pos <= last {
+                return Err(format!(
+                    "'{}' is out of canonical order \"{}\" or duplicated!",
+                    c, LETTERS
+                ));
+            }
+            if !SUPPORTED_LETTERS.contains(c) {
+                return Err(format!("unsupported extension '{}'!", c));
+            }
+            result.set_letter(c);
+            last = pos;
+        }
+        let mut last = None;
+        for name in parts {
+            let key = order(name)?;
+            if last.map_or(false, |l| l >= key) {
+                return Err(format!(
+                    "'{}' is out of canonical order \"z by category, s, x\" or duplicated!",
+                    name
+                ));
+            }
+            last = Some(key);
+            if let Some(ext) = IsaExt::from_name(name) {
+                result.set_ext(ext);
+            } else if let Some(vlen) = zvl(name) {
+                result.zvl = Some(vlen);
+            } else {
+                return Err(format!("unsupported extension '{}'!", name));
+            }
+        }
+        //csr instructions and fence.i are always implemented
+        result.set_ext(IsaExt::Zicsr);
+        result.set_ext(IsaExt::Zifencei);
+        //'h' implies 's' and 's' implies 'u', without them the hart only has M mode
+        if result.has('h') {
+            result.set_letter('s');
+        }
+        if result.has('s') {
+            result.set_letter('u');
+        }
+        //Zfh implies Zfhmin
+        if result.has_ext(IsaExt::Zfh) {
+            result.set_ext(IsaExt::Zfhmin);
+        }
+        //Zk is zkn + zkr + zkt
+        if result.has_ext(IsaExt::Zk) {
+            [IsaExt::Zkn, IsaExt::Zkr, IsaExt::Zkt]
+                .iter()
+                .for_each(|e| result.set_ext(*e));
+        }
+        //Zkn is zbkb + zbkc + zbkx + zkne + zknd + zknh
+        if result.has_ext(IsaExt::Zkn) {
+            [IsaExt::Zbkb, IsaExt::Zbkc, IsaExt::Zbkx]
+                .iter()
+                .chain([IsaExt::Zkne, IsaExt::Zknd, IsaExt::Zknh].iter())
+                .for_each(|e| result.set_ext(*e));
+        }
+        //Zks is zbkb + zbkc + zbkx + zksed + zksh
+        if result.has_ext(IsaExt::Zks) {
+            [IsaExt::Zbkb, IsaExt::Zbkc, IsaExt::Zbkx]
+                .iter()
+                .chain([IsaExt::Zksed, IsaExt::Zksh].iter())
+                .for_each(|e| result.set_ext(*e));
+        }
+        //C is zca, plus zcf with F in rv32 and zcd with D
+        let zcf = result.has('f') && xlen == XLen::X32;
+        let zcd = result.has('d');
+        if result.has('c') {
+            result.set_ext(IsaExt::Zca);
+            if zcf {
+                result.set_ext(IsaExt::Zcf);
+            }
+            if zcd {
+                result.set_ext(IsaExt::Zcd);
+            }
+        } else if result.has_ext(IsaExt::Zca)
+            && (!zcf || result.has_ext(IsaExt::Zcf))
+            && (!zcd || result.has_ext(IsaExt::Zcd))
+        {
+            result.set_letter('c');
+        }
+        //B is zba + zbb + zbs
+        if result.has('b') {
+            result.set_ext(IsaExt::Zba);
+            result.set_ext(IsaExt::Zbb);
+            result.set_ext(IsaExt::Zbs);
+        } else if result.has_ext(IsaExt::Zba)
+            && result.has_ext(IsaExt::Zbb)
+            && result.has_ext(IsaExt::Zbs)
+        {
+            result.set_letter('b');
+        }
+        result.check()?;
+        Ok(result)
+    }
+}
+
+impl Display for Isa {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.isa)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn isa_parse_test() {
+        let isa = Isa::from_str("rv64imafdc_zicsr_zifencei_zba_zbb").unwrap();
+        assert_eq!(isa.xlen(), XLen::X64);
+        assert!(isa.has('m') && isa.has('c') && !isa.has('v'));
+        assert!(isa.has_ext(IsaExt::Zba) && !isa.has_ext(IsaExt::Zbs) && !isa.has('b'));
+        assert_eq!(isa.to_string(), "rv64imafdc_zicsr_zifencei_zba_zbb");
+        assert_eq!(isa.base(), "rv64i");
+        assert_eq!(
+            isa.extensions(),
+            vec!["i", "m", "a", "f", "d", "c", "zicsr", "zifencei", "zca", "zcd", "zba", "zbb"]
+        );
+
+        let isa = Isa::from_str("RV64GCBVSU_svinval_svnapot").unwrap();
+        assert!(isa.has('d') && isa.has('b') && isa.has_ext(IsaExt::Zifencei));
+        assert!(isa.has_ext(IsaExt::Zbs) && !isa.has_ext(IsaExt::Zbc));
+        assert_eq!(isa.to_string(), "rv64gcbvsu_svinval_svnapot");
+        assert_eq!(
+            isa.extensions(),
+            vec![
+                "i", "m", "a", "f", "d", "c", "b", "v", "zicsr", "zifencei", "zca", "zcd", "zba",
+                "zbb", "zbs", "svinval", "svnapot"
+            ]
+        );
+
+        let isa = Isa::from_str("rv32imac_zba_zbb_zbs").unwrap();
+        assert_eq!(isa.xlen(), XLen::X32);
+        assert!(isa.has('b'));
+
+        let isa = Isa::from_str("rv64imafdc_zfa_zfbfmin_zfh").unwrap();
+        assert!(isa.has_ext(IsaExt::Zfhmin) && isa.has_ext(IsaExt::Zfbfmin));
+        assert_eq!(
+            isa.extensions(),
+            vec![
+                "i", "m", "a", "f", "d", "c", "zicsr", "zifencei", "zfa", "zfbfmin", "zfh",
+                "zfhmin", "zca", "zcd"
+            ]
+        );
+
+        let isa = Isa::from_str("rv64imafdcs_zicsr_zifencei_zba_zbb_sstc").unwrap();
+        assert!(isa.has('s') && isa.has('u') && isa.has_ext(IsaExt::Sstc));
+        assert_eq!(
+            isa.extensions(),
+            vec![
+                "i", "m", "a", "f", "d", "c", "zicsr", "zifencei", "zca", "zcd", "zba", "zbb",
+                "sstc"
+            ]
+        );
+
+        //privilege modes
+        let isa = Isa::from_str("rv32imc").unwrap();
+        assert!(!isa.has('s') && !isa.has('u'));
+        assert_eq!(
+            isa.extensions(),
+            vec!["i", "m", "c", "zicsr", "zifencei", "zca"]
+        );
+        let isa = Isa::from_str("rv64imacu").unwrap();
+        assert!(!isa.has('s') && isa.has('u'));
+        assert!(Isa::from_str("rv64imacs").unwrap().has('u'));
+        let isa = Isa::from_str("rv64imach").unwrap();
+        assert!(isa.has('s') && isa.has('u'));
+        assert!(Isa::from_str("rv64imachu").unwrap().has('s'));
+
+        let isa = Isa::from_str("rv32imac_zk_zks").unwrap();
+        assert!(isa.has_ext(IsaExt::Zbkx) && isa.has_ext(IsaExt::Zknd) && isa.has_ext(IsaExt::Zkr));
+        assert!(isa.has_ext(IsaExt::Zksed) && !isa.has_ext(IsaExt::Zbb));
+
+        let isa = Isa::from_str("rv32imafc_zcb_zcmp").unwrap();
+        assert!(isa.has_ext(IsaExt::Zca) && isa.has_ext(IsaExt::Zcf) && !isa.has_ext(IsaExt::Zcd));
+        let isa = Isa::from_str("rv32imafd_zicsr_zca_zcf_zcmt").unwrap();
+        assert!(!isa.has('c') && !isa.has_ext(IsaExt::Zcd));
+        assert!(Isa::from_str("rv32imaf_zca_zcf").unwrap().has('c'));
+
+        //ordering
+        assert!(Isa::from_str("rv64imfad").is_err());
+        assert!(Isa::from_str("rv64imac_zbb_zba").is_err());
+        assert!(Isa::from_str("rv64imacsu_svinval_zba").is_err());
+        assert!(Isa::from_str("rv64imac_zba_zba").is_err());
+        assert!(Isa::from_str("rv64imac_zkn_zbkb").is_err());
+        //dependencies
+        assert!(Isa::from_str("rv64imadc").is_err());
+        assert!(Isa::from_str("rv64imafqc").is_err());
+        assert!(Isa::from_str("rv64imacu_svinval").is_err());
+        assert!(Isa::from_str("rv64imac_sstc").is_err());
+        assert!(Isa::from_str("rv64imac_zfh").is_err());
+        assert!(Isa::from_str("rv64imc_zacas").is_err());
+        assert!(Isa::from_str("rv64ima_zcb").is_err());
+        assert!(Isa::from_str("rv64ima_zcmt").is_err());
+        assert!(Isa::from_str("rv64imac_zcf").is_err());
+        assert!(Isa::from_str("rv64gc_zcmp").is_err());
+        assert!(Isa::from_str("rv32g_zca_zcd_zcf_zcmp").is_err());
+        assert!(Isa::from_str("rv32imacsu_svnapot").is_err());
+        assert!(Isa::from_str("rv32imafdchsu").is_err());
+        assert!(Isa::from_str("rv64imafdc_zvl256b").is_err());
+        assert!(Isa::from_str("rv64imafdcv_zvl256b").is_ok());
+        //unsupported
+        assert!(Isa::from_str("rv64e").is_err());
+        assert!(Isa::from_str("rv64imafdqlc").is_err());
+        assert!(Isa::from_str("rv64imac_zfoo").is_err());
+        assert!(Isa::from_str("rv64i2p1").is_err());
+        assert!(Isa::from_str("imac").is_err());
+    }
+}
@@ -2,6 +2,7 @@ use crate::devices::bus::Bus;
 use crate::prelude::RegT;
 use crate::processor::mmu::{Mmu, MmuOpt};
 use crate::processor::trap::Exception;
+use crate::processor::trigger::TriggerOp;
 use crate::processor::ProcessorState;
 use std::rc::Rc;
 
@@ -23,7 +24,7 @@ impl LoadStore {
     ) -> Result<(), Exception> {
         let pa = mmu.ls_translate(state, addr, 1, MmuOpt::Load)?;
         match self.bus.read_u8(&pa, data) {
-            Ok(_) => Ok(()),
+            Ok(_) => state.check_triggers(TriggerOp::Load, *addr, 1, *data as RegT),
             Err(_) => Err(Exception::LoadAccess(*addr)),
         }
     }
@@ -40,7 +41,7 @@ impl LoadStore {
         }
         let pa = mmu.ls_translate(state, addr, 2, MmuOpt::Load)?;
         match self.bus.read_u16(&pa, data) {
-            Ok(_) => Ok(()),
+            Ok(_) => state.check_triggers(TriggerOp::Load, *addr, 2, *data as RegT),
             Err(_) => Err(Exception::LoadAccess(*addr)),
         }
     }
@@ -56,7 +57,7 @@ impl LoadStore {
         }
         let pa = mmu.ls_translate(state, addr, 4, MmuOpt::Load)?;
         match self.bus.read_u32(&pa, data) {
-            Ok(_) => Ok(()),
+            Ok(_) => state.check_triggers(TriggerOp::Load, *addr, 4, *data as RegT),
             Err(_) => Err(Exception::LoadAccess(*addr)),
         }
     }
@@ -72,7 +73,33 @@ impl LoadStore {
         }
         let pa = mmu.ls_translate(state, addr, 8, MmuOpt::Load)?;
         match self.bus.read_u64(&pa, data) {
-            Ok(_) => Ok(()),
+            Ok(_) => state.check_triggers(TriggerOp::Load, *addr, 8, *data as RegT),
+            Err(_) => Err(Exception::LoadAccess(*addr)),
+        }
+    }
+    pub fn load_quad_word(
+        &self,
+        state: &ProcessorState,
+        addr: &RegT,
+        data: &mut u128,
+        mmu: &Mmu,
+    ) -> Result<(), Exception> {
+        if addr.trailing_zeros() < 4 {
+            return Err(Exception::LoadMisaligned(*addr));
+        }
+        let pa = mmu.ls_translate(state, addr, 16, MmuOpt::Load)?;
+        //bus is at most 64 bits wide, aligned quad word never crosses a page
+        let mut low: u64 = 0;
+        let mut high: u64 = 0;
+        match self
+            .bus
+            .read_u64(&pa, &mut low)
+            .and_then(|_| self.bus.read_u64(&(pa + 8), &mut high))
+        {
+            Ok(_) => {
+                *data = (high as u128) << 64 | low as u128;
+                state.check_triggers(TriggerOp::Load, *addr, 16, low as RegT)
+            }
             Err(_) => Err(Exception::LoadAccess(*addr)),
         }
     }
@@ -83,6 +110,7 @@ impl LoadStore {
         data: &u8,
         mmu: &Mmu,
     ) -> Result<(), Exception> {
+        state.check_triggers(TriggerOp::Store, *addr, 1, *data as RegT)?;
         let pa = mmu.ls_translate(state, addr, 1, MmuOpt::Store)?;
         if let Some(lock_holder) = self.bus.lock_holder(addr, 1) {
             if lock_holder != state.hartid {
@@ -104,6 +132,7 @@ impl LoadStore {
         if addr.trailing_zeros() < 1 {
             return Err(Exception::StoreMisaligned(*addr));
         }
+        state.check_triggers(TriggerOp::Store, *addr, 2, *data as RegT)?;
         let pa = mmu.ls_translate(state, addr, 2, MmuOpt::Store)?;
         if let Some(lock_holder) = self.bus.lock_holder(addr, 2) {
             if lock_holder != state.hartid {
@@ -125,6 +154,7 @@ impl LoadStore {
         if addr.trailing_zeros() < 2 {
             return Err(Exception::StoreMisaligned(*addr));
         }
+        state.check_triggers(TriggerOp::Store, *addr, 4, *data as RegT)?;
         let pa = mmu.ls_translate(state, addr, 4, MmuOpt::Store)?;
         if let Some(lock_holder) = self.bus.lock_holder(addr, 4) {
             if lock_holder != state.hartid {
@@ -146,6 +176,7 @@ impl LoadStore {
         if addr.trailing_zeros() < 3 {
             return Err(Exception::StoreMisaligned(*addr));
         }
+        state.check_triggers(TriggerOp::Store, *addr, 8, *data as RegT)?;
         let pa = mmu.ls_translate(state, addr, 8, MmuOpt::Store)?;
         if let Some(lock_holder) = self.bus.lock_holder(addr, 8) {
             if lock_holder != state.hartid {
@@ -158,19 +189,212 @@ impl LoadStore {
         }
     }
 
-    fn amo_u32<F: Fn(u32) -> u32>(&self, addr: &u64, f: F) -> Result<u32, u64> {
+    pub fn store_quad_word(
+        &self,
+        state: &ProcessorState,
+        addr: &RegT,
+        data: &u128,
+        mmu: &Mmu,
+    ) -> Result<(), Exception> {
+        if addr.trailing_zeros() < 4 {
+            return Err(Exception::StoreMisaligned(*addr));
+        }
+        state.check_triggers(TriggerOp::Store, *addr, 16, *data as RegT)?;
+        let pa = mmu.ls_translate(state, addr, 16, MmuOpt::Store)?;
+        if let Some(lock_holder) = self.bus.lock_holder(addr, 16) {
+            if lock_holder != state.hartid {
+                self.bus.invalid_lock(addr, 16, lock_holder);
+            }
+        }
+        match self
+            .bus
+            .write_u64(&pa, &(*data as u64))
+            .and_then(|_| self.bus.write_u64(&(pa + 8), &((*data >> 64) as u64)))
+        {
+            Ok(_) => Ok(()),
+            Err(_) => Err(Exception::StoreAccess(*addr)),
+        }
+    }
+
+    //cbo.clean, cbo.flush and cbo.inval, no cache is modeled, only permission is checked
+    //read or write permission is required, but faults are reported as store faults
+    pub fn check_cache_block(
+        &self,
+        state: &ProcessorState,
+        addr: &RegT,
+        len: usize,
+        mmu: &Mmu,
+    ) -> Result<(), Exception> {
+        let base = addr & !(len as RegT - 1);
+        match mmu.ls_translate(state, &base, len, MmuOpt::Load) {
+            Ok(_) => Ok(()),
+            Err(Exception::LoadAccess(va)) => Err(Exception::StoreAccess(va)),
+            Err(Exception::LoadPageFault(va)) => Err(Exception::StorePageFault(va)),
+            Err(Exception::LoadGuestPageFault(va, gpa)) => {
+                Err(Exception::StoreGuestPageFault(va, gpa))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    //cbo.zero
+    pub fn zero_cache_block(
+        &self,
+        state: &ProcessorState,
+        addr: &RegT,
+        len: usize,
+        mmu: &Mmu,
+    ) -> Result<(), Exception> {
+        let base = addr & !(len as RegT - 1);
+        state.check_triggers(TriggerOp::Store, base, len, 0)?;
+        let pa = mmu.ls_translate(state, &base, len, MmuOpt::Store)?;
+        if let Some(lock_holder) = self.bus.lock_holder(&base, len) {
+            if lock_holder != state.hartid {
+                self.bus.invalid_lock(&base, len, lock_holder);
+            }
+        }
+        for offset in (0..len as u64).step_by(8) {
+            if self.bus.write_u64(&(pa + offset), &0).is_err() {
+                return Err(Exception::StoreAccess(base + offset));
+            }
+        }
+        Ok(())
+    }
+
+    fn amo_u8<F: Fn(u8) -> u8>(
+        &self,
+        state: &ProcessorState,
+        addr: &RegT,
+        pa: &u64,
+        f: F,
+    ) -> Result<u8, Exception> {
+        let mut read: u8 = 0;
+        self.bus
+            .read_u8(pa, &mut read)
+            .map_err(|_| Exception::StoreAccess(*addr))?;
+        state.check_triggers(TriggerOp::Load, *addr, 1, read as RegT)?;
+        let write = f(read);
+        state.check_triggers(TriggerOp::Store, *addr, 1, write as RegT)?;
+        self.bus
+            .write_u8(pa, &write)
+            .map_err(|_| Exception::StoreAccess(*addr))?;
+        Ok(read)
+    }
+    fn amo_u16<F: Fn(u16) -> u16>(
+        &self,
+        state: &ProcessorState,
+        addr: &RegT,
+        pa: &u64,
+        f: F,
+    ) -> Result<u16, Exception> {
+        let mut read: u16 = 0;
+        self.bus
+            .read_u16(pa, &mut read)
+            .map_err(|_| Exception::StoreAccess(*addr))?;
+        state.check_triggers(TriggerOp::Load, *addr, 2, read as RegT)?;
+        let write = f(read);
+        state.check_triggers(TriggerOp::Store, *addr, 2, write as RegT)?;
+        self.bus
+            .write_u16(pa, &write)
+            .map_err(|_| Exception::StoreAccess(*addr))?;
+        Ok(read)
+    }
+    fn amo_u32<F: Fn(u32) -> u32>(
+        &self,
+        state: &ProcessorState,
+        addr: &RegT,
+        pa: &u64,
+        f: F,
+    ) -> Result<u32, Exception> {
         let mut read: u32 = 0;
-        self.bus.read_u32(addr, &mut read)?;
-        self.bus.write_u32(addr, &f(read))?;
+        self.bus
+            .read_u32(pa, &mut read)
+            .map_err(|_| Exception::StoreAccess(*addr))?;
+        state.check_triggers(TriggerOp::Load, *addr, 4, read as RegT)?;
+        let write = f(read);
+        state.check_triggers(TriggerOp::Store, *addr, 4, write as RegT)?;
+        self.bus
+            .write_u32(pa, &write)
+            .map_err(|_| Exception::StoreAccess(*addr))?;
         Ok(read)
     }
-    fn amo_u64<F: Fn(u64) -> u64>(&self, addr: &u64, f: F) -> Result<u64, u64> {
+    fn amo_u64<F: Fn(u64) -> u64>(
+        &self,
+        state: &ProcessorState,
+        addr: &RegT,
+        pa: &u64,
+        f: F,
+    ) -> Result<u64, Exception> {
         let mut read: u64 = 0;
-        self.bus.read_u64(addr, &mut read)?;
-        self.bus.write_u64(addr, &f(read))?;
+        self.bus
+            .read_u64(pa, &mut read)
+            .map_err(|_| Exception::StoreAccess(*addr))?;
+        state.check_triggers(TriggerOp::Load, *addr, 8, read as RegT)?;
+        let write = f(read);
+        state.check_triggers(TriggerOp::Store, *addr, 8, write as RegT)?;
+        self.bus
+            .write_u64(pa, &write)
+            .map_err(|_| Exception::StoreAccess(*addr))?;
+        Ok(read)
+    }
+    //bus is at most 64 bits wide, aligned quad word never crosses a page
+    fn amo_u128<F: Fn(u128) -> u128>(
+        &self,
+        state: &ProcessorState,
+        addr: &RegT,
+        pa: &u64,
+        f: F,
+    ) -> Result<u128, Exception> {
+        let mut low: u64 = 0;
+        let mut high: u64 = 0;
+        self.bus
+            .read_u64(pa, &mut low)
+            .and_then(|_| self.bus.read_u64(&(pa + 8), &mut high))
+            .map_err(|_| Exception::StoreAccess(*addr))?;
+        state.check_triggers(TriggerOp::Load, *addr, 16, low as RegT)?;
+        let read = (high as u128) << 64 | low as u128;
+        let write = f(read);
+        state.check_triggers(TriggerOp::Store, *addr, 16, write as RegT)?;
+        self.bus
+            .write_u64(pa, &(write as u64))
+            .and_then(|_| self.bus.write_u64(&(pa + 8), &((write >> 64) as u64)))
+            .map_err(|_| Exception::StoreAccess(*addr))?;
         Ok(read)
     }
 
+    pub fn amo_byte<F: Fn(u8) -> u8>(
+        &self,
+        state: &ProcessorState,
+        addr: &RegT,
+        f: F,
+        mmu: &Mmu,
+    ) -> Result<RegT, Exception> {
+        let pa = mmu.ls_translate(state, addr, 1, MmuOpt::Store)?;
+        if let Some(lock_holder) = self.bus.lock_holder(addr, 1) {
+            if lock_holder != state.hartid {
+                self.bus.invalid_lock(addr, 1, lock_holder);
+            }
+        }
+        self.amo_u8(state, addr, &pa, f).map(|data| data as RegT)
+    }
+    pub fn amo_half_word<F: Fn(u16) -> u16>(
+        &self,
+        state: &ProcessorState,
+        addr: &RegT,
+        f: F,
+        mmu: &Mmu,
+    ) -> Result<RegT, Exception> {
+        if addr.trailing_zeros() < 1 {
+            return Err(Exception::StoreMisaligned(*addr));
+        }
+        let pa = mmu.ls_translate(state, addr, 2, MmuOpt::Store)?;
+        if let Some(lock_holder) = self.bus.lock_holder(addr, 2) {
+            if lock_holder != state.hartid {
+                self.bus.invalid_lock(addr, 2, lock_holder);
+            }
+        }
+        self.amo_u16(state, addr, &pa, f).map(|data| data as RegT)
+    }
     pub fn amo_word<F: Fn(u32) -> u32>(
         &self,
         state: &ProcessorState,
@@ -187,10 +411,7 @@ impl LoadStore {
                 self.bus.invalid_lock(addr, 4, lock_holder);
             }
         }
-        match self.amo_u32(&pa, f) {
-            Ok(data) => Ok(data as RegT),
-            Err(_) => Err(Exception::StoreAccess(*addr)),
-        }
+        self.amo_u32(state, addr, &pa, f).map(|data| data as RegT)
     }
     pub fn amo_double_word<F: Fn(u64) -> u64>(
         &self,
@@ -208,10 +429,25 @@ impl LoadStore {
                 self.bus.invalid_lock(addr, 8, lock_holder);
             }
         }
-        match self.amo_u64(&pa, f) {
-            Ok(data) => Ok(data as RegT),
-            Err(_) => Err(Exception::StoreAccess(*addr)),
+        self.amo_u64(state, addr, &pa, f).map(|data| data as RegT)
+    }
+    pub fn amo_quad_word<F: Fn(u128) -> u128>(
+        &self,
+        state: &ProcessorState,
+        addr: &RegT,
+        f: F,
+        mmu: &Mmu,
+    ) -> Result<u128, Exception> {
+        if addr.trailing_zeros() < 4 {
+            return Err(Exception::StoreMisaligned(*addr));
+        }
+        let pa = mmu.ls_translate(state, addr, 16, MmuOpt::Store)?;
+        if let Some(lock_holder) = self.bus.lock_holder(addr, 16) {
+            if lock_holder != state.hartid {
+                self.bus.invalid_lock(addr, 16, lock_holder);
+            }
         }
+        self.amo_u128(state, addr, &pa, f)
     }
 
     pub fn acquire(
@@ -244,6 +480,14 @@ impl LoadStore {
         }
     }
 
+    //wrs waits until the reservation of lr is lost
+    pub fn holding_lock(&self, state: &ProcessorState, addr: &RegT, len: usize, mmu: &Mmu) -> bool {
+        match mmu.ls_translate(state, addr, len, MmuOpt::Load) {
+            Ok(pa) => self.bus.lock_holder(&pa, len) == Some(state.hartid),
+            Err(_) => false,
+        }
+    }
+
     pub fn release(&self, state: &ProcessorState) {
         self.bus.release(state.hartid)
     }
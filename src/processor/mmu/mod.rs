@@ -1,7 +1,10 @@
 use crate::devices::bus::Bus;
 use crate::prelude::*;
+use crate::processor::hpm::HpmEvent;
+use crate::processor::isa::IsaExt;
 use crate::processor::trap::Exception;
 use crate::processor::ProcessorState;
+use std::cell::Cell;
 use std::convert::TryFrom;
 use std::marker::PhantomData;
 
@@ -17,18 +20,21 @@ mod tlb;
 
 use tlb::*;
 
+
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub enum MmuOpt {
     Load,
     Store,
     Fetch,
+    //hlvx, load with execute permission
+    LoadX,
 }
 
 impl MmuOpt {
     fn access_exception(&self, addr: RegT) -> Exception {
         match self {
             MmuOpt::Fetch => Exception::FetchAccess(addr as u64),
-            MmuOpt::Load => Exception::LoadAccess(addr as u64),
+            MmuOpt::Load | MmuOpt::LoadX => Exception::LoadAccess(addr as u64),
             MmuOpt::Store => Exception::StoreAccess(addr as u64),
         }
     }
@@ -36,25 +42,71 @@ impl MmuOpt {
     fn pagefault_exception(&self, addr: RegT) -> Exception {
         match self {
             MmuOpt::Fetch => Exception::FetchPageFault(addr as u64),
-            MmuOpt::Load => Exception::LoadPageFault(addr as u64),
+            MmuOpt::Load | MmuOpt::LoadX => Exception::LoadPageFault(addr as u64),
             MmuOpt::Store => Exception::StorePageFault(addr as u64),
         }
     }
 
+    fn guest_pagefault_exception(&self, gva: RegT, gpa: u64) -> Exception {
+        match self {
+            MmuOpt::Fetch => Exception::FetchGuestPageFault(gva as u64, gpa),
+            MmuOpt::Load | MmuOpt::LoadX => Exception::LoadGuestPageFault(gva as u64, gpa),
+            MmuOpt::Store => Exception::StoreGuestPageFault(gva as u64, gpa),
+        }
+    }
+
+    //faults of implicit accesses are reported as the type of the original access
+    fn implicit_exception(&self, e: Exception) -> Exception {
+        match e {
+            Exception::FetchGuestPageFault(gva, gpa)
+            | Exception::LoadGuestPageFault(gva, gpa)
+            | Exception::StoreGuestPageFault(gva, gpa) => {
+                self.guest_pagefault_exception(gva as RegT, gpa)
+            }
+            Exception::FetchAccess(addr)
+            | Exception::LoadAccess(addr)
+            | Exception::StoreAccess(addr) => self.access_exception(addr as RegT),
+            _ => e,
+        }
+    }
+
     fn pmp_match(&self, pmpcfg: &PmpCfgEntry) -> bool {
         match self {
             MmuOpt::Fetch => pmpcfg.x() == 1,
-            MmuOpt::Load => pmpcfg.r() == 1,
+            MmuOpt::Load | MmuOpt::LoadX => pmpcfg.r() == 1,
             MmuOpt::Store => pmpcfg.w() == 1,
         }
     }
 }
 
+//one stage of address translation
+struct Stage {
+    info: PteInfo,
+    //ppn of root page table
+    root: u64,
+    privilege: u8,
+    mxr: bool,
+    sum: bool,
+    //pbmt is enabled by svpbmt and menvcfg.pbmte, VS-stage also needs henvcfg.pbmte
+    pbmte: bool,
+}
+
+impl Stage {
+    //everything check_pte_privilege depends on besides the pte
+    fn context(&self) -> u8 {
+        self.privilege | (self.mxr as u8) << 2 | (self.sum as u8) << 3
+    }
+}
+
 pub struct Mmu {
     bus: Rc<dyn Bus>,
     fetch_tlb: RefCell<TLB>,
     load_tlb: RefCell<TLB>,
     store_tlb: RefCell<TLB>,
+    //hypervisor load/store in progress, true for hlvx
+    hyper: Cell<Option<bool>>,
+    //debugger access in progress, page walks neither check permissions nor update A/D bits
+    debug: Cell<bool>,
 }
 
 impl Mmu {
@@ -64,6 +116,8 @@ impl Mmu {
             fetch_tlb: RefCell::new(TLB::new()),
             load_tlb: RefCell::new(TLB::new()),
             store_tlb: RefCell::new(TLB::new()),
+            hyper: Cell::new(None),
+            debug: Cell::new(false),
         }
     }
     #[cfg_attr(feature = "no-inline", inline(never))]
@@ -124,122 +178,242 @@ impl Mmu {
             *privilege == 3
         }
     }
+    //return privilege and virtualization mode of the access
     #[cfg_attr(feature = "no-inline", inline(never))]
-    fn get_privileage(&self, state: &ProcessorState, opt: &MmuOpt) -> u8 {
-        let is_mprv = state.priv_m().mstatus().mprv() == 1;
+    fn get_privileage(&self, state: &ProcessorState, opt: &MmuOpt) -> (u8, bool) {
+        if self.hyper.get().is_some() {
+            let spvp = state.priv_h().map_or(0, |h| h.hstatus().spvp());
+            return (spvp as u8, true);
+        }
+        let is_mprv = state.priv_m().mstatus().mprv() == 1
+            && (!state.debug_mode() || state.priv_m().dcsr().mprven() == 1);
         let mpp = state.priv_m().mstatus().mpp() as u8 & 3;
+        let mpv = mpp != 3 && state.priv_m().mstatus().mpv() == 1;
         match opt {
-            &MmuOpt::Load if is_mprv => mpp,
-            &MmuOpt::Store if is_mprv => mpp,
-            _ => (*state.privilege()).into(),
+            &MmuOpt::Load if is_mprv => (mpp, mpv),
+            &MmuOpt::Store if is_mprv => (mpp, mpv),
+            _ => ((*state.privilege()).into(), state.virt()),
         }
     }
     #[cfg_attr(feature = "no-inline", inline(never))]
-    fn check_pte_privilege(
-        &self,
-        state: &ProcessorState,
-        addr: RegT,
-        pte_attr: &PteAttr,
-        opt: &MmuOpt,
-        privilege: &u8,
-    ) -> Result<(), Exception> {
-        let priv_s = *privilege == 1;
+    fn check_pte_privilege(&self, pte_attr: &PteAttr, opt: &MmuOpt, stage: &Stage) -> bool {
+        let priv_s = stage.privilege == 1;
         let pte_x = pte_attr.x() == 1;
         let pte_u = pte_attr.u() == 1;
         let pte_r = pte_attr.r() == 1;
         let pte_w = pte_attr.w() == 1;
-        let mxr = state.priv_m().mstatus().mxr() == 1;
-        let sum = state.priv_m().mstatus().sum() == 1;
+        let mxr = stage.mxr;
+        let sum = stage.sum;
         match opt {
-            &MmuOpt::Fetch => {
-                if !pte_x || pte_u == priv_s {
-                    return Err(opt.pagefault_exception(addr));
-                }
-            }
+            &MmuOpt::Fetch | &MmuOpt::LoadX => !(!pte_x || pte_u == priv_s),
             &MmuOpt::Load => {
-                if priv_s && !sum && pte_u
+                !(priv_s && !sum && pte_u
                     || !pte_u && !priv_s
                     || !pte_r && !mxr
-                    || mxr && !pte_r && !pte_x
-                {
-                    return Err(opt.pagefault_exception(addr));
-                }
+                    || mxr && !pte_r && !pte_x)
             }
             &MmuOpt::Store => {
-                if priv_s && !sum && pte_u || !pte_u && !priv_s || !pte_w || !pte_r {
-                    return Err(opt.pagefault_exception(addr));
-                }
+                !(priv_s && !sum && pte_u || !pte_u && !priv_s || !pte_w || !pte_r)
             }
         }
-        Ok(())
     }
+
+    //VS-stage when virt, otherwise the only stage, return it with asid
+    fn s_stage(
+        &self,
+        state: &ProcessorState,
+        privilege: u8,
+        virt: bool,
+    ) -> Result<(Stage, u16), Exception> {
+        let mstatus = state.priv_m().mstatus();
+        let pbmte = state.config().isa.has_ext(IsaExt::Svpbmt)
+            && state.priv_m().menvcfg().pbmte() == 1;
+        if virt {
+            let h = state.priv_h()?;
+            let vsatp = h.vsatp();
+            let vsstatus = h.vsstatus();
+            Ok((
+                Stage {
+                    info: PteInfo::new(&*vsatp),
+                    root: vsatp.ppn() as u64,
+                    privilege,
+                    mxr: vsstatus.mxr() == 1 || mstatus.mxr() == 1,
+                    sum: vsstatus.sum() == 1,
+                    pbmte: pbmte && h.henvcfg().pbmte() == 1,
+                },
+                vsatp.asid() as u16,
+            ))
+        } else {
+            let satp = state.priv_s()?.satp();
+            Ok((
+                Stage {
+                    info: PteInfo::new(&*satp),
+                    root: satp.ppn() as u64,
+                    privilege,
+                    mxr: mstatus.mxr() == 1,
+                    sum: mstatus.sum() == 1,
+                    pbmte,
+                },
+                satp.asid() as u16,
+            ))
+        }
+    }
+
+    //G-stage, accesses are always treated as U mode, return it with vmid
+    fn g_stage(&self, state: &ProcessorState) -> Result<(Stage, u16), Exception> {
+        let hgatp = state.priv_h()?.hgatp();
+        Ok((
+            Stage {
+                info: PteInfo::with_mode(hgatp.xlen, hgatp.mode() as u8),
+                root: hgatp.ppn() as u64,
+                privilege: 0,
+                mxr: state.priv_m().mstatus().mxr() == 1,
+                sum: false,
+                pbmte: state.config().isa.has_ext(IsaExt::Svpbmt)
+                    && state.priv_m().menvcfg().pbmte() == 1,
+            },
+            hgatp.vmid() as u16,
+        ))
+    }
+
+    //walk the page table of stage, with pte addresses translated by g if present,
+    //gva is present when stage is the G-stage, then faults are guest page faults
     #[cfg_attr(feature = "no-inline", inline(never))]
     fn pt_walk(
         &self,
         state: &ProcessorState,
+        stage: &Stage,
         vaddr: &Vaddr,
         opt: &MmuOpt,
-        privilege: &u8,
-        info: &PteInfo,
-    ) -> Result<u64, Exception> {
+        g: Option<&Stage>,
+        gva: Option<RegT>,
+    ) -> Result<(u64, PteAttr, u64), Exception> {
+        let info = &stage.info;
+        let page_fault = || match gva {
+            Some(gva) => opt.guest_pagefault_exception(gva, vaddr.value() as u64),
+            None => opt.pagefault_exception(vaddr.value()),
+        };
+        let access_fault = || opt.access_exception(gva.unwrap_or(vaddr.value()));
+        //n and pbmt are reserved without Svnapot and enabled Svpbmt
+        let svnapot = state.config().isa.has_ext(IsaExt::Svnapot);
         //step 1
-        let ppn = state.priv_s()?.satp().ppn();
-        let mut a = (ppn << info.page_size_shift) as RegT;
+        let mut a = (stage.root << info.page_size_shift) as RegT;
         let mut level = info.level - 1;
         let mut leaf_pte: Pte;
         let mut pte_addr: u64;
         loop {
             //step 2
-            pte_addr = (a + (vaddr.vpn(level) << (info.size_shift as RegT))) as u64;
+            let mut vpn = vaddr.vpn(level);
+            //root page table of G-stage is 4 times larger
+            if gva.is_some() && level == info.level - 1 {
+                vpn |= ((vaddr.value() >> (info.page_size_shift + 9 * info.level)) & 0x3) << 9;
+            }
+            pte_addr = (a + (vpn << (info.size_shift as RegT))) as u64;
+            //implicit access of VS-stage page table
+            if let Some(g) = g {
+                pte_addr = self
+                    .g_translate(state, g, pte_addr, vaddr.value(), &MmuOpt::Load)
+                    .map_err(|e| opt.implicit_exception(e))?;
+            }
             if !self.check_pmp(state, &pte_addr, 1 << info.size_shift, &MmuOpt::Load, &1) {
-                return Err(opt.access_exception(vaddr.value()));
+                return Err(access_fault());
             }
             let pte = match Pte::load(info, &self.bus, &pte_addr) {
                 Ok(pte) => pte,
-                Err(_) => return Err(opt.access_exception(vaddr.value())),
+                Err(_) => return Err(access_fault()),
             };
             //step 3
-            if pte.attr().v() == 0 || pte.attr().r() == 0 && pte.attr().w() == 1 {
-                return Err(opt.pagefault_exception(vaddr.value()));
+            if pte.attr().v() == 0
+                || pte.attr().r() == 0 && pte.attr().w() == 1
+                || pte.reserved() != 0
+                || pte.pbmt() == PBMT_RESERVED
+                || !svnapot && pte.n() != 0
+                || !stage.pbmte && pte.pbmt() != 0
+            {
+                return Err(page_fault());
             }
             //step 4
             if pte.attr().r() == 1 || pte.attr().x() == 1 {
                 leaf_pte = pte;
                 break;
-            } else if level == 0 {
-                return Err(opt.pagefault_exception(vaddr.value()));
+            } else if level == 0 || pte.n() != 0 || pte.pbmt() != 0 {
+                return Err(page_fault());
             } else {
                 level -= 1;
                 a = pte.ppn_all() << info.page_size_shift as RegT;
             }
         }
         //step 5
-        self.check_pte_privilege(state, vaddr.value(), &leaf_pte.attr(), opt, privilege)?;
+        if !self.debug.get() && !self.check_pte_privilege(&leaf_pte.attr(), opt, stage) {
+            return Err(page_fault());
+        }
         //step 6
         for l in 0..level {
             if leaf_pte.ppn(l) != 0 {
-                return Err(opt.pagefault_exception(vaddr.value()));
+                return Err(page_fault());
             }
         }
+        //Svnapot, only 64KiB pages are supported
+        if leaf_pte.n() != 0 && (level != 0 || leaf_pte.ppn(0) & NAPOT_PPN_MASK != NAPOT_64K) {
+            return Err(page_fault());
+        }
         //step 7
-        if leaf_pte.attr().d() == 0 && *opt == MmuOpt::Store || leaf_pte.attr().a() == 0 {
+        if !self.debug.get()
+            && (leaf_pte.attr().d() == 0 && *opt == MmuOpt::Store || leaf_pte.attr().a() == 0)
+        {
             if state.config().enable_dirty {
                 let mut new_attr = leaf_pte.attr();
                 new_attr.set_a(1);
-                new_attr.set_d((*opt == MmuOpt::Store) as u8);
+                new_attr.set_d(leaf_pte.attr().d() | (*opt == MmuOpt::Store) as u8);
                 if !self.check_pmp(state, &pte_addr, 1 << info.size_shift, &MmuOpt::Store, &1) {
-                    return Err(opt.access_exception(vaddr.value()));
+                    return Err(access_fault());
                 }
                 leaf_pte.set_attr(&new_attr);
                 if leaf_pte.store(&self.bus, &pte_addr).is_err() {
-                    return Err(opt.access_exception(vaddr.value()));
+                    return Err(access_fault());
                 }
             } else {
-                return Err(opt.pagefault_exception(vaddr.value()));
+                return Err(page_fault());
             }
         }
         //step 8
-        Ok(Paddr::new(vaddr, &leaf_pte, info, level).value() as u64)
+        let mut pa = Paddr::new(vaddr, &leaf_pte, info, level);
+        if leaf_pte.n() != 0 {
+            pa.set_ppn(
+                0,
+                leaf_pte.ppn(0) & !NAPOT_PPN_MASK | vaddr.vpn(0) & NAPOT_PPN_MASK,
+            );
+        }
+        //vpn bits covered by the leaf page
+        let mask = if leaf_pte.n() != 0 {
+            NAPOT_PPN_MASK as u64
+        } else {
+            let vpn_bits = if info.size_shift == 2 { 10 } else { 9 };
+            (1u64 << (level * vpn_bits)) - 1
+        };
+        Ok((pa.value() as u64, leaf_pte.attr(), mask))
+    }
+
+    //translate guest physical address by G-stage
+    #[cfg_attr(feature = "no-inline", inline(never))]
+    fn g_translate(
+        &self,
+        state: &ProcessorState,
+        g: &Stage,
+        gpa: u64,
+        gva: RegT,
+        opt: &MmuOpt,
+    ) -> Result<u64, Exception> {
+        if g.info.mode == PTE_BARE {
+            return Ok(gpa);
+        }
+        //x4 modes have 2 more bits than the corresponding satp mode
+        if gpa >> (g.info.page_size_shift + 9 * g.info.level + 2) != 0 {
+            return Err(opt.guest_pagefault_exception(gva, gpa));
+        }
+        let vaddr = Vaddr::new(g.info.mode, gpa as RegT);
+        let (pa, _, _) = self.pt_walk(state, g, &vaddr, opt, None, Some(gva))?;
+        Ok(pa)
     }
 
     pub fn flush_tlb(&self) {
@@ -248,10 +422,84 @@ impl Mmu {
         self.store_tlb.borrow_mut().invalid_all();
     }
 
-    pub fn flush_by_vpn(&self, vpn: u64) {
-        self.fetch_tlb.borrow_mut().invalid_by_vpn(vpn);
-        self.load_tlb.borrow_mut().invalid_by_vpn(vpn);
-        self.store_tlb.borrow_mut().invalid_by_vpn(vpn);
+    //sfence.vma and hfence.vvma, None means all addresses or all address spaces,
+    //guest translations of current vmid are flushed if virt
+    pub fn flush(&self, state: &ProcessorState, virt: bool, va: Option<RegT>, asid: Option<RegT>) {
+        //entries are tagged with vpn of current mode, flush all addresses if bare
+        let (mode, vmid) = if virt {
+            match state.priv_h() {
+                Ok(h) => (
+                    PteInfo::new(&*h.vsatp()).mode,
+                    Some(h.hgatp().vmid() as u16),
+                ),
+                Err(_) => return,
+            }
+        } else {
+            (
+                state
+                    .priv_s()
+                    .map_or(PTE_BARE, |s| PteInfo::new(&*s.satp()).mode),
+                None,
+            )
+        };
+        let vpn = va.and_then(|va| {
+            if mode != PTE_BARE {
+                Some(Vaddr::new(mode, va).vpn_all() as u64)
+            } else if virt {
+                Some((va as u64) >> 12)
+            } else {
+                None
+            }
+        });
+        let asid = asid.map(|asid| asid as u16);
+        self.fetch_tlb.borrow_mut().invalid(virt, vmid, vpn, asid);
+        self.load_tlb.borrow_mut().invalid(virt, vmid, vpn, asid);
+        self.store_tlb.borrow_mut().invalid(virt, vmid, vpn, asid);
+    }
+
+    //hfence.gvma, entries do not keep guest physical address, flush whole vmid
+    pub fn flush_gstage(&self, vmid: Option<RegT>) {
+        let vmid = vmid.map(|vmid| vmid as u16);
+        self.fetch_tlb.borrow_mut().invalid(true, vmid, None, None);
+        self.load_tlb.borrow_mut().invalid(true, vmid, None, None);
+        self.store_tlb.borrow_mut().invalid(true, vmid, None, None);
+    }
+
+    //hypervisor virtual-machine load and store, translated as VS or VU mode of hstatus.spvp
+    pub fn hyper_access<T, F: FnOnce() -> Result<T, Exception>>(
+        &self,
+        hlvx: bool,
+        f: F,
+    ) -> Result<T, Exception> {
+        self.hyper.set(Some(hlvx));
+        let res = f();
+        //keep it when fail, so that trap handler knows the address is guest virtual address
+        if res.is_ok() {
+            self.hyper.set(None);
+        }
+        res
+    }
+
+    pub fn take_hyper_access(&self) -> bool {
+        self.hyper.take().is_some()
+    }
+
+    //translation context of instruction fetch, icache entries are tagged with it
+    pub fn fetch_context(&self, state: &ProcessorState) -> u64 {
+        let privilege: u8 = (*state.privilege()).into();
+        let virt = state.virt();
+        let mut ctx = privilege as u64 | (virt as u64) << 2;
+        if privilege != 3 {
+            if let Ok((s, asid)) = self.s_stage(state, privilege, virt) {
+                ctx |= (s.info.mode as u64) << 3 | (asid as u64) << 11;
+            }
+            if virt {
+                if let Ok((g, vmid)) = self.g_stage(state) {
+                    ctx |= (g.info.mode as u64) << 7 | (vmid as u64) << 27;
+                }
+            }
+        }
+        ctx
     }
 
     #[cfg_attr(feature = "no-inline", inline(never))]
@@ -262,20 +510,84 @@ impl Mmu {
         len: usize,
         opt: MmuOpt,
     ) -> Result<u64, Exception> {
+        let (privilege, virt) = self.get_privileage(state, &opt);
+        let opt = if opt == MmuOpt::Load && self.hyper.get() == Some(true) {
+            MmuOpt::LoadX
+        } else {
+            opt
+        };
         self.translate(
             state,
             va,
             len,
             opt,
-            self.get_privileage(state, &opt),
+            privilege,
+            virt,
             &mut *match opt {
                 MmuOpt::Store => self.store_tlb.borrow_mut(),
-                MmuOpt::Load => self.load_tlb.borrow_mut(),
+                MmuOpt::Load | MmuOpt::LoadX => self.load_tlb.borrow_mut(),
                 _ => unreachable!(),
             },
         )
     }
 
+    //page walks of both stages, return pa, leaf attributes of both stages and vpn mask of the leaf
+    fn walk(
+        &self,
+        state: &ProcessorState,
+        va: &RegT,
+        opt: &MmuOpt,
+        s: &Stage,
+        g: Option<&Stage>,
+    ) -> Result<(u64, Option<PteAttr>, Option<PteAttr>, u64), Exception> {
+        let (gpa, attr, mask) = if s.info.mode == PTE_BARE {
+            (*va as u64, None, 0)
+        } else {
+            let vaddr = Vaddr::new(s.info.mode, *va);
+            let (gpa, attr, mask) = self.pt_walk(state, s, &vaddr, opt, g, None)?;
+            (gpa, Some(attr), mask)
+        };
+        match g {
+            Some(g) if g.info.mode != PTE_BARE => {
+                let vaddr = Vaddr::new(g.info.mode, gpa as RegT);
+                if gpa >> (g.info.page_size_shift + 9 * g.info.level + 2) != 0 {
+                    return Err(opt.guest_pagefault_exception(*va, gpa));
+                }
+                let (pa, g_attr, _) = self.pt_walk(state, g, &vaddr, opt, None, Some(*va))?;
+                Ok((pa, attr, Some(g_attr), mask))
+            }
+            _ => Ok((gpa, attr, None, mask)),
+        }
+    }
+
+    //translate for debugger with the privilege in dcsr when halted,
+    //without permission check, A/D update, tlb fill or hpm event
+    pub fn debug_translate(&self, state: &ProcessorState, va: &RegT) -> Option<u64> {
+        let (privilege, virt) = if state.debug_mode() {
+            let dcsr = state.priv_m().dcsr();
+            (dcsr.prv() as u8, dcsr.v() == 1)
+        } else {
+            ((*state.privilege()).into(), state.virt())
+        };
+        if privilege == 3 {
+            return Some(*va as u64);
+        }
+        //no translation without S mode
+        let s = match self.s_stage(state, privilege, virt) {
+            Ok((s, _)) => s,
+            Err(_) => return Some(*va as u64),
+        };
+        let g = if virt {
+            Some(self.g_stage(state).ok()?.0)
+        } else {
+            None
+        };
+        self.debug.set(true);
+        let result = self.walk(state, va, &MmuOpt::Load, &s, g.as_ref());
+        self.debug.set(false);
+        result.ok().map(|(pa, _, _, _)| pa)
+    }
+
     #[cfg_attr(feature = "no-inline", inline(never))]
     fn translate(
         &self,
@@ -284,31 +596,79 @@ impl Mmu {
         len: usize,
         opt: MmuOpt,
         privilege: u8,
+        virt: bool,
         tlb: &mut TLB,
     ) -> Result<u64, Exception> {
         if privilege == 3 {
             return Ok(*va as u64);
         }
-        let info = PteInfo::new(&*state.priv_s()?.satp());
-        if info.mode == PTE_BARE {
+        let (s, asid) = self.s_stage(state, privilege, virt)?;
+        let (g, vmid) = if virt {
+            let (g, vmid) = self.g_stage(state)?;
+            (Some(g), vmid)
+        } else {
+            (None, 0)
+        };
+        if s.info.mode == PTE_BARE && g.as_ref().map_or(true, |g| g.info.mode == PTE_BARE) {
             return Ok(*va as u64);
         }
-        let vaddr = Vaddr::new(info.mode, *va);
-        if let Some(ppn) = tlb.get_ppn(vaddr.vpn_all()) {
-            let pa = (*ppn << (info.page_size_shift as u64)) | vaddr.offset();
-            return Ok(pa);
-        }
-        match self.pt_walk(state, &vaddr, &opt, &privilege, &info) {
-            Ok(pa) => {
-                if !self.check_pmp(state, &pa, len as usize, &opt, &privilege) {
-                    return Err(opt.access_exception(*va));
-                } else {
-                    tlb.set_entry(vaddr.vpn_all(), pa >> (info.page_size_shift as u64));
-                    Ok(pa)
+        let vpn = if s.info.mode == PTE_BARE {
+            (*va as u64) >> 12
+        } else {
+            Vaddr::new(s.info.mode, *va).vpn_all() as u64
+        };
+        let ctx = s.context() | g.as_ref().map_or(0, |g| (g.mxr as u8) << 4);
+        //hlvx is rare, not cached
+        let cached = opt != MmuOpt::LoadX;
+        if let Some(e) = tlb.get_entry(virt, vmid, vpn, asid).filter(|_| cached) {
+            //A/D updates need a page walk
+            let fresh = |attr: &Option<PteAttr>| {
+                attr.map_or(true, |a| a.a() == 1 && (opt != MmuOpt::Store || a.d() == 1))
+            };
+            if fresh(&e.attr) && fresh(&e.g_attr) {
+                let pa = (e.ppn << 12) | (*va as u64 & 0xfff);
+                if e.ctx == ctx {
+                    return Ok(pa);
+                }
+                //privilege, MXR or SUM changed since the last check, walk again if not allowed
+                if e.attr.map_or(true, |a| self.check_pte_privilege(&a, &opt, &s))
+                    && e.g_attr.map_or(true, |a| {
+                        self.check_pte_privilege(&a, &opt, g.as_ref().unwrap())
+                    })
+                    && self.check_pmp(state, &pa, len, &opt, &privilege)
+                {
+                    e.ctx = ctx;
+                    return Ok(pa);
                 }
             }
-            Err(e) => Err(e),
         }
+        if cached {
+            state.hpm_event(match opt {
+                MmuOpt::Fetch => HpmEvent::ITlbMiss,
+                MmuOpt::Store => HpmEvent::DTlbStoreMiss,
+                _ => HpmEvent::DTlbLoadMiss,
+            });
+        }
+        let (pa, attr, g_attr, mask) = self.walk(state, va, &opt, &s, g.as_ref())?;
+        if !self.check_pmp(state, &pa, len, &opt, &privilege) {
+            return Err(opt.access_exception(*va));
+        }
+        if cached {
+            tlb.set_entry(TLBEntry {
+                valid: true,
+                virt,
+                vmid,
+                vpn,
+                mask,
+                asid,
+                global: attr.map_or(false, |a| a.g() == 1),
+                ppn: pa >> 12,
+                attr,
+                g_attr,
+                ctx,
+            });
+        }
+        Ok(pa)
     }
 
     #[cfg_attr(feature = "no-inline", inline(never))]
@@ -324,6 +684,7 @@ impl Mmu {
             len,
             MmuOpt::Fetch,
             (*state.privilege()).into(),
+            state.virt(),
             &mut self.fetch_tlb.borrow_mut(),
         )
     }
@@ -332,11 +693,13 @@ impl Mmu {
 #[cfg(test)]
 use crate::prelude::XLen;
 #[cfg(test)]
-use crate::processor::ProcessorCfg;
+use crate::processor::{ProcessorCfg, SatpMode};
 #[cfg(test)]
 use crate::system::System;
 use std::cell::RefCell;
 use std::rc::Rc;
+#[cfg(test)]
+use terminus_spaceport::memory::region::GHEAP;
 
 #[test]
 fn pmp_basic_test() {
@@ -344,8 +707,15 @@ fn pmp_basic_test() {
     sys.new_processor(ProcessorCfg {
         xlen: XLen::X32,
         enable_dirty: true,
-        extensions: vec![].into_boxed_slice(),
+        isa: "rv32i".parse().unwrap(),
         freq: 1000000000,
+        triggers: 0,
+        satp_modes: vec![SatpMode::Sv32].into_boxed_slice(),
+        vlen: 128,
+        elen: 64,
+        cache_block_size: 64,
+        entropy_seed: 0,
+        hpm_counters: 0,
     });
     sys.reset(vec![-1i64 as u64]).unwrap();
 
@@ -430,3 +800,240 @@ fn pmp_basic_test() {
         .match_pmpcfg_entry(p.state(), &0x2001_0000, 4)
         .is_some());
 }
+
+#[test]
+fn debug_translate_test() {
+    let mut sys = System::new("test", "top_tests/elf/rv64ui-p-add", 100, 1);
+    sys.new_processor(ProcessorCfg {
+        xlen: XLen::X64,
+        enable_dirty: true,
+        isa: "rv64imacsu".parse().unwrap(),
+        freq: 1000000000,
+        triggers: 0,
+        satp_modes: vec![SatpMode::Sv39].into_boxed_slice(),
+        vlen: 128,
+        elen: 64,
+        cache_block_size: 64,
+        entropy_seed: 0,
+        hpm_counters: 0,
+    });
+    sys.register_memory(
+        "main_memory",
+        0x8000_0000,
+        &GHEAP.alloc(0x2_0000, 1).unwrap(),
+    )
+    .unwrap();
+    sys.reset(vec![-1i64 as u64]).unwrap();
+    let bus = sys.bus().clone();
+
+    //1GiB page at 0x4000_0000 -> 0x8000_0000, A and D are clear
+    let pte_addr = 0x8001_0000 + 8;
+    let pte = (0x8000_0000u64 >> 12) << 10 | 0x7;
+    bus.write_u64(&pte_addr, &pte).unwrap();
+    let p = sys.processor(0).unwrap();
+    p.state()
+        .priv_s()
+        .unwrap()
+        .satp_mut()
+        .set((SatpMode::Sv39 as RegT) << 60 | 0x8001_0000 >> 12);
+    p.debug_halt();
+
+    //halted in M mode, addresses are physical
+    assert_eq!(p.debug_translate(0x4000_0100), Some(0x4000_0100));
+    //translated as S mode in dcsr.prv, without touching pte or tlb
+    p.state().priv_m().dcsr_mut().set_prv(1);
+    assert_eq!(p.debug_translate(0x4000_0100), Some(0x8000_0100));
+    assert_eq!(p.debug_translate(0x8000_0100), None);
+    let mut data = 0;
+    bus.read_u64(&pte_addr, &mut data).unwrap();
+    assert_eq!(data, pte);
+    assert!(p
+        .mmu()
+        .load_tlb
+        .borrow_mut()
+        .get_entry(false, 0, 0x4_0000, 0)
+        .is_none());
+}
+
+#[test]
+fn tlb_ctx_test() {
+    let mut sys = System::new("test", "top_tests/elf/rv64ui-p-add", 100, 1);
+    sys.new_processor(ProcessorCfg {
+        xlen: XLen::X64,
+        enable_dirty: true,
+        isa: "rv64imacsu".parse().unwrap(),
+        freq: 1000000000,
+        triggers: 0,
+        satp_modes: vec![SatpMode::Sv39].into_boxed_slice(),
+        vlen: 128,
+        elen: 64,
+        cache_block_size: 64,
+        entropy_seed: 0,
+        hpm_counters: 0,
+    });
+    sys.register_memory(
+        "main_memory",
+        0x8000_0000,
+        &GHEAP.alloc(0x2_0000, 1).unwrap(),
+    )
+    .unwrap();
+    sys.reset(vec![-1i64 as u64]).unwrap();
+    let bus = sys.bus().clone();
+
+    //1GiB user page at 0x4000_0000 and execute-only supervisor page at 0xc000_0000,
+    //both map to 0x8000_0000 with A and D set
+    let ppn = 0x8000_0000u64 >> 12;
+    bus.write_u64(&(0x8001_0000 + 8), &(ppn << 10 | 0xd7))
+        .unwrap();
+    bus.write_u64(&(0x8001_0000 + 24), &(ppn << 10 | 0xc9))
+        .unwrap();
+    let p = sys.processor(0).unwrap();
+    p.state()
+        .priv_s()
+        .unwrap()
+        .satp_mut()
+        .set((SatpMode::Sv39 as RegT) << 60 | 0x8001_0000 >> 12);
+    //TOR RWX below 4GiB
+    p.state().priv_m().pmpcfg0_mut().set_bit_range(4, 0, 0xf);
+    p.state().priv_m().pmpaddr0_mut().set(0x1_0000_0000 >> 2);
+    //loads are done with the privilege in mpp
+    p.state().priv_m().mstatus_mut().set_mprv(1);
+    p.state().priv_m().mstatus_mut().set_mpp(1);
+    let load = |va: RegT| p.mmu().ls_translate(p.state(), &va, 8, MmuOpt::Load).ok();
+    let cached = |va: RegT| {
+        p.mmu()
+            .load_tlb
+            .borrow_mut()
+            .get_entry(false, 0, va >> 12, 0)
+            .is_some()
+    };
+
+    //SUM
+    assert_eq!(load(0x4000_0100), None);
+    p.state().priv_m().mstatus_mut().set_sum(1);
+    assert_eq!(load(0x4000_0100), Some(0x8000_0100));
+    assert!(cached(0x4000_0100));
+    p.state().priv_m().mstatus_mut().set_sum(0);
+    assert_eq!(load(0x4000_0100), None);
+
+    //privilege
+    p.state().priv_m().mstatus_mut().set_mpp(0);
+    assert_eq!(load(0x4000_0100), Some(0x8000_0100));
+    p.state().priv_m().mstatus_mut().set_mpp(1);
+    assert_eq!(load(0x4000_0100), None);
+
+    //MXR
+    assert_eq!(load(0xc000_0100), None);
+    p.state().priv_m().mstatus_mut().set_mxr(1);
+    assert_eq!(load(0xc000_0100), Some(0x8000_0100));
+    assert!(cached(0xc000_0100));
+    p.state().priv_m().mstatus_mut().set_mpp(0);
+    assert_eq!(load(0xc000_0100), None);
+    p.state().priv_m().mstatus_mut().set_mpp(1);
+    assert_eq!(load(0xc000_0100), Some(0x8000_0100));
+    p.state().priv_m().mstatus_mut().set_mxr(0);
+    assert_eq!(load(0xc000_0100), None);
+}
+
+#[test]
+fn sv57_walk_test() {
+    let mut sys = System::new("test", "top_tests/elf/rv64ui-p-add", 100, 1);
+    sys.new_processor(ProcessorCfg {
+        xlen: XLen::X64,
+        enable_dirty: true,
+        isa: "rv64imacsu".parse().unwrap(),
+        freq: 1000000000,
+        triggers: 0,
+        satp_modes: vec![SatpMode::Sv39, SatpMode::Sv57].into_boxed_slice(),
+        vlen: 128,
+        elen: 64,
+        cache_block_size: 64,
+        entropy_seed: 0,
+        hpm_counters: 0,
+    });
+    sys.register_memory(
+        "main_memory",
+        0x8000_0000,
+        &GHEAP.alloc(0x2_0000, 1).unwrap(),
+    )
+    .unwrap();
+    sys.reset(vec![-1i64 as u64]).unwrap();
+    let bus = sys.bus().clone();
+
+    //5 levels of page tables at 0x8001_0000 - 0x8001_4000, 4KiB page at
+    //vpn[4:0] = {1, 2, 3, 4, 5} -> 0x8000_0000
+    let va = 1 << 48 | 2 << 39 | 3 << 30 | 4 << 21 | 5 << 12;
+    for (level, vpn) in [1u64, 2, 3, 4].iter().enumerate() {
+        let table = 0x8001_0000 + level as u64 * 0x1000;
+        let next = (table + 0x1000) >> 12;
+        bus.write_u64(&(table + vpn * 8), &(next << 10 | 0x1))
+            .unwrap();
+    }
+    bus.write_u64(&(0x8001_4000 + 5 * 8), &(0x8_0000 << 10 | 0xc7))
+        .unwrap();
+    //256TiB pages at vpn[4] = 6, misaligned, and vpn[4] = 7, aligned to 0
+    bus.write_u64(&(0x8001_0000 + 6 * 8), &(0x8_0000 << 10 | 0xc7))
+        .unwrap();
+    bus.write_u64(&(0x8001_0000 + 7 * 8), &0xc7).unwrap();
+    let p = sys.processor(0).unwrap();
+    p.state()
+        .priv_s()
+        .unwrap()
+        .satp_mut()
+        .set((SatpMode::Sv57 as RegT) << 60 | 0x8001_0000 >> 12);
+    //TOR RWX below 4GiB
+    p.state().priv_m().pmpcfg0_mut().set_bit_range(4, 0, 0xf);
+    p.state().priv_m().pmpaddr0_mut().set(0x1_0000_0000 >> 2);
+    //loads are done with the privilege in mpp
+    p.state().priv_m().mstatus_mut().set_mprv(1);
+    p.state().priv_m().mstatus_mut().set_mpp(1);
+    let load = |va: RegT| p.mmu().ls_translate(p.state(), &va, 8, MmuOpt::Load);
+
+    assert_eq!(load(va | 0x120), Ok(0x8000_0120));
+    assert_eq!(load(7 << 48 | 0x8000_0120), Ok(0x8000_0120));
+    assert_eq!(
+        load(6 << 48 | 0x120),
+        Err(Exception::LoadPageFault(6 << 48 | 0x120))
+    );
+    //vpn[0] = 6 is not mapped
+    assert_eq!(
+        load(va + 0x1000),
+        Err(Exception::LoadPageFault(va + 0x1000))
+    );
+}
+
+#[test]
+fn satp_warl_test() {
+    let mut sys = System::new("test", "top_tests/elf/rv64ui-p-add", 100, 1);
+    sys.new_processor(ProcessorCfg {
+        xlen: XLen::X64,
+        enable_dirty: true,
+        isa: "rv64imacsu".parse().unwrap(),
+        freq: 1000000000,
+        triggers: 0,
+        satp_modes: vec![SatpMode::Sv39, SatpMode::Sv57].into_boxed_slice(),
+        vlen: 128,
+        elen: 64,
+        cache_block_size: 64,
+        entropy_seed: 0,
+        hpm_counters: 0,
+    });
+    sys.reset(vec![-1i64 as u64]).unwrap();
+    let p = sys.processor(0).unwrap();
+    let write = |value: RegT| {
+        p.state().debug_set_csr(0x180, value).unwrap();
+        p.state().debug_csr(0x180).unwrap()
+    };
+    let satp = |mode: SatpMode, ppn: RegT| (mode as RegT) << 60 | ppn;
+
+    let sv39 = satp(SatpMode::Sv39, 0x8_0010);
+    assert_eq!(write(sv39), sv39);
+    //unsupported modes have no effect, including ppn and asid
+    assert_eq!(write(satp(SatpMode::Sv48, 0x8_0020)), sv39);
+    assert_eq!(write(satp(SatpMode::Sv32, 0x8_0020)), sv39);
+    assert_eq!(write(15 << 60 | 1 << 44), sv39);
+    let sv57 = satp(SatpMode::Sv57, 0x8_0030);
+    assert_eq!(write(sv57), sv57);
+    //bare
+    assert_eq!(write(0), 0);
+}
@@ -8,9 +8,14 @@ pub const PTE_BARE: u8 = 0;
 pub const PTE_SV32: u8 = 1;
 pub const PTE_SV39: u8 = 8;
 pub const PTE_SV48: u8 = 9;
-// pub const PTE_SV57: u8 = 10;
+pub const PTE_SV57: u8 = 10;
 // pub const PTE_SV64: u8 = 11;
 
+pub const PBMT_RESERVED: RegT = 3;
+//ppn[0][3:0] of 64KiB NAPOT page
+pub const NAPOT_PPN_MASK: RegT = 0xf;
+pub const NAPOT_64K: RegT = 0x8;
+
 pub struct PteInfo {
     pub mode: u8,
     pub level: usize,
@@ -21,18 +26,23 @@ pub struct PteInfo {
 impl PteInfo {
     #[cfg_attr(feature = "no-inline", inline(never))]
     pub fn new(satp: &Satp) -> PteInfo {
-        match satp.xlen {
+        Self::with_mode(satp.xlen, satp.mode() as u8)
+    }
+
+    //hgatp modes share the encoding and pte format with satp modes
+    pub fn with_mode(xlen: usize, mode: u8) -> PteInfo {
+        match xlen {
             32 => PteInfo {
-                mode: satp.mode() as u8,
+                mode,
                 level: 2,
                 size_shift: 2,
                 page_size_shift: 12,
             },
             64 => {
-                let mode = satp.mode() as u8;
                 let level = match mode {
                     PTE_SV39 => 3,
                     PTE_SV48 => 4,
+                    PTE_SV57 => 5,
                     PTE_BARE => 0,
                     _ => unreachable!(),
                 };
@@ -48,10 +58,14 @@ impl PteInfo {
     }
 }
 
-#[derive(Eq, PartialEq)]
+#[derive(Eq, PartialEq, Copy, Clone)]
 pub struct PteAttr(u8);
 
 impl PteAttr {
+    pub const fn new(v: u8) -> PteAttr {
+        PteAttr(v)
+    }
+
     pub const fn v(&self) -> u8 {
         self.0 & 0x1
     }
@@ -72,9 +86,9 @@ impl PteAttr {
         (self.0 >> 4) & 0x1
     }
 
-    // pub fn g(&self) -> u8 {
-    //     (self.0 >> 5) & 0x1
-    // }
+    pub const fn g(&self) -> u8 {
+        (self.0 >> 5) & 0x1
+    }
 
     pub const fn a(&self) -> u8 {
         (self.0 >> 6) & 0x1
@@ -151,6 +165,15 @@ impl Sv32Pte {
     const fn ppn_all(&self) -> RegT {
         (self.0 >> 10) & 0x3fffff
     }
+    const fn n(&self) -> RegT {
+        0
+    }
+    const fn pbmt(&self) -> RegT {
+        0
+    }
+    const fn reserved(&self) -> RegT {
+        0
+    }
     const fn value(&self) -> RegT {
         self.0
     }
@@ -220,6 +243,17 @@ impl Sv39Pte {
     const fn ppn_all(&self) -> RegT {
         (self.0 >> 10) & 0xfff_ffffffff
     }
+    //Svnapot
+    const fn n(&self) -> RegT {
+        self.0 >> 63
+    }
+    //Svpbmt
+    const fn pbmt(&self) -> RegT {
+        (self.0 >> 61) & 0x3
+    }
+    const fn reserved(&self) -> RegT {
+        (self.0 >> 54) & 0x7f
+    }
     const fn value(&self) -> RegT {
         self.0
     }
@@ -292,6 +326,17 @@ impl Sv48Pte {
     const fn ppn_all(&self) -> RegT {
         (self.0 >> 10) & 0xfff_ffffffff
     }
+    //Svnapot
+    const fn n(&self) -> RegT {
+        self.0 >> 63
+    }
+    //Svpbmt
+    const fn pbmt(&self) -> RegT {
+        (self.0 >> 61) & 0x3
+    }
+    const fn reserved(&self) -> RegT {
+        (self.0 >> 54) & 0x7f
+    }
     const fn value(&self) -> RegT {
         self.0
     }
@@ -306,6 +351,86 @@ impl Sv48Pte {
     }
 }
 
+pub struct Sv57Vaddr(RegT);
+
+impl Sv57Vaddr {
+    const fn vpn(&self, level: usize) -> RegT {
+        match level {
+            0 => (self.0 >> 12) & 0x1ff,
+            1 => (self.0 >> 21) & 0x1ff,
+            2 => (self.0 >> 30) & 0x1ff,
+            3 => (self.0 >> 39) & 0x1ff,
+            4 => (self.0 >> 48) & 0x1ff,
+            _ => 0,
+        }
+    }
+    const fn value(&self) -> RegT {
+        self.0
+    }
+    const fn vpn_all(&self) -> RegT {
+        (self.0 >> 12) & 0x1fff_ffffffff
+    }
+    const fn offset(&self) -> RegT {
+        self.0 & 0xfff
+    }
+}
+
+pub struct Sv57Paddr(RegT);
+
+impl Sv57Paddr {
+    fn set_ppn(&mut self, level: usize, ppn: RegT) {
+        match level {
+            0 => self.0 = self.0 & 0xffffffff_ffe00fff | (ppn & 0x1ff) << 12,
+            1 => self.0 = self.0 & 0xffffffff_c01fffff | (ppn & 0x1ff) << 21,
+            2 => self.0 = self.0 & 0xffffff80_3fffffff | (ppn & 0x1ff) << 30,
+            3 => self.0 = self.0 & 0xffff007f_ffffffff | (ppn & 0x1ff) << 39,
+            4 => self.0 = self.0 & 0xff00ffff_ffffffff | (ppn & 0xff) << 48,
+            _ => {}
+        }
+    }
+    const fn value(&self) -> RegT {
+        self.0 & ((1 << 56) - 1)
+    }
+}
+
+pub struct Sv57Pte(RegT);
+
+impl Sv57Pte {
+    const fn ppn(&self, level: usize) -> RegT {
+        match level {
+            0 => (self.0 >> 10) & 0x1ff,
+            1 => (self.0 >> 19) & 0x1ff,
+            2 => (self.0 >> 28) & 0x1ff,
+            3 => (self.0 >> 37) & 0x1ff,
+            4 => (self.0 >> 46) & 0xff,
+            _ => 0,
+        }
+    }
+    const fn ppn_all(&self) -> RegT {
+        (self.0 >> 10) & 0xfff_ffffffff
+    }
+    //Svnapot
+    const fn n(&self) -> RegT {
+        self.0 >> 63
+    }
+    //Svpbmt
+    const fn pbmt(&self) -> RegT {
+        (self.0 >> 61) & 0x3
+    }
+    const fn reserved(&self) -> RegT {
+        (self.0 >> 54) & 0x7f
+    }
+    const fn value(&self) -> RegT {
+        self.0
+    }
+    fn attr(&self) -> PteAttr {
+        PteAttr::from(self.0 as u8)
+    }
+    fn set_attr(&mut self, attr: &PteAttr) {
+        self.0 = self.0 & 0xffffffff_fffffff0 | attr.0 as RegT
+    }
+}
+
 macro_rules! pt_const_export {
     ($name:ident, $vis:vis $method:ident, $rt:ty, $($args:ident : $ty:ty),*) => {
         #[cfg_attr(feature = "no-inline", inline(never))]
@@ -314,6 +439,7 @@ macro_rules! pt_const_export {
                 $name::Sv32(addr) => addr.$method($($args),*),
                 $name::Sv39(addr)  => addr.$method($($args),*),
                 $name::Sv48(addr) => addr.$method($($args),*),
+                $name::Sv57(addr) => addr.$method($($args),*),
             }
         }
     };
@@ -324,6 +450,7 @@ macro_rules! pt_const_export {
                 $name::Sv32(addr) => addr.$method(),
                 $name::Sv39(addr)  => addr.$method(),
                 $name::Sv48(addr) => addr.$method(),
+                $name::Sv57(addr) => addr.$method(),
             }
         }
     };
@@ -337,6 +464,7 @@ macro_rules! pt_export {
                 $name::Sv32(addr) => addr.$method(),
                 $name::Sv39(addr)  => addr.$method(),
                 $name::Sv48(addr) => addr.$method(),
+                $name::Sv57(addr) => addr.$method(),
             }
         }
     };
@@ -346,6 +474,7 @@ pub enum Vaddr {
     Sv32(Sv32Vaddr),
     Sv39(Sv39Vaddr),
     Sv48(Sv48Vaddr),
+    Sv57(Sv57Vaddr),
 }
 
 impl Vaddr {
@@ -355,6 +484,7 @@ impl Vaddr {
             PTE_SV32 => Vaddr::Sv32(Sv32Vaddr(addr)),
             PTE_SV39 => Vaddr::Sv39(Sv39Vaddr(addr)),
             PTE_SV48 => Vaddr::Sv48(Sv48Vaddr(addr)),
+            PTE_SV57 => Vaddr::Sv57(Sv57Vaddr(addr)),
             _ => panic!("unsupported PteMode {:?}", mode),
         }
     }
@@ -368,6 +498,7 @@ pub enum Paddr {
     Sv32(Sv32Paddr),
     Sv39(Sv39Paddr),
     Sv48(Sv48Paddr),
+    Sv57(Sv57Paddr),
 }
 
 impl Paddr {
@@ -376,6 +507,7 @@ impl Paddr {
             Vaddr::Sv32(addr) => Paddr::Sv32(Sv32Paddr(addr.vpn_all() << 12 | addr.offset())),
             Vaddr::Sv39(addr) => Paddr::Sv39(Sv39Paddr(addr.vpn_all() << 12 | addr.offset())),
             Vaddr::Sv48(addr) => Paddr::Sv48(Sv48Paddr(addr.vpn_all() << 12 | addr.offset())),
+            Vaddr::Sv57(addr) => Paddr::Sv57(Sv57Paddr(addr.vpn_all() << 12 | addr.offset())),
         };
         for i in level..info.level {
             pa.set_ppn(i, pte.ppn(i))
@@ -389,6 +521,7 @@ impl Paddr {
             Paddr::Sv32(addr) => addr.set_ppn(level, ppn),
             Paddr::Sv39(addr) => addr.set_ppn(level, ppn),
             Paddr::Sv48(addr) => addr.set_ppn(level, ppn),
+            Paddr::Sv57(addr) => addr.set_ppn(level, ppn),
         }
     }
 }
@@ -397,6 +530,7 @@ pub enum Pte {
     Sv32(Sv32Pte),
     Sv39(Sv39Pte),
     Sv48(Sv48Pte),
+    Sv57(Sv57Pte),
 }
 
 impl Pte {
@@ -405,6 +539,7 @@ impl Pte {
             PTE_SV32 => Pte::Sv32(Sv32Pte(value)),
             PTE_SV39 => Pte::Sv39(Sv39Pte(value)),
             PTE_SV48 => Pte::Sv48(Sv48Pte(value)),
+            PTE_SV57 => Pte::Sv57(Sv57Pte(value)),
             _ => panic!("unsupported PteMode {:?}", mode),
         }
     }
@@ -434,6 +569,9 @@ impl Pte {
 
     pt_const_export!(Pte, pub ppn, RegT, level:usize);
     pt_const_export!(Pte, pub ppn_all, RegT);
+    pt_const_export!(Pte, pub n, RegT);
+    pt_const_export!(Pte, pub pbmt, RegT);
+    pt_const_export!(Pte, pub reserved, RegT);
     pt_export!(Pte, pub attr, PteAttr);
     pt_const_export!(Pte, pub value, RegT);
 
@@ -442,6 +580,7 @@ impl Pte {
             Pte::Sv32(addr) => addr.set_attr(attr),
             Pte::Sv39(addr) => addr.set_attr(attr),
             Pte::Sv48(addr) => addr.set_attr(attr),
+            Pte::Sv57(addr) => addr.set_attr(attr),
         }
     }
 }
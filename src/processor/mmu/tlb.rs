@@ -1,51 +1,256 @@
-#[derive(Default, Copy, Clone)]
-struct TLBEntry {
-    valid: bool,
-    vpn: u64,
-    ppn: u64,
+use super::pte::PteAttr;
+
+const TLB_SETS: usize = 64;
+const TLB_WAYS: usize = 4;
+
+//entries are filled per 4KiB page, mask covers the vpn bits of the whole leaf page,
+//so that sfence.vma with an address hitting any part of a superpage flushes all of it
+#[derive(Copy, Clone)]
+pub struct TLBEntry {
+    pub valid: bool,
+    //guest translation, tagged with vmid
+    pub virt: bool,
+    pub vmid: u16,
+    pub vpn: u64,
+    pub mask: u64,
+    pub asid: u16,
+    pub global: bool,
+    pub ppn: u64,
+    //U/R/W/X/A/D of the leaf pte, None if the stage is bare
+    pub attr: Option<PteAttr>,
+    pub g_attr: Option<PteAttr>,
+    //privilege/MXR/SUM context of the last permission check passed
+    pub ctx: u8,
+}
+
+impl TLBEntry {
+    fn hit(&self, virt: bool, vmid: u16, vpn: u64, asid: u16) -> bool {
+        self.valid
+            && self.vpn == vpn
+            && self.virt == virt
+            && self.vmid == vmid
+            && (self.global || self.asid == asid)
+    }
+
+    fn flush_match(
+        &self,
+        virt: bool,
+        vmid: Option<u16>,
+        vpn: Option<u64>,
+        asid: Option<u16>,
+    ) -> bool {
+        let vmid_match = vmid.map_or(true, |vmid| self.vmid == vmid);
+        let vpn_match = vpn.map_or(true, |vpn| (vpn ^ self.vpn) & !self.mask == 0);
+        //global mappings are not flushed by asid
+        let asid_match = asid.map_or(true, |asid| !self.global && self.asid == asid);
+        self.virt == virt && vmid_match && vpn_match && asid_match
+    }
+}
+
+struct TLBSet {
+    ptr: usize,
+    entries: [TLBEntry; TLB_WAYS],
 }
 
 pub struct TLB {
-    entries: [TLBEntry; 256],
-    size: usize,
+    sets: [TLBSet; TLB_SETS],
 }
 
 impl TLB {
     pub fn new() -> TLB {
+        const SET: TLBSet = TLBSet {
+            ptr: 0,
+            entries: [TLBEntry {
+                valid: false,
+                virt: false,
+                vmid: 0,
+                vpn: 0,
+                mask: 0,
+                asid: 0,
+                global: false,
+                ppn: 0,
+                attr: None,
+                g_attr: None,
+                ctx: 0,
+            }; TLB_WAYS],
+        };
         TLB {
-            entries: [TLBEntry::default(); 256],
-            size: 256,
+            sets: [SET; TLB_SETS],
         }
     }
+
+    fn set_mut(&mut self, vpn: u64) -> &mut TLBSet {
+        unsafe { self.sets.get_unchecked_mut((vpn as usize) & (TLB_SETS - 1)) }
+    }
     #[cfg_attr(feature = "no-inline", inline(never))]
-    pub fn get_ppn(&self, vpn: u64) -> Option<&u64> {
-        let e = unsafe { self.entries.get_unchecked((vpn as usize) & (self.size - 1)) };
-        if e.valid && e.vpn == vpn {
-            Some(&e.ppn)
-        } else {
-            None
-        }
+    pub fn get_entry(
+        &mut self,
+        virt: bool,
+        vmid: u16,
+        vpn: u64,
+        asid: u16,
+    ) -> Option<&mut TLBEntry> {
+        self.set_mut(vpn)
+            .entries
+            .iter_mut()
+            .find(|e| e.hit(virt, vmid, vpn, asid))
     }
     #[cfg_attr(feature = "no-inline", inline(never))]
-    pub fn set_entry(&mut self, vpn: u64, ppn: u64) {
-        let e = unsafe {
-            self.entries
-                .get_unchecked_mut((vpn as usize) & (self.size - 1))
+    pub fn set_entry(&mut self, entry: TLBEntry) {
+        let set = self.set_mut(entry.vpn);
+        //replace the stale one if exists, otherwise round-robin
+        let way = if let Some(way) = set
+            .entries
+            .iter()
+            .position(|e| e.hit(entry.virt, entry.vmid, entry.vpn, entry.asid))
+        {
+            way
+        } else {
+            let way = set.ptr;
+            set.ptr = (way + 1) & (TLB_WAYS - 1);
+            way
         };
-        e.valid = true;
-        e.vpn = vpn;
-        e.ppn = ppn;
+        set.entries[way] = entry;
+    }
+
+    //sfence.vma/hfence semantics, None means all addresses, address spaces or virtual machines
+    pub fn invalid(
+        &mut self,
+        virt: bool,
+        vmid: Option<u16>,
+        vpn: Option<u64>,
+        asid: Option<u16>,
+    ) {
+        self.sets
+            .iter_mut()
+            .flat_map(|s| s.entries.iter_mut())
+            .filter(|e| e.flush_match(virt, vmid, vpn, asid))
+            .for_each(|e| e.valid = false)
     }
 
     pub fn invalid_all(&mut self) {
-        self.entries.iter_mut().for_each(|e| e.valid = false)
+        self.sets
+            .iter_mut()
+            .flat_map(|s| s.entries.iter_mut())
+            .for_each(|e| e.valid = false)
     }
+}
 
-    pub fn invalid_by_vpn(&mut self, vpn: u64) {
-        let e = unsafe {
-            self.entries
-                .get_unchecked_mut((vpn as usize) & (self.size - 1))
-        };
-        e.valid = false;
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(vpn: u64, mask: u64, asid: u16, global: bool) -> TLBEntry {
+        TLBEntry {
+            valid: true,
+            virt: false,
+            vmid: 0,
+            vpn,
+            mask,
+            asid,
+            global,
+            ppn: vpn + 0x8_0000,
+            attr: None,
+            g_attr: None,
+            ctx: 0,
+        }
+    }
+
+    #[test]
+    fn tlb_asid_test() {
+        let mut tlb = TLB::new();
+        tlb.set_entry(entry(0x100, 0, 1, false));
+        tlb.set_entry(entry(0x200, 0, 1, true));
+        assert_eq!(tlb.get_entry(false, 0, 0x100, 1).unwrap().ppn, 0x8_0100);
+        assert!(tlb.get_entry(false, 0, 0x100, 2).is_none());
+        //global mappings hit in every address space
+        assert!(tlb.get_entry(false, 0, 0x200, 2).is_some());
+        //guest translations are tagged separately
+        assert!(tlb.get_entry(true, 0, 0x100, 1).is_none());
+        assert!(tlb.get_entry(true, 0, 0x200, 1).is_none());
+        //the same page in another address space takes another way
+        let mut e = entry(0x100, 0, 2, false);
+        e.ppn = 0x9_0100;
+        tlb.set_entry(e);
+        assert_eq!(tlb.get_entry(false, 0, 0x100, 1).unwrap().ppn, 0x8_0100);
+        assert_eq!(tlb.get_entry(false, 0, 0x100, 2).unwrap().ppn, 0x9_0100);
+    }
+
+    #[test]
+    fn tlb_replace_test() {
+        let mut tlb = TLB::new();
+        //all map to the same set
+        let vpn = |i: usize| (0x40 + i * TLB_SETS) as u64;
+        for i in 0..TLB_WAYS {
+            tlb.set_entry(entry(vpn(i), 0, 1, false));
+        }
+        //refilling a present page replaces it in place
+        let mut e = entry(vpn(1), 0, 1, false);
+        e.ppn = 0x1234;
+        tlb.set_entry(e);
+        for i in 0..TLB_WAYS {
+            assert!(tlb.get_entry(false, 0, vpn(i), 1).is_some());
+        }
+        assert_eq!(tlb.get_entry(false, 0, vpn(1), 1).unwrap().ppn, 0x1234);
+        //round-robin evicts the oldest way
+        tlb.set_entry(entry(vpn(TLB_WAYS), 0, 1, false));
+        assert!(tlb.get_entry(false, 0, vpn(0), 1).is_none());
+        for i in 1..=TLB_WAYS {
+            assert!(tlb.get_entry(false, 0, vpn(i), 1).is_some());
+        }
+    }
+
+    #[test]
+    fn tlb_sfence_test() {
+        let mut tlb = TLB::new();
+        tlb.set_entry(entry(0x100, 0, 1, false));
+        tlb.set_entry(entry(0x101, 0, 1, false));
+        tlb.set_entry(entry(0x102, 0, 2, false));
+        tlb.set_entry(entry(0x103, 0, 1, true));
+
+        //by asid, global entries are kept
+        tlb.invalid(false, None, None, Some(1));
+        assert!(tlb.get_entry(false, 0, 0x100, 1).is_none());
+        assert!(tlb.get_entry(false, 0, 0x101, 1).is_none());
+        assert!(tlb.get_entry(false, 0, 0x102, 2).is_some());
+        assert!(tlb.get_entry(false, 0, 0x103, 1).is_some());
+
+        //by va in all address spaces, including global entries
+        tlb.set_entry(entry(0x100, 0, 1, false));
+        tlb.invalid(false, None, Some(0x103), None);
+        assert!(tlb.get_entry(false, 0, 0x103, 1).is_none());
+        assert!(tlb.get_entry(false, 0, 0x100, 1).is_some());
+
+        //by va and asid
+        tlb.invalid(false, None, Some(0x102), Some(1));
+        assert!(tlb.get_entry(false, 0, 0x102, 2).is_some());
+        tlb.invalid(false, None, Some(0x102), Some(2));
+        assert!(tlb.get_entry(false, 0, 0x102, 2).is_none());
+
+        //guest flushes leave host entries alone
+        tlb.invalid(true, None, None, None);
+        assert!(tlb.get_entry(false, 0, 0x100, 1).is_some());
+        tlb.invalid(false, None, None, None);
+        assert!(tlb.get_entry(false, 0, 0x100, 1).is_none());
+    }
+
+    #[test]
+    fn tlb_mask_test() {
+        let mut tlb = TLB::new();
+        //4KiB pieces of a 64KiB napot page and of a 2MiB superpage
+        tlb.set_entry(entry(0x12345, 0xf, 1, false));
+        tlb.set_entry(entry(0x12355, 0xf, 1, false));
+        tlb.set_entry(entry(0x40123, 0x1ff, 1, false));
+        //filled per 4KiB page, other parts of the leaf page miss
+        assert!(tlb.get_entry(false, 0, 0x12346, 1).is_none());
+
+        //any address in the leaf page flushes the whole of it
+        tlb.invalid(false, None, Some(0x12340), Some(1));
+        assert!(tlb.get_entry(false, 0, 0x12345, 1).is_none());
+        assert!(tlb.get_entry(false, 0, 0x12355, 1).is_some());
+        tlb.invalid(false, None, Some(0x401ff), None);
+        assert!(tlb.get_entry(false, 0, 0x40123, 1).is_none());
+        tlb.invalid(false, None, Some(0x12340), None);
+        assert!(tlb.get_entry(false, 0, 0x12355, 1).is_some());
     }
 }
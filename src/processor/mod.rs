@@ -1,8 +1,13 @@
 use crate::devices::bus::Bus;
+use crate::devices::clint::HartTimer;
+use crate::devices::imsic::HartImsic;
 use crate::prelude::*;
+use crate::snapshot::{snapshot_err, SnapshotReader, SnapshotWriter};
+use num_enum::IntoPrimitive;
 use std::cell::RefCell;
 use std::fmt::{Display, Formatter};
-use std::io::Write;
+use std::io;
+use std::io::{Read, Write};
 use std::mem::MaybeUninit;
 use std::rc::Rc;
 use terminus_spaceport::irq::IrqVec;
@@ -14,10 +19,28 @@ pub mod trap;
 
 use trap::{Exception, Interrupt, Trap};
 
+pub mod trigger;
+
+use trigger::*;
+
+pub mod hpm;
+
+use hpm::*;
+
+pub mod aia;
+
+use aia::*;
+
+pub mod isa;
+
+use isa::*;
+
 pub mod extensions;
 
 use extensions::*;
 
+use extensions::f::{FLen, FRegT};
+
 mod mmu;
 
 use mmu::*;
@@ -44,32 +67,81 @@ trait NoCsr {
     }
 }
 
+//satp.mode encoding
+#[derive(IntoPrimitive, Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+#[repr(u8)]
+pub enum SatpMode {
+    Sv32 = 1,
+    Sv39 = 8,
+    Sv48 = 9,
+    Sv57 = 10,
+}
+
+impl SatpMode {
+    pub const fn xlen(&self) -> XLen {
+        match self {
+            SatpMode::Sv32 => XLen::X32,
+            _ => XLen::X64,
+        }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            SatpMode::Sv32 => "sv32",
+            SatpMode::Sv39 => "sv39",
+            SatpMode::Sv48 => "sv48",
+            SatpMode::Sv57 => "sv57",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ProcessorCfg {
+    //must match the base of isa
     pub xlen: XLen,
     pub enable_dirty: bool,
-    pub extensions: Box<[char]>,
+    //isa string, e.g. "rv64imafdcsu_zicsr_zifencei_zba_zbb"
+    pub isa: Isa,
     pub freq: usize,
+    pub triggers: usize,
+    //writing other modes except bare to satp.mode is ignored
+    pub satp_modes: Box<[SatpMode]>,
+    //vector register length and max element length in bits, only used by 'v' extension
+    pub vlen: usize,
+    pub elen: usize,
+    //cache block size in bytes, only used by zicbom and zicboz
+    pub cache_block_size: usize,
+    //initial state of the entropy source of zkr seed csr, mixed with hartid
+    pub entropy_seed: u64,
+    //number of programmable mhpmcounters, only used by zihpm
+    pub hpm_counters: usize,
 }
 
 pub struct ProcessorState {
     hartid: usize,
     config: ProcessorCfg,
     privilege: PrivilegeStates,
+    triggers: Triggers,
+    hpm: Hpm,
+    aia: Aia,
     xreg: [RegT; 32],
     extensions: [Extension; 26],
     pc: RegT,
     next_pc: RegT,
     ir: InsnT,
     insns_cnt: Rc<RefCell<u64>>,
+    //steps taken out of debug mode, including the ones waiting in wfi
+    steps_cnt: Rc<RefCell<u64>>,
     clint: Option<IrqVec>,
     plic: Option<IrqVec>,
+    timer: Option<HartTimer>,
+    imsic: Option<HartImsic>,
     wfi: bool,
 }
 
 impl ProcessorState {
     pub fn trace(&self) -> String {
-        format!("hartid = {}; privilege = {:?};pc = {:#x}; ir = {:#x}; next_pc = {:#x}; insns_cnt = {};", self.hartid, self.privilege(), self.pc(), *self.ir(), self.next_pc(), *self.insns_cnt().borrow())
+        format!("hartid = {}; privilege = {:?}; virt = {};pc = {:#x}; ir = {:#x}; next_pc = {:#x}; insns_cnt = {};", self.hartid, self.privilege(), self.virt(), self.pc(), *self.ir(), self.next_pc(), *self.insns_cnt().borrow())
     }
 }
 
@@ -91,6 +163,17 @@ impl Display for ProcessorState {
                 writeln!(f, "   f{:<2} : {:#x}", i, v)?;
             }
         }
+        if let Extension::V(ref vector) = self.get_extension('v') {
+            for i in 0..32 {
+                let value = vector
+                    .vreg(i)
+                    .iter()
+                    .rev()
+                    .map(|b| format!("{:02x}", b))
+                    .collect::<String>();
+                writeln!(f, "   v{:<2} : 0x{}", i, value)?;
+            }
+        }
         writeln!(f, "")?;
         Ok(())
     }
@@ -104,10 +187,25 @@ impl ProcessorState {
         plic: Option<IrqVec>,
     ) -> ProcessorState {
         let privilege = PrivilegeStates::new(&config);
+        let triggers = Triggers::new(config.xlen, config.triggers);
+        let hpm = Hpm::new(
+            config.xlen,
+            config.hpm_counters,
+            config.isa.has_ext(IsaExt::Zihpm),
+            config.isa.has_ext(IsaExt::Sscofpmf),
+        );
+        let aia = Aia::new(
+            config.xlen,
+            config.isa.has_ext(IsaExt::Smaia),
+            config.isa.has_ext(IsaExt::Ssaia),
+        );
         let mut state = ProcessorState {
             hartid,
             config,
             privilege,
+            triggers,
+            hpm,
+            aia,
             xreg: [0 as RegT; 32],
             extensions: unsafe {
                 let mut arr: MaybeUninit<[Extension; 26]> = MaybeUninit::uninit();
@@ -122,12 +220,19 @@ impl ProcessorState {
             next_pc: 0,
             ir: 0,
             insns_cnt: Rc::new(RefCell::new(0)),
+            steps_cnt: Rc::new(RefCell::new(0)),
             clint,
             plic,
+            timer: None,
+            imsic: None,
             wfi: false,
         };
+        state.check_isa().expect("isa error!");
         state.add_extension().expect("add extension error!");
-        state.privilege.delegate_insns_cnt(state.insns_cnt());
+        state.check_satp_modes().expect("satp modes error!");
+        state.check_vector_cfg().expect("vector config error!");
+        state.check_cbo_cfg().expect("cache block config error!");
+        state.check_hpm_cfg().expect("hpm config error!");
         state
     }
 
@@ -146,21 +251,136 @@ impl ProcessorState {
         if let Some(ref clint) = self.clint {
             self.privilege.delegate_si_ti(clint);
         }
-        if let Some(ref pilc) = self.plic {
-            self.privilege.delegate_ei(pilc);
+        if self.plic.is_some() || self.imsic.is_some() {
+            self.privilege
+                .delegate_ei(self.plic.as_ref(), self.imsic.as_ref());
+        }
+        if let Some(ref timer) = self.timer {
+            self.privilege.delegate_timer(timer, &self.config);
         }
         self.privilege.init_isa(self.hartid as RegT, self.config());
         Ok(())
     }
 
-    fn add_extension(&mut self) -> Result<(), String> {
-        let exts = self
+    fn check_satp_modes(&self) -> Result<(), String> {
+        if let Some(mode) = self
             .config()
-            .extensions
+            .satp_modes
             .iter()
-            .filter(|&e| *e != 'i')
-            .map(|e| *e)
+            .find(|m| m.xlen() != self.config().xlen)
+        {
+            return Err(format!(
+                "cpu{}:satp mode {:?} is not supported when xlen == {:?}!",
+                self.hartid,
+                mode,
+                self.config().xlen
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_isa(&self) -> Result<(), String> {
+        if self.config().isa.xlen() != self.config().xlen {
+            return Err(format!(
+                "cpu{}:isa {} mismatch with xlen == {:?}!",
+                self.hartid,
+                self.config().isa,
+                self.config().xlen
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_vector_cfg(&self) -> Result<(), String> {
+        if !self.config().isa.has('v') {
+            return Ok(());
+        }
+        let (vlen, elen) = (self.config().vlen, self.config().elen);
+        if elen != 32 && elen != 64 {
+            return Err(format!("cpu{}:elen {} is not 32 or 64!", self.hartid, elen));
+        }
+        if !vlen.is_power_of_two() || vlen < elen || vlen > 65536 {
+            return Err(format!(
+                "cpu{}:vlen {} must be a power of 2 in [elen, 65536]!",
+                self.hartid, vlen
+            ));
+        }
+        if let Some(zvl) = self.config().isa.zvl() {
+            if vlen < zvl {
+                return Err(format!(
+                    "cpu{}:vlen {} is less than zvl{}b!",
+                    self.hartid, vlen, zvl
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_cbo_cfg(&self) -> Result<(), String> {
+        let size = self.config().cache_block_size;
+        if !size.is_power_of_two() || size < 8 || size > 4096 {
+            return Err(format!(
+                "cpu{}:cache block size {} must be a power of 2 in [8, 4096]!",
+                self.hartid, size
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_hpm_cfg(&self) -> Result<(), String> {
+        if self.config().hpm_counters > MAX_HPM_COUNTERS {
+            return Err(format!(
+                "cpu{}:hpm counters {} is more than {}!",
+                self.hartid,
+                self.config().hpm_counters,
+                MAX_HPM_COUNTERS
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn satp_mode_supported(&self, mode: RegT) -> bool {
+        mode == 0
+            || self
+                .config()
+                .satp_modes
+                .iter()
+                .any(|m| u8::from(*m) as RegT == mode)
+    }
+
+    fn add_extension(&mut self) -> Result<(), String> {
+        let isa = &self.config().isa;
+        let mut exts = ('a'..='z')
+            .filter(|&e| e != 'i' && e != 'b' && e != 'c' && isa.has(e))
             .collect::<Vec<char>>();
+        //'c' extension implements zca, zcb, zcd, zcf, zcmp and zcmt
+        if isa.has_ext(IsaExt::Zca) {
+            exts.push('c');
+        }
+        //'b' extension implements any of zba, zbb, zbc and zbs
+        if [IsaExt::Zba, IsaExt::Zbb, IsaExt::Zbc, IsaExt::Zbs]
+            .iter()
+            .any(|e| isa.has_ext(*e))
+        {
+            exts.push('b');
+        }
+        //'k' extension implements scalar cryptography, zkt has nothing to implement
+        if [
+            IsaExt::Zbkb,
+            IsaExt::Zbkc,
+            IsaExt::Zbkx,
+            IsaExt::Zknd,
+            IsaExt::Zkne,
+            IsaExt::Zknh,
+            IsaExt::Zkr,
+            IsaExt::Zksed,
+            IsaExt::Zksh,
+        ]
+        .iter()
+        .any(|e| isa.has_ext(*e))
+        {
+            exts.push('k');
+        }
         let mut add_one_extension = |id: char| -> Result<(), String> {
             let ext = Extension::new(self, id)?;
             self.extensions[(id as u8 - 'a' as u8) as usize] = ext;
@@ -202,8 +422,7 @@ impl ProcessorState {
     }
 
     pub fn isa_string(&self) -> String {
-        let exts: String = self.config().extensions.iter().collect();
-        format!("rv{}{}", self.config().xlen.len(), exts)
+        self.config().isa.to_string()
     }
 
     pub const fn config(&self) -> &ProcessorCfg {
@@ -214,14 +433,55 @@ impl ProcessorState {
         self.hartid
     }
 
+    pub fn timer(&self) -> Option<&HartTimer> {
+        self.timer.as_ref()
+    }
+
+    pub fn hpm(&self) -> &Hpm {
+        &self.hpm
+    }
+
+    pub fn imsic(&self) -> Option<&HartImsic> {
+        self.imsic.as_ref()
+    }
+
+    //overflow of sscofpmf raises local counter overflow interrupt
+    pub fn hpm_event(&self, event: HpmEvent) {
+        if self.hpm.count(event, self.privilege(), self.virt()) {
+            self.priv_m().mip_mut().set_lcofip(1);
+        }
+    }
+
+    fn hpm_retire(&self) {
+        let len = if *self.ir() & 0x3 == 0x3 { 4 } else { 2 };
+        let taken = *self.next_pc() != self.pc().wrapping_add(len);
+        if self
+            .hpm
+            .retire(*self.ir(), taken, self.privilege(), self.virt())
+        {
+            self.priv_m().mip_mut().set_lcofip(1);
+        }
+    }
+
     pub fn csr(&self, id: InsnT) -> Result<RegT, Exception> {
         let trip_id = id & 0xfff;
-        self.privilege
-            .csr_privilege_check(trip_id)
-            .map_err(|_| Exception::IllegalInsn(*self.ir()))?;
+        self.privilege.csr_privilege_check(trip_id, *self.ir())?;
+        self.aia.check_stateen(self, trip_id)?;
         if let Some(v) = self.privilege.csr_read(self, trip_id) {
             return Ok(v);
         }
+        if let Some(v) = self.triggers.csr_read(trip_id) {
+            return Ok(v);
+        }
+        if let Some(v) = self
+            .hpm
+            .csr_read(trip_id, |i| self.privilege.counter_enabled(i))
+        {
+            return Ok(v);
+        }
+        if let Some(v) = self.aia.csr_read(self, trip_id) {
+            return Ok(v);
+        }
         match self
             .extensions()
             .iter()
@@ -234,12 +494,24 @@ impl ProcessorState {
 
     pub fn set_csr(&self, id: InsnT, value: RegT) -> Result<(), Exception> {
         let trip_id = id & 0xfff;
-        self.privilege
-            .csr_privilege_check(trip_id)
-            .map_err(|_| Exception::IllegalInsn(*self.ir()))?;
+        self.privilege.csr_privilege_check(trip_id, *self.ir())?;
+        self.aia.check_stateen(self, trip_id)?;
         if self.privilege.csr_write(self, trip_id, value).is_some() {
             return Ok(());
         }
+        if self
+            .triggers
+            .csr_write(trip_id, value, self.debug_mode())
+            .is_some()
+        {
+            return Ok(());
+        }
+        if self.hpm.csr_write(trip_id, value).is_some() {
+            return Ok(());
+        }
+        if self.aia.csr_write(self, trip_id, value).is_some() {
+            return Ok(());
+        }
         match self
             .extensions()
             .iter()
@@ -250,11 +522,164 @@ impl ProcessorState {
         }
     }
 
+    pub fn debug_csr(&self, id: InsnT) -> Option<RegT> {
+        let trip_id = id & 0xfff;
+        //reading seed consumes entropy, the debugger must not change what the hart reads next
+        if trip_id == 0x015 {
+            return None;
+        }
+        if let Some(v) = self.privilege.csr_read(self, trip_id) {
+            return Some(v);
+        }
+        if let Some(v) = self.triggers.csr_read(trip_id) {
+            return Some(v);
+        }
+        if let Some(v) = self.hpm.csr_read(trip_id, |_| true) {
+            return Some(v);
+        }
+        if let Some(v) = self.aia.csr_read(self, trip_id) {
+            return Some(v);
+        }
+        self.extensions()
+            .iter()
+            .find_map(|e| e.csr_read(self, trip_id))
+    }
+
+    pub fn debug_set_csr(&self, id: InsnT, value: RegT) -> Option<()> {
+        let trip_id = id & 0xfff;
+        if self.privilege.csr_write(self, trip_id, value).is_some() {
+            return Some(());
+        }
+        if self
+            .triggers
+            .csr_write(trip_id, value, self.debug_mode())
+            .is_some()
+        {
+            return Some(());
+        }
+        if self.hpm.csr_write(trip_id, value).is_some() {
+            return Some(());
+        }
+        if self.aia.csr_write(self, trip_id, value).is_some() {
+            return Some(());
+        }
+        self.extensions()
+            .iter()
+            .find_map(|e| e.csr_write(self, trip_id, value))
+    }
+
+    pub fn flen(&self) -> Option<FLen> {
+        if let Extension::F(ref float) = self.get_extension('f') {
+            Some(float.flen)
+        } else {
+            None
+        }
+    }
+
+    pub fn freg(&self, id: InsnT) -> Option<FRegT> {
+        if let Extension::F(ref float) = self.get_extension('f') {
+            Some(*float.freg(id))
+        } else {
+            None
+        }
+    }
+
+    pub fn set_freg(&mut self, id: InsnT, value: FRegT) -> Option<()> {
+        if let Extension::F(ref mut float) = self.get_extension_mut('f') {
+            float.set_freg(id, value);
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn save_snapshot<W: Write>(&self, w: &mut SnapshotWriter<W>) -> io::Result<()> {
+        w.write_str(&self.isa_string())?;
+        w.write_u64(self.hartid as u64)?;
+        for x in self.xreg.iter() {
+            w.write_u64(*x)?;
+        }
+        w.write_u64(self.pc)?;
+        w.write_u64(self.next_pc)?;
+        w.write_u32(self.ir)?;
+        w.write_bool(self.wfi)?;
+        w.write_u64(*self.insns_cnt.borrow())?;
+        w.write_u64(*self.steps_cnt.borrow())?;
+        self.privilege.save_snapshot(w)?;
+        self.triggers.save_snapshot(w)?;
+        self.hpm.save_snapshot(w)?;
+        self.aia.save_snapshot(w)?;
+        if let Extension::A(ref a) = self.get_extension('a') {
+            a.save_snapshot(w)?;
+        }
+        if let Extension::F(ref float) = self.get_extension('f') {
+            float.save_snapshot(w)?;
+        }
+        if let Extension::V(ref vector) = self.get_extension('v') {
+            vector.save_snapshot(w)?;
+        }
+        if let Extension::K(ref k) = self.get_extension('k') {
+            k.save_snapshot(w)?;
+        }
+        if let Extension::C(ref c) = self.get_extension('c') {
+            c.save_snapshot(w)?;
+        }
+        Ok(())
+    }
+
+    fn restore_snapshot<R: Read>(&mut self, r: &mut SnapshotReader<R>) -> io::Result<()> {
+        let isa = r.read_str()?;
+        if isa != self.isa_string() {
+            return snapshot_err(format!(
+                "cpu{}: snapshot isa {} mismatch with {}!",
+                self.hartid,
+                isa,
+                self.isa_string()
+            ));
+        }
+        r.expect_u64(self.hartid as u64, "hartid")?;
+        for x in self.xreg.iter_mut() {
+            *x = r.read_u64()?;
+        }
+        self.pc = r.read_u64()?;
+        self.next_pc = r.read_u64()?;
+        self.ir = r.read_u32()?;
+        self.wfi = r.read_bool()?;
+        *self.insns_cnt.borrow_mut() = r.read_u64()?;
+        *self.steps_cnt.borrow_mut() = r.read_u64()?;
+        self.privilege.restore_snapshot(r)?;
+        self.triggers.restore_snapshot(r)?;
+        self.hpm.restore_snapshot(r)?;
+        self.aia.restore_snapshot(r)?;
+        if let Extension::A(ref a) = self.get_extension('a') {
+            a.restore_snapshot(r)?;
+        }
+        if let Extension::F(ref mut float) = self.get_extension_mut('f') {
+            float.restore_snapshot(r)?;
+        }
+        if let Extension::V(ref vector) = self.get_extension('v') {
+            vector.restore_snapshot(r)?;
+        }
+        if let Extension::K(ref k) = self.get_extension('k') {
+            k.restore_snapshot(r)?;
+        }
+        if let Extension::C(ref c) = self.get_extension('c') {
+            c.restore_snapshot(r)?;
+        }
+        Ok(())
+    }
+
     pub fn check_extension(&self, ext: char) -> Result<(), Exception> {
         self.privilege
             .check_extension(ext)
             .map_err(|_| Exception::IllegalInsn(*self.ir()))
     }
+
+    //state enable bit of smstateen, bit of mstateenN
+    pub fn check_stateen(&self, n: usize, bit: RegT) -> Result<(), Exception> {
+        self.privilege.stateen_check(n, bit, *self.ir())
+    }
+
     pub const fn priv_m(&self) -> &PrivM {
         self.privilege.m()
     }
@@ -263,26 +688,76 @@ impl ProcessorState {
         self.privilege.s().ok_or(Exception::IllegalInsn(*self.ir()))
     }
 
+    pub fn priv_h(&self) -> Result<&PrivH, Exception> {
+        self.privilege.h().ok_or(Exception::IllegalInsn(*self.ir()))
+    }
+
+    //VS or VU mode
+    pub const fn virt(&self) -> bool {
+        self.privilege.virt()
+    }
+
     pub const fn privilege(&self) -> &Privilege {
         self.privilege.cur_privilege()
     }
 
-    pub fn trap_enter(&mut self, code: RegT, int_flag: bool, val: RegT) {
-        let (pc, privilege) = self.privilege.trap_enter(self, code, int_flag, val);
+    pub fn trap_enter(&mut self, code: RegT, int_flag: bool, val: RegT, val2: RegT, gva: bool) {
+        let (pc, privilege, virt) = self
+            .privilege
+            .trap_enter(self, code, int_flag, val, val2, gva);
         self.set_pc(pc);
         self.privilege.set_priv(privilege);
+        self.privilege.set_virt(virt);
     }
 
     pub fn trap_return(&mut self, cur_privilege: &Privilege) {
-        let (pc, privilege) = self.privilege.trap_return(cur_privilege);
+        let (pc, privilege, virt) = self.privilege.trap_return(cur_privilege);
         self.set_pc(pc);
         self.privilege.set_priv(privilege);
+        self.privilege.set_virt(virt);
+    }
+
+    pub const fn debug_mode(&self) -> bool {
+        self.privilege.debug_mode()
+    }
+
+    pub fn debug_enter(&mut self, cause: DebugCause, pc: RegT) {
+        self.privilege.debug_enter(cause, pc);
+        self.set_pc(pc);
+    }
+
+    pub fn debug_return(&mut self) {
+        let pc = self.privilege.debug_return();
+        self.set_pc(pc);
+    }
+
+    #[cfg_attr(feature = "no-inline", inline(never))]
+    pub fn check_triggers(
+        &self,
+        op: TriggerOp,
+        addr: RegT,
+        len: usize,
+        data: RegT,
+    ) -> Result<(), Exception> {
+        if !self.triggers.enabled(&op) || self.debug_mode() {
+            return Ok(());
+        }
+        self.triggers
+            .check(op, self.privilege(), self.virt(), addr, len, data)
     }
 
     pub fn pending_interrupts(&self) -> RegT {
         self.privilege.pending_interrupts()
     }
 
+    pub fn check_isa_ext(&self, ext: IsaExt) -> Result<(), Exception> {
+        if self.config().isa.has_ext(ext) {
+            Ok(())
+        } else {
+            Err(Exception::IllegalInsn(*self.ir()))
+        }
+    }
+
     pub fn check_xlen(&self, xlen: XLen) -> Result<(), Exception> {
         if xlen == self.config().xlen {
             Ok(())
@@ -315,6 +790,10 @@ impl ProcessorState {
         &self.insns_cnt
     }
 
+    pub const fn steps_cnt(&self) -> &Rc<RefCell<u64>> {
+        &self.steps_cnt
+    }
+
     pub fn xreg(&self, id: InsnT) -> &RegT {
         let trip_id = id & 0x1f;
         if trip_id == 0 {
@@ -361,6 +840,16 @@ impl Processor {
         }
     }
 
+    //time and stimecmp csrs are backed by the timer
+    pub fn connect_timer(&mut self, timer: HartTimer) {
+        self.state.timer = Some(timer)
+    }
+
+    //interrupt files of smaia and ssaia
+    pub fn connect_imsic(&mut self, imsic: HartImsic) {
+        self.state.imsic = Some(imsic)
+    }
+
     pub fn reset(&mut self, start_address: u64) -> Result<(), String> {
         self.state.reset(start_address)?;
         self.load_store().release(self.state());
@@ -396,11 +885,13 @@ impl Processor {
         match inst.execute(self) {
             Ok(_) => {
                 *(*self.state.insns_cnt).borrow_mut() += 1;
+                self.state.hpm_retire();
                 Ok(())
             }
             Err(e) => {
                 if e.executed() {
                     *(*self.state.insns_cnt).borrow_mut() += 1;
+                    self.state.hpm_retire();
                 }
                 Err(e)
             }
@@ -414,12 +905,17 @@ impl Processor {
         const SEIP: RegT = 1 << 9;
         const SSIP: RegT = 1 << 1;
         const STIP: RegT = 1 << 5;
+        const SGEIP: RegT = 1 << 12;
+        const VSEIP: RegT = 1 << 10;
+        const VSSIP: RegT = 1 << 2;
+        const VSTIP: RegT = 1 << 6;
+        const LCOFIP: RegT = 1 << 13;
 
         let interrupts = self.state().pending_interrupts();
         if interrupts == 0 {
             Ok(())
         } else {
-            // MEI > MSI > MTI > SEI > SSI > STI
+            // MEI > MSI > MTI > SEI > SSI > STI > SGEI > VSEI > VSSI > VSTI > LCOFI
             if interrupts & MEIP != 0 {
                 return Err(Interrupt::MEInt);
             } else if interrupts & MSIP != 0 {
@@ -432,6 +928,16 @@ impl Processor {
                 return Err(Interrupt::SSInt);
             } else if interrupts & STIP != 0 {
                 return Err(Interrupt::STInt);
+            } else if interrupts & SGEIP != 0 {
+                return Err(Interrupt::SGEInt);
+            } else if interrupts & VSEIP != 0 {
+                return Err(Interrupt::VSEInt);
+            } else if interrupts & VSSIP != 0 {
+                return Err(Interrupt::VSSInt);
+            } else if interrupts & VSTIP != 0 {
+                return Err(Interrupt::VSTInt);
+            } else if interrupts & LCOFIP != 0 {
+                return Err(Interrupt::LCOFInt);
             } else {
                 unreachable!()
             }
@@ -439,33 +945,110 @@ impl Processor {
     }
 
     fn handle_trap(&mut self, trap: Trap) {
-        let (int_flag, code, tval) = match trap {
-            Trap::Exception(e) => (false, e.code(), e.tval()),
-            Trap::Interrupt(i) => (true, i.code(), i.tval()),
+        //hypervisor load/store accesses guest memory
+        let hyper = self.mmu().take_hyper_access();
+        if let Trap::Exception(Exception::Breakpoint) = trap {
+            let cause = if self.state().triggers.take_debug_pending() {
+                Some(DebugCause::Trigger)
+            } else if self.state().privilege.ebreak_to_debug() {
+                Some(DebugCause::Ebreak)
+            } else {
+                None
+            };
+            if let Some(cause) = cause {
+                let pc = *self.state().pc();
+                self.state_mut().debug_enter(cause, pc);
+                self.mmu().flush_tlb();
+                self.fetcher().flush_icache();
+                return;
+            }
+        }
+        let (int_flag, code, tval, tval2, gva) = match trap {
+            Trap::Exception(e) => (
+                false,
+                e.code(),
+                e.tval(),
+                e.tval2(),
+                e.has_vaddr() && (self.state().virt() || hyper),
+            ),
+            Trap::Interrupt(i) => (true, i.code(), i.tval(), 0, false),
         };
-        self.state_mut().trap_enter(code, int_flag, tval);
-        self.mmu().flush_tlb();
-        self.fetcher().flush_icache();
+        self.state().hpm_event(if int_flag {
+            HpmEvent::Interrupt
+        } else {
+            HpmEvent::Exception
+        });
+        self.state_mut().trap_enter(code, int_flag, tval, tval2, gva);
     }
 
-    fn execute_one(&mut self) -> Result<(), Trap> {
-        self.take_interrupt()?;
+    fn execute_one(&mut self, stepping: bool) -> Result<(), Trap> {
+        if !stepping || self.state().priv_m().dcsr().stepie() == 1 {
+            self.take_interrupt()?;
+        }
         self.one_insn()?;
         Ok(())
     }
 
+    //wrs of zawrs sleeps like wfi until the lr reservation is lost
+    fn wrs_wakeup(&self) -> bool {
+        if let Extension::A(ref a) = self.state().get_extension('a') {
+            a.wrs_wakeup(self)
+        } else {
+            false
+        }
+    }
+
     fn one_step(&mut self) {
+        //halted
+        if self.state().debug_mode() {
+            return;
+        }
+        *(*self.state.steps_cnt).borrow_mut() += 1;
+        self.state().hpm.tick();
+        let stepping = self.state().priv_m().dcsr().step() == 1;
         if self.state().wfi() {
             let m = self.state().priv_m();
-            if m.mip().get() & m.mie().get() == 0 {
+            if m.mip().get() & m.mie().get() == 0 && !stepping && !self.wrs_wakeup() {
                 return;
             } else {
                 self.state_mut().set_wfi(false)
             }
         }
-        if let Err(trap) = self.execute_one() {
+        if let Err(trap) = self.execute_one(stepping) {
             self.handle_trap(trap)
         }
+        if stepping && !self.state().debug_mode() {
+            self.state_mut().set_wfi(false);
+            let pc = *self.state().next_pc();
+            self.state_mut().debug_enter(DebugCause::Step, pc);
+        }
+    }
+
+    pub fn debug_halt(&mut self) {
+        if !self.state().debug_mode() {
+            self.state_mut().set_wfi(false);
+            let pc = *self.state().next_pc();
+            self.state_mut().debug_enter(DebugCause::HaltReq, pc);
+        }
+    }
+
+    pub fn debug_resume(&mut self) {
+        if self.state().debug_mode() {
+            self.state_mut().debug_return();
+            self.mmu().flush_tlb();
+            self.fetcher().flush_icache();
+        }
+    }
+
+    pub fn debug_step(&mut self) {
+        if !self.state().debug_mode() {
+            return;
+        }
+        let step = self.state().priv_m().dcsr().step();
+        self.state().priv_m().dcsr_mut().set_step(1);
+        self.debug_resume();
+        self.step(1);
+        self.state().priv_m().dcsr_mut().set_step(step);
     }
 
     pub fn step(&mut self, n: usize) {
@@ -480,6 +1063,21 @@ impl Processor {
         }
     }
 
+    pub fn save_snapshot<W: Write>(&self, w: &mut SnapshotWriter<W>) -> io::Result<()> {
+        self.state.save_snapshot(w)
+    }
+
+    pub fn restore_snapshot<R: Read>(&mut self, r: &mut SnapshotReader<R>) -> io::Result<()> {
+        self.state.restore_snapshot(r)?;
+        self.mmu.flush_tlb();
+        self.fetcher.flush_icache();
+        Ok(())
+    }
+
+    pub fn debug_translate(&self, va: RegT) -> Option<u64> {
+        self.mmu.debug_translate(self.state(), &va)
+    }
+
     pub fn step_with_debug<O: Write>(
         &mut self,
         n: usize,
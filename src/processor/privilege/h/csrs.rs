@@ -0,0 +1,153 @@
+use crate::prelude::*;
+use crate::processor::privilege::m::csrs::*;
+use crate::processor::privilege::s::csrs::*;
+csr_map! {
+pub HCsrs(0x0, 0xfff) {
+    vsstatus(RW):Status, 0x200;
+    vsie(RW):Sie, 0x204;
+    vstvec(RW):Tvec, 0x205;
+    vsscratch(RW):Scratch, 0x240;
+    vsepc(RW):Epc, 0x241;
+    vscause(RW):Cause, 0x242;
+    vstval(RW):Tval, 0x243;
+    vsip(RW):Sip, 0x244;
+    vsatp(RW):Satp, 0x280;
+    mtinst(RW):Tinst, 0x34A;
+    mtval2(RW):Tval, 0x34B;
+    hstatus(RW):Hstatus, 0x600;
+    hedeleg(RW):Hedeleg, 0x602;
+    hideleg(RW):Hideleg, 0x603;
+    hie(RW):Hie, 0x604;
+    htimedelta(RW):Htimedelta, 0x605;
+    hcounteren(RW):Counteren, 0x606;
+    hgeie(RW):Hgeie, 0x607;
+    henvcfg(RW):Envcfg, 0x60A;
+    hstateen0(RW):Mstateen0, 0x60C;
+    hstateen1(RW):Mstateen, 0x60D;
+    hstateen2(RW):Mstateen, 0x60E;
+    hstateen3(RW):Mstateen, 0x60F;
+    htimedeltah(RW):Htimedelta, 0x615;
+    htval(RW):Tval, 0x643;
+    hip(RW):Hip, 0x644;
+    hvip(RW):Hvip, 0x645;
+    htinst(RW):Tinst, 0x64A;
+    hgatp(RW):Hgatp, 0x680;
+    hgeip(RO):Hgeie, 0xE12;
+}
+}
+
+define_csr! {
+Hstatus {
+    fields {
+        vsbe(RO):5, 5;
+        gva(RW):6, 6;
+        spv(RW):7, 7;
+        spvp(RW):8, 8;
+        hu(RW):9, 9;
+        vgein(RO):17, 12;
+        vtvm(RW):20, 20;
+        vtw(RW):21, 21;
+        vtsr(RW):22, 22;
+    },
+    fields64 {
+        vsxl(RO):33, 32;
+    },
+}
+}
+
+//exceptions from VS/VU mode, guest page faults and virtual instruction can not be delegated
+define_csr! {
+Hedeleg {
+    fields {
+        fetch_misaligned(RW):0, 0;
+        fetch_access(RW):1, 1;
+        illegal_insn(RW):2, 2;
+        breakpoint(RW):3, 3;
+        load_misaligned(RW):4, 4;
+        load_access(RW):5, 5;
+        store_misaligned(RW):6, 6;
+        store_access(RW):7, 7;
+        ucall(RW):8, 8;
+        fetch_page_fault(RW):12, 12;
+        load_page_fault(RW):13, 13;
+        store_page_fault(RW):15, 15;
+    }
+}
+}
+
+define_csr! {
+Hideleg {
+    fields {
+        vssip(RW):2, 2;
+        vstip(RW):6, 6;
+        vseip(RW):10, 10;
+    }
+}
+}
+
+define_csr! {
+Hie {
+    fields {
+        vssie(RW):2, 2;
+        vstie(RW):6, 6;
+        vseie(RW):10, 10;
+        sgeie(RW):12, 12;
+    }
+}
+}
+
+define_csr! {
+Hip {
+    fields {
+        vssip(RW):2, 2;
+        vstip(RO):6, 6;
+        vseip(RO):10, 10;
+        sgeip(RO):12, 12;
+    }
+}
+}
+
+define_csr! {
+Hvip {
+    fields {
+        vssip(RW):2, 2;
+        vstip(RW):6, 6;
+        vseip(RW):10, 10;
+    }
+}
+}
+
+//no guest external interrupt, GEILEN = 0
+define_csr! {
+Hgeie {
+    fields32 {
+        geie(RO):31, 1;
+    },
+    fields64 {
+        geie(RO):63, 1;
+    },
+}
+}
+
+define_csr! {
+Htimedelta {}
+}
+
+define_csr! {
+Hgatp {
+    fields32 {
+        ppn(RW):21, 0;
+        vmid(RW):28, 22;
+        mode(RW):31, 31;
+    },
+    fields64 {
+        ppn(RW):43, 0;
+        vmid(RW):57, 44;
+        mode(RW):63, 60;
+    }
+}
+}
+
+define_csr! {
+Tinst {}
+}
@@ -0,0 +1,212 @@
+use crate::prelude::XLen;
+use crate::processor::isa::IsaExt;
+use crate::processor::ProcessorCfg;
+use paste::paste;
+use std::ops::Deref;
+use std::rc::Rc;
+pub mod csrs;
+use super::PrivM;
+use csrs::*;
+
+pub struct PrivH {
+    csrs: Rc<HCsrs>,
+}
+
+impl PrivH {
+    pub fn new(cfg: &ProcessorCfg, m: &PrivM) -> PrivH {
+        let h = PrivH {
+            csrs: Rc::new(HCsrs::new(cfg.xlen.len())),
+        };
+        h.csrs.henvcfg_mut().config_cbo(&cfg.isa);
+        //vstimecmp is not supported, stimecmp in VS mode is always a virtual instruction
+        h.csrs.henvcfg_mut().set_stce_transform(|_| 0);
+        //htimedeltah only exists in rv32
+        let rv64 = cfg.xlen != XLen::X32;
+        h.csrs.htimedeltah_mut().get_forbidden(rv64);
+        h.csrs.htimedeltah_mut().set_forbidden(rv64);
+        //henvcfg.pbmte can only be set when menvcfg.pbmte is set
+        h.csrs.henvcfg_mut().set_pbmte_transform({
+            let csrs = (*m).clone();
+            move |pbmte| pbmte & csrs.menvcfg().pbmte()
+        });
+        //hstateen0-3 are only accessible with smstateen, bits clear in mstateen0-3 are read-only zero
+        let smstateen = cfg.isa.has_ext(IsaExt::Smstateen);
+        macro_rules! mask_hstateen {
+            ($n:literal, $($field:ident),+) => {
+                paste! {
+                    h.csrs.[<hstateen $n _mut>]().get_forbidden(!smstateen);
+                    h.csrs.[<hstateen $n _mut>]().set_forbidden(!smstateen);
+                    $(h.csrs.[<hstateen $n _mut>]().[<set_ $field _transform>]({
+                        let csrs = (*m).clone();
+                        move |field| field & csrs.[<mstateen $n>]().$field()
+                    });)+
+                }
+            };
+        }
+        mask_hstateen!(0, jvt, imsic, aia, csrind, envcfg, se0);
+        mask_hstateen!(1, se0);
+        mask_hstateen!(2, se0);
+        mask_hstateen!(3, se0);
+        //vs interrupts are always delegated to HS
+        m.mideleg_mut().set_vssip(1);
+        m.mideleg_mut().set_vstip(1);
+        m.mideleg_mut().set_vseip(1);
+        m.mideleg_mut().set_sgeip(1);
+
+        //vsstatus is the real state of VS mode
+        h.csrs.vsstatus_mut().as_s_priv();
+        h.csrs.vsstatus_mut().sd_transform({
+            let csrs = h.csrs.clone();
+            move |_| {
+                if csrs.vsstatus().fs() == 0x3
+                    || csrs.vsstatus().vs() == 0x3
+                    || csrs.vsstatus().xs() == 0x3
+                {
+                    1
+                } else {
+                    0
+                }
+            }
+        });
+        if cfg.xlen == XLen::X64 {
+            h.csrs.vsstatus_mut().set_uxl(2);
+            h.csrs.hstatus_mut().set_vsxl(2);
+        }
+
+        //deleg hie to mie
+        macro_rules! deleg_hie {
+            ($field:ident) => {
+                paste! {
+                    h.csrs.hie_mut().[<$field _transform>]({
+                    let csrs = (*m).clone();
+                        move |_| {
+                            csrs.mie().$field()
+                        }
+                    });
+                    h.csrs.hie_mut().[<set_ $field _transform>]({
+                    let csrs = (*m).clone();
+                        move |field| {
+                            csrs.mie_mut().[<set_ $field>](field);
+                            0
+                        }
+                    });
+                }
+            };
+        }
+        deleg_hie!(vssie);
+        deleg_hie!(vstie);
+        deleg_hie!(vseie);
+        deleg_hie!(sgeie);
+
+        //deleg hip and hvip to mip
+        macro_rules! deleg_hip {
+            ($csr:ident, $field:ident) => {
+                paste! {
+                    h.csrs.[<$csr _mut>]().[<$field _transform>]({
+                    let csrs = (*m).clone();
+                        move |_| {
+                            csrs.mip().$field()
+                        }
+                    });
+                    h.csrs.[<$csr _mut>]().[<set_ $field _transform>]({
+                    let csrs = (*m).clone();
+                        move |field| {
+                            csrs.mip_mut().[<set_ $field>](field);
+                            0
+                        }
+                    });
+                }
+            };
+        }
+        deleg_hip!(hip, vssip);
+        h.csrs.hip_mut().vstip_transform({
+            let csrs = (*m).clone();
+            move |_| csrs.mip().vstip()
+        });
+        h.csrs.hip_mut().vseip_transform({
+            let csrs = (*m).clone();
+            move |_| csrs.mip().vseip()
+        });
+        deleg_hip!(hvip, vssip);
+        deleg_hip!(hvip, vstip);
+        deleg_hip!(hvip, vseip);
+
+        //vsip and vsie are views of VS bits of mip and mie in S positions
+        macro_rules! deleg_vsip {
+            ($field:ident, $vs_field:ident) => {
+                paste! {
+                    h.csrs.vsip_mut().[<$field _transform>]({
+                    let csrs = (*m).clone();
+                    let h_csrs = h.csrs.clone();
+                        move |_| {
+                            h_csrs.hideleg().$vs_field() & csrs.mip().$vs_field()
+                        }
+                    });
+                }
+            };
+        }
+        h.csrs.vsip_mut().set_ssip_transform({
+            let csrs = (*m).clone();
+            let h_csrs = h.csrs.clone();
+            move |field| {
+                if h_csrs.hideleg().vssip() == 1 {
+                    csrs.mip_mut().set_vssip(field)
+                }
+                0
+            }
+        });
+        deleg_vsip!(ssip, vssip);
+        deleg_vsip!(stip, vstip);
+        deleg_vsip!(seip, vseip);
+        h.csrs.vsip_mut().usip_transform(|_| 0);
+        h.csrs.vsip_mut().utip_transform(|_| 0);
+        h.csrs.vsip_mut().ueip_transform(|_| 0);
+
+        macro_rules! deleg_vsie {
+            ($deleg_field:ident, $field:ident, $vs_field:ident) => {
+                paste! {
+                    h.csrs.vsie_mut().[<$field _transform>]({
+                    let csrs = (*m).clone();
+                    let h_csrs = h.csrs.clone();
+                        move |_| {
+                            h_csrs.hideleg().$deleg_field() & csrs.mie().$vs_field()
+                        }
+                    });
+                    h.csrs.vsie_mut().[<set_ $field _transform>]({
+                    let csrs = (*m).clone();
+                    let h_csrs = h.csrs.clone();
+                        move |field| {
+                            if h_csrs.hideleg().$deleg_field() == 1 {
+                                csrs.mie_mut().[<set_ $vs_field>](field)
+                            }
+                            0
+                        }
+                    });
+                }
+            };
+        }
+        deleg_vsie!(vssip, ssie, vssie);
+        deleg_vsie!(vstip, stie, vstie);
+        deleg_vsie!(vseip, seie, vseie);
+        h.csrs.vsie_mut().usie_transform(|_| 0);
+        h.csrs.vsie_mut().utie_transform(|_| 0);
+        h.csrs.vsie_mut().ueie_transform(|_| 0);
+        h
+    }
+
+    //offset of time in VS and VU mode, htimedeltah holds the high half in rv32
+    pub fn time_delta(&self, xlen: XLen) -> u64 {
+        if xlen == XLen::X32 {
+            self.csrs.htimedelta().get() & 0xffff_ffff | self.csrs.htimedeltah().get() << 32
+        } else {
+            self.csrs.htimedelta().get()
+        }
+    }
+}
+
+impl Deref for PrivH {
+    type Target = Rc<HCsrs>;
+    fn deref(&self) -> &Self::Target {
+        &self.csrs
+    }
+}
@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use crate::processor::isa::{Isa, IsaExt};
 use crate::processor::privilege::Privilege;
 use std::convert::TryFrom;
 csr_map! {
@@ -10,6 +11,18 @@ pub MCsrs(0x0, 0xfff) {
     mie(RW):Mie, 0x304;
     mtvec(RW):Tvec, 0x305;
     mcounteren(RW):Counteren, 0x306;
+    mvien(RW):Mvien, 0x308;
+    mvip(RW):Mvip, 0x309;
+    menvcfg(RW):Envcfg, 0x30A;
+    mstateen0(RW):Mstateen0, 0x30C;
+    mstateen1(RW):Mstateen, 0x30D;
+    mstateen2(RW):Mstateen, 0x30E;
+    mstateen3(RW):Mstateen, 0x30F;
+    menvcfgh(RW):Envcfgh, 0x31A;
+    mstateen0h(RW):Mstateen0h, 0x31C;
+    mstateen1h(RW):Mstateenh, 0x31D;
+    mstateen2h(RW):Mstateenh, 0x31E;
+    mstateen3h(RW):Mstateenh, 0x31F;
     mscratch(RW):Scratch, 0x340;
     mepc(RW):Epc, 0x341;
     mcause(RW):Cause, 0x342;
@@ -35,12 +48,11 @@ pub MCsrs(0x0, 0xfff) {
     pmpaddr13(RW):PmpAddr, 0x3BD;
     pmpaddr14(RW):PmpAddr, 0x3BE;
     pmpaddr15(RW):PmpAddr, 0x3BF;
-    //no debug
-    tselect(RO):Tselect, 0x7A0;
-    mcycle(RO):Cycle, 0xB00;
-    minstret(RO):Instret, 0xB02;
-    mcycleh(RO):Cycle, 0xB80;
-    minstreth(RO):Instret, 0xB82;
+    mseccfg(RW):Mseccfg, 0x747;
+    dcsr(RW):Dcsr, 0x7B0;
+    dpc(RW):Epc, 0x7B1;
+    dscratch0(RW):Scratch, 0x7B2;
+    dscratch1(RW):Scratch, 0x7B3;
     mvendorid(RO):Mvendorid, 0xF11;
     marchid(RO):Marchid, 0xF12;
     mimpid(RO):Mimpid, 0xF13;
@@ -112,6 +124,7 @@ Status {
          spie(RW): 5, 5;
          mpie(RW): 7, 7;
          spp(RW): 8, 8;
+         vs(RW): 10, 9;
          mpp(RW): 12, 11;
          fs(RW): 14, 13;
          xs(RW): 16, 15;
@@ -128,6 +141,8 @@ Status {
     fields64 {
          uxl(RO): 33, 32;
          sxl(RO): 35,34;
+         gva(RW): 38, 38;
+         mpv(RW): 39, 39;
          sd(RW): 63, 63;
     },
 }
@@ -143,6 +158,8 @@ impl Status {
         self.tw_transform(|_| 0);
         self.tsr_transform(|_| 0);
         self.sxl_transform(|_| 0);
+        self.gva_transform(|_| 0);
+        self.mpv_transform(|_| 0);
     }
 
     pub fn push_privilege(&mut self, tgt_p: &Privilege, cur_p: &Privilege) {
@@ -275,13 +292,18 @@ Mideleg {
     fields{
         usip(RW):0,0;
         ssip(RW):1,1;
+        vssip(RO):2,2;
         msip(RW):3,3;
         utip(RW):4,4;
         stip(RW):5,5;
+        vstip(RO):6,6;
         mtip(RW):7,7;
         ueip(RW):8,8;
         seip(RW):9,9;
+        vseip(RO):10,10;
         meip(RW):11,11;
+        sgeip(RO):12,12;
+        lcofip(RW):13,13;
     }
 }
 }
@@ -291,13 +313,34 @@ Mip {
     fields{
         usip(RW):0,0;
         ssip(RW):1,1;
+        vssip(RW):2,2;
         msip(RO):3,3;
         utip(RW):4,4;
         stip(RW):5,5;
+        vstip(RO):6,6;
         mtip(RO):7,7;
         ueip(RW):8,8;
         seip(RW):9,9;
+        vseip(RO):10,10;
         meip(RO):11,11;
+        sgeip(RO):12,12;
+        lcofip(RW):13,13;
+    }
+}
+}
+
+//virtual supervisor interrupts of smaia are not supported
+define_csr! {
+Mvien {
+}
+}
+
+define_csr! {
+Mvip {
+    fields{
+        ssip(RW):1,1;
+        stip(RW):5,5;
+        seip(RW):9,9;
     }
 }
 }
@@ -307,13 +350,18 @@ Mie {
     fields{
         usie(RW):0,0;
         ssie(RW):1,1;
+        vssie(RW):2,2;
         msie(RW):3,3;
         utie(RW):4,4;
         stie(RW):5,5;
+        vstie(RW):6,6;
         mtie(RW):7,7;
         ueie(RW):8,8;
         seie(RW):9,9;
+        vseie(RW):10,10;
         meie(RW):11,11;
+        sgeie(RW):12,12;
+        lcofie(RW):13,13;
     }
 }
 }
@@ -324,13 +372,12 @@ Epc {}
 
 define_csr! {
 Cause {
-    fields {
-       code(RW):3,0;
-    },
     fields32{
+        code(RW):30,0;
         int(RW):31,31;
     },
     fields64{
+        code(RW):62,0;
         int(RW):63,63;
     }
 }
@@ -352,36 +399,217 @@ Scratch {}
 }
 
 define_csr! {
-Tselect {}
+Dcsr {
+    fields {
+        prv(RW):1, 0;
+        step(RW):2, 2;
+        nmip(RO):3, 3;
+        v(RW):5, 5;
+        mprven(RW):4, 4;
+        cause(RO):8, 6;
+        stoptime(RO):9, 9;
+        stopcount(RO):10, 10;
+        stepie(RW):11, 11;
+        ebreaku(RW):12, 12;
+        ebreaks(RW):13, 13;
+        ebreakm(RW):15, 15;
+        ebreakvu(RW):16, 16;
+        ebreakvs(RW):17, 17;
+        xdebugver(RO):31, 28;
+    },
+}
 }
 
 define_csr! {
 Counteren {
     fields {
        cy(RW):0, 0;
+       tm(RW):1, 1;
        ir(RW):2, 2;
+       hpm(RW):31, 3;
+    },
+}
+}
+
+define_csr! {
+Envcfg {
+    fields {
+       fiom(RW):0, 0;
+       cbie(RW):5, 4;
+       cbcfe(RW):6, 6;
+       cbze(RW):7, 7;
     },
+    fields32 {
+    },
+    fields64 {
+       pbmte(RW):62, 62;
+       stce(RW):63, 63;
+    },
+}
+}
+
+impl Envcfg {
+    //cbie and cbcfe are hardwired to 0 without zicbom, cbze without zicboz
+    pub fn config_cbo(&mut self, isa: &Isa) {
+        if isa.has_ext(IsaExt::Zicbom) {
+            //cbie = 2 is reserved
+            self.set_cbie_transform(|cbie| if cbie == 2 { 0 } else { cbie });
+        } else {
+            self.set_cbie_transform(|_| 0);
+            self.set_cbcfe_transform(|_| 0);
+        }
+        if !isa.has_ext(IsaExt::Zicboz) {
+            self.set_cbze_transform(|_| 0);
+        }
+    }
+
+    //stce is hardwired to 0 without sstc, only used in rv64
+    pub fn config_stce(&mut self, isa: &Isa) {
+        if !isa.has_ext(IsaExt::Sstc) {
+            self.set_stce_transform(|_| 0);
+        }
+    }
+
+    //pbmte is hardwired to 0 without svpbmt, only used in rv64
+    pub fn config_pbmte(&mut self, isa: &Isa) {
+        if !isa.has_ext(IsaExt::Svpbmt) {
+            self.set_pbmte_transform(|_| 0);
+        }
+    }
+}
+
+define_csr! {
+Envcfgh {
+    fields {
+       pbmte(RW):30, 30;
+       stce(RW):31, 31;
+    },
+}
 }
+
+impl Envcfgh {
+    pub fn config_stce(&mut self, isa: &Isa) {
+        if !isa.has_ext(IsaExt::Sstc) {
+            self.set_stce_transform(|_| 0);
+        }
+    }
+
+    pub fn config_pbmte(&mut self, isa: &Isa) {
+        if !isa.has_ext(IsaExt::Svpbmt) {
+            self.set_pbmte_transform(|_| 0);
+        }
+    }
 }
 
+//c is hardwired to 0 without custom state, fcsr is hardwired to 0 without zfinx
 define_csr! {
-Cycle {
+Mstateen0 {
+    fields {
+       c(RO):0, 0;
+       fcsr(RO):1, 1;
+       jvt(RW):2, 2;
+    },
     fields32 {
-       cycle(RO):31, 0;
     },
     fields64 {
-       cycle(RO):63, 0;
+       imsic(RW):58, 58;
+       aia(RW):59, 59;
+       csrind(RW):60, 60;
+       envcfg(RW):62, 62;
+       se0(RW):63, 63;
     },
 }
 }
 
+impl Mstateen0 {
+    //jvt is hardwired to 0 without zcmt
+    pub fn config_jvt(&mut self, isa: &Isa) {
+        if !isa.has_ext(IsaExt::Zcmt) {
+            self.set_jvt_transform(|_| 0);
+        }
+    }
+
+    //imsic, aia and csrind are hardwired to 0 without ssaia, envcfg and se0 without s mode,
+    //only used in rv64
+    pub fn config(&mut self, isa: &Isa) {
+        if !isa.has_ext(IsaExt::Ssaia) {
+            self.set_imsic_transform(|_| 0);
+            self.set_aia_transform(|_| 0);
+            self.set_csrind_transform(|_| 0);
+        }
+        if !isa.has('s') {
+            self.set_envcfg_transform(|_| 0);
+            self.set_se0_transform(|_| 0);
+        }
+    }
+}
+
 define_csr! {
-Instret {
+Mstateen0h {
+    fields {
+       imsic(RW):26, 26;
+       aia(RW):27, 27;
+       csrind(RW):28, 28;
+       envcfg(RW):30, 30;
+       se0(RW):31, 31;
+    },
+}
+}
+
+impl Mstateen0h {
+    pub fn config(&mut self, isa: &Isa) {
+        if !isa.has_ext(IsaExt::Ssaia) {
+            self.set_imsic_transform(|_| 0);
+            self.set_aia_transform(|_| 0);
+            self.set_csrind_transform(|_| 0);
+        }
+        if !isa.has('s') {
+            self.set_envcfg_transform(|_| 0);
+            self.set_se0_transform(|_| 0);
+        }
+    }
+}
+
+//mstateen1-3 have no state defined except se0
+define_csr! {
+Mstateen {
     fields32 {
-       instret(RO):31, 0;
     },
     fields64 {
-       instret(RO):63, 0;
+       se0(RW):63, 63;
+    },
+}
+}
+
+impl Mstateen {
+    pub fn config(&mut self, isa: &Isa) {
+        if !isa.has('s') {
+            self.set_se0_transform(|_| 0);
+        }
+    }
+}
+
+define_csr! {
+Mstateenh {
+    fields {
+       se0(RW):31, 31;
+    },
+}
+}
+
+impl Mstateenh {
+    pub fn config(&mut self, isa: &Isa) {
+        if !isa.has('s') {
+            self.set_se0_transform(|_| 0);
+        }
+    }
+}
+
+define_csr! {
+Mseccfg {
+    fields {
+       useed(RW):8, 8;
+       sseed(RW):9, 9;
     },
 }
 }
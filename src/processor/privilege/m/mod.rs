@@ -1,4 +1,6 @@
+use crate::processor::isa::IsaExt;
 use crate::processor::ProcessorCfg;
+use paste::paste;
 use std::ops::Deref;
 use std::rc::Rc;
 
@@ -15,14 +17,20 @@ impl PrivM {
         let m = PrivM {
             csrs: Rc::new(MCsrs::new(cfg.xlen.len())),
         };
-        //no debug
-        m.csrs.tselect_mut().set(0xffff_ffff_ffff_ffff);
+        //debug spec 0.13/1.0, counters stop in debug mode
+        m.csrs.dcsr_mut().set_xdebugver(4);
+        m.csrs.dcsr_mut().set_stopcount(1);
+        m.csrs.dcsr_mut().set_prv(3);
+        m.csrs.dcsr_mut().set_prv_transform(|prv| if prv == 2 { 3 } else { prv });
         //mstatus
         //sd bit
         m.csrs.mstatus_mut().sd_transform({
             let csrs = m.csrs.clone();
             move |_| {
-                if csrs.mstatus().fs() == 0x3 && csrs.mstatus().xs() == 0x3 {
+                if csrs.mstatus().fs() == 0x3
+                    || csrs.mstatus().vs() == 0x3
+                    || csrs.mstatus().xs() == 0x3
+                {
                     1
                 } else {
                     0
@@ -30,8 +38,66 @@ impl PrivM {
             }
         });
 
-        m.csrs.mcycleh_mut().get_forbidden(cfg.xlen != XLen::X32);
-        m.csrs.minstreth_mut().get_forbidden(cfg.xlen != XLen::X32);
+        m.csrs.menvcfg_mut().config_cbo(&cfg.isa);
+        //menvcfg.stce and menvcfg.pbmte are in menvcfgh in rv32
+        if cfg.xlen == XLen::X64 {
+            m.csrs.menvcfg_mut().config_stce(&cfg.isa);
+            m.csrs.menvcfg_mut().config_pbmte(&cfg.isa);
+        }
+        m.csrs.menvcfgh_mut().config_stce(&cfg.isa);
+        m.csrs.menvcfgh_mut().config_pbmte(&cfg.isa);
+        m.csrs.menvcfgh_mut().get_forbidden(cfg.xlen != XLen::X32);
+        m.csrs.menvcfgh_mut().set_forbidden(cfg.xlen != XLen::X32);
+        //mseccfg only holds useed and sseed of zkr
+        let zkr = cfg.isa.has_ext(IsaExt::Zkr);
+        m.csrs.mseccfg_mut().get_forbidden(!zkr);
+        m.csrs.mseccfg_mut().set_forbidden(!zkr);
+        //mstateen0-3 and mstateen0h-3h in rv32 are only accessible with smstateen
+        let smstateen = cfg.isa.has_ext(IsaExt::Smstateen);
+        let smstateenh = smstateen && cfg.xlen == XLen::X32;
+        m.csrs.mstateen0_mut().config_jvt(&cfg.isa);
+        //bits 63:32 of mstateen0-3 are in mstateen0h-3h in rv32
+        macro_rules! config_mstateen {
+            ($($n:literal),+) => {
+                $(paste! {
+                    if cfg.xlen == XLen::X64 {
+                        m.csrs.[<mstateen $n _mut>]().config(&cfg.isa);
+                    } else {
+                        m.csrs.[<mstateen $n h_mut>]().config(&cfg.isa);
+                    }
+                    m.csrs.[<mstateen $n _mut>]().get_forbidden(!smstateen);
+                    m.csrs.[<mstateen $n _mut>]().set_forbidden(!smstateen);
+                    m.csrs.[<mstateen $n h_mut>]().get_forbidden(!smstateenh);
+                    m.csrs.[<mstateen $n h_mut>]().set_forbidden(!smstateenh);
+                })+
+            };
+        }
+        config_mstateen!(0, 1, 2, 3);
+        //mvien is zero, mvip.ssip and mvip.stip alias mip and mvip.seip is the software written seip
+        let smaia = cfg.isa.has_ext(IsaExt::Smaia);
+        m.csrs.mvien_mut().get_forbidden(!smaia);
+        m.csrs.mvien_mut().set_forbidden(!smaia);
+        m.csrs.mvip_mut().get_forbidden(!smaia);
+        m.csrs.mvip_mut().set_forbidden(!smaia);
+        macro_rules! deleg_mvip {
+            ($field:ident) => {
+                paste! {
+                    m.csrs.mvip_mut().[<set_ $field _transform>]({
+                        let csrs = m.csrs.clone();
+                        move |field| {
+                            csrs.mip_mut().[<set_ $field>](field);
+                            0
+                        }
+                    });
+                    m.csrs.mvip_mut().[<$field _transform>]({
+                        let csrs = m.csrs.clone();
+                        move |_| csrs.mip().$field()
+                    });
+                }
+            };
+        }
+        deleg_mvip!(ssip);
+        deleg_mvip!(stip);
         m
     }
 }
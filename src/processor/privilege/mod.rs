@@ -1,8 +1,16 @@
+use crate::devices::clint::HartTimer;
+use crate::devices::imsic::{HartImsic, IMSIC_M, IMSIC_S};
 use crate::prelude::{sext, InsnT, RegT, XLen};
+use crate::processor::isa::IsaExt;
+use crate::processor::trap::Exception;
 use crate::processor::{HasCsr, ProcessorCfg, ProcessorState};
+use crate::snapshot::{snapshot_err, SnapshotReader, SnapshotWriter};
 use num_enum::{IntoPrimitive, TryFromPrimitive};
+use paste::paste;
 use std::cell::RefCell;
 use std::convert::TryFrom;
+use std::io;
+use std::io::{Read, Write};
 use std::rc::Rc;
 use terminus_spaceport::irq::IrqVec;
 
@@ -21,6 +29,11 @@ mod u;
 pub use u::csrs::*;
 pub use u::PrivU;
 
+mod h;
+
+pub use h::csrs::*;
+pub use h::PrivH;
+
 #[derive(IntoPrimitive, TryFromPrimitive, Debug, Copy, Clone, Eq, PartialEq)]
 #[repr(u8)]
 pub enum Privilege {
@@ -29,28 +42,221 @@ pub enum Privilege {
     M = 3,
 }
 
+macro_rules! save_csrs {
+    ($w:expr, $csrs:expr, $($name:ident),+) => {
+        $(
+            $w.write_u64($csrs.$name().get() as u64)?;
+        )+
+    };
+}
+
+macro_rules! restore_csrs {
+    ($r:expr, $csrs:expr, $($name:ident),+) => {
+        paste! {
+            $(
+                $csrs.[<$name _mut>]().set($r.read_u64()? as RegT);
+            )+
+        }
+    };
+}
+
+//sstatus, sie and sip are views of m csrs
+macro_rules! m_csrs {
+    ($mac:ident, $rw:expr, $csrs:expr) => {
+        $mac!(
+            $rw, $csrs, mstatus, misa, medeleg, mideleg, mie, mtvec, mcounteren, mvien, mvip,
+            menvcfg, menvcfgh, mstateen0, mstateen1, mstateen2, mstateen3, mstateen0h, mstateen1h,
+            mstateen2h, mstateen3h, mscratch, mepc, mcause, mtval, mip, pmpcfg0, pmpcfg1, pmpcfg2,
+            pmpcfg3, pmpaddr0, pmpaddr1, pmpaddr2, pmpaddr3, pmpaddr4, pmpaddr5, pmpaddr6,
+            pmpaddr7, pmpaddr8, pmpaddr9, pmpaddr10, pmpaddr11, pmpaddr12, pmpaddr13, pmpaddr14,
+            pmpaddr15, mseccfg, dcsr, dpc, dscratch0, dscratch1
+        )
+    };
+}
+
+macro_rules! s_csrs {
+    ($mac:ident, $rw:expr, $csrs:expr) => {
+        $mac!(
+            $rw, $csrs, stvec, scounteren, senvcfg, sstateen0, sstateen1, sstateen2, sstateen3,
+            sscratch, sepc, scause, stval, satp
+        )
+    };
+}
+
+//hie, hip, vsie and vsip are views of m csrs, hvip holds VS pending bits of mip
+macro_rules! h_csrs {
+    ($mac:ident, $rw:expr, $csrs:expr) => {
+        $mac!(
+            $rw,
+            $csrs,
+            vsstatus,
+            vstvec,
+            vsscratch,
+            vsepc,
+            vscause,
+            vstval,
+            vsatp,
+            mtinst,
+            mtval2,
+            hstatus,
+            hedeleg,
+            hideleg,
+            htimedelta,
+            hcounteren,
+            henvcfg,
+            hstateen0,
+            hstateen1,
+            hstateen2,
+            hstateen3,
+            htimedeltah,
+            htval,
+            hvip,
+            htinst,
+            hgatp
+        )
+    };
+}
+
+//S csrs accessed in VS mode are substituted by VS csrs
+const fn vs_csr(addr: InsnT) -> Option<InsnT> {
+    match addr {
+        0x100 | 0x104 | 0x105 | 0x140 | 0x141 | 0x142 | 0x143 | 0x144 | 0x180 => Some(addr + 0x100),
+        _ => None,
+    }
+}
+
+//menvcfg.stce, it is in menvcfgh in rv32
+fn sstc_enabled(m: &MCsrs) -> bool {
+    if m.misa().mxl() == 2 {
+        m.menvcfg().stce() == 1
+    } else {
+        m.menvcfgh().stce() == 1
+    }
+}
+
+//mstateenN, bits 63:32 are in mstateenNh in rv32
+fn mstateen(m: &MCsrs, n: usize) -> RegT {
+    let (lo, hi) = match n {
+        0 => (m.mstateen0().get(), m.mstateen0h().get()),
+        1 => (m.mstateen1().get(), m.mstateen1h().get()),
+        2 => (m.mstateen2().get(), m.mstateen2h().get()),
+        _ => (m.mstateen3().get(), m.mstateen3h().get()),
+    };
+    if m.misa().mxl() == 2 {
+        lo
+    } else {
+        lo | hi << 32
+    }
+}
+
+fn hstateen(h: &HCsrs, n: usize) -> RegT {
+    match n {
+        0 => h.hstateen0().get(),
+        1 => h.hstateen1().get(),
+        2 => h.hstateen2().get(),
+        _ => h.hstateen3().get(),
+    }
+}
+
+fn sstateen(s: &SCsrs, n: usize) -> RegT {
+    match n {
+        0 => s.sstateen0().get(),
+        1 => s.sstateen1().get(),
+        2 => s.sstateen2().get(),
+        _ => s.sstateen3().get(),
+    }
+}
+
+//index of cycle, time, instret and hpmcounter3-31 and their high halves
+const fn counter_csr(addr: InsnT) -> Option<usize> {
+    match addr {
+        0xC00..=0xC1F | 0xC80..=0xC9F => Some((addr & 0x1f) as usize),
+        _ => None,
+    }
+}
+
+//csrs gated by state enable bits, as (stateen index, bit)
+const fn stateen_csr(addr: InsnT) -> Option<(usize, RegT)> {
+    match addr {
+        //jvt
+        0x017 => Some((0, 2)),
+        //sstateen0-3 and hstateen0-3
+        0x10C..=0x10F => Some(((addr - 0x10C) as usize, 63)),
+        0x60C..=0x60F => Some(((addr - 0x60C) as usize, 63)),
+        //senvcfg and henvcfg
+        0x10A | 0x60A => Some((0, 62)),
+        //siselect and sireg
+        0x150 | 0x151 => Some((0, 60)),
+        //sieh, siph and stopi
+        0x114 | 0x154 | 0xDB0 => Some((0, 59)),
+        //stopei
+        0x15C => Some((0, 58)),
+        _ => None,
+    }
+}
+
+#[derive(IntoPrimitive, TryFromPrimitive, Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(u8)]
+pub enum DebugCause {
+    Ebreak = 1,
+    Trigger = 2,
+    HaltReq = 3,
+    Step = 4,
+    ResetHaltReq = 5,
+}
+
 pub struct PrivilegeStates {
     m: PrivM,
     s: Option<PrivS>,
     u: Option<PrivU>,
+    h: Option<PrivH>,
     cur: Privilege,
+    //virtualization mode, VS and VU when set
+    virt: bool,
+    debug_mode: bool,
+    //smstateen
+    stateen: bool,
 }
 
 impl PrivilegeStates {
     pub fn new(cfg: &ProcessorCfg) -> PrivilegeStates {
         let m_state = PrivM::new(cfg);
-        let u = if cfg.extensions.contains(&'u') {
-            Some(PrivU::new(cfg, &m_state))
+        let u = if cfg.isa.has('u') {
+            Some(PrivU::new(cfg))
         } else {
             None
         };
-        let s = if u.is_some() && cfg.extensions.contains(&'s') {
+        let s = if u.is_some() && cfg.isa.has('s') {
             Some(PrivS::new(cfg, &m_state))
         } else {
             m_state.mstatus_mut().set_tvm_transform(|_| 0);
             m_state.mstatus_mut().set_tsr_transform(|_| 0);
             None
         };
+        let h = if s.is_some() && cfg.isa.has('h') {
+            Some(PrivH::new(cfg, &m_state))
+        } else {
+            m_state.mstatus_mut().set_mpv_transform(|_| 0);
+            m_state.mstatus_mut().set_gva_transform(|_| 0);
+            m_state.mip_mut().set_vssip_transform(|_| 0);
+            m_state.mie_mut().set_vssie_transform(|_| 0);
+            m_state.mie_mut().set_vstie_transform(|_| 0);
+            m_state.mie_mut().set_vseie_transform(|_| 0);
+            m_state.mie_mut().set_sgeie_transform(|_| 0);
+            m_state.dcsr_mut().set_v_transform(|_| 0);
+            m_state.dcsr_mut().set_ebreakvs_transform(|_| 0);
+            m_state.dcsr_mut().set_ebreakvu_transform(|_| 0);
+            None
+        };
+        if !cfg.isa.has('v') {
+            m_state.mstatus_mut().set_vs_transform(|_| 0);
+        }
+        //local counter overflow interrupt of sscofpmf
+        if !cfg.isa.has_ext(IsaExt::Sscofpmf) {
+            m_state.mip_mut().set_lcofip_transform(|_| 0);
+            m_state.mie_mut().set_lcofie_transform(|_| 0);
+            m_state.mideleg_mut().set_lcofip_transform(|_| 0);
+        }
 
         //privilege_level config
         if u.is_none() {
@@ -73,7 +279,11 @@ impl PrivilegeStates {
             m: m_state,
             s,
             u,
+            h,
             cur: Privilege::M,
+            virt: false,
+            debug_mode: false,
+            stateen: cfg.isa.has_ext(IsaExt::Smstateen),
         }
     }
 
@@ -89,6 +299,89 @@ impl PrivilegeStates {
         &self.cur
     }
 
+    pub fn set_virt(&mut self, virt: bool) {
+        self.virt = virt && self.h.is_some() && self.cur != Privilege::M
+    }
+
+    pub const fn virt(&self) -> bool {
+        self.virt
+    }
+
+    pub const fn debug_mode(&self) -> bool {
+        self.debug_mode
+    }
+
+    pub fn ebreak_to_debug(&self) -> bool {
+        let dcsr = self.m().dcsr();
+        match self.cur {
+            Privilege::M => dcsr.ebreakm() == 1,
+            Privilege::S if self.virt => dcsr.ebreakvs() == 1,
+            Privilege::U if self.virt => dcsr.ebreakvu() == 1,
+            Privilege::S => dcsr.ebreaks() == 1,
+            Privilege::U => dcsr.ebreaku() == 1,
+        }
+    }
+
+    pub fn debug_enter(&mut self, cause: DebugCause, pc: RegT) {
+        let cur: u8 = self.cur.into();
+        let cause: u8 = cause.into();
+        self.m().dcsr_mut().set_cause(cause as RegT);
+        self.m().dcsr_mut().set_prv(cur as RegT);
+        self.m().dcsr_mut().set_v(self.virt as RegT);
+        self.m().dpc_mut().set(pc);
+        self.debug_mode = true;
+        self.cur = Privilege::M;
+        self.virt = false;
+    }
+
+    pub fn debug_return(&mut self) -> RegT {
+        let prv = Privilege::try_from(self.m().dcsr().prv() as u8).unwrap();
+        if prv != Privilege::M {
+            self.m().mstatus_mut().set_mprv(0);
+        }
+        self.debug_mode = false;
+        self.set_priv(prv);
+        self.set_virt(self.m().dcsr().v() == 1);
+        let dpc = self.m().dpc().get();
+        if self.check_extension('c').is_err() {
+            (dpc >> 2) << 2
+        } else {
+            dpc
+        }
+    }
+
+    pub fn save_snapshot<W: Write>(&self, w: &mut SnapshotWriter<W>) -> io::Result<()> {
+        let cur: u8 = self.cur.into();
+        w.write_u8(cur)?;
+        w.write_bool(self.debug_mode)?;
+        m_csrs!(save_csrs, w, self.m());
+        if let Some(s) = self.s() {
+            s_csrs!(save_csrs, w, s);
+        }
+        if let Some(h) = self.h() {
+            w.write_bool(self.virt)?;
+            h_csrs!(save_csrs, w, h);
+        }
+        Ok(())
+    }
+
+    pub fn restore_snapshot<R: Read>(&mut self, r: &mut SnapshotReader<R>) -> io::Result<()> {
+        self.cur = match Privilege::try_from(r.read_u8()?) {
+            Ok(p) => p,
+            Err(e) => return snapshot_err(e.to_string()),
+        };
+        self.debug_mode = r.read_bool()?;
+        m_csrs!(restore_csrs, r, self.m());
+        if let Some(s) = self.s() {
+            s_csrs!(restore_csrs, r, s);
+        }
+        if let Some(h) = self.h() {
+            self.virt = r.read_bool()?;
+            h_csrs!(restore_csrs, r, h);
+        }
+        Ok(())
+    }
+
     pub fn check_extension(&self, ext: char) -> Result<(), ()> {
         if self.m().misa().get() & ((1 as RegT) << ((ext as u8 - 'a' as u8) as RegT)) != 0 {
             Ok(())
@@ -97,25 +390,33 @@ impl PrivilegeStates {
         }
     }
 
-    pub fn delegate_insns_cnt(&self, cnt: &Rc<RefCell<u64>>) {
-        self.m.minstret_mut().instret_transform({
-            let count = cnt.clone();
-            move |_| *count.borrow() as RegT
-        });
-        self.m.minstreth_mut().instret_transform({
-            let count = cnt.clone();
-            move |_| (*count.borrow() >> 32) as RegT
-        });
-    }
-
-    pub fn delegate_ei(&self, irq: &IrqVec) {
+    //meip and seip are driven by plic or aplic lines and by interrupt files of imsic
+    pub fn delegate_ei(&self, irq: Option<&IrqVec>, imsic: Option<&HartImsic>) {
         self.m().mip_mut().meip_transform({
-            let l = irq.listener(0).unwrap();
-            move |_| l.pending_uncheck() as RegT
+            let l = irq.map(|irq| irq.listener(0).unwrap());
+            let imsic = imsic.cloned();
+            move |_| {
+                (l.as_ref().map_or(false, |l| l.pending_uncheck())
+                    || imsic.as_ref().map_or(false, |i| i.pending(IMSIC_M))) as RegT
+            }
+        });
+        //software written seip is kept in mvip.seip
+        self.m().mip_mut().set_seip_transform({
+            let csrs = (*self.m()).clone();
+            move |field| {
+                csrs.mvip_mut().set_seip(field);
+                0
+            }
         });
         self.m().mip_mut().seip_transform({
-            let l = irq.listener(0).unwrap();
-            move |_| l.pending_uncheck() as RegT
+            let l = irq.map(|irq| irq.listener(1).unwrap());
+            let imsic = imsic.cloned();
+            let csrs = (*self.m()).clone();
+            move |_| {
+                (l.as_ref().map_or(false, |l| l.pending_uncheck())
+                    || imsic.as_ref().map_or(false, |i| i.pending(IMSIC_S))
+                    || csrs.mvip().seip() == 1) as RegT
+            }
         });
     }
 
@@ -130,12 +431,83 @@ impl PrivilegeStates {
         });
     }
 
+    pub fn delegate_timer(&self, timer: &HartTimer, cfg: &ProcessorCfg) {
+        if let Some(u) = self.u() {
+            u.time_mut().time_transform({
+                let timer = timer.clone();
+                move |_| timer.time() as RegT
+            });
+            u.timeh_mut().time_transform({
+                let timer = timer.clone();
+                move |_| (timer.time() >> 32) as RegT
+            });
+        }
+        if !cfg.isa.has_ext(IsaExt::Sstc) {
+            return;
+        }
+        //stip is driven by stimecmp instead of software when menvcfg.stce is set
+        let sw_stip = Rc::new(RefCell::new(0));
+        self.m().mip_mut().set_stip_transform({
+            let sw_stip = sw_stip.clone();
+            move |value| {
+                *sw_stip.borrow_mut() = value;
+                0
+            }
+        });
+        self.m().mip_mut().stip_transform({
+            let csrs = (*self.m()).clone();
+            let timer = timer.clone();
+            move |_| {
+                if sstc_enabled(&csrs) {
+                    timer.stip() as RegT
+                } else {
+                    *sw_stip.borrow()
+                }
+            }
+        });
+        if let Some(s) = self.s() {
+            let xlen = cfg.xlen;
+            s.stimecmp_mut().get_forbidden(false);
+            s.stimecmp_mut().set_forbidden(false);
+            s.stimecmp_mut().stimecmp_transform({
+                let timer = timer.clone();
+                move |_| timer.stimecmp() as RegT
+            });
+            s.stimecmp_mut().set_stimecmp_transform({
+                let timer = timer.clone();
+                move |value| {
+                    if xlen == XLen::X64 {
+                        timer.set_stimecmp(value as u64)
+                    } else {
+                        timer.set_stimecmp(timer.stimecmp() & !0xffff_ffff | value as u64)
+                    }
+                    0
+                }
+            });
+            if xlen == XLen::X32 {
+                s.stimecmph_mut().get_forbidden(false);
+                s.stimecmph_mut().set_forbidden(false);
+                s.stimecmph_mut().stimecmp_transform({
+                    let timer = timer.clone();
+                    move |_| (timer.stimecmp() >> 32) as RegT
+                });
+                s.stimecmph_mut().set_stimecmp_transform({
+                    let timer = timer.clone();
+                    move |value| {
+                        timer.set_stimecmp(timer.stimecmp() & 0xffff_ffff | (value as u64) << 32);
+                        0
+                    }
+                });
+            }
+        }
+    }
+
     pub fn init_isa(&self, hartid: RegT, cfg: &ProcessorCfg) {
         //hartid
         self.m().mhartid_mut().set(hartid);
         //extensions config, only f, d can disable
         let mut misa = self.m().misa_mut();
-        for ext in cfg.extensions.iter() {
+        for ext in ('a'..='z').filter(|e| cfg.isa.has(*e)) {
             match ext {
                 'a' => misa.set_a(1),
                 'b' => misa.set_b(1),
@@ -166,6 +538,10 @@ impl PrivilegeStates {
                 _ => unreachable!(),
             }
         }
+        //misa.c stands for zca, even without zcf or zcd
+        if cfg.isa.has_ext(IsaExt::Zca) {
+            misa.set_c(1);
+        }
 
         //xlen config
         match cfg.xlen {
@@ -180,16 +556,113 @@ impl PrivilegeStates {
         }
     }
 
-    pub fn csr_privilege_check(&self, id: InsnT) -> Result<(), ()> {
+    pub fn csr_privilege_check(&self, id: InsnT, ir: InsnT) -> Result<(), Exception> {
         let trip_id = id & 0xfff;
         let cur_priv: u8 = self.cur.into();
         let csr_priv: u8 = ((trip_id >> 8) & 0x3) as u8;
+        if self.virt {
+            //hypervisor and VS csrs, S csrs in VU mode and satp when VTVM are virtual instructions
+            if csr_priv == 3 {
+                return Err(Exception::IllegalInsn(ir));
+            }
+            if let Some((n, bit)) = stateen_csr(trip_id) {
+                self.stateen_check(n, bit, ir)?;
+            }
+            if let Some(idx) = counter_csr(trip_id) {
+                self.counter_check(idx, ir)?;
+            }
+            //henvcfg.stce is hardwired to 0
+            if trip_id == 0x14D || trip_id == 0x15D {
+                return if self.stimecmp_enabled() {
+                    Err(Exception::VirtualInsn(ir))
+                } else {
+                    Err(Exception::IllegalInsn(ir))
+                };
+            }
+            if csr_priv == 2
+                || cur_priv < csr_priv
+                || trip_id == 0x180 && self.h().unwrap().hstatus().vtvm() == 1
+            {
+                return Err(Exception::VirtualInsn(ir));
+            }
+            return Ok(());
+        }
+        //HS mode accesses hypervisor and VS csrs
+        let cur_priv = if self.cur == Privilege::S && self.h.is_some() {
+            2
+        } else {
+            cur_priv
+        };
         if cur_priv < csr_priv {
-            return Err(());
+            return Err(Exception::IllegalInsn(ir));
+        }
+        if let Some((n, bit)) = stateen_csr(trip_id) {
+            self.stateen_check(n, bit, ir)?;
+        }
+        if (trip_id == 0x14D || trip_id == 0x15D)
+            && self.cur == Privilege::S
+            && !self.stimecmp_enabled()
+        {
+            return Err(Exception::IllegalInsn(ir));
         }
         Ok(())
     }
 
+    //state below M mode is controlled by mstateen, VS and VU mode are also controlled by hstateen,
+    //U and VU mode are also controlled by sstateen, which only holds bits 31:0
+    pub fn stateen_check(&self, n: usize, bit: RegT, ir: InsnT) -> Result<(), Exception> {
+        if !self.stateen || self.cur == Privilege::M {
+            return Ok(());
+        }
+        let enabled = |stateen: RegT| (stateen >> bit) & 1 == 1;
+        if !enabled(mstateen(self.m(), n)) {
+            return Err(Exception::IllegalInsn(ir));
+        }
+        if self.virt && !enabled(hstateen(self.h().unwrap(), n)) {
+            return Err(Exception::VirtualInsn(ir));
+        }
+        if self.cur == Privilege::U
+            && bit < 32
+            && !self.s().map_or(true, |s| enabled(sstateen(s, n)))
+        {
+            return Err(Exception::IllegalInsn(ir));
+        }
+        Ok(())
+    }
+
+    //counters disabled by mcounteren are illegal, VS and VU mode accesses to counters enabled by
+    //mcounteren but disabled by hcounteren, or VU mode accesses disabled by scounteren, are virtual
+    fn counter_check(&self, idx: usize, ir: InsnT) -> Result<(), Exception> {
+        let enabled = |counteren: RegT| counteren & ((1 as RegT) << idx as RegT) != 0;
+        if !enabled(self.m().mcounteren().get()) {
+            return Err(Exception::IllegalInsn(ir));
+        }
+        if !enabled(self.h().unwrap().hcounteren().get())
+            || self.cur == Privilege::U && !self.s().map_or(true, |s| enabled(s.scounteren().get()))
+        {
+            return Err(Exception::VirtualInsn(ir));
+        }
+        Ok(())
+    }
+
+    //counters below M mode are controlled by mcounteren and scounteren,
+    //VS and VU mode are also controlled by hcounteren
+    pub fn counter_enabled(&self, idx: usize) -> bool {
+        let enabled = |counteren: RegT| counteren & ((1 as RegT) << idx as RegT) != 0;
+        let mcounter_en = enabled(self.m().mcounteren().get());
+        let counter_en = match self.cur {
+            Privilege::S => mcounter_en,
+            Privilege::U => mcounter_en && self.s().map_or(true, |s| enabled(s.scounteren().get())),
+            _ => true,
+        };
+        counter_en && (!self.virt || enabled(self.h().unwrap().hcounteren().get()))
+    }
+
+    //stimecmp below M mode is gated by mcounteren.tm and menvcfg.stce
+    fn stimecmp_enabled(&self) -> bool {
+        self.m().mcounteren().tm() == 1 && sstc_enabled(self.m())
+    }
+
     pub const fn m(&self) -> &PrivM {
         &self.m
     }
@@ -202,6 +675,10 @@ impl PrivilegeStates {
         self.u.as_ref()
     }
 
+    pub fn h(&self) -> Option<&PrivH> {
+        self.h.as_ref()
+    }
+
     pub fn pending_interrupts(&self) -> RegT {
         let m = self.m();
         let pendings = m.mip().get() & m.mie().get();
@@ -211,20 +688,30 @@ impl PrivilegeStates {
         let mie = m.mstatus().mie();
         let sie = m.mstatus().sie();
         let deleg = m.mideleg().get();
+        let hideleg = self.h().map_or(0, |h| h.hideleg().get());
         let m_enabled = self.cur != Privilege::M || (self.cur == Privilege::M && mie == 1);
         let m_pendings = pendings & !deleg & sext(m_enabled as RegT, 1);
-        let s_enabled = self.cur == Privilege::U || (self.cur == Privilege::S && sie == 1);
-        let s_pendings = pendings & deleg & sext(s_enabled as RegT, 1);
+        //HS interrupts are always enabled in VS and VU mode
+        let s_enabled =
+            self.virt || self.cur == Privilege::U || (self.cur == Privilege::S && sie == 1);
+        let s_pendings = pendings & deleg & !hideleg & sext(s_enabled as RegT, 1);
+        let vs_enabled = self.virt
+            && (self.cur == Privilege::U
+                || (self.cur == Privilege::S && self.h().unwrap().vsstatus().sie() == 1));
+        let vs_pendings = pendings & deleg & hideleg & sext(vs_enabled as RegT, 1);
 
-        //m_pendings > s_pendings
-        if m_pendings == 0 {
+        //m_pendings > s_pendings > vs_pendings
+        if m_pendings != 0 {
+            m_pendings
+        } else if s_pendings != 0 {
             s_pendings
         } else {
-            m_pendings
+            vs_pendings
         }
     }
 
-    fn delegate_priv(&self, code: RegT, int_flag: bool) -> Privilege {
+    //return target privilege and virtualization mode
+    fn delegate_priv(&self, code: RegT, int_flag: bool) -> (Privilege, bool) {
         let deleg = if int_flag {
             self.m().mideleg().get()
         } else {
@@ -232,21 +719,78 @@ impl PrivilegeStates {
         };
         //deleg to s-mode
         let degeged = self.cur != Privilege::M && (deleg >> code) & 1 == 1;
-        if degeged {
-            Privilege::S
-        } else {
-            Privilege::M
+        if !degeged {
+            return (Privilege::M, false);
         }
+        //deleg to vs-mode
+        if self.virt {
+            let h = self.h().unwrap();
+            let hdeleg = if int_flag {
+                h.hideleg().get()
+            } else {
+                h.hedeleg().get()
+            };
+            if (hdeleg >> code) & 1 == 1 {
+                return (Privilege::S, true);
+            }
+        }
+        (Privilege::S, false)
     }
 
+    //val2 is written to htval/mtval2, gva is set if val is a guest virtual address
     pub fn trap_enter(
         &self,
         state: &ProcessorState,
         code: RegT,
         int_flag: bool,
         val: RegT,
-    ) -> (RegT, Privilege) {
-        let tgt_privilege = self.delegate_priv(code, int_flag);
+        val2: RegT,
+        gva: bool,
+    ) -> (RegT, Privilege, bool) {
+        let epc_value = if int_flag {
+            *state.next_pc()
+        } else {
+            *state.pc()
+        };
+        let (tgt_privilege, tgt_virt) = self.delegate_priv(code, int_flag);
+        if tgt_virt {
+            let h = self.h().unwrap();
+            //VS interrupts are seen as S interrupts by the guest
+            let code = if int_flag && (code == 2 || code == 6 || code == 10) {
+                code - 1
+            } else {
+                code
+            };
+            let pc = h.vstvec().get_trap_pc(code, int_flag);
+            h.vscause_mut().set_cause(code, int_flag);
+            h.vsepc_mut().set(epc_value);
+            h.vstval_mut().set(val);
+            h.vsstatus_mut().push_privilege(&tgt_privilege, &self.cur);
+            return (pc, tgt_privilege, true);
+        }
+        if let Some(h) = self.h() {
+            match &tgt_privilege {
+                Privilege::M => {
+                    h.mtval2_mut().set(val2);
+                    h.mtinst_mut().set(0);
+                    let mut mstatus = self.m().mstatus_mut();
+                    mstatus.set_mpv(self.virt as RegT);
+                    mstatus.set_gva(gva as RegT);
+                }
+                Privilege::S => {
+                    h.htval_mut().set(val2);
+                    h.htinst_mut().set(0);
+                    let mut hstatus = h.hstatus_mut();
+                    hstatus.set_spv(self.virt as RegT);
+                    if self.virt {
+                        let cur: u8 = self.cur.into();
+                        hstatus.set_spvp(cur as RegT);
+                    }
+                    hstatus.set_gva(gva as RegT);
+                }
+                _ => unreachable!(),
+            }
+        }
         let (tvec, mut cause, mut epc, mut tval) = match &tgt_privilege {
             Privilege::M => (
                 self.m().mtvec(),
@@ -264,32 +808,95 @@ impl PrivilegeStates {
         };
         let pc = tvec.get_trap_pc(code, int_flag);
         cause.set_cause(code, int_flag);
-        if int_flag {
-            epc.set(*state.next_pc());
-        } else {
-            epc.set(*state.pc());
-        }
+        epc.set(epc_value);
         tval.set(val);
 
         self.m()
             .mstatus_mut()
             .push_privilege(&tgt_privilege, &self.cur);
-        (pc, tgt_privilege)
+        (pc, tgt_privilege, false)
     }
 
-    pub fn trap_return(&self, cur_privilege: &Privilege) -> (RegT, Privilege) {
-        let epc = match cur_privilege {
-            Privilege::M => self.m().mepc(),
-            Privilege::S => self.s().unwrap().sepc(),
+    //return pc, privilege and virtualization mode to return to
+    pub fn trap_return(&self, cur_privilege: &Privilege) -> (RegT, Privilege, bool) {
+        let (epc, xpp, virt) = match cur_privilege {
+            Privilege::M => {
+                let xpp = self.m().mstatus_mut().pop_privilege(cur_privilege);
+                let mut mstatus = self.m().mstatus_mut();
+                let virt = xpp != Privilege::M && mstatus.mpv() == 1;
+                mstatus.set_mpv(0);
+                (self.m().mepc().get(), xpp, virt)
+            }
+            Privilege::S if self.virt => {
+                let h = self.h().unwrap();
+                let xpp = h.vsstatus_mut().pop_privilege(cur_privilege);
+                (h.vsepc().get(), xpp, true)
+            }
+            Privilege::S => {
+                let xpp = self.m().mstatus_mut().pop_privilege(cur_privilege);
+                let virt = self.h().map_or(false, |h| h.hstatus().spv() == 1);
+                (self.s().unwrap().sepc().get(), xpp, virt)
+            }
             _ => unreachable!(),
         };
-        let xpp = self.m().mstatus_mut().pop_privilege(cur_privilege);
         let pc = if self.check_extension('c').is_err() {
-            (epc.get() >> 2) << 2
+            (epc >> 2) << 2
         } else {
-            epc.get()
+            epc
         };
-        (pc, xpp)
+        (pc, xpp, virt)
+    }
+
+    //dcsr, dpc, dscratch0/1 only accessible in debug mode
+    fn debug_csrs_forbidden(&self) {
+        let forbidden = !self.debug_mode;
+        let m = self.m();
+        m.dcsr_mut().get_forbidden(forbidden);
+        m.dcsr_mut().set_forbidden(forbidden);
+        m.dpc_mut().get_forbidden(forbidden);
+        m.dpc_mut().set_forbidden(forbidden);
+        m.dscratch0_mut().get_forbidden(forbidden);
+        m.dscratch0_mut().set_forbidden(forbidden);
+        m.dscratch1_mut().get_forbidden(forbidden);
+        m.dscratch1_mut().set_forbidden(forbidden);
+    }
+
+    //hypervisor and VS csrs
+    fn h_csr_write(&self, state: &ProcessorState, addr: InsnT, value: RegT) -> Option<()> {
+        self.h()
+            .map(|h| {
+                let tvm = self.cur == Privilege::S && self.m().mstatus().tvm() != 0;
+                h.hgatp_mut().set_forbidden(tvm);
+                //vsatp and hgatp are WARL, writing an unsupported mode has no effect
+                let xlen = state.config().xlen.len();
+                if addr == 0x280
+                    && !state.satp_mode_supported(Satp::new(xlen, value).mode())
+                {
+                    return Some(());
+                }
+                if addr == 0x680 && !tvm {
+                    let mut hgatp = Hgatp::new(xlen, value);
+                    if !state.satp_mode_supported(hgatp.mode()) {
+                        return Some(());
+                    }
+                    //root page table of x4 modes is 16KiB aligned
+                    let ppn = hgatp.ppn();
+                    hgatp.set_ppn(ppn & !0x3);
+                    return h.write(addr as u64, hgatp.get());
+                }
+                h.write(addr as u64, value)
+            })
+            .flatten()
+    }
+
+    fn h_csr_read(&self, addr: InsnT) -> Option<RegT> {
+        self.h()
+            .map(|h| {
+                h.hgatp_mut()
+                    .get_forbidden(self.cur == Privilege::S && self.m().mstatus().tvm() != 0);
+                h.read(addr as u64)
+            })
+            .flatten()
     }
 
     fn get_priv_by_csr_idx(&self, id: InsnT) -> Result<Privilege, ()> {
@@ -303,31 +910,53 @@ impl HasCsr for PrivilegeStates {
         if let Ok(p) = self.get_priv_by_csr_idx(addr) {
             match p {
                 Privilege::M => {
+                    self.debug_csrs_forbidden();
                     self.m().misa_mut().set_ignore(
                         value & ((1 as RegT) << (('c' as u8 - 'a' as u8) as RegT)) == 0
                             && state.pc().trailing_zeros() == 1,
                     );
-                    self.m().write(addr as u64, value)
+                    self.m()
+                        .write(addr as u64, value)
+                        .or_else(|| self.h().map(|h| h.write(addr as u64, value)).flatten())
+                }
+                Privilege::S if self.virt && vs_csr(addr).is_some() => {
+                    self.h_csr_write(state, vs_csr(addr).unwrap(), value)
                 }
                 Privilege::S => self
                     .s()
                     .map(|s| {
-                        s.satp_mut().set_forbidden(
-                            self.cur == Privilege::S && self.m().mstatus().tvm() != 0,
-                        );
+                        let tvm = self.cur == Privilege::S && self.m().mstatus().tvm() != 0;
+                        s.satp_mut().set_forbidden(tvm);
+                        //satp is WARL, writing an unsupported mode has no effect
+                        if addr == 0x180
+                            && !tvm
+                            && !state.satp_mode_supported(
+                                Satp::new(state.config().xlen.len(), value).mode(),
+                            )
+                        {
+                            return Some(());
+                        }
                         s.write(addr as u64, value)
                     })
                     .flatten(),
                 Privilege::U => self.u().map(|u| u.write(addr as u64, value)).flatten(),
             }
         } else {
-            None
+            self.h_csr_write(state, addr, value)
         }
     }
     fn csr_read(&self, state: &ProcessorState, addr: InsnT) -> Option<RegT> {
         if let Ok(p) = self.get_priv_by_csr_idx(addr) {
             match p {
-                Privilege::M => self.m().read(addr as u64),
+                Privilege::M => {
+                    self.debug_csrs_forbidden();
+                    self.m()
+                        .read(addr as u64)
+                        .or_else(|| self.h().map(|h| h.read(addr as u64)).flatten())
+                }
+                Privilege::S if self.virt && vs_csr(addr).is_some() => {
+                    self.h_csr_read(vs_csr(addr).unwrap())
+                }
                 Privilege::S => self
                     .s()
                     .map(|s| {
@@ -340,35 +969,112 @@ impl HasCsr for PrivilegeStates {
                 Privilege::U => self
                     .u()
                     .map(|u| {
-                        let mcounter_dis = self.m().mcounteren().get()
-                            & ((1 as RegT) << (addr as RegT & 0x1f))
-                            == 0;
-                        let counter_dis = match self.cur {
-                            Privilege::S => mcounter_dis,
-                            Privilege::U => {
-                                if let Some(s) = self.s() {
-                                    mcounter_dis
-                                        || (s.scounteren().get()
-                                            & ((1 as RegT) << (addr as RegT & 0x1f))
-                                            == 0)
-                                } else {
-                                    mcounter_dis
-                                }
-                            }
-                            _ => false,
-                        };
-                        u.cycle_mut().get_forbidden(counter_dis);
-                        u.cycleh_mut()
-                            .get_forbidden(counter_dis || state.config().xlen != XLen::X32);
-                        u.instret_mut().get_forbidden(counter_dis);
-                        u.instreth_mut()
-                            .get_forbidden(counter_dis || state.config().xlen != XLen::X32);
-                        u.read(addr as u64)
+                        //time traps to M mode without a timer
+                        let time_dis = !self.counter_enabled((addr & 0x1f) as usize)
+                            || state.timer().is_none();
+                        u.time_mut().get_forbidden(time_dis);
+                        u.timeh_mut()
+                            .get_forbidden(time_dis || state.config().xlen != XLen::X32);
+                        let value = u.read(addr as u64)?;
+                        //time in VS and VU mode is offset by htimedelta
+                        if self.virt && (addr == 0xC01 || addr == 0xC81) {
+                            let xlen = state.config().xlen;
+                            let time = state
+                                .timer()
+                                .unwrap()
+                                .time()
+                                .wrapping_add(self.h().unwrap().time_delta(xlen));
+                            return Some(if addr == 0xC01 {
+                                time as RegT & xlen.mask()
+                            } else {
+                                (time >> 32) as RegT
+                            });
+                        }
+                        Some(value)
                     })
                     .flatten(),
             }
         } else {
-            None
+            self.h_csr_read(addr)
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::devices::bus::Bus;
+    use crate::processor::{Processor, SatpMode};
+    use crate::system::System;
+    use terminus_spaceport::memory::region::GHEAP;
+
+    //mret to mpp with V = 1, read time and ecall back to M mode, return time or mcause of the trap
+    fn virt_time(p: &mut Processor, mpp: RegT) -> Result<RegT, RegT> {
+        p.state().priv_m().mstatus_mut().set_mpp(mpp);
+        p.state().priv_m().mstatus_mut().set_mpv(1);
+        p.state().priv_m().mepc_mut().set(0x8000_0004);
+        p.state_mut().set_pc(0x8000_0000);
+        p.step(2);
+        if *p.state().privilege() == Privilege::M {
+            return Err(p.state().priv_m().mcause().get());
+        }
+        let time = *p.state().xreg(10);
+        p.step(1);
+        assert_eq!(*p.state().privilege(), Privilege::M);
+        Ok(time)
+    }
+
+    #[test]
+    fn htimedelta_test() {
+        let mut sys = System::new("test", "top_tests/elf/rv64ui-p-add", 100, 1);
+        sys.new_processor(ProcessorCfg {
+            xlen: XLen::X64,
+            enable_dirty: true,
+            isa: "rv64imach".parse().unwrap(),
+            freq: 1000000000,
+            triggers: 0,
+            satp_modes: vec![SatpMode::Sv39].into_boxed_slice(),
+            vlen: 128,
+            elen: 64,
+            cache_block_size: 64,
+            entropy_seed: 0,
+            hpm_counters: 0,
+        });
+        sys.register_memory("main_memory", 0x8000_0000, &GHEAP.alloc(0x2000, 1).unwrap())
+            .unwrap();
+        let insns: [u32; 3] = [
+            0x30200073, //mret
+            0xc0102573, //csrr a0, time
+            0x00000073, //ecall
+        ];
+        for (i, insn) in insns.iter().enumerate() {
+            sys.bus()
+                .write_u32(&(0x8000_0000 + i as u64 * 4), insn)
+                .unwrap();
+        }
+        sys.reset(vec![0x8000_0000]).unwrap();
+        let p = sys.processor(0).unwrap();
+        //TOR RWX below 4GiB
+        p.state().priv_m().pmpcfg0_mut().set_bit_range(4, 0, 0xf);
+        p.state().priv_m().pmpaddr0_mut().set(0x1_0000_0000 >> 2);
+        p.state().priv_h().unwrap().htimedelta_mut().set(1000);
+
+        //disabled by mcounteren
+        assert_eq!(virt_time(p, 1), Err(2));
+        //enabled by mcounteren but disabled by hcounteren
+        p.state().priv_m().mcounteren_mut().set_tm(1);
+        assert_eq!(virt_time(p, 1), Err(22));
+        assert_eq!(virt_time(p, 0), Err(22));
+
+        p.state().priv_h().unwrap().hcounteren_mut().set_tm(1);
+        let before = p.state().timer().unwrap().time();
+        let time = virt_time(p, 1).unwrap();
+        let after = p.state().timer().unwrap().time();
+        assert!(time >= before + 1000 && time <= after + 1000);
+        //VU mode is also controlled by scounteren
+        assert_eq!(virt_time(p, 0), Err(22));
+        p.state().priv_s().unwrap().scounteren_mut().set_tm(1);
+        let time = virt_time(p, 0).unwrap();
+        assert!(time >= before + 1000 && time <= p.state().timer().unwrap().time() + 1000);
+    }
+}
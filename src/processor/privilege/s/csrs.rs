@@ -6,11 +6,18 @@ pub SCsrs(0x0, 0xfff) {
     sie(RW):Sie, 0x104;
     stvec(RW):Tvec, 0x105;
     scounteren(RW):Counteren, 0x106;
+    senvcfg(RW):Envcfg, 0x10A;
+    sstateen0(RW):Sstateen0, 0x10C;
+    sstateen1(RW):Sstateen, 0x10D;
+    sstateen2(RW):Sstateen, 0x10E;
+    sstateen3(RW):Sstateen, 0x10F;
     sscratch(RW):Scratch, 0x140;
     sepc(RW):Epc, 0x141;
     scause(RW):Cause, 0x142;
     stval(RW):Tval, 0x143;
     sip(RW):Sip, 0x144;
+    stimecmp(RW):Stimecmp, 0x14D;
+    stimecmph(RW):Stimecmp, 0x15D;
     satp(RW):Satp, 0x180;
 }
 }
@@ -30,6 +37,32 @@ Satp {
 }
 }
 
+define_csr! {
+Sstateen0 {
+    fields {
+       c(RO):0, 0;
+       fcsr(RO):1, 1;
+       jvt(RW):2, 2;
+    },
+}
+}
+
+define_csr! {
+Sstateen {
+}
+}
+
+define_csr! {
+Stimecmp {
+    fields32 {
+       stimecmp(RW):31, 0;
+    },
+    fields64 {
+       stimecmp(RW):63, 0;
+    },
+}
+}
+
 define_csr! {
 Sip {
     fields{
@@ -39,6 +72,7 @@ Sip {
         stip(RO):5,5;
         ueip(RO):8,8;
         seip(RO):9,9;
+        lcofip(RW):13,13;
     }
 }
 }
@@ -52,6 +86,7 @@ Sie {
         stie(RW):5,5;
         ueie(RW):8,8;
         seie(RW):9,9;
+        lcofie(RW):13,13;
     }
 }
 }
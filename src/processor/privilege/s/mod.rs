@@ -1,3 +1,5 @@
+use crate::prelude::XLen;
+use crate::processor::isa::IsaExt;
 use crate::processor::ProcessorCfg;
 use paste::paste;
 use std::ops::Deref;
@@ -15,6 +17,30 @@ impl PrivS {
             csrs: Rc::new(SCsrs::new(cfg.xlen.len())),
         };
         s.csrs.sstatus_mut().as_s_priv();
+        s.csrs.senvcfg_mut().config_cbo(&cfg.isa);
+        if cfg.xlen == XLen::X64 {
+            s.csrs.senvcfg_mut().set_stce_transform(|_| 0);
+            s.csrs.senvcfg_mut().set_pbmte_transform(|_| 0);
+        }
+        //sstateen0-3 are only accessible with smstateen, jvt is read-only zero when mstateen0.jvt is clear
+        let smstateen = cfg.isa.has_ext(IsaExt::Smstateen);
+        s.csrs.sstateen0_mut().set_jvt_transform({
+            let csrs = (*m).clone();
+            move |jvt| jvt & csrs.mstateen0().jvt()
+        });
+        s.csrs.sstateen0_mut().get_forbidden(!smstateen);
+        s.csrs.sstateen0_mut().set_forbidden(!smstateen);
+        s.csrs.sstateen1_mut().get_forbidden(!smstateen);
+        s.csrs.sstateen1_mut().set_forbidden(!smstateen);
+        s.csrs.sstateen2_mut().get_forbidden(!smstateen);
+        s.csrs.sstateen2_mut().set_forbidden(!smstateen);
+        s.csrs.sstateen3_mut().get_forbidden(!smstateen);
+        s.csrs.sstateen3_mut().set_forbidden(!smstateen);
+        //stimecmp is only accessible with sstc and a timer
+        s.csrs.stimecmp_mut().get_forbidden(true);
+        s.csrs.stimecmp_mut().set_forbidden(true);
+        s.csrs.stimecmph_mut().get_forbidden(true);
+        s.csrs.stimecmph_mut().set_forbidden(true);
         //deleg sstatus to mstatus
         macro_rules! deleg_sstatus {
             ($field:ident) => {
@@ -41,6 +67,7 @@ impl PrivS {
         deleg_sstatus!(spie);
         deleg_sstatus!(spp);
         deleg_sstatus!(fs);
+        deleg_sstatus!(vs);
         deleg_sstatus!(xs);
         deleg_sstatus!(sum);
         deleg_sstatus!(mxr);
@@ -69,6 +96,15 @@ impl PrivS {
                 0
             }
         });
+        s.csrs.sip_mut().set_lcofip_transform({
+            let csrs = (*m).clone();
+            move |field| {
+                if csrs.mideleg().lcofip() == 1 {
+                    csrs.mip_mut().set_lcofip(field)
+                }
+                0
+            }
+        });
 
         deleg_sip!(usip);
         deleg_sip!(ssip);
@@ -76,6 +112,7 @@ impl PrivS {
         deleg_sip!(stip);
         deleg_sip!(ueip);
         deleg_sip!(seip);
+        deleg_sip!(lcofip);
 
         //deleg sie to mie
         macro_rules! deleg_sie {
@@ -105,6 +142,7 @@ impl PrivS {
         deleg_sie!(stip, stie);
         deleg_sie!(ueip, ueie);
         deleg_sie!(seip, seie);
+        deleg_sie!(lcofip, lcofie);
         s
     }
 }
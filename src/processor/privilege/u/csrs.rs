@@ -1,10 +1,18 @@
 use crate::prelude::*;
-use crate::processor::privilege::m::csrs::*;
 csr_map! {
 pub UCsrs(0x0, 0xfff) {
-    cycle(RO):Cycle, 0xC00;
-    instret(RO):Instret, 0xC02;
-    cycleh(RO):Cycle, 0xC80;
-    instreth(RO):Instret, 0xC82;
+    time(RO):Time, 0xC01;
+    timeh(RO):Time, 0xC81;
+}
+}
+
+define_csr! {
+Time {
+    fields32 {
+       time(RO):31, 0;
+    },
+    fields64 {
+       time(RO):63, 0;
+    },
 }
 }
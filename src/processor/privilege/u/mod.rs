@@ -3,7 +3,6 @@ use std::ops::Deref;
 use std::rc::Rc;
 
 pub mod csrs;
-use super::PrivM;
 use csrs::*;
 
 pub struct PrivU {
@@ -11,19 +10,10 @@ pub struct PrivU {
 }
 
 impl PrivU {
-    pub fn new(cfg: &ProcessorCfg, m: &PrivM) -> PrivU {
-        let u = PrivU {
+    pub fn new(cfg: &ProcessorCfg) -> PrivU {
+        PrivU {
             csrs: Rc::new(UCsrs::new(cfg.xlen.len())),
-        };
-        u.csrs.instret_mut().instret_transform({
-            let csrs = (*m).clone();
-            move |_| csrs.minstret().get()
-        });
-        u.csrs.instreth_mut().instret_transform({
-            let csrs = (*m).clone();
-            move |_| csrs.minstreth().get()
-        });
-        u
+        }
     }
 }
 
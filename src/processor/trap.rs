@@ -32,7 +32,13 @@ pub enum Exception {
     Breakpoint,
     UCall,
     SCall,
+    VSCall,
     MCall,
+    //(guest virtual address, guest physical address)
+    FetchGuestPageFault(u64, u64),
+    LoadGuestPageFault(u64, u64),
+    VirtualInsn(InsnT),
+    StoreGuestPageFault(u64, u64),
 }
 impl From<Error> for Exception {
     fn from(e: Error) -> Self {
@@ -52,10 +58,15 @@ impl Exception {
             Exception::StoreAccess(_) => 7,
             Exception::UCall => 8,
             Exception::SCall => 9,
+            Exception::VSCall => 10,
             Exception::MCall => 11,
             Exception::FetchPageFault(_) => 12,
             Exception::LoadPageFault(_) => 13,
             Exception::StorePageFault(_) => 15,
+            Exception::FetchGuestPageFault(_, _) => 20,
+            Exception::LoadGuestPageFault(_, _) => 21,
+            Exception::VirtualInsn(_) => 22,
+            Exception::StoreGuestPageFault(_, _) => 23,
         }
     }
     pub const fn tval(&self) -> RegT {
@@ -70,10 +81,44 @@ impl Exception {
             Exception::StoreAccess(addr) => *addr as RegT,
             Exception::UCall => 0 as RegT,
             Exception::SCall => 0 as RegT,
+            Exception::VSCall => 0 as RegT,
             Exception::MCall => 0 as RegT,
             Exception::FetchPageFault(addr) => *addr as RegT,
             Exception::LoadPageFault(addr) => *addr as RegT,
             Exception::StorePageFault(addr) => *addr as RegT,
+            Exception::FetchGuestPageFault(addr, _) => *addr as RegT,
+            Exception::LoadGuestPageFault(addr, _) => *addr as RegT,
+            Exception::VirtualInsn(inst) => *inst as RegT,
+            Exception::StoreGuestPageFault(addr, _) => *addr as RegT,
+        }
+    }
+
+    //value of htval/mtval2
+    pub const fn tval2(&self) -> RegT {
+        match self {
+            Exception::FetchGuestPageFault(_, gpa) => (*gpa >> 2) as RegT,
+            Exception::LoadGuestPageFault(_, gpa) => (*gpa >> 2) as RegT,
+            Exception::StoreGuestPageFault(_, gpa) => (*gpa >> 2) as RegT,
+            _ => 0,
+        }
+    }
+
+    //tval is a virtual address
+    pub const fn has_vaddr(&self) -> bool {
+        match self {
+            Exception::FetchMisaligned(_)
+            | Exception::FetchAccess(_)
+            | Exception::LoadMisaligned(_)
+            | Exception::LoadAccess(_)
+            | Exception::StoreMisaligned(_)
+            | Exception::StoreAccess(_)
+            | Exception::FetchPageFault(_)
+            | Exception::LoadPageFault(_)
+            | Exception::StorePageFault(_)
+            | Exception::FetchGuestPageFault(_, _)
+            | Exception::LoadGuestPageFault(_, _)
+            | Exception::StoreGuestPageFault(_, _) => true,
+            _ => false,
         }
     }
 
@@ -82,6 +127,7 @@ impl Exception {
             Exception::Breakpoint => true,
             Exception::UCall => true,
             Exception::SCall => true,
+            Exception::VSCall => true,
             Exception::MCall => true,
             _ => false,
         }
@@ -92,13 +138,18 @@ impl Exception {
 pub enum Interrupt {
     USInt,
     SSInt,
+    VSSInt,
     MSInt,
     UTInt,
     STInt,
+    VSTInt,
     MTInt,
     UEInt,
     SEInt,
+    VSEInt,
     MEInt,
+    SGEInt,
+    LCOFInt,
 }
 
 impl Interrupt {
@@ -106,13 +157,18 @@ impl Interrupt {
         match self {
             Interrupt::USInt => 0,
             Interrupt::SSInt => 1,
+            Interrupt::VSSInt => 2,
             Interrupt::MSInt => 3,
             Interrupt::UTInt => 4,
             Interrupt::STInt => 5,
+            Interrupt::VSTInt => 6,
             Interrupt::MTInt => 7,
             Interrupt::UEInt => 8,
             Interrupt::SEInt => 9,
+            Interrupt::VSEInt => 10,
             Interrupt::MEInt => 11,
+            Interrupt::SGEInt => 12,
+            Interrupt::LCOFInt => 13,
         }
     }
     pub const fn tval(&self) -> RegT {
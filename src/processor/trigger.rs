@@ -0,0 +1,679 @@
+use crate::prelude::*;
+use crate::processor::privilege::Privilege;
+use crate::processor::trap::Exception;
+use crate::snapshot::{SnapshotReader, SnapshotWriter};
+use std::cell::{Cell, RefCell};
+use std::io;
+use std::io::{Read, Write};
+
+const TSELECT: InsnT = 0x7A0;
+const TDATA1: InsnT = 0x7A1;
+const TDATA2: InsnT = 0x7A2;
+const TDATA3: InsnT = 0x7A3;
+const TINFO: InsnT = 0x7A4;
+
+const TYPE_MCONTROL: RegT = 2;
+const TYPE_MCONTROL6: RegT = 6;
+const TYPE_DISABLED: RegT = 15;
+
+const ACTION_BREAKPOINT: RegT = 0;
+const ACTION_DEBUG: RegT = 1;
+
+define_csr! {
+Mcontrol {
+    fields {
+        load(RW):0, 0;
+        store(RW):1, 1;
+        execute(RW):2, 2;
+        u(RW):3, 3;
+        s(RW):4, 4;
+        m(RW):6, 6;
+        mtch(RW):10, 7;
+        chain(RW):11, 11;
+        action(RW):15, 12;
+        sizelo(RW):17, 16;
+        timing(RW):18, 18;
+        select(RW):19, 19;
+        hit(RW):20, 20;
+    },
+    fields32 {
+        maskmax(RO):26, 21;
+        dmode(RW):27, 27;
+        ty(RW):31, 28;
+    },
+    fields64 {
+        sizehi(RW):22, 21;
+        maskmax(RO):58, 53;
+        dmode(RW):59, 59;
+        ty(RW):63, 60;
+    },
+}
+}
+
+define_csr! {
+Mcontrol6 {
+    fields {
+        load(RW):0, 0;
+        store(RW):1, 1;
+        execute(RW):2, 2;
+        u(RW):3, 3;
+        s(RW):4, 4;
+        uncertainen(RW):5, 5;
+        m(RW):6, 6;
+        mtch(RW):10, 7;
+        chain(RW):11, 11;
+        action(RW):15, 12;
+        size(RW):19, 16;
+        select(RW):21, 21;
+        hit0(RW):22, 22;
+        vu(RW):23, 23;
+        vs(RW):24, 24;
+        hit1(RW):25, 25;
+        uncertain(RW):26, 26;
+    },
+    fields32 {
+        dmode(RW):27, 27;
+        ty(RW):31, 28;
+    },
+    fields64 {
+        dmode(RW):59, 59;
+        ty(RW):63, 60;
+    },
+}
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TriggerOp {
+    Load,
+    Store,
+    Execute,
+}
+
+impl TriggerOp {
+    const fn mask(&self) -> u8 {
+        match self {
+            TriggerOp::Load => 1,
+            TriggerOp::Store => 2,
+            TriggerOp::Execute => 4,
+        }
+    }
+}
+
+//mcontrol and mcontrol6 decoded into one form
+struct MatchCtrl {
+    ops: u8,
+    m: bool,
+    s: bool,
+    u: bool,
+    vs: bool,
+    vu: bool,
+    mtch: RegT,
+    chain: bool,
+    action: RegT,
+    size: usize,
+    select: bool,
+}
+
+impl MatchCtrl {
+    //VS and VU mode match vs and vu instead of s and u
+    fn privilege_match(&self, privilege: &Privilege, virt: bool) -> bool {
+        match (privilege, virt) {
+            (Privilege::M, _) => self.m,
+            (Privilege::S, false) => self.s,
+            (Privilege::U, false) => self.u,
+            (Privilege::S, true) => self.vs,
+            (Privilege::U, true) => self.vu,
+        }
+    }
+
+    fn value_match(&self, xlen: &XLen, tdata2: RegT, value: RegT, len: usize) -> bool {
+        let half = xlen.len() >> 1;
+        let half_mask = ((1 as RegT) << half as RegT) - 1;
+        let matched = match self.mtch & 0x7 {
+            //address matches if any accessed byte hits
+            0 if !self.select => tdata2 >= value && tdata2 - value < len as RegT,
+            0 => tdata2 == value,
+            1 => {
+                let ignore = tdata2.trailing_ones() + 1;
+                value.checked_shr(ignore).unwrap_or(0) == tdata2.checked_shr(ignore).unwrap_or(0)
+            }
+            2 => value >= tdata2,
+            3 => value < tdata2,
+            4 => value & (tdata2 >> half as RegT) & half_mask == tdata2 & half_mask,
+            5 => (value >> half as RegT) & (tdata2 >> half as RegT) & half_mask == tdata2 & half_mask,
+            _ => false,
+        };
+        matched ^ (self.mtch & 0x8 != 0)
+    }
+}
+
+struct Trigger {
+    tdata1: RegT,
+    tdata2: RegT,
+}
+
+pub struct Triggers {
+    xlen: XLen,
+    tselect: Cell<usize>,
+    triggers: RefCell<Vec<Trigger>>,
+    ops_en: Cell<u8>,
+    debug_pending: Cell<bool>,
+}
+
+impl Triggers {
+    pub fn new(xlen: XLen, num: usize) -> Triggers {
+        let triggers = Triggers {
+            xlen,
+            tselect: Cell::new(0),
+            triggers: RefCell::new(vec![]),
+            ops_en: Cell::new(0),
+            debug_pending: Cell::new(false),
+        };
+        let disabled = triggers.disabled();
+        for _ in 0..num {
+            triggers.triggers.borrow_mut().push(Trigger {
+                tdata1: disabled,
+                tdata2: 0,
+            })
+        }
+        triggers
+    }
+
+    pub fn num(&self) -> usize {
+        self.triggers.borrow().len()
+    }
+
+    fn disabled(&self) -> RegT {
+        TYPE_DISABLED << (self.xlen.len() - 4) as RegT
+    }
+
+    fn ty(&self, tdata1: RegT) -> RegT {
+        tdata1 >> (self.xlen.len() - 4) as RegT
+    }
+
+    fn dmode(&self, tdata1: RegT) -> bool {
+        (tdata1 >> (self.xlen.len() - 5) as RegT) & 1 == 1
+    }
+
+    fn decode(&self, tdata1: RegT) -> Option<MatchCtrl> {
+        match self.ty(tdata1) {
+            TYPE_MCONTROL => {
+                let c = Mcontrol::new(self.xlen.len(), tdata1);
+                let size = if self.xlen == XLen::X64 {
+                    c.sizehi() << 2 | c.sizelo()
+                } else {
+                    c.sizelo()
+                };
+                Some(MatchCtrl {
+                    ops: (c.execute() << 2 | c.store() << 1 | c.load()) as u8,
+                    m: c.m() == 1,
+                    s: c.s() == 1,
+                    u: c.u() == 1,
+                    vs: false,
+                    vu: false,
+                    mtch: c.mtch(),
+                    chain: c.chain() == 1,
+                    action: c.action(),
+                    size: Self::size_bytes(size),
+                    select: c.select() == 1,
+                })
+            }
+            TYPE_MCONTROL6 => {
+                let c = Mcontrol6::new(self.xlen.len(), tdata1);
+                Some(MatchCtrl {
+                    ops: (c.execute() << 2 | c.store() << 1 | c.load()) as u8,
+                    m: c.m() == 1,
+                    s: c.s() == 1,
+                    u: c.u() == 1,
+                    vs: c.vs() == 1,
+                    vu: c.vu() == 1,
+                    mtch: c.mtch(),
+                    chain: c.chain() == 1,
+                    action: c.action(),
+                    size: Self::size_bytes(c.size()),
+                    select: c.select() == 1,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn size_bytes(size: RegT) -> usize {
+        match size {
+            0 => 0,
+            1 => 1,
+            2 => 2,
+            3 => 4,
+            4 => 6,
+            5 => 8,
+            _ => 0,
+        }
+    }
+
+    fn legal_match(mtch: RegT) -> RegT {
+        match mtch {
+            0 | 1 | 2 | 3 | 4 | 5 | 8 | 9 | 12 | 13 => mtch,
+            _ => 0,
+        }
+    }
+
+    //WARL
+    fn legalize(&self, value: RegT, debug_mode: bool) -> RegT {
+        let xlen = self.xlen.len();
+        match self.ty(value) {
+            TYPE_MCONTROL => {
+                let mut c = Mcontrol::new(xlen, value);
+                let dmode = c.dmode() == 1 && debug_mode;
+                c.set_dmode(dmode as RegT);
+                if c.action() != ACTION_DEBUG || !dmode {
+                    c.set_action(ACTION_BREAKPOINT)
+                }
+                let mtch = Self::legal_match(c.mtch());
+                c.set_mtch(mtch);
+                c.set_timing(0);
+                c.set_maskmax((xlen - 1) as RegT);
+                if self.xlen == XLen::X64 && (c.sizehi() << 2 | c.sizelo()) > 5 {
+                    c.set_sizehi(0);
+                    c.set_sizelo(0);
+                }
+                c.get()
+            }
+            TYPE_MCONTROL6 => {
+                let mut c = Mcontrol6::new(xlen, value);
+                let dmode = c.dmode() == 1 && debug_mode;
+                c.set_dmode(dmode as RegT);
+                if c.action() != ACTION_DEBUG || !dmode {
+                    c.set_action(ACTION_BREAKPOINT)
+                }
+                let mtch = Self::legal_match(c.mtch());
+                c.set_mtch(mtch);
+                if c.size() > 5 {
+                    c.set_size(0)
+                }
+                c.set_uncertainen(0);
+                c.set_uncertain(0);
+                c.set_vs(0);
+                c.set_vu(0);
+                c.get()
+            }
+            _ => {
+                let dmode = (self.dmode(value) && debug_mode) as RegT;
+                self.disabled() | dmode << (xlen - 5) as RegT
+            }
+        }
+    }
+
+    fn update_ops_en(&self, triggers: &[Trigger]) {
+        let ops = triggers
+            .iter()
+            .filter_map(|t| self.decode(t.tdata1))
+            .fold(0, |acc, c| acc | c.ops);
+        self.ops_en.set(ops)
+    }
+
+    pub fn csr_read(&self, addr: InsnT) -> Option<RegT> {
+        let triggers = self.triggers.borrow();
+        if triggers.is_empty() {
+            //no trigger
+            return if addr == TSELECT {
+                Some(self.xlen.mask())
+            } else {
+                None
+            };
+        }
+        let t = &triggers[self.tselect.get()];
+        match addr {
+            TSELECT => Some(self.tselect.get() as RegT),
+            TDATA1 => Some(t.tdata1),
+            TDATA2 => Some(t.tdata2),
+            TDATA3 => Some(0),
+            //version 1, mcontrol, mcontrol6 and disabled
+            TINFO => Some(
+                (1 << 24)
+                    | (1 << TYPE_MCONTROL)
+                    | (1 << TYPE_MCONTROL6)
+                    | (1 << TYPE_DISABLED),
+            ),
+            _ => None,
+        }
+    }
+
+    pub fn csr_write(&self, addr: InsnT, value: RegT, debug_mode: bool) -> Option<()> {
+        let mut triggers = self.triggers.borrow_mut();
+        if triggers.is_empty() {
+            return if addr == TSELECT { Some(()) } else { None };
+        }
+        let sel = self.tselect.get();
+        match addr {
+            TSELECT => {
+                if (value as usize) < triggers.len() {
+                    self.tselect.set(value as usize)
+                }
+            }
+            TDATA1 => {
+                if !self.dmode(triggers[sel].tdata1) || debug_mode {
+                    triggers[sel].tdata1 = self.legalize(value & self.xlen.mask(), debug_mode);
+                    self.update_ops_en(&triggers);
+                }
+            }
+            TDATA2 => {
+                if !self.dmode(triggers[sel].tdata1) || debug_mode {
+                    triggers[sel].tdata2 = value & self.xlen.mask();
+                }
+            }
+            TDATA3 | TINFO => {}
+            _ => return None,
+        }
+        Some(())
+    }
+
+    pub fn save_snapshot<W: Write>(&self, w: &mut SnapshotWriter<W>) -> io::Result<()> {
+        w.write_u64(self.tselect.get() as u64)?;
+        for t in self.triggers.borrow().iter() {
+            w.write_u64(t.tdata1)?;
+            w.write_u64(t.tdata2)?;
+        }
+        Ok(())
+    }
+
+    pub fn restore_snapshot<R: Read>(&self, r: &mut SnapshotReader<R>) -> io::Result<()> {
+        self.tselect.set(r.read_u64()? as usize);
+        let mut triggers = self.triggers.borrow_mut();
+        for t in triggers.iter_mut() {
+            t.tdata1 = r.read_u64()?;
+            t.tdata2 = r.read_u64()?;
+        }
+        self.update_ops_en(&triggers);
+        self.debug_pending.set(false);
+        Ok(())
+    }
+
+    pub fn enabled(&self, op: &TriggerOp) -> bool {
+        self.ops_en.get() & op.mask() != 0
+    }
+
+    pub fn take_debug_pending(&self) -> bool {
+        self.debug_pending.replace(false)
+    }
+
+    fn set_hit(&self, t: &mut Trigger) {
+        let xlen = self.xlen.len();
+        match self.ty(t.tdata1) {
+            TYPE_MCONTROL => {
+                let mut c = Mcontrol::new(xlen, t.tdata1);
+                c.set_hit(1);
+                t.tdata1 = c.get()
+            }
+            TYPE_MCONTROL6 => {
+                let mut c = Mcontrol6::new(xlen, t.tdata1);
+                c.set_hit0(1);
+                c.set_hit1(0);
+                t.tdata1 = c.get()
+            }
+            _ => {}
+        }
+    }
+
+    pub fn check(
+        &self,
+        op: TriggerOp,
+        privilege: &Privilege,
+        virt: bool,
+        addr: RegT,
+        len: usize,
+        data: RegT,
+    ) -> Result<(), Exception> {
+        let mut triggers = self.triggers.borrow_mut();
+        let mut chain_ok = true;
+        let mut chain_start = 0;
+        for i in 0..triggers.len() {
+            let ctrl = match self.decode(triggers[i].tdata1) {
+                Some(c) => c,
+                None => {
+                    chain_ok = true;
+                    chain_start = i + 1;
+                    continue;
+                }
+            };
+            let matched = chain_ok
+                && ctrl.ops & op.mask() != 0
+                && ctrl.privilege_match(privilege, virt)
+                && (ctrl.size == 0 || ctrl.size == len)
+                && ctrl.value_match(
+                    &self.xlen,
+                    triggers[i].tdata2,
+                    if ctrl.select { data } else { addr },
+                    len,
+                );
+            if ctrl.chain && i + 1 < triggers.len() {
+                chain_ok = matched;
+                continue;
+            }
+            if matched {
+                for t in triggers[chain_start..=i].iter_mut() {
+                    self.set_hit(t)
+                }
+                self.debug_pending.set(ctrl.action == ACTION_DEBUG);
+                return Err(Exception::Breakpoint);
+            }
+            chain_ok = true;
+            chain_start = i + 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::devices::bus::Bus;
+    use crate::processor::{ProcessorCfg, SatpMode};
+    use crate::system::System;
+    use terminus_spaceport::memory::region::GHEAP;
+
+    const LOAD: RegT = 1;
+    const STORE: RegT = 2;
+
+    //mcontrol6 enabled in M, S and U mode
+    fn mcontrol6(ops: RegT, mtch: RegT, chain: bool) -> RegT {
+        let mut c = Mcontrol6::new(64, 0);
+        c.set_ty(TYPE_MCONTROL6);
+        c.set_load(ops & 1);
+        c.set_store((ops >> 1) & 1);
+        c.set_execute((ops >> 2) & 1);
+        c.set_m(1);
+        c.set_s(1);
+        c.set_u(1);
+        c.set_mtch(mtch);
+        c.set_chain(chain as RegT);
+        c.get()
+    }
+
+    fn triggers(tdata: &[(RegT, RegT)]) -> Triggers {
+        let t = Triggers::new(XLen::X64, tdata.len());
+        for (i, (tdata1, tdata2)) in tdata.iter().enumerate() {
+            t.csr_write(TSELECT, i as RegT, false).unwrap();
+            t.csr_write(TDATA1, *tdata1, false).unwrap();
+            t.csr_write(TDATA2, *tdata2, false).unwrap();
+        }
+        t
+    }
+
+    fn tdata1(t: &Triggers, i: usize) -> RegT {
+        t.csr_write(TSELECT, i as RegT, false).unwrap();
+        t.csr_read(TDATA1).unwrap()
+    }
+
+    fn load(t: &Triggers, addr: RegT, len: usize) -> bool {
+        t.check(TriggerOp::Load, &Privilege::M, false, addr, len, 0)
+            .is_err()
+    }
+
+    #[test]
+    fn legalize_test() {
+        let t = triggers(&[(0, 0)]);
+        let write = |value: RegT, debug_mode: bool| {
+            t.csr_write(TDATA1, value, debug_mode).unwrap();
+            t.csr_read(TDATA1).unwrap()
+        };
+        //unsupported type is disabled
+        assert_eq!(write(3 << 60 | 0x44, false), TYPE_DISABLED << 60);
+        //action debug needs dmode, which is only writable in debug mode
+        let debug = mcontrol6(LOAD, 0, false) | 1 << 59 | ACTION_DEBUG << 12;
+        let c = Mcontrol6::new(64, write(debug, false));
+        assert_eq!((c.dmode(), c.action()), (0, ACTION_BREAKPOINT));
+        let c = Mcontrol6::new(64, write(debug, true));
+        assert_eq!((c.dmode(), c.action()), (1, ACTION_DEBUG));
+        //dmode triggers are read-only out of debug mode
+        write(mcontrol6(STORE, 0, false), false);
+        assert_eq!(tdata1(&t, 0), debug);
+        write(mcontrol6(STORE, 0, false), true);
+        assert_eq!(tdata1(&t, 0), mcontrol6(STORE, 0, false));
+        //unsupported action, match, size, vs and vu
+        let mut c = Mcontrol6::new(64, mcontrol6(LOAD, 6, false));
+        c.set_action(5);
+        c.set_size(7);
+        c.set_vs(1);
+        c.set_vu(1);
+        assert_eq!(write(c.get(), false), mcontrol6(LOAD, 0, false));
+        assert_eq!(
+            write(mcontrol6(LOAD, 9, false), false),
+            mcontrol6(LOAD, 9, false)
+        );
+        //mcontrol only supports timing before, maskmax is xlen - 1
+        let mut c = Mcontrol::new(64, 0);
+        c.set_ty(TYPE_MCONTROL);
+        c.set_load(1);
+        c.set_timing(1);
+        c.set_sizehi(1);
+        c.set_sizelo(3);
+        let c = Mcontrol::new(64, write(c.get(), false));
+        assert_eq!((c.timing(), c.sizehi(), c.sizelo()), (0, 0, 0));
+        assert_eq!(c.maskmax(), 63);
+    }
+
+    #[test]
+    fn match_test() {
+        let matches = |mtch: RegT, tdata2: RegT, addr: RegT, len: usize| {
+            load(
+                &triggers(&[(mcontrol6(LOAD, mtch, false), tdata2)]),
+                addr,
+                len,
+            )
+        };
+        //equal, hit by any accessed byte
+        assert!(matches(0, 0x1000, 0x1000, 1));
+        assert!(matches(0, 0x1000, 0xffd, 4));
+        assert!(!matches(0, 0x1000, 0xffc, 4));
+        assert!(!matches(0, 0x1000, 0x1001, 1));
+        //napot, 0x1000 - 0x1007
+        assert!(matches(1, 0x1003, 0x1007, 1));
+        assert!(!matches(1, 0x1003, 0x1008, 1));
+        assert!(!matches(1, 0x1003, 0xfff, 1));
+        //ge and lt
+        assert!(matches(2, 0x1000, 0x1000, 1));
+        assert!(!matches(2, 0x1000, 0xfff, 1));
+        assert!(matches(3, 0x1000, 0xfff, 1));
+        assert!(!matches(3, 0x1000, 0x1000, 1));
+        //mask low and mask high, mask in the high half of tdata2
+        assert!(matches(4, 0xff00 << 32 | 0x1200, 0x5_0000_12ab, 1));
+        assert!(!matches(4, 0xff00 << 32 | 0x1200, 0x13ab, 1));
+        assert!(matches(5, 0xffff << 32 | 0x8000, 0x8000_0000_1234, 1));
+        assert!(!matches(5, 0xffff << 32 | 0x8000, 0x4000_0000_1234, 1));
+        //negated equal
+        assert!(matches(8, 0x1000, 0x2000, 1));
+        assert!(!matches(8, 0x1000, 0x1000, 1));
+    }
+
+    #[test]
+    fn chain_test() {
+        //first trigger hits but second misses
+        let t = triggers(&[
+            (mcontrol6(LOAD, 0, true), 0x1000),
+            (mcontrol6(LOAD, 3, false), 0x1000),
+        ]);
+        assert!(!load(&t, 0x1000, 1));
+        //second trigger hits but first misses
+        assert!(!load(&t, 0xfff, 1));
+        assert_eq!(tdata1(&t, 0), mcontrol6(LOAD, 0, true));
+        assert_eq!(tdata1(&t, 1), mcontrol6(LOAD, 3, false));
+        //both hit
+        let t = triggers(&[
+            (mcontrol6(LOAD, 0, true), 0x1000),
+            (mcontrol6(LOAD, 3, false), 0x1004),
+        ]);
+        assert_eq!(
+            t.check(TriggerOp::Load, &Privilege::M, false, 0x1000, 1, 0),
+            Err(Exception::Breakpoint)
+        );
+        assert_eq!(Mcontrol6::new(64, tdata1(&t, 0)).hit0(), 1);
+        assert_eq!(Mcontrol6::new(64, tdata1(&t, 1)).hit0(), 1);
+    }
+
+    #[test]
+    fn virt_test() {
+        let t = triggers(&[(mcontrol6(LOAD, 0, false), 0x1000)]);
+        let check = |privilege: Privilege, virt: bool| {
+            t.check(TriggerOp::Load, &privilege, virt, 0x1000, 1, 0)
+                .is_err()
+        };
+        assert!(check(Privilege::S, false) && check(Privilege::U, false));
+        //vs and vu are hardwired to 0
+        assert!(!check(Privilege::S, true) && !check(Privilege::U, true));
+        assert!(check(Privilege::M, false));
+    }
+
+    #[test]
+    fn timing_test() {
+        let mut sys = System::new("test", "top_tests/elf/rv64ui-p-add", 100, 1);
+        sys.new_processor(ProcessorCfg {
+            xlen: XLen::X64,
+            enable_dirty: true,
+            isa: "rv64imac".parse().unwrap(),
+            freq: 1000000000,
+            triggers: 1,
+            satp_modes: vec![SatpMode::Sv39].into_boxed_slice(),
+            vlen: 128,
+            elen: 64,
+            cache_block_size: 64,
+            entropy_seed: 0,
+            hpm_counters: 0,
+        });
+        sys.register_memory("main_memory", 0x8000_0000, &GHEAP.alloc(0x2000, 1).unwrap())
+            .unwrap();
+        let insns: [u32; 2] = [
+            0x00b62023, //sw a1, 0(a2)
+            0x00062503, //lw a0, 0(a2)
+        ];
+        for (i, insn) in insns.iter().enumerate() {
+            sys.bus()
+                .write_u32(&(0x8000_0000 + i as u64 * 4), insn)
+                .unwrap();
+        }
+        sys.bus().write_u32(&0x8000_1000, &0x55).unwrap();
+        sys.reset(vec![0x8000_0000]).unwrap();
+        let p = sys.processor(0).unwrap();
+        let t = &p.state().triggers;
+        t.csr_write(TDATA1, mcontrol6(LOAD | STORE, 0, false), false)
+            .unwrap();
+        t.csr_write(TDATA2, 0x8000_1000, false).unwrap();
+        p.state_mut().set_xreg(11, 0xaa);
+        p.state_mut().set_xreg(12, 0x8000_1000);
+
+        //triggers fire before the store is performed
+        p.step(1);
+        assert_eq!(p.state().priv_m().mcause().get(), 3);
+        assert_eq!(p.state().priv_m().mepc().get(), 0x8000_0000);
+        let mut data = 0;
+        sys.bus().read_u32(&0x8000_1000, &mut data).unwrap();
+        assert_eq!(data, 0x55);
+
+        //and before the load writes back
+        let p = sys.processor(0).unwrap();
+        p.state_mut().set_pc(0x8000_0004);
+        p.step(1);
+        assert_eq!(p.state().priv_m().mcause().get(), 3);
+        assert_eq!(p.state().priv_m().mepc().get(), 0x8000_0004);
+        assert_eq!(*p.state().xreg(10), 0);
+    }
+}
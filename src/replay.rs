@@ -0,0 +1,366 @@
+use crate::snapshot::{snapshot_err, SnapshotReader, SnapshotWriter};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use std::cell::{Cell, RefCell};
+use std::cmp::max;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter};
+use std::rc::Rc;
+
+// Non-deterministic inputs are logged against the total number of steps taken by all harts, and the
+// steps of each hart are logged in the order they are taken. Re-executing the same steps in the
+// same order and replaying the same inputs at the same step counts reproduces the same execution.
+
+const REPLAY_MAGIC: &[u8; 8] = b"TERMRPLY";
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, IntoPrimitive, TryFromPrimitive)]
+#[repr(u8)]
+pub enum InputSrc {
+    Htif = 0,
+    VirtIOConsole = 1,
+    VirtIONet = 2,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, IntoPrimitive, TryFromPrimitive)]
+#[repr(u8)]
+enum EventKind {
+    Tick = 0,
+    Input = 1,
+    Sched = 2,
+}
+
+struct TickEvent {
+    clock: u64,
+    n: u64,
+}
+
+struct InputEvent {
+    clock: u64,
+    src: InputSrc,
+    data: Vec<u8>,
+}
+
+//hart took n steps from clock
+struct SchedEvent {
+    clock: u64,
+    hart: usize,
+    n: u64,
+}
+
+//position in the event history, saved with checkpoints
+#[derive(Copy, Clone, Debug)]
+pub struct ReplayPos {
+    tick: usize,
+    input: usize,
+}
+
+struct ReplayInner {
+    clocks: Vec<Rc<RefCell<u64>>>,
+    //keep events in memory, so that the history can be rewound
+    keep: bool,
+    log: Option<SnapshotWriter<BufWriter<File>>>,
+    ticks: Vec<TickEvent>,
+    inputs: Vec<InputEvent>,
+    scheds: Vec<SchedEvent>,
+    tick_pos: usize,
+    input_pos: usize,
+    sched_pos: usize,
+    //the furthest point of history, inputs come from host after it
+    end_clock: u64,
+}
+
+impl ReplayInner {
+    fn clock(&self) -> u64 {
+        self.clocks.iter().map(|c| *c.borrow()).sum()
+    }
+
+    fn tick_live(&self, clock: u64) -> bool {
+        self.tick_pos == self.ticks.len() && clock >= self.end_clock
+    }
+
+    fn input_live(&self, clock: u64) -> bool {
+        self.input_pos == self.inputs.len() && clock >= self.end_clock
+    }
+
+    //index of the first schedule not finished at clock
+    fn sched_seek(&mut self, clock: u64) {
+        while let Some(e) = self.scheds.get(self.sched_pos) {
+            if e.clock + e.n > clock {
+                break;
+            }
+            self.sched_pos += 1;
+        }
+    }
+
+    //consecutive steps of the same hart are merged
+    fn push_sched(&mut self, e: SchedEvent) {
+        if let Some(last) = self.scheds.last_mut() {
+            if last.hart == e.hart && last.clock + last.n == e.clock {
+                last.n += e.n;
+                return;
+            }
+        }
+        self.scheds.push(e)
+    }
+
+    fn write_sched(&mut self, e: &SchedEvent) -> io::Result<()> {
+        if let Some(ref mut log) = self.log {
+            log.write_u8(EventKind::Sched.into())?;
+            log.write_u64(e.clock)?;
+            log.write_u64(e.hart as u64)?;
+            log.write_u64(e.n)?;
+        }
+        Ok(())
+    }
+
+    fn write_tick(&mut self, e: &TickEvent) -> io::Result<()> {
+        if let Some(ref mut log) = self.log {
+            log.write_u8(EventKind::Tick.into())?;
+            log.write_u64(e.clock)?;
+            log.write_u64(e.n)?;
+        }
+        Ok(())
+    }
+
+    fn write_input(&mut self, e: &InputEvent) -> io::Result<()> {
+        if let Some(ref mut log) = self.log {
+            log.write_u8(EventKind::Input.into())?;
+            log.write_u64(e.clock)?;
+            log.write_u8(e.src.into())?;
+            log.write_u64(e.data.len() as u64)?;
+            log.write_bytes(&e.data)?;
+            //inputs are rare and precious, do not lose them on crash
+            log.flush()?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Replay {
+    inner: RefCell<ReplayInner>,
+    muted: Cell<bool>,
+}
+
+impl Replay {
+    pub fn new() -> Replay {
+        Replay {
+            inner: RefCell::new(ReplayInner {
+                clocks: vec![],
+                keep: false,
+                log: None,
+                ticks: vec![],
+                inputs: vec![],
+                scheds: vec![],
+                tick_pos: 0,
+                input_pos: 0,
+                sched_pos: 0,
+                end_clock: 0,
+            }),
+            muted: Cell::new(false),
+        }
+    }
+
+    pub fn add_clock(&self, steps_cnt: &Rc<RefCell<u64>>) {
+        self.inner.borrow_mut().clocks.push(steps_cnt.clone())
+    }
+
+    pub fn clock(&self) -> u64 {
+        self.inner.borrow().clock()
+    }
+
+    //keep events in memory for rewinding
+    pub fn keep_events(&self) {
+        self.inner.borrow_mut().keep = true
+    }
+
+    pub fn record(&self, path: &str) -> io::Result<()> {
+        let mut inner = self.inner.borrow_mut();
+        inner.keep = true;
+        inner.log = Some(SnapshotWriter::with_magic(
+            BufWriter::new(File::create(path)?),
+            REPLAY_MAGIC,
+        )?);
+        Ok(())
+    }
+
+    pub fn replay(&self, path: &str) -> io::Result<()> {
+        let mut r = SnapshotReader::with_magic(BufReader::new(File::open(path)?), REPLAY_MAGIC)?;
+        let mut inner = self.inner.borrow_mut();
+        inner.keep = true;
+        while r.has_more()? {
+            let kind = r.read_u8()?;
+            match EventKind::try_from(kind) {
+                Ok(EventKind::Tick) => {
+                    let clock = r.read_u64()?;
+                    let n = r.read_u64()?;
+                    inner.end_clock = max(inner.end_clock, clock);
+                    inner.ticks.push(TickEvent { clock, n })
+                }
+                Ok(EventKind::Input) => {
+                    let clock = r.read_u64()?;
+                    let src = r.read_u8()?;
+                    let src = match InputSrc::try_from(src) {
+                        Ok(src) => src,
+                        Err(_) => return snapshot_err(format!("invalid input source {}!", src)),
+                    };
+                    let len = r.read_u64()?;
+                    let mut data = vec![0u8; len as usize];
+                    r.read_bytes(&mut data)?;
+                    inner.end_clock = max(inner.end_clock, clock);
+                    inner.inputs.push(InputEvent { clock, src, data })
+                }
+                Ok(EventKind::Sched) => {
+                    let clock = r.read_u64()?;
+                    let hart = r.read_u64()? as usize;
+                    let n = r.read_u64()?;
+                    inner.end_clock = max(inner.end_clock, clock + n);
+                    inner.push_sched(SchedEvent { clock, hart, n })
+                }
+                Err(_) => return snapshot_err(format!("invalid replay event {}!", kind)),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn flush(&self) -> io::Result<()> {
+        if let Some(ref mut log) = self.inner.borrow_mut().log {
+            log.flush()
+        } else {
+            Ok(())
+        }
+    }
+
+    //host output is muted while re-executing history
+    pub fn muted(&self) -> bool {
+        self.muted.get()
+    }
+
+    pub fn set_muted(&self, muted: bool) {
+        self.muted.set(muted)
+    }
+
+    pub fn pos(&self) -> ReplayPos {
+        let inner = self.inner.borrow();
+        ReplayPos {
+            tick: inner.tick_pos,
+            input: inner.input_pos,
+        }
+    }
+
+    pub fn rewind(&self, pos: ReplayPos) {
+        let mut inner = self.inner.borrow_mut();
+        inner.tick_pos = pos.tick;
+        inner.input_pos = pos.input;
+        inner.sched_pos = 0;
+        let clock = inner.clock();
+        inner.sched_seek(clock);
+    }
+
+    //log that hart took n steps from clock, steps in recorded history only move forward
+    pub fn schedule(&self, hart: usize, clock: u64, n: u64) {
+        let mut inner = self.inner.borrow_mut();
+        if n == 0 || !inner.keep {
+            return;
+        }
+        inner.sched_seek(clock);
+        if inner.sched_pos == inner.scheds.len() && clock + n > inner.end_clock {
+            let e = SchedEvent { clock, hart, n };
+            inner.write_sched(&e).expect("write replay log fail!");
+            inner.push_sched(e);
+            inner.sched_pos = inner.scheds.len();
+            inner.end_clock = clock + n;
+        } else {
+            inner.sched_seek(clock + n);
+        }
+    }
+
+    //the hart taking the next step in recorded history
+    pub fn scheduled(&self) -> Option<usize> {
+        let mut inner = self.inner.borrow_mut();
+        let clock = inner.clock();
+        inner.sched_seek(clock);
+        match inner.scheds.get(inner.sched_pos) {
+            Some(e) if e.clock <= clock => Some(e.hart),
+            _ => None,
+        }
+    }
+
+    //read a byte from host, or from history when replaying
+    pub fn input<F: FnOnce() -> Option<u8>>(&self, src: InputSrc, f: F) -> Option<u8> {
+        self.input_bytes(src, || f().map(|data| vec![data]))
+            .map(|data| data[0])
+    }
+
+    //read a chunk of bytes from host, or from history when replaying
+    pub fn input_bytes<F: FnOnce() -> Option<Vec<u8>>>(
+        &self,
+        src: InputSrc,
+        f: F,
+    ) -> Option<Vec<u8>> {
+        let mut inner = self.inner.borrow_mut();
+        let clock = inner.clock();
+        if inner.input_live(clock) {
+            let data = f();
+            if let Some(ref data) = data {
+                if inner.keep {
+                    let e = InputEvent {
+                        clock,
+                        src,
+                        data: data.clone(),
+                    };
+                    inner.write_input(&e).expect("write replay log fail!");
+                    inner.inputs.push(e);
+                    inner.input_pos = inner.inputs.len();
+                }
+                inner.end_clock = clock;
+            }
+            data
+        } else {
+            let data = match inner.inputs.get(inner.input_pos) {
+                Some(e) if e.clock <= clock && e.src == src => Some(e.data.clone()),
+                _ => None,
+            };
+            if data.is_some() {
+                inner.input_pos += 1;
+            }
+            data
+        }
+    }
+
+    //return true if the tick should be applied, ticks are ignored when replaying
+    pub fn tick(&self, n: u64) -> bool {
+        let mut inner = self.inner.borrow_mut();
+        let clock = inner.clock();
+        if inner.tick_live(clock) {
+            if inner.keep {
+                let e = TickEvent { clock, n };
+                inner.write_tick(&e).expect("write replay log fail!");
+                inner.ticks.push(e);
+                inner.tick_pos = inner.ticks.len();
+            }
+            inner.end_clock = clock;
+            true
+        } else {
+            false
+        }
+    }
+
+    //return the sum of recorded ticks due at current clock
+    pub fn sync(&self) -> u64 {
+        let mut inner = self.inner.borrow_mut();
+        let clock = inner.clock();
+        let mut n = 0;
+        while let Some(e) = inner.ticks.get(inner.tick_pos) {
+            if e.clock > clock {
+                break;
+            }
+            n += e.n;
+            inner.tick_pos += 1;
+        }
+        if inner.tick_pos == inner.ticks.len() && inner.input_pos == inner.inputs.len() {
+            inner.end_clock = max(inner.end_clock, clock)
+        }
+        n
+    }
+}
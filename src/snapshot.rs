@@ -0,0 +1,151 @@
+use std::io;
+use std::io::{BufRead, ErrorKind, Read, Write};
+
+const SNAPSHOT_MAGIC: &[u8; 8] = b"TERMSNAP";
+//a snapshot is the magic and version followed by little endian fields written by each component in
+//order: harts, timer, interrupt controllers, htif, virtio-mmio devices, bus locks and memory contents
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+pub fn snapshot_err<T>(msg: String) -> io::Result<T> {
+    Err(io::Error::new(ErrorKind::InvalidData, msg))
+}
+
+pub struct SnapshotWriter<W: Write> {
+    w: W,
+}
+
+impl<W: Write> SnapshotWriter<W> {
+    pub fn new(w: W) -> io::Result<SnapshotWriter<W>> {
+        Self::with_magic(w, SNAPSHOT_MAGIC)
+    }
+
+    pub fn with_magic(mut w: W, magic: &[u8; 8]) -> io::Result<SnapshotWriter<W>> {
+        w.write_all(magic)?;
+        w.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+        Ok(SnapshotWriter { w })
+    }
+
+    pub fn write_bytes(&mut self, data: &[u8]) -> io::Result<()> {
+        self.w.write_all(data)
+    }
+
+    pub fn write_bool(&mut self, v: bool) -> io::Result<()> {
+        self.write_bytes(&[v as u8])
+    }
+
+    pub fn write_u8(&mut self, v: u8) -> io::Result<()> {
+        self.write_bytes(&[v])
+    }
+
+    pub fn write_u32(&mut self, v: u32) -> io::Result<()> {
+        self.write_bytes(&v.to_le_bytes())
+    }
+
+    pub fn write_u64(&mut self, v: u64) -> io::Result<()> {
+        self.write_bytes(&v.to_le_bytes())
+    }
+
+    pub fn write_u128(&mut self, v: u128) -> io::Result<()> {
+        self.write_bytes(&v.to_le_bytes())
+    }
+
+    pub fn write_str(&mut self, v: &str) -> io::Result<()> {
+        self.write_u64(v.len() as u64)?;
+        self.write_bytes(v.as_bytes())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.w.flush()
+    }
+}
+
+pub struct SnapshotReader<R: Read> {
+    r: R,
+}
+
+impl<R: Read> SnapshotReader<R> {
+    pub fn new(r: R) -> io::Result<SnapshotReader<R>> {
+        Self::with_magic(r, SNAPSHOT_MAGIC)
+    }
+
+    pub fn with_magic(mut r: R, expect: &[u8; 8]) -> io::Result<SnapshotReader<R>> {
+        let mut magic = [0u8; 8];
+        r.read_exact(&mut magic)?;
+        if &magic != expect {
+            return snapshot_err(format!(
+                "not a terminus {} file!",
+                String::from_utf8_lossy(expect)
+            ));
+        }
+        let mut version = [0u8; 4];
+        r.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+        if version != SNAPSHOT_VERSION {
+            return snapshot_err(format!(
+                "snapshot version {} is not supported, expect {}!",
+                version, SNAPSHOT_VERSION
+            ));
+        }
+        Ok(SnapshotReader { r })
+    }
+
+    pub fn read_bytes(&mut self, data: &mut [u8]) -> io::Result<()> {
+        self.r.read_exact(data)
+    }
+
+    //false at the end of input
+    pub fn has_more(&mut self) -> io::Result<bool>
+    where
+        R: BufRead,
+    {
+        Ok(!self.r.fill_buf()?.is_empty())
+    }
+
+    pub fn read_bool(&mut self) -> io::Result<bool> {
+        Ok(self.read_u8()? != 0)
+    }
+
+    pub fn read_u8(&mut self) -> io::Result<u8> {
+        let mut bytes = [0u8; 1];
+        self.read_bytes(&mut bytes)?;
+        Ok(bytes[0])
+    }
+
+    pub fn read_u32(&mut self) -> io::Result<u32> {
+        let mut bytes = [0u8; 4];
+        self.read_bytes(&mut bytes)?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    pub fn read_u64(&mut self) -> io::Result<u64> {
+        let mut bytes = [0u8; 8];
+        self.read_bytes(&mut bytes)?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    pub fn read_u128(&mut self) -> io::Result<u128> {
+        let mut bytes = [0u8; 16];
+        self.read_bytes(&mut bytes)?;
+        Ok(u128::from_le_bytes(bytes))
+    }
+
+    pub fn read_str(&mut self) -> io::Result<String> {
+        let len = self.read_u64()? as usize;
+        let mut bytes = vec![0u8; len];
+        self.read_bytes(&mut bytes)?;
+        String::from_utf8(bytes).or_else(|e| snapshot_err(e.to_string()))
+    }
+
+    //check a configuration value matches the current system
+    pub fn expect_u64(&mut self, expect: u64, what: &str) -> io::Result<()> {
+        let v = self.read_u64()?;
+        if v != expect {
+            snapshot_err(format!(
+                "snapshot {} mismatch, expect {:#x} but get {:#x}!",
+                what, expect, v
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,330 @@
+use super::{Error, Result, System};
+use crate::processor::Processor;
+use crate::replay::ReplayPos;
+use std::rc::Rc;
+
+// Reverse execution: restore the latest checkpoint before the target and re-execute history.
+// Harts stepped by step_hart are re-executed one step at a time in the recorded order, and inputs
+// and timer ticks are replayed at the recorded step counts, so re-execution is exact for any number
+// of harts.
+
+const MAX_CHECKPOINTS: usize = 32;
+
+pub struct Checkpoint {
+    clock: u64,
+    pos: ReplayPos,
+    data: Rc<Vec<u8>>,
+}
+
+impl System {
+    //total instructions executed by all harts
+    pub fn insns_clock(&self) -> u64 {
+        self.processors
+            .iter()
+            .map(|p| *p.state().insns_cnt().borrow())
+            .sum()
+    }
+
+    //step a hart by f and log its steps, so that re-execution takes them in the same order
+    pub fn step_hart<T, F: FnOnce(&mut Processor) -> T>(&mut self, hart: usize, f: F) -> T {
+        let clock = self.replay.clock();
+        let p = &mut self.processors[hart];
+        let start = *p.state().steps_cnt().borrow();
+        let result = f(p);
+        let n = *p.state().steps_cnt().borrow() - start;
+        self.replay.schedule(hart, clock, n);
+        result
+    }
+
+    //take a checkpoint every interval instructions in replay_sync, events are kept for rewinding
+    pub fn enable_checkpoints(&mut self, interval: u64) -> Result<()> {
+        if interval == 0 {
+            return Err(Error::SnapshotErr(
+                "checkpoint interval can not be 0!".to_string(),
+            ));
+        }
+        self.replay.keep_events();
+        self.checkpoint_interval = interval;
+        self.take_checkpoint()
+    }
+
+    //apply recorded ticks when replaying and take checkpoints, should be called after every step
+    pub fn replay_sync(&mut self) {
+        let n = self.replay.sync();
+        if n != 0 {
+            self.timer.tick(n)
+        }
+        if self.checkpoint_interval != 0 {
+            let last = self.checkpoints.last().map_or(0, |cp| cp.clock);
+            if self.insns_clock() >= last + self.checkpoint_interval {
+                self.take_checkpoint().expect("take checkpoint fail!")
+            }
+        }
+    }
+
+    fn take_checkpoint(&mut self) -> Result<()> {
+        let mut data = vec![];
+        self.save_snapshot_to(&mut data)?;
+        self.checkpoints.push(Checkpoint {
+            clock: self.insns_clock(),
+            pos: self.replay.pos(),
+            data: Rc::new(data),
+        });
+        //keep whole history with less precision
+        if self.checkpoints.len() > MAX_CHECKPOINTS {
+            let mut i = 0;
+            self.checkpoints.retain(|_| {
+                i += 1;
+                i & 1 == 1
+            });
+            self.checkpoint_interval <<= 1;
+        }
+        Ok(())
+    }
+
+    fn restore_checkpoint(&mut self, i: usize) -> Result<()> {
+        let data = self.checkpoints[i].data.clone();
+        self.restore_snapshot_from(&data[..])?;
+        self.replay.rewind(self.checkpoints[i].pos);
+        Ok(())
+    }
+
+    //step harts one step at a time in the recorded order until total instructions reach end,
+    //recorded history ends, a hart halts or stop returns true, return the hart stopped
+    //stop is called with (steps, hartid, processor, clock) after each step
+    fn rerun<F: FnMut(usize, usize, &Processor, u64) -> bool>(
+        &mut self,
+        end: u64,
+        mut stop: F,
+    ) -> Option<usize> {
+        self.replay.set_muted(true);
+        for p in self.processors.iter_mut() {
+            p.debug_resume()
+        }
+        let mut steps = 0;
+        let stopped = loop {
+            let n = self.replay.sync();
+            if n != 0 {
+                self.timer.tick(n)
+            }
+            if self.insns_clock() >= end {
+                break None;
+            }
+            let i = match self.replay.scheduled() {
+                Some(i) if i < self.processors.len() => i,
+                _ => break None,
+            };
+            self.processors[i].step(1);
+            //recorded virtio input is taken at the step it was polled
+            for device in self.virtio_mmios.iter() {
+                device.poll()
+            }
+            steps += 1;
+            let clock = self.insns_clock();
+            let p = &self.processors[i];
+            if stop(steps, i, p, clock) || p.state().debug_mode() {
+                break Some(i);
+            }
+        };
+        self.replay.set_muted(false);
+        stopped
+    }
+
+    //true if there is recorded history after current point
+    pub fn in_history(&self) -> bool {
+        self.replay.scheduled().is_some()
+    }
+
+    //go forward in recorded history until hit(hartid, processor) is true or a hart halts after a
+    //step, return the hartid, or None at the end of history
+    pub fn forward_search<F: FnMut(usize, &Processor) -> bool>(
+        &mut self,
+        mut hit: F,
+    ) -> Option<usize> {
+        self.rerun(u64::MAX, |_, hart, p, _| hit(hart, p))
+    }
+
+    //go to the point where total instructions reach target, within recorded history
+    pub fn goto_insns(&mut self, target: u64) -> Result<()> {
+        if target < self.insns_clock() {
+            let i = match self.checkpoints.iter().rposition(|cp| cp.clock <= target) {
+                Some(i) => i,
+                None => {
+                    return Err(Error::SnapshotErr(format!(
+                        "no checkpoint before {}!",
+                        target
+                    )))
+                }
+            };
+            self.restore_checkpoint(i)?;
+        }
+        self.rerun(target, |_, _, _, _| false);
+        Ok(())
+    }
+
+    //go back to the last point before current where hit(hartid, processor) is true, return the hartid
+    //if no hit, go to the beginning of history and return None
+    pub fn reverse_search<F: FnMut(usize, &Processor) -> bool>(
+        &mut self,
+        mut hit: F,
+    ) -> Result<Option<usize>> {
+        let mut end = self.insns_clock();
+        for i in (0..self.checkpoints.len()).rev() {
+            if self.checkpoints[i].clock >= end {
+                continue;
+            }
+            self.restore_checkpoint(i)?;
+            let mut last = None;
+            self.rerun(end, |steps, hart, p, clock| {
+                if clock < end && hit(hart, p) {
+                    last = Some((steps, hart))
+                }
+                false
+            });
+            if let Some((steps, hart)) = last {
+                self.restore_checkpoint(i)?;
+                self.rerun(u64::MAX, |s, _, _, _| s == steps);
+                return Ok(Some(hart));
+            }
+            end = self.checkpoints[i].clock;
+        }
+        if !self.checkpoints.is_empty() {
+            self.restore_checkpoint(0)?;
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::devices::bus::Bus;
+    use crate::prelude::{RegT, XLen};
+    use crate::processor::{ProcessorCfg, SatpMode};
+    use terminus_spaceport::memory::region::GHEAP;
+
+    //lr/sc loop whose distance between lr and sc grows past the reservation timeout:
+    //      auipc a0, 1
+    //loop: lr.w t0, (a0)
+    //      andi t2, s1, 15
+    //delay:beqz t2, done
+    //      addi t2, t2, -1
+    //      j delay
+    //done: addi t0, t0, 1
+    //      sc.w t1, t0, (a0)
+    //      add s0, s0, t1
+    //      addi s1, s1, 1
+    //      j loop
+    const LR_SC_LOOP: [u32; 11] = [
+        0x00001517, 0x100522af, 0x00f4f393, 0x00038663, 0xfff38393, 0xff9ff06f, 0x00128293,
+        0x1855232f, 0x00640433, 0x00148493, 0xfddff06f,
+    ];
+
+    fn lr_sc_state(sys: &mut System) -> (u64, RegT, RegT) {
+        let mut data = 0;
+        sys.bus().read_u64(&0x8000_1000, &mut data).unwrap();
+        let p = sys.processor(0).unwrap();
+        (data, *p.state().xreg(8), *p.state().xreg(9))
+    }
+
+    fn system(harts: usize, program: &[u32]) -> System {
+        let mut sys = System::new("test", "top_tests/elf/rv64ui-p-add", 100, 1);
+        for _ in 0..harts {
+            sys.new_processor(ProcessorCfg {
+                xlen: XLen::X64,
+                enable_dirty: true,
+                isa: "rv64imacsu".parse().unwrap(),
+                freq: 1000000000,
+                triggers: 0,
+                satp_modes: vec![SatpMode::Sv39].into_boxed_slice(),
+                vlen: 128,
+                elen: 64,
+                cache_block_size: 64,
+                entropy_seed: 0,
+                hpm_counters: 0,
+            });
+        }
+        sys.register_memory("main_memory", 0x8000_0000, &GHEAP.alloc(0x2000, 1).unwrap())
+            .unwrap();
+        for (i, insn) in program.iter().enumerate() {
+            sys.bus()
+                .write_u32(&(0x8000_0000 + i as u64 * 4), insn)
+                .unwrap();
+        }
+        sys.reset(vec![0x8000_0000; harts]).unwrap();
+        sys
+    }
+
+    #[test]
+    fn rerun_lr_sc_test() {
+        let mut sys = system(1, &LR_SC_LOOP);
+        sys.enable_checkpoints(1_000_000).unwrap();
+
+        //record like the main loop does, many instructions in one step
+        for _ in 0..8 {
+            sys.step_hart(0, |p| p.step(500));
+            sys.replay_sync();
+        }
+        assert_eq!(sys.insns_clock(), 4000);
+        let recorded = lr_sc_state(&mut sys);
+        //both successful and failed sc happened
+        assert!(recorded.0 != 0 && recorded.1 != 0);
+
+        //re-execute from the start one instruction at a time
+        sys.goto_insns(1000).unwrap();
+        assert_eq!(sys.insns_clock(), 1000);
+        sys.goto_insns(4000).unwrap();
+        assert_eq!(sys.insns_clock(), 4000);
+        assert_eq!(lr_sc_state(&mut sys), recorded);
+    }
+
+    //racy increment of a shared counter, lost updates depend on how harts interleave:
+    //      auipc a0, 1
+    //loop: lw t0, 0(a0)
+    //      addi t0, t0, 1
+    //      sw t0, 0(a0)
+    //      addi s0, s0, 1
+    //      j loop
+    const INC_LOOP: [u32; 6] = [
+        0x00001517, 0x00052283, 0x00128293, 0x00552023, 0x00140413, 0xff1ff06f,
+    ];
+
+    fn inc_state(sys: &mut System) -> (u32, RegT, RegT) {
+        let mut data = 0;
+        sys.bus().read_u32(&0x8000_1000, &mut data).unwrap();
+        let s0 = |sys: &mut System, hart: usize| *sys.processor(hart).unwrap().state().xreg(8);
+        (data, s0(sys, 0), s0(sys, 1))
+    }
+
+    #[test]
+    fn rerun_smp_test() {
+        let mut sys = system(2, &INC_LOOP);
+        sys.enable_checkpoints(1_000_000).unwrap();
+
+        //record with turns of different lengths
+        for i in 0..8 {
+            sys.step_hart(0, |p| p.step(100 + i * 7));
+            sys.step_hart(1, |p| p.step(300 - i * 11));
+            sys.replay_sync();
+        }
+        let end = sys.insns_clock();
+        let recorded = inc_state(&mut sys);
+        //harts overwrote increments of each other
+        assert!((recorded.0 as RegT) < recorded.1 + recorded.2);
+        assert!(!sys.in_history());
+
+        //re-execute with the recorded interleaving
+        sys.goto_insns(1000).unwrap();
+        assert_eq!(sys.insns_clock(), 1000);
+        assert!(sys.in_history());
+        sys.goto_insns(end).unwrap();
+        assert_eq!(sys.insns_clock(), end);
+        assert_eq!(inc_state(&mut sys), recorded);
+        assert!(!sys.in_history());
+
+        //hart 0 takes 1960..2095 in round 5, forward in history stops at the first step of hart 1
+        sys.goto_insns(2000).unwrap();
+        assert_eq!(sys.forward_search(|i, _| i == 1), Some(1));
+        assert_eq!(sys.insns_clock(), 2096);
+    }
+}
@@ -0,0 +1,1098 @@
+use crate::devices::bus::Bus;
+use crate::prelude::{InsnT, RegT};
+use crate::processor::extensions::f::FRegT;
+use crate::processor::Processor;
+use crate::system::System;
+use std::collections::BTreeSet;
+use std::io;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::net::{UnixListener, UnixStream};
+use terminus_spaceport::EXIT_CTRL;
+
+const GDB_SIGINT: u8 = 2;
+const GDB_SIGTRAP: u8 = 5;
+//steps of a hart in one turn when continued
+const GDB_RESUME_STEPS: usize = 64;
+
+//gdb riscv register numbers
+const GDB_PC_REGNUM: usize = 32;
+const GDB_FIRST_FPR_REGNUM: usize = 33;
+const GDB_LAST_FPR_REGNUM: usize = 64;
+const GDB_FIRST_CSR_REGNUM: usize = 65;
+const GDB_LAST_CSR_REGNUM: usize = GDB_FIRST_CSR_REGNUM + 0xfff;
+
+const DPC: InsnT = 0x7B1;
+
+const XREG_NAMES: [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "fp", "s1", "a0", "a1", "a2", "a3", "a4",
+    "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+    "t5", "t6",
+];
+
+const CSR_NAMES: [(&str, InsnT); 68] = [
+    ("fflags", 0x001),
+    ("frm", 0x002),
+    ("fcsr", 0x003),
+    ("cycle", 0xC00),
+    ("instret", 0xC02),
+    ("cycleh", 0xC80),
+    ("instreth", 0xC82),
+    ("sstatus", 0x100),
+    ("sie", 0x104),
+    ("stvec", 0x105),
+    ("scounteren", 0x106),
+    ("senvcfg", 0x10A),
+    ("sscratch", 0x140),
+    ("sepc", 0x141),
+    ("scause", 0x142),
+    ("stval", 0x143),
+    ("sip", 0x144),
+    ("satp", 0x180),
+    ("mstatus", 0x300),
+    ("misa", 0x301),
+    ("medeleg", 0x302),
+    ("mideleg", 0x303),
+    ("mie", 0x304),
+    ("mtvec", 0x305),
+    ("mcounteren", 0x306),
+    ("menvcfg", 0x30A),
+    ("mscratch", 0x340),
+    ("mepc", 0x341),
+    ("mcause", 0x342),
+    ("mtval", 0x343),
+    ("mip", 0x344),
+    ("pmpcfg0", 0x3A0),
+    ("pmpcfg1", 0x3A1),
+    ("pmpcfg2", 0x3A2),
+    ("pmpcfg3", 0x3A3),
+    ("pmpaddr0", 0x3B0),
+    ("pmpaddr1", 0x3B1),
+    ("pmpaddr2", 0x3B2),
+    ("pmpaddr3", 0x3B3),
+    ("pmpaddr4", 0x3B4),
+    ("pmpaddr5", 0x3B5),
+    ("pmpaddr6", 0x3B6),
+    ("pmpaddr7", 0x3B7),
+    ("pmpaddr8", 0x3B8),
+    ("pmpaddr9", 0x3B9),
+    ("pmpaddr10", 0x3BA),
+    ("pmpaddr11", 0x3BB),
+    ("pmpaddr12", 0x3BC),
+    ("pmpaddr13", 0x3BD),
+    ("pmpaddr14", 0x3BE),
+    ("pmpaddr15", 0x3BF),
+    ("tselect", 0x7A0),
+    ("dcsr", 0x7B0),
+    ("dpc", 0x7B1),
+    ("dscratch0", 0x7B2),
+    ("dscratch1", 0x7B3),
+    ("mcycle", 0xB00),
+    ("minstret", 0xB02),
+    ("mcycleh", 0xB80),
+    ("minstreth", 0xB82),
+    ("mvendorid", 0xF11),
+    ("marchid", 0xF12),
+    ("mimpid", 0xF13),
+    ("mhartid", 0xF14),
+    ("tdata1", 0x7A1),
+    ("tdata2", 0x7A2),
+    ("tdata3", 0x7A3),
+    ("tinfo", 0x7A4),
+];
+
+pub trait GdbConnection: Read + Write {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()>;
+}
+
+impl GdbConnection for TcpStream {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        TcpStream::set_nonblocking(self, nonblocking)
+    }
+}
+
+impl GdbConnection for UnixStream {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        UnixStream::set_nonblocking(self, nonblocking)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum GdbAction {
+    Reply(String),
+    Resume(Option<usize>),
+    Reverse(bool),
+    Detach,
+    Kill,
+}
+
+pub struct GdbServer {
+    conn: Box<dyn GdbConnection>,
+    breakpoints: BTreeSet<u64>,
+    g_hart: usize,
+    c_hart: Option<usize>,
+    no_ack: bool,
+}
+
+impl GdbServer {
+    //"unix:<path>" for unix socket, otherwise "<host>:<port>"
+    pub fn accept(addr: &str) -> io::Result<GdbServer> {
+        let conn: Box<dyn GdbConnection> = if let Some(path) = addr.strip_prefix("unix:") {
+            let _ = std::fs::remove_file(path);
+            let listener = UnixListener::bind(path)?;
+            eprintln!("gdb server listening on {}...", addr);
+            let (stream, _) = listener.accept()?;
+            Box::new(stream)
+        } else {
+            let listener = TcpListener::bind(addr)?;
+            eprintln!("gdb server listening on {}...", addr);
+            let (stream, _) = listener.accept()?;
+            stream.set_nodelay(true)?;
+            Box::new(stream)
+        };
+        Ok(GdbServer::new(conn))
+    }
+
+    pub fn new(conn: Box<dyn GdbConnection>) -> GdbServer {
+        GdbServer {
+            conn,
+            breakpoints: BTreeSet::new(),
+            g_hart: 0,
+            c_hart: None,
+            no_ack: false,
+        }
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut buf = [0u8; 1];
+        loop {
+            match self.conn.read(&mut buf) {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(buf[0])),
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn read_packet(&mut self) -> io::Result<Option<String>> {
+        loop {
+            match self.read_byte()? {
+                None => return Ok(None),
+                Some(b'$') => break,
+                //ctrl-c while halted
+                Some(0x03) => return Ok(Some("?".to_string())),
+                Some(_) => {}
+            }
+        }
+        let mut data = vec![];
+        loop {
+            match self.read_byte()? {
+                None => return Ok(None),
+                Some(b'#') => break,
+                Some(b) => data.push(b),
+            }
+        }
+        let mut checksum = [0u8; 2];
+        for c in checksum.iter_mut() {
+            match self.read_byte()? {
+                None => return Ok(None),
+                Some(b) => *c = b,
+            }
+        }
+        if !self.no_ack {
+            let expect = data.iter().fold(0u8, |acc, b| acc.wrapping_add(*b));
+            let actual = std::str::from_utf8(&checksum)
+                .ok()
+                .and_then(|s| u8::from_str_radix(s, 16).ok());
+            if actual != Some(expect) {
+                self.conn.write_all(b"-")?;
+                return self.read_packet();
+            }
+            self.conn.write_all(b"+")?;
+        }
+        Ok(Some(unescape(&data)))
+    }
+
+    fn write_packet(&mut self, data: &str) -> io::Result<()> {
+        let checksum = data.bytes().fold(0u8, |acc, b| acc.wrapping_add(b));
+        let packet = format!("${}#{:02x}", data, checksum);
+        loop {
+            self.conn.write_all(packet.as_bytes())?;
+            self.conn.flush()?;
+            if self.no_ack {
+                return Ok(());
+            }
+            match self.read_byte()? {
+                Some(b'+') | None => return Ok(()),
+                Some(_) => {}
+            }
+        }
+    }
+
+    fn interrupted(&mut self) -> io::Result<bool> {
+        let mut buf = [0u8; 1];
+        match self.conn.read(&mut buf) {
+            Ok(0) => Ok(true),
+            Ok(_) => Ok(buf[0] == 0x03),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    //tick is called once all harts have stepped one instruction, with the number of instructions per hart
+    //return true if the system should keep running after gdb detached
+    pub fn serve<F: FnMut(&mut System, usize)>(
+        &mut self,
+        sys: &mut System,
+        mut tick: F,
+    ) -> io::Result<bool> {
+        self.attach(sys, true);
+        let result = self.serve_loop(sys, &mut tick);
+        self.attach(sys, false);
+        result
+    }
+
+    fn serve_loop<F: FnMut(&mut System, usize)>(
+        &mut self,
+        sys: &mut System,
+        tick: &mut F,
+    ) -> io::Result<bool> {
+        loop {
+            let packet = match self.read_packet()? {
+                Some(p) => p,
+                None => return Ok(true),
+            };
+            match self.handle(sys, &packet) {
+                GdbAction::Reply(resp) => self.write_packet(&resp)?,
+                GdbAction::Resume(step_hart) => {
+                    let resp = self.resume(sys, step_hart, tick)?;
+                    self.write_packet(&resp)?;
+                    if resp.starts_with('W') {
+                        return Ok(false);
+                    }
+                }
+                GdbAction::Reverse(step) => {
+                    let resp = self.reverse(sys, step);
+                    self.write_packet(&resp)?
+                }
+                GdbAction::Detach => {
+                    self.write_packet("OK")?;
+                    return Ok(true);
+                }
+                GdbAction::Kill => return Ok(false),
+            }
+        }
+    }
+
+    fn resume<F: FnMut(&mut System, usize)>(
+        &mut self,
+        sys: &mut System,
+        step_hart: Option<usize>,
+        tick: &mut F,
+    ) -> io::Result<String> {
+        //follow recorded history before running live
+        if sys.in_history() {
+            let breakpoints = &self.breakpoints;
+            let stopped = if let Some(hart) = step_hart {
+                sys.forward_search(|i, _| i == hart)
+            } else {
+                sys.forward_search(|_, p| breakpoints.contains(p.state().next_pc()))
+            };
+            for p in sys.processors().iter_mut() {
+                p.debug_halt()
+            }
+            if let Some(hart) = stopped {
+                self.g_hart = hart;
+                return Ok(self.stop_reply(hart, GDB_SIGTRAP));
+            }
+        }
+        if let Some(hart) = step_hart {
+            if hart < sys.processors().len() {
+                sys.step_hart(hart, |p| p.debug_step());
+            }
+            tick(sys, 1);
+            self.g_hart = hart;
+            return Ok(self.stop_reply(hart, GDB_SIGTRAP));
+        }
+        for p in sys.processors().iter_mut() {
+            p.debug_resume()
+        }
+        self.conn.set_nonblocking(true)?;
+        let mut cnt: usize = 0;
+        let resp = 'outer: loop {
+            if let Ok(msg) = EXIT_CTRL.poll() {
+                eprintln!("{}", msg);
+                break "W00".to_string();
+            }
+            //harts take turns of a few steps to keep the recorded schedule short
+            for i in 0..sys.processors().len() {
+                let breakpoints = &self.breakpoints;
+                let hit = sys.step_hart(i, |p| {
+                    for _ in 0..GDB_RESUME_STEPS {
+                        p.step(1);
+                        //ebreak or trigger
+                        if p.state().debug_mode() || breakpoints.contains(p.state().next_pc()) {
+                            return true;
+                        }
+                    }
+                    false
+                });
+                if hit {
+                    break 'outer self.stop_reply(i, GDB_SIGTRAP);
+                }
+            }
+            tick(sys, GDB_RESUME_STEPS);
+            cnt += 1;
+            if cnt & 0x3f == 0 && self.interrupted()? {
+                break self.stop_reply(self.g_hart, GDB_SIGINT);
+            }
+        };
+        self.conn.set_nonblocking(false)?;
+        for p in sys.processors().iter_mut() {
+            p.debug_halt()
+        }
+        if let Some(hart) = parse_stop_hart(&resp) {
+            self.g_hart = hart
+        }
+        Ok(resp)
+    }
+
+    //reverse step/continue by re-executing from checkpoints, see System::reverse_search
+    fn reverse(&mut self, sys: &mut System, step: bool) -> String {
+        let hart = self.c_hart.unwrap_or(self.g_hart);
+        let breakpoints = &self.breakpoints;
+        let result = if step {
+            sys.reverse_search(|i, _| i == hart)
+        } else {
+            sys.reverse_search(|_, p| breakpoints.contains(p.state().next_pc()))
+        };
+        for p in sys.processors().iter_mut() {
+            p.debug_halt()
+        }
+        match result {
+            Ok(Some(hart)) => {
+                self.g_hart = hart;
+                self.stop_reply(hart, GDB_SIGTRAP)
+            }
+            Ok(None) => format!("T{:02x}replaylog:begin;", GDB_SIGTRAP),
+            Err(e) => {
+                eprintln!("{}", e);
+                "E01".to_string()
+            }
+        }
+    }
+
+    fn monitor(&mut self, sys: &mut System, cmd: &str) -> String {
+        let mut fields = cmd.split_whitespace();
+        match (fields.next(), fields.next()) {
+            (Some("insns"), None) => format!("{}\n", sys.insns_clock()),
+            (Some("goto"), Some(n)) => match n.parse::<u64>() {
+                Ok(target) => {
+                    let result = sys.goto_insns(target);
+                    for p in sys.processors().iter_mut() {
+                        p.debug_halt()
+                    }
+                    match result {
+                        Ok(_) => format!("at {}\n", sys.insns_clock()),
+                        Err(e) => format!("{}\n", e),
+                    }
+                }
+                Err(_) => "goto expect a decimal int\n".to_string(),
+            },
+            _ => "monitor commands:\n  insns: total instructions executed by all harts\n  goto <insns>: go to the point where total instructions reach <insns>\n".to_string(),
+        }
+    }
+
+    fn attach(&self, sys: &mut System, en: bool) {
+        for p in sys.processors().iter_mut() {
+            if en {
+                p.debug_halt();
+            }
+            let mut dcsr = p.state().priv_m().dcsr_mut();
+            dcsr.set_ebreakm(en as RegT);
+            dcsr.set_ebreaks(en as RegT);
+            dcsr.set_ebreaku(en as RegT);
+            drop(dcsr);
+            if !en {
+                p.debug_resume();
+            }
+        }
+    }
+
+    fn stop_reply(&self, hart: usize, sig: u8) -> String {
+        format!("T{:02x}thread:{:x};", sig, hart + 1)
+    }
+
+    fn handle(&mut self, sys: &mut System, packet: &str) -> GdbAction {
+        let (cmd, args) = split_first(packet);
+        match cmd {
+            "?" => GdbAction::Reply(self.stop_reply(self.g_hart, GDB_SIGTRAP)),
+            "q" => GdbAction::Reply(self.handle_query(sys, args)),
+            "Q" => GdbAction::Reply(if args == "StartNoAckMode" {
+                self.no_ack = true;
+                "OK".to_string()
+            } else {
+                String::new()
+            }),
+            "H" => GdbAction::Reply(self.handle_thread_select(sys, args)),
+            "T" => GdbAction::Reply(match parse_thread(args) {
+                Some(Some(hart)) if hart < sys.processors().len() => "OK".to_string(),
+                _ => "E01".to_string(),
+            }),
+            "g" => GdbAction::Reply(self.read_registers(sys)),
+            "G" => GdbAction::Reply(self.write_registers(sys, args)),
+            "p" => GdbAction::Reply(
+                usize::from_str_radix(args, 16)
+                    .ok()
+                    .and_then(|n| self.read_register(sys, n))
+                    .unwrap_or("E01".to_string()),
+            ),
+            "P" => GdbAction::Reply(self.write_register(sys, args)),
+            "m" => GdbAction::Reply(self.read_memory(sys, args)),
+            "M" => GdbAction::Reply(self.write_memory(sys, args)),
+            "s" => GdbAction::Resume(Some(self.c_hart.unwrap_or(self.g_hart))),
+            "c" => GdbAction::Resume(None),
+            "v" => self.handle_v(sys, args),
+            "Z" | "z" => GdbAction::Reply(self.handle_breakpoint(cmd == "Z", args)),
+            "b" if args == "s" || args == "c" => GdbAction::Reverse(args == "s"),
+            "D" => GdbAction::Detach,
+            "k" => GdbAction::Kill,
+            _ => GdbAction::Reply(String::new()),
+        }
+    }
+
+    fn handle_query(&mut self, sys: &mut System, args: &str) -> String {
+        if args.starts_with("Supported") {
+            "PacketSize=4000;qXfer:features:read+;QStartNoAckMode+;swbreak+;hwbreak+;vContSupported+;ReverseStep+;ReverseContinue+"
+                .to_string()
+        } else if args == "Attached" {
+            "1".to_string()
+        } else if args == "C" {
+            format!("QC{:x}", self.g_hart + 1)
+        } else if args == "fThreadInfo" {
+            format!(
+                "m{}",
+                (1..=sys.processors().len())
+                    .map(|i| format!("{:x}", i))
+                    .collect::<Vec<String>>()
+                    .join(",")
+            )
+        } else if args == "sThreadInfo" {
+            "l".to_string()
+        } else if let Some(thread) = args.strip_prefix("ThreadExtraInfo,") {
+            match usize::from_str_radix(thread, 16) {
+                Ok(id) if id > 0 && id <= sys.processors().len() => {
+                    let p = sys.processor(id - 1).unwrap();
+                    to_hex(format!("hart{} {:?}", id - 1, p.state().privilege()).as_bytes())
+                }
+                _ => "E01".to_string(),
+            }
+        } else if let Some(cmd) = args.strip_prefix("Rcmd,") {
+            match from_hex(cmd.as_bytes()).map(|c| String::from_utf8_lossy(&c).to_string()) {
+                Some(cmd) => to_hex(self.monitor(sys, &cmd).as_bytes()),
+                None => "E01".to_string(),
+            }
+        } else if let Some(annex) = args.strip_prefix("Xfer:features:read:") {
+            let mut fields = annex.splitn(2, ':');
+            let name = fields.next().unwrap_or_default();
+            let range = fields.next().unwrap_or_default();
+            if name != "target.xml" {
+                return "E00".to_string();
+            }
+            let mut range = range.splitn(2, ',');
+            let offset = usize::from_str_radix(range.next().unwrap_or_default(), 16).unwrap_or(0);
+            let len = usize::from_str_radix(range.next().unwrap_or_default(), 16).unwrap_or(0);
+            let xml = match self.target_xml(sys) {
+                Some(xml) => xml,
+                None => return "E01".to_string(),
+            };
+            if offset >= xml.len() {
+                "l".to_string()
+            } else if offset.saturating_add(len) >= xml.len() {
+                format!("l{}", escape(&xml[offset..]))
+            } else {
+                format!("m{}", escape(&xml[offset..offset + len]))
+            }
+        } else {
+            String::new()
+        }
+    }
+
+    fn handle_thread_select(&mut self, sys: &mut System, args: &str) -> String {
+        let (op, thread) = split_first(args);
+        let hart = match parse_thread(thread) {
+            Some(h) => h,
+            None => return "E01".to_string(),
+        };
+        if let Some(h) = hart {
+            if h >= sys.processors().len() {
+                return "E01".to_string();
+            }
+        }
+        match op {
+            "g" => self.g_hart = hart.unwrap_or(self.g_hart),
+            "c" => self.c_hart = hart,
+            _ => return "E01".to_string(),
+        }
+        "OK".to_string()
+    }
+
+    fn handle_v(&mut self, sys: &mut System, args: &str) -> GdbAction {
+        if args == "Cont?" {
+            GdbAction::Reply("vCont;c;C;s;S".to_string())
+        } else if let Some(actions) = args.strip_prefix("Cont;") {
+            for action in actions.split(';') {
+                let mut fields = action.splitn(2, ':');
+                let kind = fields.next().unwrap_or_default();
+                let hart = match fields.next().map(parse_thread) {
+                    Some(Some(Some(h))) if h < sys.processors().len() => h,
+                    Some(Some(Some(_))) | Some(None) => continue,
+                    _ => self.g_hart,
+                };
+                if kind.starts_with('s') || kind.starts_with('S') {
+                    return GdbAction::Resume(Some(hart));
+                }
+            }
+            GdbAction::Resume(None)
+        } else if args.starts_with("Kill") {
+            GdbAction::Kill
+        } else {
+            GdbAction::Reply(String::new())
+        }
+    }
+
+    fn handle_breakpoint(&mut self, insert: bool, args: &str) -> String {
+        let mut fields = args.split(',');
+        let ty = fields.next().unwrap_or_default();
+        if ty != "0" && ty != "1" {
+            return String::new();
+        }
+        let addr = match fields.next().map(|a| u64::from_str_radix(a, 16)) {
+            Some(Ok(addr)) => addr,
+            _ => return "E01".to_string(),
+        };
+        if insert {
+            self.breakpoints.insert(addr);
+        } else {
+            self.breakpoints.remove(&addr);
+        }
+        "OK".to_string()
+    }
+
+    fn xlen_size(&self, p: &Processor) -> usize {
+        p.state().config().xlen.size()
+    }
+
+    fn read_register(&self, sys: &mut System, n: usize) -> Option<String> {
+        let p = sys.processor(self.g_hart)?;
+        let size = self.xlen_size(p);
+        let state = p.state();
+        match n {
+            0..=31 => Some(reg_to_hex(*state.xreg(n as InsnT), size)),
+            GDB_PC_REGNUM => Some(reg_to_hex(*state.next_pc(), size)),
+            GDB_FIRST_FPR_REGNUM..=GDB_LAST_FPR_REGNUM => {
+                let flen = state.flen()?;
+                let value = state.freg((n - GDB_FIRST_FPR_REGNUM) as InsnT)?;
+                Some(freg_to_hex(value, flen.size()))
+            }
+            GDB_FIRST_CSR_REGNUM..=GDB_LAST_CSR_REGNUM => state
+                .debug_csr((n - GDB_FIRST_CSR_REGNUM) as InsnT)
+                .map(|v| reg_to_hex(v, size)),
+            _ => None,
+        }
+    }
+
+    fn write_register(&mut self, sys: &mut System, args: &str) -> String {
+        let mut fields = args.splitn(2, '=');
+        let n = match usize::from_str_radix(fields.next().unwrap_or_default(), 16) {
+            Ok(n) => n,
+            Err(_) => return "E01".to_string(),
+        };
+        let bytes = match fields.next().and_then(|v| from_hex(v.as_bytes())) {
+            Some(b) => b,
+            None => return "E01".to_string(),
+        };
+        let p = match sys.processor(self.g_hart) {
+            Some(p) => p,
+            None => return "E01".to_string(),
+        };
+        let value = bytes_to_u128(&bytes);
+        let state = p.state_mut();
+        let res = match n {
+            0..=31 => {
+                state.set_xreg(n as InsnT, value as RegT);
+                Some(())
+            }
+            GDB_PC_REGNUM => {
+                state.set_pc(value as RegT);
+                state.debug_set_csr(DPC, value as RegT)
+            }
+            GDB_FIRST_FPR_REGNUM..=GDB_LAST_FPR_REGNUM => {
+                state.set_freg((n - GDB_FIRST_FPR_REGNUM) as InsnT, value as FRegT)
+            }
+            GDB_FIRST_CSR_REGNUM..=GDB_LAST_CSR_REGNUM => {
+                state.debug_set_csr((n - GDB_FIRST_CSR_REGNUM) as InsnT, value as RegT)
+            }
+            _ => None,
+        };
+        if res.is_some() {
+            "OK".to_string()
+        } else {
+            "E01".to_string()
+        }
+    }
+
+    fn read_registers(&self, sys: &mut System) -> String {
+        (0..=GDB_PC_REGNUM)
+            .map(|n| self.read_register(sys, n).unwrap_or_default())
+            .collect::<Vec<String>>()
+            .concat()
+    }
+
+    fn write_registers(&self, sys: &mut System, args: &str) -> String {
+        let bytes = match from_hex(args.as_bytes()) {
+            Some(b) => b,
+            None => return "E01".to_string(),
+        };
+        let p = match sys.processor(self.g_hart) {
+            Some(p) => p,
+            None => return "E01".to_string(),
+        };
+        let size = self.xlen_size(p);
+        for (n, chunk) in bytes.chunks(size).take(GDB_PC_REGNUM + 1).enumerate() {
+            let value = bytes_to_u128(chunk) as RegT;
+            if n == GDB_PC_REGNUM {
+                p.state_mut().set_pc(value);
+                p.state().debug_set_csr(DPC, value);
+            } else {
+                p.state_mut().set_xreg(n as InsnT, value)
+            }
+        }
+        "OK".to_string()
+    }
+
+    fn parse_mem_args(args: &str) -> Option<(u64, usize, Option<&str>)> {
+        let mut fields = args.splitn(2, ':');
+        let range = fields.next()?;
+        let data = fields.next();
+        let mut range = range.splitn(2, ',');
+        let addr = u64::from_str_radix(range.next()?, 16).ok()?;
+        let len = usize::from_str_radix(range.next()?, 16).ok()?;
+        Some((addr, len, data))
+    }
+
+    fn read_memory(&self, sys: &mut System, args: &str) -> String {
+        let (addr, len, _) = match Self::parse_mem_args(args) {
+            Some(a) => a,
+            None => return "E01".to_string(),
+        };
+        let bus = sys.bus().clone();
+        let p = match sys.processor(self.g_hart) {
+            Some(p) => p,
+            None => return "E01".to_string(),
+        };
+        let mut data = vec![];
+        for i in 0..len as u64 {
+            let pa = match p.debug_translate(addr + i) {
+                Some(pa) => pa,
+                None => break,
+            };
+            let mut byte: u8 = 0;
+            if bus.read_u8(&pa, &mut byte).is_err() {
+                break;
+            }
+            data.push(byte)
+        }
+        if data.is_empty() && len != 0 {
+            "E14".to_string()
+        } else {
+            to_hex(&data)
+        }
+    }
+
+    fn write_memory(&self, sys: &mut System, args: &str) -> String {
+        let (addr, len, data) = match Self::parse_mem_args(args) {
+            Some(a) => a,
+            None => return "E01".to_string(),
+        };
+        let data = match data.and_then(|d| from_hex(d.as_bytes())) {
+            Some(d) if d.len() == len => d,
+            _ => return "E01".to_string(),
+        };
+        let bus = sys.bus().clone();
+        {
+            let p = match sys.processor(self.g_hart) {
+                Some(p) => p,
+                None => return "E01".to_string(),
+            };
+            for (i, byte) in data.iter().enumerate() {
+                let pa = match p.debug_translate(addr + i as u64) {
+                    Some(pa) => pa,
+                    None => return "E14".to_string(),
+                };
+                if bus.write_u8(&pa, byte).is_err() {
+                    return "E14".to_string();
+                }
+            }
+        }
+        for p in sys.processors().iter() {
+            p.fetcher().flush_icache()
+        }
+        "OK".to_string()
+    }
+
+    fn target_xml(&self, sys: &mut System) -> Option<String> {
+        let p = sys.processor(self.g_hart)?;
+        let state = p.state();
+        let xlen = state.config().xlen.len();
+        let mut xml = String::new();
+        xml.push_str("<?xml version=\"1.0\"?>\n<!DOCTYPE target SYSTEM \"gdb-target.dtd\">\n<target version=\"1.0\">\n");
+        xml.push_str(&format!(
+            "<architecture>riscv:rv{}</architecture>\n",
+            xlen
+        ));
+        xml.push_str("<feature name=\"org.gnu.gdb.riscv.cpu\">\n");
+        for (i, name) in XREG_NAMES.iter().enumerate() {
+            let ty = match i {
+                1 => "code_ptr",
+                2 | 8 => "data_ptr",
+                _ => "int",
+            };
+            xml.push_str(&format!(
+                "<reg name=\"{}\" bitsize=\"{}\" regnum=\"{}\" type=\"{}\"/>\n",
+                name, xlen, i, ty
+            ));
+        }
+        xml.push_str(&format!(
+            "<reg name=\"pc\" bitsize=\"{}\" regnum=\"{}\" type=\"code_ptr\"/>\n",
+            xlen, GDB_PC_REGNUM
+        ));
+        xml.push_str("</feature>\n");
+        if let Some(flen) = state.flen() {
+            let ty = match flen.len() {
+                32 => "ieee_single",
+                64 => "ieee_double",
+                _ => "int",
+            };
+            xml.push_str("<feature name=\"org.gnu.gdb.riscv.fpu\">\n");
+            for i in 0..32 {
+                xml.push_str(&format!(
+                    "<reg name=\"f{}\" bitsize=\"{}\" regnum=\"{}\" type=\"{}\"/>\n",
+                    i,
+                    flen.len(),
+                    GDB_FIRST_FPR_REGNUM + i,
+                    ty
+                ));
+            }
+            for (name, addr) in CSR_NAMES.iter().filter(|(_, addr)| *addr <= 0x3) {
+                xml.push_str(&format!(
+                    "<reg name=\"{}\" bitsize=\"{}\" regnum=\"{}\" type=\"int\" group=\"float\"/>\n",
+                    name,
+                    xlen,
+                    GDB_FIRST_CSR_REGNUM + *addr as usize
+                ));
+            }
+            xml.push_str("</feature>\n");
+        }
+        xml.push_str("<feature name=\"org.gnu.gdb.riscv.csr\">\n");
+        for addr in 0x4..0x1000 as InsnT {
+            if state.debug_csr(addr).is_none() {
+                continue;
+            }
+            let name = CSR_NAMES
+                .iter()
+                .find(|(_, a)| *a == addr)
+                .map(|(n, _)| n.to_string())
+                .unwrap_or(format!("csr{:#x}", addr));
+            xml.push_str(&format!(
+                "<reg name=\"{}\" bitsize=\"{}\" regnum=\"{}\" type=\"int\" group=\"csr\"/>\n",
+                name,
+                xlen,
+                GDB_FIRST_CSR_REGNUM + addr as usize
+            ));
+        }
+        xml.push_str("</feature>\n</target>\n");
+        Some(xml)
+    }
+}
+
+//split the command char of a packet, which may be non-ascii
+fn split_first(s: &str) -> (&str, &str) {
+    s.split_at(s.chars().next().map_or(0, char::len_utf8))
+}
+
+//Some(None) for all threads
+fn parse_thread(thread: &str) -> Option<Option<usize>> {
+    match thread {
+        "-1" | "0" => Some(None),
+        t => match usize::from_str_radix(t, 16) {
+            Ok(id) if id > 0 => Some(Some(id - 1)),
+            _ => None,
+        },
+    }
+}
+
+fn parse_stop_hart(resp: &str) -> Option<usize> {
+    let thread = resp.split("thread:").nth(1)?;
+    usize::from_str_radix(thread.trim_end_matches(';'), 16)
+        .ok()
+        .map(|id| id - 1)
+}
+
+fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+//packets may carry any byte, only ascii hex digits are accepted
+fn from_hex(data: &[u8]) -> Option<Vec<u8>> {
+    if data.len() & 1 != 0 {
+        return None;
+    }
+    let digit = |b: u8| (b as char).to_digit(16).map(|d| d as u8);
+    data.chunks(2)
+        .map(|c| Some(digit(c[0])? << 4 | digit(c[1])?))
+        .collect()
+}
+
+fn reg_to_hex(value: RegT, size: usize) -> String {
+    to_hex(&value.to_le_bytes()[..size])
+}
+
+fn freg_to_hex(value: FRegT, size: usize) -> String {
+    to_hex(&value.to_le_bytes()[..size])
+}
+
+fn bytes_to_u128(bytes: &[u8]) -> u128 {
+    bytes
+        .iter()
+        .enumerate()
+        .take(16)
+        .fold(0u128, |acc, (i, b)| acc | (*b as u128) << (i << 3))
+}
+
+fn escape(s: &str) -> String {
+    let mut res = String::new();
+    for c in s.chars() {
+        match c {
+            '#' | '$' | '}' | '*' => {
+                res.push('}');
+                res.push(((c as u8) ^ 0x20) as char)
+            }
+            _ => res.push(c),
+        }
+    }
+    res
+}
+
+fn unescape(data: &[u8]) -> String {
+    let mut res = vec![];
+    let mut iter = data.iter();
+    while let Some(b) = iter.next() {
+        if *b == b'}' {
+            if let Some(n) = iter.next() {
+                res.push(*n ^ 0x20)
+            }
+        } else {
+            res.push(*b)
+        }
+    }
+    String::from_utf8_lossy(&res).to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::XLen;
+    use crate::processor::{ProcessorCfg, SatpMode};
+    use std::cell::RefCell;
+    use std::io::Cursor;
+    use std::rc::Rc;
+
+    struct MockConn {
+        input: Cursor<Vec<u8>>,
+        output: Rc<RefCell<Vec<u8>>>,
+    }
+
+    impl Read for MockConn {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for MockConn {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.output.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl GdbConnection for MockConn {
+        fn set_nonblocking(&self, _: bool) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn server(input: &[u8]) -> (GdbServer, Rc<RefCell<Vec<u8>>>) {
+        let output = Rc::new(RefCell::new(vec![]));
+        let conn = MockConn {
+            input: Cursor::new(input.to_vec()),
+            output: output.clone(),
+        };
+        (GdbServer::new(Box::new(conn)), output)
+    }
+
+    fn system(harts: usize) -> System {
+        let mut sys = System::new("test", "top_tests/elf/rv64ui-p-add", 100, 1);
+        for _ in 0..harts {
+            sys.new_processor_no_int(ProcessorCfg {
+                xlen: XLen::X64,
+                enable_dirty: true,
+                isa: "rv64imacsu".parse().unwrap(),
+                freq: 1000000000,
+                triggers: 0,
+                satp_modes: vec![SatpMode::Sv39].into_boxed_slice(),
+                vlen: 128,
+                elen: 64,
+                cache_block_size: 64,
+                entropy_seed: 0,
+                hpm_counters: 0,
+            });
+        }
+        sys
+    }
+
+    #[test]
+    fn packet_test() {
+        //garbage before '$', ctrl-c, bad checksum, escaped '}'
+        let (mut s, output) = server(b"xx$g#67\x03$m0,4#00$m0,4#fd$X}]#32");
+        assert_eq!(s.read_packet().unwrap(), Some("g".to_string()));
+        assert_eq!(s.read_packet().unwrap(), Some("?".to_string()));
+        assert_eq!(s.read_packet().unwrap(), Some("m0,4".to_string()));
+        assert_eq!(s.read_packet().unwrap(), Some("X}".to_string()));
+        assert_eq!(s.read_packet().unwrap(), None);
+        assert_eq!(&output.borrow()[..], b"+-++");
+
+        //resend until acked
+        let (mut s, output) = server(b"-+");
+        s.write_packet("OK").unwrap();
+        assert_eq!(&output.borrow()[..], b"$OK#9a$OK#9a");
+
+        //no ack mode ignores checksum
+        let (mut s, output) = server(b"$g#00");
+        s.no_ack = true;
+        assert_eq!(s.read_packet().unwrap(), Some("g".to_string()));
+        s.write_packet("OK").unwrap();
+        assert_eq!(&output.borrow()[..], b"$OK#9a");
+    }
+
+    #[test]
+    fn escape_test() {
+        let s = "a#b$c}d*e";
+        assert_eq!(escape(s), "a}\u{3}b}\u{4}c}]d}\ne");
+        assert_eq!(unescape(escape(s).as_bytes()), s);
+        //trailing '}' is dropped
+        assert_eq!(unescape(b"ab}"), "ab");
+    }
+
+    #[test]
+    fn hex_test() {
+        assert_eq!(to_hex(&[0x00, 0xab, 0x7f]), "00ab7f");
+        assert_eq!(from_hex(b"00aB7f"), Some(vec![0x00, 0xab, 0x7f]));
+        assert_eq!(from_hex(b""), Some(vec![]));
+        assert_eq!(from_hex(b"abc"), None);
+        assert_eq!(from_hex(b"+f"), None);
+        assert_eq!(from_hex("é1".as_bytes()), None);
+        assert_eq!(from_hex("éé".as_bytes()), None);
+        assert_eq!(reg_to_hex(0x1234, 4), "34120000");
+        assert_eq!(bytes_to_u128(&[0x34, 0x12]), 0x1234);
+
+        let mut sys = system(1);
+        let (mut s, _) = server(b"");
+        assert_eq!(
+            s.handle(&mut sys, "qRcmd,éé"),
+            GdbAction::Reply("E01".to_string())
+        );
+        assert_eq!(
+            s.handle(&mut sys, "P1=12é"),
+            GdbAction::Reply("E01".to_string())
+        );
+        assert_eq!(s.handle(&mut sys, "é"), GdbAction::Reply(String::new()));
+    }
+
+    #[test]
+    fn thread_test() {
+        assert_eq!(parse_thread("-1"), Some(None));
+        assert_eq!(parse_thread("0"), Some(None));
+        assert_eq!(parse_thread("1"), Some(Some(0)));
+        assert_eq!(parse_thread("a"), Some(Some(9)));
+        assert_eq!(parse_thread("x"), None);
+        assert_eq!(parse_thread(""), None);
+        assert_eq!(parse_stop_hart("T05thread:2;"), Some(1));
+
+        let mut sys = system(2);
+        let (mut s, _) = server(b"");
+        let ok = GdbAction::Reply("OK".to_string());
+        let err = GdbAction::Reply("E01".to_string());
+        //H
+        assert_eq!(s.handle(&mut sys, "Hg2"), ok);
+        assert_eq!(s.g_hart, 1);
+        assert_eq!(s.handle(&mut sys, "Hg0"), ok);
+        assert_eq!(s.g_hart, 1);
+        assert_eq!(s.handle(&mut sys, "Hc1"), ok);
+        assert_eq!(s.c_hart, Some(0));
+        assert_eq!(s.handle(&mut sys, "Hc-1"), ok);
+        assert_eq!(s.c_hart, None);
+        for packet in ["Hg3", "Hgx", "H", "Hx1", "Hé1"].iter() {
+            assert_eq!(s.handle(&mut sys, packet), err);
+        }
+        //T
+        assert_eq!(s.handle(&mut sys, "T2"), ok);
+        assert_eq!(s.handle(&mut sys, "T3"), err);
+        assert_eq!(s.handle(&mut sys, "T0"), err);
+        //vCont, threads out of range are skipped
+        assert_eq!(
+            s.handle(&mut sys, "vCont;s:1;c"),
+            GdbAction::Resume(Some(0))
+        );
+        assert_eq!(s.handle(&mut sys, "vCont;s:3;c"), GdbAction::Resume(None));
+        assert_eq!(s.handle(&mut sys, "vCont;s"), GdbAction::Resume(Some(1)));
+        assert_eq!(s.handle(&mut sys, "vCont;c:2"), GdbAction::Resume(None));
+        assert_eq!(s.handle(&mut sys, "s"), GdbAction::Resume(Some(1)));
+
+        //target.xml of invalid thread
+        assert!(s
+            .handle_query(&mut sys, "Xfer:features:read:target.xml:0,40")
+            .starts_with("m<?xml"));
+        s.g_hart = 2;
+        assert_eq!(
+            s.handle_query(&mut sys, "Xfer:features:read:target.xml:0,40"),
+            "E01"
+        );
+    }
+
+    #[test]
+    fn breakpoint_test() {
+        let (mut s, _) = server(b"");
+        assert_eq!(s.handle_breakpoint(true, "0,80000000,4"), "OK");
+        assert_eq!(s.handle_breakpoint(true, "1,80000004,2"), "OK");
+        assert_eq!(s.handle_breakpoint(true, "0,80000000,4"), "OK");
+        //watchpoints are not supported
+        assert_eq!(s.handle_breakpoint(true, "2,80000008,4"), "");
+        assert_eq!(s.handle_breakpoint(true, "0,xyz,4"), "E01");
+        assert_eq!(s.handle_breakpoint(true, "0"), "E01");
+        assert_eq!(
+            s.breakpoints.iter().cloned().collect::<Vec<u64>>(),
+            vec![0x8000_0000, 0x8000_0004]
+        );
+        assert_eq!(s.handle_breakpoint(false, "0,80000000,4"), "OK");
+        assert_eq!(s.handle_breakpoint(false, "0,80000010,4"), "OK");
+        assert_eq!(
+            s.breakpoints.iter().cloned().collect::<Vec<u64>>(),
+            vec![0x8000_0004]
+        );
+    }
+}
@@ -1,12 +1,23 @@
-use crate::devices::bus::TerminusBus;
-use crate::devices::clint::Timer;
-use crate::devices::htif::HTIF;
+use crate::devices::aplic::Aplic;
+use crate::devices::bus::{Bus, TerminusBus};
+use crate::devices::clint::{HartTimer, Timer};
+use crate::devices::htif::{HTIFRegs, HTIF};
+use crate::devices::imsic::{HartImsic, Imsic, IMSIC_NUM_IDS};
 use crate::devices::plic::Intc;
+use crate::devices::virtio_mmio::{VirtIOMmio, VirtIOMmioDevice};
 use crate::prelude::XLen;
+use crate::processor::hpm::HPM_SBI_EVENTS;
+use crate::processor::isa::IsaExt;
 use crate::processor::{Processor, ProcessorCfg};
+use crate::replay::Replay;
+use crate::snapshot::{SnapshotReader, SnapshotWriter};
+use std::cell::RefCell;
 use std::cmp::min;
 use std::fmt;
 use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter, Read, Write};
 use std::rc::Rc;
 use terminus_spaceport::irq::IrqVec;
 use terminus_spaceport::memory::region::{BytesAccess, IOAccess, Region, GHEAP};
@@ -21,11 +32,20 @@ use fdt::{FdtNode, FdtProp};
 pub mod elf;
 
 use elf::ElfLoader;
+
+pub mod gdb;
+
+pub mod checkpoint;
+
+use checkpoint::Checkpoint;
+
 use std::collections::HashMap;
 use terminus_spaceport::virtio::{MMIODevice, VirtIOInfo};
 
 const VIRTIO_BASE: u64 = 0x40000000;
 const VIRTIO_SIZE: u64 = 0x1000;
+const VIRTIO_STATUS: u64 = 0x70;
+const SNAPSHOT_PAGE_SIZE: u64 = 0x1000;
 
 #[derive(Debug)]
 pub enum Error {
@@ -33,6 +53,7 @@ pub enum Error {
     ElfErr(String),
     FdtErr(String),
     ResetErr(String),
+    SnapshotErr(String),
 }
 
 impl Display for Error {
@@ -42,6 +63,7 @@ impl Display for Error {
             Error::ElfErr(s) => write!(f, "ElfErr!{}", s),
             Error::FdtErr(s) => write!(f, "FdtErr!{}", s),
             Error::ResetErr(s) => write!(f, "ResetErr!{}", s),
+            Error::SnapshotErr(s) => write!(f, "SnapshotErr!{}", s),
         }
     }
 }
@@ -52,6 +74,12 @@ impl From<space::Error> for Error {
     }
 }
 
+impl From<io::Error> for Error {
+    fn from(v: io::Error) -> Error {
+        Error::SnapshotErr(v.to_string())
+    }
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 pub struct System {
@@ -59,10 +87,20 @@ pub struct System {
     bus: Rc<TerminusBus>,
     timer: Rc<Timer>,
     intc: Rc<Intc>,
+    aplic: Option<Rc<Aplic>>,
+    imsic: Option<Rc<Imsic>>,
     elf: ElfLoader,
     processors: Vec<Processor>,
     virtio_infos: Vec<VirtIOInfo>,
+    virtio_mmios: Vec<Rc<VirtIOMmioDevice>>,
+    //name and base of virtio devices whose state can not be saved
+    ext_virtios: Vec<(String, u64)>,
     ext_fdt_prop: HashMap<String, Vec<FdtProp>>,
+    memories: RefCell<Vec<(String, Rc<Region>)>>,
+    htif: RefCell<Option<HTIFRegs>>,
+    replay: Rc<Replay>,
+    checkpoints: Vec<Checkpoint>,
+    checkpoint_interval: u64,
 }
 
 impl System {
@@ -74,30 +112,67 @@ impl System {
             bus,
             timer: Rc::new(Timer::new(timer_freq)),
             intc: Rc::new(Intc::new(max_int_src)),
+            aplic: None,
+            imsic: None,
             elf,
             processors: vec![],
             virtio_infos: vec![],
+            virtio_mmios: vec![],
+            ext_virtios: vec![],
             ext_fdt_prop: HashMap::new(),
+            memories: RefCell::new(vec![]),
+            htif: RefCell::new(None),
+            replay: Rc::new(Replay::new()),
+            checkpoints: vec![],
+            checkpoint_interval: 0,
         };
         sys
     }
 
+    fn add_processor(&mut self, p: Processor) {
+        self.replay.add_clock(p.state().steps_cnt());
+        self.processors.push(p)
+    }
+
+    //sources of intc are routed by aplic instead of plic, and msis of aplic are sent to imsic
+    pub fn enable_aia(&mut self, imsic: bool) {
+        assert!(
+            self.processors.is_empty(),
+            "aia must be enabled before processors are created!"
+        );
+        self.aplic = Some(Aplic::new(&self.intc, &self.bus));
+        if imsic {
+            self.imsic = Some(Rc::new(Imsic::new(IMSIC_NUM_IDS)))
+        }
+    }
+
     pub fn new_processor(&mut self, config: ProcessorCfg) {
-        let p = Processor::new(
+        let (timer, clint) = HartTimer::alloc(&self.timer);
+        let irq = if let Some(ref aplic) = self.aplic {
+            aplic.alloc_irq()
+        } else {
+            self.intc.alloc_irq()
+        };
+        let mut p = Processor::new(
             self.processors.len(),
             config,
             &self.bus,
-            Some(self.timer.alloc_irq()),
-            Some(self.intc.alloc_irq()),
+            Some(clint),
+            Some(irq),
         );
-        self.processors.push(p)
+        p.connect_timer(timer);
+        if let Some(ref imsic) = self.imsic {
+            p.connect_imsic(HartImsic::alloc(imsic));
+        }
+        self.add_processor(p)
     }
 
     pub fn new_processor_no_int(&mut self, config: ProcessorCfg) {
         let p = Processor::new(self.processors.len(), config, &self.bus, None, None);
-        self.processors.push(p)
+        self.add_processor(p)
     }
 
+    //line 0 of plic is meip driven by M context and line 1 is seip driven by S context
     pub fn new_processor_with_int(&mut self, config: ProcessorCfg, clint: IrqVec, plic: IrqVec) {
         let p = Processor::new(
             self.processors.len(),
@@ -106,7 +181,7 @@ impl System {
             Some(clint),
             Some(plic),
         );
-        self.processors.push(p)
+        self.add_processor(p)
     }
 
     fn register_region(&self, name: &str, base: u64, region: &Rc<Region>) -> Result<()> {
@@ -118,12 +193,11 @@ impl System {
 
     pub fn register_htif(&self, input_en: bool) {
         if let Some((base, tohost, fromhost)) = self.elf.htif_section().expect("Invalid ELF!") {
-            self.register_region(
-                "htif",
-                base,
-                &Region::io(0, 0x1000, Box::new(HTIF::new(tohost, fromhost, input_en))),
-            )
-            .unwrap();
+            let mut htif = HTIF::new(tohost, fromhost, input_en);
+            htif.set_replay(&self.replay);
+            *self.htif.borrow_mut() = Some(htif.regs());
+            self.register_region("htif", base, &Region::io(0, 0x1000, Box::new(htif)))
+                .unwrap();
         }
     }
 
@@ -151,6 +225,25 @@ impl System {
         &self.intc
     }
 
+    pub fn aplic(&self) -> Option<&Rc<Aplic>> {
+        self.aplic.as_ref()
+    }
+
+    pub fn imsic(&self) -> Option<&Rc<Imsic>> {
+        self.imsic.as_ref()
+    }
+
+    pub fn replay(&self) -> &Rc<Replay> {
+        &self.replay
+    }
+
+    //ticks are taken from history instead when replaying
+    pub fn tick_timer(&self, n: u64) {
+        if self.replay.tick(n) {
+            self.timer.tick(n)
+        }
+    }
+
     pub fn register_device<D: IOAccess + 'static>(
         &self,
         name: &str,
@@ -183,6 +276,7 @@ impl System {
         let base = VIRTIO_BASE + id * VIRTIO_SIZE;
         let irq_id = device.device().irq_id() as u32;
         self.register_device(name, base, VIRTIO_SIZE, device)?;
+        self.ext_virtios.push((name.to_string(), base));
         self.virtio_infos.push(VirtIOInfo {
             base,
             size: VIRTIO_SIZE,
@@ -192,7 +286,30 @@ impl System {
         Ok(())
     }
 
+    //virtio devices of this crate, their host input and output go through record/replay
+    pub fn register_virtio_mmio(
+        &mut self,
+        name: &str,
+        device: &Rc<VirtIOMmioDevice>,
+    ) -> Result<()> {
+        let id = self.virtio_infos.len() as u64;
+        let base = VIRTIO_BASE + id * VIRTIO_SIZE;
+        device.set_replay(&self.replay);
+        self.register_device(name, base, VIRTIO_SIZE, VirtIOMmio::new(device))?;
+        self.virtio_mmios.push(device.clone());
+        self.virtio_infos.push(VirtIOInfo {
+            base,
+            size: VIRTIO_SIZE,
+            irq_id: device.irq_id() as u32,
+            ty: "mmio".to_string(),
+        });
+        Ok(())
+    }
+
     pub fn register_memory(&self, name: &str, base: u64, mem: &Rc<Region>) -> Result<()> {
+        self.memories
+            .borrow_mut()
+            .push((name.to_string(), mem.clone()));
         match self.register_region(name, base, &mem) {
             Ok(_) => Ok(()),
             Err(e) => {
@@ -303,13 +420,36 @@ impl System {
                 "riscv,isa",
                 vec![&p.state().isa_string()],
             ));
+            cpu.add_prop(FdtProp::str_prop(
+                "riscv,isa-base",
+                vec![&p.state().config().isa.base()],
+            ));
+            cpu.add_prop(FdtProp::str_prop(
+                "riscv,isa-extensions",
+                p.state()
+                    .config()
+                    .isa
+                    .extensions()
+                    .iter()
+                    .map(|e| e.as_str())
+                    .collect(),
+            ));
+            let block_size = p.state().config().cache_block_size as u32;
+            if p.state().config().isa.has_ext(IsaExt::Zicbom) {
+                cpu.add_prop(FdtProp::u32_prop("riscv,cbom-block-size", vec![block_size]));
+            }
+            if p.state().config().isa.has_ext(IsaExt::Zicboz) {
+                cpu.add_prop(FdtProp::u32_prop("riscv,cboz-block-size", vec![block_size]));
+            }
             cpu.add_prop(FdtProp::u32_prop(
                 "clock-frequency",
                 vec![p.state().config().freq as u32],
             ));
-            match p.state().config().xlen {
-                XLen::X64 => cpu.add_prop(FdtProp::str_prop("mmu-type", vec!["riscv,sv48"])),
-                XLen::X32 => cpu.add_prop(FdtProp::str_prop("mmu-type", vec!["riscv,sv32"])),
+            if let Some(mode) = p.state().config().satp_modes.iter().max() {
+                cpu.add_prop(FdtProp::str_prop(
+                    "mmu-type",
+                    vec![&format!("riscv,{}", mode.name())],
+                ));
             }
             let mut intc = FdtNode::new("interrupt-controller");
             intc.add_prop(FdtProp::u32_prop("#interrupt-cells", vec![1]));
@@ -324,6 +464,40 @@ impl System {
         }
         root.add_node(cpus);
 
+        //events of mhpmcounters for sbi pmu, all harts are configured the same
+        if let Some(p) = self.processors.first() {
+            let num = p.state().hpm().num() as u32;
+            if num != 0 {
+                let counters = ((1u64 << (num + 3)) as u32).wrapping_sub(1 << 3);
+                let mut pmu = FdtNode::new("pmu");
+                pmu.add_prop(FdtProp::str_prop("compatible", vec!["riscv,pmu"]));
+                let mut event_to_mhpmevent = vec![];
+                let mut event_to_mhpmcounters = vec![];
+                for (code, event) in HPM_SBI_EVENTS.iter() {
+                    event_to_mhpmevent.push(*code);
+                    event_to_mhpmevent.push(0);
+                    event_to_mhpmevent.push(u8::from(*event) as u32);
+                    event_to_mhpmcounters.push(*code);
+                    event_to_mhpmcounters.push(*code);
+                    event_to_mhpmcounters.push(counters);
+                }
+                pmu.add_prop(FdtProp::u32_prop(
+                    "riscv,event-to-mhpmevent",
+                    event_to_mhpmevent,
+                ));
+                pmu.add_prop(FdtProp::u32_prop(
+                    "riscv,event-to-mhpmcounters",
+                    event_to_mhpmcounters,
+                ));
+                //raw events are written to mhpmevent directly
+                pmu.add_prop(FdtProp::u32_prop(
+                    "riscv,raw-event-to-mhpmcounters",
+                    vec![0, 0, 0xffff_ffff, 0xffff_ff00, counters],
+                ));
+                root.add_node(pmu);
+            }
+        }
+
         if let Some(main_memory) = self.bus.space().get_region("main_memory") {
             let base = main_memory.info.base;
             let mut size = main_memory.info.size;
@@ -386,12 +560,13 @@ impl System {
                 plic.add_prop(FdtProp::u32_prop("riscv,ndev", vec![num_ints]));
                 plic.add_prop(FdtProp::u32_prop("riscv,max-priority", vec![0x7]));
                 plic.add_prop(FdtProp::str_prop("compatible", vec!["riscv,plic0"]));
+                //M context and S context of each hart
                 let mut interrupts_extended = vec![];
                 for p in self.processors.iter() {
-                    interrupts_extended.push((p.state().hartid() + 1) as u32);
-                    interrupts_extended.push(9 as u32);
                     interrupts_extended.push((p.state().hartid() + 1) as u32);
                     interrupts_extended.push(11 as u32);
+                    interrupts_extended.push((p.state().hartid() + 1) as u32);
+                    interrupts_extended.push(9 as u32);
                 }
                 plic.add_prop(FdtProp::u32_prop(
                     "interrupts-extended",
@@ -406,8 +581,102 @@ impl System {
             }
         }
 
+        //imsics of M level and S level, mtopei and stopei are the only interrupt of a file
+        let imsic_phandles = [
+            self.processors.len() as u32 + 4,
+            self.processors.len() as u32 + 5,
+        ];
+        if let Some(ref imsic) = self.imsic {
+            for ((name, irq), phandle) in [("imsic_m", 11), ("imsic_s", 9)]
+                .iter()
+                .zip(imsic_phandles.iter())
+            {
+                if let Some(imsic_region) = self.bus.space().get_region(name) {
+                    let mut imsics = FdtNode::new_with_num("imsics", imsic_region.info.base);
+                    imsics.add_prop(FdtProp::str_prop("compatible", vec!["riscv,imsics"]));
+                    imsics.add_prop(FdtProp::null_prop("interrupt-controller"));
+                    imsics.add_prop(FdtProp::u32_prop("#interrupt-cells", vec![0]));
+                    imsics.add_prop(FdtProp::null_prop("msi-controller"));
+                    imsics.add_prop(FdtProp::u32_prop("#msi-cells", vec![0]));
+                    let mut interrupts_extended = vec![];
+                    for p in self.processors.iter() {
+                        interrupts_extended.push((p.state().hartid() + 1) as u32);
+                        interrupts_extended.push(*irq as u32);
+                    }
+                    imsics.add_prop(FdtProp::u32_prop(
+                        "interrupts-extended",
+                        interrupts_extended,
+                    ));
+                    imsics.add_prop(FdtProp::u64_prop(
+                        "reg",
+                        vec![imsic_region.info.base, imsic_region.info.size],
+                    ));
+                    imsics.add_prop(FdtProp::u32_prop(
+                        "riscv,num-ids",
+                        vec![imsic.num_ids() as u32],
+                    ));
+                    imsics.add_prop(FdtProp::u32_prop("phandle", vec![*phandle]));
+                    soc.add_node(imsics);
+                }
+            }
+        }
+
+        //all sources are delegated to the S level domain, which is the only child of the M level domain
+        let aplic_phandles = [
+            self.processors.len() as u32 + 2,
+            self.processors.len() as u32 + 3,
+        ];
+        let num_ints = self.intc.num_src() as u32 - 1;
+        if self.aplic.is_some() && num_ints != 0 {
+            for (((name, irq), phandle), imsic_phandle) in [("aplic_m", 11), ("aplic_s", 9)]
+                .iter()
+                .zip(aplic_phandles.iter())
+                .zip(imsic_phandles.iter())
+            {
+                if let Some(aplic_region) = self.bus.space().get_region(name) {
+                    let mut aplic = FdtNode::new_with_num("aplic", aplic_region.info.base);
+                    aplic.add_prop(FdtProp::str_prop("compatible", vec!["riscv,aplic"]));
+                    aplic.add_prop(FdtProp::null_prop("interrupt-controller"));
+                    aplic.add_prop(FdtProp::u32_prop("#interrupt-cells", vec![2]));
+                    if self.imsic.is_some() {
+                        aplic.add_prop(FdtProp::u32_prop("msi-parent", vec![*imsic_phandle]));
+                    } else {
+                        let mut interrupts_extended = vec![];
+                        for p in self.processors.iter() {
+                            interrupts_extended.push((p.state().hartid() + 1) as u32);
+                            interrupts_extended.push(*irq as u32);
+                        }
+                        aplic.add_prop(FdtProp::u32_prop(
+                            "interrupts-extended",
+                            interrupts_extended,
+                        ));
+                    }
+                    aplic.add_prop(FdtProp::u64_prop(
+                        "reg",
+                        vec![aplic_region.info.base, aplic_region.info.size],
+                    ));
+                    aplic.add_prop(FdtProp::u32_prop("riscv,num-sources", vec![num_ints]));
+                    if *name == "aplic_m" {
+                        aplic
+                            .add_prop(FdtProp::u32_prop("riscv,children", vec![aplic_phandles[1]]));
+                        aplic.add_prop(FdtProp::u32_prop(
+                            "riscv,delegation",
+                            vec![aplic_phandles[1], 1, num_ints],
+                        ));
+                    }
+                    aplic.add_prop(FdtProp::u32_prop("phandle", vec![*phandle]));
+                    soc.add_node(aplic);
+                }
+            }
+        }
+
         if !self.virtio_infos.is_empty() {
-            assert!(self.bus.space().get_region("plic").is_some());
+            let aia = self.aplic.is_some();
+            if aia {
+                assert!(self.bus.space().get_region("aplic_s").is_some());
+            } else {
+                assert!(self.bus.space().get_region("plic").is_some());
+            }
             for info in self.virtio_infos.iter() {
                 let mut virtio = FdtNode::new_with_num("virtio", info.base);
                 virtio.add_prop(FdtProp::str_prop(
@@ -415,9 +684,14 @@ impl System {
                     vec![&format!("virtio,{}", info.ty)],
                 ));
                 virtio.add_prop(FdtProp::u64_prop("reg", vec![info.base, info.size]));
+                //sources of aplic are level triggered
                 virtio.add_prop(FdtProp::u32_prop(
                     "interrupts-extended",
-                    vec![plic_phandle, info.irq_id],
+                    if aia {
+                        vec![aplic_phandles[1], info.irq_id, 4]
+                    } else {
+                        vec![plic_phandle, info.irq_id]
+                    },
                 ));
                 soc.add_node(virtio)
             }
@@ -525,6 +799,193 @@ impl System {
     }
 }
 
+//snapshot covers harts, clint, plic, aplic, imsic, htif, virtio-mmio devices, lr/sc reservations and all
+//registered memories. Other devices registered by register_device keep their own state and are not saved,
+//saving fails once the driver has touched a virtio device registered by register_virtio.
+impl System {
+    pub fn save_snapshot(&self, path: &str) -> Result<()> {
+        let file = File::create(path)?;
+        self.save_snapshot_to(BufWriter::new(file))
+    }
+
+    pub fn restore_snapshot(&mut self, path: &str) -> Result<()> {
+        let file = File::open(path)?;
+        self.restore_snapshot_from(BufReader::new(file))
+    }
+
+    //a non-zero status register means the driver has started to initialize the device
+    fn check_ext_virtios(&self) -> Result<()> {
+        for (name, base) in self.ext_virtios.iter() {
+            let mut status: u32 = 0;
+            self.bus
+                .read_u32(&(*base + VIRTIO_STATUS), &mut status)
+                .map_err(|addr| {
+                    Error::SnapshotErr(format!("read {} status at {:#x} fail!", name, addr))
+                })?;
+            if status != 0 {
+                return Err(Error::SnapshotErr(format!(
+                    "virtio device {} is active and can not be saved!",
+                    name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn save_snapshot_to<W: Write>(&self, w: W) -> Result<()> {
+        self.check_ext_virtios()?;
+        let mut w = SnapshotWriter::new(w)?;
+        w.write_u64(self.processors.len() as u64)?;
+        for p in self.processors.iter() {
+            p.save_snapshot(&mut w)?;
+        }
+        self.timer.save_snapshot(&mut w)?;
+        self.intc.save_snapshot(&mut w)?;
+        w.write_bool(self.aplic.is_some())?;
+        if let Some(ref aplic) = self.aplic {
+            aplic.save_snapshot(&mut w)?;
+        }
+        w.write_bool(self.imsic.is_some())?;
+        if let Some(ref imsic) = self.imsic {
+            imsic.save_snapshot(&mut w)?;
+        }
+        if let Some(ref htif) = *self.htif.borrow() {
+            w.write_bool(true)?;
+            w.write_u64(htif.tohost())?;
+            w.write_u64(htif.fromhost())?;
+        } else {
+            w.write_bool(false)?;
+        }
+        w.write_u64(self.virtio_mmios.len() as u64)?;
+        for device in self.virtio_mmios.iter() {
+            device.save_snapshot(&mut w)?;
+        }
+        let locks = self.bus.locks();
+        w.write_u64(locks.len() as u64)?;
+        for (addr, len, holder) in locks.iter() {
+            w.write_u64(*addr)?;
+            w.write_u64(*len as u64)?;
+            w.write_u64(*holder as u64)?;
+        }
+        let memories = self.memories.borrow();
+        w.write_u64(memories.len() as u64)?;
+        for (name, mem) in memories.iter() {
+            Self::save_memory(&mut w, name, mem)?;
+        }
+        w.flush()?;
+        Ok(())
+    }
+
+    pub fn restore_snapshot_from<R: Read>(&mut self, r: R) -> Result<()> {
+        let mut r = SnapshotReader::new(r)?;
+        r.expect_u64(self.processors.len() as u64, "processor num")?;
+        for p in self.processors.iter_mut() {
+            p.restore_snapshot(&mut r)?;
+        }
+        self.timer.restore_snapshot(&mut r)?;
+        self.intc.restore_snapshot(&mut r)?;
+        if r.read_bool()? != self.aplic.is_some() {
+            return Err(Error::SnapshotErr("aplic mismatch!".to_string()));
+        }
+        if let Some(ref aplic) = self.aplic {
+            aplic.restore_snapshot(&mut r)?;
+        }
+        if r.read_bool()? != self.imsic.is_some() {
+            return Err(Error::SnapshotErr("imsic mismatch!".to_string()));
+        }
+        if let Some(ref imsic) = self.imsic {
+            imsic.restore_snapshot(&mut r)?;
+        }
+        let has_htif = r.read_bool()?;
+        if has_htif != self.htif.borrow().is_some() {
+            return Err(Error::SnapshotErr("htif mismatch!".to_string()));
+        }
+        if let Some(ref htif) = *self.htif.borrow() {
+            htif.set_tohost(r.read_u64()?);
+            htif.set_fromhost(r.read_u64()?);
+        }
+        r.expect_u64(self.virtio_mmios.len() as u64, "virtio-mmio num")?;
+        for device in self.virtio_mmios.iter() {
+            device.restore_snapshot(&mut r)?;
+        }
+        let mut locks = vec![];
+        for _ in 0..r.read_u64()? {
+            locks.push((r.read_u64()?, r.read_u64()? as usize, r.read_u64()? as usize));
+        }
+        self.bus.set_locks(&locks);
+        let memories = self.memories.borrow();
+        r.expect_u64(memories.len() as u64, "memory num")?;
+        for (name, mem) in memories.iter() {
+            Self::restore_memory(&mut r, name, mem)?;
+        }
+        Ok(())
+    }
+
+    //memories are saved as sparse pages, all-zero pages are skipped
+    fn save_memory<W: Write>(
+        w: &mut SnapshotWriter<W>,
+        name: &str,
+        mem: &Rc<Region>,
+    ) -> io::Result<()> {
+        w.write_str(name)?;
+        w.write_u64(mem.info.size)?;
+        let mut page = vec![0u8; SNAPSHOT_PAGE_SIZE as usize];
+        let mut offset = 0;
+        while offset < mem.info.size {
+            let len = min(SNAPSHOT_PAGE_SIZE, mem.info.size - offset) as usize;
+            BytesAccess::read(&**mem, &(mem.info.base + offset), &mut page[..len]).unwrap();
+            if page[..len].iter().any(|b| *b != 0) {
+                w.write_bool(true)?;
+                w.write_u64(offset)?;
+                w.write_bytes(&page[..len])?;
+            }
+            offset += SNAPSHOT_PAGE_SIZE;
+        }
+        w.write_bool(false)
+    }
+
+    fn restore_memory<R: Read>(
+        r: &mut SnapshotReader<R>,
+        name: &str,
+        mem: &Rc<Region>,
+    ) -> Result<()> {
+        let saved_name = r.read_str()?;
+        if saved_name != name {
+            return Err(Error::SnapshotErr(format!(
+                "memory {} mismatch with {}!",
+                saved_name, name
+            )));
+        }
+        r.expect_u64(mem.info.size, &format!("{} size", name))?;
+        let zeros = vec![0u8; SNAPSHOT_PAGE_SIZE as usize];
+        let mut page = vec![0u8; SNAPSHOT_PAGE_SIZE as usize];
+        let mut offset = 0;
+        while r.read_bool()? {
+            let next = r.read_u64()?;
+            if next >= mem.info.size || next < offset || next % SNAPSHOT_PAGE_SIZE != 0 {
+                return Err(Error::SnapshotErr(format!(
+                    "invalid page {:#x} of {}!",
+                    next, name
+                )));
+            }
+            while offset < next {
+                BytesAccess::write(&**mem, &(mem.info.base + offset), &zeros).unwrap();
+                offset += SNAPSHOT_PAGE_SIZE;
+            }
+            let len = min(SNAPSHOT_PAGE_SIZE, mem.info.size - offset) as usize;
+            r.read_bytes(&mut page[..len])?;
+            BytesAccess::write(&**mem, &(mem.info.base + offset), &page[..len]).unwrap();
+            offset += SNAPSHOT_PAGE_SIZE;
+        }
+        while offset < mem.info.size {
+            let len = min(SNAPSHOT_PAGE_SIZE, mem.info.size - offset) as usize;
+            BytesAccess::write(&**mem, &(mem.info.base + offset), &zeros[..len]).unwrap();
+            offset += SNAPSHOT_PAGE_SIZE;
+        }
+        Ok(())
+    }
+}
+
 impl Display for System {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         writeln!(f, "Machine {}:", self.name)?;
@@ -1,7 +1,7 @@
 use std::ops::Deref;
 use std::path::Path;
 use terminus::global::XLen;
-use terminus::processor::ProcessorCfg;
+use terminus::processor::{ProcessorCfg, SatpMode};
 use terminus::system::System;
 use terminus_spaceport::devices::term_exit;
 use terminus_spaceport::memory::region::{U64Access, GHEAP};
@@ -77,8 +77,21 @@ fn riscv_test(xlen: XLen, name: &str, debug: bool, num_cores: usize) -> bool {
         ProcessorCfg {
             xlen,
             enable_dirty: true,
-            extensions: vec!['m', 'f', 'd', 's', 'u', 'c', 'a'].into_boxed_slice(),
+            isa: format!("rv{}imafdcsu_zicsr_zifencei", xlen.len())
+                .parse()
+                .unwrap(),
             freq: 1000000000,
+            triggers: 4,
+            satp_modes: match xlen {
+                XLen::X32 => vec![SatpMode::Sv32],
+                XLen::X64 => vec![SatpMode::Sv39, SatpMode::Sv48, SatpMode::Sv57],
+            }
+            .into_boxed_slice(),
+            vlen: 128,
+            elen: 64,
+            cache_block_size: 64,
+            entropy_seed: 0,
+            hpm_counters: 8,
         };
         num_cores
     ];